@@ -1,88 +1,37 @@
 //! Benchmarks
 
-use ark_bls12_381::{Bls12_381, Fr as BlsScalar};
-use ark_ec::{PairingEngine, TEModelParameters};
+use ark_bls12_381::Bls12_381;
 use ark_ed_on_bls12_381::EdwardsParameters;
+use ark_plonk::bench::{self, BenchCircuit, CircuitKind};
 use ark_plonk::prelude::*;
-use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::kzg10::KZG10;
-use core::marker::PhantomData;
+use ark_plonk::srs::SrsManager;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use rand_core::OsRng;
 
-/// Benchmark Circuit
-#[derive(derivative::Derivative)]
-#[derivative(Debug, Default)]
-pub struct BenchCircuit<E, P>
-where
-    E: PairingEngine,
-    P: TEModelParameters<BaseField = E::Fr>,
-{
-    /// Circuit Size
-    size: usize,
+const MINIMUM_DEGREE: usize = 5;
+const MAXIMUM_DEGREE: usize = 19;
 
-    /// Type Parameter Marker
-    __: PhantomData<(E, P)>,
-}
-
-impl<E, P> BenchCircuit<E, P>
-where
-    E: PairingEngine,
-    P: TEModelParameters<BaseField = E::Fr>,
-{
-    /// Builds a new circuit with a constraint count of `2^degree`.
-    #[inline]
-    pub fn new(degree: usize) -> Self {
-        Self {
-            size: 1 << degree,
-            __: PhantomData,
-        }
-    }
-}
-
-impl<E, P> Circuit<E, P> for BenchCircuit<E, P>
-where
-    E: PairingEngine,
-    P: TEModelParameters<BaseField = E::Fr>,
-{
-    const CIRCUIT_ID: [u8; 32] = [0xff; 32];
-
-    #[inline]
-    fn gadget(
-        &mut self,
-        composer: &mut StandardComposer<E, P>,
-    ) -> Result<(), Error> {
-        while composer.circuit_size() < self.size - 1 {
-            composer.add_dummy_constraints();
-        }
-        Ok(())
-    }
-
-    #[inline]
-    fn padded_circuit_size(&self) -> usize {
-        self.size
-    }
+/// Returns the kind and degree of every benchmarked circuit.
+fn benchmark_circuits() -> impl Iterator<Item = (CircuitKind, usize)> {
+    CircuitKind::ALL.into_iter().flat_map(|kind| {
+        (MINIMUM_DEGREE..MAXIMUM_DEGREE).map(move |degree| (kind, degree))
+    })
 }
 
 /// Generates full benchmark suite for compiling, proving, and verifying.
 fn constraint_system_benchmark(c: &mut Criterion) {
-    let label = b"ark".as_slice();
-
-    const MINIMUM_DEGREE: usize = 5;
-    const MAXIMUM_DEGREE: usize = 19;
+    let config = ProverConfig::new(b"ark");
+    let verifier_config = config.verifier_config();
 
-    let pp = KZG10::<Bls12_381, DensePolynomial<BlsScalar>>::setup(
-        1 << MAXIMUM_DEGREE,
-        false,
-        &mut OsRng,
-    )
-    .expect("Unable to sample public parameters.");
+    let pp = bench::setup::<Bls12_381, _>(MAXIMUM_DEGREE - 1, &mut OsRng)
+        .expect("Unable to sample public parameters.");
 
     let mut compiling_benchmarks = c.benchmark_group("compile");
-    for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
-        let mut circuit = BenchCircuit::<_, EdwardsParameters>::new(degree);
+    for (kind, degree) in benchmark_circuits() {
+        let mut circuit =
+            BenchCircuit::<_, EdwardsParameters>::new(kind, degree);
         compiling_benchmarks.bench_with_input(
-            BenchmarkId::from_parameter(degree),
+            BenchmarkId::new(kind.name(), degree),
             &degree,
             |b, _| {
                 b.iter(|| {
@@ -94,29 +43,61 @@ fn constraint_system_benchmark(c: &mut Criterion) {
     compiling_benchmarks.finish();
 
     let mut proving_benchmarks = c.benchmark_group("prove");
-    for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
-        let mut circuit = BenchCircuit::<_, EdwardsParameters>::new(degree);
+    for (kind, degree) in benchmark_circuits() {
+        let mut circuit =
+            BenchCircuit::<_, EdwardsParameters>::new(kind, degree);
         let (pk_p, _) =
             circuit.compile(&pp).expect("Unable to compile circuit.");
         proving_benchmarks.bench_with_input(
-            BenchmarkId::from_parameter(degree),
+            BenchmarkId::new(kind.name(), degree),
             &degree,
-            |b, _| {
-                b.iter(|| circuit.gen_proof(&pp, pk_p.clone(), &label).unwrap())
-            },
+            |b, _| b.iter(|| circuit.gen_proof(&pp, &pk_p, &config).unwrap()),
         );
     }
     proving_benchmarks.finish();
 
+    // Proving a compiled circuit, committing to the wires from their
+    // coefficients or from their evaluations with a Lagrange basis
+    let srs = SrsManager::new(pp.clone());
+    let mut compiled_benchmarks = c.benchmark_group("prove_compiled");
+    for (kind, degree) in benchmark_circuits() {
+        let mut circuit =
+            BenchCircuit::<_, EdwardsParameters>::new(kind, degree);
+        let (pk_p, _) =
+            circuit.compile(&pp).expect("Unable to compile circuit.");
+        let mut compiled =
+            CompiledCircuit::from_srs(&mut circuit, &srs, pk_p, &config)
+                .expect("Unable to run the benchmark gadget.");
+        compiled_benchmarks.bench_with_input(
+            BenchmarkId::new(format!("{}/monomial", kind.name()), degree),
+            &degree,
+            |b, _| b.iter(|| compiled.prove().unwrap()),
+        );
+        let basis = srs
+            .lagrange_basis(circuit.padded_circuit_size())
+            .expect("Unable to compute the Lagrange basis.");
+        compiled.set_lagrange_basis(basis);
+        compiled_benchmarks.bench_with_input(
+            BenchmarkId::new(format!("{}/lagrange", kind.name()), degree),
+            &degree,
+            |b, _| b.iter(|| compiled.prove().unwrap()),
+        );
+    }
+    compiled_benchmarks.finish();
+
     let mut verifying_benchmarks = c.benchmark_group("verify");
-    for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
-        let mut circuit = BenchCircuit::<_, EdwardsParameters>::new(degree);
+    for (kind, degree) in benchmark_circuits() {
+        let mut circuit =
+            BenchCircuit::<_, EdwardsParameters>::new(kind, degree);
         let (pk_p, verifier_data) =
             circuit.compile(&pp).expect("Unable to compile circuit.");
-        let proof = circuit.gen_proof(&pp, pk_p.clone(), &label).unwrap();
-        let VerifierData { key, pi_pos } = verifier_data;
+        let proof = circuit.gen_proof(&pp, &pk_p, &config).unwrap();
+        let public_inputs = verifier_data
+            .public_inputs(&[])
+            .expect("Unable to build benchmark public inputs.");
+        let VerifierData { key, .. } = verifier_data;
         verifying_benchmarks.bench_with_input(
-            BenchmarkId::from_parameter(degree),
+            BenchmarkId::new(kind.name(), degree),
             &degree,
             |b, _| {
                 b.iter(|| {
@@ -124,9 +105,8 @@ fn constraint_system_benchmark(c: &mut Criterion) {
                         &pp,
                         key.clone(),
                         &proof,
-                        &[],
-                        &pi_pos,
-                        &label,
+                        &public_inputs,
+                        &verifier_config,
                     )
                     .expect("Unable to verify benchmark circuit.");
                 })
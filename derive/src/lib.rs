@@ -0,0 +1,244 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Derive macros for the `ark-plonk` circuits.
+//!
+//! This crate is re-exported by `ark-plonk` behind its `derive` feature and
+//! shouldn't be used directly.
+
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Fields, GenericParam, Type,
+};
+
+/// Role of a field of the circuit.
+#[derive(Clone, Copy, PartialEq)]
+enum Role {
+    /// Private input
+    Witness,
+    /// Public input
+    Public,
+    /// Scalar of the embedded curve, added as a private input
+    EmbeddedScalar,
+}
+
+/// Field of the circuit allocated in the composer.
+struct Input {
+    /// Name of the field
+    name: Ident,
+    /// Role of the field
+    role: Role,
+    /// Whether the field is a point of the embedded curve
+    point: bool,
+}
+
+/// Derives `ark_plonk::circuit::CircuitInputs` for a circuit struct.
+///
+/// The fields annotated with `#[witness]`, `#[public]` or
+/// `#[embedded_scalar]` are allocated in the composer, in declaration order,
+/// and returned in a `<Name>Variables` struct with a field of the same name
+/// for each of them. Fields of type `GroupAffine<P>` are allocated as
+/// points, the other ones as scalars, and the fields without annotation are
+/// left out.
+///
+/// The struct must name its pairing engine `E` and the parameters of its
+/// embedded curve `P`.
+#[proc_macro_derive(PlonkCircuit, attributes(witness, public, embedded_scalar))]
+pub fn derive_plonk_circuit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input,
+                    "PlonkCircuit requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input,
+                "PlonkCircuit can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut inputs = Vec::new();
+    for field in fields {
+        let mut roles = Vec::new();
+        for attr in &field.attrs {
+            let role = if attr.path.is_ident("witness") {
+                Role::Witness
+            } else if attr.path.is_ident("public") {
+                Role::Public
+            } else if attr.path.is_ident("embedded_scalar") {
+                Role::EmbeddedScalar
+            } else {
+                continue;
+            };
+            if !attr.tokens.is_empty() {
+                return Err(Error::new_spanned(attr, "unexpected arguments"));
+            }
+            roles.push(role);
+        }
+        let role =
+            match roles.as_slice() {
+                [] => continue,
+                [role] => *role,
+                _ => return Err(Error::new_spanned(
+                    field,
+                    "a field can only have one of #[witness], #[public] and \
+                     #[embedded_scalar]",
+                )),
+            };
+        let point = is_group_affine(&field.ty);
+        if point && role == Role::EmbeddedScalar {
+            return Err(Error::new_spanned(
+                &field.ty,
+                "#[embedded_scalar] can't be used on a point",
+            ));
+        }
+        inputs.push(Input {
+            name: field.ident.clone().expect("named field"),
+            role,
+            point,
+        });
+    }
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let variables = format_ident!("{}Variables", name);
+    let generics = &input.generics;
+    let params = &generics.params;
+    let where_clause = &generics.where_clause;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let type_params: Vec<&Ident> = params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            _ => None,
+        })
+        .collect();
+
+    let variables_doc = format!("Variables allocated for `{}`.", name);
+    let variable_fields = inputs.iter().map(|input| {
+        let name = &input.name;
+        let doc = format!("Allocated `{}`", name);
+        let ty = match input.point {
+            true => quote!(::ark_plonk::constraint_system::ecc::Point<E, P>),
+            false => quote!(::ark_plonk::constraint_system::Variable),
+        };
+        quote! {
+            #[doc = #doc]
+            pub #name: #ty,
+        }
+    });
+
+    let allocations = inputs.iter().map(|input| {
+        let name = &input.name;
+        let value = match (input.role, input.point) {
+            (Role::Witness, false) => quote!(composer.add_input(self.#name)),
+            (Role::Witness, true) => quote!(composer.add_affine(self.#name)),
+            (Role::Public, false) => quote! {{
+                let variable = composer.add_input(self.#name);
+                composer.set_public(variable);
+                variable
+            }},
+            (Role::Public, true) => {
+                quote!(composer.add_public_affine(self.#name))
+            }
+            (Role::EmbeddedScalar, _) => {
+                quote!(composer.add_embedded_scalar(self.#name))
+            }
+        };
+        quote!(#name: #value,)
+    });
+
+    let public: Vec<&Input> = inputs
+        .iter()
+        .filter(|input| input.role == Role::Public)
+        .collect();
+    let public_kinds = public.iter().map(|input| {
+        let name = input.name.to_string();
+        let kind = match input.point {
+            true => quote!(Point),
+            false => quote!(Scalar),
+        };
+        quote! {
+            (#name, ::ark_plonk::circuit::PublicInputKind::#kind),
+        }
+    });
+    let public_values = public.iter().map(|input| {
+        let name = &input.name;
+        match input.point {
+            true => quote! {
+                ::ark_plonk::circuit::GeIntoPubInput::into_pi(self.#name),
+            },
+            false => quote! {
+                ::ark_plonk::circuit::FeIntoPubInput::into_pi(self.#name),
+            },
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #variables_doc]
+        #vis struct #variables<#params> #where_clause {
+            #(#variable_fields)*
+            __: ::core::marker::PhantomData<(#(#type_params,)*)>,
+        }
+
+        impl #impl_generics ::ark_plonk::circuit::CircuitInputs<E, P>
+            for #name #ty_generics #where_clause
+        {
+            type Variables = #variables #ty_generics;
+
+            const PUBLIC_INPUTS: &'static [(
+                &'static str,
+                ::ark_plonk::circuit::PublicInputKind,
+            )] = &[#(#public_kinds)*];
+
+            fn allocate(
+                &self,
+                composer: &mut ::ark_plonk::constraint_system::StandardComposer<E, P>,
+            ) -> Self::Variables {
+                #variables {
+                    #(#allocations)*
+                    __: ::core::marker::PhantomData,
+                }
+            }
+
+            fn public_input_values(
+                &self,
+            ) -> ::std::vec::Vec<::ark_plonk::circuit::PublicInputValue<P>> {
+                ::std::vec![#(#public_values)*]
+            }
+        }
+    })
+}
+
+/// Checks whether `ty` is a `GroupAffine`, whatever its path.
+fn is_group_affine(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "GroupAffine")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
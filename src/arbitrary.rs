@@ -0,0 +1,233 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Random circuits for property-based testing.
+//!
+//! A [`RandomCircuit`] is a sequence of [`Operation`]s mixing the gates of
+//! the [`StandardComposer`], generated with [`proptest`](mod@proptest).
+//! Replaying it with [`RandomCircuit::build`] always yields a satisfied
+//! circuit, so that proofs of random circuits can be checked to verify, and
+//! [`corrupt_wire`] breaks the witness of a composer so that they can be
+//! checked to fail.
+
+use crate::constraint_system::{StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use num_traits::{One, Zero};
+use proptest::prelude::*;
+
+/// Number of operations of the circuits generated by [`RandomCircuit`]'s
+/// [`Arbitrary`] implementation, unless specified.
+pub const DEFAULT_MAX_OPERATIONS: usize = 32;
+
+/// Operation of a [`RandomCircuit`].
+///
+/// Operations refer to the [`Variable`]s created by the previous ones by
+/// index, taken modulo their number.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Operation {
+    /// Adds a witness
+    Input(u64),
+
+    /// Adds the two variables with an arithmetic gate
+    Add(usize, usize),
+
+    /// Multiplies the two variables with an arithmetic gate
+    Mul(usize, usize),
+
+    /// Copies the variable into a new witness constrained to be equal
+    Copy(usize),
+
+    /// Makes the variable a public input
+    Public(usize),
+
+    /// Adds a boolean witness
+    Bool(bool),
+
+    /// Adds a witness of the given number of bits, checked by a range gate
+    Range(u64, usize),
+
+    /// XORs two witnesses of the given number of bits with a logic gate
+    Xor(u64, u64, usize),
+}
+
+/// Circuit made of a random sequence of [`Operation`]s.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RandomCircuit {
+    /// Operations adding the gates of the circuit
+    pub operations: Vec<Operation>,
+}
+
+impl RandomCircuit {
+    /// Adds the gates of the circuit to `composer`, with a satisfying
+    /// witness.
+    pub fn build<E, P>(&self, composer: &mut StandardComposer<E, P>)
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut variables: Vec<Variable> = vec![composer.zero_var()];
+        let pick =
+            |variables: &[Variable], i: usize| variables[i % variables.len()];
+        for operation in &self.operations {
+            let variable = match *operation {
+                Operation::Input(value) => composer.add_input(value.into()),
+                Operation::Add(a, b) => composer.add(
+                    (E::Fr::one(), pick(&variables, a)),
+                    (E::Fr::one(), pick(&variables, b)),
+                    E::Fr::zero(),
+                    None,
+                ),
+                Operation::Mul(a, b) => composer.mul(
+                    E::Fr::one(),
+                    pick(&variables, a),
+                    pick(&variables, b),
+                    E::Fr::zero(),
+                    None,
+                ),
+                Operation::Copy(a) => {
+                    let a = pick(&variables, a);
+                    let copy = composer.add_input(composer.variables[a.0]);
+                    composer.assert_equal(copy, a);
+                    copy
+                }
+                Operation::Public(a) => {
+                    let a = pick(&variables, a);
+                    composer.set_public(a);
+                    continue;
+                }
+                Operation::Bool(value) => composer.add_bool(value).variable(),
+                Operation::Range(value, num_bits) => {
+                    let value =
+                        composer.add_input(mask(value, num_bits).into());
+                    composer.range_gate(value, num_bits);
+                    value
+                }
+                Operation::Xor(a, b, num_bits) => {
+                    let a = composer.add_input(mask(a, num_bits).into());
+                    let b = composer.add_input(mask(b, num_bits).into());
+                    composer.xor_gate(a, b, num_bits)
+                }
+            };
+            variables.push(variable);
+        }
+    }
+}
+
+impl Arbitrary for RandomCircuit {
+    type Parameters = usize;
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates circuits of at most `max_operations` operations, or
+    /// [`DEFAULT_MAX_OPERATIONS`] if zero.
+    fn arbitrary_with(max_operations: usize) -> Self::Strategy {
+        let max_operations = match max_operations {
+            0 => DEFAULT_MAX_OPERATIONS,
+            max_operations => max_operations,
+        };
+        prop::collection::vec(operation(), 1..=max_operations)
+            .prop_map(|operations| RandomCircuit { operations })
+            .boxed()
+    }
+}
+
+/// Strategy generating a single [`Operation`], with even numbers of bits
+/// of at most 16 for the range and logic gates.
+pub fn operation() -> impl Strategy<Value = Operation> {
+    let num_bits = (1..=8usize).prop_map(|half| 2 * half);
+    prop_oneof![
+        any::<u64>().prop_map(Operation::Input),
+        (any::<usize>(), any::<usize>())
+            .prop_map(|(a, b)| Operation::Add(a, b)),
+        (any::<usize>(), any::<usize>())
+            .prop_map(|(a, b)| Operation::Mul(a, b)),
+        any::<usize>().prop_map(Operation::Copy),
+        any::<usize>().prop_map(Operation::Public),
+        any::<bool>().prop_map(Operation::Bool),
+        (any::<u64>(), num_bits.clone())
+            .prop_map(|(value, num_bits)| Operation::Range(value, num_bits)),
+        (any::<u64>(), any::<u64>(), num_bits)
+            .prop_map(|(a, b, num_bits)| Operation::Xor(a, b, num_bits)),
+    ]
+}
+
+/// Keeps the `num_bits` lowest bits of `value`.
+fn mask(value: u64, num_bits: usize) -> u64 {
+    value & ((1 << num_bits) - 1)
+}
+
+/// Adds one to the witness of the output of an arithmetic gate, picked by
+/// `seed` among the gates whose output appears on no other wire of the gate,
+/// so that the constraint of the gate no longer holds.
+///
+/// Returns `false` if the composer has no such gate.
+pub fn corrupt_wire<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    seed: usize,
+) -> bool
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let gates: Vec<usize> = (0..composer.n)
+        .filter(|i| {
+            let output = composer.w_o[*i];
+            composer.q_arith[*i].is_one()
+                && !composer.q_o[*i].is_zero()
+                && output != composer.zero_var
+                && ![composer.w_l[*i], composer.w_r[*i], composer.w_4[*i]]
+                    .contains(&output)
+        })
+        .collect();
+    if gates.is_empty() {
+        return false;
+    }
+    let output = composer.w_o[gates[seed % gates.len()]];
+    composer.variables[output.0] += E::Fr::one();
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constraint_system::helper::{dummy_gadget, gadget_tester};
+    use ark_bls12_381::Bls12_381;
+    use ark_ed_on_bls12_381::EdwardsParameters;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(8))]
+
+        /// Tests that the proofs of random circuits verify.
+        #[test]
+        fn test_random_circuit(circuit in any::<RandomCircuit>()) {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<Bls12_381, EdwardsParameters>| {
+                    circuit.build(composer);
+                    dummy_gadget(8, composer);
+                },
+                1 << 10,
+            );
+            prop_assert!(res.is_ok());
+        }
+
+        /// Tests that the proofs of random circuits with a corrupted wire
+        /// don't verify.
+        #[test]
+        fn test_corrupted_circuit(
+            circuit in any::<RandomCircuit>(),
+            seed in any::<usize>(),
+        ) {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<Bls12_381, EdwardsParameters>| {
+                    circuit.build(composer);
+                    dummy_gadget(8, composer);
+                    assert!(corrupt_wire(composer, seed));
+                },
+                1 << 10,
+            );
+            prop_assert!(res.is_err());
+        }
+    }
+}
@@ -0,0 +1,398 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Self-describing binary format for proofs and keys.
+//!
+//! The [`CanonicalSerialize`] encoding of proofs and keys carries no
+//! information about what it encodes, so that feeding a proof generated over
+//! BLS12-377 to a verifier over BLS12-381 fails with an arbitrary
+//! deserialization error, if it fails at all. [`write_artifact`] prefixes
+//! the encoding with an [`ArtifactHeader`]:
+//! - the magic bytes [`ARTIFACT_MAGIC`],
+//! - the [`ARTIFACT_FORMAT_VERSION`],
+//! - the [`ArtifactKind`],
+//! - the [`curve_id`] of the scalar field and embedded curve,
+//! - the circuit size, or zero for proofs which don't carry it,
+//!
+//! which [`read_artifact`] checks before reading the artifact itself,
+//! reporting mismatches with a dedicated [`Error`].
+
+use crate::circuit::VerifierData;
+use crate::error::Error;
+use crate::proof_system::{Proof, ProverKey, VerifierKey};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{Field, PrimeField};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
+use merlin::Transcript;
+
+/// Magic bytes starting every artifact.
+pub const ARTIFACT_MAGIC: [u8; 4] = *b"PLNK";
+
+/// Version of the artifact format written by [`write_artifact`].
+pub const ARTIFACT_FORMAT_VERSION: u16 = 2;
+
+/// Kind of a serialized artifact.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ArtifactKind {
+    /// A [`Proof`]
+    Proof,
+
+    /// A [`VerifierData`]
+    VerifierData,
+
+    /// A [`ProverKey`]
+    ProverKey,
+
+    /// A [`VerifierKey`]
+    VerifierKey,
+}
+
+impl ArtifactKind {
+    /// Returns the tag the kind is serialized as.
+    fn tag(self) -> u8 {
+        match self {
+            Self::Proof => 1,
+            Self::VerifierData => 2,
+            Self::ProverKey => 3,
+            Self::VerifierKey => 4,
+        }
+    }
+
+    /// Returns the kind serialized as `tag`, if any.
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(Self::Proof),
+            2 => Some(Self::VerifierData),
+            3 => Some(Self::ProverKey),
+            4 => Some(Self::VerifierKey),
+            _ => None,
+        }
+    }
+}
+
+/// Header prefixing a serialized artifact.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArtifactHeader {
+    /// Version of the format
+    pub version: u16,
+
+    /// Kind of the artifact
+    pub kind: ArtifactKind,
+
+    /// [`curve_id`] of the artifact
+    pub curve_id: [u8; 8],
+
+    /// Size of the circuit of the artifact, zero if unknown
+    pub circuit_size: u64,
+}
+
+impl ArtifactHeader {
+    /// Writes the header, starting with [`ARTIFACT_MAGIC`].
+    pub fn write<W>(&self, mut writer: W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        writer.write_all(&ARTIFACT_MAGIC)?;
+        self.version.serialize(&mut writer)?;
+        self.kind.tag().serialize(&mut writer)?;
+        writer.write_all(&self.curve_id)?;
+        self.circuit_size.serialize(&mut writer)?;
+        Ok(())
+    }
+
+    /// Reads a header, checking its magic bytes and version without checking
+    /// what it describes.
+    pub fn read<R>(mut reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != ARTIFACT_MAGIC {
+            return Err(Error::InvalidArtifactMagic);
+        }
+        let version = u16::deserialize(&mut reader)?;
+        if version != ARTIFACT_FORMAT_VERSION {
+            return Err(Error::UnsupportedArtifactVersion { version });
+        }
+        let kind = ArtifactKind::from_tag(u8::deserialize(&mut reader)?)
+            .ok_or(SerializationError::InvalidData)?;
+        let mut curve_id = [0u8; 8];
+        reader.read_exact(&mut curve_id)?;
+        let circuit_size = u64::deserialize(&mut reader)?;
+        Ok(Self {
+            version,
+            kind,
+            curve_id,
+            circuit_size,
+        })
+    }
+}
+
+/// Proof or key that can be serialized with an [`ArtifactHeader`].
+pub trait Artifact: CanonicalSerialize + CanonicalDeserialize {
+    /// Kind of the artifact
+    const KIND: ArtifactKind;
+
+    /// Returns the [`curve_id`] of the artifact type.
+    fn curve_id() -> [u8; 8];
+
+    /// Returns the size of the circuit of the artifact, if it carries it.
+    fn circuit_size(&self) -> Option<usize>;
+}
+
+/// Returns the identifier of the curves an artifact is defined over, derived
+/// from the modulus of the scalar field `F` and the one of the scalar field
+/// of the embedded curve `P`.
+pub fn curve_id<F, P>() -> [u8; 8]
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    let mut transcript = Transcript::new(b"ark-plonk-curve-id");
+    let mut modulus = Vec::new();
+    F::characteristic()
+        .serialize(&mut modulus)
+        .expect("serializing to a vector never fails");
+    transcript.append_message(b"scalar_field", &modulus);
+    modulus.clear();
+    <P::ScalarField as Field>::characteristic()
+        .serialize(&mut modulus)
+        .expect("serializing to a vector never fails");
+    transcript.append_message(b"embedded_scalar_field", &modulus);
+    let mut id = [0u8; 8];
+    transcript.challenge_bytes(b"curve_id", &mut id);
+    id
+}
+
+/// Writes `artifact` prefixed with its [`ArtifactHeader`].
+pub fn write_artifact<T, W>(artifact: &T, mut writer: W) -> Result<(), Error>
+where
+    T: Artifact,
+    W: Write,
+{
+    ArtifactHeader {
+        version: ARTIFACT_FORMAT_VERSION,
+        kind: T::KIND,
+        curve_id: T::curve_id(),
+        circuit_size: artifact.circuit_size().unwrap_or(0) as u64,
+    }
+    .write(&mut writer)?;
+    artifact.serialize(&mut writer)?;
+    Ok(())
+}
+
+/// Reads an artifact written by [`write_artifact`], checking that its header
+/// matches the type `T` and the artifact itself.
+pub fn read_artifact<T, R>(mut reader: R) -> Result<T, Error>
+where
+    T: Artifact,
+    R: Read,
+{
+    let header = ArtifactHeader::read(&mut reader)?;
+    if header.kind != T::KIND {
+        return Err(Error::ArtifactKindMismatch {
+            expected: T::KIND,
+            found: header.kind,
+        });
+    }
+    if header.curve_id != T::curve_id() {
+        return Err(Error::ArtifactCurveMismatch {
+            expected: T::curve_id(),
+            found: header.curve_id,
+        });
+    }
+    let artifact = T::deserialize(&mut reader)?;
+    if let Some(circuit_size) = artifact.circuit_size() {
+        if header.circuit_size != circuit_size as u64 {
+            return Err(Error::ArtifactCircuitSizeMismatch {
+                header: header.circuit_size,
+                actual: circuit_size,
+            });
+        }
+    }
+    Ok(artifact)
+}
+
+impl<E, P> Artifact for Proof<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    const KIND: ArtifactKind = ArtifactKind::Proof;
+
+    fn curve_id() -> [u8; 8] {
+        curve_id::<E::Fr, P>()
+    }
+
+    fn circuit_size(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl<E, P> Artifact for VerifierData<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    const KIND: ArtifactKind = ArtifactKind::VerifierData;
+
+    fn curve_id() -> [u8; 8] {
+        curve_id::<E::Fr, P>()
+    }
+
+    fn circuit_size(&self) -> Option<usize> {
+        Some(self.key.n)
+    }
+}
+
+impl<F, P> Artifact for ProverKey<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    const KIND: ArtifactKind = ArtifactKind::ProverKey;
+
+    fn curve_id() -> [u8; 8] {
+        curve_id::<F, P>()
+    }
+
+    fn circuit_size(&self) -> Option<usize> {
+        Some(self.n)
+    }
+}
+
+impl<E, P> Artifact for VerifierKey<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    const KIND: ArtifactKind = ArtifactKind::VerifierKey;
+
+    fn curve_id() -> [u8; 8] {
+        curve_id::<E::Fr, P>()
+    }
+
+    fn circuit_size(&self) -> Option<usize> {
+        Some(self.n)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::circuit::trim_commit_key;
+    use crate::proof_system::{Prover, Verifier};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use rand_core::OsRng;
+
+    fn test_artifact_roundtrip<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut prover: Prover<E, P> = Prover::new(b"artifact");
+        let mut verifier: Verifier<E, P> = Verifier::new(b"artifact");
+        prover.mut_cs().add_dummy_constraints();
+        verifier.mut_cs().add_dummy_constraints();
+        let n = prover.circuit_size().next_power_of_two();
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let commit_key = trim_commit_key(&pp, n).unwrap();
+        verifier.preprocess(&commit_key).unwrap();
+        let proof = prover.prove(&commit_key).unwrap();
+        let prover_key = prover.prover_key.unwrap();
+        let verifier_key = verifier.verifier_key.unwrap();
+        let verifier_data = VerifierData::new(verifier_key.clone(), vec![]);
+
+        fn roundtrip<T: Artifact + PartialEq + core::fmt::Debug>(artifact: &T) {
+            let mut bytes = Vec::new();
+            write_artifact(artifact, &mut bytes).unwrap();
+            let header = ArtifactHeader::read(&bytes[..]).unwrap();
+            assert_eq!(header.kind, T::KIND);
+            assert_eq!(
+                header.circuit_size,
+                artifact.circuit_size().unwrap_or(0) as u64
+            );
+            assert_eq!(&read_artifact::<T, _>(&bytes[..]).unwrap(), artifact);
+        }
+        roundtrip(&proof);
+        roundtrip(&prover_key);
+        roundtrip(&verifier_key);
+        roundtrip(&verifier_data);
+
+        // Mismatches are reported before reading the artifact
+        let mut bytes = Vec::new();
+        write_artifact(&verifier_key, &mut bytes).unwrap();
+        assert!(matches!(
+            read_artifact::<Proof<E, P>, _>(&bytes[..]),
+            Err(Error::ArtifactKindMismatch {
+                expected: ArtifactKind::Proof,
+                found: ArtifactKind::VerifierKey,
+            })
+        ));
+        let mut other_curve = bytes.clone();
+        other_curve[7] ^= 1;
+        assert!(matches!(
+            read_artifact::<VerifierKey<E, P>, _>(&other_curve[..]),
+            Err(Error::ArtifactCurveMismatch { .. })
+        ));
+        let mut resized = bytes.clone();
+        resized[15..23].copy_from_slice(&1u64.to_le_bytes());
+        assert!(matches!(
+            read_artifact::<VerifierKey<E, P>, _>(&resized[..]),
+            Err(Error::ArtifactCircuitSizeMismatch { header: 1, .. })
+        ));
+        bytes[4] += 1;
+        assert!(matches!(
+            read_artifact::<VerifierKey<E, P>, _>(&bytes[..]),
+            Err(Error::UnsupportedArtifactVersion { version: 3 })
+        ));
+        bytes[0] = 0;
+        assert!(matches!(
+            read_artifact::<VerifierKey<E, P>, _>(&bytes[..]),
+            Err(Error::InvalidArtifactMagic)
+        ));
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_artifact_roundtrip],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_artifact_roundtrip],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_artifact_roundtrip],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,302 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Standard circuits and helpers to track the performance of the crate.
+//!
+//! A [`BenchCircuit`] fills a circuit of `2^k` gates with a single
+//! [`CircuitKind`] of gadget. It is a regular [`Circuit`], so it can be
+//! measured with any benchmark harness such as Criterion, or timed once with
+//! [`time_circuit`] to compare runs on the same hardware.
+//!
+//! This module is only available with the `bench` feature.
+
+use crate::circuit::{verify_proof, Circuit};
+use crate::config::ProverConfig;
+use crate::constraint_system::StandardComposer;
+use crate::error::Error;
+use ark_ec::models::twisted_edwards_extended::GroupAffine;
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::kzg10::{UniversalParams, KZG10};
+use core::marker::PhantomData;
+use num_traits::{One, Zero};
+use rand_core::RngCore;
+use std::time::{Duration, Instant};
+
+/// Label the transcripts of the benchmarked proofs are initialized with.
+const TRANSCRIPT_INIT: &[u8] = b"ark-plonk-bench";
+
+/// Kind of gadget a [`BenchCircuit`] is made of.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CircuitKind {
+    /// Chained multiplications and additions
+    Arithmetic,
+
+    /// Variable base point additions
+    Ecc,
+
+    /// Bitwise XORs, made of range and logic gates
+    Bits,
+}
+
+impl CircuitKind {
+    /// All the kinds of circuits.
+    pub const ALL: [Self; 3] = [Self::Arithmetic, Self::Ecc, Self::Bits];
+
+    /// Returns the name of the kind, to label benchmarks.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Arithmetic => "arithmetic",
+            Self::Ecc => "ecc",
+            Self::Bits => "bits",
+        }
+    }
+}
+
+/// Circuit of `2^degree` gates made of a single [`CircuitKind`] of gadget.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug)]
+pub struct BenchCircuit<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Kind of gadget the circuit is made of
+    kind: CircuitKind,
+
+    /// Base 2 logarithm of the circuit size
+    degree: usize,
+
+    /// Type Parameter Marker
+    __: PhantomData<(E, P)>,
+}
+
+impl<E, P> BenchCircuit<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Builds a new circuit of `kind` with a constraint count of
+    /// `2^degree`.
+    pub fn new(kind: CircuitKind, degree: usize) -> Self {
+        Self {
+            kind,
+            degree,
+            __: PhantomData,
+        }
+    }
+
+    /// Returns the kind of gadget the circuit is made of.
+    pub fn kind(&self) -> CircuitKind {
+        self.kind
+    }
+
+    /// Returns the base 2 logarithm of the circuit size.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+}
+
+/// Repeats `step` as long as the circuit fits in `gates` gates, and pads it
+/// up to that size.
+fn fill<E, P, F>(
+    composer: &mut StandardComposer<E, P>,
+    gates: usize,
+    mut step: F,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    F: FnMut(&mut StandardComposer<E, P>),
+{
+    loop {
+        let checkpoint = composer.checkpoint();
+        step(composer);
+        if composer.circuit_size() > gates {
+            composer.rollback(checkpoint);
+            break;
+        }
+    }
+    let zero = composer.zero_var();
+    while composer.circuit_size() < gates {
+        composer.constrain_to_constant(zero, E::Fr::zero(), None);
+    }
+}
+
+impl<E, P> Circuit<E, P> for BenchCircuit<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn gadget(
+        &mut self,
+        composer: &mut StandardComposer<E, P>,
+    ) -> Result<(), Error> {
+        let gates = (1 << self.degree) - 1;
+        match self.kind {
+            CircuitKind::Arithmetic => {
+                let x = composer.add_input(E::Fr::from(3u64));
+                let mut acc = composer.add_input(E::Fr::one());
+                fill(composer, gates, |composer| {
+                    acc =
+                        composer.mul(E::Fr::one(), acc, x, E::Fr::zero(), None);
+                    acc = composer.add(
+                        (E::Fr::one(), acc),
+                        (E::Fr::one(), x),
+                        E::Fr::zero(),
+                        None,
+                    );
+                });
+            }
+            CircuitKind::Ecc => {
+                let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+                let mut point =
+                    composer.add_affine(GroupAffine::<P>::new(x, y));
+                fill(composer, gates, |composer| {
+                    point = composer.point_addition_gate(point, point);
+                });
+            }
+            CircuitKind::Bits => {
+                let x = composer.add_input(E::Fr::from(0x5eed_cafeu64));
+                let mut acc = composer.add_input(E::Fr::from(0xdead_beefu64));
+                fill(composer, gates, |composer| {
+                    acc = composer.xor_gate(acc, x, 32);
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn padded_circuit_size(&mut self) -> usize {
+        1 << self.degree
+    }
+}
+
+/// Time spent in each stage of the lifecycle of a [`BenchCircuit`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Timings {
+    /// Time spent compiling the circuit
+    pub compile: Duration,
+
+    /// Time spent generating a proof
+    pub prove: Duration,
+
+    /// Time spent verifying the proof
+    pub verify: Duration,
+}
+
+/// Generates public parameters supporting the [`BenchCircuit`]s of degree
+/// up to `max_degree`.
+pub fn setup<E, R>(
+    max_degree: usize,
+    rng: &mut R,
+) -> Result<UniversalParams<E>, Error>
+where
+    E: PairingEngine,
+    R: RngCore,
+{
+    Ok(KZG10::<E, DensePolynomial<E::Fr>>::setup(
+        1 << (max_degree + 1),
+        false,
+        rng,
+    )?)
+}
+
+/// Compiles, proves and verifies the [`BenchCircuit`] of `kind` and
+/// `degree`, returning the time spent in each stage.
+pub fn time_circuit<E, P>(
+    u_params: &UniversalParams<E>,
+    kind: CircuitKind,
+    degree: usize,
+) -> Result<Timings, Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut circuit = BenchCircuit::<E, P>::new(kind, degree);
+    let config = ProverConfig::new(TRANSCRIPT_INIT);
+
+    let start = Instant::now();
+    let (prover_key, verifier_data) = circuit.compile(u_params)?;
+    let compile = start.elapsed();
+
+    let start = Instant::now();
+    let proof = circuit.gen_proof(u_params, &prover_key, &config)?;
+    let prove = start.elapsed();
+
+    let public_inputs = verifier_data.public_inputs(&[])?;
+    let start = Instant::now();
+    verify_proof(
+        u_params,
+        verifier_data.key,
+        &proof,
+        &public_inputs,
+        &config.verifier_config(),
+    )?;
+    let verify = start.elapsed();
+
+    Ok(Timings {
+        compile,
+        prove,
+        verify,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use rand_core::OsRng;
+
+    fn test_bench_circuits<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        const DEGREE: usize = 7;
+        let u_params = setup::<E, _>(DEGREE, &mut OsRng).unwrap();
+        for kind in CircuitKind::ALL {
+            let mut circuit = BenchCircuit::<E, P>::new(kind, DEGREE);
+            let mut composer = StandardComposer::new();
+            circuit.gadget(&mut composer).unwrap();
+            assert_eq!(composer.circuit_size(), (1 << DEGREE) - 1);
+
+            time_circuit::<E, P>(&u_params, kind, DEGREE).unwrap();
+        }
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_bench_circuits],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_bench_circuits],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_bench_circuits],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
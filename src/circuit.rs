@@ -6,20 +6,39 @@
 
 //! Tools & traits for PLONK circuits
 
-use crate::constraint_system::StandardComposer;
+use crate::config::{ProverConfig, VerifierConfig};
+use crate::constraint_system::{
+    CircuitStats, PoseidonParameters, StandardComposer, Variable,
+};
 use crate::error::Error;
-use crate::proof_system::{Proof, Prover, ProverKey, Verifier, VerifierKey};
+use crate::key_storage::{public_inputs_layout_digest, KeyDigest, KeyStorage};
+use crate::proof_system::{
+    LagrangeBasis, Proof, Prover, ProverKey, SelectorBlinding,
+    VerificationCost, VerificationTrace, VerifierKey,
+};
+use crate::srs::SrsManager;
+use crate::transcript::TranscriptWrapper;
+use crate::util;
+use alloc::sync::Arc;
 use ark_ec::models::TEModelParameters;
 use ark_ec::{
     twisted_edwards_extended::{GroupAffine, GroupProjective},
     PairingEngine, ProjectiveCurve,
 };
-use ark_ff::PrimeField;
+use ark_ff::{FftField, FftParameters};
 use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::kzg10::{self, Powers, UniversalParams};
+use ark_poly::GeneralEvaluationDomain;
+use ark_poly_commit::kzg10::{self, Commitment, Powers, UniversalParams};
 use ark_poly_commit::sonic_pc::SonicKZG10;
 use ark_poly_commit::PolynomialCommitment;
 use ark_serialize::*;
+use core::marker::PhantomData;
+use merlin::Transcript;
+use num_traits::Zero;
+use rand_core::RngCore;
+
+#[cfg(feature = "derive")]
+pub use ark_plonk_derive::PlonkCircuit;
 
 /// Field Element Into Public Input
 ///
@@ -100,6 +119,419 @@ where
     }
 }
 
+/// Public inputs of a circuit: the scalar values together with the gate
+/// positions they are bound to.
+///
+/// Entries are kept sorted by position, so two sets of public inputs compare
+/// and hash equal as soon as they assign the same values to the same
+/// positions, regardless of the order they were built in.
+#[derive(CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(bound = ""),
+    Default(bound = ""),
+    Eq(bound = ""),
+    Hash(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct PublicInputs<E>
+where
+    E: PairingEngine,
+{
+    /// Gate positions, in increasing order
+    positions: Vec<usize>,
+
+    /// Values assigned to each position
+    values: Vec<E::Fr>,
+}
+
+impl<E> PublicInputs<E>
+where
+    E: PairingEngine,
+{
+    /// Creates an empty set of public inputs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the public inputs from the values of a [`Circuit`] and the
+    /// public input positions returned in its [`VerifierData`].
+    ///
+    /// Fails if the number of scalar values doesn't match the number of
+    /// positions, if a position is repeated, or if a point value isn't on
+    /// the curve.
+    pub fn from_values<P>(
+        values: &[PublicInputValue<P>],
+        positions: &[usize],
+    ) -> Result<Self, Error>
+    where
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let flattened: Vec<E::Fr> = values
+            .iter()
+            .flat_map(|value| value.values.iter().copied())
+            .collect();
+        if flattened.len() != positions.len() {
+            return Err(Error::PublicInputLengthMismatch {
+                expected: positions.len(),
+                actual: flattened.len(),
+            });
+        }
+        let mut start = 0;
+        for value in values {
+            if let [x, y] = value.values[..] {
+                check_on_curve::<P>(x, y, positions[start])?;
+            }
+            start += value.values.len();
+        }
+        Self::from_pairs(positions.iter().copied().zip(flattened))
+    }
+
+    /// Builds the public inputs from `(position, value)` pairs, such as the
+    /// entries of a `BTreeMap`.
+    ///
+    /// Fails if a position is repeated.
+    pub fn from_pairs<I>(pairs: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (usize, E::Fr)>,
+    {
+        let mut pi = Self::new();
+        for (position, value) in pairs {
+            if pi.positions.binary_search(&position).is_ok() {
+                return Err(Error::DuplicatePublicInputPosition { position });
+            }
+            pi.insert(position, value);
+        }
+        Ok(pi)
+    }
+
+    /// Assigns `value` to the public input at `position`, replacing any
+    /// previous value.
+    pub fn insert(&mut self, position: usize, value: E::Fr) {
+        match self.positions.binary_search(&position) {
+            Ok(i) => self.values[i] = value,
+            Err(i) => {
+                self.positions.insert(i, position);
+                self.values.insert(i, value);
+            }
+        }
+    }
+
+    /// Returns the number of public inputs.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if there are no public inputs.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the positions of the public inputs in increasing order.
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// Returns the values of the public inputs, ordered by position.
+    pub fn values(&self) -> &[E::Fr] {
+        &self.values
+    }
+
+    /// Returns a domain-separated 32-byte digest of the canonical
+    /// serialization of the public inputs.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        self.serialize(&mut bytes)
+            .expect("serialization into a vector can't fail");
+        let mut transcript = Transcript::new(b"ark-plonk-public-inputs");
+        transcript.append_message(b"public_inputs", &bytes);
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"digest", &mut digest);
+        digest
+    }
+
+    /// Builds the dense public input vector of a circuit of size `n`, as
+    /// used by the verifier.
+    fn as_evals(&self, n: usize) -> Result<Vec<E::Fr>, Error> {
+        if let Some(position) =
+            self.positions.iter().find(|position| **position >= n)
+        {
+            return Err(Error::PublicInputPositionOutOfRange {
+                position: *position,
+                circuit_size: n,
+            });
+        }
+        let mut pi = vec![E::Fr::zero(); n];
+        self.positions
+            .iter()
+            .zip(&self.values)
+            .for_each(|(position, value)| pi[*position] = -*value);
+        Ok(pi)
+    }
+}
+
+impl<E> CanonicalDeserialize for PublicInputs<E>
+where
+    E: PairingEngine,
+{
+    /// Reads the public inputs through [`PublicInputs::from_pairs`], so that
+    /// the positions of the result are sorted and unique.
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let positions = Vec::<usize>::deserialize(&mut reader)?;
+        let values = Vec::<E::Fr>::deserialize(&mut reader)?;
+        if positions.len() != values.len() {
+            return Err(SerializationError::InvalidData);
+        }
+        Self::from_pairs(positions.into_iter().zip(values))
+            .map_err(|_| SerializationError::InvalidData)
+    }
+}
+
+/// Checks that the coordinates of a point public input starting at gate
+/// `gate_index` satisfy the curve equation.
+fn check_on_curve<P>(
+    x: P::BaseField,
+    y: P::BaseField,
+    gate_index: usize,
+) -> Result<(), Error>
+where
+    P: TEModelParameters,
+{
+    if GroupAffine::<P>::new(x, y).is_on_curve() {
+        Ok(())
+    } else {
+        Err(Error::PointNotOnCurve { gate_index })
+    }
+}
+
+/// Kind of a public input declared by a circuit.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PublicInputKind {
+    /// A scalar, spanning a single public input position
+    Scalar,
+
+    /// A point of the embedded curve, spanning the positions of its x and y
+    /// coordinates
+    Point,
+}
+
+impl PublicInputKind {
+    /// Returns the tag the kind is serialized as.
+    fn tag(self) -> u8 {
+        match self {
+            Self::Scalar => 0,
+            Self::Point => 1,
+        }
+    }
+}
+
+impl CanonicalSerialize for PublicInputKind {
+    fn serialize<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        self.tag().serialize(writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1
+    }
+}
+
+impl CanonicalDeserialize for PublicInputKind {
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(reader)? {
+            0 => Ok(Self::Scalar),
+            1 => Ok(Self::Point),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+/// Builder of the [`PublicInputs`] of a circuit, returned by
+/// [`VerifierData::public_input_builder`].
+///
+/// The values are given in the order the circuit declares its public inputs,
+/// and each of them is checked against the [`PublicInputKind`] the circuit
+/// expects as soon as it is given.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct PublicInputBuilder<'a, E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Public input positions of the circuit
+    positions: &'a [usize],
+
+    /// Kinds of the public inputs of the circuit
+    kinds: &'a [PublicInputKind],
+
+    /// Public inputs assigned so far
+    public_inputs: PublicInputs<E>,
+
+    /// Number of public inputs assigned so far
+    assigned: usize,
+
+    /// Type Parameter Marker
+    __: PhantomData<P>,
+}
+
+impl<'a, E, P> PublicInputBuilder<'a, E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Assigns `value` to the next public input, which must be a
+    /// [`PublicInputKind::Scalar`].
+    pub fn scalar(self, value: E::Fr) -> Result<Self, Error> {
+        self.push(PublicInputKind::Scalar, &[value])
+    }
+
+    /// Assigns `point` to the next public input, which must be a
+    /// [`PublicInputKind::Point`].
+    ///
+    /// Fails with [`Error::PointNotOnCurve`] if `point` doesn't satisfy the
+    /// curve equation.
+    pub fn point(self, point: GroupAffine<P>) -> Result<Self, Error> {
+        if let Some(position) =
+            self.positions.get(self.public_inputs.len()).copied()
+        {
+            check_on_curve::<P>(point.x, point.y, position)?;
+        }
+        self.push(PublicInputKind::Point, &[point.x, point.y])
+    }
+
+    /// Returns the public inputs, failing if some of them haven't been
+    /// assigned.
+    pub fn finish(self) -> Result<PublicInputs<E>, Error> {
+        if self.assigned != self.kinds.len() {
+            return Err(Error::PublicInputLengthMismatch {
+                expected: self.kinds.len(),
+                actual: self.assigned,
+            });
+        }
+        Ok(self.public_inputs)
+    }
+
+    /// Assigns `values` to the positions of the next public input, checking
+    /// that it is of the given `kind`.
+    fn push(
+        mut self,
+        kind: PublicInputKind,
+        values: &[E::Fr],
+    ) -> Result<Self, Error> {
+        match self.kinds.get(self.assigned) {
+            None => {
+                return Err(Error::PublicInputLengthMismatch {
+                    expected: self.kinds.len(),
+                    actual: self.assigned + 1,
+                })
+            }
+            Some(expected) if *expected != kind => {
+                return Err(Error::PublicInputKindMismatch {
+                    index: self.assigned,
+                    expected: *expected,
+                    actual: kind,
+                })
+            }
+            _ => {}
+        }
+        let start = self.public_inputs.len();
+        let positions = self.positions.get(start..start + values.len()).ok_or(
+            Error::PublicInputLengthMismatch {
+                expected: self.positions.len(),
+                actual: start + values.len(),
+            },
+        )?;
+        for (position, value) in positions.iter().zip(values) {
+            self.public_inputs.insert(*position, *value);
+        }
+        self.assigned += 1;
+        Ok(self)
+    }
+}
+
+/// Commitment to the witness of a circuit, published ahead of its [`Proof`].
+///
+/// In optimistic flows a prover publishes only the commitments to the wire
+/// polynomials together with the public inputs, which is much cheaper than
+/// computing a full proof. If the statement is challenged, the prover then
+/// produces the full [`Proof`], which is only accepted by
+/// [`verify_committed_proof`] if it opens the same wire commitments.
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct WitnessCommitment<E>
+where
+    E: PairingEngine,
+{
+    /// Commitment to the witness polynomial for the left wires
+    a_comm: Commitment<E>,
+
+    /// Commitment to the witness polynomial for the right wires
+    b_comm: Commitment<E>,
+
+    /// Commitment to the witness polynomial for the output wires
+    c_comm: Commitment<E>,
+
+    /// Commitment to the witness polynomial for the fourth wires
+    d_comm: Commitment<E>,
+
+    /// Public inputs of the circuit
+    public_inputs: PublicInputs<E>,
+}
+
+impl<E> WitnessCommitment<E>
+where
+    E: PairingEngine,
+{
+    /// Commits to the witness held by `prover`.
+    fn new<P>(
+        prover: &Prover<E, P>,
+        commit_key: &Powers<E>,
+    ) -> Result<Self, Error>
+    where
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let public_inputs = prover.public_inputs();
+        let [a_comm, b_comm, c_comm, d_comm] =
+            prover.commit_wires(commit_key)?;
+        Ok(Self {
+            a_comm,
+            b_comm,
+            c_comm,
+            d_comm,
+            public_inputs,
+        })
+    }
+
+    /// Returns the commitments to the left, right, output and fourth wire
+    /// polynomials.
+    pub fn wires(&self) -> [Commitment<E>; 4] {
+        [self.a_comm, self.b_comm, self.c_comm, self.d_comm]
+    }
+
+    /// Returns the public inputs of the circuit.
+    pub fn public_inputs(&self) -> &PublicInputs<E> {
+        &self.public_inputs
+    }
+
+    /// Checks that `proof` was generated for the committed witness.
+    pub fn check_binding<P>(&self, proof: &Proof<E, P>) -> Result<(), Error>
+    where
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let proof_wires =
+            [proof.a_comm, proof.b_comm, proof.c_comm, proof.d_comm];
+        if proof_wires != self.wires() {
+            return Err(Error::WitnessCommitmentMismatch);
+        }
+        Ok(())
+    }
+}
+
 /// Collection of structs/objects that the Verifier will use in order to
 /// de/serialize data needed for Circuit proof verification.
 /// This structure can be seen as a link between the [`Circuit`] public input
@@ -121,6 +553,9 @@ where
 
     /// Public Input Positions
     pub pi_pos: Vec<usize>,
+
+    /// Kinds of the Public Inputs, in the order of their positions
+    pub pi_kinds: Vec<PublicInputKind>,
 }
 
 impl<E, P> VerifierData<E, P>
@@ -129,9 +564,15 @@ where
     P: TEModelParameters<BaseField = E::Fr>,
 {
     /// Creates a new `VerifierData` from a [`VerifierKey`] and the public
-    /// input positions of the circuit that it represents.
+    /// input positions of the circuit that it represents, all of them
+    /// holding a [`PublicInputKind::Scalar`].
     pub fn new(key: VerifierKey<E, P>, pi_pos: Vec<usize>) -> Self {
-        Self { key, pi_pos }
+        let pi_kinds = vec![PublicInputKind::Scalar; pi_pos.len()];
+        Self {
+            key,
+            pi_pos,
+            pi_kinds,
+        }
     }
 
     /// Returns a reference to the contained [`VerifierKey`].
@@ -139,10 +580,105 @@ where
         &self.key
     }
 
+    /// Returns the identifier of the circuit the key is bound to, if any.
+    ///
+    /// For circuits relying on [`DERIVED_CIRCUIT_ID`], it is the
+    /// [`VerifierKey::circuit_digest`] of the key.
+    pub fn circuit_id(&self) -> Option<[u8; 32]> {
+        self.key.circuit_id.as_ref().map(|circuit_id| circuit_id.0)
+    }
+
     /// Returns a reference to the contained Public Input positions.
     pub fn pi_pos(&self) -> &[usize] {
         &self.pi_pos
     }
+
+    /// Returns the kinds of the Public Inputs, in the order of their
+    /// positions.
+    pub fn pi_kinds(&self) -> &[PublicInputKind] {
+        &self.pi_kinds
+    }
+
+    /// Returns a [`PublicInputBuilder`] checking the values it is given
+    /// against the public inputs the circuit declares.
+    pub fn public_input_builder(&self) -> PublicInputBuilder<'_, E, P> {
+        PublicInputBuilder {
+            positions: &self.pi_pos,
+            kinds: &self.pi_kinds,
+            public_inputs: PublicInputs::new(),
+            assigned: 0,
+            __: PhantomData,
+        }
+    }
+
+    /// Returns the cost of verifying a proof of the circuit, see
+    /// [`VerifierKey::verification_cost`].
+    pub fn verification_cost(&self) -> VerificationCost {
+        self.key.verification_cost(self.pi_pos.len())
+    }
+
+    /// Assigns `values` to the public input positions of the circuit.
+    pub fn public_inputs(
+        &self,
+        values: &[PublicInputValue<P>],
+    ) -> Result<PublicInputs<E>, Error> {
+        PublicInputs::from_values(values, &self.pi_pos)
+    }
+
+    /// Builds the public inputs of a circuit exposing the hash of its
+    /// logical public inputs `values` with
+    /// [`StandardComposer::commit_public_inputs`].
+    ///
+    /// The circuit must have no other public input.
+    pub fn committed_public_inputs(
+        &self,
+        values: &[E::Fr],
+    ) -> Result<PublicInputs<E>, Error> {
+        if self.pi_pos.len() != 1 {
+            return Err(Error::PublicInputLengthMismatch {
+                expected: self.pi_pos.len(),
+                actual: 1,
+            });
+        }
+        let mut public_inputs = PublicInputs::new();
+        public_inputs
+            .insert(self.pi_pos[0], PoseidonParameters::new().hash(values));
+        Ok(public_inputs)
+    }
+}
+
+/// Value of [`Circuit::CIRCUIT_ID`] requesting the identifier to be derived
+/// from the compiled circuit.
+pub const DERIVED_CIRCUIT_ID: [u8; 32] = [0u8; 32];
+
+/// Inputs of a circuit, allocated in the composer by its gadget.
+///
+/// With the `derive` feature, this trait is implemented by
+/// `#[derive(PlonkCircuit)]` from the fields of a circuit annotated with
+/// `#[witness]`, `#[public]` or `#[embedded_scalar]`.
+pub trait CircuitInputs<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// [`Variable`]s of the allocated inputs.
+    type Variables;
+
+    /// Names and kinds of the public inputs, in the order of
+    /// [`CircuitInputs::public_input_values`].
+    const PUBLIC_INPUTS: &'static [(&'static str, PublicInputKind)];
+
+    /// Allocates the inputs in `composer`, the public ones being bound to
+    /// their positions in the order of [`CircuitInputs::PUBLIC_INPUTS`].
+    fn allocate(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+    ) -> Self::Variables;
+
+    /// Returns the values of the public inputs, to be given to
+    /// [`VerifierData::public_inputs`] as long as the gadget adds no public
+    /// inputs before allocating the inputs.
+    fn public_input_values(&self) -> Vec<PublicInputValue<P>>;
 }
 
 /// Trait that should be implemented for any circuit function to provide to it
@@ -160,11 +696,8 @@ where
 ///     EdwardsAffine as JubjubAffine, EdwardsParameters as JubjubParameters,
 ///     EdwardsProjective as JubjubProjective, Fr as JubjubScalar,
 /// };
-/// use ark_ff::{PrimeField, BigInteger};
-/// use ark_plonk::circuit::{Circuit, PublicInputValue, verify_proof, GeIntoPubInput, FeIntoPubInput};
-/// use ark_plonk::constraint_system::StandardComposer;
-/// use ark_plonk::error::Error;
-/// use ark_plonk::prelude::VerifierData;
+/// use ark_ff::PrimeField;
+/// use ark_plonk::prelude::*;
 /// use ark_poly::polynomial::univariate::DensePolynomial;
 /// use ark_poly_commit::kzg10::KZG10;
 /// use num_traits::{Zero, One};
@@ -239,8 +772,8 @@ where
 ///         let mul_result = composer.mul(E::Fr::one(), a, b, E::Fr::zero(), Some(-self.d));
 ///         composer.assert_equal(mul_result, composer.zero_var());
 ///
-///         let e_repr = self.e.into_repr().to_bytes_le();
-///         let e = composer.add_input(E::Fr::from_le_bytes_mod_order(&e_repr));
+///         let e = composer
+///             .add_input(from_embedded_curve_scalar::<E, P>(self.e));
 ///         let (x, y) = P::AFFINE_GENERATOR_COEFFS;
 ///         let generator = GroupAffine::new(x, y);
 ///         let scalar_mul_result =
@@ -250,10 +783,6 @@ where
 ///             .assert_equal_public_point(scalar_mul_result, self.f.clone());
 ///         Ok(())
 ///     }
-///
-///     fn padded_circuit_size(&self) -> usize {
-///         1 << 11
-///     }
 /// }
 ///
 /// let pp = KZG10::<Bls12_381,DensePolynomial<BlsScalar>,>::setup(
@@ -283,7 +812,7 @@ where
 ///         e: JubjubScalar::from(2u64),
 ///         f: point_f_pi,
 ///     };
-///     circuit.gen_proof(&pp, pk, b"Test")
+///     circuit.gen_proof(&pp, &pk, &ProverConfig::new(b"Test"))
 /// }?;
 ///
 /// // Verifier POV
@@ -292,14 +821,13 @@ where
 ///     BlsScalar::from(100u64).into_pi(),
 ///     GeIntoPubInput::into_pi(point_f_pi),
 /// ];
-/// let VerifierData { key, pi_pos } = vd;
+/// let public_inputs = vd.public_inputs(&public_inputs)?;
 /// verify_proof(
 ///     &pp,
-///     key,
+///     vd.key,
 ///     &proof,
 ///     &public_inputs,
-///     &pi_pos,
-///     b"Test",
+///     &VerifierConfig::new(b"Test"),
 /// )
 /// }
 /// ```
@@ -309,7 +837,28 @@ where
     P: TEModelParameters<BaseField = E::Fr>,
 {
     /// Circuit identifier associated constant.
-    const CIRCUIT_ID: [u8; 32];
+    ///
+    /// It defaults to [`DERIVED_CIRCUIT_ID`], in which case the keys are
+    /// bound to the [`VerifierKey::circuit_digest`] of the compiled circuit
+    /// instead, so that the identifier can't drift from the circuit.
+    const CIRCUIT_ID: [u8; 32] = DERIVED_CIRCUIT_ID;
+
+    /// Whether the keys generated by [`Circuit::compile`] are bound to
+    /// [`Circuit::CIRCUIT_ID`].
+    ///
+    /// When enabled, the circuit identifier and the digest of the
+    /// [`VerifierKey`] are appended to the transcript of every proof, which
+    /// prevents proofs from being replayed against a different circuit that
+    /// shares the same transcript label.
+    const BIND_CIRCUIT_ID: bool = true;
+
+    /// Whether [`Circuit::compile`] drops the selectors of the custom gates
+    /// the circuit doesn't use from the [`ProverKey`], see
+    /// [`ProverKey::compress_selectors`].
+    ///
+    /// Only the prover key is compressed: the [`VerifierKey`] and the proofs
+    /// are the same either way.
+    const COMPRESS_SELECTORS: bool = false;
 
     /// Gadget implementation used to fill the composer.
     fn gadget(
@@ -319,162 +868,781 @@ where
 
     /// Compiles the circuit by using a function that returns a `Result`
     /// with the `ProverKey`, `VerifierKey` and the circuit size.
+    ///
+    /// Compilation is deterministic: the selector and permutation
+    /// polynomials are not blinded, so compiling the same circuit against the
+    /// same `u_params` always yields the same keys. Two parties can therefore
+    /// compile independently and compare the [`VerifierKey::digest`] of their
+    /// keys to confirm they verify against identical circuit commitments.
+    /// Keys whose selectors should be hidden can be blinded afterwards with
+    /// [`rerandomize_keys`].
     #[allow(clippy::type_complexity)] // NOTE: Clippy is too hash here.
     fn compile(
         &mut self,
         u_params: &UniversalParams<E>,
     ) -> Result<(ProverKey<E::Fr, P>, VerifierData<E, P>), Error> {
         // Setup PublicParams
-        // XXX: KZG10 does not have a trim function so we use sonics and
-        // then do a transformation between sonic CommiterKey to KZG10
-        // powers
         let circuit_size = self.padded_circuit_size();
-        let (ck, _) = SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(
-            u_params,
-            circuit_size,
-            0,
-            None,
-        )
-        .unwrap();
-        let powers = Powers {
-            powers_of_g: ck.powers_of_g.into(),
-            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
-        };
+        let powers = trim_commit_key(u_params, circuit_size)?;
         //Generate & save `ProverKey` with some random values.
-        let mut prover = Prover::new(b"CircuitCompilation");
-        self.gadget(prover.mut_cs())?;
-        let pi_pos = prover.mut_cs().pi_positions();
-        prover.preprocess(&powers)?;
+        let mut prover_cs = StandardComposer::new();
+        self.gadget(&mut prover_cs)?;
+        let pi_pos = prover_cs.pi_positions();
+        let pi_kinds = prover_cs.public_input_kinds();
+        let mut prover_key = prover_cs.preprocess_prover(
+            &powers,
+            &mut TranscriptWrapper::new(b"CircuitCompilation"),
+        )?;
+        if Self::COMPRESS_SELECTORS {
+            prover_key.compress_selectors();
+        }
 
         // Generate & save `VerifierKey` with some random values.
-        let mut verifier = Verifier::new(b"CircuitCompilation");
-        self.gadget(verifier.mut_cs())?;
-        verifier.preprocess(&powers)?;
-        Ok((
-            prover
-                .prover_key
-                .expect("Unexpected error. Missing ProverKey in compilation"),
-            VerifierData::new(
-                verifier.verifier_key.expect(
-                    "Unexpected error. Missing VerifierKey in compilation",
-                ),
-                pi_pos,
-            ),
-        ))
+        let mut verifier_cs = StandardComposer::new();
+        self.gadget(&mut verifier_cs)?;
+        let mut verifier_key = verifier_cs.preprocess_verifier(
+            &powers,
+            &mut TranscriptWrapper::new(b"CircuitCompilation"),
+        )?;
+        if Self::BIND_CIRCUIT_ID {
+            let circuit_id = match Self::CIRCUIT_ID {
+                DERIVED_CIRCUIT_ID => verifier_key.circuit_digest(),
+                circuit_id => circuit_id,
+            };
+            verifier_key.bind_circuit(circuit_id, &mut prover_key);
+        }
+        let verifier_data = VerifierData {
+            key: verifier_key,
+            pi_pos,
+            pi_kinds,
+        };
+        Ok((prover_key, verifier_data))
+    }
+
+    /// Compiles the circuit like [`Circuit::compile`], unless its keys are
+    /// already in `storage`.
+    ///
+    /// The keys are looked up by `vk_digest`, the [`VerifierKey::digest`] of
+    /// the keys of the circuit, as known from a previous compilation. On a
+    /// miss, the circuit is compiled and its keys are stored, unless the
+    /// compiled verifier key has another digest, in which case
+    /// [`Error::KeyDigestMismatch`] is returned.
+    ///
+    /// On a hit, the gadget is still run once, and
+    /// [`Error::KeyDigestMismatch`] is returned if the size or the public
+    /// input positions and kinds of the circuit differ from the ones the
+    /// stored keys were compiled for, e.g. when `vk_digest` is stale.
+    #[allow(clippy::type_complexity)]
+    fn compile_with_storage<S>(
+        &mut self,
+        u_params: &UniversalParams<E>,
+        storage: &S,
+        vk_digest: &KeyDigest,
+    ) -> Result<(ProverKey<E::Fr, P>, VerifierData<E, P>), Error>
+    where
+        S: KeyStorage,
+    {
+        let layout_digest = public_inputs_layout_digest(vk_digest);
+        if let Some(key) = storage.get_verifier_key(vk_digest)? {
+            let prover_key = storage.get_prover_key(&key)?;
+            let layout: Option<(Vec<usize>, Vec<PublicInputKind>)> =
+                storage.get_key(&layout_digest)?;
+            if let (Some(prover_key), Some((pi_pos, pi_kinds))) =
+                (prover_key, layout)
+            {
+                let mut composer = StandardComposer::new();
+                self.gadget(&mut composer)?;
+                let padded_size = composer.circuit_size().next_power_of_two();
+                if padded_size != key.padded_circuit_size()
+                    || padded_size != prover_key.n
+                    || pi_pos != composer.pi_positions()
+                    || pi_kinds != composer.public_input_kinds()
+                {
+                    return Err(Error::KeyDigestMismatch);
+                }
+                let verifier_data = VerifierData {
+                    key,
+                    pi_pos,
+                    pi_kinds,
+                };
+                return Ok((prover_key, verifier_data));
+            }
+        }
+
+        let (prover_key, verifier_data) = self.compile(u_params)?;
+        if verifier_data.key.digest() != *vk_digest {
+            return Err(Error::KeyDigestMismatch);
+        }
+        storage.put_verifier_key(&verifier_data.key)?;
+        storage.put_prover_key(&prover_key)?;
+        storage.put_key(
+            &layout_digest,
+            &(verifier_data.pi_pos.clone(), verifier_data.pi_kinds.clone()),
+        )?;
+        Ok((prover_key, verifier_data))
     }
 
     /// Generates a proof using the provided `CircuitInputs` & `ProverKey`
     /// instances.
+    ///
+    /// The `prover_key` is only borrowed, so a single key can be shared by
+    /// every proof of the circuit.
     fn gen_proof(
         &mut self,
         u_params: &UniversalParams<E>,
-        prover_key: ProverKey<E::Fr, P>,
-        transcript_init: &'static [u8],
+        prover_key: &ProverKey<E::Fr, P>,
+        config: &ProverConfig,
     ) -> Result<Proof<E, P>, Error> {
-        // XXX: KZG10 does not have a trim function so we use sonics and
-        // then do a transformation between sonic CommiterKey to KZG10
-        // powers
         let circuit_size = self.padded_circuit_size();
-        let (ck, _) = SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(
-            u_params,
-            circuit_size,
-            0,
-            None,
-        )
-        .unwrap();
-        let powers = Powers {
-            powers_of_g: ck.powers_of_g.into(),
-            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
-        };
+        let powers = trim_commit_key(u_params, circuit_size)?;
         // New Prover instance
-        let mut prover = Prover::new(transcript_init);
+        let mut prover = setup_prover(config);
         // Fill witnesses for Prover
         self.gadget(prover.mut_cs())?;
-        // Add ProverKey to Prover
-        prover.prover_key = Some(prover_key);
-        prover.prove(&powers)
+        prover.prove_with_preprocessed(&powers, prover_key)
+    }
+
+    /// Commits to the witness of the circuit without generating a proof, see
+    /// [`WitnessCommitment`].
+    fn commit_witness(
+        &mut self,
+        u_params: &UniversalParams<E>,
+    ) -> Result<WitnessCommitment<E>, Error> {
+        let circuit_size = self.padded_circuit_size();
+        let powers = trim_commit_key(u_params, circuit_size)?;
+        let mut prover = Prover::default();
+        self.gadget(prover.mut_cs())?;
+        WitnessCommitment::new(&prover, &powers)
+    }
+
+    /// Returns the [`CircuitStats`] of the circuit compiled by
+    /// [`Circuit::compile`], computed by running [`Circuit::gadget`] on an
+    /// empty [`StandardComposer`].
+    fn stats(&mut self) -> Result<CircuitStats, Error> {
+        let mut composer = StandardComposer::new();
+        self.gadget(&mut composer)?;
+        Ok(CircuitStats {
+            domain_size: self.padded_circuit_size(),
+            ..composer.stats()
+        })
     }
 
     /// Returns the Circuit size padded to the next power of two.
-    fn padded_circuit_size(&self) -> usize;
+    ///
+    /// The default implementation runs [`Circuit::gadget`] on an empty
+    /// [`StandardComposer`] and rounds the number of gates it adds up to the
+    /// next power of two. Circuits with an expensive gadget can override it
+    /// with a constant.
+    ///
+    /// An error returned by the gadget is ignored here, since it is surfaced
+    /// by the subsequent [`Circuit::compile`] or [`Circuit::gen_proof`] call.
+    fn padded_circuit_size(&mut self) -> usize {
+        let mut composer = StandardComposer::<E, P>::new();
+        let _ = self.gadget(&mut composer);
+        composer.circuit_size().next_power_of_two()
+    }
 }
 
-/// Verifies a proof using the provided `CircuitInputs` & `VerifierKey`
-/// instances.
-pub fn verify_proof<E, P>(
-    u_params: &UniversalParams<E>,
-    plonk_verifier_key: VerifierKey<E, P>,
-    proof: &Proof<E, P>,
-    pub_inputs_values: &[PublicInputValue<P>],
-    pub_inputs_positions: &[usize],
-    transcript_init: &'static [u8],
-) -> Result<(), Error>
+/// Circuit compiled once and proved many times.
+///
+/// [`Circuit::gen_proof`] runs the gadget to rebuild the whole composer for
+/// every proof. A `CompiledCircuit` instead runs the gadget a single time and
+/// keeps the resulting composer together with the [`ProverKey`] and the
+/// trimmed commit key, so that further proofs only require new values for
+/// the witness [`Variable`]s and the [`PublicInputs`].
+///
+/// Variables are allocated in the order the gadget adds them, so a circuit
+/// can record the [`Variable`]s it needs to reassign while running its
+/// gadget. Every witness whose value depends on the reassigned ones must be
+/// reassigned as well, otherwise the resulting proof won't verify.
+pub struct CompiledCircuit<E, P>
 where
     E: PairingEngine,
     P: TEModelParameters<BaseField = E::Fr>,
 {
-    let mut verifier: Verifier<E, P> = Verifier::new(transcript_init);
-    let padded_circuit_size = plonk_verifier_key.padded_circuit_size();
-    // let key: VerifierKey<E, P> = *plonk_verifier_key;
-    verifier.verifier_key = Some(plonk_verifier_key);
-    let (_, sonic_vk) = SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(
-        u_params,
-        padded_circuit_size,
-        0,
-        None,
-    )
-    .unwrap();
+    /// Prover holding the composer filled by the gadget
+    prover: Prover<E, P>,
 
-    let vk = kzg10::VerifierKey {
-        g: sonic_vk.g,
-        gamma_g: sonic_vk.gamma_g,
-        h: sonic_vk.h,
-        beta_h: sonic_vk.beta_h,
-        prepared_h: sonic_vk.prepared_h,
-        prepared_beta_h: sonic_vk.prepared_beta_h,
-    };
+    /// Proving key of the circuit
+    prover_key: ProverKey<E::Fr, P>,
 
-    verifier.verify(
-        proof,
-        &vk,
-        build_pi(pub_inputs_values, pub_inputs_positions, padded_circuit_size)
-            .as_slice(),
-    )
+    /// Commit key trimmed to the circuit size
+    commit_key: Arc<Powers<'static, E>>,
 }
 
-/// Build PI vector for Proof verifications.
-fn build_pi<F, P>(
-    pub_input_values: &[PublicInputValue<P>],
-    pub_input_pos: &[usize],
-    trim_size: usize,
-) -> Vec<F>
+impl<E, P> CompiledCircuit<E, P>
 where
-    F: PrimeField,
-    P: TEModelParameters<BaseField = F>,
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
 {
-    let mut pi = vec![F::zero(); trim_size];
-    pub_input_values
-        .iter()
-        .flat_map(|pub_input| pub_input.values.clone())
-        .zip(pub_input_pos.iter().copied())
-        .for_each(|(value, pos)| {
-            pi[pos] = -value;
-        });
-    pi
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::{constraint_system::StandardComposer, util};
-    use ark_bls12_377::Bls12_377;
-    use ark_bls12_381::Bls12_381;
-    use ark_ec::twisted_edwards_extended::GroupAffine;
-    use ark_ec::AffineCurve;
-    use ark_poly_commit::kzg10::KZG10;
-    use num_traits::{One, Zero};
+    /// Runs the gadget of `circuit` and prepares it for proving with the
+    /// `prover_key` returned by [`Circuit::compile`].
+    pub fn new<C>(
+        circuit: &mut C,
+        u_params: &UniversalParams<E>,
+        prover_key: ProverKey<E::Fr, P>,
+        config: &ProverConfig,
+    ) -> Result<Self, Error>
+    where
+        C: Circuit<E, P>,
+    {
+        let commit_key =
+            trim_commit_key(u_params, circuit.padded_circuit_size())?;
+        Self::with_commit_key(circuit, Arc::new(commit_key), prover_key, config)
+    }
 
-    // Implements a circuit that checks:
+    /// Runs the gadget of `circuit` and prepares it for proving with the
+    /// `prover_key` returned by [`Circuit::compile`] and the commit key
+    /// handed out by `srs`, which is shared with every other circuit of the
+    /// same size.
+    pub fn from_srs<C>(
+        circuit: &mut C,
+        srs: &SrsManager<E>,
+        prover_key: ProverKey<E::Fr, P>,
+        config: &ProverConfig,
+    ) -> Result<Self, Error>
+    where
+        C: Circuit<E, P>,
+    {
+        let commit_key = srs.commit_key(circuit.padded_circuit_size())?;
+        Self::with_commit_key(circuit, commit_key, prover_key, config)
+    }
+
+    /// Runs the gadget of `circuit` and prepares it for proving with the
+    /// prover key returned by [`Circuit::compile_with_storage`], which only
+    /// compiles the circuit if its keys aren't in `storage` yet.
+    pub fn from_storage<C, S>(
+        circuit: &mut C,
+        u_params: &UniversalParams<E>,
+        storage: &S,
+        vk_digest: &KeyDigest,
+        config: &ProverConfig,
+    ) -> Result<Self, Error>
+    where
+        C: Circuit<E, P>,
+        S: KeyStorage,
+    {
+        let (prover_key, _) =
+            circuit.compile_with_storage(u_params, storage, vk_digest)?;
+        Self::new(circuit, u_params, prover_key, config)
+    }
+
+    /// Runs the gadget of `circuit` and keeps it together with its keys.
+    fn with_commit_key<C>(
+        circuit: &mut C,
+        commit_key: Arc<Powers<'static, E>>,
+        prover_key: ProverKey<E::Fr, P>,
+        config: &ProverConfig,
+    ) -> Result<Self, Error>
+    where
+        C: Circuit<E, P>,
+    {
+        let mut prover = setup_prover(config);
+        circuit.gadget(prover.mut_cs())?;
+        Ok(Self {
+            prover,
+            prover_key,
+            commit_key,
+        })
+    }
+
+    /// Returns the [`CircuitStats`] of the circuit.
+    pub fn stats(&self) -> CircuitStats {
+        CircuitStats {
+            domain_size: self.prover_key.n,
+            ..self.prover.cs.stats()
+        }
+    }
+
+    /// Sets the [`LagrangeBasis`] used to commit to the wires, see
+    /// [`Prover::set_lagrange_basis`].
+    pub fn set_lagrange_basis(&mut self, basis: Arc<LagrangeBasis<E>>) {
+        self.prover.set_lagrange_basis(basis);
+    }
+
+    /// Returns the composer filled by the gadget of the circuit.
+    pub fn composer(&self) -> &StandardComposer<E, P> {
+        &self.prover.cs
+    }
+
+    /// Assigns `value` to the witness `variable`.
+    ///
+    /// The gadget isn't run again, so the witnesses it computed from the
+    /// previous value of `variable`, such as the outputs of the gates using
+    /// it, keep their values. They must be assigned as well, otherwise the
+    /// resulting proof won't verify.
+    pub fn assign(
+        &mut self,
+        variable: Variable,
+        value: E::Fr,
+    ) -> Result<(), Error> {
+        let witness = self
+            .prover
+            .cs
+            .variables
+            .get_mut(variable.0)
+            .ok_or(Error::UnknownVariable { index: variable.0 })?;
+        *witness = value;
+        Ok(())
+    }
+
+    /// Assigns the values of `public_inputs`, which must cover exactly the
+    /// public input positions of the circuit.
+    pub fn set_public_inputs(
+        &mut self,
+        public_inputs: &PublicInputs<E>,
+    ) -> Result<(), Error> {
+        let store = &mut self.prover.cs.public_inputs_sparse_store;
+        if store.len() != public_inputs.len() {
+            return Err(Error::PublicInputLengthMismatch {
+                expected: store.len(),
+                actual: public_inputs.len(),
+            });
+        }
+        if let Some(position) = public_inputs
+            .positions()
+            .iter()
+            .find(|position| !store.contains_key(position))
+        {
+            return Err(Error::PublicInputPositionMismatch {
+                position: *position,
+            });
+        }
+        // The composer stores the public inputs negated, as they appear in
+        // the arithmetic gates
+        public_inputs
+            .positions()
+            .iter()
+            .zip(public_inputs.values())
+            .for_each(|(position, value)| {
+                store.insert(*position, -*value);
+            });
+        Ok(())
+    }
+
+    /// Commits to the current witness and public input values, see
+    /// [`WitnessCommitment`].
+    pub fn commit_witness(&self) -> Result<WitnessCommitment<E>, Error> {
+        WitnessCommitment::new(&self.prover, &self.commit_key)
+    }
+
+    /// Generates a proof for the current witness and public input values.
+    pub fn prove(&self) -> Result<Proof<E, P>, Error> {
+        self.prover
+            .prove_with_preprocessed(&self.commit_key, &self.prover_key)
+    }
+}
+
+/// [`VerifierKey`] together with the data that stays the same between the
+/// verifications of proofs of its circuit, see [`VerifierKey::prepare`].
+///
+/// It holds the commitment scheme verifier key, whose `G2` elements are
+/// already prepared for the pairings, and the evaluation domain of the
+/// circuit, so that [`verify_prepared_proof`] only does the work specific to
+/// each proof.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct PreparedVerifierKey<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Verifier key of the circuit
+    key: VerifierKey<E, P>,
+
+    /// Commitment scheme verifier key trimmed to the circuit size
+    pc_verifier_key: kzg10::VerifierKey<E>,
+
+    /// Evaluation domain of the circuit
+    domain: GeneralEvaluationDomain<E::Fr>,
+}
+
+impl<E, P> PreparedVerifierKey<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Returns the verifier key of the circuit.
+    pub fn key(&self) -> &VerifierKey<E, P> {
+        &self.key
+    }
+
+    /// Returns the commitment scheme verifier key, holding the prepared `G2`
+    /// elements.
+    pub fn pc_verifier_key(&self) -> &kzg10::VerifierKey<E> {
+        &self.pc_verifier_key
+    }
+
+    /// Returns the evaluation domain of the circuit.
+    pub fn domain(&self) -> &GeneralEvaluationDomain<E::Fr> {
+        &self.domain
+    }
+}
+
+impl<E, P> VerifierKey<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Trims `u_params` to the size of the circuit and precomputes everything
+    /// the verification of its proofs needs besides the proofs themselves.
+    ///
+    /// The returned [`PreparedVerifierKey`] is meant to be kept around by
+    /// verifiers checking many proofs of the same circuit with
+    /// [`verify_prepared_proof`].
+    pub fn prepare(
+        &self,
+        u_params: &UniversalParams<E>,
+    ) -> Result<PreparedVerifierKey<E, P>, Error> {
+        let pc_verifier_key =
+            trim_verifier_key(u_params, self.padded_circuit_size())?;
+        self.prepare_with(pc_verifier_key)
+    }
+
+    /// Prepares the key with a commitment scheme verifier key already
+    /// trimmed to the size of the circuit.
+    pub(crate) fn prepare_with(
+        &self,
+        pc_verifier_key: kzg10::VerifierKey<E>,
+    ) -> Result<PreparedVerifierKey<E, P>, Error> {
+        Ok(PreparedVerifierKey {
+            key: self.clone(),
+            pc_verifier_key,
+            domain: util::evaluation_domain(self.n)?,
+        })
+    }
+}
+
+/// Verifies a proof using the provided `CircuitInputs` & `VerifierKey`
+/// instances.
+///
+/// This prepares the key on every call; verifiers checking many proofs of
+/// the same circuit should use [`verify_prepared_proof`] instead, which
+/// documents the checks run on the proof and expected from the key.
+pub fn verify_proof<E, P>(
+    u_params: &UniversalParams<E>,
+    plonk_verifier_key: VerifierKey<E, P>,
+    proof: &Proof<E, P>,
+    public_inputs: &PublicInputs<E>,
+    config: &VerifierConfig,
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    verify_prepared_proof(
+        &plonk_verifier_key.prepare(u_params)?,
+        proof,
+        public_inputs,
+        config,
+    )
+}
+
+/// Generates a proof of `circuit` whose transcript is initialized with
+/// `label`.
+///
+/// This is [`Circuit::gen_proof`] with the default [`ProverConfig`].
+pub fn prove<E, P, C>(
+    circuit: &mut C,
+    u_params: &UniversalParams<E>,
+    prover_key: &ProverKey<E::Fr, P>,
+    label: &[u8],
+) -> Result<Proof<E, P>, Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    C: Circuit<E, P> + ?Sized,
+{
+    circuit.gen_proof(u_params, prover_key, &ProverConfig::new(label))
+}
+
+/// Verifies a proof generated by [`prove`] with the same `label`, against
+/// the `public_inputs` of the circuit of `verifier_data`.
+pub fn verify<E, P>(
+    verifier_data: &VerifierData<E, P>,
+    u_params: &UniversalParams<E>,
+    proof: &Proof<E, P>,
+    public_inputs: &[PublicInputValue<P>],
+    label: &[u8],
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    verify_proof(
+        u_params,
+        verifier_data.key.clone(),
+        proof,
+        &verifier_data.public_inputs(public_inputs)?,
+        &VerifierConfig::new(label),
+    )
+}
+
+/// Blinds the selectors of a compiled `prover_key` and of the key of
+/// `verifier_data` with fresh randomness, without recompiling the circuit.
+///
+/// Deployments relying on circuit hiding can rotate the blinding of their
+/// keys periodically: the new keys prove and verify the same circuit, with
+/// commitments unrelated to those of the previous ones. See
+/// [`ProverKey::rerandomize`].
+///
+/// Returns the blinding applied to the keys, with which a
+/// [`KeyEquivalenceProof`](crate::proof_system::KeyEquivalenceProof) shows that
+/// the new verifier key describes the same circuit as the previous one.
+pub fn rerandomize_keys<E, P, R>(
+    u_params: &UniversalParams<E>,
+    prover_key: &mut ProverKey<E::Fr, P>,
+    verifier_data: &mut VerifierData<E, P>,
+    rng: &mut R,
+) -> Result<SelectorBlinding<E::Fr>, Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    R: RngCore,
+{
+    let commit_key =
+        trim_commit_key(u_params, verifier_data.key.padded_circuit_size())?;
+    let blinding = prover_key.rerandomize(rng);
+    verifier_data
+        .key
+        .rerandomize(&commit_key, &blinding, prover_key)?;
+    Ok(blinding)
+}
+
+/// Verifies a proof against a [`PreparedVerifierKey`].
+///
+/// The commitments of the proof are checked to be in the prime order
+/// subgroup before anything else, so that proofs from untrusted sources can
+/// be deserialized with [`Proof::deserialize_checked`] as well as without
+/// checks. The verifier key is trusted: keys coming from elsewhere should be
+/// deserialized with [`VerifierKey::deserialize_checked`].
+pub fn verify_prepared_proof<E, P>(
+    prepared_key: &PreparedVerifierKey<E, P>,
+    proof: &Proof<E, P>,
+    public_inputs: &PublicInputs<E>,
+    config: &VerifierConfig,
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    proof.verify(
+        &prepared_key.key,
+        &prepared_key.domain,
+        &mut TranscriptWrapper::new(config.transcript_init()),
+        &prepared_key.pc_verifier_key,
+        &public_inputs.as_evals(prepared_key.key.padded_circuit_size())?,
+    )
+}
+
+/// Verifies a proof against public inputs given as `(position, value)`
+/// pairs, such as the entries of a `BTreeMap`, see
+/// [`PublicInputs::from_pairs`].
+pub fn verify_proof_with_pi_map<E, P, I>(
+    u_params: &UniversalParams<E>,
+    plonk_verifier_key: VerifierKey<E, P>,
+    proof: &Proof<E, P>,
+    public_inputs: I,
+    config: &VerifierConfig,
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    I: IntoIterator<Item = (usize, E::Fr)>,
+{
+    verify_proof(
+        u_params,
+        plonk_verifier_key,
+        proof,
+        &PublicInputs::from_pairs(public_inputs)?,
+        config,
+    )
+}
+
+/// Verifies a proof that was requested for a previously published
+/// [`WitnessCommitment`].
+///
+/// The proof is checked against the public inputs of `witness_commitment`
+/// and must open the same wire commitments.
+pub fn verify_committed_proof<E, P>(
+    u_params: &UniversalParams<E>,
+    plonk_verifier_key: VerifierKey<E, P>,
+    witness_commitment: &WitnessCommitment<E>,
+    proof: &Proof<E, P>,
+    config: &VerifierConfig,
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    witness_commitment.check_binding(proof)?;
+    verify_proof(
+        u_params,
+        plonk_verifier_key,
+        proof,
+        witness_commitment.public_inputs(),
+        config,
+    )
+}
+
+/// Runs the same checks as [`verify_proof`] but returns the challenges and
+/// intermediate values computed by the verifier instead of a plain result.
+///
+/// The returned [`VerificationTrace`] is produced whether the proof is valid
+/// or not, so that the views of a prover and a verifier running on different
+/// machines can be compared to find where they diverge.
+pub fn verify_proof_debug<E, P>(
+    u_params: &UniversalParams<E>,
+    plonk_verifier_key: VerifierKey<E, P>,
+    proof: &Proof<E, P>,
+    public_inputs: &PublicInputs<E>,
+    config: &VerifierConfig,
+) -> Result<VerificationTrace<E>, Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let prepared_key = plonk_verifier_key.prepare(u_params)?;
+    proof.verification_trace(
+        &prepared_key.key,
+        &prepared_key.domain,
+        &mut TranscriptWrapper::new(config.transcript_init()),
+        &prepared_key.pc_verifier_key,
+        &public_inputs.as_evals(prepared_key.key.padded_circuit_size())?,
+    )
+}
+
+/// Builds a [`Prover`] set up according to `config`.
+fn setup_prover<E, P>(config: &ProverConfig) -> Prover<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut prover = Prover::new(config.transcript_init());
+    prover.progress = config.progress_callback().cloned();
+    prover
+}
+
+/// Checks that `u_params` has enough powers for a circuit of `circuit_size`
+/// gates before trimming them.
+///
+/// Committing to the polynomials of a circuit padded to `n` gates requires
+/// `n + 1` powers. No extra powers are needed for blinding factors, since
+/// this crate doesn't add them.
+fn check_circuit_size<E>(
+    u_params: &UniversalParams<E>,
+    circuit_size: usize,
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+{
+    let required_powers = circuit_size + 1;
+    let available_powers = u_params.powers_of_g.len();
+    if required_powers > available_powers {
+        return Err(Error::UniversalParamsTooSmall {
+            circuit_size,
+            required_powers,
+            available_powers,
+        });
+    }
+    Ok(())
+}
+
+/// Trims `u_params` to a commit key for a circuit of `circuit_size` gates.
+pub(crate) fn trim_commit_key<E>(
+    u_params: &UniversalParams<E>,
+    circuit_size: usize,
+) -> Result<Powers<'static, E>, Error>
+where
+    E: PairingEngine,
+{
+    // XXX: KZG10 does not have a trim function so we use sonics and
+    // then do a transformation between sonic CommiterKey to KZG10
+    // powers
+    check_circuit_size(u_params, circuit_size)?;
+    let (ck, _) = SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(
+        u_params,
+        circuit_size,
+        0,
+        None,
+    )?;
+    Ok(Powers {
+        powers_of_g: ck.powers_of_g.into(),
+        powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+    })
+}
+
+/// Trims `u_params` to a commitment scheme verifier key for a circuit of
+/// `circuit_size` gates.
+pub(crate) fn trim_verifier_key<E>(
+    u_params: &UniversalParams<E>,
+    circuit_size: usize,
+) -> Result<kzg10::VerifierKey<E>, Error>
+where
+    E: PairingEngine,
+{
+    check_circuit_size(u_params, circuit_size)?;
+    let (_, sonic_vk) = SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(
+        u_params,
+        circuit_size,
+        0,
+        None,
+    )?;
+    Ok(kzg10::VerifierKey {
+        g: sonic_vk.g,
+        gamma_g: sonic_vk.gamma_g,
+        h: sonic_vk.h,
+        beta_h: sonic_vk.beta_h,
+        prepared_h: sonic_vk.prepared_h,
+        prepared_beta_h: sonic_vk.prepared_beta_h,
+    })
+}
+
+/// Returns the largest padded circuit size that can be compiled, proved and
+/// verified with `u_params`.
+///
+/// Circuits are padded to the next power of two and their polynomials are
+/// committed using `u_params` trimmed to that size, so the supported size is
+/// the largest power of two not exceeding the maximum degree of the
+/// parameters. The only blinding factors are those of
+/// [`rerandomize_keys`], which keep the degree of the selectors at most the
+/// circuit size, so no extra degrees are reserved. The size is further
+/// bounded by the largest FFT domain of the scalar field, since the prover
+/// works over an extended domain four times the size of the circuit.
+pub fn max_supported_circuit_size<E>(u_params: &UniversalParams<E>) -> usize
+where
+    E: PairingEngine,
+{
+    let max_degree = u_params.powers_of_g.len().saturating_sub(1);
+    if max_degree == 0 {
+        return 0;
+    }
+    let max_domain_size =
+        1usize << (<E::Fr as FftField>::FftParams::TWO_ADICITY - 2).min(62);
+    let srs_size = 1 << (usize::BITS - 1 - max_degree.leading_zeros());
+    srs_size.min(max_domain_size)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constraint_system::StandardComposer;
+    use crate::proof_system::{Bytes32, ProverPhase};
+    use alloc::collections::BTreeMap;
+    use alloc::sync::Arc;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_bw6_761::BW6_761;
+    use ark_ec::twisted_edwards_extended::GroupAffine;
+    use ark_ec::AffineCurve;
+    use ark_ff::PrimeField;
+    use ark_poly::EvaluationDomain;
+    use ark_poly_commit::kzg10::KZG10;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use num_traits::{One, Zero};
+
+    // Implements a circuit that checks:
     // 1) a + b = c where C is a PI
     // 2) a <= 2^6
     // 3) b <= 2^5
@@ -525,8 +1693,7 @@ mod test {
                 composer.mul(E::Fr::one(), a, b, E::Fr::zero(), Some(-self.d));
             composer.assert_equal(mul_result, composer.zero_var());
 
-            let e = composer
-                .add_input(util::from_embedded_curve_scalar::<E, P>(self.e));
+            let e = composer.add_embedded_scalar(self.e);
             let (x, y) = P::AFFINE_GENERATOR_COEFFS;
             let generator = GroupAffine::new(x, y);
             let scalar_mul_result =
@@ -536,10 +1703,6 @@ mod test {
             composer.assert_equal_public_point(scalar_mul_result, self.f);
             Ok(())
         }
-
-        fn padded_circuit_size(&self) -> usize {
-            1 << 11
-        }
     }
 
     fn test_full<E: PairingEngine, P: TEModelParameters<BaseField = E::Fr>>(
@@ -557,6 +1720,32 @@ mod test {
 
         // Compile the circuit
         let (pk_p, verifier_data) = circuit.compile(&pp)?;
+        assert!(pk_p.matches(&verifier_data.key));
+        assert_eq!(pk_p.verifier_key_digest(), verifier_data.key.digest());
+        assert_eq!(verifier_data.circuit_id(), Some([0xff; 32]));
+        assert_ne!(verifier_data.key.circuit_digest(), [0xff; 32]);
+
+        // Compiling again reproduces the same keys
+        let (pk_again, verifier_data_again) = circuit.compile(&pp)?;
+        assert_eq!(pk_again, pk_p);
+        assert_eq!(verifier_data_again, verifier_data);
+
+        // The SRS must be large enough for the circuit, whose size is
+        // computed by running the gadget
+        assert_eq!(circuit.padded_circuit_size(), pk_p.n);
+        assert!(
+            circuit.padded_circuit_size() <= max_supported_circuit_size(&pp)
+        );
+
+        // Transcript labels don't need to be known at compile time
+        let label = format!("Test-{}", E::Fr::size_in_bits()).into_bytes();
+        let phases = Arc::new(AtomicUsize::new(0));
+        let counter = phases.clone();
+        let config =
+            ProverConfig::new(&label).with_progress_callback(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        let verifier_config = config.verifier_config();
 
         let (x, y) = P::AFFINE_GENERATOR_COEFFS;
         let generator: GroupAffine<P> = GroupAffine::new(x, y);
@@ -577,8 +1766,9 @@ mod test {
                 f: point_f_pi,
             };
 
-            circuit.gen_proof(&pp, pk_p, b"Test")?
+            circuit.gen_proof(&pp, &pk_p, &config)?
         };
+        assert_eq!(phases.load(Ordering::SeqCst), ProverPhase::ALL.len());
 
         // Test serialisation for verifier_data
         let mut verifier_data_bytes = Vec::new();
@@ -595,32 +1785,1120 @@ mod test {
             E::Fr::from(100u64).into_pi(),
             GeIntoPubInput::into_pi(point_f_pi),
         ];
+        let public_inputs = verifier_data.public_inputs(&public_inputs)?;
 
-        let VerifierData { key, pi_pos } = verifier_data;
+        // The builder assigns the same values, checking their kinds
+        assert_eq!(
+            verifier_data.pi_kinds(),
+            [
+                PublicInputKind::Scalar,
+                PublicInputKind::Scalar,
+                PublicInputKind::Point
+            ]
+        );
+        let built = verifier_data
+            .public_input_builder()
+            .scalar(E::Fr::from(25u64))?
+            .scalar(E::Fr::from(100u64))?
+            .point(point_f_pi)?
+            .finish()?;
+        assert_eq!(built, public_inputs);
+        assert!(matches!(
+            verifier_data
+                .public_input_builder()
+                .scalar(E::Fr::from(25u64))?
+                .point(point_f_pi),
+            Err(Error::PublicInputKindMismatch {
+                index: 1,
+                expected: PublicInputKind::Scalar,
+                actual: PublicInputKind::Point,
+            })
+        ));
+        assert!(matches!(
+            verifier_data
+                .public_input_builder()
+                .scalar(E::Fr::from(25u64))?
+                .finish(),
+            Err(Error::PublicInputLengthMismatch {
+                expected: 3,
+                actual: 1
+            })
+        ));
+        assert!(matches!(
+            verifier_data
+                .public_input_builder()
+                .scalar(E::Fr::from(25u64))?
+                .scalar(E::Fr::from(100u64))?
+                .point(point_f_pi)?
+                .scalar(E::Fr::one()),
+            Err(Error::PublicInputLengthMismatch {
+                expected: 3,
+                actual: 4
+            })
+        ));
 
-        // TODO: non-ideal hack for a first functional version.
-        assert!(verify_proof::<E, P>(
+        // Public inputs must match the circuit positions
+        assert!(matches!(
+            verifier_data.public_inputs(&[E::Fr::from(25u64).into_pi()]),
+            Err(Error::PublicInputLengthMismatch { actual: 1, .. })
+        ));
+        assert!(matches!(
+            PublicInputs::<E>::from_values::<P>(
+                &[E::Fr::one().into_pi(), E::Fr::one().into_pi()],
+                &[4, 4],
+            ),
+            Err(Error::DuplicatePublicInputPosition { position: 4 })
+        ));
+
+        // Point values must satisfy the curve equation
+        let off_curve = GroupAffine::<P>::new(E::Fr::one(), E::Fr::one());
+        assert!(!off_curve.is_on_curve());
+        let point_pos = verifier_data.pi_pos()[2];
+        assert!(matches!(
+            verifier_data.public_inputs(&[
+                E::Fr::from(25u64).into_pi(),
+                E::Fr::from(100u64).into_pi(),
+                GeIntoPubInput::into_pi(off_curve),
+            ]),
+            Err(Error::PointNotOnCurve { gate_index }) if gate_index == point_pos
+        ));
+        assert!(matches!(
+            verifier_data
+                .public_input_builder()
+                .scalar(E::Fr::from(25u64))?
+                .scalar(E::Fr::from(100u64))?
+                .point(off_curve),
+            Err(Error::PointNotOnCurve { gate_index }) if gate_index == point_pos
+        ));
+
+        let VerifierData { key, pi_pos, .. } = verifier_data;
+
+        // Public inputs are canonical regardless of insertion order
+        let mut reordered = PublicInputs::<E>::new();
+        pi_pos
+            .iter()
+            .zip(public_inputs.values())
+            .rev()
+            .for_each(|(pos, value)| reordered.insert(*pos, *value));
+        assert_eq!(reordered, public_inputs);
+        assert_eq!(reordered.digest(), public_inputs.digest());
+
+        // Deserialized public inputs keep the invariants of `from_pairs`
+        let mut bytes = Vec::new();
+        public_inputs.serialize(&mut bytes).unwrap();
+        assert_eq!(
+            PublicInputs::<E>::deserialize(&bytes[..]).unwrap(),
+            public_inputs
+        );
+        let read = |positions: Vec<usize>, values: Vec<E::Fr>| {
+            let mut bytes = Vec::new();
+            (positions, values).serialize(&mut bytes).unwrap();
+            PublicInputs::<E>::deserialize(&bytes[..])
+        };
+        let values = public_inputs.values().to_vec();
+        let unsorted: Vec<usize> = pi_pos.iter().rev().copied().collect();
+        let unsorted_values: Vec<E::Fr> =
+            values.iter().rev().copied().collect();
+        assert_eq!(read(unsorted, unsorted_values).unwrap(), public_inputs);
+        assert!(read(vec![pi_pos[0], pi_pos[0]], values[..2].to_vec()).is_err());
+        assert!(read(pi_pos.clone(), values[1..].to_vec()).is_err());
+
+        // Proofs don't record the transcript label, so a different label is
+        // only caught by the pairing check
+        assert!(matches!(
+            verify_proof::<E, P>(
+                &pp,
+                key.clone(),
+                &proof,
+                &public_inputs,
+                &VerifierConfig::new(b"Test"),
+            ),
+            Err(Error::PairingCheckFailure)
+        ));
+
+        // Public inputs can be given as a map from positions to values
+        let pi_map: BTreeMap<usize, E::Fr> = public_inputs
+            .positions()
+            .iter()
+            .copied()
+            .zip(public_inputs.values().iter().copied())
+            .collect();
+        assert!(verify_proof_with_pi_map::<E, P, _>(
             &pp,
-            key,
+            key.clone(),
             &proof,
-            &public_inputs,
-            &pi_pos,
-            b"Test",
+            pi_map,
+            &verifier_config,
         )
         .is_ok());
+        assert!(matches!(
+            verify_proof_with_pi_map::<E, P, _>(
+                &pp,
+                key.clone(),
+                &proof,
+                vec![(pi_pos[0], E::Fr::one()), (pi_pos[0], E::Fr::one())],
+                &verifier_config,
+            ),
+            Err(Error::DuplicatePublicInputPosition { .. })
+        ));
 
-        Ok(())
-    }
+        // Positions past the end of the circuit are rejected
+        let mut out_of_range = public_inputs.clone();
+        out_of_range.insert(key.padded_circuit_size(), E::Fr::one());
+        assert!(matches!(
+            verify_proof::<E, P>(
+                &pp,
+                key.clone(),
+                &proof,
+                &out_of_range,
+                &verifier_config,
+            ),
+            Err(Error::PublicInputPositionOutOfRange { position, circuit_size })
+                if position == circuit_size
+                    && circuit_size == key.padded_circuit_size()
+        ));
 
-    #[test]
-    #[allow(non_snake_case)]
-    fn test_full_on_Bls12_381() -> Result<(), Error> {
-        test_full::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>()
-    }
+        // Parameters too small for the circuit are reported as such
+        let small_pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            key.padded_circuit_size() - 1,
+            false,
+            &mut OsRng,
+        )?;
+        assert!(matches!(
+            verify_proof::<E, P>(
+                &small_pp,
+                key.clone(),
+                &proof,
+                &public_inputs,
+                &verifier_config,
+            ),
+            Err(Error::UniversalParamsTooSmall {
+                circuit_size,
+                required_powers,
+                ..
+            }) if circuit_size == key.padded_circuit_size()
+                && required_powers == circuit_size + 1
+        ));
+        assert!(matches!(
+            circuit.compile(&small_pp),
+            Err(Error::UniversalParamsTooSmall { .. })
+        ));
 
-    #[test]
+        // The proof is bound to the circuit identifier of the key
+        let mut other_key = key.clone();
+        other_key.circuit_id = Some(Bytes32([0u8; 32]));
+        assert_ne!(other_key.digest(), key.digest());
+        assert!(verify_proof::<E, P>(
+            &pp,
+            other_key,
+            &proof,
+            &public_inputs,
+            &verifier_config,
+        )
+        .is_err());
+
+        // The trace of a failed verification agrees with a valid one up
+        // to the first diverging transcript message
+        let trace = verify_proof_debug::<E, P>(
+            &pp,
+            key.clone(),
+            &proof,
+            &public_inputs,
+            &verifier_config,
+        )?;
+        assert!(trace.pairing_check);
+        let mut bad_inputs = public_inputs.clone();
+        bad_inputs.insert(pi_pos[0], E::Fr::from(26u64));
+        assert_ne!(bad_inputs.digest(), public_inputs.digest());
+        let bad_trace = verify_proof_debug::<E, P>(
+            &pp,
+            key.clone(),
+            &proof,
+            &bad_inputs,
+            &verifier_config,
+        )?;
+        assert!(!bad_trace.pairing_check);
+        assert_eq!(trace.z_challenge, bad_trace.z_challenge);
+        assert_ne!(trace.aggregate_proof_eval, bad_trace.aggregate_proof_eval);
+
+        // A prepared key is reused across verifications
+        let prepared_key = key.prepare(&pp)?;
+        assert_eq!(prepared_key.domain().size(), key.padded_circuit_size());
+        for _ in 0..2 {
+            assert!(verify_prepared_proof(
+                &prepared_key,
+                &proof,
+                &public_inputs,
+                &verifier_config,
+            )
+            .is_ok());
+        }
+        assert!(matches!(
+            verify_prepared_proof(
+                &prepared_key,
+                &proof,
+                &bad_inputs,
+                &verifier_config,
+            ),
+            Err(Error::PairingCheckFailure)
+        ));
+        assert!(matches!(
+            key.prepare(&small_pp),
+            Err(Error::UniversalParamsTooSmall { .. })
+        ));
+
+        // TODO: non-ideal hack for a first functional version.
+        assert!(verify_proof::<E, P>(
+            &pp,
+            key,
+            &proof,
+            &public_inputs,
+            &verifier_config,
+        )
+        .is_ok());
+
+        Ok(())
+    }
+
+    // Implements a circuit that checks a * b = c where C is a PI, recording
+    // the variables of a and b. Its identifier is derived from the circuit.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct MulCircuit<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    > {
+        a: E::Fr,
+        b: E::Fr,
+        c: E::Fr,
+        vars: Vec<Variable>,
+        __: core::marker::PhantomData<P>,
+    }
+
+    impl<E, P> Circuit<E, P> for MulCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let mul_result =
+                composer.mul(E::Fr::one(), a, b, E::Fr::zero(), Some(-self.c));
+            composer.assert_equal(mul_result, composer.zero_var());
+            self.vars = vec![a, b];
+            Ok(())
+        }
+    }
+
+    fn test_prove_verify<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 12,
+            false,
+            &mut OsRng,
+        )?;
+        let (pk_p, verifier_data) =
+            TestCircuit::<E, P>::default().compile(&pp)?;
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        let point_f_pi: GroupAffine<P> = AffineCurve::mul(
+            &generator,
+            P::ScalarField::from(2u64).into_repr(),
+        )
+        .into_affine();
+        let mut circuit = TestCircuit::<E, P> {
+            a: E::Fr::from(20u64),
+            b: E::Fr::from(5u64),
+            c: E::Fr::from(25u64),
+            d: E::Fr::from(100u64),
+            e: P::ScalarField::from(2u64),
+            f: point_f_pi,
+        };
+        let proof = prove(&mut circuit, &pp, &pk_p, b"Test")?;
+
+        let public_inputs = [
+            E::Fr::from(25u64).into_pi(),
+            E::Fr::from(100u64).into_pi(),
+            GeIntoPubInput::into_pi(point_f_pi),
+        ];
+        verify(&verifier_data, &pp, &proof, &public_inputs, b"Test")?;
+
+        // The labels of the prover and the verifier must match
+        assert!(matches!(
+            verify(&verifier_data, &pp, &proof, &public_inputs, b"Other"),
+            Err(Error::PairingCheckFailure)
+        ));
+        Ok(())
+    }
+
+    // Implements a circuit that checks:
+    // 1) a * a = c where C is a PI
+    // 2) JubJub::GENERATOR * e(JubJubScalar) = f where F is a PI
+    #[cfg(feature = "derive")]
+    #[derive(PlonkCircuit, derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct DerivedCircuit<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    > {
+        #[witness]
+        a: E::Fr,
+        #[public]
+        c: E::Fr,
+        #[embedded_scalar]
+        e: P::ScalarField,
+        #[public]
+        f: GroupAffine<P>,
+        // Not an input of the circuit
+        label: &'static str,
+    }
+
+    #[cfg(feature = "derive")]
+    impl<E, P> Circuit<E, P> for DerivedCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let inputs = self.allocate(composer);
+            let square = composer.mul(
+                E::Fr::one(),
+                inputs.a,
+                inputs.a,
+                E::Fr::zero(),
+                None,
+            );
+            composer.assert_equal(square, inputs.c);
+
+            let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+            let generator = GroupAffine::new(x, y);
+            let scalar_mul_result =
+                composer.fixed_base_scalar_mul(inputs.e, generator);
+            composer.assert_equal_point(scalar_mul_result, inputs.f);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    fn test_derived_circuit<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 12,
+            false,
+            &mut OsRng,
+        )?;
+
+        // The public inputs are ordered as declared
+        assert_eq!(
+            DerivedCircuit::<E, P>::PUBLIC_INPUTS,
+            [
+                ("c", PublicInputKind::Scalar),
+                ("f", PublicInputKind::Point)
+            ]
+        );
+        let (pk_p, verifier_data) =
+            DerivedCircuit::<E, P>::default().compile(&pp)?;
+        assert_eq!(
+            verifier_data.pi_kinds(),
+            [PublicInputKind::Scalar, PublicInputKind::Point]
+        );
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        let mut circuit = DerivedCircuit::<E, P> {
+            a: E::Fr::from(3u64),
+            c: E::Fr::from(9u64),
+            e: P::ScalarField::from(2u64),
+            f: AffineCurve::mul(
+                &generator,
+                P::ScalarField::from(2u64).into_repr(),
+            )
+            .into_affine(),
+            label: "derived",
+        };
+        let config = ProverConfig::new(circuit.label.as_bytes());
+        let proof = circuit.gen_proof(&pp, &pk_p, &config)?;
+
+        let public_inputs =
+            verifier_data.public_inputs(&circuit.public_input_values())?;
+        verify_proof(
+            &pp,
+            verifier_data.key,
+            &proof,
+            &public_inputs,
+            &config.verifier_config(),
+        )
+    }
+
+    // Implements a circuit that checks a * b + a = c where C is a PI,
+    // recording the variables of a, b and of the product a * b.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct MulAddCircuit<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    > {
+        a: E::Fr,
+        b: E::Fr,
+        c: E::Fr,
+        vars: Vec<Variable>,
+        __: core::marker::PhantomData<P>,
+    }
+
+    impl<E, P> Circuit<E, P> for MulAddCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let product = composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+            let sum = composer.add(
+                (E::Fr::one(), product),
+                (E::Fr::one(), a),
+                E::Fr::zero(),
+                Some(-self.c),
+            );
+            composer.assert_equal(sum, composer.zero_var());
+            self.vars = vec![a, b, product];
+            Ok(())
+        }
+    }
+
+    fn test_compiled_circuit<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 5,
+            false,
+            &mut OsRng,
+        )?;
+        let (pk, verifier_data) = MulCircuit::<E, P>::default().compile(&pp)?;
+        assert_eq!(verifier_data.verification_cost().public_inputs, 1);
+        assert_eq!(
+            verifier_data.circuit_id(),
+            Some(verifier_data.key.circuit_digest())
+        );
+
+        let mut circuit = MulCircuit::<E, P> {
+            a: E::Fr::from(2u64),
+            b: E::Fr::from(3u64),
+            c: E::Fr::from(6u64),
+            ..Default::default()
+        };
+        let mut compiled = CompiledCircuit::new(
+            &mut circuit,
+            &pp,
+            pk,
+            &ProverConfig::new(b"Mul"),
+        )?;
+        let (a, b) = (circuit.vars[0], circuit.vars[1]);
+        let stats = circuit.stats()?;
+        assert_eq!(compiled.stats(), stats);
+        assert_eq!(stats.domain_size, verifier_data.key.padded_circuit_size());
+        assert_eq!(stats.public_inputs, verifier_data.pi_pos.len());
+
+        let public_inputs =
+            verifier_data.public_inputs(&[E::Fr::from(6u64).into_pi()])?;
+        let proof = compiled.prove()?;
+        assert_eq!(stats.proof_bytes, proof.serialized_size());
+        verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &proof,
+            &public_inputs,
+            &VerifierConfig::new(b"Mul"),
+        )?;
+
+        // Prove again with new witnesses, without running the gadget
+        compiled.assign(a, E::Fr::from(4u64))?;
+        compiled.assign(b, E::Fr::from(5u64))?;
+        let public_inputs =
+            verifier_data.public_inputs(&[E::Fr::from(20u64).into_pi()])?;
+        compiled.set_public_inputs(&public_inputs)?;
+        let proof = compiled.prove()?;
+        verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &proof,
+            &public_inputs,
+            &VerifierConfig::new(b"Mul"),
+        )?;
+
+        // The proof doesn't hold for other public inputs
+        let wrong_inputs =
+            verifier_data.public_inputs(&[E::Fr::from(6u64).into_pi()])?;
+        assert!(verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &proof,
+            &wrong_inputs,
+            &VerifierConfig::new(b"Mul"),
+        )
+        .is_err());
+
+        // Only the variables and public inputs of the circuit can be assigned
+        let unknown = Variable(compiled.composer().variables.len());
+        assert!(matches!(
+            compiled.assign(unknown, E::Fr::one()),
+            Err(Error::UnknownVariable { .. })
+        ));
+        let mut other_inputs = PublicInputs::new();
+        other_inputs.insert(0, E::Fr::one());
+        assert!(matches!(
+            compiled.set_public_inputs(&other_inputs),
+            Err(Error::PublicInputPositionMismatch { position: 0 })
+        ));
+
+        // Witnesses computed by the gadget from the reassigned ones must be
+        // reassigned too
+        let (pk, verifier_data) =
+            MulAddCircuit::<E, P>::default().compile(&pp)?;
+        let mut circuit = MulAddCircuit::<E, P> {
+            a: E::Fr::from(2u64),
+            b: E::Fr::from(3u64),
+            c: E::Fr::from(8u64),
+            ..Default::default()
+        };
+        let mut compiled = CompiledCircuit::new(
+            &mut circuit,
+            &pp,
+            pk,
+            &ProverConfig::new(b"MulAdd"),
+        )?;
+        let (a, b, product) =
+            (circuit.vars[0], circuit.vars[1], circuit.vars[2]);
+        compiled.assign(a, E::Fr::from(4u64))?;
+        compiled.assign(b, E::Fr::from(5u64))?;
+        let public_inputs =
+            verifier_data.public_inputs(&[E::Fr::from(24u64).into_pi()])?;
+        compiled.set_public_inputs(&public_inputs)?;
+        let stale = compiled.prove()?;
+        assert!(verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &stale,
+            &public_inputs,
+            &VerifierConfig::new(b"MulAdd"),
+        )
+        .is_err());
+
+        compiled.assign(product, E::Fr::from(20u64))?;
+        let proof = compiled.prove()?;
+        verify_proof(
+            &pp,
+            verifier_data.key,
+            &proof,
+            &public_inputs,
+            &VerifierConfig::new(b"MulAdd"),
+        )
+    }
+
+    fn test_compile_with_storage<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use crate::key_storage::MemoryKeyStorage;
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 5,
+            false,
+            &mut OsRng,
+        )?;
+        let (pk, verifier_data) = MulCircuit::<E, P>::default().compile(&pp)?;
+        let vk_digest = verifier_data.key.digest();
+
+        // The keys are compiled and stored on a miss, then read back
+        let storage = MemoryKeyStorage::new();
+        let compiled = MulCircuit::<E, P>::default()
+            .compile_with_storage(&pp, &storage, &vk_digest)?;
+        assert_eq!(storage.len(), 3);
+        let stored = MulCircuit::<E, P>::default()
+            .compile_with_storage(&pp, &storage, &vk_digest)?;
+        for (stored_pk, stored_data) in [compiled, stored] {
+            assert_eq!(stored_pk, pk);
+            assert_eq!(stored_data.key, verifier_data.key);
+            assert_eq!(stored_data.pi_pos, verifier_data.pi_pos);
+            assert_eq!(stored_data.pi_kinds, verifier_data.pi_kinds);
+        }
+
+        // Stored keys are checked against the circuit, e.g. when the digest
+        // is stale
+        assert!(matches!(
+            MulAddCircuit::<E, P>::default()
+                .compile_with_storage(&pp, &storage, &vk_digest),
+            Err(Error::KeyDigestMismatch)
+        ));
+
+        // The compiled keys are checked against the expected digest
+        assert!(matches!(
+            MulAddCircuit::<E, P>::default().compile_with_storage(
+                &pp,
+                &MemoryKeyStorage::new(),
+                &vk_digest
+            ),
+            Err(Error::KeyDigestMismatch)
+        ));
+
+        let mut circuit = MulCircuit::<E, P> {
+            a: E::Fr::from(2u64),
+            b: E::Fr::from(3u64),
+            c: E::Fr::from(6u64),
+            ..Default::default()
+        };
+        let compiled = CompiledCircuit::from_storage(
+            &mut circuit,
+            &pp,
+            &storage,
+            &vk_digest,
+            &ProverConfig::new(b"Mul"),
+        )?;
+        let public_inputs =
+            verifier_data.public_inputs(&[E::Fr::from(6u64).into_pi()])?;
+        verify_proof(
+            &pp,
+            verifier_data.key,
+            &compiled.prove()?,
+            &public_inputs,
+            &VerifierConfig::new(b"Mul"),
+        )
+    }
+
+    fn test_witness_commitment<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 5,
+            false,
+            &mut OsRng,
+        )?;
+        let (pk, verifier_data) = MulCircuit::<E, P>::default().compile(&pp)?;
+
+        let mut circuit = MulCircuit::<E, P> {
+            a: E::Fr::from(2u64),
+            b: E::Fr::from(3u64),
+            c: E::Fr::from(6u64),
+            ..Default::default()
+        };
+
+        // Only the commitment is published at first
+        let commitment = circuit.commit_witness(&pp)?;
+        let mut bytes = Vec::new();
+        commitment.serialize(&mut bytes)?;
+        let commitment = WitnessCommitment::<E>::deserialize(&bytes[..])?;
+        assert_eq!(
+            commitment.public_inputs(),
+            &verifier_data.public_inputs(&[E::Fr::from(6u64).into_pi()])?
+        );
+
+        // The proof produced when challenged opens the committed witness
+        let proof = circuit.gen_proof(&pp, &pk, &ProverConfig::new(b"Mul"))?;
+        verify_committed_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &commitment,
+            &proof,
+            &VerifierConfig::new(b"Mul"),
+        )?;
+
+        // A valid proof for another witness with the same public inputs is
+        // rejected
+        let mut other = MulCircuit::<E, P> {
+            a: E::Fr::from(1u64),
+            b: E::Fr::from(6u64),
+            c: E::Fr::from(6u64),
+            ..Default::default()
+        };
+        let other_proof =
+            other.gen_proof(&pp, &pk, &ProverConfig::new(b"Mul"))?;
+        verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &other_proof,
+            commitment.public_inputs(),
+            &VerifierConfig::new(b"Mul"),
+        )?;
+        assert!(matches!(
+            verify_committed_proof(
+                &pp,
+                verifier_data.key,
+                &commitment,
+                &other_proof,
+                &VerifierConfig::new(b"Mul"),
+            ),
+            Err(Error::WitnessCommitmentMismatch)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_supported_circuit_size() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        for (degree, expected) in [(1, 1), (60, 32), (64, 64), (100, 64)] {
+            let pp = KZG10::<Bls12_381, DensePolynomial<_>>::setup(
+                degree, false, &mut OsRng,
+            )?;
+            let size = max_supported_circuit_size(&pp);
+            assert_eq!(size, expected);
+            assert!(SonicKZG10::<Bls12_381, DensePolynomial<_>>::trim(
+                &pp, size, 0, None
+            )
+            .is_ok());
+            assert!(SonicKZG10::<Bls12_381, DensePolynomial<_>>::trim(
+                &pp,
+                2 * size,
+                0,
+                None
+            )
+            .is_err());
+        }
+        Ok(())
+    }
+
+    // MulCircuit compiled with the selectors of unused custom gates dropped
+    // from the prover key.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct CompressedMulCircuit<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >(MulCircuit<E, P>);
+
+    impl<E, P> Circuit<E, P> for CompressedMulCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        const COMPRESS_SELECTORS: bool = true;
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            self.0.gadget(composer)
+        }
+    }
+
+    fn test_compress_selectors<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 10,
+            false,
+            &mut OsRng,
+        )?;
+        let (pk, verifier_data) = MulCircuit::<E, P>::default().compile(&pp)?;
+        let (compressed_pk, compressed_verifier_data) =
+            CompressedMulCircuit::<E, P>::default().compile(&pp)?;
+
+        // None of the custom gates are used, only the prover key shrinks
+        assert_eq!(compressed_verifier_data, verifier_data);
+        assert!(compressed_pk.matches(&verifier_data.key));
+        assert!(compressed_pk.memory_footprint() < pk.memory_footprint());
+        let mut recompressed_pk = pk.clone();
+        assert_eq!(recompressed_pk.compress_selectors(), 4);
+        assert_eq!(recompressed_pk.compress_selectors(), 0);
+        assert_eq!(recompressed_pk, compressed_pk);
+
+        // Proofs of the compressed key verify against the same verifier key
+        let mut circuit = CompressedMulCircuit(MulCircuit::<E, P> {
+            a: E::Fr::from(2u64),
+            b: E::Fr::from(3u64),
+            c: E::Fr::from(6u64),
+            ..Default::default()
+        });
+        let proof = circuit.gen_proof(
+            &pp,
+            &compressed_pk,
+            &ProverConfig::new(b"Mul"),
+        )?;
+        let uncompressed_proof =
+            circuit.0.gen_proof(&pp, &pk, &ProverConfig::new(b"Mul"))?;
+        assert_eq!(
+            proof.serialized_size(),
+            uncompressed_proof.serialized_size()
+        );
+        let public_inputs =
+            verifier_data.public_inputs(&[E::Fr::from(6u64).into_pi()])?;
+        verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &proof,
+            &public_inputs,
+            &VerifierConfig::new(b"Mul"),
+        )?;
+
+        // Selectors of the custom gates a circuit uses are kept
+        let (mut pk, _) = TestCircuit::<E, P>::default().compile(&pp)?;
+        assert!(pk.compress_selectors() < 4);
+        Ok(())
+    }
+
+    fn test_rerandomize_keys<
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    >() -> Result<(), Error> {
+        use rand_core::OsRng;
+
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 12,
+            false,
+            &mut OsRng,
+        )?;
+        let (pk, verifier_data) =
+            TestCircuit::<E, P>::default().compile(&pp)?;
+        let (mut blinded_pk, mut blinded_verifier_data) =
+            (pk.clone(), verifier_data.clone());
+        rerandomize_keys(
+            &pp,
+            &mut blinded_pk,
+            &mut blinded_verifier_data,
+            &mut OsRng,
+        )?;
+        assert!(blinded_pk.matches(&blinded_verifier_data.key));
+        assert_ne!(
+            blinded_verifier_data.key.arithmetic.q_m,
+            verifier_data.key.arithmetic.q_m
+        );
+        assert_ne!(
+            blinded_verifier_data.key.range_selector_commitment,
+            verifier_data.key.range_selector_commitment
+        );
+        assert_eq!(
+            blinded_verifier_data.key.permutation,
+            verifier_data.key.permutation
+        );
+        assert_eq!(blinded_verifier_data.circuit_id(), Some([0xff; 32]));
+
+        // The blinded keys still prove and verify the circuit, but not
+        // together with the previous ones
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        let point: GroupAffine<P> = AffineCurve::mul(
+            &generator,
+            P::ScalarField::from(2u64).into_repr(),
+        )
+        .into_affine();
+        let mut circuit = TestCircuit::<E, P> {
+            a: E::Fr::from(20u64),
+            b: E::Fr::from(5u64),
+            c: E::Fr::from(25u64),
+            d: E::Fr::from(100u64),
+            e: P::ScalarField::from(2u64),
+            f: point,
+        };
+        let public_inputs = verifier_data.public_inputs(&[
+            E::Fr::from(25u64).into_pi(),
+            E::Fr::from(100u64).into_pi(),
+            GeIntoPubInput::into_pi(point),
+        ])?;
+        let config = ProverConfig::new(b"Test");
+        let proof = circuit.gen_proof(&pp, &blinded_pk, &config)?;
+        verify_proof(
+            &pp,
+            blinded_verifier_data.key.clone(),
+            &proof,
+            &public_inputs,
+            &config.verifier_config(),
+        )?;
+        assert!(verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &proof,
+            &public_inputs,
+            &config.verifier_config(),
+        )
+        .is_err());
+
+        // Compressed selectors stay dropped
+        let (mut compressed_pk, mut verifier_data) =
+            CompressedMulCircuit::<E, P>::default().compile(&pp)?;
+        rerandomize_keys(
+            &pp,
+            &mut compressed_pk,
+            &mut verifier_data,
+            &mut OsRng,
+        )?;
+        assert!(compressed_pk.logic_selector.1.evals.is_empty());
+        assert!(verifier_data.key.logic_selector_commitment.0.is_zero());
+        Ok(())
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_full_on_Bls12_381() -> Result<(), Error> {
+        test_full::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>()
+    }
+
+    #[test]
     #[allow(non_snake_case)]
     fn test_full_on_Bls12_377() -> Result<(), Error> {
         test_full::<Bls12_377, ark_ed_on_bls12_377::EdwardsParameters>()
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_full_on_Bn254() -> Result<(), Error> {
+        test_full::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_full_on_BW6_761() -> Result<(), Error> {
+        test_full::<BW6_761, ark_ed_on_bw6_761::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_prove_verify_on_Bls12_381() -> Result<(), Error> {
+        test_prove_verify::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    #[cfg(feature = "derive")]
+    fn test_derived_circuit_on_Bls12_381() -> Result<(), Error> {
+        test_derived_circuit::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>(
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compiled_circuit_on_Bls12_381() -> Result<(), Error> {
+        test_compiled_circuit::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>(
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compiled_circuit_on_Bls12_377() -> Result<(), Error> {
+        test_compiled_circuit::<Bls12_377, ark_ed_on_bls12_377::EdwardsParameters>(
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compiled_circuit_on_Bn254() -> Result<(), Error> {
+        test_compiled_circuit::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compiled_circuit_on_BW6_761() -> Result<(), Error> {
+        test_compiled_circuit::<BW6_761, ark_ed_on_bw6_761::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compile_with_storage_on_Bls12_381() -> Result<(), Error> {
+        test_compile_with_storage::<
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compile_with_storage_on_Bn254() -> Result<(), Error> {
+        test_compile_with_storage::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_witness_commitment_on_Bls12_381() -> Result<(), Error> {
+        test_witness_commitment::<
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_witness_commitment_on_Bls12_377() -> Result<(), Error> {
+        test_witness_commitment::<
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_witness_commitment_on_Bn254() -> Result<(), Error> {
+        test_witness_commitment::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compress_selectors_on_Bls12_381() -> Result<(), Error> {
+        test_compress_selectors::<
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compress_selectors_on_Bls12_377() -> Result<(), Error> {
+        test_compress_selectors::<
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compress_selectors_on_Bn254() -> Result<(), Error> {
+        test_compress_selectors::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_rerandomize_keys_on_Bls12_381() -> Result<(), Error> {
+        test_rerandomize_keys::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>(
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_rerandomize_keys_on_Bls12_377() -> Result<(), Error> {
+        test_rerandomize_keys::<Bls12_377, ark_ed_on_bls12_377::EdwardsParameters>(
+        )
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_rerandomize_keys_on_Bn254() -> Result<(), Error> {
+        test_rerandomize_keys::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
 }
@@ -0,0 +1,95 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Configuration of proof generation and verification.
+//!
+//! A [`ProverConfig`] is accepted by [`Circuit::gen_proof`] and
+//! [`CompiledCircuit::new`], and a [`VerifierConfig`] by [`verify_proof`] and
+//! its variants. Both are built from the label the transcript is initialized
+//! with, and further options are set with builder methods, so that new
+//! options don't change the signature of the functions accepting them.
+//!
+//! [`Circuit::gen_proof`]: crate::circuit::Circuit::gen_proof
+//! [`CompiledCircuit::new`]: crate::circuit::CompiledCircuit::new
+//! [`verify_proof`]: crate::circuit::verify_proof
+
+use crate::proof_system::{ProgressCallback, ProverProgress};
+use alloc::sync::Arc;
+
+/// Options of proof generation.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug)]
+pub struct ProverConfig {
+    /// Label the transcript is initialized with
+    transcript_init: Vec<u8>,
+
+    /// Callback notified of the progress of proof generation
+    #[derivative(Debug = "ignore")]
+    progress: Option<ProgressCallback>,
+}
+
+impl ProverConfig {
+    /// Creates a configuration initializing the transcript of the proof with
+    /// `transcript_init`.
+    pub fn new(transcript_init: &[u8]) -> Self {
+        Self {
+            transcript_init: transcript_init.to_vec(),
+            progress: None,
+        }
+    }
+
+    /// Sets a `callback` notified with the timing and overall progress of
+    /// each phase of proof generation, see
+    /// [`Prover::set_progress_callback`].
+    ///
+    /// [`Prover::set_progress_callback`]:
+    /// crate::proof_system::Prover::set_progress_callback
+    pub fn with_progress_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ProverProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Returns the label the transcript is initialized with.
+    pub fn transcript_init(&self) -> &[u8] {
+        &self.transcript_init
+    }
+
+    /// Returns the callback notified of the progress of proof generation.
+    pub fn progress_callback(&self) -> Option<&ProgressCallback> {
+        self.progress.as_ref()
+    }
+
+    /// Returns the [`VerifierConfig`] matching this configuration.
+    pub fn verifier_config(&self) -> VerifierConfig {
+        VerifierConfig::new(&self.transcript_init)
+    }
+}
+
+/// Options of proof verification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifierConfig {
+    /// Label the transcript is initialized with
+    transcript_init: Vec<u8>,
+}
+
+impl VerifierConfig {
+    /// Creates a configuration initializing the transcript of the verifier
+    /// with `transcript_init`, which must match the label the proof was
+    /// generated with.
+    pub fn new(transcript_init: &[u8]) -> Self {
+        Self {
+            transcript_init: transcript_init.to_vec(),
+        }
+    }
+
+    /// Returns the label the transcript is initialized with.
+    pub fn transcript_init(&self) -> &[u8] {
+        &self.transcript_init
+    }
+}
@@ -78,6 +78,7 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         if let Some(pi) = pi {
             assert!(self.public_inputs_sparse_store.insert(self.n, pi).is_none(),"The invariant of already having a PI inserted for this position should never exist");
@@ -157,6 +158,7 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         if let Some(pi) = pi {
             assert!(
@@ -223,6 +225,7 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         if let Some(pi) = pi {
             assert!(
@@ -290,9 +293,9 @@ where
         let q_o = -E::Fr::one();
 
         // Compute the output wire
-        let a_eval = self.variables[&a];
-        let b_eval = self.variables[&b];
-        let d_eval = self.variables[&d];
+        let a_eval = self.variables[a.0];
+        let b_eval = self.variables[b.0];
+        let d_eval = self.variables[d.0];
         let c_eval = (q_l * a_eval)
             + (q_r * b_eval)
             + (q_4 * d_eval)
@@ -360,9 +363,9 @@ where
         };
 
         // Compute output wire
-        let a_eval = self.variables[&a];
-        let b_eval = self.variables[&b];
-        let d_eval = self.variables[&d];
+        let a_eval = self.variables[a.0];
+        let b_eval = self.variables[b.0];
+        let d_eval = self.variables[d.0];
         let c_eval = (q_m * a_eval * b_eval)
             + (q_4 * d_eval)
             + q_c
@@ -402,9 +405,9 @@ where
         };
 
         // compute output wire
-        let a_eval = self.variables[&a];
-        let b_eval = self.variables[&b];
-        let d_eval = self.variables[&d];
+        let a_eval = self.variables[a.0];
+        let b_eval = self.variables[b.0];
+        let d_eval = self.variables[d.0];
 
         let c_eval = (q_m * a_eval * b_eval)
             + (q_l * a_eval)
@@ -429,6 +432,172 @@ where
             pi,
         )
     }
+
+    /// Adds a [`StandardComposer::big_arith`] gate for each pair of `a` and
+    /// `b`, with the same coefficients, and returns their outputs.
+    ///
+    /// Equation: `(a[i]*b[i])*q_m + a[i]*q_l + b[i]*q_r + q_c = c[i]`
+    ///
+    /// The vectors of the composer are grown once for all the gates, which
+    /// makes this cheaper than adding them one by one for large batches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` and `b` have different lengths.
+    pub fn big_arith_batch(
+        &mut self,
+        q_m: E::Fr,
+        a: &[Variable],
+        b: &[Variable],
+        q_l: E::Fr,
+        q_r: E::Fr,
+        q_c: E::Fr,
+    ) -> Vec<Variable> {
+        assert_eq!(a.len(), b.len(), "the batches must have the same length");
+
+        let c_evals: Vec<_> = a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| {
+                let a_eval = self.variables[a.0];
+                let b_eval = self.variables[b.0];
+                (q_m * a_eval * b_eval) + (q_l * a_eval) + (q_r * b_eval) + q_c
+            })
+            .collect();
+        let c = self.add_inputs(&c_evals);
+
+        self.reserve_gates(c.len());
+        for ((a, b), c) in a.iter().zip(b).zip(&c) {
+            self.big_arith_gate(
+                *a,
+                *b,
+                *c,
+                None,
+                q_m,
+                q_l,
+                q_r,
+                -E::Fr::one(),
+                q_c,
+                E::Fr::zero(),
+                None,
+            );
+        }
+        c
+    }
+
+    /// Adds a width-4 arithmetic gate whose result is placed on the left
+    /// wire of the next gate instead of its own output wire, computing &
+    /// returning the result [`Variable`].
+    ///
+    /// This is a hint for sequential computations such as hash chains, where
+    /// the result of each gate is the left input of the following one.
+    /// Instead of linking both wires with a copy constraint, the gate reads
+    /// its result from the next row of the circuit. The returned [`Variable`]
+    /// **must** be used as the left input of the very next gate added to the
+    /// composer, otherwise the preprocessing fails with
+    /// [`Error::LookaheadWireMismatch`](crate::error::Error::LookaheadWireMismatch).
+    ///
+    /// Equation: `(a*b)*q_m + a*q_l + b*q_r + d*q_4 + q_c + PI = a_next`
+    /// ### Returns
+    /// `a_next`
+    pub fn big_arith_chained(
+        &mut self,
+        q_m: E::Fr,
+        a: Variable,
+        b: Variable,
+        q_l: E::Fr,
+        q_r: E::Fr,
+        q_4_d: Option<(E::Fr, Variable)>,
+        q_c: E::Fr,
+        pi: Option<E::Fr>,
+    ) -> Variable {
+        // check if advice wire is available
+        let (q_4, d) = match q_4_d {
+            Some((q_4, d)) => (q_4, d),
+            None => (E::Fr::zero(), self.zero_var),
+        };
+
+        // compute the left wire of the next gate
+        let a_eval = self.variables[a.0];
+        let b_eval = self.variables[b.0];
+        let d_eval = self.variables[d.0];
+
+        let a_next_eval = (q_m * a_eval * b_eval)
+            + (q_l * a_eval)
+            + (q_r * b_eval)
+            + (q_4 * d_eval)
+            + q_c
+            + pi.unwrap_or_default();
+
+        let a_next = self.add_input(a_next_eval);
+
+        // The output wire is unused
+        self.w_l.push(a);
+        self.w_r.push(b);
+        self.w_o.push(self.zero_var);
+        self.w_4.push(d);
+
+        // Add selector vectors
+        self.q_m.push(q_m);
+        self.q_o.push(E::Fr::zero());
+        self.q_c.push(q_c);
+        self.q_4.push(q_4);
+        self.q_l.push(q_l);
+        self.q_r.push(q_r);
+        self.q_arith.push(E::Fr::one());
+
+        self.q_range.push(E::Fr::zero());
+        self.q_logic.push(E::Fr::zero());
+        self.q_fixed_group_add.push(E::Fr::zero());
+        self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(-E::Fr::one());
+
+        if let Some(pi) = pi {
+            assert!(
+                self.public_inputs_sparse_store.insert(self.n, pi).is_none(),"The invariant of already having a PI inserted for this position should never exist"
+            );
+        }
+
+        self.perm
+            .add_variables_to_map(a, b, self.zero_var, d, self.n);
+        self.lookahead_wires.insert(self.n, a_next);
+
+        self.n += 1;
+
+        a_next
+    }
+
+    /// Returns a [`Variable`] holding the sum of `constant` and of the
+    /// scaled `terms`, constrained by a [`StandardComposer::big_add`] with
+    /// three of them followed by one for every two others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `terms` is empty.
+    pub(crate) fn linear_combination(
+        &mut self,
+        terms: &[(E::Fr, Variable)],
+        constant: E::Fr,
+    ) -> Variable {
+        let zero = (E::Fr::zero(), self.zero_var);
+        let mut acc = self.big_add(
+            terms[0],
+            terms.get(1).copied().unwrap_or(zero),
+            terms.get(2).copied(),
+            constant,
+            None,
+        );
+        for pair in terms.get(3..).unwrap_or_default().chunks(2) {
+            acc = self.big_add(
+                (E::Fr::one(), acc),
+                pair[0],
+                pair.get(1).copied(),
+                E::Fr::zero(),
+                None,
+            );
+        }
+        acc
+    }
 }
 
 #[cfg(test)]
@@ -436,8 +605,10 @@ mod test {
     use super::*;
     use crate::batch_test;
     use crate::constraint_system::helper::*;
+    use crate::error::Error;
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
 
     fn test_public_inputs<E, P>()
     where
@@ -727,6 +898,195 @@ mod test {
         assert!(res.is_err());
     }
 
+    fn test_correct_chained_gates<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                // Iterate x -> x^2 + 3 * y + 5 starting from x = 2
+                let mut x = composer.add_input(E::Fr::from(2u64));
+                let y = composer.add_input(E::Fr::from(7u64));
+                let mut expected = E::Fr::from(2u64);
+                for _ in 0..10 {
+                    x = composer.big_arith_chained(
+                        E::Fr::one(),
+                        x,
+                        x,
+                        E::Fr::zero(),
+                        E::Fr::zero(),
+                        Some((E::Fr::from(3u64), y)),
+                        E::Fr::from(5u64),
+                        None,
+                    );
+                    expected = expected * expected
+                        + E::Fr::from(21u64)
+                        + E::Fr::from(5u64);
+                }
+                // Intermediate results appear on a single wire each
+                assert_eq!(composer.lookahead_wires.len(), 10);
+                composer.constrain_to_constant(x, expected, None);
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_incorrect_chained_gates<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        // The result of a chained gate must be the left wire of the next gate
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let two = composer.add_input(E::Fr::from(2u64));
+                let four = composer.big_arith_chained(
+                    E::Fr::one(),
+                    two,
+                    two,
+                    E::Fr::zero(),
+                    E::Fr::zero(),
+                    None,
+                    E::Fr::zero(),
+                    None,
+                );
+                composer.constrain_to_constant(two, E::Fr::from(2u64), None);
+                composer.constrain_to_constant(four, E::Fr::from(4u64), None);
+            },
+            200,
+        );
+        assert!(matches!(
+            res,
+            Err(Error::LookaheadWireMismatch { gate_index: 3 })
+        ));
+
+        // A wrong result doesn't satisfy the gate
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let two = composer.add_input(E::Fr::from(2u64));
+                let four = composer.big_arith_chained(
+                    E::Fr::one(),
+                    two,
+                    two,
+                    E::Fr::zero(),
+                    E::Fr::zero(),
+                    None,
+                    E::Fr::zero(),
+                    None,
+                );
+                composer.variables[four.0] = E::Fr::from(5u64);
+                composer.constrain_to_constant(four, E::Fr::from(5u64), None);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_big_arith_batch<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                // Compute a*(a+1) + 2*a + 3 for every a in 0..16
+                let values: Vec<_> = (0..17u64).map(E::Fr::from).collect();
+                let inputs = composer.add_inputs(&values);
+                let outputs = composer.big_arith_batch(
+                    E::Fr::one(),
+                    &inputs[..16],
+                    &inputs[1..],
+                    E::Fr::from(2u64),
+                    E::Fr::zero(),
+                    E::Fr::from(3u64),
+                );
+                assert_eq!(outputs.len(), 16);
+                for (a, output) in (0..16u64).zip(outputs) {
+                    composer.constrain_to_constant(
+                        output,
+                        E::Fr::from(a * (a + 1) + 2 * a + 3),
+                        None,
+                    );
+                }
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let inputs = composer
+                    .add_inputs(&[E::Fr::from(2u64), E::Fr::from(3u64)]);
+                let outputs = composer.big_arith_batch(
+                    E::Fr::one(),
+                    &inputs,
+                    &inputs,
+                    E::Fr::zero(),
+                    E::Fr::zero(),
+                    E::Fr::zero(),
+                );
+                composer.variables[outputs[1].0] = E::Fr::from(10u64);
+                composer.constrain_to_constant(
+                    outputs[1],
+                    E::Fr::from(10u64),
+                    None,
+                );
+                dummy_gadget(8, composer);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_linear_combination<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                // Sum 7 and 1*1 + 2*2 + ... + n*n for n in 1..=6
+                for n in 1..=6u64 {
+                    let terms: Vec<_> = (1..=n)
+                        .map(|i| {
+                            let value = composer.add_input(E::Fr::from(i));
+                            (E::Fr::from(i), value)
+                        })
+                        .collect();
+                    let gates = composer.circuit_size();
+                    let sum =
+                        composer.linear_combination(&terms, E::Fr::from(7u64));
+                    // Three terms in the first gate, two in the following
+                    assert_eq!(
+                        composer.circuit_size() - gates,
+                        1 + (n.saturating_sub(3) as usize).div_ceil(2)
+                    );
+                    composer.constrain_to_constant(
+                        sum,
+                        E::Fr::from(7 + n * (n + 1) * (2 * n + 1) / 6),
+                        None,
+                    );
+                }
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let terms: Vec<_> = (1..=5u64)
+                    .map(|i| (E::Fr::one(), composer.add_input(E::Fr::from(i))))
+                    .collect();
+                let sum = composer.linear_combination(&terms, E::Fr::zero());
+                composer.constrain_to_constant(sum, E::Fr::from(16u64), None);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
     // Bls12-381 tests
     batch_test!(
         [
@@ -736,7 +1096,11 @@ mod test {
             test_correct_big_add_mul_gate,
             test_correct_big_arith_gate,
             test_incorrect_add_mul_gate,
-            test_incorrect_big_arith_gate
+            test_incorrect_big_arith_gate,
+            test_correct_chained_gates,
+            test_incorrect_chained_gates,
+            test_big_arith_batch,
+            test_linear_combination
         ],
         [] => (
             Bls12_381,
@@ -753,11 +1117,36 @@ mod test {
             test_correct_big_add_mul_gate,
             test_correct_big_arith_gate,
             test_incorrect_add_mul_gate,
-            test_incorrect_big_arith_gate
+            test_incorrect_big_arith_gate,
+            test_correct_chained_gates,
+            test_incorrect_chained_gates,
+            test_big_arith_batch,
+            test_linear_combination
         ],
         [] => (
             Bls12_377,
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Bn254 tests
+    batch_test!(
+        [
+            test_public_inputs,
+            test_correct_add_mul_gate,
+            test_correct_add_gate,
+            test_correct_big_add_mul_gate,
+            test_correct_big_arith_gate,
+            test_incorrect_add_mul_gate,
+            test_incorrect_big_arith_gate,
+            test_correct_chained_gates,
+            test_incorrect_chained_gates,
+            test_big_arith_batch,
+            test_linear_combination
+        ],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
@@ -6,7 +6,7 @@
 
 //! Boolean Gates
 
-use crate::constraint_system::{StandardComposer, Variable};
+use crate::constraint_system::{BoolVariable, StandardComposer, Variable};
 use ark_ec::{PairingEngine, TEModelParameters};
 use num_traits::{One, Zero};
 
@@ -22,7 +22,10 @@ where
     /// Note that using this constraint with whatever [`Variable`] that is not
     /// representing a value equalling 0 or 1, will always force the equation to
     /// fail.
-    pub fn boolean_gate(&mut self, a: Variable) -> Variable {
+    ///
+    /// Returns the [`BoolVariable`] wrapping `a`, which the gadgets expecting
+    /// a bit take instead of a plain [`Variable`].
+    pub fn boolean_gate(&mut self, a: Variable) -> BoolVariable {
         self.w_l.push(a);
         self.w_r.push(a);
         self.w_o.push(a);
@@ -40,13 +43,21 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         self.perm
             .add_variables_to_map(a, a, a, self.zero_var, self.n);
 
         self.n += 1;
 
-        a
+        BoolVariable(a)
+    }
+
+    /// Adds a witness holding `value`, constrained by a
+    /// [`StandardComposer::boolean_gate`].
+    pub fn add_bool(&mut self, value: bool) -> BoolVariable {
+        let var = self.add_input(E::Fr::from(value as u64));
+        self.boolean_gate(var)
     }
 }
 
@@ -57,6 +68,7 @@ mod test {
     use crate::constraint_system::helper::*;
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use num_traits::One;
 
     fn test_correct_bool_gate<E, P>()
@@ -70,6 +82,8 @@ mod test {
                 let one = composer.add_input(E::Fr::one());
                 composer.boolean_gate(zero);
                 composer.boolean_gate(one);
+                let bit = composer.add_bool(true);
+                composer.assert_equal(bit.variable(), one);
             },
             32,
         );
@@ -116,4 +130,16 @@ mod test {
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Test for Bn254
+    batch_test!(
+        [
+            test_correct_bool_gate,
+            test_incorrect_bool_gate
+        ],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
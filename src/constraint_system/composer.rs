@@ -14,16 +14,21 @@
 //! It allows us not only to build Add and Mul constraints but also to build
 //! ECC op. gates, Range checks, Logical gates (Bitwise ops) etc.
 
-use crate::constraint_system::Variable;
+use crate::circuit::PublicInputKind;
+use crate::constraint_system::{BoolVariable, Variable};
 use crate::permutation::Permutation;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use ark_ec::models::TEModelParameters;
 use ark_ec::PairingEngine;
 #[cfg(feature = "trace")]
 use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::CanonicalSerialize;
 use core::marker::PhantomData;
 use hashbrown::HashMap;
+use merlin::Transcript;
 use num_traits::{One, Zero};
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// The StandardComposer is the circuit-builder tool that the `dusk-plonk`
 /// repository provides so that circuit descriptions can be written, stored and
@@ -83,11 +88,22 @@ where
     pub(crate) q_fixed_group_add: Vec<E::Fr>,
     /// Variable base group addition selector
     pub(crate) q_variable_group_add: Vec<E::Fr>,
+    /// Lookahead selector, scaling the left wire of the next gate
+    pub(crate) q_lookahead: Vec<E::Fr>,
 
     /// Sparse representation of the Public Inputs linking the positions of the
     /// non-zero ones to it's actual values.
     pub(crate) public_inputs_sparse_store: BTreeMap<usize, E::Fr>,
 
+    /// Positions of the public inputs holding the x coordinate of a public
+    /// point, whose y coordinate is the public input that follows.
+    pub(crate) public_points: BTreeSet<usize>,
+
+    /// Results of the chained gates, indexed by the position of the gate
+    /// that computes them. Each of them must be placed on the left wire of
+    /// the gate that follows.
+    pub(crate) lookahead_wires: BTreeMap<usize, Variable>,
+
     // Witness vectors
     /// Left wire witness vector.
     pub(crate) w_l: Vec<Variable>,
@@ -104,8 +120,12 @@ where
     /// wire to be the variable that references zero
     pub(crate) zero_var: Variable,
 
-    /// These are the actual variable values.
-    pub(crate) variables: HashMap<Variable, E::Fr>,
+    /// These are the actual variable values, indexed by [`Variable`].
+    pub(crate) variables: Vec<E::Fr>,
+
+    /// Variables constrained to a constant by
+    /// [`StandardComposer::add_constant`], indexed by their value.
+    pub(crate) constants: HashMap<E::Fr, Variable>,
 
     /// Permutation argument.
     pub(crate) perm: Permutation<E::Fr>,
@@ -143,6 +163,232 @@ where
         // or Iterator.
         self.public_inputs_sparse_store.keys().copied().collect()
     }
+
+    /// Returns the kinds of the Public Inputs of this Composer instance, in
+    /// the order of their positions.
+    ///
+    /// The public points added by
+    /// [`StandardComposer::assert_equal_public_point`] span two positions,
+    /// while every other public input is a scalar.
+    pub fn public_input_kinds(&self) -> Vec<PublicInputKind> {
+        let mut kinds = Vec::new();
+        let mut positions = self.public_inputs_sparse_store.keys();
+        while let Some(position) = positions.next() {
+            if self.public_points.contains(position) {
+                positions.next();
+                kinds.push(PublicInputKind::Point);
+            } else {
+                kinds.push(PublicInputKind::Scalar);
+            }
+        }
+        kinds
+    }
+
+    /// Returns the `(position, value)` pairs of the Public Inputs of this
+    /// Composer instance, ordered by position.
+    ///
+    /// The composer stores the public inputs negated, as they appear in the
+    /// gates, while the values returned here are the ones expected by the
+    /// verifier, for instance through
+    /// [`PublicInputs::from_pairs`](crate::circuit::PublicInputs::from_pairs).
+    pub fn public_input_values(&self) -> Vec<(usize, E::Fr)> {
+        self.public_inputs_sparse_store
+            .iter()
+            .map(|(position, value)| (*position, -*value))
+            .collect()
+    }
+
+    /// Returns a domain-separated 32-byte digest of the values of every
+    /// witness [`Variable`] and public input of the composer.
+    ///
+    /// Two composers filled by the same gadget with the same inputs have the
+    /// same digest, which makes it suitable to identify proving requests.
+    pub fn witness_digest(&self) -> [u8; 32] {
+        let mut transcript = Transcript::new(b"ark-plonk-witness");
+        let mut bytes = Vec::new();
+        for value in &self.variables {
+            bytes.clear();
+            value
+                .serialize(&mut bytes)
+                .expect("serialization into a vector can't fail");
+            transcript.append_message(b"witness", &bytes);
+        }
+        for (position, value) in &self.public_inputs_sparse_store {
+            bytes.clear();
+            value
+                .serialize(&mut bytes)
+                .expect("serialization into a vector can't fail");
+            transcript.append_u64(b"pi_position", *position as u64);
+            transcript.append_message(b"pi", &bytes);
+        }
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"digest", &mut digest);
+        digest
+    }
+
+    /// Records the current state of the composer, so that the gates,
+    /// [`Variable`]s and public inputs added afterwards can be discarded with
+    /// [`StandardComposer::rollback`].
+    ///
+    /// This allows a gadget to try an encoding, measure its gate count and
+    /// fall back to another one.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            gates: self.n,
+            variables: self.variables.len(),
+        }
+    }
+
+    /// Reverts the composer to the state recorded by `checkpoint`, removing
+    /// every gate, [`Variable`] and public input added after it.
+    ///
+    /// The [`Variable`]s created after the checkpoint must not be used once
+    /// the composer is rolled back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the composer has already been rolled back past
+    /// `checkpoint`.
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        let Checkpoint { gates, variables } = checkpoint;
+        assert!(
+            gates <= self.n && variables <= self.variables.len(),
+            "the composer has been rolled back past the checkpoint"
+        );
+
+        self.n = gates;
+        for selector in [
+            &mut self.q_m,
+            &mut self.q_l,
+            &mut self.q_r,
+            &mut self.q_o,
+            &mut self.q_4,
+            &mut self.q_c,
+            &mut self.q_arith,
+            &mut self.q_range,
+            &mut self.q_logic,
+            &mut self.q_fixed_group_add,
+            &mut self.q_variable_group_add,
+            &mut self.q_lookahead,
+        ] {
+            selector.truncate(gates);
+        }
+        for wire in [&mut self.w_l, &mut self.w_r, &mut self.w_o, &mut self.w_4]
+        {
+            wire.truncate(gates);
+        }
+
+        self.public_inputs_sparse_store.split_off(&gates);
+        self.public_points.split_off(&gates);
+        self.lookahead_wires.split_off(&gates);
+
+        self.variables.truncate(variables);
+        self.constants.retain(|_, var| var.0 < variables);
+        self.perm.truncate(variables, gates);
+    }
+
+    /// Appends the gates of `other` after the ones of the composer, and
+    /// returns the [`Variable`]s of the composer that `variables` of `other`
+    /// turned into.
+    ///
+    /// The [`Variable`]s of `other` are added as new ones, except for its
+    /// zero variable which is replaced by the one of the composer, and the
+    /// positions of its public inputs are shifted by the size of the
+    /// composer. This allows gadgets to be built in composers of their own,
+    /// and then linked to the rest of the circuit, for instance with
+    /// [`StandardComposer::assert_equal`], through the returned
+    /// [`Variable`]s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of `variables` doesn't belong to `other`.
+    pub fn append(
+        &mut self,
+        other: StandardComposer<E, P>,
+        variables: &[Variable],
+    ) -> Vec<Variable> {
+        let offset = self.n;
+        let remapped: Vec<Variable> = other
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                if Variable(index) == other.zero_var {
+                    self.zero_var
+                } else {
+                    self.add_input(*value)
+                }
+            })
+            .collect();
+        let remap = |var: &Variable| remapped[var.0];
+
+        for (selector, other) in [
+            (&mut self.q_m, &other.q_m),
+            (&mut self.q_l, &other.q_l),
+            (&mut self.q_r, &other.q_r),
+            (&mut self.q_o, &other.q_o),
+            (&mut self.q_4, &other.q_4),
+            (&mut self.q_c, &other.q_c),
+            (&mut self.q_arith, &other.q_arith),
+            (&mut self.q_range, &other.q_range),
+            (&mut self.q_logic, &other.q_logic),
+            (&mut self.q_fixed_group_add, &other.q_fixed_group_add),
+            (&mut self.q_variable_group_add, &other.q_variable_group_add),
+            (&mut self.q_lookahead, &other.q_lookahead),
+        ] {
+            selector.extend_from_slice(other);
+        }
+        for (wire, other) in [
+            (&mut self.w_l, &other.w_l),
+            (&mut self.w_r, &other.w_r),
+            (&mut self.w_o, &other.w_o),
+            (&mut self.w_4, &other.w_4),
+        ] {
+            wire.extend(other.iter().map(remap));
+        }
+
+        self.public_inputs_sparse_store.extend(
+            other
+                .public_inputs_sparse_store
+                .iter()
+                .map(|(pos, value)| (pos + offset, *value)),
+        );
+        self.public_points
+            .extend(other.public_points.iter().map(|pos| pos + offset));
+        self.lookahead_wires.extend(
+            other
+                .lookahead_wires
+                .iter()
+                .map(|(pos, var)| (pos + offset, remap(var))),
+        );
+        for (value, var) in &other.constants {
+            self.constants.entry(*value).or_insert_with(|| remap(var));
+        }
+
+        self.n += other.n;
+        for i in offset..self.n {
+            self.perm.add_variables_to_map(
+                self.w_l[i],
+                self.w_r[i],
+                self.w_o[i],
+                self.w_4[i],
+                i,
+            );
+        }
+
+        variables.iter().map(remap).collect()
+    }
+}
+
+/// State of a [`StandardComposer`] recorded by
+/// [`StandardComposer::checkpoint`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Checkpoint {
+    /// Number of gates of the composer
+    gates: usize,
+
+    /// Number of variables of the composer
+    variables: usize,
 }
 
 impl<E, P> Default for StandardComposer<E, P>
@@ -156,6 +402,38 @@ where
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<E, P> Zeroize for StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Overwrites the values of the variables, which hold the witness. The
+    /// gates are kept, but no longer satisfied.
+    fn zeroize(&mut self) {
+        self.variables.iter_mut().zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E, P> Drop for StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<E, P> ZeroizeOnDrop for StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+}
+
 impl<E, P> StandardComposer<E, P>
 where
     E: PairingEngine,
@@ -184,6 +462,21 @@ where
         var
     }
 
+    /// Returns a [`Variable`] fixed to `value` as a part of the circuit
+    /// description, like
+    /// [`StandardComposer::add_witness_to_circuit_description`].
+    ///
+    /// Constants are interned: only the first call for a given `value` adds
+    /// a gate, and the following ones return the same [`Variable`].
+    pub fn add_constant(&mut self, value: E::Fr) -> Variable {
+        if let Some(var) = self.constants.get(&value) {
+            return *var;
+        }
+        let var = self.add_witness_to_circuit_description(value);
+        self.constants.insert(value, var);
+        var
+    }
+
     /// Creates a new circuit with an expected circuit size.
     /// This will allow for less reallocations when building the circuit
     /// since the `Vec`s will already have an appropriate allocation at the
@@ -202,13 +495,17 @@ where
             q_logic: Vec::with_capacity(expected_size),
             q_fixed_group_add: Vec::with_capacity(expected_size),
             q_variable_group_add: Vec::with_capacity(expected_size),
+            q_lookahead: Vec::with_capacity(expected_size),
             public_inputs_sparse_store: BTreeMap::new(),
+            public_points: BTreeSet::new(),
+            lookahead_wires: BTreeMap::new(),
             w_l: Vec::with_capacity(expected_size),
             w_r: Vec::with_capacity(expected_size),
             w_o: Vec::with_capacity(expected_size),
             w_4: Vec::with_capacity(expected_size),
             zero_var: Variable(0),
-            variables: HashMap::with_capacity(expected_size),
+            variables: Vec::with_capacity(expected_size),
+            constants: HashMap::new(),
             perm: Permutation::new(),
             __: PhantomData,
         };
@@ -216,6 +513,7 @@ where
         // Reserve the first variable to be zero
         composer.zero_var =
             composer.add_witness_to_circuit_description(E::Fr::zero());
+        composer.constants.insert(E::Fr::zero(), composer.zero_var);
 
         // Add dummy constraints
         composer.add_dummy_constraints();
@@ -239,12 +537,48 @@ where
         // Get a new Variable from the permutation
         let var = self.perm.new_variable();
         // The composer now links the E::Fr to the Variable returned from
-        // the Permutation
-        self.variables.insert(var, s);
+        // the Permutation, whose index is the position of its value
+        debug_assert_eq!(var.0, self.variables.len());
+        self.variables.push(s);
 
         var
     }
 
+    /// Adds a [`Variable`] for each of `values`, like
+    /// [`StandardComposer::add_input`], and returns them in the same order.
+    ///
+    /// The variable maps are grown once for all the values, which avoids
+    /// rehashing them repeatedly when loading large witnesses.
+    pub fn add_inputs(&mut self, values: &[E::Fr]) -> Vec<Variable> {
+        self.variables.reserve(values.len());
+        self.perm.reserve(values.len());
+        values.iter().map(|value| self.add_input(*value)).collect()
+    }
+
+    /// Reserves capacity for at least `additional` more gates.
+    pub(crate) fn reserve_gates(&mut self, additional: usize) {
+        for selector in [
+            &mut self.q_m,
+            &mut self.q_l,
+            &mut self.q_r,
+            &mut self.q_o,
+            &mut self.q_4,
+            &mut self.q_c,
+            &mut self.q_arith,
+            &mut self.q_range,
+            &mut self.q_logic,
+            &mut self.q_fixed_group_add,
+            &mut self.q_variable_group_add,
+            &mut self.q_lookahead,
+        ] {
+            selector.reserve(additional);
+        }
+        for wire in [&mut self.w_l, &mut self.w_r, &mut self.w_o, &mut self.w_4]
+        {
+            wire.reserve(additional);
+        }
+    }
+
     /// Adds a width-3 poly gate.
     /// This gate gives total freedom to the end user to implement the
     /// corresponding circuits in the most optimized way possible because
@@ -284,6 +618,7 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         if let Some(pi) = pi {
             assert!(self
@@ -322,6 +657,19 @@ where
         );
     }
 
+    /// Exposes the current value of `var` as a public input, and returns the
+    /// position of the gate holding it.
+    ///
+    /// Like the public inputs of [`StandardComposer::constrain_to_constant`],
+    /// the public input is the negated value of `var`, so that
+    /// `var - value = 0`.
+    pub fn set_public(&mut self, var: Variable) -> usize {
+        let position = self.n;
+        let value = self.variables[var.0];
+        self.constrain_to_constant(var, E::Fr::zero(), Some(-value));
+        position
+    }
+
     /// Add a constraint into the circuit description that states that two
     /// [`Variable`]s are equal.
     pub fn assert_equal(&mut self, a: Variable, b: Variable) {
@@ -344,16 +692,15 @@ where
     /// bit == 1 => choice_a,
     /// bit == 0 => choice_b,
     ///
-    /// # Note
-    /// The `bit` used as input which is a [`Variable`] should had previously
-    /// been constrained to be either 1 or 0 using a bool constrain. See:
-    /// [`StandardComposer::boolean_gate`].
+    /// The `bit` is a [`BoolVariable`], constrained to be either `1` or `0`
+    /// by [`StandardComposer::boolean_gate`].
     pub fn conditional_select(
         &mut self,
-        bit: Variable,
+        bit: BoolVariable,
         choice_a: Variable,
         choice_b: Variable,
     ) -> Variable {
+        let bit = bit.variable();
         // bit * choice_a
         let bit_times_a =
             self.mul(E::Fr::one(), bit, choice_a, E::Fr::zero(), None);
@@ -384,15 +731,14 @@ where
     /// bit == 1 => value,
     /// bit == 0 => 0,
     ///
-    /// # Note
-    /// The `bit` used as input which is a [`Variable`] should had previously
-    /// been constrained to be either 1 or 0 using a bool constrain. See:
-    /// [`StandardComposer::boolean_gate`].
+    /// The `bit` is a [`BoolVariable`], constrained to be either `1` or `0`
+    /// by [`StandardComposer::boolean_gate`].
     pub fn conditional_select_zero(
         &mut self,
-        bit: Variable,
+        bit: BoolVariable,
         value: Variable,
     ) -> Variable {
+        let bit = bit.variable();
         // returns bit * value
         self.mul(E::Fr::one(), bit, value, E::Fr::zero(), None)
     }
@@ -402,17 +748,16 @@ where
     /// bit == 1 => value,
     /// bit == 0 => 1,
     ///
-    /// # Note
-    /// The `bit` used as input which is a [`Variable`] should had previously
-    /// been constrained to be either 1 or 0 using a bool constrain. See:
-    /// [`StandardComposer::boolean_gate`].
+    /// The `bit` is a [`BoolVariable`], constrained to be either `1` or `0`
+    /// by [`StandardComposer::boolean_gate`].
     pub fn conditional_select_one(
         &mut self,
-        bit: Variable,
+        bit: BoolVariable,
         value: Variable,
     ) -> Variable {
-        let value_scalar = self.variables.get(&value).unwrap();
-        let bit_scalar = self.variables.get(&bit).unwrap();
+        let bit = bit.variable();
+        let value_scalar = &self.variables[value.0];
+        let bit_scalar = &self.variables[bit.0];
 
         let f_x_scalar =
             E::Fr::one() - bit_scalar + (*bit_scalar * value_scalar);
@@ -449,6 +794,7 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
         let var_six = self.add_input(E::Fr::from(6u64));
         let var_one = self.add_input(E::Fr::from(1u64));
         let var_seven = self.add_input(E::Fr::from(7u64));
@@ -478,6 +824,7 @@ where
         self.q_logic.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
         self.w_l.push(var_min_twenty);
         self.w_r.push(var_six);
         self.w_o.push(var_seven);
@@ -508,22 +855,22 @@ where
         let w_l: Vec<&E::Fr> = self
             .w_l
             .iter()
-            .map(|w_l_i| self.variables.get(w_l_i).unwrap())
+            .map(|w_l_i| &self.variables[w_l_i.0])
             .collect();
         let w_r: Vec<&E::Fr> = self
             .w_r
             .iter()
-            .map(|w_r_i| self.variables.get(w_r_i).unwrap())
+            .map(|w_r_i| &self.variables[w_r_i.0])
             .collect();
         let w_o: Vec<&E::Fr> = self
             .w_o
             .iter()
-            .map(|w_o_i| self.variables.get(w_o_i).unwrap())
+            .map(|w_o_i| &self.variables[w_o_i.0])
             .collect();
         let w_4: Vec<&E::Fr> = self
             .w_4
             .iter()
-            .map(|w_4_i| self.variables.get(w_4_i).unwrap())
+            .map(|w_4_i| &self.variables[w_4_i.0])
             .collect();
         // Computes f(f-1)(f-2)(f-3)
         let delta = |f: E::Fr| -> E::Fr {
@@ -548,6 +895,7 @@ where
             let qfixed = self.q_fixed_group_add[i];
             #[cfg(all(feature = "trace-print", feature = "std"))]
             let qvar = self.q_variable_group_add[i];
+            let qlookahead = self.q_lookahead[i];
             let pi = pi_vec[i];
 
             let a = w_l[i];
@@ -604,6 +952,7 @@ where
                         + (qr * b)
                         + (qo * c)
                         + (q4 * d)
+                        + (qlookahead * a_next)
                         + pi
                         + qc)
                     + qlogic
@@ -670,6 +1019,8 @@ mod test {
     use crate::prelude::Verifier;
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_bw6_761::BW6_761;
     use ark_poly::univariate::DensePolynomial;
     use ark_poly_commit::kzg10::{self, Powers, UniversalParams, KZG10};
     use ark_poly_commit::sonic_pc::SonicKZG10;
@@ -710,8 +1061,8 @@ mod test {
     {
         let res = gadget_tester(
             |composer: &mut StandardComposer<E, P>| {
-                let bit_1 = composer.add_input(E::Fr::one());
-                let bit_0 = composer.zero_var();
+                let bit_1 = composer.add_bool(true);
+                let bit_0 = composer.add_bool(false);
 
                 let choice_a = composer.add_input(E::Fr::from(10u64));
                 let choice_b = composer.add_input(E::Fr::from(20u64));
@@ -729,6 +1080,231 @@ mod test {
         assert!(res.is_ok(), "{:?}", res.err().unwrap());
     }
 
+    fn test_checkpoint_rollback<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let gadget = |composer: &mut StandardComposer<E, P>| {
+            let a = composer.add_input(E::Fr::from(3u64));
+            composer.constrain_to_constant(
+                a,
+                E::Fr::zero(),
+                Some(-E::Fr::from(3u64)),
+            );
+            a
+        };
+
+        let mut composer = StandardComposer::<E, P>::new();
+        let a = gadget(&mut composer);
+
+        // Try an encoding of the range check and discard it
+        let checkpoint = composer.checkpoint();
+        let b = composer.add_input(E::Fr::from(9u64));
+        composer.constrain_to_constant(
+            b,
+            E::Fr::zero(),
+            Some(-E::Fr::from(9u64)),
+        );
+        composer.range_gate(a, 8);
+        let speculative_size = composer.circuit_size();
+        composer.rollback(checkpoint);
+        assert!(speculative_size > composer.circuit_size());
+
+        // The composer is left as if the encoding was never tried
+        let mut expected = StandardComposer::<E, P>::new();
+        gadget(&mut expected);
+        assert_eq!(composer.circuit_size(), expected.circuit_size());
+        assert_eq!(composer.pi_positions(), expected.pi_positions());
+        assert_eq!(composer.witness_digest(), expected.witness_digest());
+        assert_eq!(composer.checkpoint(), expected.checkpoint());
+
+        // The circuit can still be extended and proven after the rollback
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(3u64));
+                let checkpoint = composer.checkpoint();
+                let b = composer.add_input(E::Fr::from(5u64));
+                composer.range_gate(b, 16);
+                composer.rollback(checkpoint);
+                composer.range_gate(a, 4);
+            },
+            64,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
+    fn test_append<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        // Squares a witness and exposes the square as a public input
+        fn square<E, P>(value: u64) -> (StandardComposer<E, P>, Variable)
+        where
+            E: PairingEngine,
+            P: TEModelParameters<BaseField = E::Fr>,
+        {
+            let mut composer = StandardComposer::<E, P>::new();
+            let x = composer.add_input(E::Fr::from(value));
+            let y = composer.mul(E::Fr::one(), x, x, E::Fr::zero(), None);
+            composer.constrain_to_constant(
+                y,
+                E::Fr::zero(),
+                Some(-E::Fr::from(value * value)),
+            );
+            composer.add_constant(E::Fr::from(value));
+            (composer, x)
+        }
+
+        let mut composer = StandardComposer::<E, P>::new();
+        composer.add_input(E::Fr::from(3u64));
+        let size = composer.circuit_size();
+        let (gadget, x) = square(3);
+        let gadget_size = gadget.circuit_size();
+        let gadget_pi_positions = gadget.pi_positions();
+        let remapped = composer.append(gadget, &[x]);
+        assert_eq!(composer.circuit_size(), size + gadget_size);
+        assert_eq!(
+            composer.pi_positions(),
+            gadget_pi_positions
+                .iter()
+                .map(|pos| pos + size)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(composer.variables[remapped[0].0], E::Fr::from(3u64));
+
+        // The constants of the gadget are interned in the composer
+        let checkpoint = composer.checkpoint();
+        composer.add_constant(E::Fr::from(3u64));
+        assert_eq!(composer.checkpoint(), checkpoint);
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(3u64));
+                let (gadget, x) = square(3);
+                let x = composer.append(gadget, &[x])[0];
+                composer.assert_equal(a, x);
+                let (gadget, x) = square(5);
+                composer.append(gadget, &[x]);
+                dummy_gadget(8, composer);
+            },
+            64,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        // The appended gates are still checked once linked
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(4u64));
+                let (gadget, x) = square(3);
+                let x = composer.append(gadget, &[x])[0];
+                composer.assert_equal(a, x);
+                dummy_gadget(8, composer);
+            },
+            64,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_set_public<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut composer = StandardComposer::<E, P>::new();
+        let a = composer.add_input(E::Fr::from(3u64));
+        let b = composer.mul(E::Fr::one(), a, a, E::Fr::zero(), None);
+        let position = composer.set_public(b);
+        assert_eq!(position, composer.circuit_size() - 1);
+        assert_eq!(composer.pi_positions(), vec![position]);
+        assert_eq!(
+            composer.construct_dense_pi_vec()[position],
+            -E::Fr::from(9u64)
+        );
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(3u64));
+                let b = composer.mul(E::Fr::one(), a, a, E::Fr::zero(), None);
+                composer.set_public(b);
+                dummy_gadget(8, composer);
+            },
+            64,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        // The public input is bound to the variable
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(3u64));
+                let b = composer.mul(E::Fr::one(), a, a, E::Fr::zero(), None);
+                composer.variables[b.0] = E::Fr::from(10u64);
+                let position = composer.set_public(b);
+                composer
+                    .public_inputs_sparse_store
+                    .insert(position, -E::Fr::from(9u64));
+                dummy_gadget(8, composer);
+            },
+            64,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_add_constant<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut composer = StandardComposer::<E, P>::new();
+        let size = composer.circuit_size();
+        assert_eq!(composer.add_constant(E::Fr::zero()), composer.zero_var());
+        assert_eq!(composer.circuit_size(), size);
+
+        // Only the first use of a constant adds a gate
+        let five = composer.add_constant(E::Fr::from(5u64));
+        assert_eq!(composer.circuit_size(), size + 1);
+        assert_eq!(composer.add_constant(E::Fr::from(5u64)), five);
+        assert_eq!(composer.circuit_size(), size + 1);
+
+        // Constants added after a checkpoint are discarded by the rollback
+        let checkpoint = composer.checkpoint();
+        composer.add_constant(E::Fr::from(7u64));
+        composer.rollback(checkpoint);
+        composer.add_constant(E::Fr::from(7u64));
+        assert_eq!(composer.circuit_size(), size + 2);
+
+        // Constants whose gate is optimized away are constrained again
+        let mut composer = StandardComposer::<E, P>::new();
+        composer.add_constant(E::Fr::from(5u64));
+        assert_eq!(composer.optimize(), 1);
+        composer.add_constant(E::Fr::from(5u64));
+        assert_eq!(composer.circuit_size(), size + 1);
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(5u64));
+                let five = composer.add_constant(E::Fr::from(5u64));
+                composer.assert_equal(a, five);
+                let b = composer.add_input(E::Fr::from(5u64));
+                let five = composer.add_constant(E::Fr::from(5u64));
+                composer.assert_equal(b, five);
+            },
+            32,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(6u64));
+                let five = composer.add_constant(E::Fr::from(5u64));
+                composer.assert_equal(a, five);
+            },
+            32,
+        );
+        assert!(res.is_err());
+    }
+
     // FIXME: Move this to integration tests
     fn test_multiple_proofs<E, P>()
     where
@@ -764,6 +1340,7 @@ mod test {
 
         // Preprocess circuit
         prover.preprocess(&powers).unwrap();
+        prover.warmup(&powers).unwrap();
 
         let public_inputs = prover.cs.construct_dense_pi_vec();
 
@@ -815,12 +1392,35 @@ mod test {
         }
     }
 
+    /// Tests that zeroizing a composer overwrites its witness but keeps its
+    /// gates.
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_zeroize() {
+        let mut composer = StandardComposer::<
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >::new();
+        let a = composer.add_input(42u64.into());
+        composer.range_gate(a, 8);
+        let (n, num_variables) = (composer.n, composer.variables.len());
+
+        composer.zeroize();
+        assert_eq!(composer.n, n);
+        assert_eq!(composer.variables.len(), num_variables);
+        assert!(composer.variables.iter().all(Zero::is_zero));
+    }
+
     // Tests for Bls12_381
     batch_test!(
         [
             test_initial_circuit_size,
             test_prove_verify,
             test_conditional_select,
+            test_checkpoint_rollback,
+            test_add_constant,
+            test_append,
+            test_set_public,
             test_multiple_proofs
         ],
         [] => (
@@ -835,6 +1435,10 @@ mod test {
             test_initial_circuit_size,
             test_prove_verify,
             test_conditional_select,
+            test_checkpoint_rollback,
+            test_add_constant,
+            test_append,
+            test_set_public,
             test_multiple_proofs
         ],
         [] => (
@@ -842,4 +1446,31 @@ mod test {
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Tests for Bn254
+    batch_test!(
+        [
+            test_initial_circuit_size,
+            test_prove_verify,
+            test_conditional_select,
+            test_checkpoint_rollback,
+            test_add_constant,
+            test_append,
+            test_set_public,
+            test_multiple_proofs
+        ],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+
+    // Tests for BW6-761, the outer curve of BLS12-377 recursion
+    batch_test!(
+        [test_prove_verify, test_multiple_proofs],
+        [] => (
+            BW6_761,
+            ark_ed_on_bw6_761::EdwardsParameters
+        )
+    );
 }
@@ -0,0 +1,349 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Structured description of a compiled circuit, for inspection.
+//!
+//! A [`CircuitDescription`] lists the selector values, the wire assignment
+//! and the public input of every gate of a [`StandardComposer`] together with
+//! its copy constraints. It can be rendered as JSON for external tooling, or
+//! as a Graphviz graph to visualize small circuits.
+
+use crate::constraint_system::export::{signed_decimal, to_decimal};
+use crate::constraint_system::{StandardComposer, WireData};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use core::fmt::Write;
+
+/// Names of the selectors of a gate, in the order of
+/// [`GateDescription::selectors`].
+pub const SELECTOR_NAMES: [&str; 12] = [
+    "q_m",
+    "q_l",
+    "q_r",
+    "q_o",
+    "q_4",
+    "q_c",
+    "q_arith",
+    "q_range",
+    "q_logic",
+    "q_fixed_group_add",
+    "q_variable_group_add",
+    "q_lookahead",
+];
+
+/// Names of the wires of a gate, in the order of
+/// [`GateDescription::wires`].
+pub const WIRE_NAMES: [&str; 4] = ["left", "right", "output", "fourth"];
+
+/// Description of a single gate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GateDescription<F>
+where
+    F: PrimeField,
+{
+    /// Values of the selectors, named by [`SELECTOR_NAMES`]
+    pub selectors: [F; 12],
+
+    /// Indices of the variables on the wires, named by [`WIRE_NAMES`]
+    pub wires: [usize; 4],
+
+    /// Public input of the gate, zero if there is none
+    pub public_input: F,
+}
+
+/// Wires that must hold the same variable.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CopyConstraint {
+    /// Index of the variable
+    pub variable: usize,
+
+    /// Gate index and wire name of every wire holding the variable
+    pub wires: Vec<(usize, &'static str)>,
+}
+
+/// Description of the gates and copy constraints of a circuit, see
+/// [`StandardComposer::export`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitDescription<F>
+where
+    F: PrimeField,
+{
+    /// Gates of the circuit, in order
+    pub gates: Vec<GateDescription<F>>,
+
+    /// Copy constraints of the variables used by more than one wire,
+    /// ordered by variable index
+    pub copy_constraints: Vec<CopyConstraint>,
+}
+
+/// Returns the signed decimal representation of `value`.
+fn decimal<F>(value: F) -> String
+where
+    F: PrimeField,
+{
+    match signed_decimal(value) {
+        (true, abs) => format!("-{}", abs),
+        (false, abs) => abs,
+    }
+}
+
+impl<F> CircuitDescription<F>
+where
+    F: PrimeField,
+{
+    /// Renders the description as a JSON document.
+    ///
+    /// Field elements are written as strings holding signed decimal
+    /// integers, so that no precision is lost by JSON parsers.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        out.push_str("{\n");
+        writeln!(
+            out,
+            "  \"modulus\": \"{}\",",
+            to_decimal(F::characteristic())
+        )
+        .unwrap();
+
+        out.push_str("  \"gates\": [");
+        for (index, gate) in self.gates.iter().enumerate() {
+            let selectors: Vec<String> = SELECTOR_NAMES
+                .iter()
+                .zip(gate.selectors.iter())
+                .map(|(name, value)| {
+                    format!("\"{}\": \"{}\"", name, decimal(*value))
+                })
+                .collect();
+            let wires: Vec<String> = WIRE_NAMES
+                .iter()
+                .zip(gate.wires.iter())
+                .map(|(name, variable)| format!("\"{}\": {}", name, variable))
+                .collect();
+            out.push_str(if index == 0 { "\n" } else { ",\n" });
+            write!(
+                out,
+                "    {{\"index\": {}, \"selectors\": {{{}}}, \"wires\": {{{}}}, \
+                 \"public_input\": \"{}\"}}",
+                index,
+                selectors.join(", "),
+                wires.join(", "),
+                decimal(gate.public_input)
+            )
+            .unwrap();
+        }
+        out.push_str("\n  ],\n");
+
+        out.push_str("  \"copy_constraints\": [");
+        for (index, constraint) in self.copy_constraints.iter().enumerate() {
+            let wires: Vec<String> = constraint
+                .wires
+                .iter()
+                .map(|(gate, wire)| {
+                    format!("{{\"gate\": {}, \"wire\": \"{}\"}}", gate, wire)
+                })
+                .collect();
+            out.push_str(if index == 0 { "\n" } else { ",\n" });
+            write!(
+                out,
+                "    {{\"variable\": {}, \"wires\": [{}]}}",
+                constraint.variable,
+                wires.join(", ")
+            )
+            .unwrap();
+        }
+        out.push_str("\n  ]\n}\n");
+        out
+    }
+
+    /// Renders the description as a Graphviz graph, where every gate is
+    /// linked to the variables on its wires.
+    ///
+    /// The graph grows with the number of gates, so this is only useful to
+    /// visualize small circuits.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph circuit {\n");
+        for (index, gate) in self.gates.iter().enumerate() {
+            let mut label = format!("gate {}", index);
+            for (name, value) in
+                SELECTOR_NAMES.iter().zip(gate.selectors.iter())
+            {
+                if !value.is_zero() {
+                    write!(label, "\\n{} = {}", name, decimal(*value)).unwrap();
+                }
+            }
+            if !gate.public_input.is_zero() {
+                write!(label, "\\npi = {}", decimal(gate.public_input))
+                    .unwrap();
+            }
+            writeln!(out, "  g{} [shape=box, label=\"{}\"];", index, label)
+                .unwrap();
+            for (name, variable) in WIRE_NAMES.iter().zip(gate.wires.iter()) {
+                writeln!(
+                    out,
+                    "  w{} -> g{} [label=\"{}\"];",
+                    variable, index, name
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Returns the [`CircuitDescription`] of the gates added so far.
+    pub fn export(&self) -> CircuitDescription<E::Fr> {
+        let public_inputs = self.construct_dense_pi_vec();
+        let gates = (0..self.n)
+            .map(|i| GateDescription {
+                selectors: [
+                    self.q_m[i],
+                    self.q_l[i],
+                    self.q_r[i],
+                    self.q_o[i],
+                    self.q_4[i],
+                    self.q_c[i],
+                    self.q_arith[i],
+                    self.q_range[i],
+                    self.q_logic[i],
+                    self.q_fixed_group_add[i],
+                    self.q_variable_group_add[i],
+                    self.q_lookahead[i],
+                ],
+                wires: [
+                    self.w_l[i].0,
+                    self.w_r[i].0,
+                    self.w_o[i].0,
+                    self.w_4[i].0,
+                ],
+                public_input: public_inputs[i],
+            })
+            .collect();
+
+        let mut copy_constraints: Vec<CopyConstraint> = self
+            .perm
+            .variable_map
+            .iter()
+            .filter(|(_, wires)| wires.len() > 1)
+            .map(|(variable, wires)| CopyConstraint {
+                variable: variable.0,
+                wires: wires
+                    .iter()
+                    .map(|wire| match wire {
+                        WireData::Left(gate) => (*gate, WIRE_NAMES[0]),
+                        WireData::Right(gate) => (*gate, WIRE_NAMES[1]),
+                        WireData::Output(gate) => (*gate, WIRE_NAMES[2]),
+                        WireData::Fourth(gate) => (*gate, WIRE_NAMES[3]),
+                    })
+                    .collect(),
+            })
+            .collect();
+        copy_constraints.sort_by_key(|constraint| constraint.variable);
+
+        CircuitDescription {
+            gates,
+            copy_constraints,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use num_traits::{One, Zero};
+
+    fn test_export<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut composer = StandardComposer::<E, P>::new();
+        let a = composer.add_input(E::Fr::from(2u64));
+        let b = composer.add_input(E::Fr::from(3u64));
+        let c = composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+        composer.constrain_to_constant(
+            c,
+            E::Fr::zero(),
+            Some(-E::Fr::from(6u64)),
+        );
+
+        let description = composer.export();
+        assert_eq!(description.gates.len(), composer.circuit_size());
+        let mul = &description.gates[3];
+        assert_eq!(mul.selectors[0], E::Fr::one());
+        assert_eq!(mul.selectors[3], -E::Fr::one());
+        assert_eq!(mul.wires[..3], [a.0, b.0, c.0]);
+        let constant = &description.gates[4];
+        assert_eq!(constant.public_input, -E::Fr::from(6u64));
+
+        // `c` is copied from the output of the multiplication to the first
+        // three wires of the constant gate
+        let copy = description
+            .copy_constraints
+            .iter()
+            .find(|constraint| constraint.variable == c.0)
+            .unwrap();
+        assert_eq!(
+            copy.wires,
+            vec![(3, "output"), (4, "left"), (4, "right"), (4, "output")]
+        );
+
+        let json = description.to_json();
+        assert!(json.contains("\"q_m\": \"1\", \"q_l\": \"0\""));
+        assert!(json.contains("\"q_o\": \"-1\""));
+        assert!(json.contains(&format!(
+            "{{\"variable\": {}, \"wires\": [{{\"gate\": 3, \"wire\": \
+             \"output\"}}, {{\"gate\": 4, \"wire\": \"left\"}}, ",
+            c.0
+        )));
+        assert!(json.contains("\"public_input\": \"-6\""));
+
+        let dot = description.to_dot();
+        assert!(dot.starts_with("digraph circuit {\n"));
+        assert!(dot.contains("g4 [shape=box, label=\"gate 4\\nq_l = 1"));
+        assert!(dot.contains(&format!("w{} -> g3 [label=\"output\"];", c.0)));
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_export],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_export],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_export],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
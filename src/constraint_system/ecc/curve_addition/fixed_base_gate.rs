@@ -95,6 +95,7 @@ where
         self.q_o.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::one());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         self.q_m.push(E::Fr::zero());
         self.q_4.push(E::Fr::zero());
@@ -38,10 +38,10 @@ where
         let y_2 = point_b.y;
 
         // Compute the resulting point
-        let x_1_scalar = self.variables.get(&x_1).unwrap();
-        let y_1_scalar = self.variables.get(&y_1).unwrap();
-        let x_2_scalar = self.variables.get(&x_2).unwrap();
-        let y_2_scalar = self.variables.get(&y_2).unwrap();
+        let x_1_scalar = &self.variables[x_1.0];
+        let y_1_scalar = &self.variables[y_1.0];
+        let x_2_scalar = &self.variables[x_2.0];
+        let y_2_scalar = &self.variables[y_2.0];
 
         let p1 = GroupAffine::<P>::new(*x_1_scalar, *y_1_scalar);
         let p2 = GroupAffine::<P>::new(*x_2_scalar, *y_2_scalar);
@@ -75,7 +75,9 @@ where
         self.q_fixed_group_add.extend(&zeros);
 
         self.q_variable_group_add.push(E::Fr::one());
+        self.q_lookahead.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         self.perm.add_variables_to_map(x_1, y_1, x_2, y_2, self.n);
         self.n += 1;
@@ -99,6 +101,7 @@ mod test {
     use crate::{batch_test, constraint_system::helper::*};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_ff::Field;
 
     /// Adds two curve points together using the classical point addition
@@ -139,10 +142,10 @@ mod test {
             None,
         );
 
-        // y1y2 - a * x1x2 (a=-1) => y1y2 + x1x2
+        // y1y2 - a * x1x2
         let y_numerator = composer.add(
             (E::Fr::one(), y1_y2),
-            (E::Fr::one(), x1_x2),
+            (-P::COEFF_A, x1_x2),
             E::Fr::zero(),
             None,
         );
@@ -158,7 +161,7 @@ mod test {
         // Compute the inverse
         let inv_x_denom = composer
             .variables
-            .get(&x_denominator)
+            .get(x_denominator.0)
             .unwrap()
             .inverse()
             .unwrap();
@@ -185,7 +188,7 @@ mod test {
         );
         let inv_y_denom = composer
             .variables
-            .get(&y_denominator)
+            .get(y_denominator.0)
             .unwrap()
             .inverse()
             .unwrap();
@@ -266,4 +269,12 @@ mod test {
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    batch_test!(
+        [test_curve_addition],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
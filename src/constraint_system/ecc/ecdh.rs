@@ -0,0 +1,171 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Diffie-Hellman key agreement over the embedded curve.
+//!
+//! Two parties holding secret scalars `a` and `b`, with public keys `a G`
+//! and `b G`, share the point `a b G`. [`shared_secret`] computes it from
+//! one secret and the other public key, and [`derive_key`] hashes it into a
+//! symmetric key with the
+//! [Poseidon](crate::constraint_system::PoseidonParameters) hash.
+//!
+//! [`StandardComposer::ecdh_key`] constrains the same derivation, so that
+//! circuits can show for instance that a note is encrypted to the owner of a
+//! public key, or that a stealth address is derived from an ephemeral key.
+
+use crate::constraint_system::ecc::Point;
+use crate::constraint_system::{
+    PoseidonParameters, StandardComposer, Variable,
+};
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, PairingEngine,
+    ProjectiveCurve, TEModelParameters,
+};
+
+/// Domain separator of the key derivation, distinct from those of the other
+/// hashes of points.
+const KEY_DOMAIN: u64 = 4;
+
+/// Returns the point shared by the owner of `secret` and the owner of
+/// `public_key`.
+pub fn shared_secret<E, P>(
+    secret: P::ScalarField,
+    public_key: GroupAffine<P>,
+) -> GroupAffine<P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    public_key.mul(secret).into_affine()
+}
+
+/// Derives a symmetric key from the `shared` point.
+pub fn derive_key<E, P>(
+    params: &PoseidonParameters<E::Fr>,
+    shared: GroupAffine<P>,
+) -> E::Fr
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    params.hash(&[E::Fr::from(KEY_DOMAIN), shared.x, shared.y])
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the point shared by the owner of `secret` and the owner of
+    /// `public_key`, as computed by [`shared_secret`].
+    ///
+    /// The `secret` is a scalar of the embedded curve, added with
+    /// [`StandardComposer::add_embedded_scalar`].
+    pub fn ecdh_shared_secret(
+        &mut self,
+        secret: Variable,
+        public_key: Point<E, P>,
+    ) -> Point<E, P> {
+        self.variable_base_scalar_mul(secret, public_key)
+    }
+
+    /// Constrains the symmetric key derived from the point shared by the
+    /// owner of `secret` and the owner of `public_key`, returning the
+    /// [`Variable`] holding the result of [`derive_key`].
+    pub fn ecdh_key(
+        &mut self,
+        params: &PoseidonParameters<E::Fr>,
+        secret: Variable,
+        public_key: Point<E, P>,
+    ) -> Variable {
+        let shared = self.ecdh_shared_secret(secret, public_key);
+        let domain = self.add_constant(E::Fr::from(KEY_DOMAIN));
+        self.poseidon_hash(params, &[domain, *shared.x(), *shared.y()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constraint_system::ecc::generator;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use num_traits::One;
+    use rand_core::OsRng;
+
+    fn test_ecdh<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = PoseidonParameters::new();
+        let [alice, bob] = [(); 2].map(|_| P::ScalarField::rand(&mut OsRng));
+        let [alice_key, bob_key] = [alice, bob]
+            .map(|secret| generator::<P>().mul(secret).into_affine());
+
+        // Both parties derive the same key
+        let shared = shared_secret::<E, P>(alice, bob_key);
+        assert_eq!(shared, shared_secret::<E, P>(bob, alice_key));
+        let key = derive_key::<E, P>(&params, shared);
+        assert_ne!(key, derive_key::<E, P>(&params, alice_key));
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let secret = composer.add_embedded_scalar(alice);
+                let public_key = composer.add_public_affine(bob_key);
+                let derived = composer.ecdh_key(&params, secret, public_key);
+                composer.constrain_to_constant(derived, key, None);
+            },
+            1 << 12,
+        );
+        assert!(res.is_ok());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let secret =
+                    composer.add_embedded_scalar(alice + P::ScalarField::one());
+                let public_key = composer.add_public_affine(bob_key);
+                let derived = composer.ecdh_key(&params, secret, public_key);
+                composer.constrain_to_constant(derived, key, None);
+            },
+            1 << 12,
+        );
+        assert!(res.is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_ecdh],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_ecdh],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_ecdh],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
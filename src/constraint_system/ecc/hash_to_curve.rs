@@ -0,0 +1,522 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Hashing to the embedded curve.
+//!
+//! [`map_to_curve`] maps a field element to a point of the embedded curve
+//! with Elligator2, as specified in RFC 9380 for Montgomery curves, followed
+//! by the rational map to the twisted Edwards form. [`hash_to_curve`] hashes
+//! field elements into two such points with the
+//! [Poseidon](crate::constraint_system::PoseidonParameters) hash, adds them
+//! and clears the cofactor, so that the result is uniformly distributed in
+//! the prime order subgroup. The [`StandardComposer`] methods of the same
+//! names constrain the same computations in circuit.
+//!
+//! The map requires the embedded curve to be complete, that is `a` to be a
+//! square and `d` a non-square, which holds for all the supported embedded
+//! curves. The rational map to the twisted Edwards form then has a single
+//! exceptional point, `(s, t) = (0, 0)`, which is only reached for `u = 0`
+//! and which is mapped to the identity `(0, 1)` as RFC 9380 specifies.
+
+use crate::constraint_system::{
+    ecc::Point, BoolVariable, PoseidonParameters, StandardComposer, Variable,
+};
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, PairingEngine,
+    TEModelParameters,
+};
+use ark_ff::{BigInteger, BitIteratorBE, Field, PrimeField, SquareRootField};
+use num_traits::{One, Zero};
+
+/// Constants of the Montgomery curve `K t^2 = s^3 + J s^2 + s` birationally
+/// equivalent to the embedded curve.
+struct Montgomery<F>
+where
+    F: Field,
+{
+    /// `J / K`
+    c1: F,
+
+    /// `1 / K^2`
+    c2: F,
+
+    /// `K`
+    k: F,
+
+    /// Non-square of the field
+    z: F,
+}
+
+impl<F> Montgomery<F>
+where
+    F: PrimeField + SquareRootField,
+{
+    /// Returns the constants for the twisted Edwards curve
+    /// `a x^2 + y^2 = 1 + d x^2 y^2`, for which `J = 2 (a + d) / (a - d)`
+    /// and `K = 4 / (a - d)`.
+    fn new(a: F, d: F) -> Self {
+        let k = F::from(4u64)
+            * (a - d)
+                .inverse()
+                .expect("a and d of the curve are distinct");
+        Self {
+            c1: (a + d) / F::from(2u64),
+            c2: k.square().inverse().expect("K is non-zero"),
+            k,
+            z: (2u64..)
+                .map(F::from)
+                .find(|z| z.legendre().is_qnr())
+                .expect("the field has non-squares"),
+        }
+    }
+
+    /// Returns `x (x^2 + c1 x + c2)`, which is `(s^3 + J s^2 + s) / K^3`
+    /// for `s = K x`.
+    fn g(&self, x: F) -> F {
+        x * (x.square() + self.c1 * x + self.c2)
+    }
+}
+
+/// Maps `u` to a point of the embedded curve with Elligator2.
+///
+/// The point isn't cleared of the cofactor, and `u` and `-u` are mapped to
+/// the same point. `0` is mapped to the identity.
+///
+/// The sign of the Montgomery `t` coordinate is chosen by the parity of its
+/// canonical representative. It is even for the exceptional `t = 0`, which
+/// only occurs on the branch where `g(x1)` isn't a square, so that it is
+/// consistent with the parity that branch requires.
+pub fn map_to_curve<E, P>(u: E::Fr) -> GroupAffine<P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mont = Montgomery::new(P::COEFF_A, P::COEFF_D);
+    let x1 = -mont.c1 / (E::Fr::one() + mont.z * u.square());
+    let gx1 = mont.g(x1);
+    let (x, gx, square) = match gx1.sqrt() {
+        Some(_) => (x1, gx1, true),
+        None => {
+            let x2 = mont.z * u.square() * x1;
+            (x2, mont.g(x2), false)
+        }
+    };
+    let mut y = gx.sqrt().expect("Elligator2 always finds a square");
+    if y.into_repr().is_odd() != square {
+        y = -y;
+    }
+
+    // Rational map from `(s, t) = (K x, K y)` to the twisted Edwards form,
+    // whose exceptional point `t = 0` maps to the identity. `s = -1` isn't
+    // on the curve, as `d` is a non-square
+    if y.is_zero() {
+        return GroupAffine::zero();
+    }
+    let kx = mont.k * x;
+    GroupAffine::new(x / y, (kx - E::Fr::one()) / (kx + E::Fr::one()))
+}
+
+/// Hashes `inputs` to a point of the prime order subgroup of the embedded
+/// curve.
+///
+/// Each of the two field elements mapped to the curve is the Poseidon hash
+/// of its index followed by `inputs`.
+pub fn hash_to_curve<E, P>(
+    params: &PoseidonParameters<E::Fr>,
+    inputs: &[E::Fr],
+) -> GroupAffine<P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let [p0, p1] = [0u64, 1].map(|index| {
+        let mut preimage = vec![E::Fr::from(index)];
+        preimage.extend_from_slice(inputs);
+        map_to_curve::<E, P>(params.hash(&preimage))
+    });
+    (p0 + p1).mul_by_cofactor()
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the point [`map_to_curve`] maps `u` to.
+    pub fn map_to_curve(&mut self, u: Variable) -> Point<E, P> {
+        let mont = Montgomery::new(P::COEFF_A, P::COEFF_D);
+        let zero = self.zero_var;
+
+        // x1 = -c1 / (1 + Z u^2), whose denominator is never zero as `-1 / Z`
+        // isn't a square
+        let u2 = self.mul(E::Fr::one(), u, u, E::Fr::zero(), None);
+        let den =
+            self.add((mont.z, u2), (E::Fr::zero(), zero), E::Fr::one(), None);
+        let x1_value = -mont.c1 / self.variables[den.0];
+        let x1 = self.add_input(x1_value);
+        self.poly_gate(
+            den,
+            x1,
+            zero,
+            E::Fr::one(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            mont.c1,
+            None,
+        );
+        let x2 = self.mul(mont.z, u2, x1, E::Fr::zero(), None);
+        let gx1 = self.montgomery_rhs(&mont, x1);
+        let gx2 = self.montgomery_rhs(&mont, x2);
+
+        // g(x1) is a square if and only if `square` is set, as `Z g(x1)` is a
+        // square otherwise
+        let gx1_value = self.variables[gx1.0];
+        let gx1_root = gx1_value.sqrt();
+        let square = self.add_bool(gx1_root.is_some());
+        let factor = self.add(
+            (E::Fr::one() - mont.z, square.variable()),
+            (E::Fr::zero(), zero),
+            mont.z,
+            None,
+        );
+        let root_value = gx1_root
+            .or_else(|| (mont.z * gx1_value).sqrt())
+            .expect("either g(x1) or Z g(x1) is a square");
+        let root = self.add_input(root_value);
+        let root_squared =
+            self.mul(E::Fr::one(), root, root, E::Fr::zero(), None);
+        let scaled = self.mul(E::Fr::one(), gx1, factor, E::Fr::zero(), None);
+        self.assert_equal(root_squared, scaled);
+
+        // y is the root of g(x) whose parity is `square`
+        let x = self.conditional_select(square, x1, x2);
+        let gx = self.conditional_select(square, gx1, gx2);
+        let mut y_value = self.variables[gx.0]
+            .sqrt()
+            .expect("Elligator2 always finds a square");
+        if y_value.into_repr().is_odd() != gx1_root.is_some() {
+            y_value = -y_value;
+        }
+        let y = self.add_input(y_value);
+        let y_squared = self.mul(E::Fr::one(), y, y, E::Fr::zero(), None);
+        self.assert_equal(y_squared, gx);
+        let parity = self.canonical_parity(y);
+        self.assert_equal(parity.variable(), square.variable());
+
+        // Rational map to the twisted Edwards form. `y` is zero only for the
+        // exceptional point, where `x` is zero as well, so `edwards_x` is
+        // zero there and `edwards_y` is selected to be one. `kx + 1` is never
+        // zero
+        let x_value = self.variables[x.0];
+        let kx_value = mont.k * x_value;
+        let (y_inverse, non_zero) = self.inverse_or_zero(y);
+        let edwards_x =
+            self.mul(E::Fr::one(), x, y_inverse, E::Fr::zero(), None);
+        let kx_plus_one =
+            self.add((mont.k, x), (E::Fr::zero(), zero), E::Fr::one(), None);
+        let mapped_y = self
+            .add_input((kx_value - E::Fr::one()) / (kx_value + E::Fr::one()));
+        self.poly_gate(
+            mapped_y,
+            kx_plus_one,
+            x,
+            E::Fr::one(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            -mont.k,
+            E::Fr::one(),
+            None,
+        );
+        let edwards_y = self.conditional_select_one(non_zero, mapped_y);
+        Point::new(edwards_x, edwards_y)
+    }
+
+    /// Constrains the multiplication of `point` by the cofactor of the
+    /// embedded curve.
+    pub fn clear_cofactor(&mut self, point: Point<E, P>) -> Point<E, P> {
+        let mut bits = BitIteratorBE::without_leading_zeros(P::COFACTOR);
+        // The cofactor is non-zero, so its leading bit is set
+        bits.next();
+        let mut result = point;
+        for bit in bits {
+            result = self.point_addition_gate(result, result);
+            if bit {
+                result = self.point_addition_gate(result, point);
+            }
+        }
+        result
+    }
+
+    /// Constrains the point [`hash_to_curve`] hashes `inputs` to.
+    pub fn hash_to_curve(
+        &mut self,
+        params: &PoseidonParameters<E::Fr>,
+        inputs: &[Variable],
+    ) -> Point<E, P> {
+        let [p0, p1] = [0u64, 1].map(|index| {
+            let mut preimage = vec![self.add_constant(E::Fr::from(index))];
+            preimage.extend_from_slice(inputs);
+            let u = self.poseidon_hash(params, &preimage);
+            self.map_to_curve(u)
+        });
+        let sum = self.point_addition_gate(p0, p1);
+        self.clear_cofactor(sum)
+    }
+
+    /// Constrains the inverse of `value`, or zero if `value` is zero, and a
+    /// bit set if and only if `value` is non-zero.
+    fn inverse_or_zero(&mut self, value: Variable) -> (Variable, BoolVariable) {
+        let zero = self.zero_var;
+        let value_scalar = self.variables[value.0];
+        let non_zero = self.add_bool(!value_scalar.is_zero());
+        let inverse =
+            self.add_input(value_scalar.inverse().unwrap_or_else(E::Fr::zero));
+
+        // value * inverse = non_zero
+        self.poly_gate(
+            value,
+            inverse,
+            non_zero.variable(),
+            E::Fr::one(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            -E::Fr::one(),
+            E::Fr::zero(),
+            None,
+        );
+        // value * (1 - non_zero) = 0
+        self.poly_gate(
+            value,
+            non_zero.variable(),
+            zero,
+            -E::Fr::one(),
+            E::Fr::one(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            None,
+        );
+        (inverse, non_zero)
+    }
+
+    /// Constrains `x (x^2 + c1 x + c2)`.
+    fn montgomery_rhs(
+        &mut self,
+        mont: &Montgomery<E::Fr>,
+        x: Variable,
+    ) -> Variable {
+        let x_squared = self.mul(E::Fr::one(), x, x, E::Fr::zero(), None);
+        let inner =
+            self.add((E::Fr::one(), x_squared), (mont.c1, x), mont.c2, None);
+        self.mul(E::Fr::one(), x, inner, E::Fr::zero(), None)
+    }
+
+    /// Returns the parity of the canonical representative of `value`.
+    ///
+    /// The value is decomposed as `2 k + parity` with `k` at most
+    /// `(p - 1) / 2`, which only the canonical representative satisfies.
+    fn canonical_parity(&mut self, value: Variable) -> BoolVariable {
+        let value_repr = self.variables[value.0].into_repr();
+        let parity = self.add_bool(value_repr.is_odd());
+        let half_value = (self.variables[value.0]
+            - self.variables[parity.variable().0])
+            / E::Fr::from(2u64);
+        let half = self.add_input(half_value);
+        let recomposed = self.add(
+            (E::Fr::from(2u64), half),
+            (E::Fr::one(), parity.variable()),
+            E::Fr::zero(),
+            None,
+        );
+        self.assert_equal(recomposed, value);
+
+        // k <= (p - 1) / 2
+        self.assert_at_most(half, -E::Fr::from(2u64).inverse().unwrap());
+
+        parity
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use rand_core::OsRng;
+
+    fn test_map_to_curve<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        // The embedded curve is complete
+        assert!(P::COEFF_A.legendre().is_qr());
+        assert!(P::COEFF_D.legendre().is_qnr());
+
+        for _ in 0..4 {
+            let u = E::Fr::rand(&mut OsRng);
+            let point = map_to_curve::<E, P>(u);
+            assert!(point.is_on_curve());
+            assert_eq!(point, map_to_curve::<E, P>(-u));
+
+            let cleared = point.mul_by_cofactor();
+            assert!(cleared.is_in_correct_subgroup_assuming_on_curve());
+
+            let mut composer = StandardComposer::<E, P>::new();
+            let u = composer.add_input(u);
+            let mapped = composer.map_to_curve(u);
+            assert_eq!(composer.variables[mapped.x().0], point.x);
+            assert_eq!(composer.variables[mapped.y().0], point.y);
+            let mapped = composer.clear_cofactor(mapped);
+            assert_eq!(composer.variables[mapped.x().0], cleared.x);
+            assert_eq!(composer.variables[mapped.y().0], cleared.y);
+        }
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let u = E::Fr::from(7u64);
+                let var = composer.add_input(u);
+                let point = composer.map_to_curve(var);
+                composer
+                    .assert_equal_public_point(point, map_to_curve::<E, P>(u));
+            },
+            1 << 11,
+        );
+        assert!(res.is_ok());
+
+        // The parity pins the point among it and its negation
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let u = E::Fr::from(7u64);
+                let var = composer.add_input(u);
+                let point = composer.map_to_curve(var);
+                composer
+                    .assert_equal_public_point(point, -map_to_curve::<E, P>(u));
+            },
+            1 << 11,
+        );
+        assert!(res.is_err());
+
+        // Zero is the exceptional point of the map, mapped to the identity
+        // rather than to the point of order two `(0, -1)`
+        assert_eq!(map_to_curve::<E, P>(E::Fr::zero()), GroupAffine::zero());
+        for (expected, satisfied) in [
+            (GroupAffine::<P>::zero(), true),
+            (GroupAffine::new(E::Fr::zero(), -E::Fr::one()), false),
+        ] {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let var = composer.add_input(E::Fr::zero());
+                    let point = composer.map_to_curve(var);
+                    composer.assert_equal_public_point(point, expected);
+                },
+                1 << 11,
+            );
+            assert_eq!(res.is_ok(), satisfied);
+        }
+    }
+
+    fn test_canonical_parity<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        for value in [
+            E::Fr::zero(),
+            E::Fr::one(),
+            -E::Fr::one(),
+            -E::Fr::from(2u64).inverse().unwrap(),
+            E::Fr::rand(&mut OsRng),
+        ] {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let var = composer.add_input(value);
+                    let parity = composer.canonical_parity(var);
+                    let neg = composer.add(
+                        (-E::Fr::one(), var),
+                        (E::Fr::zero(), composer.zero_var),
+                        E::Fr::zero(),
+                        None,
+                    );
+                    let neg_parity = composer.canonical_parity(neg);
+                    let expected = value.into_repr().is_odd();
+                    composer.constrain_to_constant(
+                        parity.variable(),
+                        E::Fr::from(expected as u64),
+                        None,
+                    );
+                    if !value.is_zero() {
+                        composer.constrain_to_constant(
+                            neg_parity.variable(),
+                            E::Fr::from(!expected as u64),
+                            None,
+                        );
+                    }
+                },
+                1 << 9,
+            );
+            assert!(res.is_ok());
+        }
+    }
+
+    fn test_hash_to_curve<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = PoseidonParameters::new();
+        let values = [E::Fr::from(3u64), E::Fr::from(5u64)];
+        let point = hash_to_curve::<E, P>(&params, &values);
+        assert!(point.is_on_curve());
+        assert!(point.is_in_correct_subgroup_assuming_on_curve());
+        assert_ne!(point, hash_to_curve::<E, P>(&params, &values[..1]));
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let inputs: Vec<Variable> =
+                    values.iter().map(|v| composer.add_input(*v)).collect();
+                let hashed = composer.hash_to_curve(&params, &inputs);
+                composer.assert_equal_public_point(hashed, point);
+            },
+            1 << 12,
+        );
+        assert!(res.is_ok());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_map_to_curve, test_canonical_parity, test_hash_to_curve],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_map_to_curve, test_canonical_parity, test_hash_to_curve],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_map_to_curve, test_canonical_parity, test_hash_to_curve],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -6,12 +6,20 @@
 
 //! Elliptic Curve Gates
 
-pub mod curve_addition;
-pub mod scalar_mul;
-
-use crate::constraint_system::{variable::Variable, StandardComposer};
+pub(crate) mod curve_addition;
+pub mod ecdh;
+pub mod hash_to_curve;
+pub(crate) mod scalar_mul;
+pub mod vrf;
+
+use crate::constraint_system::{
+    variable::{BoolVariable, Variable},
+    StandardComposer,
+};
+use crate::util;
 use ark_ec::{
-    twisted_edwards_extended::GroupAffine, PairingEngine, TEModelParameters,
+    twisted_edwards_extended::GroupAffine, ModelParameters, PairingEngine,
+    TEModelParameters,
 };
 use core::marker::PhantomData;
 use num_traits::{One, Zero};
@@ -56,7 +64,7 @@ where
 
     /// Returns an identity point.
     pub fn identity(composer: &mut StandardComposer<E, P>) -> Self {
-        let one = composer.add_witness_to_circuit_description(E::Fr::one());
+        let one = composer.add_constant(E::Fr::one());
         Self::new(composer.zero_var, one)
     }
 
@@ -71,6 +79,15 @@ where
     }
 }
 
+/// Returns the generator of the embedded curve.
+pub(crate) fn generator<P>() -> GroupAffine<P>
+where
+    P: TEModelParameters,
+{
+    let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+    GroupAffine::new(x, y)
+}
+
 impl<E, P> StandardComposer<E, P>
 where
     E: PairingEngine,
@@ -86,22 +103,26 @@ where
     /// without constraining the values
     pub fn add_public_affine(&mut self, affine: GroupAffine<P>) -> Point<E, P> {
         let point = self.add_affine(affine);
-        self.constrain_to_constant(point.x, E::Fr::zero(), Some(-affine.x));
-        self.constrain_to_constant(point.y, E::Fr::zero(), Some(-affine.y));
+        self.assert_equal_public_point(point, affine);
         point
     }
 
+    /// Adds a scalar of the embeded curve as a witness, e.g. to be used by
+    /// [`StandardComposer::fixed_base_scalar_mul`].
+    pub fn add_embedded_scalar(
+        &mut self,
+        scalar: <P as ModelParameters>::ScalarField,
+    ) -> Variable {
+        self.add_input(util::from_embedded_curve_scalar::<E, P>(scalar))
+    }
+
     /// Add the provided affine point as a circuit description and return its
     /// constrained witness value
     pub fn add_affine_to_circuit_description(
         &mut self,
         affine: GroupAffine<P>,
     ) -> Point<E, P> {
-        // NOTE: Not using individual gates because one of these may be zero.
-        Point::new(
-            self.add_witness_to_circuit_description(affine.x),
-            self.add_witness_to_circuit_description(affine.y),
-        )
+        Point::new(self.add_constant(affine.x), self.add_constant(affine.y))
     }
 
     /// Asserts that a [`Point`] in the circuit is equal to a known public
@@ -111,6 +132,7 @@ where
         point: Point<E, P>,
         public_point: GroupAffine<P>,
     ) {
+        self.public_points.insert(self.n);
         self.constrain_to_constant(
             point.x,
             E::Fr::zero(),
@@ -130,6 +152,33 @@ where
         self.assert_equal(lhs.y, rhs.y);
     }
 
+    /// Asserts that a point in the circuit satisfies the equation
+    /// `a x^2 + y^2 = 1 + d x^2 y^2` of the embedded curve.
+    ///
+    /// The addition gates only compute the group law for points on the
+    /// curve, so points given as witnesses must be checked before being
+    /// added.
+    pub fn assert_on_curve(&mut self, point: Point<E, P>) {
+        let x_squared =
+            self.mul(E::Fr::one(), point.x, point.x, E::Fr::zero(), None);
+        let y_squared =
+            self.mul(E::Fr::one(), point.y, point.y, E::Fr::zero(), None);
+        let product =
+            self.mul(E::Fr::one(), x_squared, y_squared, E::Fr::zero(), None);
+        // a x^2 + y^2 - d x^2 y^2 - 1 = 0
+        self.poly_gate(
+            x_squared,
+            y_squared,
+            product,
+            E::Fr::zero(),
+            P::COEFF_A,
+            E::Fr::one(),
+            -P::COEFF_D,
+            -E::Fr::one(),
+            None,
+        );
+    }
+
     /// Adds to the circuit description the conditional selection of the
     /// a point between two of them:
     ///
@@ -138,16 +187,13 @@ where
     /// bit == 0 => point_0,
     /// ```
     ///
-    /// # Note
-    ///
-    /// The `bit` used as input which is a [`Variable`] should have previously
-    /// been constrained to be either `1` or `0` using a boolean constraint.
-    /// See: [`StandardComposer::boolean_gate`].
+    /// The `bit` is a [`BoolVariable`], constrained to be either `1` or `0`
+    /// by [`StandardComposer::boolean_gate`].
     pub fn conditional_point_select(
         &mut self,
         point_1: Point<E, P>,
         point_0: Point<E, P>,
-        bit: Variable,
+        bit: BoolVariable,
     ) -> Point<E, P> {
         Point::new(
             self.conditional_select(bit, point_1.x, point_0.x),
@@ -159,13 +205,11 @@ where
     /// bit == 1 => -value,
     /// bit == 0 => value,
     ///
-    /// # Note
-    /// The `bit` used as input which is a [`Variable`] should had previously
-    /// been constrained to be either 1 or 0 using a bool constrain. See:
-    /// [`StandardComposer::boolean_gate`].
+    /// The `bit` is a [`BoolVariable`], constrained to be either `1` or `0`
+    /// by [`StandardComposer::boolean_gate`].
     pub fn conditional_point_neg(
         &mut self,
-        bit: Variable,
+        bit: BoolVariable,
         point_b: Point<E, P>,
     ) -> Point<E, P> {
         let x = point_b.x;
@@ -191,14 +235,11 @@ where
     /// bit == 0 => 1,
     /// ```
     ///
-    /// # Note
-    ///
-    /// The `bit` used as input which is a [`Variable`] should have previously
-    /// been constrained to be either `1` or `0` using a boolean constraint.
-    /// See: [`StandardComposer::boolean_gate`].
+    /// The `bit` is a [`BoolVariable`], constrained to be either `1` or `0`
+    /// by [`StandardComposer::boolean_gate`].
     fn conditional_select_identity(
         &mut self,
-        bit: Variable,
+        bit: BoolVariable,
         point: Point<E, P>,
     ) -> Point<E, P> {
         Point::new(
@@ -214,6 +255,7 @@ mod test {
     use crate::{batch_test, constraint_system::helper::*};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
 
     fn test_conditional_select_point<E, P>()
     where
@@ -222,8 +264,8 @@ mod test {
     {
         let res = gadget_tester(
             |composer: &mut StandardComposer<E, P>| {
-                let bit_1 = composer.add_input(E::Fr::one());
-                let bit_0 = composer.zero_var();
+                let bit_1 = composer.add_bool(true);
+                let bit_0 = composer.add_bool(false);
 
                 let point_a = Point::identity(composer);
                 let point_b = Point::new(
@@ -252,8 +294,8 @@ mod test {
     {
         gadget_tester(
             |composer: &mut StandardComposer<E, P>| {
-                let bit_1 = composer.add_input(E::Fr::one());
-                let bit_0 = composer.zero_var();
+                let bit_1 = composer.add_bool(true);
+                let bit_0 = composer.add_bool(false);
 
                 let point =
                     GroupAffine::new(E::Fr::from(10u64), E::Fr::from(20u64));
@@ -275,11 +317,36 @@ mod test {
         .expect("test failed");
     }
 
+    fn test_assert_on_curve<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let on_curve = generator::<P>();
+        let off_curve = GroupAffine::<P>::new(on_curve.x, on_curve.x);
+        assert!(!off_curve.is_on_curve());
+        for (point, satisfied) in [
+            (on_curve, true),
+            (GroupAffine::zero(), true),
+            (off_curve, false),
+        ] {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let point = composer.add_affine(point);
+                    composer.assert_on_curve(point);
+                },
+                32,
+            );
+            assert_eq!(res.is_ok(), satisfied);
+        }
+    }
+
     // Bls12-381 tests
     batch_test!(
         [
             test_conditional_select_point,
-            test_conditional_point_neg
+            test_conditional_point_neg,
+            test_assert_on_curve
         ],
         [] => (
             Bls12_381,
@@ -291,11 +358,25 @@ mod test {
     batch_test!(
         [
             test_conditional_select_point,
-            test_conditional_point_neg
+            test_conditional_point_neg,
+            test_assert_on_curve
         ],
         [] => (
             Bls12_377,
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Bn254 tests
+    batch_test!(
+        [
+            test_conditional_select_point,
+            test_conditional_point_neg,
+            test_assert_on_curve
+        ],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
@@ -58,7 +58,7 @@ where
             compute_wnaf_point_multiples(base_point.into());
         point_multiples.reverse();
 
-        let scalar_value = self.variables.get(&scalar).unwrap();
+        let scalar_value = &self.variables[scalar.0];
 
         // Convert scalar to wnaf_2(k)
         let wnaf_entries =
@@ -176,6 +176,7 @@ mod tests {
     use crate::{batch_test, constraint_system::helper::*, util};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_ec::{group::Group, AffineCurve};
     use ark_ff::PrimeField;
 
@@ -481,4 +482,20 @@ mod tests {
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Bn254 tests
+    batch_test!(
+        [
+            test_ecc_constraint,
+            test_ecc_constraint_zero,
+            test_ecc_constraint_should_fail,
+            test_point_addition,
+            test_pedersen_hash,
+            test_pedersen_balance
+        ],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
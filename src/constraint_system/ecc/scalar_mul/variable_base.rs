@@ -7,7 +7,10 @@
 //! Variable-base Scalar Multiplication Gate
 
 use crate::constraint_system::ecc::Point;
-use crate::constraint_system::{variable::Variable, StandardComposer};
+use crate::constraint_system::{
+    variable::{BoolVariable, Variable},
+    StandardComposer,
+};
 use ark_ec::models::TEModelParameters;
 use ark_ec::PairingEngine;
 use ark_ff::{BigInteger, Field, FpParameters, PrimeField};
@@ -33,12 +36,11 @@ where
         // Turn scalar into bits
         let raw_scalar = *self
             .variables
-            .get(&curve_var)
+            .get(curve_var.0)
             // We can unwrap safely here since it should be impossible to obtain
-            // a `Variable` without first linking it inside of the
-            // HashMap from which we are calling the `get()` now. Therefore, if
-            // the `get()` fn fails now, somethig is going really
-            // bad.
+            // a `Variable` without first pushing its value to the vector from
+            // which we are calling the `get()` now. Therefore, if the `get()`
+            // fn fails now, somethig is going really bad.
             .expect("Variable in existance without referenced scalar");
         let scalar_bits_var = self.scalar_decomposition(curve_var, raw_scalar);
 
@@ -58,7 +60,7 @@ where
         &mut self,
         witness_var: Variable,
         witness_scalar: E::Fr,
-    ) -> Vec<Variable> {
+    ) -> Vec<BoolVariable> {
         // Decompose the bits
         let scalar_bits_iter = witness_scalar.into_repr().to_bits_le();
 
@@ -77,10 +79,11 @@ where
         let mut accumulator_var = self.zero_var;
         let mut accumulator_scalar = E::Fr::zero();
 
+        let mut bits = Vec::with_capacity(scalar_bits_var.len());
         for (power, bit) in scalar_bits_var.iter().enumerate() {
-            self.boolean_gate(*bit);
+            bits.push(self.boolean_gate(*bit));
 
-            let two_pow = E::Fr::from(2u64).pow([power as u64, 0, 0, 0]);
+            let two_pow = E::Fr::from(2u64).pow([power as u64]);
 
             let q_l_a = (two_pow, *bit);
             let q_r_b = (E::Fr::one(), accumulator_var);
@@ -93,7 +96,7 @@ where
         }
         self.assert_equal(accumulator_var, witness_var);
 
-        scalar_bits_var
+        bits
     }
 }
 
@@ -103,6 +106,7 @@ mod test {
     use crate::{batch_test, constraint_system::helper::*, util};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_ec::{twisted_edwards_extended::GroupAffine, AffineCurve};
 
     fn test_var_base_scalar_mul<E, P>()
@@ -160,4 +164,13 @@ mod test {
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Tests for Bn254
+    batch_test!(
+        [test_var_base_scalar_mul],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
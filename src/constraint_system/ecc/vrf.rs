@@ -0,0 +1,405 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Verifiable random function over the embedded curve.
+//!
+//! A [`VrfProof`] is an EC-VRF proof, following RFC 9381 with the
+//! [Poseidon](crate::constraint_system::PoseidonParameters) hash and
+//! [`hash_to_curve`]. The holder of a secret scalar `x`, with public key
+//! `Y = x G`, hashes an input to a point `H` and publishes `Gamma = x H`,
+//! along with a Schnorr proof `(c, s)` that `Gamma` and `Y` share the same
+//! discrete logarithm. The output of the function is the hash of `Gamma`,
+//! cleared of the cofactor.
+//!
+//! [`StandardComposer::vrf_verify`] constrains the verification of a proof
+//! and returns the output, so that circuits can for instance check that the
+//! output of a leader election or a lottery is below a threshold.
+
+use crate::constraint_system::ecc::hash_to_curve::hash_to_curve;
+use crate::constraint_system::ecc::{generator, Point};
+use crate::constraint_system::{
+    PoseidonParameters, StandardComposer, Variable,
+};
+use crate::error::Error;
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, ModelParameters,
+    PairingEngine, ProjectiveCurve, TEModelParameters,
+};
+use ark_ff::{BigInteger, PrimeField, UniformRand};
+use ark_serialize::*;
+use num_traits::{One, Zero};
+use rand_core::RngCore;
+
+/// Domain separator of the challenge hash.
+const CHALLENGE_DOMAIN: u64 = 2;
+
+/// Domain separator of the output hash.
+const OUTPUT_DOMAIN: u64 = 3;
+
+/// EC-VRF proof of the evaluation of the function on an input.
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct VrfProof<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Hash of the input multiplied by the secret key
+    gamma: GroupAffine<P>,
+
+    /// Challenge of the Schnorr proof
+    challenge: E::Fr,
+
+    /// Response of the Schnorr proof
+    response: P::ScalarField,
+}
+
+impl<E, P> VrfProof<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Evaluates the function keyed by `secret` on `input`, and proves the
+    /// evaluation.
+    pub fn prove<R>(
+        params: &PoseidonParameters<E::Fr>,
+        secret: P::ScalarField,
+        input: &[E::Fr],
+        rng: &mut R,
+    ) -> Self
+    where
+        R: RngCore,
+    {
+        let generator = generator::<P>();
+        let hashed =
+            hash_input::<E, P>(params, public_key::<E, P>(secret), input);
+        let gamma = hashed.mul(secret).into_affine();
+        let nonce = P::ScalarField::rand(rng);
+        let challenge = challenge_hash::<E, P>(
+            params,
+            hashed,
+            gamma,
+            generator.mul(nonce).into_affine(),
+            hashed.mul(nonce).into_affine(),
+        );
+        Self {
+            gamma,
+            challenge,
+            response: nonce + to_embedded_scalar::<E, P>(challenge) * secret,
+        }
+    }
+
+    /// Verifies the proof for `public_key` and `input`, returning the output
+    /// of the function.
+    ///
+    /// Fails with [`Error::VrfVerificationFailure`] if the proof is invalid.
+    pub fn verify(
+        &self,
+        params: &PoseidonParameters<E::Fr>,
+        public_key: GroupAffine<P>,
+        input: &[E::Fr],
+    ) -> Result<E::Fr, Error> {
+        let hashed = hash_input::<E, P>(params, public_key, input);
+        let challenge = self.challenge.into_repr();
+        let nonce_commitment = generator::<P>().mul(self.response)
+            - public_key.into_projective().mul(challenge);
+        let hashed_nonce_commitment = hashed.mul(self.response)
+            - self.gamma.into_projective().mul(challenge);
+        let expected = challenge_hash::<E, P>(
+            params,
+            hashed,
+            self.gamma,
+            nonce_commitment.into_affine(),
+            hashed_nonce_commitment.into_affine(),
+        );
+        if expected != self.challenge {
+            return Err(Error::VrfVerificationFailure);
+        }
+        Ok(self.output(params))
+    }
+
+    /// Returns the output of the function, which is only meaningful once the
+    /// proof is verified.
+    pub fn output(&self, params: &PoseidonParameters<E::Fr>) -> E::Fr {
+        let gamma = self.gamma.mul_by_cofactor();
+        params.hash(&[E::Fr::from(OUTPUT_DOMAIN), gamma.x, gamma.y])
+    }
+
+    /// Returns the hash of the input multiplied by the secret key.
+    pub fn gamma(&self) -> GroupAffine<P> {
+        self.gamma
+    }
+
+    /// Returns the challenge of the Schnorr proof.
+    pub fn challenge(&self) -> E::Fr {
+        self.challenge
+    }
+
+    /// Returns the response of the Schnorr proof.
+    pub fn response(&self) -> P::ScalarField {
+        self.response
+    }
+}
+
+/// Returns the public key of `secret`, multiplying the generator of the
+/// embedded curve.
+pub fn public_key<E, P>(secret: P::ScalarField) -> GroupAffine<P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    generator::<P>().mul(secret).into_affine()
+}
+
+/// Hashes `input` to the curve, along with `public_key`.
+fn hash_input<E, P>(
+    params: &PoseidonParameters<E::Fr>,
+    public_key: GroupAffine<P>,
+    input: &[E::Fr],
+) -> GroupAffine<P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut preimage = vec![public_key.x, public_key.y];
+    preimage.extend_from_slice(input);
+    hash_to_curve::<E, P>(params, &preimage)
+}
+
+/// Returns the challenge of the Schnorr proof, hashing all of its points.
+fn challenge_hash<E, P>(
+    params: &PoseidonParameters<E::Fr>,
+    hashed: GroupAffine<P>,
+    gamma: GroupAffine<P>,
+    nonce_commitment: GroupAffine<P>,
+    hashed_nonce_commitment: GroupAffine<P>,
+) -> E::Fr
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut preimage = vec![E::Fr::from(CHALLENGE_DOMAIN)];
+    for point in [hashed, gamma, nonce_commitment, hashed_nonce_commitment] {
+        preimage.extend([point.x, point.y]);
+    }
+    params.hash(&preimage)
+}
+
+/// Reduces `value` modulo the order of the embedded curve, as scalar
+/// multiplications of its points do.
+fn to_embedded_scalar<E, P>(value: E::Fr) -> <P as ModelParameters>::ScalarField
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    P::ScalarField::from_le_bytes_mod_order(&value.into_repr().to_bytes_le())
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the verification of the [`VrfProof`] made of `gamma`,
+    /// `challenge` and `response` for `public_key` and `input`, returning the
+    /// [`Variable`] holding the output of the function.
+    ///
+    /// The `response` is a scalar of the embedded curve, added with
+    /// [`StandardComposer::add_embedded_scalar`]. `gamma` is constrained to
+    /// be on the curve, while `public_key` is expected to be a known point,
+    /// e.g. added with [`StandardComposer::add_public_affine`], or checked
+    /// with [`StandardComposer::assert_on_curve`] by the caller.
+    pub fn vrf_verify(
+        &mut self,
+        params: &PoseidonParameters<E::Fr>,
+        public_key: Point<E, P>,
+        input: &[Variable],
+        gamma: Point<E, P>,
+        challenge: Variable,
+        response: Variable,
+    ) -> Variable {
+        // The addition gates are only a group law on the curve, so the
+        // uniqueness of the output requires `gamma` to be on it
+        self.assert_on_curve(gamma);
+
+        let mut preimage = vec![*public_key.x(), *public_key.y()];
+        preimage.extend_from_slice(input);
+        let hashed = self.hash_to_curve(params, &preimage);
+
+        let response_generator =
+            self.fixed_base_scalar_mul(response, generator::<P>());
+        let challenge_key =
+            self.variable_base_scalar_mul(challenge, public_key);
+        let challenge_key = self.point_neg(challenge_key);
+        let nonce_commitment =
+            self.point_addition_gate(response_generator, challenge_key);
+
+        let response_hashed = self.variable_base_scalar_mul(response, hashed);
+        let challenge_gamma = self.variable_base_scalar_mul(challenge, gamma);
+        let challenge_gamma = self.point_neg(challenge_gamma);
+        let hashed_nonce_commitment =
+            self.point_addition_gate(response_hashed, challenge_gamma);
+
+        let mut preimage =
+            vec![self.add_constant(E::Fr::from(CHALLENGE_DOMAIN))];
+        for point in [hashed, gamma, nonce_commitment, hashed_nonce_commitment]
+        {
+            preimage.extend([*point.x(), *point.y()]);
+        }
+        let expected = self.poseidon_hash(params, &preimage);
+        self.assert_equal(expected, challenge);
+
+        let gamma = self.clear_cofactor(gamma);
+        let domain = self.add_constant(E::Fr::from(OUTPUT_DOMAIN));
+        self.poseidon_hash(params, &[domain, *gamma.x(), *gamma.y()])
+    }
+
+    /// Constrains the negation of `point`.
+    fn point_neg(&mut self, point: Point<E, P>) -> Point<E, P> {
+        let x = self.add(
+            (-E::Fr::one(), *point.x()),
+            (E::Fr::zero(), self.zero_var),
+            E::Fr::zero(),
+            None,
+        );
+        Point::new(x, *point.y())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use rand_core::OsRng;
+
+    fn test_vrf<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = PoseidonParameters::new();
+        let secret = P::ScalarField::rand(&mut OsRng);
+        let key = public_key::<E, P>(secret);
+        let input = [E::Fr::from(11u64), E::Fr::from(13u64)];
+        let proof =
+            VrfProof::<E, P>::prove(&params, secret, &input, &mut OsRng);
+
+        // The output only depends on the key and the input
+        let output = proof.verify(&params, key, &input).unwrap();
+        let other =
+            VrfProof::<E, P>::prove(&params, secret, &input, &mut OsRng);
+        assert_ne!(proof, other);
+        assert_eq!(other.verify(&params, key, &input).unwrap(), output);
+
+        // Proofs don't verify for other inputs, keys or outputs
+        assert!(matches!(
+            proof.verify(&params, key, &input[..1]),
+            Err(Error::VrfVerificationFailure)
+        ));
+        let other_key = public_key::<E, P>(secret + P::ScalarField::one());
+        assert!(matches!(
+            proof.verify(&params, other_key, &input),
+            Err(Error::VrfVerificationFailure)
+        ));
+        let mut forged = proof;
+        forged.gamma += generator::<P>();
+        assert!(matches!(
+            forged.verify(&params, key, &input),
+            Err(Error::VrfVerificationFailure)
+        ));
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let public_key = composer.add_public_affine(key);
+                let input: Vec<Variable> =
+                    input.iter().map(|v| composer.add_input(*v)).collect();
+                let gamma = composer.add_affine(proof.gamma());
+                let challenge = composer.add_input(proof.challenge());
+                let response = composer.add_embedded_scalar(proof.response());
+                let result = composer.vrf_verify(
+                    &params, public_key, &input, gamma, challenge, response,
+                );
+                composer.constrain_to_constant(result, output, None);
+            },
+            1 << 15,
+        );
+        assert!(res.is_ok());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let public_key = composer.add_public_affine(key);
+                let input: Vec<Variable> =
+                    input.iter().map(|v| composer.add_input(*v)).collect();
+                let gamma = composer.add_affine(forged.gamma());
+                let challenge = composer.add_input(forged.challenge());
+                let response = composer.add_embedded_scalar(forged.response());
+                composer.vrf_verify(
+                    &params, public_key, &input, gamma, challenge, response,
+                );
+            },
+            1 << 15,
+        );
+        assert!(res.is_err());
+
+        // Points off the curve aren't accepted as gamma
+        let off_curve = GroupAffine::<P>::new(proof.gamma().x, proof.gamma().x);
+        assert!(!off_curve.is_on_curve());
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let public_key = composer.add_public_affine(key);
+                let input: Vec<Variable> =
+                    input.iter().map(|v| composer.add_input(*v)).collect();
+                let gamma = composer.add_affine(off_curve);
+                let challenge = composer.add_input(proof.challenge());
+                let response = composer.add_embedded_scalar(proof.response());
+                composer.vrf_verify(
+                    &params, public_key, &input, gamma, challenge, response,
+                );
+            },
+            1 << 15,
+        );
+        assert!(res.is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_vrf],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_vrf],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_vrf],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
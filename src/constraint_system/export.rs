@@ -0,0 +1,656 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Export of the constraint system for formal analysis tools.
+//!
+//! Every gate of the [`StandardComposer`] is lowered into the polynomial
+//! equations enforced by its widgets, expanded over the witness
+//! [`Variable`]s and the public inputs. Copy constraints are implicit: a
+//! [`Variable`] used by several wires is a single unknown of the system.
+//! Custom gates are split into the individual equations that their
+//! separation challenges combine, so that each one of them must vanish on its
+//! own.
+
+use crate::constraint_system::{StandardComposer, Variable};
+use alloc::collections::BTreeMap;
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{Field, PrimeField};
+use core::fmt::Write;
+use core::ops::{Add, Mul, Sub};
+use num_traits::{One, Zero};
+
+/// Unknown of the exported system.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum Symbol {
+    /// Witness [`Variable`] with the given index.
+    Witness(usize),
+    /// Public input of the gate with the given index.
+    PublicInput(usize),
+}
+
+impl core::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Witness(index) => write!(f, "w{}", index),
+            Self::PublicInput(gate) => write!(f, "pi{}", gate),
+        }
+    }
+}
+
+/// Sparse multivariate polynomial, mapping each monomial (as a sorted list of
+/// [`Symbol`]s) to its coefficient.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Poly<F>
+where
+    F: Field,
+{
+    terms: BTreeMap<Vec<Symbol>, F>,
+}
+
+impl<F> Poly<F>
+where
+    F: Field,
+{
+    fn constant(value: F) -> Self {
+        Self::monomial(Vec::new(), value)
+    }
+
+    fn symbol(symbol: Symbol) -> Self {
+        Self::monomial(vec![symbol], F::one())
+    }
+
+    fn monomial(symbols: Vec<Symbol>, coeff: F) -> Self {
+        let mut poly = Self::default();
+        if !coeff.is_zero() {
+            poly.terms.insert(symbols, coeff);
+        }
+        poly
+    }
+
+    fn add_term(&mut self, symbols: Vec<Symbol>, coeff: F) {
+        let entry = self.terms.entry(symbols).or_insert_with(F::zero);
+        *entry += coeff;
+        if entry.is_zero() {
+            self.terms.retain(|_, coeff| !coeff.is_zero());
+        }
+    }
+
+    fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.terms.keys().flatten()
+    }
+
+    /// Returns the terms of the polynomial by decreasing degree.
+    fn sorted_terms(&self) -> Vec<(&Vec<Symbol>, &F)> {
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by(|(lhs, _), (rhs, _)| {
+            rhs.len().cmp(&lhs.len()).then_with(|| lhs.cmp(rhs))
+        });
+        terms
+    }
+}
+
+impl<F> Add for Poly<F>
+where
+    F: Field,
+{
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (symbols, coeff) in other.terms {
+            self.add_term(symbols, coeff);
+        }
+        self
+    }
+}
+
+impl<F> Sub for Poly<F>
+where
+    F: Field,
+{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + other * -F::one()
+    }
+}
+
+impl<F> Mul for Poly<F>
+where
+    F: Field,
+{
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut product = Self::default();
+        for (lhs, lhs_coeff) in &self.terms {
+            for (rhs, rhs_coeff) in &other.terms {
+                let mut symbols = lhs.clone();
+                symbols.extend(rhs.iter().copied());
+                symbols.sort_unstable();
+                product.add_term(symbols, *lhs_coeff * rhs_coeff);
+            }
+        }
+        product
+    }
+}
+
+impl<F> Mul<F> for Poly<F>
+where
+    F: Field,
+{
+    type Output = Self;
+
+    fn mul(self, scalar: F) -> Self {
+        if scalar.is_zero() {
+            return Self::default();
+        }
+        Self {
+            terms: self
+                .terms
+                .into_iter()
+                .map(|(symbols, coeff)| (symbols, coeff * scalar))
+                .collect(),
+        }
+    }
+}
+
+/// Computes `f(f-1)(f-2)(f-3)`
+fn delta<F>(f: Poly<F>) -> Poly<F>
+where
+    F: Field,
+{
+    let f_1 = f.clone() - Poly::constant(F::one());
+    let f_2 = f.clone() - Poly::constant(F::from(2u64));
+    let f_3 = f.clone() - Poly::constant(F::from(3u64));
+    f * f_1 * f_2 * f_3
+}
+
+/// Symbolic version of [`delta_xor_and`](crate::proof_system::widget::logic).
+#[allow(non_snake_case)]
+fn delta_xor_and<F>(
+    a: Poly<F>,
+    b: Poly<F>,
+    w: Poly<F>,
+    c: Poly<F>,
+    q_c: F,
+) -> Poly<F>
+where
+    F: Field,
+{
+    let k = |value: u64| Poly::constant(F::from(value));
+    let a_plus_b = a.clone() + b.clone();
+    let F = w.clone()
+        * (w.clone()
+            * (w.clone() * F::from(4u64) - a_plus_b.clone() * F::from(18u64)
+                + k(81))
+            + (a.clone() * a + b.clone() * b) * F::from(18u64)
+            - a_plus_b.clone() * F::from(81u64)
+            + k(83));
+    let E = (a_plus_b.clone() + c.clone()) * F::from(3u64) - F * F::from(2u64);
+    let B = (c * F::from(9u64) - a_plus_b * F::from(3u64)) * q_c;
+    B + E
+}
+
+/// Returns the decimal representation of a little-endian integer.
+pub(super) fn to_decimal(limbs: &[u64]) -> String {
+    let mut limbs = limbs.to_vec();
+    let mut digits = Vec::new();
+    while limbs.iter().any(|limb| *limb != 0) {
+        let mut rem = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let current = (rem << 64) | (*limb as u128);
+            *limb = (current / 10) as u64;
+            rem = current % 10;
+        }
+        digits.push(b'0' + rem as u8);
+    }
+    if digits.is_empty() {
+        return "0".into();
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("decimal digits are valid UTF-8")
+}
+
+/// Returns whether `value` is closer to the modulus than to zero, together
+/// with the decimal representation of its absolute value.
+pub(super) fn signed_decimal<F>(value: F) -> (bool, String)
+where
+    F: PrimeField,
+{
+    let neg = -value;
+    if neg.into_repr() < value.into_repr() {
+        (true, to_decimal(neg.into_repr().as_ref()))
+    } else {
+        (false, to_decimal(value.into_repr().as_ref()))
+    }
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Returns the gate that follows the gate at `index` once the circuit is
+    /// padded to a power of two, or `None` if it is a padding gate.
+    fn next_gate(&self, index: usize) -> Option<usize> {
+        if index + 1 < self.n {
+            Some(index + 1)
+        } else if self.n.is_power_of_two() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// Lowers every gate of the circuit into the polynomial equations its
+    /// widgets enforce, returned together with the index of their gate.
+    fn constraint_polynomials(&self) -> Vec<(usize, Poly<E::Fr>)> {
+        let wire = |wires: &[Variable], index: Option<usize>| {
+            let var = index.map_or(self.zero_var, |index| wires[index]);
+            Poly::symbol(Symbol::Witness(var.0))
+        };
+        let k = |value: u64| Poly::constant(E::Fr::from(value));
+        let four = E::Fr::from(4u64);
+        let coeff_a = P::COEFF_A;
+        let coeff_d = P::COEFF_D;
+
+        let mut constraints = Vec::new();
+        for i in 0..self.n {
+            let next = self.next_gate(i);
+            let a = wire(&self.w_l, Some(i));
+            let b = wire(&self.w_r, Some(i));
+            let c = wire(&self.w_o, Some(i));
+            let d = wire(&self.w_4, Some(i));
+            let a_next = wire(&self.w_l, next);
+            let b_next = wire(&self.w_r, next);
+            let d_next = wire(&self.w_4, next);
+            let q_c = self.q_c[i];
+
+            let mut gate = Vec::new();
+
+            // Arithmetic
+            let pi = match self.public_inputs_sparse_store.get(&i) {
+                Some(_) => Poly::symbol(Symbol::PublicInput(i)),
+                None => Poly::default(),
+            };
+            gate.push(
+                (a.clone() * b.clone() * self.q_m[i]
+                    + a.clone() * self.q_l[i]
+                    + b.clone() * self.q_r[i]
+                    + c.clone() * self.q_o[i]
+                    + d.clone() * self.q_4[i]
+                    + a_next.clone() * self.q_lookahead[i]
+                    + Poly::constant(q_c))
+                    * self.q_arith[i]
+                    + pi,
+            );
+
+            // Range
+            let q_range = self.q_range[i];
+            if !q_range.is_zero() {
+                gate.extend(
+                    [
+                        delta(c.clone() - d.clone() * four),
+                        delta(b.clone() - c.clone() * four),
+                        delta(a.clone() - b.clone() * four),
+                        delta(d_next.clone() - a.clone() * four),
+                    ]
+                    .into_iter()
+                    .map(|poly| poly * q_range),
+                );
+            }
+
+            // Logic
+            let q_logic = self.q_logic[i];
+            if !q_logic.is_zero() {
+                let a_quad = a_next.clone() - a.clone() * four;
+                let b_quad = b_next.clone() - b.clone() * four;
+                let d_quad = d_next.clone() - d.clone() * four;
+                gate.extend(
+                    [
+                        delta(a_quad.clone()),
+                        delta(b_quad.clone()),
+                        delta(d_quad.clone()),
+                        c.clone() - a_quad.clone() * b_quad.clone(),
+                        delta_xor_and(a_quad, b_quad, c.clone(), d_quad, q_c),
+                    ]
+                    .into_iter()
+                    .map(|poly| poly * q_logic),
+                );
+            }
+
+            // Fixed base scalar multiplication
+            let q_fixed = self.q_fixed_group_add[i];
+            if !q_fixed.is_zero() {
+                let x_beta = self.q_l[i];
+                let y_beta = self.q_r[i];
+                let bit = d_next.clone() - d.clone() * E::Fr::from(2u64);
+                let y_alpha =
+                    bit.clone() * bit.clone() * (y_beta - E::Fr::one()) + k(1);
+                let x_alpha = bit.clone() * x_beta;
+                let acc = c.clone() * a.clone() * b.clone() * coeff_d;
+                gate.extend(
+                    [
+                        bit.clone()
+                            * (bit.clone() - k(1))
+                            * (bit.clone() + k(1)),
+                        bit * q_c - c.clone(),
+                        a_next.clone() + a_next.clone() * acc.clone()
+                            - (x_alpha.clone() * b.clone()
+                                + y_alpha.clone() * a.clone()),
+                        b_next.clone()
+                            - b_next.clone() * acc
+                            - (y_alpha * b.clone()
+                                - x_alpha * a.clone() * coeff_a),
+                    ]
+                    .into_iter()
+                    .map(|poly| poly * q_fixed),
+                );
+            }
+
+            // Variable base curve addition
+            let q_variable = self.q_variable_group_add[i];
+            if !q_variable.is_zero() {
+                let (x_1, y_1, x_2, y_2) = (a.clone(), b.clone(), c, d);
+                let (x_3, y_3, x1_y2) = (a_next, b_next, d_next);
+                let y1_x2 = y_1.clone() * x_2.clone();
+                let cross = x1_y2.clone() * y1_x2.clone() * coeff_d;
+                gate.extend(
+                    [
+                        x_1.clone() * y_2.clone() - x1_y2.clone(),
+                        x1_y2 + y1_x2 - (x_3.clone() + x_3 * cross.clone()),
+                        y_1 * y_2
+                            - x_1 * x_2 * coeff_a
+                            - (y_3.clone() - y_3 * cross),
+                    ]
+                    .into_iter()
+                    .map(|poly| poly * q_variable),
+                );
+            }
+
+            constraints.extend(
+                gate.into_iter()
+                    .filter(|poly| !poly.terms.is_empty())
+                    .map(|poly| (i, poly)),
+            );
+        }
+        constraints
+    }
+
+    /// Exports the constraint system as an SMT-LIB 2 script over the theory
+    /// of finite fields (`QF_FF`), to be consumed by constraint-analysis
+    /// tools such as under-constraint detectors.
+    ///
+    /// The witness [`Variable`]s are declared as `w<index>` and the public
+    /// input of the gate at position `i` as `pi<i>`. Every gate is lowered
+    /// into the polynomial equations enforced by its widgets and each
+    /// equation is asserted to be zero. Copy constraints are implicit since a
+    /// [`Variable`] shared by several wires is a single unknown.
+    pub fn to_smtlib(&self) -> String {
+        let constraints = self.constraint_polynomials();
+        let mut out = String::new();
+        let constant = |value: E::Fr| match signed_decimal(value) {
+            (false, abs) => format!("(as ff{} F)", abs),
+            (true, abs) => format!("(ff.neg (as ff{} F))", abs),
+        };
+
+        writeln!(out, "; ark-plonk constraint system").unwrap();
+        writeln!(out, "; gates: {}", self.n).unwrap();
+        writeln!(out, "(set-logic QF_FF)").unwrap();
+        writeln!(
+            out,
+            "(define-sort F () (_ FiniteField {}))",
+            to_decimal(<E::Fr as Field>::characteristic())
+        )
+        .unwrap();
+        for symbol in self.exported_symbols(&constraints) {
+            writeln!(out, "(declare-fun {} () F)", symbol).unwrap();
+        }
+
+        let mut last_gate = None;
+        for (gate, poly) in constraints {
+            if last_gate != Some(gate) {
+                writeln!(out, "; gate {}", gate).unwrap();
+                last_gate = Some(gate);
+            }
+            let terms: Vec<String> = poly
+                .sorted_terms()
+                .into_iter()
+                .map(|(symbols, coeff)| {
+                    let mut factors: Vec<String> =
+                        symbols.iter().map(ToString::to_string).collect();
+                    if symbols.is_empty() || !coeff.is_one() {
+                        factors.insert(0, constant(*coeff));
+                    }
+                    match factors.len() {
+                        1 => factors.remove(0),
+                        _ => format!("(ff.mul {})", factors.join(" ")),
+                    }
+                })
+                .collect();
+            let sum = match terms.len() {
+                1 => terms[0].clone(),
+                _ => format!("(ff.add {})", terms.join(" ")),
+            };
+            writeln!(out, "(assert (= {} {}))", sum, constant(E::Fr::zero()))
+                .unwrap();
+        }
+        out
+    }
+
+    /// Exports the constraint system as a list of polynomial equations over
+    /// the scalar field, one per line, preceded by a comment with the
+    /// modulus and the gate each group of equations comes from.
+    ///
+    /// The unknowns are named like in [`StandardComposer::to_smtlib`] and
+    /// coefficients are written as signed decimal integers.
+    pub fn to_polynomials(&self) -> String {
+        let constraints = self.constraint_polynomials();
+        let mut out = String::new();
+
+        writeln!(
+            out,
+            "# modulus: {}",
+            to_decimal(<E::Fr as Field>::characteristic())
+        )
+        .unwrap();
+        let mut last_gate = None;
+        for (gate, poly) in constraints {
+            if last_gate != Some(gate) {
+                writeln!(out, "# gate {}", gate).unwrap();
+                last_gate = Some(gate);
+            }
+            let mut line = String::new();
+            for (index, (symbols, coeff)) in
+                poly.sorted_terms().into_iter().enumerate()
+            {
+                let (negative, abs) = signed_decimal(*coeff);
+                match (index, negative) {
+                    (0, true) => line.push('-'),
+                    (0, false) => {}
+                    (_, true) => line.push_str(" - "),
+                    (_, false) => line.push_str(" + "),
+                }
+                let mut factors: Vec<String> =
+                    symbols.iter().map(ToString::to_string).collect();
+                if symbols.is_empty() || abs != "1" {
+                    factors.insert(0, abs);
+                }
+                line.push_str(&factors.join("*"));
+            }
+            writeln!(out, "{} = 0", line).unwrap();
+        }
+        out
+    }
+
+    /// Returns the sorted unknowns used by `constraints`.
+    fn exported_symbols(
+        &self,
+        constraints: &[(usize, Poly<E::Fr>)],
+    ) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = constraints
+            .iter()
+            .flat_map(|(_, poly)| poly.symbols().copied())
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::constraint_system::ecc::Point;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::twisted_edwards_extended::GroupAffine;
+
+    /// Evaluates `poly` on the witness and public inputs of `composer`.
+    fn evaluate<E, P>(
+        poly: &Poly<E::Fr>,
+        composer: &StandardComposer<E, P>,
+    ) -> E::Fr
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        poly.terms
+            .iter()
+            .map(|(symbols, coeff)| {
+                symbols.iter().fold(*coeff, |acc, symbol| {
+                    acc * match symbol {
+                        Symbol::Witness(index) => composer.variables[*index],
+                        Symbol::PublicInput(gate) => {
+                            composer.public_inputs_sparse_store[gate]
+                        }
+                    }
+                })
+            })
+            .sum()
+    }
+
+    fn gadget<E, P>(composer: &mut StandardComposer<E, P>)
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let a = composer.add_input(E::Fr::from(500u64));
+        let b = composer.add_input(E::Fr::from(357u64));
+        let sum = composer.add(
+            (E::Fr::one(), a),
+            (E::Fr::one(), b),
+            E::Fr::zero(),
+            Some(-E::Fr::from(1u64)),
+        );
+        composer.range_gate(sum, 16);
+        composer.xor_gate(a, b, 10);
+        composer.and_gate(a, b, 10);
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator = GroupAffine::<P>::new(x, y);
+        let point = composer.fixed_base_scalar_mul(b, generator);
+        let doubled: Point<E, P> = composer.point_addition_gate(point, point);
+        composer.variable_base_scalar_mul(a, doubled);
+    }
+
+    fn test_exported_constraints_hold<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut composer: StandardComposer<E, P> = StandardComposer::new();
+        gadget(&mut composer);
+
+        let constraints = composer.constraint_polynomials();
+        assert!(constraints.len() > composer.circuit_size());
+        for (gate, poly) in &constraints {
+            assert!(
+                evaluate(poly, &composer).is_zero(),
+                "constraint of gate {} doesn't hold",
+                gate
+            );
+        }
+
+        // A wrong witness breaks some of the exported equations
+        let a = Variable(composer.variables.len() - 1);
+        composer.variables[a.0] += E::Fr::one();
+        assert!(constraints
+            .iter()
+            .any(|(_, poly)| !evaluate(poly, &composer).is_zero()));
+    }
+
+    fn test_export_formats<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut composer: StandardComposer<E, P> = StandardComposer::new();
+        let a = composer.add_input(E::Fr::from(2u64));
+        let b = composer.add_input(E::Fr::from(3u64));
+        let c = composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+        composer.constrain_to_constant(c, E::Fr::from(6u64), None);
+
+        let smt = composer.to_smtlib();
+        assert!(smt.contains("(set-logic QF_FF)"));
+        assert!(smt.contains(&format!(
+            "(define-sort F () (_ FiniteField {}))",
+            to_decimal(<E::Fr as Field>::characteristic())
+        )));
+        assert!(smt.contains(&format!("(declare-fun w{} () F)", c.0)));
+        assert!(smt.contains(&format!(
+            "(assert (= (ff.add (ff.mul w{} w{}) (ff.mul (ff.neg (as ff1 F)) w{})) (as ff0 F)))",
+            a.0, b.0, c.0
+        )));
+
+        let polys = composer.to_polynomials();
+        assert!(polys.contains(&format!("w{}*w{} - w{} = 0", a.0, b.0, c.0)));
+        assert!(polys.contains(&format!("w{} - 6 = 0", c.0)));
+    }
+
+    #[test]
+    fn test_to_decimal() {
+        assert_eq!(to_decimal(&[0, 0]), "0");
+        assert_eq!(to_decimal(&[42]), "42");
+        assert_eq!(to_decimal(&[0, 1]), "18446744073709551616");
+        assert_eq!(
+            to_decimal(&[u64::MAX, u64::MAX]),
+            "340282366920938463463374607431768211455"
+        );
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_exported_constraints_hold, test_export_formats],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_exported_constraints_hold, test_export_formats],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_exported_constraints_hold, test_export_formats],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -4,6 +4,9 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+//! Helpers to test gadgets, exposed by the `test-utils` feature together with
+//! the [`batch_test`](crate::batch_test) macro.
+
 use super::StandardComposer;
 use crate::error::Error;
 use crate::proof_system::{Prover, Verifier};
@@ -16,11 +19,8 @@ use num_traits::{One, Zero};
 use rand_core::OsRng;
 
 /// Adds dummy constraints using arithmetic gates.
-#[allow(dead_code)]
-pub(crate) fn dummy_gadget<E, P>(
-    n: usize,
-    composer: &mut StandardComposer<E, P>,
-) where
+pub fn dummy_gadget<E, P>(n: usize, composer: &mut StandardComposer<E, P>)
+where
     E: PairingEngine,
     P: TEModelParameters<BaseField = E::Fr>,
 {
@@ -37,16 +37,17 @@ pub(crate) fn dummy_gadget<E, P>(
     }
 }
 
-/// Takes a generic gadget function with no auxillary input and tests whether it
-/// passes an end-to-end test.
-#[allow(dead_code)]
-pub(crate) fn gadget_tester<E, P>(
-    gadget: fn(&mut StandardComposer<E, P>),
-    n: usize,
-) -> Result<(), Error>
+/// Takes a gadget with no auxillary input and tests whether it passes an
+/// end-to-end test: the circuit it builds is preprocessed, proven and
+/// verified with universal parameters of degree `2 * n`.
+///
+/// The gadget is called once by the prover and once by the verifier, and
+/// must build the same circuit both times.
+pub fn gadget_tester<E, P, F>(gadget: F, n: usize) -> Result<(), Error>
 where
     E: PairingEngine,
     P: TEModelParameters<BaseField = E::Fr>,
+    F: Fn(&mut StandardComposer<E, P>),
 {
     // Common View
     let universal_params =
@@ -68,8 +69,7 @@ where
             prover.circuit_size().next_power_of_two(),
             0,
             None,
-        )
-        .unwrap();
+        )?;
         let powers = Powers {
             powers_of_g: ck.powers_of_g.into(),
             powers_of_gamma_g: ck.powers_of_gamma_g.into(),
@@ -101,8 +101,7 @@ where
         verifier.circuit_size().next_power_of_two(),
         0,
         None,
-    )
-    .unwrap();
+    )?;
     let powers = Powers {
         powers_of_g: sonic_ck.powers_of_g.into(),
         powers_of_gamma_g: sonic_ck.powers_of_gamma_g.into(),
@@ -122,3 +121,49 @@ where
     // Verify proof
     verifier.verify(&proof, &vk, &public_inputs)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+
+    /// Tests that the gadget can capture its environment.
+    fn test_capturing_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let square = |value: u64| {
+            move |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(value));
+                let b = composer.mul(E::Fr::one(), a, a, E::Fr::zero(), None);
+                composer.constrain_to_constant(b, E::Fr::from(9u64), None);
+                dummy_gadget(8, composer);
+            }
+        };
+        assert!(gadget_tester(square(3), 64).is_ok());
+        assert!(gadget_tester(square(4), 64).is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_capturing_gadget],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_capturing_gadget],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
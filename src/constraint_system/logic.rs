@@ -9,7 +9,9 @@
 //! This module includes a generic logic gate that can either be an `XOR` or an
 //! `AND` gate.
 
-use crate::constraint_system::{StandardComposer, Variable, WireData};
+use crate::constraint_system::{
+    StandardComposer, Variable, WireData, WordVariable,
+};
 use ark_ec::{PairingEngine, TEModelParameters};
 use ark_ff::{BigInteger, PrimeField};
 use num_traits::{One, Zero};
@@ -53,21 +55,17 @@ where
         let mut out_accumulator = E::Fr::zero();
         let mut left_quad: u8;
         let mut right_quad: u8;
-        // Get vars as bits and reverse them to get the Little Endian repr.
-        let a_bits: Vec<_> = self.variables[&a]
-            .into_repr()
-            .to_bits_be()
-            .iter()
-            .skip(256 - num_bits)
-            .map(|bit| *bit as u8)
-            .collect();
-        let b_bits: Vec<_> = self.variables[&b]
-            .into_repr()
-            .to_bits_be()
-            .iter()
-            .skip(256 - num_bits)
-            .map(|bit| *bit as u8)
-            .collect();
+        // Get the `num_bits` least significant bits of the vars, most
+        // significant first.
+        let low_bits = |var: &Variable| -> Vec<u8> {
+            let bits = self.variables[var.0].into_repr().to_bits_be();
+            bits.iter()
+                .skip(bits.len().saturating_sub(num_bits))
+                .map(|bit| *bit as u8)
+                .collect()
+        };
+        let a_bits = low_bits(&a);
+        let b_bits = low_bits(&b);
         assert!(a_bits.len() >= num_bits);
         assert!(b_bits.len() >= num_bits);
 
@@ -248,6 +246,7 @@ where
             self.q_range.push(E::Fr::zero());
             self.q_fixed_group_add.push(E::Fr::zero());
             self.q_variable_group_add.push(E::Fr::zero());
+            self.q_lookahead.push(E::Fr::zero());
             match is_xor_gate {
                 true => {
                     self.q_c.push(-E::Fr::one());
@@ -269,6 +268,7 @@ where
         self.q_range.push(E::Fr::zero());
         self.q_fixed_group_add.push(E::Fr::zero());
         self.q_variable_group_add.push(E::Fr::zero());
+        self.q_lookahead.push(E::Fr::zero());
 
         self.q_c.push(E::Fr::zero());
         self.q_logic.push(E::Fr::zero());
@@ -292,17 +292,17 @@ where
         // slower just for the check. We should think about it.
 
         // assert_eq!(
-        //     self.variables[&a].into_repr()
+        //     self.variables[a.0].into_repr()
         //         & (E::Fr::from(2u64).pow(&[(num_bits) as u64, 0, 0, 0])
         //             - E::Fr::one())
         //         .into_repr(),
-        //     self.variables[&self.w_l[self.n - 1]]
+        //     self.variables[self.w_l[self.n - 1].0]
         // );
         // assert_eq!(
-        //     self.variables[&b]
+        //     self.variables[b.0]
         //         & (E::Fr::from(2u64).pow(&[(num_bits) as u64, 0, 0, 0])
         //             - E::Fr::one()),
-        //     self.variables[&self.w_r[self.n - 1]]
+        //     self.variables[self.w_r[self.n - 1].0]
         // );
 
         // Once the inputs are checked against the accumulated additions,
@@ -342,6 +342,48 @@ where
     ) -> Variable {
         self.logic_gate(a, b, num_bits, false)
     }
+
+    /// Adds a [`StandardComposer::xor_gate`] between two [`WordVariable`]s of
+    /// the same width, returning their XOR as a word of that width.
+    ///
+    /// # Panics
+    ///
+    /// If `a` and `b` don't have the same number of bits.
+    pub fn xor_words(
+        &mut self,
+        a: WordVariable,
+        b: WordVariable,
+    ) -> WordVariable {
+        self.logic_words(a, b, true)
+    }
+
+    /// Adds a [`StandardComposer::and_gate`] between two [`WordVariable`]s of
+    /// the same width, returning their bitwise AND as a word of that width.
+    ///
+    /// # Panics
+    ///
+    /// If `a` and `b` don't have the same number of bits.
+    pub fn and_words(
+        &mut self,
+        a: WordVariable,
+        b: WordVariable,
+    ) -> WordVariable {
+        self.logic_words(a, b, false)
+    }
+
+    /// Adds a logic gate between two words of the same width.
+    fn logic_words(
+        &mut self,
+        a: WordVariable,
+        b: WordVariable,
+        is_xor_gate: bool,
+    ) -> WordVariable {
+        assert_eq!(a.bits, b.bits, "the words must have the same width");
+        WordVariable {
+            var: self.logic_gate(a.var, b.var, a.bits, is_xor_gate),
+            bits: a.bits,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -351,6 +393,7 @@ mod test {
     use crate::{batch_test, constraint_system::StandardComposer};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
 
     fn test_logic_xor_and_constraint<E, P>()
     where
@@ -449,9 +492,39 @@ mod test {
         );
     }
 
+    fn test_logic_words<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(500u64));
+                let a = composer.range_gate(a, 10);
+                let b = composer.add_input(E::Fr::from(357u64));
+                let b = composer.range_gate(b, 10);
+                let xor = composer.xor_words(a, b);
+                let and = composer.and_words(a, b);
+                assert_eq!((xor.bits(), and.bits()), (10, 10));
+                composer.constrain_to_constant(
+                    xor.variable(),
+                    E::Fr::from(500u64 ^ 357u64),
+                    None,
+                );
+                composer.constrain_to_constant(
+                    and.variable(),
+                    E::Fr::from(500u64 & 357u64),
+                    None,
+                );
+            },
+            200,
+        );
+        assert!(res.is_ok(), "{:?}", res.err().unwrap());
+    }
+
     // Test for Bls12_381
     batch_test!(
-        [test_logic_xor_and_constraint],
+        [test_logic_xor_and_constraint, test_logic_words],
         [test_logical_gate_odd_bit_num]
         => (
             Bls12_381,
@@ -461,11 +534,21 @@ mod test {
 
     // Test for Bls12_377
     batch_test!(
-        [test_logic_xor_and_constraint],
+        [test_logic_xor_and_constraint, test_logic_words],
         [test_logical_gate_odd_bit_num]
         => (
             Bls12_377,
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Test for Bn254
+    batch_test!(
+        [test_logic_xor_and_constraint, test_logic_words],
+        [test_logical_gate_odd_bit_num]
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
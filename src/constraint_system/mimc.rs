@@ -0,0 +1,535 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! MiMC and GMiMC hash gadgets.
+//!
+//! [`MimcParameters`] define the MiMC-7 Feistel permutation of two field
+//! elements under a key, and a sponge absorbing one element per
+//! permutation. [`GmimcParameters`] define the GMiMC-erf permutation, a
+//! generalized Feistel network over the same three element state as the
+//! [Poseidon](super::PoseidonParameters) hash, and a sponge with the same
+//! padding. Each of them is constrained in circuit by a method of the
+//! [`StandardComposer`] computing the same values.
+//!
+//! Both apply a single power per round, so that they need fewer gates per
+//! round than the other hashes, at the cost of more rounds.
+
+use super::poseidon::{sbox_exponent, RATE, WIDTH};
+use crate::constraint_system::{StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use merlin::Transcript;
+use num_traits::{One, Zero};
+
+/// Exponent of the round function of MiMC-7.
+const MIMC_ALPHA: u64 = 7;
+
+/// Parameters of the MiMC-7 Feistel permutation over `F`.
+///
+/// They are derived deterministically from the field, so the prover and the
+/// verifier always agree on them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MimcParameters<F>
+where
+    F: PrimeField,
+{
+    /// Constants added to the left element in each round
+    round_constants: Vec<F>,
+}
+
+impl<F> MimcParameters<F>
+where
+    F: PrimeField,
+{
+    /// Derives the parameters for `F`.
+    ///
+    /// The Feistel network has twice as many rounds as needed for `x^7` to
+    /// reach the degree of the field, and its round constants are squeezed
+    /// from a transcript. The network is a permutation even on the fields
+    /// where `x^7` isn't.
+    pub fn new() -> Self {
+        let rounds = 2 * degree_rounds::<F>(MIMC_ALPHA);
+        Self {
+            round_constants: round_constants(
+                b"ark-plonk-mimc",
+                MIMC_ALPHA,
+                rounds,
+            ),
+        }
+    }
+
+    /// Returns the exponent of the round function.
+    pub fn alpha(&self) -> u64 {
+        MIMC_ALPHA
+    }
+
+    /// Returns the number of rounds of the Feistel network.
+    pub fn rounds(&self) -> usize {
+        self.round_constants.len()
+    }
+
+    /// Applies the Feistel permutation keyed by `key` to `(left, right)`.
+    ///
+    /// Every round maps `(l, r)` to `(r + (l + key + c)^7, l)`.
+    pub fn feistel(&self, left: F, right: F, key: F) -> (F, F) {
+        self.round_constants.iter().fold(
+            (left, right),
+            |(left, right), constant| {
+                (right + (left + key + constant).pow([MIMC_ALPHA]), left)
+            },
+        )
+    }
+
+    /// Hashes `inputs` into a single field element, with a sponge absorbing
+    /// one input into the left element per permutation, keyed by zero.
+    ///
+    /// The right element of the state is initialized with the number of
+    /// inputs, so inputs of different lengths never collide by padding.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = (F::zero(), F::from(inputs.len() as u64));
+        for input in inputs {
+            state = self.feistel(state.0 + input, state.1, F::zero());
+        }
+        if inputs.is_empty() {
+            state = self.feistel(state.0, state.1, F::zero());
+        }
+        state.0
+    }
+}
+
+impl<F> Default for MimcParameters<F>
+where
+    F: PrimeField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parameters of the GMiMC-erf permutation over `F`.
+///
+/// They are derived deterministically from the field, so the prover and the
+/// verifier always agree on them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GmimcParameters<F>
+where
+    F: PrimeField,
+{
+    /// Exponent of the round function
+    alpha: u64,
+
+    /// Constants added to the first element in each round
+    round_constants: Vec<F>,
+}
+
+impl<F> GmimcParameters<F>
+where
+    F: PrimeField,
+{
+    /// Derives the parameters for `F`.
+    ///
+    /// The exponent is the S-box exponent of Poseidon, and the network has
+    /// as many rounds as needed for it to reach the degree of the field,
+    /// plus two for every element of the state so that each of them depends
+    /// on all the others. The round constants are squeezed from a
+    /// transcript.
+    pub fn new() -> Self {
+        let alpha = sbox_exponent::<F>();
+        let rounds = degree_rounds::<F>(alpha) + 2 * WIDTH;
+        Self {
+            alpha,
+            round_constants: round_constants(b"ark-plonk-gmimc", alpha, rounds),
+        }
+    }
+
+    /// Returns the exponent of the round function.
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /// Returns the number of rounds of the permutation.
+    pub fn rounds(&self) -> usize {
+        self.round_constants.len()
+    }
+
+    /// Hashes `inputs` into a single field element.
+    ///
+    /// The capacity element of the state is initialized with the number of
+    /// inputs, so inputs of different lengths never collide by padding.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = [F::zero(); WIDTH];
+        state[0] = F::from(inputs.len() as u64);
+        for chunk in inputs.chunks(RATE) {
+            state[1..]
+                .iter_mut()
+                .zip(chunk)
+                .for_each(|(element, input)| *element += input);
+            self.permute(&mut state);
+        }
+        if inputs.is_empty() {
+            self.permute(&mut state);
+        }
+        state[1]
+    }
+
+    /// Applies the GMiMC-erf permutation to `state`: every round adds
+    /// `(x_0 + c)^alpha` to the other elements and rotates the state.
+    fn permute(&self, state: &mut [F; WIDTH]) {
+        for constant in &self.round_constants {
+            let power = (state[0] + constant).pow([self.alpha]);
+            state[1..].iter_mut().for_each(|element| *element += power);
+            state.rotate_left(1);
+        }
+    }
+}
+
+impl<F> Default for GmimcParameters<F>
+where
+    F: PrimeField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the smallest number of rounds `r` such that `alpha^r` has more
+/// bits than the modulus of `F`, and thus exceeds it.
+fn degree_rounds<F>(alpha: u64) -> usize
+where
+    F: PrimeField,
+{
+    let target_bits = F::size_in_bits() as u32;
+    let mut power = vec![1u64];
+    let mut rounds = 0;
+    loop {
+        let bits = 64 * power.len() as u32
+            - power.last().copied().unwrap_or(0).leading_zeros();
+        if bits > target_bits {
+            return rounds;
+        }
+        let mut carry = 0u128;
+        for limb in power.iter_mut() {
+            let product = *limb as u128 * alpha as u128 + carry;
+            *limb = product as u64;
+            carry = product >> 64;
+        }
+        if carry != 0 {
+            power.push(carry as u64);
+        }
+        rounds += 1;
+    }
+}
+
+/// Squeezes `rounds` round constants from a transcript labelled `label`.
+fn round_constants<F>(label: &'static [u8], alpha: u64, rounds: usize) -> Vec<F>
+where
+    F: PrimeField,
+{
+    let mut transcript = Transcript::new(label);
+    transcript.append_u64(b"alpha", alpha);
+    transcript.append_u64(b"rounds", rounds as u64);
+    (0..rounds)
+        .map(|_| {
+            let mut bytes = [0u8; 64];
+            transcript.challenge_bytes(b"round_constant", &mut bytes);
+            F::from_le_bytes_mod_order(&bytes)
+        })
+        .collect()
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the MiMC-7 Feistel permutation keyed by `key` of
+    /// `(left, right)`, returning the [`Variable`]s holding the result of
+    /// [`MimcParameters::feistel`].
+    pub fn mimc_feistel(
+        &mut self,
+        params: &MimcParameters<E::Fr>,
+        left: Variable,
+        right: Variable,
+        key: Variable,
+    ) -> (Variable, Variable) {
+        let mut state = (left, right);
+        for constant in &params.round_constants {
+            let shifted = self.add(
+                (E::Fr::one(), state.0),
+                (E::Fr::one(), key),
+                *constant,
+                None,
+            );
+            let power = self.poseidon_sbox(MIMC_ALPHA, shifted);
+            let sum = self.add(
+                (E::Fr::one(), state.1),
+                (E::Fr::one(), power),
+                E::Fr::zero(),
+                None,
+            );
+            state = (sum, state.0);
+        }
+        state
+    }
+
+    /// Constrains the MiMC-7 sponge hash of `inputs` with `params`,
+    /// returning the [`Variable`] holding the result of
+    /// [`MimcParameters::hash`].
+    pub fn mimc_hash(
+        &mut self,
+        params: &MimcParameters<E::Fr>,
+        inputs: &[Variable],
+    ) -> Variable {
+        let zero = self.zero_var;
+        let length = self.add(
+            (E::Fr::zero(), zero),
+            (E::Fr::zero(), zero),
+            E::Fr::from(inputs.len() as u64),
+            None,
+        );
+        let mut state = (zero, length);
+        for input in inputs {
+            let left = self.add(
+                (E::Fr::one(), state.0),
+                (E::Fr::one(), *input),
+                E::Fr::zero(),
+                None,
+            );
+            state = self.mimc_feistel(params, left, state.1, zero);
+        }
+        if inputs.is_empty() {
+            state = self.mimc_feistel(params, state.0, state.1, zero);
+        }
+        state.0
+    }
+
+    /// Constrains the GMiMC-erf hash of `inputs` with `params`, returning
+    /// the [`Variable`] holding the result of [`GmimcParameters::hash`].
+    pub fn gmimc_hash(
+        &mut self,
+        params: &GmimcParameters<E::Fr>,
+        inputs: &[Variable],
+    ) -> Variable {
+        let zero = self.zero_var;
+        let length = self.add(
+            (E::Fr::zero(), zero),
+            (E::Fr::zero(), zero),
+            E::Fr::from(inputs.len() as u64),
+            None,
+        );
+        let mut state = [length, zero, zero];
+        for chunk in inputs.chunks(RATE) {
+            for (element, input) in state[1..].iter_mut().zip(chunk) {
+                *element = self.add(
+                    (E::Fr::one(), *element),
+                    (E::Fr::one(), *input),
+                    E::Fr::zero(),
+                    None,
+                );
+            }
+            self.gmimc_permutation(params, &mut state);
+        }
+        if inputs.is_empty() {
+            self.gmimc_permutation(params, &mut state);
+        }
+        state[1]
+    }
+
+    /// Constrains the GMiMC-erf permutation of `state`.
+    fn gmimc_permutation(
+        &mut self,
+        params: &GmimcParameters<E::Fr>,
+        state: &mut [Variable; WIDTH],
+    ) {
+        let zero = self.zero_var;
+        for constant in &params.round_constants {
+            let shifted = self.add(
+                (E::Fr::one(), state[0]),
+                (E::Fr::zero(), zero),
+                *constant,
+                None,
+            );
+            let power = self.poseidon_sbox(params.alpha, shifted);
+            for element in state[1..].iter_mut() {
+                *element = self.add(
+                    (E::Fr::one(), *element),
+                    (E::Fr::one(), power),
+                    E::Fr::zero(),
+                    None,
+                );
+            }
+            state.rotate_left(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::constraint_system::helper::*;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    fn test_mimc_hash<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = MimcParameters::<E::Fr>::new();
+        let bits = E::Fr::size_in_bits();
+        assert_eq!(degree_rounds::<E::Fr>(2), bits);
+        assert_eq!(params.rounds(), 2 * degree_rounds::<E::Fr>(MIMC_ALPHA));
+
+        // The gadget agrees with the Feistel permutation
+        let [left, right, key] = [3u64, 5, 7].map(E::Fr::from);
+        let mut composer = StandardComposer::<E, P>::new();
+        let vars = [left, right, key].map(|value| composer.add_input(value));
+        let (l, r) = composer.mimc_feistel(&params, vars[0], vars[1], vars[2]);
+        let expected = params.feistel(left, right, key);
+        assert_eq!(
+            (composer.variables[l.0], composer.variables[r.0]),
+            expected
+        );
+        assert_ne!(expected, params.feistel(left, right, E::Fr::zero()));
+
+        // The gadget agrees with the sponge for every input length
+        let mut hashes = Vec::new();
+        for length in 0..4u64 {
+            let values: Vec<E::Fr> =
+                (0..length).map(|i| E::Fr::from(i + 1)).collect();
+            let mut composer = StandardComposer::<E, P>::new();
+            let inputs: Vec<Variable> =
+                values.iter().map(|v| composer.add_input(*v)).collect();
+            let hash = composer.mimc_hash(&params, &inputs);
+            assert_eq!(composer.variables[hash.0], params.hash(&values));
+            hashes.push(params.hash(&values));
+        }
+        assert_ne!(hashes[1], params.hash(&[E::Fr::from(1u64), E::Fr::zero()]));
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = MimcParameters::new();
+                let values = [E::Fr::from(3u64), E::Fr::from(5u64)];
+                let inputs: Vec<Variable> =
+                    values.iter().map(|v| composer.add_input(*v)).collect();
+                let hash = composer.mimc_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&values),
+                    None,
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_ok());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = MimcParameters::new();
+                let inputs = [composer.add_input(E::Fr::from(3u64))];
+                let hash = composer.mimc_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&[E::Fr::from(4u64)]),
+                    None,
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_gmimc_hash<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = GmimcParameters::<E::Fr>::new();
+        assert!(params.rounds() > 2 * WIDTH);
+
+        // The gadget agrees with the native hash for every input length
+        let mut hashes = Vec::new();
+        for length in 0..5u64 {
+            let values: Vec<E::Fr> =
+                (0..length).map(|i| E::Fr::from(i + 1)).collect();
+            let mut composer = StandardComposer::<E, P>::new();
+            let inputs: Vec<Variable> =
+                values.iter().map(|v| composer.add_input(*v)).collect();
+            let hash = composer.gmimc_hash(&params, &inputs);
+            assert_eq!(composer.variables[hash.0], params.hash(&values));
+            hashes.push(params.hash(&values));
+        }
+        assert_ne!(hashes[1], params.hash(&[E::Fr::from(1u64), E::Fr::zero()]));
+        assert_ne!(
+            hashes[2],
+            MimcParameters::new().hash(&[E::Fr::from(1u64), E::Fr::from(2u64)])
+        );
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = GmimcParameters::new();
+                let values = [E::Fr::from(3u64), E::Fr::from(5u64)];
+                let inputs: Vec<Variable> =
+                    values.iter().map(|v| composer.add_input(*v)).collect();
+                let hash = composer.gmimc_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&values),
+                    None,
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_ok());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = GmimcParameters::new();
+                let inputs = [composer.add_input(E::Fr::from(3u64))];
+                let hash = composer.gmimc_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&[E::Fr::from(4u64)]),
+                    None,
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_mimc_hash, test_gmimc_hash],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_mimc_hash, test_gmimc_hash],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_mimc_hash, test_gmimc_hash],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
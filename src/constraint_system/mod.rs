@@ -10,16 +10,35 @@
 
 mod arithmetic;
 mod boolean;
+mod description;
+mod export;
 mod logic;
+mod mimc;
+mod optimize;
+mod poseidon;
 mod range;
+mod rescue;
+mod stats;
+mod wide_poseidon;
 
 pub(crate) mod composer;
-pub(crate) mod helper;
 pub(crate) mod variable;
 
+#[cfg(any(test, feature = "test-utils"))]
+pub mod helper;
+
 pub mod ecc;
 
 pub(crate) use variable::WireData;
 
-pub use composer::StandardComposer;
-pub use variable::Variable;
+pub use composer::{Checkpoint, StandardComposer};
+pub use description::{
+    CircuitDescription, CopyConstraint, GateDescription, SELECTOR_NAMES,
+    WIRE_NAMES,
+};
+pub use mimc::{GmimcParameters, MimcParameters};
+pub use poseidon::{PoseidonParameters, PoseidonSponge, PoseidonSpongeGadget};
+pub use rescue::RescueParameters;
+pub use stats::CircuitStats;
+pub use variable::{BoolVariable, Variable, WordVariable};
+pub use wide_poseidon::WidePoseidonParameters;
@@ -0,0 +1,516 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Optimization pass removing redundant gates from a circuit.
+
+use crate::constraint_system::{StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::Field;
+use hashbrown::HashMap;
+use num_traits::{One, Zero};
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Removes the redundant arithmetic gates of the circuit and returns the
+    /// number of gates removed.
+    ///
+    /// The [`Variable`]s whose value is fixed by the circuit description,
+    /// such as the ones of [`StandardComposer::constrain_to_constant`] or the
+    /// outputs of gates whose inputs are all constants, are folded into the
+    /// constant selector of the gates using them. Then the gates left
+    /// trivially satisfied and the gates whose output no other gate
+    /// references, which only constrain that output, are removed.
+    ///
+    /// Gates with public inputs, custom gates and the gates whose wires they
+    /// read are kept as they are. The circuit is satisfied by the same
+    /// witness as before, but its gates move, so the composers of the prover
+    /// and of the verifier must both be optimized, and the checkpoints
+    /// recorded before can't be rolled back to.
+    pub fn optimize(&mut self) -> usize {
+        let n = self.n;
+
+        // Gates which can be rewritten: arithmetic gates neither chained nor
+        // read as the next row of a custom gate
+        let mut foldable: Vec<bool> = self
+            .q_arith
+            .iter()
+            .map(|q_arith| q_arith.is_one())
+            .collect();
+        for i in 0..n {
+            let custom = [
+                &self.q_range,
+                &self.q_logic,
+                &self.q_fixed_group_add,
+                &self.q_variable_group_add,
+                &self.q_lookahead,
+            ]
+            .iter()
+            .any(|selector| !selector[i].is_zero());
+            if custom || self.lookahead_wires.contains_key(&i) {
+                foldable[i] = false;
+                foldable[(i + 1) % n] = false;
+            }
+        }
+        // Gates which can be removed
+        let removable: Vec<bool> = (0..n)
+            .map(|i| {
+                foldable[i] && !self.public_inputs_sparse_store.contains_key(&i)
+            })
+            .collect();
+
+        let constants = self.find_constants(&removable);
+        for i in (0..n).filter(|i| foldable[*i]) {
+            self.fold_constants(i, &constants);
+        }
+
+        let mut alive: Vec<bool> = (0..n)
+            .map(|i| !removable[i] || !self.is_trivially_satisfied(i))
+            .collect();
+        self.remove_dead_gates(&removable, &mut alive);
+
+        // Interned constants whose gate is removed are constrained again
+        // the next time they are added
+        self.constants.retain(|_, var| match constants.get(var) {
+            Some((_, gate)) => alive[*gate],
+            None => true,
+        });
+
+        let kept: Vec<usize> = (0..n).filter(|i| alive[*i]).collect();
+        self.retain_gates(&kept);
+        n - kept.len()
+    }
+
+    /// Returns the value and the defining gate of the [`Variable`]s whose
+    /// value is fixed by the circuit description, i.e. the outputs of
+    /// removable gates where every other wire is a constant.
+    fn find_constants(
+        &self,
+        removable: &[bool],
+    ) -> HashMap<Variable, (E::Fr, usize)> {
+        let chained: Vec<Variable> =
+            self.lookahead_wires.values().copied().collect();
+        let mut constants = HashMap::new();
+        loop {
+            let mut found = false;
+            for i in (0..self.n).filter(|i| removable[*i]) {
+                let output = self.w_o[i];
+                if output == self.zero_var
+                    || chained.contains(&output)
+                    || constants.contains_key(&output)
+                {
+                    continue;
+                }
+                if let Some(value) = self.solve_output(i, &constants) {
+                    constants.insert(output, (value, i));
+                    found = true;
+                }
+            }
+            if !found {
+                return constants;
+            }
+        }
+    }
+
+    /// Solves the `i`-th gate for its output [`Variable`], if the other wires
+    /// it uses are all `constants` and the output is determined.
+    fn solve_output(
+        &self,
+        i: usize,
+        constants: &HashMap<Variable, (E::Fr, usize)>,
+    ) -> Option<E::Fr> {
+        let output = self.w_o[i];
+        // Value of a wire as `coefficient * output + constant`
+        let wire = |var: Variable, used: bool| {
+            if var == output {
+                Some((E::Fr::one(), E::Fr::zero()))
+            } else if !used || var == self.zero_var {
+                Some((E::Fr::zero(), E::Fr::zero()))
+            } else {
+                constants
+                    .get(&var)
+                    .map(|(value, _)| (E::Fr::zero(), *value))
+            }
+        };
+        let q_m = self.q_m[i];
+        let (a_1, a_0) =
+            wire(self.w_l[i], !q_m.is_zero() || !self.q_l[i].is_zero())?;
+        let (b_1, b_0) =
+            wire(self.w_r[i], !q_m.is_zero() || !self.q_r[i].is_zero())?;
+        let (d_1, d_0) = wire(self.w_4[i], !self.q_4[i].is_zero())?;
+        if !(q_m * a_1 * b_1).is_zero() {
+            return None;
+        }
+        let coefficient = q_m * (a_1 * b_0 + a_0 * b_1)
+            + self.q_l[i] * a_1
+            + self.q_r[i] * b_1
+            + self.q_o[i]
+            + self.q_4[i] * d_1;
+        let constant = q_m * a_0 * b_0
+            + self.q_l[i] * a_0
+            + self.q_r[i] * b_0
+            + self.q_4[i] * d_0
+            + self.q_c[i];
+        coefficient.inverse().map(|inverse| -constant * inverse)
+    }
+
+    /// Replaces the `constants` on the wires of the `i`-th gate with the zero
+    /// [`Variable`], moving their contribution to the constant selector.
+    fn fold_constants(
+        &mut self,
+        i: usize,
+        constants: &HashMap<Variable, (E::Fr, usize)>,
+    ) {
+        let constant = |var: Variable| match constants.get(&var) {
+            Some((value, gate)) if *gate != i => Some(*value),
+            _ => None,
+        };
+        if let Some(value) = constant(self.w_l[i]) {
+            self.q_c[i] += self.q_l[i] * value;
+            self.q_r[i] += self.q_m[i] * value;
+            self.q_l[i] = E::Fr::zero();
+            self.q_m[i] = E::Fr::zero();
+            self.w_l[i] = self.zero_var;
+        }
+        if let Some(value) = constant(self.w_r[i]) {
+            self.q_c[i] += self.q_r[i] * value;
+            self.q_l[i] += self.q_m[i] * value;
+            self.q_r[i] = E::Fr::zero();
+            self.q_m[i] = E::Fr::zero();
+            self.w_r[i] = self.zero_var;
+        }
+        if let Some(value) = constant(self.w_o[i]) {
+            self.q_c[i] += self.q_o[i] * value;
+            self.q_o[i] = E::Fr::zero();
+            self.w_o[i] = self.zero_var;
+        }
+        if let Some(value) = constant(self.w_4[i]) {
+            self.q_c[i] += self.q_4[i] * value;
+            self.q_4[i] = E::Fr::zero();
+            self.w_4[i] = self.zero_var;
+        }
+    }
+
+    /// Checks whether every coefficient of the `i`-th gate is zero.
+    fn is_trivially_satisfied(&self, i: usize) -> bool {
+        [
+            &self.q_m, &self.q_l, &self.q_r, &self.q_o, &self.q_4, &self.q_c,
+        ]
+        .iter()
+        .all(|selector| selector[i].is_zero())
+    }
+
+    /// Marks the removable gates whose output isn't used by any other gate
+    /// as dead, until all of the remaining outputs are used.
+    fn remove_dead_gates(&self, removable: &[bool], alive: &mut [bool]) {
+        let mut uses = HashMap::<Variable, usize>::new();
+        for i in (0..self.n).filter(|i| alive[*i]) {
+            for var in self.gate_wires(i) {
+                *uses.entry(var).or_default() += 1;
+            }
+        }
+        let chained: Vec<Variable> =
+            self.lookahead_wires.values().copied().collect();
+
+        let mut removed = true;
+        while removed {
+            removed = false;
+            for i in 0..self.n {
+                if !alive[i] || !removable[i] {
+                    continue;
+                }
+                let output = self.w_o[i];
+                let wires = self.gate_wires(i);
+                let local_uses =
+                    wires.iter().filter(|var| **var == output).count();
+                if output == self.zero_var
+                    || chained.contains(&output)
+                    || uses[&output] != local_uses
+                    || !self.is_free_output(i)
+                {
+                    continue;
+                }
+                alive[i] = false;
+                removed = true;
+                for var in wires {
+                    *uses.get_mut(&var).unwrap() -= 1;
+                }
+            }
+        }
+    }
+
+    /// Checks whether the constraint of the `i`-th gate is linear in its
+    /// output, so that it holds for some value of the output whatever the
+    /// other wires.
+    fn is_free_output(&self, i: usize) -> bool {
+        let output = self.w_o[i];
+        if !self.q_m[i].is_zero()
+            && (self.w_l[i] == output || self.w_r[i] == output)
+        {
+            return false;
+        }
+        let coefficient = [
+            (self.w_l[i], self.q_l[i]),
+            (self.w_r[i], self.q_r[i]),
+            (self.w_o[i], self.q_o[i]),
+            (self.w_4[i], self.q_4[i]),
+        ]
+        .iter()
+        .filter(|(var, _)| *var == output)
+        .map(|(_, selector)| *selector)
+        .sum::<E::Fr>();
+        !coefficient.is_zero()
+    }
+
+    /// Returns the [`Variable`]s on the wires of the `i`-th gate.
+    fn gate_wires(&self, i: usize) -> [Variable; 4] {
+        [self.w_l[i], self.w_r[i], self.w_o[i], self.w_4[i]]
+    }
+
+    /// Keeps the gates of the given indices, in increasing order, and
+    /// removes the others.
+    fn retain_gates(&mut self, kept: &[usize]) {
+        for selector in [
+            &mut self.q_m,
+            &mut self.q_l,
+            &mut self.q_r,
+            &mut self.q_o,
+            &mut self.q_4,
+            &mut self.q_c,
+            &mut self.q_arith,
+            &mut self.q_range,
+            &mut self.q_logic,
+            &mut self.q_fixed_group_add,
+            &mut self.q_variable_group_add,
+            &mut self.q_lookahead,
+        ] {
+            *selector = kept.iter().map(|i| selector[*i]).collect();
+        }
+        for wire in [&mut self.w_l, &mut self.w_r, &mut self.w_o, &mut self.w_4]
+        {
+            *wire = kept.iter().map(|i| wire[*i]).collect();
+        }
+
+        let position: HashMap<usize, usize> = kept
+            .iter()
+            .enumerate()
+            .map(|(new, old)| (*old, new))
+            .collect();
+        self.public_inputs_sparse_store = self
+            .public_inputs_sparse_store
+            .iter()
+            .map(|(i, value)| (position[i], *value))
+            .collect();
+        self.public_points =
+            self.public_points.iter().map(|i| position[i]).collect();
+        self.lookahead_wires = self
+            .lookahead_wires
+            .iter()
+            .map(|(i, var)| (position[i], *var))
+            .collect();
+
+        self.n = kept.len();
+        self.perm.clear_wires();
+        for i in 0..self.n {
+            self.perm.add_variables_to_map(
+                self.w_l[i],
+                self.w_r[i],
+                self.w_o[i],
+                self.w_4[i],
+                i,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::constraint_system::helper::*;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    /// Tests that gates whose outputs are never used are removed.
+    fn test_optimize_dead_gates<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(3u64));
+                let b = composer.add_input(E::Fr::from(4u64));
+                let c = composer.add_input(E::Fr::from(12u64));
+                let product =
+                    composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+                composer.assert_equal(product, c);
+
+                // A chain of gates whose result is never used
+                let sum = composer.add(
+                    (E::Fr::one(), a),
+                    (E::Fr::one(), c),
+                    E::Fr::zero(),
+                    None,
+                );
+                composer.mul(E::Fr::one(), sum, sum, E::Fr::zero(), None);
+
+                let size = composer.circuit_size();
+                assert_eq!(composer.optimize(), 2);
+                assert_eq!(composer.circuit_size(), size - 2);
+                assert_eq!(composer.optimize(), 0);
+            },
+            200,
+        );
+        assert!(res.is_ok());
+    }
+
+    /// Tests that constants are folded into the gates using them.
+    fn test_optimize_constant_folding<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let five = composer
+                    .add_witness_to_circuit_description(E::Fr::from(5u64));
+                let ten = composer.add(
+                    (E::Fr::one(), five),
+                    (E::Fr::one(), five),
+                    E::Fr::zero(),
+                    None,
+                );
+                let a = composer.add_input(E::Fr::from(3u64));
+                let product =
+                    composer.mul(E::Fr::one(), a, ten, E::Fr::zero(), None);
+                composer.constrain_to_constant(
+                    product,
+                    E::Fr::from(30u64),
+                    None,
+                );
+
+                // Only the gate checking `10 * a = 30` is left
+                let size = composer.circuit_size();
+                assert_eq!(composer.optimize(), 3);
+                assert_eq!(composer.circuit_size(), size - 3);
+                dummy_gadget(8, composer);
+            },
+            200,
+        );
+        assert!(res.is_ok());
+
+        // The folded constraints are still enforced
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let ten = composer
+                    .add_witness_to_circuit_description(E::Fr::from(10u64));
+                let a = composer.add_input(E::Fr::from(4u64));
+                let product =
+                    composer.mul(E::Fr::one(), a, ten, E::Fr::zero(), None);
+                composer.constrain_to_constant(
+                    product,
+                    E::Fr::from(30u64),
+                    None,
+                );
+                composer.optimize();
+                dummy_gadget(8, composer);
+            },
+            200,
+        );
+        assert!(res.is_err());
+
+        // Conflicting constants leave an unsatisfiable gate
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let five = composer
+                    .add_witness_to_circuit_description(E::Fr::from(5u64));
+                composer.constrain_to_constant(five, E::Fr::from(6u64), None);
+                assert_eq!(composer.optimize(), 1);
+                dummy_gadget(8, composer);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    /// Tests that public inputs and custom gates survive the optimization.
+    fn test_optimize_keeps_custom_gates<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = composer.add_input(E::Fr::from(20u64));
+                // Dead gate placed before the custom gates
+                composer.mul(E::Fr::one(), a, a, E::Fr::zero(), None);
+                composer.range_gate(a, 1 << 6);
+                composer.constrain_to_constant(
+                    a,
+                    E::Fr::zero(),
+                    Some(-E::Fr::from(20u64)),
+                );
+
+                let size = composer.circuit_size();
+                let public_inputs = composer.public_inputs_sparse_store.len();
+                assert_eq!(composer.optimize(), 1);
+                assert_eq!(composer.circuit_size(), size - 1);
+                assert_eq!(
+                    composer.public_inputs_sparse_store.len(),
+                    public_inputs
+                );
+            },
+            200,
+        );
+        assert!(res.is_ok());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [
+            test_optimize_dead_gates,
+            test_optimize_constant_folding,
+            test_optimize_keeps_custom_gates
+        ],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [
+            test_optimize_dead_gates,
+            test_optimize_constant_folding,
+            test_optimize_keeps_custom_gates
+        ],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [
+            test_optimize_dead_gates,
+            test_optimize_constant_folding,
+            test_optimize_keeps_custom_gates
+        ],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
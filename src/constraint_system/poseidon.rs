@@ -0,0 +1,852 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Poseidon hash gadget.
+//!
+//! [`PoseidonParameters::hash`] computes the Poseidon hash of field elements
+//! out of circuit, and [`StandardComposer::poseidon_hash`] constrains the
+//! same computation in circuit.
+//!
+//! For streams of data, a [`PoseidonSponge`] absorbs and squeezes field
+//! elements in any order out of circuit, and the [`PoseidonSpongeGadget`]
+//! returned by [`StandardComposer::poseidon_sponge`] constrains the same
+//! sequence of operations in circuit, with the same padding, so that both
+//! squeeze the same values.
+//!
+//! Circuits with many public inputs can instead expose a single one, equal
+//! to the hash of all of them, with
+//! [`StandardComposer::commit_public_inputs`]. The verifier then builds the
+//! matching public inputs from the logical values with
+//! [`VerifierData::committed_public_inputs`], and only has to evaluate one
+//! public input.
+//!
+//! [`VerifierData::committed_public_inputs`]:
+//! crate::circuit::VerifierData::committed_public_inputs
+
+use crate::constraint_system::{StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use merlin::Transcript;
+use num_traits::{One, Zero};
+
+/// Number of field elements of the state.
+pub(super) const WIDTH: usize = 3;
+
+/// Number of field elements absorbed by every permutation.
+pub(super) const RATE: usize = WIDTH - 1;
+
+/// Number of full rounds, half of them before the partial rounds and half
+/// after.
+const FULL_ROUNDS: usize = 8;
+
+/// Candidate exponents of the S-box, in order of preference.
+const ALPHA_CANDIDATES: [u64; 8] = [3, 5, 7, 11, 13, 17, 19, 23];
+
+/// Returns the number of partial rounds for a state of `width` elements and
+/// an `x^alpha` S-box at the 128-bit security level, or `None` if the width
+/// or the exponent is not supported.
+///
+/// The rounds of `x^5` are the ones recommended by the Poseidon paper. The
+/// others are given by the round numbers script of its reference
+/// implementation, with the same security margin, for fields of at least
+/// 128 bits. Larger exponents need fewer rounds.
+pub(super) fn partial_rounds(alpha: u64, width: usize) -> Option<usize> {
+    if !matches!(width, 3 | 5 | 9) {
+        return None;
+    }
+    match alpha {
+        3 => Some(if width == 3 { 83 } else { 84 }),
+        5 => Some(match width {
+            3 => 57,
+            5 => 60,
+            _ => 63,
+        }),
+        7 => Some(if width == 9 { 47 } else { 46 }),
+        11 => Some(37),
+        13 => Some(34),
+        17 => Some(31),
+        19 => Some(30),
+        23 => Some(27),
+        _ => None,
+    }
+}
+
+/// Parameters of the Poseidon permutation over `F`.
+///
+/// They are derived deterministically from the field, so the prover and the
+/// verifier always agree on them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoseidonParameters<F>
+where
+    F: PrimeField,
+{
+    /// Exponent of the S-box
+    alpha: u64,
+
+    /// Constants added to the state at the start of every round
+    round_constants: Vec<[F; WIDTH]>,
+
+    /// Matrix mixing the state at the end of every round
+    mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F> PoseidonParameters<F>
+where
+    F: PrimeField,
+{
+    /// Derives the parameters for `F`.
+    ///
+    /// The S-box exponent is the smallest prime `alpha` such that `x^alpha`
+    /// is a permutation of `F`, and the number of partial rounds is derived
+    /// from it. The round constants are squeezed from a transcript and the
+    /// mixing matrix is the Cauchy matrix `1 / (i + j + 3)`.
+    pub fn new() -> Self {
+        let alpha = sbox_exponent::<F>();
+        let partial_rounds = partial_rounds(alpha, WIDTH)
+            .expect("every S-box exponent has a number of rounds");
+        let mut transcript = Transcript::new(b"ark-plonk-poseidon");
+        transcript.append_u64(b"alpha", alpha);
+        let round_constants = (0..FULL_ROUNDS + partial_rounds)
+            .map(|_| {
+                let mut constants = [F::zero(); WIDTH];
+                for constant in constants.iter_mut() {
+                    let mut bytes = [0u8; 64];
+                    transcript.challenge_bytes(b"round_constant", &mut bytes);
+                    *constant = F::from_le_bytes_mod_order(&bytes);
+                }
+                constants
+            })
+            .collect();
+
+        Self {
+            alpha,
+            round_constants,
+            mds: cauchy_mds(),
+        }
+    }
+
+    /// Returns the exponent of the S-box.
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /// Hashes `inputs` into a single field element.
+    ///
+    /// The capacity element of the state is initialized with the number of
+    /// inputs, so inputs of different lengths never collide by padding.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = [F::zero(); WIDTH];
+        state[0] = F::from(inputs.len() as u64);
+        for chunk in inputs.chunks(RATE) {
+            state[1..]
+                .iter_mut()
+                .zip(chunk)
+                .for_each(|(element, input)| *element += input);
+            self.permute(&mut state);
+        }
+        if inputs.is_empty() {
+            self.permute(&mut state);
+        }
+        state[1]
+    }
+
+    /// Returns `true` if the S-box is applied to the whole state in `round`.
+    fn is_full_round(&self, round: usize) -> bool {
+        round < FULL_ROUNDS / 2
+            || round >= self.round_constants.len() - FULL_ROUNDS / 2
+    }
+
+    /// Applies the Poseidon permutation to `state`.
+    fn permute(&self, state: &mut [F; WIDTH]) {
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            state
+                .iter_mut()
+                .zip(constants)
+                .for_each(|(element, constant)| *element += constant);
+            if self.is_full_round(round) {
+                state
+                    .iter_mut()
+                    .for_each(|element| *element = element.pow([self.alpha]));
+            } else {
+                state[0] = state[0].pow([self.alpha]);
+            }
+
+            mix(&self.mds, state);
+        }
+    }
+}
+
+/// Whether a sponge last absorbed or squeezed, and how many elements of the
+/// rate it used since the last permutation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SpongeMode {
+    /// Absorbing, with the number of elements added to the rate
+    Absorbing(usize),
+
+    /// Squeezing, with the number of elements read from the rate
+    Squeezing(usize),
+}
+
+/// Duplex sponge over the Poseidon permutation, computed out of circuit.
+///
+/// Elements are added to the rate of the state, which is permuted whenever
+/// it is full. Squeezing after absorbing pads the absorbed elements with a
+/// one, so that sequences of different lengths never collide, and permutes
+/// the state before reading its rate. [`PoseidonSpongeGadget`] constrains
+/// the same operations in circuit.
+#[derive(Clone, Debug)]
+pub struct PoseidonSponge<'a, F>
+where
+    F: PrimeField,
+{
+    /// Parameters of the permutation
+    params: &'a PoseidonParameters<F>,
+
+    /// State of the sponge, capacity first
+    state: [F; WIDTH],
+
+    /// Current mode of the sponge
+    mode: SpongeMode,
+}
+
+impl<'a, F> PoseidonSponge<'a, F>
+where
+    F: PrimeField,
+{
+    /// Creates a sponge whose capacity is initialized with `domain`, so
+    /// that sponges of different domains squeeze unrelated values.
+    pub fn new(params: &'a PoseidonParameters<F>, domain: u64) -> Self {
+        let mut state = [F::zero(); WIDTH];
+        state[0] = F::from(domain);
+        Self {
+            params,
+            state,
+            mode: SpongeMode::Absorbing(0),
+        }
+    }
+
+    /// Absorbs `input` into the sponge.
+    pub fn absorb(&mut self, input: F) {
+        let position = match self.mode {
+            SpongeMode::Absorbing(RATE) => {
+                self.params.permute(&mut self.state);
+                0
+            }
+            SpongeMode::Absorbing(position) => position,
+            SpongeMode::Squeezing(_) => 0,
+        };
+        self.state[1 + position] += input;
+        self.mode = SpongeMode::Absorbing(position + 1);
+    }
+
+    /// Squeezes a field element out of the sponge.
+    pub fn squeeze(&mut self) -> F {
+        let position = match self.mode {
+            SpongeMode::Absorbing(position) => {
+                if position == RATE {
+                    self.params.permute(&mut self.state);
+                    self.state[1] += F::one();
+                } else {
+                    self.state[1 + position] += F::one();
+                }
+                self.params.permute(&mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(RATE) => {
+                self.params.permute(&mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(position) => position,
+        };
+        self.mode = SpongeMode::Squeezing(position + 1);
+        self.state[1 + position]
+    }
+}
+
+/// Duplex sponge over the Poseidon permutation, constrained in circuit.
+///
+/// Created by [`StandardComposer::poseidon_sponge`], it squeezes the same
+/// values as a [`PoseidonSponge`] of the same domain going through the same
+/// operations.
+#[derive(Clone, Debug)]
+pub struct PoseidonSpongeGadget<'a, F>
+where
+    F: PrimeField,
+{
+    /// Parameters of the permutation
+    params: &'a PoseidonParameters<F>,
+
+    /// State of the sponge, capacity first
+    state: [Variable; WIDTH],
+
+    /// Current mode of the sponge
+    mode: SpongeMode,
+}
+
+impl<'a, F> PoseidonSpongeGadget<'a, F>
+where
+    F: PrimeField,
+{
+    /// Absorbs `input` into the sponge, adding its gates to `composer`.
+    pub fn absorb<E, P>(
+        &mut self,
+        composer: &mut StandardComposer<E, P>,
+        input: Variable,
+    ) where
+        E: PairingEngine<Fr = F>,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let position = match self.mode {
+            SpongeMode::Absorbing(RATE) => {
+                composer.poseidon_permutation(self.params, &mut self.state);
+                0
+            }
+            SpongeMode::Absorbing(position) => position,
+            SpongeMode::Squeezing(_) => 0,
+        };
+        let element = &mut self.state[1 + position];
+        *element = composer.add(
+            (F::one(), *element),
+            (F::one(), input),
+            F::zero(),
+            None,
+        );
+        self.mode = SpongeMode::Absorbing(position + 1);
+    }
+
+    /// Squeezes a [`Variable`] out of the sponge, adding its gates to
+    /// `composer`.
+    pub fn squeeze<E, P>(
+        &mut self,
+        composer: &mut StandardComposer<E, P>,
+    ) -> Variable
+    where
+        E: PairingEngine<Fr = F>,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let position = match self.mode {
+            SpongeMode::Absorbing(position) => {
+                let padded = if position == RATE {
+                    composer.poseidon_permutation(self.params, &mut self.state);
+                    1
+                } else {
+                    1 + position
+                };
+                let zero = composer.zero_var;
+                let element = &mut self.state[padded];
+                *element = composer.add(
+                    (F::one(), *element),
+                    (F::zero(), zero),
+                    F::one(),
+                    None,
+                );
+                composer.poseidon_permutation(self.params, &mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(RATE) => {
+                composer.poseidon_permutation(self.params, &mut self.state);
+                0
+            }
+            SpongeMode::Squeezing(position) => position,
+        };
+        self.mode = SpongeMode::Squeezing(position + 1);
+        self.state[1 + position]
+    }
+}
+
+impl<F> Default for PoseidonParameters<F>
+where
+    F: PrimeField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the smallest prime `alpha` such that `x^alpha` is a permutation
+/// of `F`.
+pub(super) fn sbox_exponent<F>() -> u64
+where
+    F: PrimeField,
+{
+    ALPHA_CANDIDATES
+        .iter()
+        .copied()
+        // `x^alpha` is a permutation unless `alpha` divides `p - 1`
+        .find(|alpha| modulus_rem::<F>(*alpha) != 1)
+        .expect("no S-box exponent is coprime to the field order")
+}
+
+/// Returns the Cauchy matrix `1 / (i + j + 3)`, which is MDS.
+pub(super) fn cauchy_mds<F>() -> [[F; WIDTH]; WIDTH]
+where
+    F: PrimeField,
+{
+    let mut mds = [[F::zero(); WIDTH]; WIDTH];
+    for (i, row) in mds.iter_mut().enumerate() {
+        for (j, entry) in row.iter_mut().enumerate() {
+            *entry = F::from((i + j + WIDTH) as u64)
+                .inverse()
+                .expect("entries of the Cauchy matrix are non-zero");
+        }
+    }
+    mds
+}
+
+/// Multiplies `state` by the matrix `mds`.
+pub(super) fn mix<F>(mds: &[[F; WIDTH]; WIDTH], state: &mut [F; WIDTH])
+where
+    F: PrimeField,
+{
+    let mut mixed = [F::zero(); WIDTH];
+    for (element, row) in mixed.iter_mut().zip(mds) {
+        *element = row
+            .iter()
+            .zip(state.iter())
+            .fold(F::zero(), |acc, (m, s)| acc + *m * s);
+    }
+    *state = mixed;
+}
+
+/// Returns the modulus of `F` reduced modulo `divisor`.
+pub(super) fn modulus_rem<F>(divisor: u64) -> u64
+where
+    F: PrimeField,
+{
+    F::characteristic().iter().rev().fold(0, |rem, limb| {
+        ((((rem as u128) << 64) | *limb as u128) % divisor as u128) as u64
+    })
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the Poseidon hash of `inputs` with `params`, returning the
+    /// [`Variable`] holding the result of [`PoseidonParameters::hash`].
+    pub fn poseidon_hash(
+        &mut self,
+        params: &PoseidonParameters<E::Fr>,
+        inputs: &[Variable],
+    ) -> Variable {
+        let zero = self.zero_var;
+        let length = self.add(
+            (E::Fr::zero(), zero),
+            (E::Fr::zero(), zero),
+            E::Fr::from(inputs.len() as u64),
+            None,
+        );
+        let mut state = [length, zero, zero];
+        for chunk in inputs.chunks(RATE) {
+            for (element, input) in state[1..].iter_mut().zip(chunk) {
+                *element = self.add(
+                    (E::Fr::one(), *element),
+                    (E::Fr::one(), *input),
+                    E::Fr::zero(),
+                    None,
+                );
+            }
+            self.poseidon_permutation(params, &mut state);
+        }
+        if inputs.is_empty() {
+            self.poseidon_permutation(params, &mut state);
+        }
+        state[1]
+    }
+
+    /// Creates a [`PoseidonSpongeGadget`] whose capacity is initialized with
+    /// `domain`, as [`PoseidonSponge::new`] does out of circuit.
+    pub fn poseidon_sponge<'a>(
+        &mut self,
+        params: &'a PoseidonParameters<E::Fr>,
+        domain: u64,
+    ) -> PoseidonSpongeGadget<'a, E::Fr> {
+        let zero = self.zero_var;
+        let capacity = self.add(
+            (E::Fr::zero(), zero),
+            (E::Fr::zero(), zero),
+            E::Fr::from(domain),
+            None,
+        );
+        PoseidonSpongeGadget {
+            params,
+            state: [capacity, zero, zero],
+            mode: SpongeMode::Absorbing(0),
+        }
+    }
+
+    /// Exposes the Poseidon hash of `inputs` as a public input of the
+    /// circuit, returning the [`Variable`] holding it.
+    ///
+    /// Circuits using this as their only public input are verified with the
+    /// public inputs built by
+    /// [`VerifierData::committed_public_inputs`](crate::circuit::VerifierData::committed_public_inputs)
+    /// from the values of `inputs`.
+    pub fn commit_public_inputs(&mut self, inputs: &[Variable]) -> Variable {
+        let hash = self.poseidon_hash(&PoseidonParameters::new(), inputs);
+        self.set_public(hash);
+        hash
+    }
+
+    /// Constrains the Poseidon permutation of `state`.
+    ///
+    /// The constants of every round are added by the gates mixing the state
+    /// at the end of the previous round.
+    fn poseidon_permutation(
+        &mut self,
+        params: &PoseidonParameters<E::Fr>,
+        state: &mut [Variable; WIDTH],
+    ) {
+        let zero = self.zero_var;
+        for (element, constant) in
+            state.iter_mut().zip(&params.round_constants[0])
+        {
+            *element = self.add(
+                (E::Fr::one(), *element),
+                (E::Fr::zero(), zero),
+                *constant,
+                None,
+            );
+        }
+
+        for round in 0..params.round_constants.len() {
+            if params.is_full_round(round) {
+                for element in state.iter_mut() {
+                    *element = self.poseidon_sbox(params.alpha, *element);
+                }
+            } else {
+                state[0] = self.poseidon_sbox(params.alpha, state[0]);
+            }
+
+            let constants = params
+                .round_constants
+                .get(round + 1)
+                .copied()
+                .unwrap_or([E::Fr::zero(); WIDTH]);
+            let mut mixed = *state;
+            for ((element, row), constant) in
+                mixed.iter_mut().zip(&params.mds).zip(constants)
+            {
+                *element = self.big_add(
+                    (row[0], state[0]),
+                    (row[1], state[1]),
+                    Some((row[2], state[2])),
+                    constant,
+                    None,
+                );
+            }
+            *state = mixed;
+        }
+    }
+
+    /// Constrains `x^alpha` by square and multiply.
+    pub(super) fn poseidon_sbox(
+        &mut self,
+        alpha: u64,
+        x: Variable,
+    ) -> Variable {
+        let mut acc = x;
+        for bit in (0..63 - alpha.leading_zeros()).rev() {
+            acc = self.mul(E::Fr::one(), acc, acc, E::Fr::zero(), None);
+            if (alpha >> bit) & 1 == 1 {
+                acc = self.mul(E::Fr::one(), acc, x, E::Fr::zero(), None);
+            }
+        }
+        acc
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::circuit::{verify_proof, Circuit};
+    use crate::config::ProverConfig;
+    use crate::constraint_system::helper::*;
+    use crate::error::Error;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use core::marker::PhantomData;
+    use rand_core::OsRng;
+
+    fn test_poseidon_hash<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = PoseidonParameters::<E::Fr>::new();
+        assert_ne!(modulus_rem::<E::Fr>(params.alpha()), 1);
+        assert_eq!(
+            params.round_constants.len(),
+            FULL_ROUNDS + partial_rounds(params.alpha(), WIDTH).unwrap()
+        );
+
+        // The gadget agrees with the native hash for every input length
+        let mut hashes = Vec::new();
+        for length in 0..5u64 {
+            let values: Vec<E::Fr> =
+                (0..length).map(|i| E::Fr::from(i + 1)).collect();
+            let mut composer = StandardComposer::<E, P>::new();
+            let inputs: Vec<Variable> =
+                values.iter().map(|v| composer.add_input(*v)).collect();
+            let hash = composer.poseidon_hash(&params, &inputs);
+            assert_eq!(composer.variables[hash.0], params.hash(&values));
+            hashes.push(params.hash(&values));
+        }
+
+        // The length is part of the hash
+        assert_ne!(hashes[1], params.hash(&[E::Fr::from(1u64), E::Fr::zero()]));
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = PoseidonParameters::new();
+                let values = [E::Fr::from(3u64), E::Fr::from(5u64)];
+                let inputs: Vec<Variable> =
+                    values.iter().map(|v| composer.add_input(*v)).collect();
+                let hash = composer.poseidon_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&values),
+                    None,
+                );
+            },
+            1 << 11,
+        );
+        assert!(res.is_ok());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = PoseidonParameters::new();
+                let inputs = [composer.add_input(E::Fr::from(3u64))];
+                let hash = composer.poseidon_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&[E::Fr::from(4u64)]),
+                    None,
+                );
+            },
+            1 << 11,
+        );
+        assert!(res.is_err());
+    }
+
+    /// Operation applied to a sponge.
+    #[derive(Clone, Copy)]
+    enum SpongeOperation {
+        Absorb(u64),
+        Squeeze,
+    }
+
+    fn test_poseidon_sponge<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        use SpongeOperation::*;
+
+        let params = PoseidonParameters::<E::Fr>::new();
+        let native = |domain, operations: &[SpongeOperation]| {
+            let mut sponge = PoseidonSponge::new(&params, domain);
+            let mut squeezed = Vec::new();
+            for operation in operations {
+                match *operation {
+                    Absorb(value) => sponge.absorb(E::Fr::from(value)),
+                    Squeeze => squeezed.push(sponge.squeeze()),
+                }
+            }
+            squeezed
+        };
+
+        // The gadget squeezes the same values as the native sponge, across
+        // any number of absorptions and squeezes
+        let operations = [
+            vec![Squeeze],
+            vec![Absorb(1), Squeeze],
+            vec![Absorb(1), Absorb(2), Squeeze, Squeeze, Squeeze],
+            vec![Absorb(1), Absorb(2), Absorb(3), Squeeze, Absorb(4), Squeeze],
+            vec![Squeeze, Squeeze, Absorb(5), Absorb(6), Squeeze],
+        ];
+        for operations in &operations {
+            let expected = native(7, operations);
+            let mut composer = StandardComposer::<E, P>::new();
+            let mut sponge = composer.poseidon_sponge(&params, 7);
+            let mut squeezed = Vec::new();
+            for operation in operations {
+                match *operation {
+                    Absorb(value) => {
+                        let input = composer.add_input(E::Fr::from(value));
+                        sponge.absorb(&mut composer, input);
+                    }
+                    Squeeze => {
+                        let output = sponge.squeeze(&mut composer);
+                        squeezed.push(composer.variables[output.0]);
+                    }
+                }
+            }
+            assert_eq!(squeezed, expected);
+        }
+
+        // Absorbed sequences are padded, and domains are separated
+        assert_ne!(
+            native(0, &[Absorb(1), Squeeze]),
+            native(0, &[Absorb(1), Absorb(0), Squeeze])
+        );
+        assert_ne!(
+            native(0, &[Absorb(1), Absorb(2), Squeeze]),
+            native(0, &[Absorb(1), Absorb(2), Absorb(0), Squeeze])
+        );
+        assert_ne!(
+            native(0, &[Absorb(1), Squeeze]),
+            native(1, &[Absorb(1), Squeeze])
+        );
+
+        let expected = native(3, &[Absorb(3), Absorb(5), Absorb(8), Squeeze]);
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = PoseidonParameters::new();
+                let mut sponge = composer.poseidon_sponge(&params, 3);
+                for value in [3u64, 5, 8] {
+                    let input = composer.add_input(E::Fr::from(value));
+                    sponge.absorb(composer, input);
+                }
+                let output = sponge.squeeze(composer);
+                composer.constrain_to_constant(output, expected[0], None);
+            },
+            1 << 11,
+        );
+        assert!(res.is_ok());
+    }
+
+    // Circuit exposing the hash of its values as its only public input
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""))]
+    struct CommittedInputsCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        values: Vec<E::Fr>,
+        __: PhantomData<P>,
+    }
+
+    impl<E, P> Circuit<E, P> for CommittedInputsCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let inputs: Vec<Variable> =
+                self.values.iter().map(|v| composer.add_input(*v)).collect();
+            composer.commit_public_inputs(&inputs);
+            Ok(())
+        }
+    }
+
+    fn test_commit_public_inputs<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let values: Vec<E::Fr> = (0..6u64).map(E::Fr::from).collect();
+        let mut circuit = CommittedInputsCircuit::<E, P> {
+            values: vec![E::Fr::zero(); values.len()],
+            __: PhantomData,
+        };
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            circuit.padded_circuit_size(),
+            false,
+            &mut OsRng,
+        )
+        .unwrap();
+        let (prover_key, verifier_data) = circuit.compile(&pp).unwrap();
+        assert_eq!(verifier_data.pi_pos().len(), 1);
+
+        let config = ProverConfig::new(b"Test");
+        circuit.values = values.clone();
+        let proof = circuit.gen_proof(&pp, &prover_key, &config).unwrap();
+
+        let public_inputs =
+            verifier_data.committed_public_inputs(&values).unwrap();
+        assert!(verify_proof(
+            &pp,
+            verifier_data.key.clone(),
+            &proof,
+            &public_inputs,
+            &config.verifier_config(),
+        )
+        .is_ok());
+
+        let mut bad_values = values;
+        bad_values[5] = E::Fr::from(7u64);
+        let bad_inputs =
+            verifier_data.committed_public_inputs(&bad_values).unwrap();
+        assert!(verify_proof(
+            &pp,
+            verifier_data.key,
+            &proof,
+            &bad_inputs,
+            &config.verifier_config(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_partial_rounds() {
+        // The rounds recommended for `x^5`
+        assert_eq!(partial_rounds(5, 3), Some(57));
+        assert_eq!(partial_rounds(5, 5), Some(60));
+        assert_eq!(partial_rounds(5, 9), Some(63));
+
+        // Every candidate exponent is supported, larger ones needing fewer
+        // rounds
+        for width in [3, 5, 9] {
+            let rounds: Vec<usize> = ALPHA_CANDIDATES
+                .iter()
+                .map(|alpha| partial_rounds(*alpha, width).unwrap())
+                .collect();
+            assert!(rounds.windows(2).all(|pair| pair[0] > pair[1]));
+        }
+        assert_eq!(partial_rounds(5, 4), None);
+        assert_eq!(partial_rounds(29, 3), None);
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_poseidon_hash, test_poseidon_sponge, test_commit_public_inputs],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_poseidon_hash, test_poseidon_sponge, test_commit_public_inputs],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_poseidon_hash, test_poseidon_sponge, test_commit_public_inputs],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -6,9 +6,11 @@
 
 //! Range Gate
 
-use crate::constraint_system::{StandardComposer, Variable, WireData};
+use crate::constraint_system::{
+    StandardComposer, Variable, WireData, WordVariable,
+};
 use ark_ec::{PairingEngine, TEModelParameters};
-use ark_ff::{BigInteger, PrimeField};
+use ark_ff::{BigInteger, Field, PrimeField};
 use num_traits::{One, Zero};
 
 impl<E, P> StandardComposer<E, P>
@@ -22,10 +24,16 @@ where
     /// This function adds `num_bits/4` gates to the circuit description in
     /// order to add the range constraint.
     ///
+    /// Returns the [`WordVariable`] wrapping `witness`.
+    ///
     ///# Panics
     /// This function will panic if the num_bits specified is not even, ie.
     /// `num_bits % 2 != 0`.
-    pub fn range_gate(&mut self, witness: Variable, num_bits: usize) {
+    pub fn range_gate(
+        &mut self,
+        witness: Variable,
+        num_bits: usize,
+    ) -> WordVariable {
         // Adds `variable` into the appropriate witness position
         // based on the accumulator number a_i
         let add_wire = |composer: &mut StandardComposer<E, P>,
@@ -64,7 +72,7 @@ where
         assert!(num_bits % 2 == 0);
 
         // Convert witness to bit representation and reverse
-        let bits = self.variables[&witness].into_repr().to_bits_le();
+        let bits = self.variables[witness.0].into_repr().to_bits_le();
 
         // For a width-4 program, one gate will contain 4 accumulators
         // Each accumulator proves that a single quad is a base-4 digit.
@@ -173,6 +181,7 @@ where
         self.q_4.extend(zeros.iter());
         self.q_fixed_group_add.extend(zeros.iter());
         self.q_variable_group_add.extend(zeros.iter());
+        self.q_lookahead.extend(zeros.iter());
         self.q_range.extend(ones.iter());
         self.q_logic.extend(zeros.iter());
         self.n += used_gates;
@@ -192,15 +201,101 @@ where
         let last_accumulator = accumulators.len() - 1;
         self.assert_equal(accumulators[last_accumulator], witness);
         accumulators[last_accumulator] = witness;
+
+        WordVariable {
+            var: witness,
+            bits: num_bits,
+        }
+    }
+
+    /// Constrains `value` to be at most `max`.
+    ///
+    /// The bound is checked on the bits of `value` above and below `2^128`
+    /// separately, with range gates, so that it holds for the canonical
+    /// representative of `value` rather than modulo the field.
+    pub fn assert_at_most(&mut self, value: Variable, max: E::Fr) {
+        let zero = self.zero_var;
+        let two_128 = E::Fr::from(2u64).pow([128]);
+
+        // value = high 2^128 + low
+        let (max_high, max_low) = split_at_128(max);
+        let high_bits = max_high.into_repr().num_bits() as usize;
+        let high_bits = (high_bits + high_bits % 2).max(2);
+        let (high_value, low_value) = split_at_128(self.variables[value.0]);
+        let high = self.add_input(high_value);
+        let low = self.add_input(low_value);
+        let recomposed =
+            self.add((two_128, high), (E::Fr::one(), low), E::Fr::zero(), None);
+        self.assert_equal(recomposed, value);
+        self.range_gate(low, 128);
+        self.range_gate(high, high_bits);
+
+        // high <= max_high
+        let high_gap = self.add(
+            (-E::Fr::one(), high),
+            (E::Fr::zero(), zero),
+            max_high,
+            None,
+        );
+        self.range_gate(high_gap, high_bits);
+
+        // low <= max_low whenever high == max_high, with `equal` set if
+        // and only if the gap of the high bits is zero
+        let gap_value = self.variables[high_gap.0];
+        let equal = self.add_bool(gap_value.is_zero());
+        let inverse =
+            self.add_input(gap_value.inverse().unwrap_or_else(E::Fr::zero));
+        self.poly_gate(
+            high_gap,
+            equal.variable(),
+            zero,
+            E::Fr::one(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            None,
+        );
+        self.poly_gate(
+            high_gap,
+            inverse,
+            equal.variable(),
+            E::Fr::one(),
+            E::Fr::zero(),
+            E::Fr::zero(),
+            E::Fr::one(),
+            -E::Fr::one(),
+            None,
+        );
+        let low_gap = self.big_add(
+            (-E::Fr::one(), low),
+            (-two_128, equal.variable()),
+            None,
+            max_low + two_128,
+            None,
+        );
+        self.range_gate(low_gap, 130);
     }
 }
 
+/// Splits `value` into its bits above and below `2^128`.
+fn split_at_128<F>(value: F) -> (F, F)
+where
+    F: PrimeField,
+{
+    let mut high = value.into_repr();
+    high.divn(128);
+    let high = F::from_repr(high).expect("the high bits are reduced");
+    (high, value - high * F::from(2u64).pow([128]))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{batch_test, constraint_system::helper::*};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
 
     fn test_range_constraint<E, P>()
     where
@@ -239,6 +334,34 @@ mod test {
         assert!(res.is_ok());
     }
 
+    fn test_assert_at_most<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let at_most = |value: E::Fr, max: E::Fr| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let witness = composer.add_input(value);
+                    composer.assert_at_most(witness, max);
+                },
+                1 << 9,
+            )
+        };
+        let max = -E::Fr::from(2u64).inverse().unwrap();
+        let two_128 = E::Fr::from(2u64).pow([128]);
+
+        // Bounds in the low bits, the high bits or both
+        assert!(at_most(E::Fr::from(41u64), E::Fr::from(42u64)).is_ok());
+        assert!(at_most(E::Fr::from(42u64), E::Fr::from(42u64)).is_ok());
+        assert!(at_most(E::Fr::from(43u64), E::Fr::from(42u64)).is_err());
+        assert!(at_most(two_128, two_128 - E::Fr::one()).is_err());
+        assert!(at_most(E::Fr::zero(), max).is_ok());
+        assert!(at_most(max, max).is_ok());
+        assert!(at_most(max + E::Fr::one(), max).is_err());
+        assert!(at_most(-E::Fr::one(), max).is_err());
+    }
+
     fn test_odd_bit_range<E, P>()
     where
         E: PairingEngine,
@@ -257,7 +380,7 @@ mod test {
 
     // Test on Bls12-381
     batch_test!(
-        [test_range_constraint],
+        [test_range_constraint, test_assert_at_most],
         [test_odd_bit_range]
         => (
             Bls12_381,
@@ -267,11 +390,21 @@ mod test {
 
     // Test on Bls12-377
     batch_test!(
-        [test_range_constraint],
+        [test_range_constraint, test_assert_at_most],
         [test_odd_bit_range]
         => (
             Bls12_377,
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Test on Bn254
+    batch_test!(
+        [test_range_constraint, test_assert_at_most],
+        [test_odd_bit_range]
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
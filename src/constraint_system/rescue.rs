@@ -0,0 +1,429 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Rescue hash gadget.
+//!
+//! [`RescueParameters::hash`] computes a hash built on the Rescue
+//! permutation out of circuit, and [`StandardComposer::rescue_hash`]
+//! constrains the same computation in circuit. The state, the mixing
+//! matrix, the S-box exponent and the padding are the same as those of the
+//! [Poseidon](super::PoseidonParameters) hash, so that both hashes can be
+//! swapped for one another.
+//!
+//! The rounds follow the structure of Rescue-Prime, and their number is
+//! derived as in its specification, but this is not Rescue-Prime: the round
+//! constants are squeezed from a Merlin transcript rather than from SHAKE256,
+//! the mixing matrix is the Cauchy matrix of Poseidon and the sponge pads
+//! inputs by their length. Hashes don't match those of the reference
+//! implementation or its test vectors.
+//!
+//! Every round applies the S-box `x^alpha` and its inverse `x^(1 / alpha)`.
+//! The inverse has a high degree, but is constrained in circuit as cheaply
+//! as the S-box, by checking that the `alpha`-th power of the result is the
+//! input.
+
+use super::poseidon::{
+    cauchy_mds, mix, modulus_rem, sbox_exponent, RATE, WIDTH,
+};
+use crate::constraint_system::{StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{Field, PrimeField};
+use merlin::Transcript;
+use num_traits::{One, Zero};
+
+/// Security level of the parameters, in bits.
+const SECURITY_LEVEL: u32 = 128;
+
+/// Parameters of the Rescue permutation over `F`.
+///
+/// They are derived deterministically from the field, so the prover and the
+/// verifier always agree on them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RescueParameters<F>
+where
+    F: PrimeField,
+{
+    /// Exponent of the S-box
+    alpha: u64,
+
+    /// Exponent of the inverse S-box, as little-endian limbs
+    alpha_inv: Vec<u64>,
+
+    /// Constants added to the state after each half round
+    round_constants: Vec<[F; WIDTH]>,
+
+    /// Matrix mixing the state in each half round
+    mds: [[F; WIDTH]; WIDTH],
+}
+
+impl<F> RescueParameters<F>
+where
+    F: PrimeField,
+{
+    /// Derives the parameters for `F`.
+    ///
+    /// The S-box exponent is the smallest prime `alpha` such that `x^alpha`
+    /// is a permutation of `F`, and the number of rounds is derived from it
+    /// as in the Rescue-Prime specification. The round constants are
+    /// squeezed from a transcript and the mixing matrix is the Cauchy matrix
+    /// `1 / (i + j + 3)`.
+    pub fn new() -> Self {
+        let alpha = sbox_exponent::<F>();
+        let rounds = num_rounds(alpha);
+
+        let mut transcript = Transcript::new(b"ark-plonk-rescue");
+        transcript.append_u64(b"alpha", alpha);
+        transcript.append_u64(b"rounds", rounds as u64);
+        let round_constants = (0..2 * rounds)
+            .map(|_| {
+                let mut constants = [F::zero(); WIDTH];
+                for constant in constants.iter_mut() {
+                    let mut bytes = [0u8; 64];
+                    transcript.challenge_bytes(b"round_constant", &mut bytes);
+                    *constant = F::from_le_bytes_mod_order(&bytes);
+                }
+                constants
+            })
+            .collect();
+
+        Self {
+            alpha,
+            alpha_inv: inverse_exponent::<F>(alpha),
+            round_constants,
+            mds: cauchy_mds(),
+        }
+    }
+
+    /// Returns the exponent of the S-box.
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /// Returns the number of rounds of the permutation, each applying the
+    /// S-box and its inverse.
+    pub fn rounds(&self) -> usize {
+        self.round_constants.len() / 2
+    }
+
+    /// Hashes `inputs` into a single field element.
+    ///
+    /// The capacity element of the state is initialized with the number of
+    /// inputs, so inputs of different lengths never collide by padding.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = [F::zero(); WIDTH];
+        state[0] = F::from(inputs.len() as u64);
+        for chunk in inputs.chunks(RATE) {
+            state[1..]
+                .iter_mut()
+                .zip(chunk)
+                .for_each(|(element, input)| *element += input);
+            self.permute(&mut state);
+        }
+        if inputs.is_empty() {
+            self.permute(&mut state);
+        }
+        state[1]
+    }
+
+    /// Applies the Rescue permutation to `state`.
+    fn permute(&self, state: &mut [F; WIDTH]) {
+        for (half_round, constants) in self.round_constants.iter().enumerate() {
+            let exponent: &[u64] = match half_round % 2 {
+                0 => &[self.alpha],
+                _ => &self.alpha_inv,
+            };
+            state
+                .iter_mut()
+                .for_each(|element| *element = element.pow(exponent));
+            mix(&self.mds, state);
+            state
+                .iter_mut()
+                .zip(constants)
+                .for_each(|(element, constant)| *element += constant);
+        }
+    }
+}
+
+impl<F> Default for RescueParameters<F>
+where
+    F: PrimeField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the number of rounds of Rescue-Prime with the S-box exponent
+/// `alpha`, as computed by the reference implementation: 50% more than the
+/// rounds of the best known Gröbner basis attack, and at least 8.
+fn num_rounds(alpha: u64) -> usize {
+    let m = WIDTH as u64;
+    let attacked_rounds = (1..25u64)
+        .find(|rounds| {
+            // The attack costs the square of the binomial coefficient
+            // `(v + d) choose v`
+            let d = (alpha - 1) * m * (rounds - 1) / 2 + 2;
+            let v = m * (rounds - 1) + RATE as u64;
+            binomial_exceeds(v + d, v, SECURITY_LEVEL / 2)
+        })
+        .unwrap_or(25);
+    let rounds = attacked_rounds.max(5) as usize;
+    3 * rounds / 2 + rounds % 2
+}
+
+/// Checks whether the binomial coefficient `n choose k` is at least
+/// `2^bits`.
+fn binomial_exceeds(n: u64, k: u64, bits: u32) -> bool {
+    let bound = 1u128 << bits;
+    let mut binomial = 1u128;
+    for i in 1..=k {
+        // `binomial` is `(n - k + i - 1) choose (i - 1)`, which grows with
+        // `i`, so it can stop as soon as it reaches the bound
+        binomial = binomial * (n - k + i) as u128 / i as u128;
+        if binomial >= bound {
+            return true;
+        }
+    }
+    false
+}
+
+/// Returns the exponent `1 / alpha` modulo `p - 1`, as little-endian limbs,
+/// such that `x^(1 / alpha)` inverts the S-box of `F`.
+fn inverse_exponent<F>(alpha: u64) -> Vec<u64>
+where
+    F: PrimeField,
+{
+    // `k (p - 1) + 1` is divisible by `alpha` for some `k < alpha`, and the
+    // quotient is the inverse of `alpha`
+    let rem = (modulus_rem::<F>(alpha) + alpha - 1) % alpha;
+    let k = (1..alpha)
+        .find(|k| k * rem % alpha == alpha - 1)
+        .expect("alpha is coprime to p - 1");
+
+    let mut limbs = F::characteristic().to_vec();
+    limbs[0] -= 1;
+    let mut carry = 0u128;
+    for limb in limbs.iter_mut() {
+        let product = *limb as u128 * k as u128 + carry;
+        *limb = product as u64;
+        carry = product >> 64;
+    }
+    limbs.push(carry as u64);
+    for limb in limbs.iter_mut() {
+        let (sum, overflow) = limb.overflowing_add(1);
+        *limb = sum;
+        if !overflow {
+            break;
+        }
+    }
+    let mut rem = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let dividend = (rem << 64) | *limb as u128;
+        *limb = (dividend / alpha as u128) as u64;
+        rem = dividend % alpha as u128;
+    }
+    debug_assert_eq!(rem, 0);
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    limbs
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the Rescue hash of `inputs` with `params`, returning
+    /// the [`Variable`] holding the result of [`RescueParameters::hash`].
+    pub fn rescue_hash(
+        &mut self,
+        params: &RescueParameters<E::Fr>,
+        inputs: &[Variable],
+    ) -> Variable {
+        let zero = self.zero_var;
+        let length = self.add(
+            (E::Fr::zero(), zero),
+            (E::Fr::zero(), zero),
+            E::Fr::from(inputs.len() as u64),
+            None,
+        );
+        let mut state = [length, zero, zero];
+        for chunk in inputs.chunks(RATE) {
+            for (element, input) in state[1..].iter_mut().zip(chunk) {
+                *element = self.add(
+                    (E::Fr::one(), *element),
+                    (E::Fr::one(), *input),
+                    E::Fr::zero(),
+                    None,
+                );
+            }
+            self.rescue_permutation(params, &mut state);
+        }
+        if inputs.is_empty() {
+            self.rescue_permutation(params, &mut state);
+        }
+        state[1]
+    }
+
+    /// Constrains the Rescue permutation of `state`.
+    ///
+    /// The constants of every half round are added by the gates mixing the
+    /// state.
+    fn rescue_permutation(
+        &mut self,
+        params: &RescueParameters<E::Fr>,
+        state: &mut [Variable; WIDTH],
+    ) {
+        for (half_round, constants) in params.round_constants.iter().enumerate()
+        {
+            for element in state.iter_mut() {
+                *element = match half_round % 2 {
+                    0 => self.poseidon_sbox(params.alpha, *element),
+                    _ => self.rescue_inverse_sbox(params, *element),
+                };
+            }
+
+            let mut mixed = *state;
+            for ((element, row), constant) in
+                mixed.iter_mut().zip(&params.mds).zip(constants)
+            {
+                *element = self.big_add(
+                    (row[0], state[0]),
+                    (row[1], state[1]),
+                    Some((row[2], state[2])),
+                    *constant,
+                    None,
+                );
+            }
+            *state = mixed;
+        }
+    }
+
+    /// Constrains `x^(1 / alpha)`, by computing it out of circuit and
+    /// checking that its `alpha`-th power is `x`.
+    fn rescue_inverse_sbox(
+        &mut self,
+        params: &RescueParameters<E::Fr>,
+        x: Variable,
+    ) -> Variable {
+        let root = self.variables[x.0].pow(&params.alpha_inv);
+        let root = self.add_input(root);
+        let power = self.poseidon_sbox(params.alpha, root);
+        self.assert_equal(power, x);
+        root
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::constraint_system::helper::*;
+    use crate::constraint_system::PoseidonParameters;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use rand_core::OsRng;
+
+    fn test_rescue_hash<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = RescueParameters::<E::Fr>::new();
+        assert!(params.rounds() >= 8);
+
+        // The inverse S-box inverts the S-box
+        let x = E::Fr::rand(&mut OsRng);
+        assert_eq!(x.pow([params.alpha()]).pow(&params.alpha_inv), x);
+        assert_eq!(x.pow(&params.alpha_inv).pow([params.alpha()]), x);
+
+        // The gadget agrees with the native hash for every input length
+        let mut hashes = Vec::new();
+        for length in 0..5u64 {
+            let values: Vec<E::Fr> =
+                (0..length).map(|i| E::Fr::from(i + 1)).collect();
+            let mut composer = StandardComposer::<E, P>::new();
+            let inputs: Vec<Variable> =
+                values.iter().map(|v| composer.add_input(*v)).collect();
+            let hash = composer.rescue_hash(&params, &inputs);
+            assert_eq!(composer.variables[hash.0], params.hash(&values));
+            hashes.push(params.hash(&values));
+        }
+
+        // The length is part of the hash, which differs from Poseidon
+        assert_ne!(hashes[1], params.hash(&[E::Fr::from(1u64), E::Fr::zero()]));
+        assert_ne!(
+            hashes[2],
+            PoseidonParameters::new()
+                .hash(&[E::Fr::from(1u64), E::Fr::from(2u64)])
+        );
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = RescueParameters::new();
+                let values = [E::Fr::from(3u64), E::Fr::from(5u64)];
+                let inputs: Vec<Variable> =
+                    values.iter().map(|v| composer.add_input(*v)).collect();
+                let hash = composer.rescue_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&values),
+                    None,
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_ok());
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let params = RescueParameters::new();
+                let inputs = [composer.add_input(E::Fr::from(3u64))];
+                let hash = composer.rescue_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    hash,
+                    params.hash(&[E::Fr::from(4u64)]),
+                    None,
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_rescue_hash],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_rescue_hash],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_rescue_hash],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,190 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Size statistics of a circuit, for capacity planning.
+
+use crate::constraint_system::StandardComposer;
+use crate::proof_system::Proof;
+use ark_ec::{PairingEngine, TEModelParameters};
+use num_traits::Zero;
+
+/// Summary of the size of a circuit and of its proofs.
+///
+/// The size of its keys is given by [`ProverKey::memory_footprint`] and by
+/// the `serialized_size` of the [`VerifierKey`].
+///
+/// [`ProverKey::memory_footprint`]:
+/// crate::proof_system::ProverKey::memory_footprint
+/// [`VerifierKey`]: crate::proof_system::VerifierKey
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CircuitStats {
+    /// Number of gates, including the ones added by every composer
+    pub gates: usize,
+
+    /// Size of the evaluation domain the circuit is padded to
+    pub domain_size: usize,
+
+    /// Number of witness variables
+    pub variables: usize,
+
+    /// Number of public inputs
+    pub public_inputs: usize,
+
+    /// Number of gates with the arithmetic selector set
+    pub arithmetic_gates: usize,
+
+    /// Number of gates chained to the next one with the lookahead selector
+    pub chained_gates: usize,
+
+    /// Number of range gates
+    pub range_gates: usize,
+
+    /// Number of logic gates
+    pub logic_gates: usize,
+
+    /// Number of fixed base scalar multiplication gates
+    pub fixed_group_add_gates: usize,
+
+    /// Number of variable base point addition gates
+    pub variable_group_add_gates: usize,
+
+    /// Bytes of a proof of the circuit serialized with compressed points
+    pub proof_bytes: usize,
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Returns the [`CircuitStats`] of the circuit built so far.
+    pub fn stats(&self) -> CircuitStats {
+        let count = |selector: &[E::Fr]| {
+            selector.iter().filter(|q| !q.is_zero()).count()
+        };
+        CircuitStats {
+            gates: self.n,
+            domain_size: self.n.next_power_of_two(),
+            variables: self.variables.len(),
+            public_inputs: self.public_inputs_sparse_store.len(),
+            arithmetic_gates: count(&self.q_arith),
+            chained_gates: count(&self.q_lookahead),
+            range_gates: count(&self.q_range),
+            logic_gates: count(&self.q_logic),
+            fixed_group_add_gates: count(&self.q_fixed_group_add),
+            variable_group_add_gates: count(&self.q_variable_group_add),
+            proof_bytes: Proof::<E, P>::serialized_size_hints().compressed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::proof_system::Prover;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::{Powers, KZG10};
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use ark_serialize::CanonicalSerialize;
+    use num_traits::One;
+    use rand_core::OsRng;
+
+    fn test_circuit_stats<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut prover: Prover<E, P> = Prover::new(b"stats");
+        let composer = prover.mut_cs();
+        let empty = composer.stats();
+        assert_eq!(empty.gates, 3);
+        assert_eq!(empty.domain_size, 4);
+        assert_eq!(empty.range_gates, 0);
+
+        let a = composer.add_input(E::Fr::from(5u64));
+        let b = composer.add_input(E::Fr::from(3u64));
+        composer.add(
+            (E::Fr::one(), a),
+            (E::Fr::one(), b),
+            E::Fr::zero(),
+            Some(-E::Fr::from(8u64)),
+        );
+        let arithmetic = composer.stats();
+        assert_eq!(arithmetic.arithmetic_gates, empty.arithmetic_gates + 1);
+        assert_eq!(arithmetic.public_inputs, 1);
+
+        composer.range_gate(a, 8);
+        composer.xor_gate(a, b, 8);
+        let stats = composer.stats();
+        assert_eq!(stats.gates, composer.circuit_size());
+        assert_eq!(stats.domain_size, stats.gates.next_power_of_two());
+        assert_eq!(stats.range_gates, 1);
+        assert_eq!(stats.logic_gates, 4);
+        assert_eq!(stats.fixed_group_add_gates, 0);
+
+        let n = stats.domain_size;
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, n, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        prover.preprocess(&powers).unwrap();
+        let proof = prover.prove(&powers).unwrap();
+
+        // Proofs have a constant size
+        let hints = Proof::<E, P>::serialized_size_hints();
+        assert_eq!(stats.proof_bytes, proof.serialized_size());
+        assert_eq!(hints.uncompressed, proof.uncompressed_size());
+
+        // 16 polynomials of n coefficients and 4n evaluations, plus the 4n
+        // evaluations of the vanishing polynomial
+        let prover_key = prover.prover_key.as_ref().unwrap();
+        assert_eq!(
+            prover_key.memory_footprint(),
+            (16 * 5 * n + 4 * n) * core::mem::size_of::<E::Fr>()
+        );
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_circuit_stats],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_circuit_stats],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_circuit_stats],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
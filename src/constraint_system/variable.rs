@@ -13,6 +13,53 @@
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Variable(pub(crate) usize);
 
+/// A [`Variable`] constrained to be either `0` or `1`, as returned by
+/// [`StandardComposer::boolean_gate`](super::StandardComposer::boolean_gate).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct BoolVariable(pub(crate) Variable);
+
+impl BoolVariable {
+    /// Returns the underlying [`Variable`].
+    pub fn variable(self) -> Variable {
+        self.0
+    }
+}
+
+impl From<BoolVariable> for Variable {
+    fn from(bit: BoolVariable) -> Self {
+        bit.0
+    }
+}
+
+/// A [`Variable`] constrained to fit in a number of bits, as returned by
+/// [`StandardComposer::range_gate`](super::StandardComposer::range_gate).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct WordVariable {
+    /// Underlying variable
+    pub(crate) var: Variable,
+
+    /// Number of bits the variable fits in
+    pub(crate) bits: usize,
+}
+
+impl WordVariable {
+    /// Returns the underlying [`Variable`].
+    pub fn variable(self) -> Variable {
+        self.var
+    }
+
+    /// Returns the number of bits the word fits in.
+    pub fn bits(self) -> usize {
+        self.bits
+    }
+}
+
+impl From<WordVariable> for Variable {
+    fn from(word: WordVariable) -> Self {
+        word.var
+    }
+}
+
 /// Stores the data for a specific wire in an arithmetic circuit
 /// This data is the gate index and the type of wire
 /// Left(1) signifies that this wire belongs to the first gate and is the left
@@ -0,0 +1,365 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Poseidon hash over a wider state.
+//!
+//! [`PoseidonParameters`](crate::constraint_system::PoseidonParameters)
+//! absorb two elements per permutation. [`WidePoseidonParameters`] permute a
+//! state of `width` elements instead, absorbing `width - 1` of them at once,
+//! so that the children of a node of a Merkle tree of arity `width - 1` are
+//! hashed by a single permutation.
+
+use crate::constraint_system::poseidon::{partial_rounds, sbox_exponent};
+use crate::constraint_system::{StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use merlin::Transcript;
+use num_traits::{One, Zero};
+
+/// Number of full rounds, half of them before the partial rounds and half
+/// after.
+const FULL_ROUNDS: usize = 8;
+
+/// Parameters of the Poseidon permutation over a state of `width` elements
+/// of `F`.
+///
+/// They are derived deterministically from the field and the width, so the
+/// prover and the verifier always agree on them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WidePoseidonParameters<F>
+where
+    F: PrimeField,
+{
+    /// Exponent of the S-box
+    alpha: u64,
+
+    /// Number of elements of the state
+    width: usize,
+
+    /// Constants added to the state at the start of every round
+    round_constants: Vec<Vec<F>>,
+
+    /// Matrix mixing the state at the end of every round
+    mds: Vec<Vec<F>>,
+}
+
+impl<F> WidePoseidonParameters<F>
+where
+    F: PrimeField,
+{
+    /// Derives the parameters for `F` and a state of `width` elements.
+    ///
+    /// The S-box exponent is the same as the one of
+    /// [`PoseidonParameters`](crate::constraint_system::PoseidonParameters),
+    /// the number of partial rounds is derived from it and the width, the
+    /// round constants are squeezed from a transcript bound to the width
+    /// and the mixing matrix is the Cauchy matrix `1 / (i + j + width)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not 3, 5 or 9, the widths hashing the children
+    /// of nodes of arity 2, 4 and 8.
+    pub fn new(width: usize) -> Self {
+        let alpha = sbox_exponent::<F>();
+        let partial_rounds = partial_rounds(alpha, width)
+            .expect("unsupported Poseidon state width");
+        let mut transcript = Transcript::new(b"ark-plonk-wide-poseidon");
+        transcript.append_u64(b"alpha", alpha);
+        transcript.append_u64(b"width", width as u64);
+        let round_constants = (0..FULL_ROUNDS + partial_rounds)
+            .map(|_| {
+                (0..width)
+                    .map(|_| {
+                        let mut bytes = [0u8; 64];
+                        transcript
+                            .challenge_bytes(b"round_constant", &mut bytes);
+                        F::from_le_bytes_mod_order(&bytes)
+                    })
+                    .collect()
+            })
+            .collect();
+        let mds = (0..width)
+            .map(|i| {
+                (0..width)
+                    .map(|j| {
+                        F::from((i + j + width) as u64)
+                            .inverse()
+                            .expect("entries of the Cauchy matrix are non-zero")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            alpha,
+            width,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Returns the exponent of the S-box.
+    pub fn alpha(&self) -> u64 {
+        self.alpha
+    }
+
+    /// Returns the number of elements of the state.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Hashes `inputs` into a single field element, absorbing `width - 1`
+    /// of them per permutation.
+    ///
+    /// The capacity element of the state is initialized with the number of
+    /// inputs, so inputs of different lengths never collide by padding.
+    pub fn hash(&self, inputs: &[F]) -> F {
+        let mut state = vec![F::zero(); self.width];
+        state[0] = F::from(inputs.len() as u64);
+        for chunk in inputs.chunks(self.width - 1) {
+            state[1..]
+                .iter_mut()
+                .zip(chunk)
+                .for_each(|(element, input)| *element += input);
+            self.permute(&mut state);
+        }
+        if inputs.is_empty() {
+            self.permute(&mut state);
+        }
+        state[1]
+    }
+
+    /// Returns `true` if the S-box is applied to the whole state in `round`.
+    fn is_full_round(&self, round: usize) -> bool {
+        round < FULL_ROUNDS / 2
+            || round >= self.round_constants.len() - FULL_ROUNDS / 2
+    }
+
+    /// Applies the Poseidon permutation to `state`.
+    fn permute(&self, state: &mut [F]) {
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            state
+                .iter_mut()
+                .zip(constants)
+                .for_each(|(element, constant)| *element += constant);
+            if self.is_full_round(round) {
+                state
+                    .iter_mut()
+                    .for_each(|element| *element = element.pow([self.alpha]));
+            } else {
+                state[0] = state[0].pow([self.alpha]);
+            }
+
+            let mixed: Vec<_> = self
+                .mds
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(state.iter())
+                        .fold(F::zero(), |acc, (m, s)| acc + *m * s)
+                })
+                .collect();
+            state.copy_from_slice(&mixed);
+        }
+    }
+}
+
+impl<E, P> StandardComposer<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Constrains the wide Poseidon hash of `inputs` with `params`,
+    /// returning the [`Variable`] holding the result of
+    /// [`WidePoseidonParameters::hash`].
+    pub fn wide_poseidon_hash(
+        &mut self,
+        params: &WidePoseidonParameters<E::Fr>,
+        inputs: &[Variable],
+    ) -> Variable {
+        let zero = self.zero_var;
+        let length = self.add(
+            (E::Fr::zero(), zero),
+            (E::Fr::zero(), zero),
+            E::Fr::from(inputs.len() as u64),
+            None,
+        );
+        let mut state = vec![zero; params.width];
+        state[0] = length;
+        for chunk in inputs.chunks(params.width - 1) {
+            for (element, input) in state[1..].iter_mut().zip(chunk) {
+                *element = self.add(
+                    (E::Fr::one(), *element),
+                    (E::Fr::one(), *input),
+                    E::Fr::zero(),
+                    None,
+                );
+            }
+            self.wide_poseidon_permutation(params, &mut state);
+        }
+        if inputs.is_empty() {
+            self.wide_poseidon_permutation(params, &mut state);
+        }
+        state[1]
+    }
+
+    /// Constrains the wide Poseidon permutation of `state`.
+    ///
+    /// The constants of every round are added by the gates mixing the state
+    /// at the end of the previous round.
+    fn wide_poseidon_permutation(
+        &mut self,
+        params: &WidePoseidonParameters<E::Fr>,
+        state: &mut [Variable],
+    ) {
+        let zero = self.zero_var;
+        for (element, constant) in
+            state.iter_mut().zip(&params.round_constants[0])
+        {
+            *element = self.add(
+                (E::Fr::one(), *element),
+                (E::Fr::zero(), zero),
+                *constant,
+                None,
+            );
+        }
+
+        for round in 0..params.round_constants.len() {
+            if params.is_full_round(round) {
+                for element in state.iter_mut() {
+                    *element = self.poseidon_sbox(params.alpha, *element);
+                }
+            } else {
+                state[0] = self.poseidon_sbox(params.alpha, state[0]);
+            }
+
+            let mixed: Vec<_> = params
+                .mds
+                .iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    let constant = params
+                        .round_constants
+                        .get(round + 1)
+                        .map_or(E::Fr::zero(), |constants| constants[i]);
+                    let terms: Vec<_> = row
+                        .iter()
+                        .copied()
+                        .zip(state.iter().copied())
+                        .collect();
+                    self.linear_combination(&terms, constant)
+                })
+                .collect();
+            state.copy_from_slice(&mixed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::constraint_system::helper::*;
+    use crate::constraint_system::poseidon::modulus_rem;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use rand_core::OsRng;
+
+    fn test_wide_poseidon_hash<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        // The gadget agrees with the native hash for a single and several
+        // permutations
+        for (width, length) in [(5, 4), (5, 6), (9, 8)] {
+            let params = WidePoseidonParameters::<E::Fr>::new(width);
+            assert_ne!(modulus_rem::<E::Fr>(params.alpha()), 1);
+            let inputs: Vec<_> =
+                (0..length).map(|_| E::Fr::rand(&mut OsRng)).collect();
+            let hash = params.hash(&inputs);
+            let mut longer = inputs.clone();
+            longer.push(E::Fr::zero());
+            assert_ne!(hash, params.hash(&longer));
+
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let inputs: Vec<_> = inputs
+                        .iter()
+                        .map(|input| composer.add_input(*input))
+                        .collect();
+                    let output = composer.wide_poseidon_hash(&params, &inputs);
+                    composer.constrain_to_constant(
+                        output,
+                        E::Fr::zero(),
+                        Some(-hash),
+                    );
+                },
+                1 << 12,
+            );
+            assert!(res.is_ok());
+        }
+    }
+
+    fn test_wide_poseidon_wrong_output<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let params = WidePoseidonParameters::<E::Fr>::new(5);
+        let inputs: Vec<_> = (0..4).map(|_| E::Fr::rand(&mut OsRng)).collect();
+        let hash = params.hash(&inputs);
+
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let inputs: Vec<_> = inputs
+                    .iter()
+                    .map(|input| composer.add_input(*input))
+                    .collect();
+                let output = composer.wide_poseidon_hash(&params, &inputs);
+                composer.constrain_to_constant(
+                    output,
+                    E::Fr::zero(),
+                    Some(-(hash + E::Fr::one())),
+                );
+            },
+            1 << 11,
+        );
+        assert!(res.is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_wide_poseidon_hash, test_wide_poseidon_wrong_output],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_wide_poseidon_hash, test_wide_poseidon_wrong_output],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_wide_poseidon_hash, test_wide_poseidon_wrong_output],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
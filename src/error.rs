@@ -7,11 +7,15 @@
 //! A collection of all possible errors encountered in PLONK.
 
 /// Defines all possible errors that can be encountered in PLONK.
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     // FFT errors
     /// This error occurs when an error triggers on any of the fft module
     /// functions.
+    #[error(
+        "evaluation domain of size 2^{log_size_of_group} is larger than the 2^{adacity} supported by the scalar field, reduce the circuit size"
+    )]
     InvalidEvalDomainSize {
         /// Log size of the group
         log_size_of_group: u32,
@@ -21,25 +25,115 @@ pub enum Error {
 
     // Prover/Verifier errors
     /// This error occurs when a proof verification fails.
+    #[error("proof verification failed")]
     ProofVerificationError,
     /// This error occurs when the circuit is not provided with all of the
     /// required inputs.
+    #[error("circuit inputs not found")]
     CircuitInputsNotFound,
     /// This error occurs when we want to verify a Proof but the pi_constructor
     /// attribute is uninitialized.
+    #[error("PI generator uninitialized")]
     UninitializedPIGenerator,
     /// PublicInput serialization error
+    #[error("invalid public input bytes")]
     InvalidPublicInputBytes,
+    /// This error occurs when the number of public input values doesn't
+    /// match the number of public input positions of the circuit.
+    #[error("expected {expected} public input values but got {actual}")]
+    PublicInputLengthMismatch {
+        /// Number of public input positions of the circuit
+        expected: usize,
+        /// Number of public input values provided
+        actual: usize,
+    },
+    /// This error occurs when a public input is given a value of a
+    /// different kind than the one declared by the circuit.
+    #[error("public input {index} is a {expected:?}, got a {actual:?}")]
+    PublicInputKindMismatch {
+        /// Index of the public input, in declaration order
+        index: usize,
+        /// Kind declared by the circuit
+        expected: crate::circuit::PublicInputKind,
+        /// Kind of the value provided
+        actual: crate::circuit::PublicInputKind,
+    },
+    /// This error occurs when public input values are assigned to a
+    /// position that isn't a public input of the circuit.
+    #[error("position {position} isn't a public input of the circuit")]
+    PublicInputPositionMismatch {
+        /// Position that isn't a public input of the circuit
+        position: usize,
+    },
+    /// This error occurs when a public input is assigned to a position past
+    /// the end of the circuit.
+    #[error(
+        "public input position {position} is out of range for a circuit of \
+         {circuit_size} gates"
+    )]
+    PublicInputPositionOutOfRange {
+        /// Position of the public input
+        position: usize,
+        /// Padded size of the circuit
+        circuit_size: usize,
+    },
+    /// This error occurs when the same position is assigned several public
+    /// input values.
+    #[error("public input position {position} is assigned twice")]
+    DuplicatePublicInputPosition {
+        /// Position assigned twice
+        position: usize,
+    },
+    /// This error occurs when a value is assigned to a variable that doesn't
+    /// belong to the circuit.
+    #[error("variable {index} doesn't belong to the circuit")]
+    UnknownVariable {
+        /// Index of the variable
+        index: usize,
+    },
+    /// This error occurs when a proof doesn't open the wire commitments of
+    /// the witness commitment it was requested for.
+    #[error("the proof doesn't open the committed witness")]
+    WitnessCommitmentMismatch,
     /// This error occurs when the Prover structure already contains a
     /// preprocessed circuit inside, but you call preprocess again.
+    #[error("circuit has already been preprocessed")]
     CircuitAlreadyPreprocessed,
 
     // Preprocessing errors
     /// This error occurs when an error triggers during the preprocessing
     /// stage.
+    #[error("the length of the wires is not the same")]
     MismatchedPolyLen,
+    /// This error occurs when committing with a Lagrange basis to a number
+    /// of evaluations other than the size of its domain.
+    #[error("expected {expected} evaluations for the Lagrange basis but got {actual}")]
+    LagrangeBasisSizeMismatch {
+        /// Size of the domain of the basis
+        expected: usize,
+        /// Number of evaluations provided
+        actual: usize,
+    },
+    /// This error occurs when an
+    /// [`MsmWorker`](crate::proof_system::distributed::MsmWorker) doesn't
+    /// return a partial sum for every shard it was given.
+    #[error("expected {expected} partial sums of MSM shards but got {actual}")]
+    MsmShardCountMismatch {
+        /// Number of shards given to the worker
+        expected: usize,
+        /// Number of partial sums returned
+        actual: usize,
+    },
+    /// This error occurs when the result of a chained gate isn't placed on
+    /// the left wire of the gate that follows it.
+    #[error("the result of chained gate {gate_index} isn't the left wire of the next gate")]
+    LookaheadWireMismatch {
+        /// Index of the chained gate
+        gate_index: usize,
+    },
 
     /// Polynomial Commitment errors
+    #[error("polynomial commitment error: {error:?}")]
     PCError {
         /// Polynomial Commitment errors
         error: ark_poly_commit::error::Error,
@@ -49,31 +143,183 @@ pub enum Error {
     // XXX: Are these errors still used?
     /// This error occurs when the user tries to create PublicParameters
     /// and supplies the max degree as zero.
+    #[error("cannot create PublicParameters with max degree 0")]
     DegreeIsZero,
     /// This error occurs when the user tries to trim PublicParameters
     /// to a degree that is larger than the maximum degree.
+    #[error("cannot trim more than the maximum degree")]
     TruncatedDegreeTooLarge,
     /// This error occurs when the user tries to trim PublicParameters
     /// down to a degree that is zero.
+    #[error("cannot trim PublicParameters to a maximum size of zero")]
     TruncatedDegreeIsZero,
     /// This error occurs when the user tries to commit to a polynomial whose
     /// degree is larger than the supported degree for that proving key.
-    PolynomialDegreeTooLarge,
+    #[error(
+        "polynomial of degree {degree} exceeds the maximum degree {max_degree} supported by the universal parameters, set them up with a larger degree"
+    )]
+    PolynomialDegreeTooLarge {
+        /// Degree that was requested
+        degree: usize,
+        /// Maximum degree supported by the parameters
+        max_degree: usize,
+    },
+    /// This error occurs when the universal parameters don't have enough
+    /// powers to commit to the polynomials of a circuit.
+    #[error(
+        "circuit of size {circuit_size} needs {required_powers} powers but the universal parameters only have {available_powers}, set them up with a degree of at least {circuit_size}"
+    )]
+    UniversalParamsTooSmall {
+        /// Circuit size padded to the next power of two
+        circuit_size: usize,
+        /// Number of powers needed by the circuit
+        required_powers: usize,
+        /// Number of powers of the universal parameters
+        available_powers: usize,
+    },
+    /// This error occurs when the universal parameters aren't a well formed
+    /// powers of tau setup, see [`check_srs`](crate::srs::check_srs).
+    #[error("invalid universal parameters: {reason}")]
+    InvalidUniversalParams {
+        /// Check failed by the parameters
+        reason: &'static str,
+    },
+    /// This error occurs when parameters aren't a valid contribution to the
+    /// parameters of a ceremony.
+    #[error("invalid ceremony contribution: {reason}")]
+    InvalidContribution {
+        /// Check failed by the contribution
+        reason: &'static str,
+    },
     /// This error occurs when the user tries to commit to a polynomial whose
     /// degree is zero.
+    #[error("cannot commit to polynomial of zero degree")]
     PolynomialDegreeIsZero,
     /// This error occurs when the pairing check fails at being equal to the
     /// Identity point.
+    #[error("pairing check failed")]
     PairingCheckFailure,
+    /// This error occurs when a
+    /// [`ProofVersion::V1`](crate::proof_system::ProofVersion::V1) proof is
+    /// verified, which is no longer supported.
+    #[error("legacy proofs are not accepted")]
+    LegacyProofRejected,
+    /// This error occurs when a
+    /// [`KeyEquivalenceProof`](crate::proof_system::KeyEquivalenceProof)
+    /// doesn't show that two verifier keys describe the same circuit.
+    #[error("the verifier keys are not shown to describe the same circuit")]
+    KeyEquivalenceFailure,
+    /// This error occurs when a
+    /// [`VrfProof`](crate::constraint_system::ecc::vrf::VrfProof) doesn't
+    /// verify for the given public key and input.
+    #[error("the VRF proof is invalid")]
+    VrfVerificationFailure,
+    /// This error occurs when an RSA signature doesn't verify for the given
+    /// [`RsaPublicKey`](crate::gadgets::rsa::RsaPublicKey) and digest.
+    #[error("the RSA signature is invalid")]
+    RsaVerificationFailure,
+    /// This error occurs when the pairing check of a
+    /// [`DeferredPairingCheck`](crate::gadgets::bls::DeferredPairingCheck)
+    /// fails.
+    #[error("the BLS signature is invalid")]
+    BlsVerificationFailure,
+    /// This error occurs when a leaf is appended to a full
+    /// [`IncrementalMerkleTree`](crate::gadgets::merkle::IncrementalMerkleTree).
+    #[error("the Merkle tree already holds {capacity} leaves")]
+    MerkleTreeFull {
+        /// Number of leaves of the tree
+        capacity: u64,
+    },
 
     /// This error occurs when there are not enough bytes to read out of a
     /// slice during deserialization.
+    #[error("not enough bytes left to read")]
     NotEnoughBytes,
     /// This error occurs when a malformed point is decoded from a byte array.
+    #[error("point bytes malformed")]
     PointMalformed,
+    /// This error occurs when a commitment of a proof or a verifier key isn't
+    /// a point of the prime order subgroup of the curve.
+    #[error("commitment {index} is not in the prime order subgroup")]
+    InvalidCommitment {
+        /// Index of the commitment in serialization order
+        index: usize,
+    },
+    /// This error occurs when a point given as a public input doesn't
+    /// satisfy the curve equation.
+    #[error("point public input at gate {gate_index} is not on the curve")]
+    PointNotOnCurve {
+        /// Gate position of the x coordinate of the point
+        gate_index: usize,
+    },
     /// This error occurs when a malformed scalar is decoded from a byte
     /// array.
+    #[error("scalar bytes malformed")]
     ScalarMalformed,
+
+    // Key storage errors
+    /// This error occurs when a key storage backend fails to read or write
+    /// an entry.
+    #[error("key storage error: {error}")]
+    StorageError {
+        /// Underlying I/O error
+        error: std::io::Error,
+    },
+    /// This error occurs when a key or universal parameters stored in a key
+    /// storage backend don't match the digest they are stored under, or when
+    /// a circuit compiles to a verifier key of another digest than the one
+    /// expected.
+    #[error("key doesn't match its expected digest")]
+    KeyDigestMismatch,
+    /// This error occurs when a serialized artifact doesn't start with the
+    /// [`ARTIFACT_MAGIC`](crate::artifact::ARTIFACT_MAGIC) bytes.
+    #[error("not a serialized ark-plonk artifact")]
+    InvalidArtifactMagic,
+    /// This error occurs when a serialized artifact has a format version this
+    /// crate doesn't support.
+    #[error("unsupported artifact format version {version}")]
+    UnsupportedArtifactVersion {
+        /// Version of the artifact
+        version: u16,
+    },
+    /// This error occurs when a serialized artifact isn't of the kind being
+    /// read, for instance a verifier key read as a proof.
+    #[error("expected a {expected:?} artifact, found a {found:?}")]
+    ArtifactKindMismatch {
+        /// Kind being read
+        expected: crate::artifact::ArtifactKind,
+        /// Kind of the artifact
+        found: crate::artifact::ArtifactKind,
+    },
+    /// This error occurs when a serialized artifact was generated over other
+    /// curves than the ones it is read for.
+    #[error(
+        "artifact was serialized for curve {found:02x?}, expected curve {expected:02x?}"
+    )]
+    ArtifactCurveMismatch {
+        /// Curve identifier of the type being read
+        expected: [u8; 8],
+        /// Curve identifier of the artifact
+        found: [u8; 8],
+    },
+    /// This error occurs when the circuit size of the header of a serialized
+    /// artifact doesn't match the artifact.
+    #[error(
+        "artifact header declares a circuit of size {header} but the artifact has size {actual}"
+    )]
+    ArtifactCircuitSizeMismatch {
+        /// Circuit size of the header
+        header: u64,
+        /// Circuit size of the artifact
+        actual: usize,
+    },
+    /// This error occurs when a stored key cannot be serialized or
+    /// deserialized.
+    #[error("key serialization error: {error}")]
+    SerializationError {
+        /// Underlying serialization error
+        error: ark_serialize::SerializationError,
+    },
 }
 
 impl From<ark_poly_commit::error::Error> for Error {
@@ -82,64 +328,14 @@ impl From<ark_poly_commit::error::Error> for Error {
     }
 }
 
-#[cfg(feature = "std")]
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InvalidEvalDomainSize {
-                log_size_of_group,
-                adacity,
-            } => write!(
-                f,
-                "Log-size of the EvaluationDomain group > TWO_ADACITY\
-            Size: {:?} > TWO_ADACITY = {:?}",
-                log_size_of_group, adacity
-            ),
-            Self::ProofVerificationError => {
-                write!(f, "proof verification failed")
-            }
-            Self::CircuitInputsNotFound => {
-                write!(f, "circuit inputs not found")
-            }
-            Self::UninitializedPIGenerator => {
-                write!(f, "PI generator uninitialized")
-            }
-            Self::InvalidPublicInputBytes => {
-                write!(f, "invalid public input bytes")
-            }
-            Self::MismatchedPolyLen => {
-                write!(f, "the length of the wires is not the same")
-            }
-            Self::PCError { error } => {
-                write!(f, "{:?}", error)
-            }
-            Self::CircuitAlreadyPreprocessed => {
-                write!(f, "circuit has already been preprocessed")
-            }
-            Self::DegreeIsZero => {
-                write!(f, "cannot create PublicParameters with max degree 0")
-            }
-            Self::TruncatedDegreeTooLarge => {
-                write!(f, "cannot trim more than the maximum degree")
-            }
-            Self::TruncatedDegreeIsZero => write!(
-                f,
-                "cannot trim PublicParameters to a maximum size of zero"
-            ),
-            Self::PolynomialDegreeTooLarge => write!(
-                f,
-                "proving key is not large enough to commit to said polynomial"
-            ),
-            Self::PolynomialDegreeIsZero => {
-                write!(f, "cannot commit to polynomial of zero degree")
-            }
-            Self::PairingCheckFailure => write!(f, "pairing check failed"),
-            Self::NotEnoughBytes => write!(f, "not enough bytes left to read"),
-            Self::PointMalformed => write!(f, "point bytes malformed"),
-            Self::ScalarMalformed => write!(f, "scalar bytes malformed"),
-        }
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::StorageError { error }
     }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for Error {}
+impl From<ark_serialize::SerializationError> for Error {
+    fn from(error: ark_serialize::SerializationError) -> Self {
+        Self::SerializationError { error }
+    }
+}
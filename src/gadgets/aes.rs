@@ -0,0 +1,609 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! AES-128 block encryption.
+//!
+//! [`encrypt`] computes the AES-128 encryption of a block out of circuit,
+//! with S-box and `xtime` tables, and [`aes_128_gadget`] constrains the same
+//! computation, so that circuits can prove statements about legacy
+//! ciphertexts, e.g. that a TLS record encrypted with a secret key in
+//! counter mode decrypts to a value with some property.
+//!
+//! The composer has no lookup argument, so the gadget works on the bits of
+//! the state. XORs cost a gate per bit, `xtime` only moves bits, and every
+//! S-box witnesses the inverse of its input in `GF(2^8)`, checked by a
+//! carry-less multiplication, before applying the affine map. Without
+//! lookups, selecting the output among the 256 entries of the table would
+//! take a tree of 255 conditional selections instead.
+//!
+//! An S-box takes 173 gates, 64 of them for the carry-less product, and the
+//! 160 S-boxes of the rounds and 40 of the key schedule make up most of the
+//! 42536 gates of a block, which fits in a circuit of `2^16` gates.
+
+use crate::constraint_system::{BoolVariable, StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{BigInteger, Field, PrimeField};
+use num_traits::{One, Zero};
+
+/// Number of rounds of AES-128.
+const ROUNDS: usize = 10;
+
+/// Reduction of `x^8` modulo the AES polynomial `x^8 + x^4 + x^3 + x + 1`.
+const REDUCTION: u8 = 0x1b;
+
+/// Constant of the affine map of the S-box.
+const AFFINE_CONSTANT: u8 = 0x63;
+
+/// Bits of a byte, from the least significant one.
+type Byte = [BoolVariable; 8];
+
+/// Returns the product of `byte` and `x` in `GF(2^8)`.
+const fn xtime(byte: u8) -> u8 {
+    (byte << 1) ^ if byte & 0x80 == 0 { 0 } else { REDUCTION }
+}
+
+/// Returns the product of `a` and `b` in `GF(2^8)`.
+const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+/// Returns the inverse of `byte` in `GF(2^8)`, or zero for zero, as
+/// `byte^254`.
+const fn gf_inverse(byte: u8) -> u8 {
+    let mut inverse = 1;
+    let mut bit = 8;
+    while bit > 0 {
+        bit -= 1;
+        inverse = gf_mul(inverse, inverse);
+        if (254 >> bit) & 1 == 1 {
+            inverse = gf_mul(inverse, byte);
+        }
+    }
+    inverse
+}
+
+/// Table of the AES S-box, the affine map of the inverse in `GF(2^8)`.
+const SBOX: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let inverse = gf_inverse(i as u8);
+        table[i] = inverse
+            ^ inverse.rotate_left(1)
+            ^ inverse.rotate_left(2)
+            ^ inverse.rotate_left(3)
+            ^ inverse.rotate_left(4)
+            ^ AFFINE_CONSTANT;
+        i += 1;
+    }
+    table
+};
+
+/// Table of the products by `x` in `GF(2^8)`.
+const XTIME: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = xtime(i as u8);
+        i += 1;
+    }
+    table
+};
+
+/// Returns the round keys of `key`.
+fn expand_key(key: &[u8; 16]) -> [[u8; 16]; ROUNDS + 1] {
+    let mut keys = [*key; ROUNDS + 1];
+    let mut rcon = 1;
+    for round in 1..=ROUNDS {
+        let (previous, next) = keys.split_at_mut(round);
+        let (previous, next) = (&previous[round - 1], &mut next[0]);
+        for i in 0..4 {
+            next[i] = previous[i]
+                ^ SBOX[previous[12 + (i + 1) % 4] as usize]
+                ^ if i == 0 { rcon } else { 0 };
+        }
+        for i in 4..16 {
+            next[i] = previous[i] ^ next[i - 4];
+        }
+        rcon = XTIME[rcon as usize];
+    }
+    keys
+}
+
+/// Returns the AES-128 encryption of `block` under `key`.
+pub fn encrypt(key: &[u8; 16], block: &[u8; 16]) -> [u8; 16] {
+    let keys = expand_key(key);
+    let mut state = *block;
+    state.iter_mut().zip(&keys[0]).for_each(|(s, k)| *s ^= k);
+    for (round, key) in keys.iter().enumerate().skip(1) {
+        state.iter_mut().for_each(|s| *s = SBOX[*s as usize]);
+        state = shift_rows(&state);
+        if round < ROUNDS {
+            for column in state.chunks_mut(4) {
+                let all = column.iter().fold(0, |acc, s| acc ^ s);
+                let first = column[0];
+                for i in 0..4 {
+                    let next = column.get(i + 1).copied().unwrap_or(first);
+                    column[i] ^= all ^ XTIME[(column[i] ^ next) as usize];
+                }
+            }
+        }
+        state.iter_mut().zip(key).for_each(|(s, k)| *s ^= k);
+    }
+    state
+}
+
+/// Shifts the row `r` of the column-major `state` left by `r` columns.
+fn shift_rows<T>(state: &[T; 16]) -> [T; 16]
+where
+    T: Copy,
+{
+    let mut shifted = *state;
+    for (i, byte) in shifted.iter_mut().enumerate() {
+        let (row, column) = (i % 4, i / 4);
+        *byte = state[row + 4 * ((column + row) % 4)];
+    }
+    shifted
+}
+
+/// Constrains the AES-128 encryption of `block` under `key`, returning the
+/// bytes of the result of [`encrypt`].
+///
+/// The bytes of the key and of the block are constrained to fit in eight
+/// bits.
+pub fn aes_128_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    key: &[Variable; 16],
+    block: &[Variable; 16],
+) -> [Variable; 16]
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let zero = BoolVariable(composer.zero_var());
+    let mut round_key = [[zero; 8]; 16];
+    let mut state = [[zero; 8]; 16];
+    for (bits, byte) in round_key.iter_mut().zip(key) {
+        *bits = byte_bits(composer, *byte);
+    }
+    for ((bits, byte), key) in state.iter_mut().zip(block).zip(&round_key) {
+        let byte = byte_bits(composer, *byte);
+        *bits = xor_bytes(composer, &byte, key);
+    }
+
+    let mut rcon = 1;
+    for round in 1..=ROUNDS {
+        for byte in state.iter_mut() {
+            *byte = sbox_gadget(composer, byte);
+        }
+        state = shift_rows(&state);
+        if round < ROUNDS {
+            for column in state.chunks_mut(4) {
+                mix_column(composer, column);
+            }
+        }
+
+        let previous = round_key;
+        for i in 0..4 {
+            let substituted =
+                sbox_gadget(composer, &previous[12 + (i + 1) % 4]);
+            let byte = xor_bytes(composer, &previous[i], &substituted);
+            round_key[i] = if i == 0 {
+                xor_constant(composer, &byte, rcon)
+            } else {
+                byte
+            };
+        }
+        for i in 4..16 {
+            round_key[i] = xor_bytes(composer, &previous[i], &round_key[i - 4]);
+        }
+        rcon = XTIME[rcon as usize];
+
+        for (byte, key) in state.iter_mut().zip(&round_key) {
+            *byte = xor_bytes(composer, byte, key);
+        }
+    }
+
+    let mut ciphertext = [composer.zero_var(); 16];
+    for (byte, bits) in ciphertext.iter_mut().zip(&state) {
+        *byte = byte_variable(composer, bits);
+    }
+    ciphertext
+}
+
+/// Returns the value of the byte held by `bits`.
+fn byte_value<E, P>(composer: &StandardComposer<E, P>, bits: &Byte) -> u8
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    bits.iter().enumerate().fold(0, |byte, (i, bit)| {
+        byte | ((!composer.variables[bit.variable().0].is_zero() as u8) << i)
+    })
+}
+
+/// Constrains the bits of `byte`, which must fit in eight bits.
+fn byte_bits<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    byte: Variable,
+) -> Byte
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let value = composer.variables[byte.0].into_repr().to_bits_le();
+    let mut bits = [BoolVariable(composer.zero_var()); 8];
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = composer.add_bool(value[i]);
+    }
+    let recomposed = byte_variable(composer, &bits);
+    composer.assert_equal(recomposed, byte);
+    bits
+}
+
+/// Returns a [`Variable`] holding the byte of `bits`.
+fn byte_variable<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    bits: &Byte,
+) -> Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let terms: Vec<_> = bits
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| (E::Fr::from(1u64 << i), bit.variable()))
+        .collect();
+    composer.linear_combination(&terms, E::Fr::zero())
+}
+
+/// Constrains the XOR of two bits, `a + b - 2 a b`.
+fn xor_bits<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: BoolVariable,
+    b: BoolVariable,
+) -> BoolVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    BoolVariable(composer.big_arith(
+        -one.double(),
+        a.variable(),
+        b.variable(),
+        one,
+        one,
+        None,
+        E::Fr::zero(),
+        None,
+    ))
+}
+
+/// Constrains the XOR of two bytes.
+fn xor_bytes<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &Byte,
+    b: &Byte,
+) -> Byte
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut xor = *a;
+    for (bit, b) in xor.iter_mut().zip(b) {
+        *bit = xor_bits(composer, *bit, *b);
+    }
+    xor
+}
+
+/// Constrains the XOR of a byte and the constant `c`, flipping the bits set
+/// in `c`.
+fn xor_constant<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &Byte,
+    c: u8,
+) -> Byte
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut xor = *a;
+    for (i, bit) in xor.iter_mut().enumerate() {
+        if (c >> i) & 1 == 1 {
+            let zero = composer.zero_var();
+            *bit = BoolVariable(composer.add(
+                (-E::Fr::one(), bit.variable()),
+                (E::Fr::zero(), zero),
+                E::Fr::one(),
+                None,
+            ));
+        }
+    }
+    xor
+}
+
+/// Returns the bits of the product of `byte` and `x`, which only moves
+/// bits around and XORs the top one into the reduction.
+fn xtime_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    byte: &Byte,
+) -> Byte
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut shifted = *byte;
+    shifted.rotate_right(1);
+    for (i, bit) in shifted.iter_mut().enumerate().skip(1) {
+        if (REDUCTION >> i) & 1 == 1 {
+            *bit = xor_bits(composer, *bit, byte[7]);
+        }
+    }
+    shifted
+}
+
+/// Constrains the mixing of the four bytes of a `column`.
+fn mix_column<E, P>(composer: &mut StandardComposer<E, P>, column: &mut [Byte])
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut all = xor_bytes(composer, &column[0], &column[1]);
+    all = xor_bytes(composer, &all, &column[2]);
+    all = xor_bytes(composer, &all, &column[3]);
+    let original = [column[0], column[1], column[2], column[3]];
+    for (i, byte) in column.iter_mut().enumerate() {
+        let pair = xor_bytes(composer, &original[i], &original[(i + 1) % 4]);
+        let doubled = xtime_gadget(composer, &pair);
+        let mixed = xor_bytes(composer, byte, &all);
+        *byte = xor_bytes(composer, &mixed, &doubled);
+    }
+}
+
+/// Constrains the S-box of a byte.
+///
+/// The inverse `y` of the input `x` is witnessed, and the coefficients of
+/// the carry-less product `x y` are accumulated as integers of at most
+/// eight terms. After reduction, every coefficient must have the parity of
+/// the matching bit of one, or of zero if `x` is zero, in which case `y`
+/// must be zero too.
+fn sbox_gadget<E, P>(composer: &mut StandardComposer<E, P>, x: &Byte) -> Byte
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let zero = E::Fr::zero();
+    let x_value = byte_value(composer, x);
+    let inverse = gf_inverse(x_value);
+    let mut y = *x;
+    for (i, bit) in y.iter_mut().enumerate() {
+        *bit = composer.add_bool((inverse >> i) & 1 == 1);
+    }
+
+    // `is_zero` is set if and only if `x` is zero, and then `y` is zero
+    let x_variable = byte_variable(composer, x);
+    let y_variable = byte_variable(composer, &y);
+    let is_zero = composer.add_bool(x_value == 0).variable();
+    let x_inverse = composer
+        .add_input(E::Fr::from(x_value).inverse().unwrap_or_else(E::Fr::zero));
+    let zero_var = composer.zero_var();
+    composer.poly_gate(
+        x_variable, x_inverse, is_zero, one, zero, zero, one, -one, None,
+    );
+    composer.poly_gate(
+        x_variable, is_zero, zero_var, one, zero, zero, zero, zero, None,
+    );
+    composer.poly_gate(
+        y_variable, is_zero, zero_var, one, zero, zero, zero, zero, None,
+    );
+
+    let mut coefficients = [zero_var; 15];
+    for (i, a) in x.iter().enumerate() {
+        for (j, b) in y.iter().enumerate() {
+            coefficients[i + j] = composer.big_mul(
+                one,
+                a.variable(),
+                b.variable(),
+                Some((one, coefficients[i + j])),
+                zero,
+                None,
+            );
+        }
+    }
+
+    let half = one.double().inverse().expect("the field has odd order");
+    for bit in 0..8 {
+        // Coefficients of `x^8` to `x^14` reduced to `x^bit`
+        let mut terms: Vec<_> = (8..15)
+            .filter(|degree| (reduce_monomial(*degree) >> bit) & 1 == 1)
+            .map(|degree| (one, coefficients[degree]))
+            .collect();
+        terms.push((one, coefficients[bit]));
+        let sum = terms
+            .iter()
+            .fold(zero, |acc, (_, c)| acc + composer.variables[c.0]);
+        let (target, constant) = if bit == 0 {
+            (one - composer.variables[is_zero.0], -one)
+        } else {
+            (zero, zero)
+        };
+        let carry = composer.add_input((sum - target) * half);
+        composer.range_gate(carry, 6);
+        terms.push((-one.double(), carry));
+        if bit == 0 {
+            terms.push((one, is_zero));
+        }
+        let difference = composer.linear_combination(&terms, constant);
+        composer.constrain_to_constant(difference, zero, None);
+    }
+
+    let mut output = y;
+    for (i, bit) in output.iter_mut().enumerate() {
+        let mut acc = y[i];
+        for shift in 4..8 {
+            acc = xor_bits(composer, acc, y[(i + shift) % 8]);
+        }
+        *bit = acc;
+    }
+    xor_constant(composer, &output, AFFINE_CONSTANT)
+}
+
+/// Returns the bits of `x^degree` reduced modulo the AES polynomial, for
+/// degrees from 8.
+fn reduce_monomial(degree: usize) -> u8 {
+    (8..degree).fold(REDUCTION, |reduced, _| xtime(reduced))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use rand_core::{OsRng, RngCore};
+
+    /// Key, plaintext and ciphertext of the examples of FIPS-197.
+    const VECTORS: [[[u8; 16]; 3]; 2] = [
+        [
+            [
+                0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7,
+                0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c,
+            ],
+            [
+                0x32, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31,
+                0x98, 0xa2, 0xe0, 0x37, 0x07, 0x34,
+            ],
+            [
+                0x39, 0x25, 0x84, 0x1d, 0x02, 0xdc, 0x09, 0xfb, 0xdc, 0x11,
+                0x85, 0x97, 0x19, 0x6a, 0x0b, 0x32,
+            ],
+        ],
+        [
+            [
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+                0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            ],
+            [
+                0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99,
+                0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff,
+            ],
+            [
+                0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd,
+                0xb7, 0x80, 0x70, 0xb4, 0xc5, 0x5a,
+            ],
+        ],
+    ];
+
+    #[test]
+    fn test_encrypt() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x53], 0xed);
+        for [key, plaintext, ciphertext] in VECTORS {
+            assert_eq!(encrypt(&key, &plaintext), ciphertext);
+        }
+    }
+
+    fn test_sbox_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut inputs = [0u8; 12];
+        OsRng.fill_bytes(&mut inputs[1..]);
+        let sbox = |input: u8, output: u8| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let input = composer.add_input(E::Fr::from(input));
+                    let bits = byte_bits(composer, input);
+                    let substituted = sbox_gadget(composer, &bits);
+                    let substituted = byte_variable(composer, &substituted);
+                    composer.constrain_to_constant(
+                        substituted,
+                        E::Fr::from(output),
+                        None,
+                    );
+                },
+                1 << 9,
+            )
+        };
+        for input in inputs {
+            assert!(sbox(input, SBOX[input as usize]).is_ok());
+            assert!(sbox(input, SBOX[input as usize] ^ 1).is_err());
+        }
+    }
+
+    fn test_aes_128_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let [key, plaintext, ciphertext] = VECTORS[0];
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let mut bytes = [[composer.zero_var(); 16]; 2];
+                for (variables, values) in
+                    bytes.iter_mut().zip([key, plaintext])
+                {
+                    for (variable, value) in variables.iter_mut().zip(values) {
+                        *variable = composer.add_input(E::Fr::from(value));
+                    }
+                }
+                let encrypted = aes_128_gadget(composer, &bytes[0], &bytes[1]);
+                for (byte, value) in encrypted.iter().zip(ciphertext) {
+                    composer.constrain_to_constant(
+                        *byte,
+                        E::Fr::from(value),
+                        None,
+                    );
+                }
+            },
+            1 << 16,
+        );
+        assert!(res.is_ok());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_sbox_gadget, test_aes_128_gadget],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_sbox_gadget, test_aes_128_gadget],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_sbox_gadget, test_aes_128_gadget],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
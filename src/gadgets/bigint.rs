@@ -0,0 +1,770 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Arithmetic on integers wider than the scalar field.
+//!
+//! A [`BigUintVariable`] holds an unsigned integer as limbs of
+//! [`LIMB_BITS`] bits, each constrained by a range gate. Products of limbs
+//! fit in the field with room to spare, so [`mul_mod_gadget`] checks
+//! `a b = q m + r` column by column: the difference of the two sides in
+//! each column is witnessed to be a multiple of `2^64` carried into the
+//! next one, with signed carries range checked around zero.
+
+use crate::constraint_system::{BoolVariable, StandardComposer, Variable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{Field, PrimeField};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
+
+/// Number of bits of the limbs of a [`BigUintVariable`].
+pub const LIMB_BITS: usize = 64;
+
+/// Unsigned integer held by limbs of [`LIMB_BITS`] bits, from the least
+/// significant one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BigUintVariable {
+    /// Limbs of the integer, each constrained to [`LIMB_BITS`] bits
+    pub(crate) limbs: Vec<Variable>,
+}
+
+impl BigUintVariable {
+    /// Adds the `num_limbs` limbs of `value` to `composer`, constraining
+    /// each of them to [`LIMB_BITS`] bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` doesn't fit in `num_limbs` limbs.
+    pub fn allocate<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: &BigUint,
+        num_limbs: usize,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        assert!(
+            value.bits() as usize <= num_limbs * LIMB_BITS,
+            "the value doesn't fit in the limbs"
+        );
+        Self::witness(composer, value, num_limbs)
+    }
+
+    /// Returns the integer fixed to `value` in `num_limbs` limbs as a part
+    /// of the circuit description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` doesn't fit in `num_limbs` limbs.
+    pub fn constant<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: &BigUint,
+        num_limbs: usize,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        assert!(
+            value.bits() as usize <= num_limbs * LIMB_BITS,
+            "the value doesn't fit in the limbs"
+        );
+        let limbs = limb_values(value, num_limbs)
+            .into_iter()
+            .map(|limb| composer.add_constant(E::Fr::from(limb)))
+            .collect();
+        Self { limbs }
+    }
+
+    /// Adds the low `num_limbs` limbs of `value` to `composer`, constraining
+    /// each of them to [`LIMB_BITS`] bits.
+    fn witness<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: &BigUint,
+        num_limbs: usize,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let limbs = limb_values(value, num_limbs)
+            .into_iter()
+            .map(|limb| {
+                let limb = composer.add_input(E::Fr::from(limb));
+                composer.range_gate(limb, LIMB_BITS);
+                limb
+            })
+            .collect();
+        Self { limbs }
+    }
+
+    /// Returns the limbs of the integer, from the least significant one.
+    pub fn limbs(&self) -> &[Variable] {
+        &self.limbs
+    }
+
+    /// Returns the value of the integer in the witness of `composer`.
+    pub fn value<E, P>(&self, composer: &StandardComposer<E, P>) -> BigUint
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        self.limb_values(composer)
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |value, limb| (value << LIMB_BITS) + limb)
+    }
+
+    /// Returns the values of the limbs in the witness of `composer`.
+    fn limb_values<E, P>(&self, composer: &StandardComposer<E, P>) -> Vec<u64>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        self.limbs
+            .iter()
+            .map(|limb| composer.variables[limb.0].into_repr().as_ref()[0])
+            .collect()
+    }
+}
+
+/// Returns the low `num_limbs` limbs of `value`.
+fn limb_values(value: &BigUint, num_limbs: usize) -> Vec<u64> {
+    let mut limbs = value.to_u64_digits();
+    limbs.resize(num_limbs, 0);
+    limbs
+}
+
+/// Returns the field element of the signed integer `value`.
+fn to_field<F>(value: &BigInt) -> F
+where
+    F: PrimeField,
+{
+    let (sign, bytes) = value.to_bytes_le();
+    let magnitude = F::from_le_bytes_mod_order(&bytes);
+    if sign == Sign::Minus {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Constrains `a` and `b` to hold the same integer, limb by limb.
+///
+/// The integer with fewer limbs is padded with zeros.
+pub fn assert_equal_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let zero = composer.zero_var();
+    for i in 0..a.limbs.len().max(b.limbs.len()) {
+        let a = a.limbs.get(i).copied().unwrap_or(zero);
+        let b = b.limbs.get(i).copied().unwrap_or(zero);
+        composer.assert_equal(a, b);
+    }
+}
+
+/// Returns `a` if `bit` is set and `b` otherwise, selecting their limbs
+/// with [`StandardComposer::conditional_select`].
+///
+/// The integer with fewer limbs is padded with zeros.
+pub fn conditional_select_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    bit: BoolVariable,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let zero = composer.zero_var();
+    let limbs = (0..a.limbs.len().max(b.limbs.len()))
+        .map(|i| {
+            let a = a.limbs.get(i).copied().unwrap_or(zero);
+            let b = b.limbs.get(i).copied().unwrap_or(zero);
+            composer.conditional_select(bit, a, b)
+        })
+        .collect();
+    BigUintVariable { limbs }
+}
+
+/// Constrains `a` to be strictly less than `b`.
+///
+/// The difference `b - a - 1` is witnessed limb by limb with borrows, and
+/// the most significant limb can't borrow, so the difference is
+/// non-negative.
+pub fn assert_less_than_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let zero = composer.zero_var();
+    let num_limbs = a.limbs.len().max(b.limbs.len());
+    let a_values = limb_values(&a.value(composer), num_limbs);
+    let b_values = limb_values(&b.value(composer), num_limbs);
+
+    let mut borrow = zero;
+    let mut borrow_value = 1;
+    for i in 0..num_limbs {
+        let difference_value =
+            b_values[i] as i128 - a_values[i] as i128 - borrow_value;
+        let mut terms = vec![
+            (one, b.limbs.get(i).copied().unwrap_or(zero)),
+            (-one, a.limbs.get(i).copied().unwrap_or(zero)),
+            (-one, borrow),
+        ];
+        if i + 1 < num_limbs {
+            let next = composer.add_bool(difference_value < 0);
+            terms.push((
+                E::Fr::from(2u64).pow([LIMB_BITS as u64]),
+                next.variable(),
+            ));
+            borrow = next.variable();
+            borrow_value = (difference_value < 0) as i128;
+        }
+        let constant = if i == 0 { -one } else { E::Fr::zero() };
+        let difference = composer.linear_combination(&terms, constant);
+        composer.range_gate(difference, LIMB_BITS);
+    }
+}
+
+/// Constrains the sum of `a` and `b` modulo `modulus`, returning the
+/// remainder, which is constrained to be less than `modulus` and has as
+/// many limbs.
+///
+/// `a` and `b` must be less than `modulus` for the circuit to be
+/// satisfiable.
+pub fn add_mod_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+    modulus: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let sum = a.value(composer) + b.value(composer);
+    let modulus_value = modulus.value(composer);
+    let wraps = sum >= modulus_value;
+    let remainder = if wraps { sum - modulus_value } else { sum };
+    linear_mod(composer, a, b, modulus, false, &remainder, wraps)
+}
+
+/// Constrains the difference of `a` and `b` modulo `modulus`, returning
+/// the remainder, which is constrained to be less than `modulus` and has as
+/// many limbs.
+///
+/// `a` and `b` must be less than `modulus` for the circuit to be
+/// satisfiable.
+pub fn sub_mod_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+    modulus: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let (a_value, b_value) = (a.value(composer), b.value(composer));
+    let wraps = a_value < b_value;
+    let remainder = if wraps {
+        a_value + modulus.value(composer) - b_value
+    } else {
+        a_value - b_value
+    };
+    linear_mod(composer, a, b, modulus, true, &remainder, wraps)
+}
+
+/// Constrains `a + b = r + k m`, or `a - b = r - k m` if `negate` is set,
+/// with the bit `k` set if `wraps` and `r` the canonical `remainder`, which
+/// is returned.
+fn linear_mod<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+    modulus: &BigUintVariable,
+    negate: bool,
+    remainder: &BigUint,
+    wraps: bool,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let (sign, sign_value) = if negate { (-one, -1) } else { (one, 1) };
+    let zero = composer.zero_var();
+    let num_limbs = modulus.limbs.len();
+    let remainder = BigUintVariable::witness(composer, remainder, num_limbs);
+    assert_less_than_gadget(composer, &remainder, modulus);
+    let wraps_value = wraps as i128;
+    let wraps = composer.add_bool(wraps).variable();
+
+    let width = a.limbs.len().max(b.limbs.len()).max(num_limbs);
+    let values: Vec<_> = [a, b, &remainder, modulus]
+        .iter()
+        .map(|x| {
+            let mut values = x.limb_values(composer);
+            values.resize(width, 0);
+            values
+        })
+        .collect();
+    let columns: Vec<_> = (0..width)
+        .map(|i| {
+            let limb =
+                |x: &BigUintVariable| x.limbs.get(i).copied().unwrap_or(zero);
+            let product =
+                composer.mul(-sign, wraps, limb(modulus), E::Fr::zero(), None);
+            let terms = vec![
+                (one, limb(a)),
+                (sign, limb(b)),
+                (-one, limb(&remainder)),
+                (one, product),
+            ];
+            let [a, b, remainder, modulus] =
+                [0, 1, 2, 3].map(|x| values[x][i] as i128);
+            let value =
+                a + sign_value * (b - wraps_value * modulus) - remainder;
+            (terms, BigInt::from(value))
+        })
+        .collect();
+    assert_columns_zero(composer, columns, LIMB_BITS + 2);
+    remainder
+}
+
+/// Constrains the product of `a` and `b`, returning it with as many limbs
+/// as `a` and `b` together.
+pub fn mul_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let num_limbs = a.limbs.len() + b.limbs.len();
+    let product = a.value(composer) * b.value(composer);
+    let product = BigUintVariable::witness(composer, &product, num_limbs);
+
+    // Columns of a b - p
+    let (accumulators, values) =
+        product_columns(composer, &[(one, a, b)], num_limbs);
+    let product_values = product.limb_values(composer);
+    let columns = accumulators
+        .into_iter()
+        .zip(values)
+        .zip(product.limbs.iter().zip(product_values))
+        .map(|((accumulator, value), (limb, limb_value))| {
+            (vec![(one, accumulator), (-one, *limb)], value - limb_value)
+        })
+        .collect();
+    let terms = a.limbs.len().min(b.limbs.len());
+    assert_columns_zero(composer, columns, product_bits(terms));
+    product
+}
+
+/// Constrains `a` modulo `modulus`, returning the remainder, which is
+/// constrained to be less than `modulus` and has as many limbs.
+///
+/// The quotient has as many limbs as `a`, so that any integer can be
+/// reduced.
+pub fn reduce_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    modulus: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let zero = composer.zero_var();
+    let num_limbs = modulus.limbs.len();
+    let (quotient, remainder) =
+        div_rem(&a.value(composer), &modulus.value(composer));
+    let quotient = BigUintVariable::witness(composer, &quotient, a.limbs.len());
+    let remainder = BigUintVariable::witness(composer, &remainder, num_limbs);
+    assert_less_than_gadget(composer, &remainder, modulus);
+
+    // Columns of q m + r - a
+    let width = a.limbs.len() + num_limbs;
+    let (accumulators, mut values) =
+        product_columns(composer, &[(one, &quotient, modulus)], width);
+    for (value, limb) in values.iter_mut().zip(remainder.limb_values(composer))
+    {
+        *value += limb;
+    }
+    for (value, limb) in values.iter_mut().zip(a.limb_values(composer)) {
+        *value -= limb;
+    }
+
+    let columns = accumulators
+        .into_iter()
+        .zip(values)
+        .enumerate()
+        .map(|(k, (accumulator, value))| {
+            let limb =
+                |x: &BigUintVariable| x.limbs.get(k).copied().unwrap_or(zero);
+            let terms = vec![
+                (one, accumulator),
+                (one, limb(&remainder)),
+                (-one, limb(a)),
+            ];
+            (terms, value)
+        })
+        .collect();
+    let terms = a.limbs.len().min(num_limbs) + 1;
+    assert_columns_zero(composer, columns, product_bits(terms));
+    remainder
+}
+
+/// Constrains the product of `a` and `b` modulo `modulus`, returning the
+/// remainder, which is constrained to be less than `modulus` and has as
+/// many limbs.
+///
+/// The quotient has as many limbs as the modulus too, so `a` and `b` must
+/// be less than `modulus` for the circuit to be satisfiable.
+pub fn mul_mod_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    b: &BigUintVariable,
+    modulus: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let zero = composer.zero_var();
+    let num_limbs = modulus.limbs.len();
+    let product = a.value(composer) * b.value(composer);
+    let (quotient, remainder) = div_rem(&product, &modulus.value(composer));
+    let quotient = BigUintVariable::witness(composer, &quotient, num_limbs);
+    let remainder = BigUintVariable::witness(composer, &remainder, num_limbs);
+    assert_less_than_gadget(composer, &remainder, modulus);
+
+    // Columns of a b - q m - r
+    let width = (a.limbs.len() + b.limbs.len()).max(2 * num_limbs) - 1;
+    let (accumulators, mut values) = product_columns(
+        composer,
+        &[(one, a, b), (-one, &quotient, modulus)],
+        width,
+    );
+    let remainder_values = remainder.limb_values(composer);
+    for (value, limb) in values.iter_mut().zip(remainder_values) {
+        *value -= limb;
+    }
+
+    let columns = accumulators
+        .into_iter()
+        .zip(values)
+        .enumerate()
+        .map(|(k, (accumulator, value))| {
+            let limb = remainder.limbs.get(k).copied().unwrap_or(zero);
+            (vec![(one, accumulator), (-one, limb)], value)
+        })
+        .collect();
+    let terms = a.limbs.len().min(b.limbs.len()) + num_limbs;
+    assert_columns_zero(composer, columns, product_bits(terms));
+    remainder
+}
+
+/// Returns the quotient and the remainder of `a` by `b`, or zeros if `b`
+/// is zero, in which case the circuit is unsatisfiable anyway.
+fn div_rem(a: &BigUint, b: &BigUint) -> (BigUint, BigUint) {
+    if b.is_zero() {
+        (BigUint::zero(), BigUint::zero())
+    } else {
+        (a / b, a % b)
+    }
+}
+
+/// Returns the number of bits of the columns of sums of `terms` products
+/// of limbs.
+fn product_bits(terms: usize) -> usize {
+    2 * LIMB_BITS + (usize::BITS - terms.leading_zeros()) as usize
+}
+
+/// Accumulates the products of the limbs of the pairs of `products`, with
+/// their coefficient of one or minus one, into `width` columns, in circuit
+/// and out of it.
+fn product_columns<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    products: &[(E::Fr, &BigUintVariable, &BigUintVariable)],
+    width: usize,
+) -> (Vec<Variable>, Vec<BigInt>)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let mut accumulators = vec![composer.zero_var(); width];
+    let mut values = vec![BigInt::zero(); width];
+    for (sign, x, y) in products {
+        let x_values = x.limb_values(composer);
+        let y_values = y.limb_values(composer);
+        for (i, x) in x.limbs.iter().enumerate() {
+            for (j, y) in y.limbs.iter().enumerate() {
+                accumulators[i + j] = composer.big_mul(
+                    *sign,
+                    *x,
+                    *y,
+                    Some((one, accumulators[i + j])),
+                    E::Fr::zero(),
+                    None,
+                );
+                let term =
+                    BigInt::from(x_values[i] as u128 * y_values[j] as u128);
+                if *sign == one {
+                    values[i + j] += term;
+                } else {
+                    values[i + j] -= term;
+                }
+            }
+        }
+    }
+    (accumulators, values)
+}
+
+/// Column of [`assert_columns_zero`]: its weighted terms and its value out
+/// of circuit.
+type Column<F> = (Vec<(F, Variable)>, BigInt);
+
+/// Constrains the integer whose limbs are the `columns` to be zero, with
+/// the terms of each column and its value out of circuit, below `2^bits` in
+/// absolute value.
+///
+/// Each column and the carry it receives is witnessed to be a multiple of
+/// `2^64`, carried into the next one, and the last column can't carry.
+/// Carries are below `2^(bits - 64 + 1)` in absolute value, and range
+/// checked around zero.
+fn assert_columns_zero<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    columns: Vec<Column<E::Fr>>,
+    bits: usize,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let zero = composer.zero_var();
+    let two_64 = E::Fr::from(2u64).pow([LIMB_BITS as u64]);
+    let carry_bits = bits - LIMB_BITS + 2;
+    let carry_bits = carry_bits + carry_bits % 2;
+    let carry_offset = E::Fr::from(2u64).pow([carry_bits as u64 - 1]);
+    let num_columns = columns.len();
+    let mut carry = zero;
+    let mut carry_value = BigInt::zero();
+    for (k, (mut terms, value)) in columns.into_iter().enumerate() {
+        terms.push((one, carry));
+        if k + 1 < num_columns {
+            carry_value = (value + carry_value) >> LIMB_BITS;
+            carry = composer.add_input(to_field(&carry_value));
+            terms.push((-two_64, carry));
+            let shifted = composer.add(
+                (one, carry),
+                (E::Fr::zero(), zero),
+                carry_offset,
+                None,
+            );
+            composer.range_gate(shifted, carry_bits);
+        }
+        let difference = composer.linear_combination(&terms, E::Fr::zero());
+        composer.constrain_to_constant(difference, E::Fr::zero(), None);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use rand_core::{OsRng, RngCore};
+
+    fn random_biguint(num_limbs: usize) -> BigUint {
+        (0..num_limbs).fold(BigUint::zero(), |value, _| {
+            (value << LIMB_BITS) + OsRng.next_u64()
+        })
+    }
+
+    fn test_mul_mod_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let modulus = random_biguint(4) | (BigUint::one() << 255);
+        let a = random_biguint(4) % &modulus;
+        let b = random_biguint(4) % &modulus;
+        let mul_mod = |expected: BigUint| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = BigUintVariable::allocate(composer, &a, 4);
+                    let b = BigUintVariable::allocate(composer, &b, 4);
+                    let modulus =
+                        BigUintVariable::constant(composer, &modulus, 4);
+                    let product = mul_mod_gadget(composer, &a, &b, &modulus);
+                    let expected =
+                        BigUintVariable::constant(composer, &expected, 4);
+                    assert_equal_gadget(composer, &product, &expected);
+                },
+                1 << 10,
+            )
+        };
+        assert!(mul_mod(&a * &b % &modulus).is_ok());
+        assert!(mul_mod(&a * &b % &modulus + 1u64).is_err());
+    }
+
+    fn test_add_sub_mod_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let modulus = random_biguint(4) | (BigUint::one() << 255);
+        let a = random_biguint(4) % &modulus;
+        let b = random_biguint(4) % &modulus;
+        let add_sub = |a: &BigUint, b: &BigUint, sum: &BigUint| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = BigUintVariable::allocate(composer, a, 4);
+                    let b = BigUintVariable::allocate(composer, b, 4);
+                    let modulus =
+                        BigUintVariable::constant(composer, &modulus, 4);
+                    let result = add_mod_gadget(composer, &a, &b, &modulus);
+                    let expected = BigUintVariable::constant(composer, sum, 4);
+                    assert_equal_gadget(composer, &result, &expected);
+                    let result =
+                        sub_mod_gadget(composer, &result, &b, &modulus);
+                    assert_equal_gadget(composer, &result, &a);
+                },
+                1 << 10,
+            )
+        };
+        let sum = (&a + &b) % &modulus;
+        assert!(add_sub(&a, &b, &sum).is_ok());
+        assert!(add_sub(&b, &a, &sum).is_ok());
+        assert!(add_sub(
+            &a,
+            &(&modulus - 1u64),
+            &((&a + &modulus - 1u64) % &modulus)
+        )
+        .is_ok());
+        assert!(add_sub(&a, &b, &(&sum ^ BigUint::one())).is_err());
+    }
+
+    fn test_mul_reduce_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let a = random_biguint(3);
+        let b = random_biguint(2);
+        let modulus = random_biguint(2) | BigUint::one();
+        let product = &a * &b;
+        let mul_reduce = |expected: BigUint| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = BigUintVariable::allocate(composer, &a, 3);
+                    let b = BigUintVariable::allocate(composer, &b, 2);
+                    let result = mul_gadget(composer, &a, &b);
+                    assert_eq!(result.value(composer), product);
+                    let modulus =
+                        BigUintVariable::constant(composer, &modulus, 2);
+                    let remainder = reduce_gadget(composer, &result, &modulus);
+                    let expected =
+                        BigUintVariable::constant(composer, &expected, 2);
+                    assert_equal_gadget(composer, &remainder, &expected);
+                },
+                1 << 10,
+            )
+        };
+        assert!(mul_reduce(&product % &modulus).is_ok());
+        assert!(mul_reduce((&product % &modulus) ^ BigUint::one()).is_err());
+    }
+
+    fn test_assert_less_than_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let less_than = |a: &BigUint, b: &BigUint| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = BigUintVariable::allocate(composer, a, 3);
+                    let b = BigUintVariable::allocate(composer, b, 3);
+                    assert_less_than_gadget(composer, &a, &b);
+                },
+                1 << 8,
+            )
+        };
+        let a = random_biguint(3);
+        let b = random_biguint(3);
+        let (a, b) = if a < b { (a, b) } else { (b, a) };
+        assert!(less_than(&a, &b).is_ok());
+        assert!(less_than(&b, &a).is_err());
+        assert!(less_than(&a, &a).is_err());
+        assert!(less_than(&(&a - 1u64), &a).is_ok());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [
+            test_mul_mod_gadget,
+            test_add_sub_mod_gadget,
+            test_mul_reduce_gadget,
+            test_assert_less_than_gadget
+        ],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [
+            test_mul_mod_gadget,
+            test_add_sub_mod_gadget,
+            test_mul_reduce_gadget,
+            test_assert_less_than_gadget
+        ],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [
+            test_mul_mod_gadget,
+            test_add_sub_mod_gadget,
+            test_mul_reduce_gadget,
+            test_assert_less_than_gadget
+        ],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,630 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! BLS signature verification with a deferred pairing check.
+//!
+//! BLS signatures with public keys in G1 and signatures in G2, as on
+//! BLS12-381 in Ethereum's consensus layer, verify with the pairing
+//! equation `e(pk, H(m)) = e(g1, sig)`, where the key of a set of signers
+//! is the sum of their keys. Pairings over a curve other than the one of
+//! the proof are far too expensive in circuit, so the check is split:
+//!
+//! - [`bls_verify_gadget`] constrains the group arithmetic, summing the
+//!   registered keys of the signers selected by private bits on G1 emulated
+//!   with [`BigUintVariable`]s, and exposes the aggregate key and the number of
+//!   signers as public inputs.
+//! - the verifier of the proof runs the pairing check of a
+//!   [`DeferredPairingCheck`] natively, and builds the public inputs of the
+//!   proof from its aggregate key with [`DeferredPairingCheck::public_inputs`].
+//!
+//! The message is hashed to G2 by the caller. The registered keys are fixed
+//! in the circuit description, so that a proof can't sum keys of the
+//! prover's choosing, and are taken as valid members of the prime order
+//! subgroup, with a proof of possession of their secret key against rogue
+//! key attacks: both are checked when keys are registered, not in circuit.
+
+use crate::constraint_system::{BoolVariable, StandardComposer};
+use crate::error::Error;
+use crate::gadgets::bigint::{
+    add_mod_gadget, assert_equal_gadget, conditional_select_gadget,
+    mul_mod_gadget, sub_mod_gadget, BigUintVariable, LIMB_BITS,
+};
+use ark_ec::short_weierstrass_jacobian::GroupAffine;
+use ark_ec::{
+    AffineCurve, PairingEngine, ProjectiveCurve, SWModelParameters,
+    TEModelParameters,
+};
+use ark_ff::{BigInteger, PrimeField};
+use core::marker::PhantomData;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Point of the short Weierstrass curve `C` whose coordinates, in the base
+/// field of `C`, are held by [`BigUintVariable`]s.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct SWPointVariable<C>
+where
+    C: SWModelParameters,
+{
+    /// X coordinate
+    x: BigUintVariable,
+
+    /// Y coordinate
+    y: BigUintVariable,
+
+    __: PhantomData<C>,
+}
+
+impl<C> SWPointVariable<C>
+where
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    /// Adds the coordinates of `point` to `composer`, constraining the
+    /// point to be on the curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` is the point at infinity.
+    pub fn allocate<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        point: &GroupAffine<C>,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        assert!(!point.infinity, "the point at infinity has no coordinates");
+        let num_limbs = num_limbs::<C>();
+        let x = BigUintVariable::allocate(
+            composer,
+            &to_biguint(&point.x),
+            num_limbs,
+        );
+        let y = BigUintVariable::allocate(
+            composer,
+            &to_biguint(&point.y),
+            num_limbs,
+        );
+
+        // y^2 = x^3 + a x + b
+        let modulus = modulus_variable::<_, _, C>(composer);
+        let y_squared = mul_mod_gadget(composer, &y, &y, &modulus);
+        let x_squared = mul_mod_gadget(composer, &x, &x, &modulus);
+        let mut rhs = mul_mod_gadget(composer, &x_squared, &x, &modulus);
+        if !C::COEFF_A.is_zero() {
+            let a = base_constant::<_, _, C>(composer, &C::COEFF_A);
+            let ax = mul_mod_gadget(composer, &a, &x, &modulus);
+            rhs = add_mod_gadget(composer, &rhs, &ax, &modulus);
+        }
+        let b = base_constant::<_, _, C>(composer, &C::COEFF_B);
+        let rhs = add_mod_gadget(composer, &rhs, &b, &modulus);
+        assert_equal_gadget(composer, &y_squared, &rhs);
+
+        Self {
+            x,
+            y,
+            __: PhantomData,
+        }
+    }
+
+    /// Returns the point fixed to `point` as a part of the circuit
+    /// description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `point` is the point at infinity.
+    pub fn constant<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        point: &GroupAffine<C>,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        assert!(!point.infinity, "the point at infinity has no coordinates");
+        Self {
+            x: base_constant::<_, _, C>(composer, &point.x),
+            y: base_constant::<_, _, C>(composer, &point.y),
+            __: PhantomData,
+        }
+    }
+
+    /// Returns the X coordinate of the point.
+    pub fn x(&self) -> &BigUintVariable {
+        &self.x
+    }
+
+    /// Returns the Y coordinate of the point.
+    pub fn y(&self) -> &BigUintVariable {
+        &self.y
+    }
+
+    /// Returns the value of the point in the witness of `composer`.
+    pub fn value<E, P>(
+        &self,
+        composer: &StandardComposer<E, P>,
+    ) -> GroupAffine<C>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let coordinate = |value: BigUint| {
+            C::BaseField::from_le_bytes_mod_order(&value.to_bytes_le())
+        };
+        GroupAffine::new(
+            coordinate(self.x.value(composer)),
+            coordinate(self.y.value(composer)),
+            false,
+        )
+    }
+}
+
+/// Returns the integer of the field element `value`.
+fn to_biguint<F>(value: &F) -> BigUint
+where
+    F: PrimeField,
+{
+    BigUint::from_bytes_le(&value.into_repr().to_bytes_le())
+}
+
+/// Returns the modulus of the base field of `C`.
+fn base_modulus<C>() -> BigUint
+where
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    to_biguint(&-C::BaseField::one()) + 1u64
+}
+
+/// Returns the number of limbs of the coordinates of points of `C`.
+fn num_limbs<C>() -> usize
+where
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    (base_modulus::<C>().bits() as usize).div_ceil(LIMB_BITS)
+}
+
+/// Returns the element `value` of the base field of `C` as a constant.
+fn base_constant<E, P, C>(
+    composer: &mut StandardComposer<E, P>,
+    value: &C::BaseField,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    BigUintVariable::constant(composer, &to_biguint(value), num_limbs::<C>())
+}
+
+/// Returns the modulus of the base field of `C` as a constant.
+fn modulus_variable<E, P, C>(
+    composer: &mut StandardComposer<E, P>,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    BigUintVariable::constant(composer, &base_modulus::<C>(), num_limbs::<C>())
+}
+
+/// Constrains the sum of the points `a` and `b`, whose X coordinates must
+/// differ.
+///
+/// The slope of the line through the points is computed with a witnessed
+/// inverse of the difference of their X coordinates, which doesn't exist
+/// if they are equal.
+fn add_points<E, P, C>(
+    composer: &mut StandardComposer<E, P>,
+    a: &SWPointVariable<C>,
+    b: &SWPointVariable<C>,
+) -> SWPointVariable<C>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    let modulus_value = base_modulus::<C>();
+    let modulus = modulus_variable::<_, _, C>(composer);
+    let dx = sub_mod_gadget(composer, &b.x, &a.x, &modulus);
+    let dy = sub_mod_gadget(composer, &b.y, &a.y, &modulus);
+
+    let inverse_value = dx
+        .value(composer)
+        .modpow(&(&modulus_value - 2u64), &modulus_value);
+    let inverse = BigUintVariable::allocate(
+        composer,
+        &inverse_value,
+        modulus.limbs().len(),
+    );
+    let product = mul_mod_gadget(composer, &dx, &inverse, &modulus);
+    let one = base_constant::<_, _, C>(composer, &C::BaseField::one());
+    assert_equal_gadget(composer, &product, &one);
+
+    // x = lambda^2 - x_a - x_b, y = lambda (x_a - x) - y_a
+    let lambda = mul_mod_gadget(composer, &dy, &inverse, &modulus);
+    let lambda_squared = mul_mod_gadget(composer, &lambda, &lambda, &modulus);
+    let x = sub_mod_gadget(composer, &lambda_squared, &a.x, &modulus);
+    let x = sub_mod_gadget(composer, &x, &b.x, &modulus);
+    let dx = sub_mod_gadget(composer, &a.x, &x, &modulus);
+    let y = mul_mod_gadget(composer, &lambda, &dx, &modulus);
+    let y = sub_mod_gadget(composer, &y, &a.y, &modulus);
+    SWPointVariable {
+        x,
+        y,
+        __: PhantomData,
+    }
+}
+
+/// Constrains the sum of the `keys` of the `signers`, returning the
+/// aggregate key.
+///
+/// The `keys` are not bound to anything by this gadget: the caller has to
+/// fix them, with [`SWPointVariable::constant`] as [`bls_verify_gadget`]
+/// does, or check them against a public commitment.
+///
+/// The sum starts from the generator of `C`, subtracted at the end, so that
+/// the incomplete addition formulas apply. Every key is added to the
+/// partial sum, whether its signer is selected or not, and the addition is
+/// unsatisfiable if both points have the same X coordinate. So the circuit
+/// is unsatisfiable if no key is selected, and for any selection of signers
+/// if a key is plus or minus the generator or a partial sum of the keys
+/// before it, which only happens with negligible probability for keys of
+/// independent secrets, but has to be ruled out when keys are registered.
+///
+/// # Panics
+///
+/// Panics if there isn't a bit for every key.
+pub fn aggregate_keys_gadget<E, P, C>(
+    composer: &mut StandardComposer<E, P>,
+    keys: &[SWPointVariable<C>],
+    signers: &[BoolVariable],
+) -> SWPointVariable<C>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    assert_eq!(keys.len(), signers.len(), "there must be a bit per key");
+    let generator = GroupAffine::<C>::prime_subgroup_generator();
+    let mut sum = SWPointVariable::constant(composer, &generator);
+    for (key, signer) in keys.iter().zip(signers) {
+        let added = add_points(composer, &sum, key);
+        sum = SWPointVariable {
+            x: conditional_select_gadget(composer, *signer, &added.x, &sum.x),
+            y: conditional_select_gadget(composer, *signer, &added.y, &sum.y),
+            __: PhantomData,
+        };
+    }
+    let offset = SWPointVariable::constant(composer, &-generator);
+    add_points(composer, &sum, &offset)
+}
+
+/// Constrains the aggregation of the registered `keys` of the `signers`
+/// with [`aggregate_keys_gadget`], and exposes the aggregate key and the
+/// number of signers as public inputs: the limbs of the X coordinate of the
+/// key, followed by the ones of its Y coordinate and by the number of
+/// signers, which the verifier compares to its threshold.
+///
+/// The `keys` are constants of the circuit, so that a proof for one set of
+/// registered keys doesn't verify with the verifier key of another.
+///
+/// The proof only attests the signature once the verifier has also run the
+/// pairing check of the [`DeferredPairingCheck`] whose
+/// [`public_inputs`](DeferredPairingCheck::public_inputs) it verified the
+/// proof with.
+///
+/// # Panics
+///
+/// Panics if there isn't a bit for every key.
+pub fn bls_verify_gadget<E, P, C>(
+    composer: &mut StandardComposer<E, P>,
+    keys: &[GroupAffine<C>],
+    signers: &[BoolVariable],
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    C: SWModelParameters,
+    C::BaseField: PrimeField,
+{
+    let keys = keys
+        .iter()
+        .map(|key| SWPointVariable::constant(composer, key))
+        .collect::<Vec<_>>();
+    let key = aggregate_keys_gadget(composer, &keys, signers);
+    for limb in key.x.limbs().iter().chain(key.y.limbs()) {
+        composer.set_public(*limb);
+    }
+    let count = signers.iter().fold(composer.zero_var(), |count, signer| {
+        composer.add(
+            (E::Fr::one(), count),
+            (E::Fr::one(), signer.variable()),
+            E::Fr::zero(),
+            None,
+        )
+    });
+    composer.set_public(count);
+}
+
+/// Pairing check of the verification of a BLS signature over the pairing
+/// `S`, with public keys in G1 and signatures in G2, deferred to the
+/// verifier of a proof of [`bls_verify_gadget`].
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct DeferredPairingCheck<S>
+where
+    S: PairingEngine,
+{
+    /// Sum of the keys of the signers
+    aggregate_key: S::G1Affine,
+
+    /// Number of signers
+    signer_count: usize,
+
+    /// Message hashed to G2
+    message: S::G2Affine,
+
+    /// Signature of the message
+    signature: S::G2Affine,
+}
+
+impl<S> DeferredPairingCheck<S>
+where
+    S: PairingEngine,
+{
+    /// Creates the check of the `signature` of `message`, hashed to G2, by
+    /// the `keys` of the `signers`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't a bit for every key.
+    pub fn new(
+        keys: &[S::G1Affine],
+        signers: &[bool],
+        message: S::G2Affine,
+        signature: S::G2Affine,
+    ) -> Self {
+        assert_eq!(keys.len(), signers.len(), "there must be a bit per key");
+        let signer_count = signers.iter().filter(|signer| **signer).count();
+        let aggregate_key = keys
+            .iter()
+            .zip(signers)
+            .filter(|(_, signer)| **signer)
+            .fold(S::G1Projective::zero(), |sum, (key, _)| sum.add_mixed(key))
+            .into_affine();
+        Self {
+            aggregate_key,
+            signer_count,
+            message,
+            signature,
+        }
+    }
+
+    /// Returns the sum of the keys of the signers.
+    pub fn aggregate_key(&self) -> S::G1Affine {
+        self.aggregate_key
+    }
+
+    /// Returns the number of signers.
+    pub fn signer_count(&self) -> usize {
+        self.signer_count
+    }
+
+    /// Checks that `e(pk, H(m)) = e(g1, sig)` for the aggregate key `pk`.
+    pub fn verify(&self) -> Result<(), Error> {
+        let generator = S::G1Affine::prime_subgroup_generator();
+        let product = S::product_of_pairings(&[
+            (self.aggregate_key.into(), self.message.into()),
+            ((-generator).into(), self.signature.into()),
+        ]);
+        if product.is_one() {
+            Ok(())
+        } else {
+            Err(Error::BlsVerificationFailure)
+        }
+    }
+
+    /// Returns the public inputs exposed by [`bls_verify_gadget`] for the
+    /// aggregate key and the number of signers, as elements of the scalar
+    /// field `F` of the proof.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the aggregate key is the point at infinity.
+    pub fn public_inputs<F, C>(&self) -> Vec<F>
+    where
+        F: PrimeField,
+        C: SWModelParameters,
+        C::BaseField: PrimeField,
+        S: PairingEngine<G1Affine = GroupAffine<C>>,
+    {
+        let key = self.aggregate_key;
+        assert!(!key.infinity, "the point at infinity has no coordinates");
+        let num_limbs = num_limbs::<C>();
+        [key.x, key.y]
+            .iter()
+            .flat_map(|coordinate| {
+                let mut limbs = to_biguint(coordinate).to_u64_digits();
+                limbs.resize(num_limbs, 0);
+                limbs
+            })
+            .chain([self.signer_count as u64])
+            .map(F::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::{g1, Bls12_381};
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use core::cell::RefCell;
+    use rand_core::OsRng;
+
+    type G1 = <Bls12_381 as PairingEngine>::G1Affine;
+    type G2 = <Bls12_381 as PairingEngine>::G2Affine;
+
+    /// Returns `n` keys, a message and the signature of all of the keys.
+    fn signature(n: usize) -> (Vec<G1>, G2, G2) {
+        let secrets: Vec<_> = (0..n)
+            .map(|_| <Bls12_381 as PairingEngine>::Fr::rand(&mut OsRng))
+            .collect();
+        let keys = secrets
+            .iter()
+            .map(|secret| G1::prime_subgroup_generator().mul(*secret))
+            .collect::<Vec<_>>();
+        let message = G2::prime_subgroup_generator()
+            .mul(<Bls12_381 as PairingEngine>::Fr::rand(&mut OsRng))
+            .into_affine();
+        let signature = secrets
+            .iter()
+            .map(|secret| message.mul(*secret))
+            .sum::<<Bls12_381 as PairingEngine>::G2Projective>();
+        (
+            ProjectiveCurve::batch_normalization_into_affine(&keys),
+            message,
+            signature.into_affine(),
+        )
+    }
+
+    #[test]
+    fn test_deferred_pairing_check() {
+        let (keys, message, signature) = signature(3);
+        let check = |signers: &[bool]| {
+            DeferredPairingCheck::<Bls12_381>::new(
+                &keys, signers, message, signature,
+            )
+            .verify()
+        };
+        assert!(check(&[true, true, true]).is_ok());
+        assert!(check(&[true, false, true]).is_err());
+        assert!(check(&[false, false, false]).is_err());
+    }
+
+    fn test_allocate<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let point = G1::prime_subgroup_generator()
+            .mul(<Bls12_381 as PairingEngine>::Fr::rand(&mut OsRng))
+            .into_affine();
+        let allocate = |point: G1| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let allocated = SWPointVariable::allocate(composer, &point);
+                    assert_eq!(allocated.value(composer), point);
+                },
+                1 << 13,
+            )
+        };
+        assert!(allocate(point).is_ok());
+        let off_curve =
+            G1::new(point.x, point.y + ark_bls12_381::Fq::one(), false);
+        assert!(allocate(off_curve).is_err());
+    }
+
+    fn test_bls_verify_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let (keys, message, signature) = signature(2);
+        let verify = |check: DeferredPairingCheck<Bls12_381>,
+                      signers: [bool; 2]| {
+            let public_inputs = RefCell::new(Vec::new());
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let signers =
+                        signers.map(|signer| composer.add_bool(signer));
+                    bls_verify_gadget(composer, &keys, &signers);
+                    *public_inputs.borrow_mut() = composer
+                        .public_input_values()
+                        .into_iter()
+                        .map(|(_, value)| value)
+                        .collect::<Vec<_>>();
+                },
+                1 << 16,
+            )?;
+
+            // The verifier builds the public inputs from the check
+            if public_inputs.into_inner()
+                != check.public_inputs::<E::Fr, g1::Parameters>()
+            {
+                return Err(Error::ProofVerificationError);
+            }
+            check.verify()
+        };
+        let check = |signers: [bool; 2]| {
+            DeferredPairingCheck::new(&keys, &signers, message, signature)
+        };
+        assert!(verify(check([true, true]), [true, true]).is_ok());
+        assert!(verify(check([false, true]), [false, true]).is_err());
+        assert!(verify(check([true, true]), [false, true]).is_err());
+        assert!(verify(check([false, false]), [false, false]).is_err());
+
+        // Keys that aren't registered sign the message, but the proof only
+        // sums the registered keys
+        let secrets = [
+            <Bls12_381 as PairingEngine>::Fr::rand(&mut OsRng),
+            <Bls12_381 as PairingEngine>::Fr::rand(&mut OsRng),
+        ];
+        let unregistered = secrets.map(|secret| {
+            G1::prime_subgroup_generator().mul(secret).into_affine()
+        });
+        let forged = message.mul(secrets[0] + secrets[1]).into_affine();
+        let check = DeferredPairingCheck::<Bls12_381>::new(
+            &unregistered,
+            &[true, true],
+            message,
+            forged,
+        );
+        assert!(check.verify().is_ok());
+        assert!(verify(check, [true, true]).is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_allocate, test_bls_verify_gadget],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_allocate, test_bls_verify_gadget],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_allocate, test_bls_verify_gadget],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
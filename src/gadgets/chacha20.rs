@@ -0,0 +1,355 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! ChaCha20 block function of RFC 8439.
+//!
+//! [`chacha20_block`] computes a block of keystream out of circuit, and
+//! [`chacha20_block_gadget`] constrains the same computation on
+//! [`WordVariable`]s of 32 bits, so that circuits can prove statements about
+//! ChaCha20-Poly1305 ciphertexts, e.g. that a record of a TLS session
+//! decrypts to some data served by a given website. The tag of such a
+//! record is constrained by [`poly1305_gadget`] from the first half of the
+//! block of counter zero.
+//!
+//! Additions modulo `2^32` witness their carry and range check the sum,
+//! XORs are [`StandardComposer::xor_words`], and rotations witness the two
+//! parts of the word they swap.
+//!
+//! [`poly1305_gadget`]: super::poly1305::poly1305_gadget
+
+use crate::constraint_system::{StandardComposer, Variable, WordVariable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use num_traits::{One, Zero};
+
+/// Constants of the first row of the state, `"expand 32-byte k"`.
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+/// Number of double rounds of ChaCha20.
+const DOUBLE_ROUNDS: usize = 10;
+
+/// Indices of the state updated by the quarter rounds of a double round,
+/// first on the columns and then on the diagonals.
+const QUARTER_ROUNDS: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Returns the initial state of the block of `key`, `counter` and `nonce`.
+fn initial_state<T>(
+    constants: [T; 4],
+    key: &[T; 8],
+    counter: T,
+    nonce: &[T; 3],
+) -> [T; 16]
+where
+    T: Copy,
+{
+    let mut state = [counter; 16];
+    state[..4].copy_from_slice(&constants);
+    state[4..12].copy_from_slice(key);
+    state[13..].copy_from_slice(nonce);
+    state
+}
+
+/// Returns the block of keystream of `key` at `counter` for `nonce`, as
+/// little-endian words.
+pub fn chacha20_block(
+    key: &[u32; 8],
+    counter: u32,
+    nonce: &[u32; 3],
+) -> [u32; 16] {
+    let initial = initial_state(CONSTANTS, key, counter, nonce);
+    let mut state = initial;
+    for _ in 0..DOUBLE_ROUNDS {
+        for [a, b, c, d] in QUARTER_ROUNDS {
+            state[a] = state[a].wrapping_add(state[b]);
+            state[d] = (state[d] ^ state[a]).rotate_left(16);
+            state[c] = state[c].wrapping_add(state[d]);
+            state[b] = (state[b] ^ state[c]).rotate_left(12);
+            state[a] = state[a].wrapping_add(state[b]);
+            state[d] = (state[d] ^ state[a]).rotate_left(8);
+            state[c] = state[c].wrapping_add(state[d]);
+            state[b] = (state[b] ^ state[c]).rotate_left(7);
+        }
+    }
+    state
+        .iter_mut()
+        .zip(initial)
+        .for_each(|(word, initial)| *word = word.wrapping_add(initial));
+    state
+}
+
+/// Constrains the block of keystream of `key` at `counter` for `nonce`,
+/// returning the words of the result of [`chacha20_block`].
+///
+/// # Panics
+///
+/// Panics if any of the words doesn't have 32 bits.
+pub fn chacha20_block_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    key: &[WordVariable; 8],
+    counter: WordVariable,
+    nonce: &[WordVariable; 3],
+) -> [WordVariable; 16]
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert!(
+        key.iter().chain(nonce).all(|word| word.bits == 32)
+            && counter.bits == 32,
+        "the words must have 32 bits"
+    );
+    let constants = CONSTANTS.map(|constant| WordVariable {
+        var: composer.add_constant(E::Fr::from(constant)),
+        bits: 32,
+    });
+    let initial = initial_state(constants, key, counter, nonce);
+    let mut state = initial;
+    for _ in 0..DOUBLE_ROUNDS {
+        for [a, b, c, d] in QUARTER_ROUNDS {
+            state[a] = add_words(composer, state[a], state[b]);
+            state[d] = composer.xor_words(state[d], state[a]);
+            state[d] = rotate_left(composer, state[d], 16);
+            state[c] = add_words(composer, state[c], state[d]);
+            state[b] = composer.xor_words(state[b], state[c]);
+            state[b] = rotate_left(composer, state[b], 12);
+            state[a] = add_words(composer, state[a], state[b]);
+            state[d] = composer.xor_words(state[d], state[a]);
+            state[d] = rotate_left(composer, state[d], 8);
+            state[c] = add_words(composer, state[c], state[d]);
+            state[b] = composer.xor_words(state[b], state[c]);
+            state[b] = rotate_left(composer, state[b], 7);
+        }
+    }
+    for (word, initial) in state.iter_mut().zip(initial) {
+        *word = add_words(composer, *word, initial);
+    }
+    state
+}
+
+/// Returns the value of the 32-bit `word`.
+fn word_value<E, P>(composer: &StandardComposer<E, P>, word: Variable) -> u32
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    composer.variables[word.0].into_repr().as_ref()[0] as u32
+}
+
+/// Constrains the sum of two 32-bit words modulo `2^32`, witnessing the
+/// carry of their sum as an integer.
+fn add_words<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: WordVariable,
+    b: WordVariable,
+) -> WordVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let (_, overflow) = word_value(composer, a.var)
+        .overflowing_add(word_value(composer, b.var));
+    let carry = composer.add_bool(overflow);
+    let sum = composer.big_add(
+        (E::Fr::one(), a.var),
+        (E::Fr::one(), b.var),
+        Some((-E::Fr::from(1u64 << 32), carry.variable())),
+        E::Fr::zero(),
+        None,
+    );
+    composer.range_gate(sum, 32)
+}
+
+/// Constrains the rotation of a 32-bit word left by `shift` bits.
+///
+/// The low `32 - shift` bits and the high `shift` bits of the word are
+/// witnessed and swapped. Range checking the low part to its exact width
+/// makes the split unique, so the high part only needs a range gate of an
+/// even number of bits.
+fn rotate_left<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    word: WordVariable,
+    shift: u32,
+) -> WordVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let low_bits = 32 - shift;
+    let value = word_value(composer, word.var);
+    let high = composer.add_input(E::Fr::from(value >> low_bits));
+    let low = composer.add_input(E::Fr::from(value & ((1 << low_bits) - 1)));
+    let recomposed = composer.add(
+        (E::Fr::from(1u64 << low_bits), high),
+        (E::Fr::one(), low),
+        E::Fr::zero(),
+        None,
+    );
+    composer.assert_equal(recomposed, word.var);
+
+    // Odd widths are checked as `low < 2^(bits + 1)` and
+    // `low + 2^bits < 2^(bits + 1)`
+    let low_range = low_bits as usize + low_bits as usize % 2;
+    composer.range_gate(low, low_range);
+    if low_bits % 2 == 1 {
+        let zero = composer.zero_var();
+        let shifted = composer.add(
+            (E::Fr::one(), low),
+            (E::Fr::zero(), zero),
+            E::Fr::from(1u64 << low_bits),
+            None,
+        );
+        composer.range_gate(shifted, low_range);
+    }
+    composer.range_gate(high, shift as usize + shift as usize % 2);
+
+    let rotated = composer.add(
+        (E::Fr::from(1u64 << shift), low),
+        (E::Fr::one(), high),
+        E::Fr::zero(),
+        None,
+    );
+    WordVariable {
+        var: rotated,
+        bits: 32,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use rand_core::{OsRng, RngCore};
+
+    /// Key, nonce and block of counter one of the example of section 2.3.2
+    /// of RFC 8439.
+    const KEY: [u32; 8] = [
+        0x03020100, 0x07060504, 0x0b0a0908, 0x0f0e0d0c, 0x13121110, 0x17161514,
+        0x1b1a1918, 0x1f1e1d1c,
+    ];
+    const NONCE: [u32; 3] = [0x09000000, 0x4a000000, 0x00000000];
+    const BLOCK: [u32; 16] = [
+        0xe4e7f110, 0x15593bd1, 0x1fdd0f50, 0xc47120a3, 0xc7f4d1c7, 0x0368c033,
+        0x9aaa2204, 0x4e6cd4c3, 0x466482d2, 0x09aa9f07, 0x05d7c214, 0xa2028bd9,
+        0xd19c12b5, 0xb94e16de, 0xe883d0cb, 0x4e3c50a2,
+    ];
+
+    #[test]
+    fn test_chacha20_block() {
+        assert_eq!(chacha20_block(&KEY, 1, &NONCE), BLOCK);
+    }
+
+    fn add_word<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: u32,
+    ) -> WordVariable
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let var = composer.add_input(E::Fr::from(value));
+        composer.range_gate(var, 32)
+    }
+
+    fn test_rotate_left<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let words = [0, u32::MAX, OsRng.next_u32()];
+        for word in words {
+            for shift in [7, 8, 12, 16] {
+                let rotate = |rotated: u32| {
+                    gadget_tester(
+                        |composer: &mut StandardComposer<E, P>| {
+                            let word = add_word(composer, word);
+                            let result = rotate_left(composer, word, shift);
+                            composer.constrain_to_constant(
+                                result.var,
+                                E::Fr::from(rotated),
+                                None,
+                            );
+                        },
+                        1 << 5,
+                    )
+                };
+                assert!(rotate(word.rotate_left(shift)).is_ok());
+                assert!(rotate(word.rotate_left(shift) ^ 1).is_err());
+            }
+        }
+    }
+
+    fn test_chacha20_block_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let block = |expected: [u32; 16]| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let key = KEY.map(|word| add_word(composer, word));
+                    let counter = add_word(composer, 1);
+                    let nonce = NONCE.map(|word| add_word(composer, word));
+                    let block =
+                        chacha20_block_gadget(composer, &key, counter, &nonce);
+                    for (word, value) in block.iter().zip(expected) {
+                        composer.constrain_to_constant(
+                            word.var,
+                            E::Fr::from(value),
+                            None,
+                        );
+                    }
+                },
+                1 << 15,
+            )
+        };
+        assert!(block(BLOCK).is_ok());
+        let mut wrong = BLOCK;
+        wrong[0] ^= 1;
+        assert!(block(wrong).is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_rotate_left, test_chacha20_block_gadget],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_rotate_left, test_chacha20_block_gadget],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_rotate_left, test_chacha20_block_gadget],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,533 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Signed integers of up to 64 bits.
+//!
+//! An [`IntVariable`] of `n` bits holds its value `x` as the field element
+//! `x`, negative values being `p - |x|`, constrained to the range
+//! `[-2^(n-1), 2^(n-1))` of an `n`-bit integer in two's complement by a
+//! range gate on `x + 2^(n-1)`. Sums and differences are range checked in
+//! the same way, so that the circuit is unsatisfiable if they overflow
+//! instead of silently wrapping around the field modulus.
+//!
+//! The two's complement encoding of `x` is `x + s 2^n` for its sign bit
+//! `s`, which is witnessed and made unique by range checking the encoding
+//! to `n` bits: [`IntVariable::to_twos_complement`] and
+//! [`IntVariable::from_twos_complement`] convert between signed integers
+//! and the [`WordVariable`]s of their encodings, and [`is_negative_gadget`]
+//! returns the sign bit.
+
+use crate::constraint_system::{
+    BoolVariable, StandardComposer, Variable, WordVariable,
+};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use num_traits::{One, Zero};
+
+/// Signed integer of [`IntVariable::bits`] bits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IntVariable {
+    /// Value of the integer in the field
+    var: Variable,
+
+    /// Number of bits of the integer in two's complement
+    bits: usize,
+}
+
+impl IntVariable {
+    /// Adds `value` to `composer` as an integer of `bits` bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` isn't an even number from 2 to 64, or if `value`
+    /// doesn't fit in `bits` bits.
+    pub fn allocate<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: i64,
+        bits: usize,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        assert_fits(value, bits);
+        let var = composer.add_input(to_field(value));
+        constrain_range(composer, var, bits)
+    }
+
+    /// Returns the integer of `bits` bits fixed to `value` as a part of the
+    /// circuit description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` isn't an even number from 2 to 64, or if `value`
+    /// doesn't fit in `bits` bits.
+    pub fn constant<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: i64,
+        bits: usize,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        assert_fits(value, bits);
+        Self {
+            var: composer.add_constant(to_field(value)),
+            bits,
+        }
+    }
+
+    /// Constrains the integer whose two's complement encoding is `word`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `word` doesn't have an even number of bits from 2 to 64.
+    pub fn from_twos_complement<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        word: WordVariable,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let bits = word.bits;
+        assert_bits(bits);
+        let encoding = composer.variables[word.var.0].into_repr().as_ref()[0];
+        let sign = composer.add_bool(encoding >> (bits - 1) & 1 == 1);
+        let var = composer.add(
+            (E::Fr::one(), word.var),
+            (-power_of_two::<E::Fr>(bits), sign.variable()),
+            E::Fr::zero(),
+            None,
+        );
+        constrain_range(composer, var, bits)
+    }
+
+    /// Constrains the two's complement encoding of the integer, returning
+    /// it as a word of [`IntVariable::bits`] bits.
+    pub fn to_twos_complement<E, P>(
+        self,
+        composer: &mut StandardComposer<E, P>,
+    ) -> WordVariable
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        sign_and_encoding(composer, self).1
+    }
+
+    /// Returns the underlying [`Variable`].
+    pub fn variable(self) -> Variable {
+        self.var
+    }
+
+    /// Returns the number of bits of the integer.
+    pub fn bits(self) -> usize {
+        self.bits
+    }
+
+    /// Returns the value of the integer in the witness of `composer`.
+    pub fn value<E, P>(self, composer: &StandardComposer<E, P>) -> i64
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let value = composer.variables[self.var.0];
+        if value.into_repr() > E::Fr::from(i64::MAX as u64).into_repr() {
+            // i64::MIN has no positive counterpart, wrapping maps it to itself
+            ((-value).into_repr().as_ref()[0] as i64).wrapping_neg()
+        } else {
+            value.into_repr().as_ref()[0] as i64
+        }
+    }
+}
+
+/// Asserts that integers can have `bits` bits.
+fn assert_bits(bits: usize) {
+    assert!(
+        bits.is_multiple_of(2) && (2..=64).contains(&bits),
+        "integers must have an even number of bits from 2 to 64"
+    );
+}
+
+/// Asserts that `value` fits in an integer of `bits` bits.
+fn assert_fits(value: i64, bits: usize) {
+    assert_bits(bits);
+    assert!(
+        bits == 64 || (-(1 << (bits - 1))..1 << (bits - 1)).contains(&value),
+        "the value doesn't fit in the bits"
+    );
+}
+
+/// Returns the field element of `value`.
+fn to_field<F>(value: i64) -> F
+where
+    F: PrimeField,
+{
+    if value < 0 {
+        -F::from(value.unsigned_abs())
+    } else {
+        F::from(value as u64)
+    }
+}
+
+/// Returns `2^exponent` in the field.
+fn power_of_two<F>(exponent: usize) -> F
+where
+    F: PrimeField,
+{
+    F::from(2u64).pow([exponent as u64])
+}
+
+/// Constrains `var` to fit in an integer of `bits` bits, checking that
+/// `var + 2^(bits - 1)` fits in `bits` bits.
+fn constrain_range<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    var: Variable,
+    bits: usize,
+) -> IntVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let zero = composer.zero_var();
+    let shifted = composer.add(
+        (E::Fr::one(), var),
+        (E::Fr::zero(), zero),
+        power_of_two(bits - 1),
+        None,
+    );
+    composer.range_gate(shifted, bits);
+    IntVariable { var, bits }
+}
+
+/// Constrains the sign bit `s` of `a` and its two's complement encoding
+/// `a + s 2^n`, which only fits in `n` bits for the right sign.
+fn sign_and_encoding<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: IntVariable,
+) -> (BoolVariable, WordVariable)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let negative = a.value(composer) < 0;
+    let sign = composer.add_bool(negative);
+    let encoding = composer.add(
+        (E::Fr::one(), a.var),
+        (power_of_two(a.bits), sign.variable()),
+        E::Fr::zero(),
+        None,
+    );
+    (sign, composer.range_gate(encoding, a.bits))
+}
+
+/// Constrains the sum of `a` and `b`, which must not overflow for the
+/// circuit to be satisfiable.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` don't have the same number of bits.
+pub fn add_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: IntVariable,
+    b: IntVariable,
+) -> IntVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert_eq!(a.bits, b.bits, "the integers must have the same bits");
+    let one = E::Fr::one();
+    let sum = composer.add((one, a.var), (one, b.var), E::Fr::zero(), None);
+    constrain_range(composer, sum, a.bits)
+}
+
+/// Constrains the difference of `a` and `b`, which must not overflow for
+/// the circuit to be satisfiable.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` don't have the same number of bits.
+pub fn sub_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: IntVariable,
+    b: IntVariable,
+) -> IntVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert_eq!(a.bits, b.bits, "the integers must have the same bits");
+    let one = E::Fr::one();
+    let difference =
+        composer.add((one, a.var), (-one, b.var), E::Fr::zero(), None);
+    constrain_range(composer, difference, a.bits)
+}
+
+/// Returns a bit set if `a` is strictly less than `b`.
+///
+/// The bit `c` is witnessed and `a - b + c 2^n` is range checked to `n`
+/// bits, which only holds for `c = 1` if `a < b` and `c = 0` otherwise.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` don't have the same number of bits.
+pub fn less_than_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: IntVariable,
+    b: IntVariable,
+) -> BoolVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert_eq!(a.bits, b.bits, "the integers must have the same bits");
+    let one = E::Fr::one();
+    let less = composer.add_bool(a.value(composer) < b.value(composer));
+    let difference = composer.big_add(
+        (one, a.var),
+        (-one, b.var),
+        Some((power_of_two(a.bits), less.variable())),
+        E::Fr::zero(),
+        None,
+    );
+    composer.range_gate(difference, a.bits);
+    less
+}
+
+/// Returns a bit set if `a` is negative.
+pub fn is_negative_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: IntVariable,
+) -> BoolVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    sign_and_encoding(composer, a).0
+}
+
+/// Constrains the absolute value of `a`, `a (1 - 2 s)` for its sign bit
+/// `s`, which overflows for the minimum integer, `-2^(n-1)`, making the
+/// circuit unsatisfiable.
+pub fn abs_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: IntVariable,
+) -> IntVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let sign = is_negative_gadget(composer, a);
+    let abs = composer.big_mul(
+        -E::Fr::from(2u64),
+        sign.variable(),
+        a.var,
+        Some((E::Fr::one(), a.var)),
+        E::Fr::zero(),
+        None,
+    );
+    constrain_range(composer, abs, a.bits)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    fn test_allocate<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        for (value, bits) in [(i64::MIN, 64), (i64::MAX, 64), (-128, 8)] {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = IntVariable::allocate(composer, value, bits);
+                    assert_eq!(a.value(composer), value);
+                },
+                1 << 6,
+            );
+            assert!(res.is_ok());
+        }
+
+        // A value out of range can't be witnessed for an integer of 8 bits
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let a = IntVariable::allocate(composer, 127, 8);
+                let one = IntVariable::allocate(composer, 1, 8);
+                let var = composer.add(
+                    (E::Fr::one(), a.var),
+                    (E::Fr::one(), one.var),
+                    E::Fr::zero(),
+                    None,
+                );
+                constrain_range(composer, var, 8);
+            },
+            1 << 6,
+        );
+        assert!(res.is_err());
+    }
+
+    fn test_add_sub_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let add_sub = |a: i64, b: i64, bits: usize| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = IntVariable::allocate(composer, a, bits);
+                    let b = IntVariable::allocate(composer, b, bits);
+                    let sum = add_gadget(composer, a, b);
+                    let difference = sub_gadget(composer, sum, b);
+                    composer.assert_equal(difference.var, a.var);
+                },
+                1 << 7,
+            )
+        };
+        assert!(add_sub(-5, 3, 64).is_ok());
+        assert!(add_sub(i64::MIN, i64::MAX, 64).is_ok());
+        assert!(add_sub(-100, -28, 8).is_ok());
+        assert!(add_sub(i64::MAX, 1, 64).is_err());
+        assert!(add_sub(i64::MIN, -1, 64).is_err());
+        assert!(add_sub(100, 28, 8).is_err());
+    }
+
+    fn test_less_than_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let less_than = |a: i64, b: i64, less: bool| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = IntVariable::allocate(composer, a, 64);
+                    let b = IntVariable::allocate(composer, b, 64);
+                    let result = less_than_gadget(composer, a, b);
+                    composer.constrain_to_constant(
+                        result.variable(),
+                        E::Fr::from(less as u64),
+                        None,
+                    );
+                },
+                1 << 6,
+            )
+        };
+        for (a, b) in [(-1, 0), (i64::MIN, i64::MAX), (-7, -7), (3, -3)] {
+            assert!(less_than(a, b, a < b).is_ok());
+            assert!(less_than(a, b, a >= b).is_err());
+        }
+    }
+
+    fn test_abs_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let abs = |a: i64, expected: i64| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = IntVariable::allocate(composer, a, 64);
+                    let result = abs_gadget(composer, a);
+                    let expected =
+                        IntVariable::constant(composer, expected, 64);
+                    composer.assert_equal(result.var, expected.var);
+                },
+                1 << 6,
+            )
+        };
+        assert!(abs(-42, 42).is_ok());
+        assert!(abs(42, 42).is_ok());
+        assert!(abs(0, 0).is_ok());
+        assert!(abs(-42, -42).is_err());
+        assert!(abs(i64::MIN + 1, i64::MAX).is_ok());
+        assert!(abs(i64::MIN, i64::MIN).is_err());
+    }
+
+    fn test_twos_complement<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        for value in [-1, 0, i64::MIN, i64::MAX, -0x1234] {
+            let res = gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = IntVariable::allocate(composer, value, 64);
+                    let word = a.to_twos_complement(composer);
+                    composer.constrain_to_constant(
+                        word.var,
+                        E::Fr::from(value as u64),
+                        None,
+                    );
+                    let b = IntVariable::from_twos_complement(composer, word);
+                    composer.assert_equal(a.var, b.var);
+                    let negative = is_negative_gadget(composer, b);
+                    composer.constrain_to_constant(
+                        negative.variable(),
+                        E::Fr::from((value < 0) as u64),
+                        None,
+                    );
+                },
+                1 << 7,
+            );
+            assert!(res.is_ok());
+        }
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [
+            test_allocate,
+            test_add_sub_gadget,
+            test_less_than_gadget,
+            test_abs_gadget,
+            test_twos_complement
+        ],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [
+            test_allocate,
+            test_add_sub_gadget,
+            test_less_than_gadget,
+            test_abs_gadget,
+            test_twos_complement
+        ],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [
+            test_allocate,
+            test_add_sub_gadget,
+            test_less_than_gadget,
+            test_abs_gadget,
+            test_twos_complement
+        ],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,825 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Merkle tree membership and updates.
+//!
+//! A [`MerklePath`] authenticates the leaf at some index of a tree of arity
+//! two, four or eight, whose nodes are the [`CircuitHash`] of their
+//! children. Trees of higher arity are shallower, and hashed with
+//! [`WidePoseidonParameters`] of width `arity + 1` they need a single
+//! permutation per node, so that paths of the same capacity hash two to
+//! three times fewer nodes than binary ones.
+//!
+//! Checking a state transition with two independent membership proofs, one
+//! against the old root and one against the new root, lets the prover
+//! change any other leaf or the index in between. [`merkle_update_gadget`]
+//! instead walks a single path, with the same siblings and the same
+//! positions, up to both roots, so that the two trees only differ at the
+//! updated leaf.
+//!
+//! An [`IncrementalMerkleTree`] maintains a tree out of circuit as leaves are
+//! appended, and hands out the [`MerklePath`] of any of them. It hashes its
+//! nodes with the same [`CircuitHash`] as the gadgets, so that the roots it
+//! computes are the ones constrained in circuit.
+//!
+//! [`WidePoseidonParameters`]: crate::constraint_system::WidePoseidonParameters
+
+use crate::constraint_system::{BoolVariable, StandardComposer, Variable};
+use crate::error::Error;
+use crate::gadgets::CircuitHash;
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::Field;
+use core::marker::PhantomData;
+use num_traits::{One, Zero};
+
+/// Authentication path of the leaf at `index` of a Merkle tree.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct MerklePath<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Number of children of every node
+    arity: usize,
+
+    /// Index of the leaf, whose digits in base `arity` from the least
+    /// significant one give the position of the node at each level
+    index: u64,
+
+    /// Siblings of the nodes from the leaf up to the children of the root,
+    /// in the order of their positions
+    siblings: Vec<Vec<E::Fr>>,
+
+    __: PhantomData<P>,
+}
+
+/// Variables allocated for a [`MerklePath`].
+#[derive(Clone, Debug)]
+pub struct MerklePathVariables {
+    /// Index of the leaf
+    pub index: Variable,
+
+    /// Bits of the index from the least significant one, giving the
+    /// position of the node at each level
+    pub directions: Vec<BoolVariable>,
+
+    /// Siblings of the nodes from the leaf up to the children of the root
+    pub siblings: Vec<Vec<Variable>>,
+}
+
+impl<E, P> MerklePath<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Creates the path of the leaf at `index` of a binary tree with the
+    /// given `siblings`, ordered from the leaf up to the children of the
+    /// root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` does not fit in a tree with one level per sibling.
+    pub fn new(index: u64, siblings: Vec<E::Fr>) -> Self {
+        Self::with_arity(
+            2,
+            index,
+            siblings.into_iter().map(|sibling| vec![sibling]).collect(),
+        )
+    }
+
+    /// Creates the path of the leaf at `index` of a tree of `arity` with
+    /// the given `siblings`, the `arity - 1` other children of every node
+    /// from the leaf up to the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `arity` is not 2, 4 or 8, if a level doesn't hold
+    /// `arity - 1` siblings, or if `index` does not fit in the tree.
+    pub fn with_arity(
+        arity: usize,
+        index: u64,
+        siblings: Vec<Vec<E::Fr>>,
+    ) -> Self {
+        assert!([2, 4, 8].contains(&arity), "the arity must be 2, 4 or 8");
+        assert!(
+            siblings.iter().all(|level| level.len() == arity - 1),
+            "every level must hold the other children of the node"
+        );
+        let bits = siblings.len() * arity.trailing_zeros() as usize;
+        assert!(
+            bits >= 64 || index >> bits == 0,
+            "the index exceeds the number of leaves"
+        );
+        Self {
+            arity,
+            index,
+            siblings,
+            __: PhantomData,
+        }
+    }
+
+    /// Returns the number of children of every node.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the index of the leaf.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Returns the siblings of the path.
+    pub fn siblings(&self) -> &[Vec<E::Fr>] {
+        &self.siblings
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Returns the position of the node among its siblings at `level`.
+    fn position(&self, level: usize) -> usize {
+        let shift = level * self.arity.trailing_zeros() as usize;
+        (self.index >> shift) as usize & (self.arity - 1)
+    }
+
+    /// Returns the root of the tree holding `leaf` at the index of the path.
+    pub fn root<H>(&self, hash: &H, leaf: E::Fr) -> E::Fr
+    where
+        H: CircuitHash<E, P>,
+    {
+        self.siblings.iter().enumerate().fold(
+            leaf,
+            |node, (level, siblings)| {
+                let mut children = siblings.clone();
+                children.insert(self.position(level), node);
+                hash.hash(&children)
+            },
+        )
+    }
+
+    /// Adds the index and the siblings of the path to `composer`,
+    /// constraining the index to its direction bits.
+    pub fn allocate(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+    ) -> MerklePathVariables {
+        let bits = self.depth() * self.arity.trailing_zeros() as usize;
+        let directions: Vec<_> = (0..bits)
+            .map(|bit| composer.add_bool((self.index >> bit) & 1 == 1))
+            .collect();
+        let mut index = composer.zero_var();
+        let mut power = E::Fr::one();
+        for direction in &directions {
+            index = composer.add(
+                (E::Fr::one(), index),
+                (power, direction.variable()),
+                E::Fr::zero(),
+                None,
+            );
+            power.double_in_place();
+        }
+        MerklePathVariables {
+            index,
+            directions,
+            siblings: self
+                .siblings
+                .iter()
+                .map(|level| {
+                    level
+                        .iter()
+                        .map(|sibling| composer.add_input(*sibling))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Merkle tree of fixed depth whose leaves are appended from left to right.
+///
+/// The leaves not appended yet are zero. Every level keeps the nodes
+/// computed so far, the last of which is on the frontier of the tree, the
+/// path from the last appended leaf up to the root. Appending a leaf only
+/// rehashes the nodes of the frontier, one per level, and the path of a leaf
+/// is read from the stored nodes, completed by the roots of empty subtrees.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = "H: Clone"), Debug(bound = "H: core::fmt::Debug"))]
+pub struct IncrementalMerkleTree<E, P, H>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    /// Hash of the children of every node
+    hash: H,
+
+    /// Number of children of every node
+    arity: usize,
+
+    /// Roots of the empty subtrees of every level, from the leaves up to the
+    /// root
+    empty: Vec<E::Fr>,
+
+    /// Nodes computed so far at every level, from the leaves up to the root
+    levels: Vec<Vec<E::Fr>>,
+
+    __: PhantomData<P>,
+}
+
+impl<E, P, H> IncrementalMerkleTree<E, P, H>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    /// Creates an empty binary tree of `depth` levels, whose nodes are the
+    /// `hash` of their children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree holds `2^64` leaves or more.
+    pub fn new(hash: H, depth: usize) -> Self {
+        Self::with_arity(hash, 2, depth)
+    }
+
+    /// Creates an empty tree of `arity` and `depth` levels, whose nodes are
+    /// the `hash` of their children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `arity` is not 2, 4 or 8, or if the tree holds `2^64`
+    /// leaves or more.
+    pub fn with_arity(hash: H, arity: usize, depth: usize) -> Self {
+        assert!([2, 4, 8].contains(&arity), "the arity must be 2, 4 or 8");
+        assert!(
+            depth * (arity.trailing_zeros() as usize) < 64,
+            "the tree must hold less than 2^64 leaves"
+        );
+        let mut empty = vec![E::Fr::zero()];
+        for level in 0..depth {
+            empty.push(hash.hash(&vec![empty[level]; arity]));
+        }
+        Self {
+            hash,
+            arity,
+            empty,
+            levels: vec![Vec::new(); depth + 1],
+            __: PhantomData,
+        }
+    }
+
+    /// Returns the hash of the nodes of the tree.
+    pub fn hash(&self) -> &H {
+        &self.hash
+    }
+
+    /// Returns the number of children of every node.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns the number of leaves the tree holds.
+    pub fn capacity(&self) -> u64 {
+        1 << (self.depth() * self.arity.trailing_zeros() as usize)
+    }
+
+    /// Returns the number of appended leaves.
+    pub fn len(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    /// Returns `true` if no leaf was appended.
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> E::Fr {
+        self.node(self.depth(), 0)
+    }
+
+    /// Returns the leaf at `index`, or `None` if it wasn't appended.
+    pub fn leaf(&self, index: u64) -> Option<E::Fr> {
+        self.levels[0].get(usize::try_from(index).ok()?).copied()
+    }
+
+    /// Returns the node at `position` of `level`.
+    fn node(&self, level: usize, position: usize) -> E::Fr {
+        self.levels[level]
+            .get(position)
+            .copied()
+            .unwrap_or(self.empty[level])
+    }
+
+    /// Returns the children of the parent of the node at `position` of
+    /// `level`.
+    fn children(&self, level: usize, position: usize) -> Vec<E::Fr> {
+        let first = position - position % self.arity;
+        (first..first + self.arity)
+            .map(|child| self.node(level, child))
+            .collect()
+    }
+
+    /// Appends `leaf` to the tree, updating the frontier up to the root, and
+    /// returns its index.
+    ///
+    /// Fails with [`Error::MerkleTreeFull`] if the tree already holds as
+    /// many leaves as it can.
+    pub fn append(&mut self, leaf: E::Fr) -> Result<u64, Error> {
+        let index = self.len();
+        if index == self.capacity() {
+            return Err(Error::MerkleTreeFull {
+                capacity: self.capacity(),
+            });
+        }
+        self.levels[0].push(leaf);
+        let mut position = index as usize;
+        for level in 0..self.depth() {
+            let parent = self.hash.hash(&self.children(level, position));
+            position /= self.arity;
+            match self.levels[level + 1].get_mut(position) {
+                Some(node) => *node = parent,
+                None => self.levels[level + 1].push(parent),
+            }
+        }
+        Ok(index)
+    }
+
+    /// Returns the path of the leaf at `index`, or `None` if it wasn't
+    /// appended.
+    ///
+    /// The path authenticates the leaf against the current
+    /// [`root`](Self::root). Its siblings change as later leaves are appended
+    /// to the subtrees next to the leaf, so paths should be taken from the
+    /// tree whose root a circuit is checked against.
+    pub fn path(&self, index: u64) -> Option<MerklePath<E, P>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut position = index as usize;
+        let siblings = (0..self.depth())
+            .map(|level| {
+                let mut siblings = self.children(level, position);
+                siblings.remove(position % self.arity);
+                position /= self.arity;
+                siblings
+            })
+            .collect();
+        Some(MerklePath::with_arity(self.arity, index, siblings))
+    }
+}
+
+/// Constrains the parent of `node`, at the position given by the `bits`
+/// among `siblings`.
+fn merkle_parent<E, P, H>(
+    composer: &mut StandardComposer<E, P>,
+    hash: &H,
+    bits: &[BoolVariable],
+    node: Variable,
+    siblings: &[Variable],
+) -> Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    let one = E::Fr::one();
+    let zero = E::Fr::zero();
+    // Flags set at the position of the node, built one bit at a time
+    let mut flags = vec![composer.add_constant(one)];
+    for bit in bits {
+        let set: Vec<_> = flags
+            .iter()
+            .map(|flag| composer.mul(one, *flag, bit.variable(), zero, None))
+            .collect();
+        let unset: Vec<_> = flags
+            .iter()
+            .zip(&set)
+            .map(|(flag, set)| {
+                composer.add((one, *flag), (-one, *set), zero, None)
+            })
+            .collect();
+        flags = unset.into_iter().chain(set).collect();
+    }
+
+    // The children before the node are the siblings at the same position,
+    // and those after it the siblings at the previous one:
+    // child = sibling + flag * (node - sibling) + after * (previous - sibling)
+    let mut after = composer.zero_var();
+    let mut children = Vec::with_capacity(flags.len());
+    for (position, flag) in flags.iter().enumerate() {
+        let sibling = siblings.get(position).copied();
+        let previous = position.checked_sub(1).map(|p| siblings[p]);
+        let child = match (sibling, previous) {
+            (Some(sibling), None) => {
+                let difference =
+                    composer.add((one, node), (-one, sibling), zero, None);
+                composer.big_mul(
+                    one,
+                    *flag,
+                    difference,
+                    Some((one, sibling)),
+                    zero,
+                    None,
+                )
+            }
+            (Some(sibling), Some(previous)) => {
+                let difference =
+                    composer.add((one, node), (-one, sibling), zero, None);
+                let partial = composer.big_mul(
+                    one,
+                    *flag,
+                    difference,
+                    Some((one, sibling)),
+                    zero,
+                    None,
+                );
+                let shift =
+                    composer.add((one, previous), (-one, sibling), zero, None);
+                composer.big_mul(
+                    one,
+                    after,
+                    shift,
+                    Some((one, partial)),
+                    zero,
+                    None,
+                )
+            }
+            // The last child is either the node or the last sibling
+            (None, Some(previous)) => {
+                let selected = composer.mul(one, *flag, node, zero, None);
+                composer.big_mul(
+                    one,
+                    after,
+                    previous,
+                    Some((one, selected)),
+                    zero,
+                    None,
+                )
+            }
+            (None, None) => unreachable!("nodes have at least two children"),
+        };
+        children.push(child);
+        if sibling.is_some() {
+            after = composer.add((one, after), (one, *flag), zero, None);
+        }
+    }
+    hash.hash_gadget(composer, &children)
+}
+
+/// Constrains the root of the tree holding `leaf` at the index of `path`,
+/// returning the [`Variable`] holding the result of [`MerklePath::root`].
+pub fn merkle_root_gadget<E, P, H>(
+    composer: &mut StandardComposer<E, P>,
+    hash: &H,
+    leaf: Variable,
+    path: &MerklePathVariables,
+) -> Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    let bits = path
+        .siblings
+        .first()
+        .map_or(1, |level| (level.len() + 1).trailing_zeros() as usize);
+    path.directions.chunks(bits).zip(&path.siblings).fold(
+        leaf,
+        |node, (bits, siblings)| {
+            merkle_parent(composer, hash, bits, node, siblings)
+        },
+    )
+}
+
+/// Constrains the roots of the trees holding `old_leaf` and `new_leaf` at
+/// the index of `path`, returning the old and the new root.
+///
+/// Both roots are computed from the same siblings and positions, so
+/// constraining them to two public roots proves that replacing the leaf at
+/// the index of the path turns the old tree into the new one.
+pub fn merkle_update_gadget<E, P, H>(
+    composer: &mut StandardComposer<E, P>,
+    hash: &H,
+    old_leaf: Variable,
+    new_leaf: Variable,
+    path: &MerklePathVariables,
+) -> (Variable, Variable)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    (
+        merkle_root_gadget(composer, hash, old_leaf, path),
+        merkle_root_gadget(composer, hash, new_leaf, path),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constraint_system::{
+        PoseidonParameters, RescueParameters, WidePoseidonParameters,
+    };
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use rand_core::OsRng;
+
+    /// Returns the levels of the tree of `arity` over `leaves`, from the
+    /// leaves up to the root.
+    fn tree<E, P, H>(
+        hash: &H,
+        arity: usize,
+        leaves: Vec<E::Fr>,
+    ) -> Vec<Vec<E::Fr>>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        H: CircuitHash<E, P>,
+    {
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let level = levels[levels.len() - 1]
+                .chunks(arity)
+                .map(|children| hash.hash(children))
+                .collect();
+            levels.push(level);
+        }
+        levels
+    }
+
+    fn path<E, P>(
+        levels: &[Vec<E::Fr>],
+        arity: usize,
+        index: usize,
+    ) -> MerklePath<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let siblings = levels[..levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| {
+                let node = index / arity.pow(level as u32);
+                let first = node - node % arity;
+                (first..first + arity)
+                    .filter(|sibling| *sibling != node)
+                    .map(|sibling| nodes[sibling])
+                    .collect()
+            })
+            .collect();
+        MerklePath::with_arity(arity, index as u64, siblings)
+    }
+
+    fn check_update<E, P, H>(
+        hash: H,
+        arity: usize,
+        depth: u32,
+        index: usize,
+        capacity: usize,
+    ) where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        H: CircuitHash<E, P>,
+    {
+        let mut leaves: Vec<_> = (0..arity.pow(depth))
+            .map(|_| E::Fr::rand(&mut OsRng))
+            .collect();
+        let old = tree::<E, P, H>(&hash, arity, leaves.clone());
+        let old_leaf = leaves[index];
+        let new_leaf = E::Fr::rand(&mut OsRng);
+        leaves[index] = new_leaf;
+        let new = tree::<E, P, H>(&hash, arity, leaves);
+        let [old_root, new_root] =
+            [old[depth as usize][0], new[depth as usize][0]];
+
+        // The path of the leaf is shared by both trees
+        let shared = path::<E, P>(&old, arity, index);
+        assert_eq!(shared, path::<E, P>(&new, arity, index));
+        assert_eq!(shared.root(&hash, old_leaf), old_root);
+        assert_eq!(shared.root(&hash, new_leaf), new_root);
+
+        let update = |path: &MerklePath<E, P>| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let variables = path.allocate(composer);
+                    composer.constrain_to_constant(
+                        variables.index,
+                        E::Fr::from(index as u64),
+                        None,
+                    );
+                    let old_leaf = composer.add_input(old_leaf);
+                    let new_leaf = composer.add_input(new_leaf);
+                    let (old, new) = merkle_update_gadget(
+                        composer, &hash, old_leaf, new_leaf, &variables,
+                    );
+                    composer.constrain_to_constant(
+                        old,
+                        E::Fr::zero(),
+                        Some(-old_root),
+                    );
+                    composer.constrain_to_constant(
+                        new,
+                        E::Fr::zero(),
+                        Some(-new_root),
+                    );
+                },
+                capacity,
+            )
+        };
+        assert!(update(&shared).is_ok());
+
+        // The siblings of another leaf do not authenticate the update
+        assert!(update(&path::<E, P>(&old, arity, index ^ 1)).is_err());
+        let mut siblings = shared.siblings().to_vec();
+        siblings[depth as usize - 1][arity - 2] = E::Fr::rand(&mut OsRng);
+        let forged = MerklePath::with_arity(arity, index as u64, siblings);
+        assert!(update(&forged).is_err());
+    }
+
+    fn check_incremental<E, P, H>(hash: H, arity: usize, depth: u32)
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        H: CircuitHash<E, P> + Clone,
+    {
+        let capacity = arity.pow(depth);
+        let leaves: Vec<_> =
+            (0..capacity).map(|_| E::Fr::rand(&mut OsRng)).collect();
+        let mut incremental =
+            IncrementalMerkleTree::with_arity(hash.clone(), arity, depth as _);
+        assert!(incremental.is_empty());
+        let mut padded = vec![E::Fr::zero(); capacity];
+        assert_eq!(
+            incremental.root(),
+            tree::<E, P, H>(&hash, arity, padded.clone())[depth as usize][0]
+        );
+
+        // Every append matches the tree over the leaves padded with zeros
+        for (index, leaf) in leaves.iter().enumerate() {
+            assert_eq!(incremental.append(*leaf).unwrap(), index as u64);
+            padded[index] = *leaf;
+            let levels = tree::<E, P, H>(&hash, arity, padded.clone());
+            assert_eq!(incremental.root(), levels[depth as usize][0]);
+            for appended in [0, index / 2, index] {
+                let expected = path::<E, P>(&levels, arity, appended);
+                assert_eq!(
+                    incremental.path(appended as u64).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    expected.root(&hash, leaves[appended]),
+                    incremental.root()
+                );
+            }
+        }
+        assert_eq!(incremental.len(), capacity as u64);
+        assert_eq!(incremental.leaf(1), Some(leaves[1]));
+        assert!(incremental.path(capacity as u64).is_none());
+        assert!(matches!(
+            incremental.append(E::Fr::one()),
+            Err(Error::MerkleTreeFull { capacity: full }) if full == capacity as u64
+        ));
+
+        // Paths of the tree verify in circuit against its root
+        let path = incremental.path(capacity as u64 - 2).unwrap();
+        let root = incremental.root();
+        let res = gadget_tester(
+            |composer: &mut StandardComposer<E, P>| {
+                let variables = path.allocate(composer);
+                let leaf = composer.add_input(leaves[capacity - 2]);
+                let computed =
+                    merkle_root_gadget(composer, &hash, leaf, &variables);
+                composer.constrain_to_constant(
+                    computed,
+                    E::Fr::zero(),
+                    Some(-root),
+                );
+            },
+            1 << 12,
+        );
+        assert!(res.is_ok());
+    }
+
+    fn test_incremental_poseidon<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        check_incremental::<E, P, _>(PoseidonParameters::new(), 2, 3);
+    }
+
+    fn test_incremental_arity_4<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        check_incremental::<E, P, _>(WidePoseidonParameters::new(5), 4, 2);
+    }
+
+    fn test_update_poseidon<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        check_update::<E, P, _>(PoseidonParameters::new(), 2, 3, 5, 1 << 12);
+    }
+
+    fn test_update_rescue<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        check_update::<E, P, _>(RescueParameters::new(), 2, 3, 5, 1 << 12);
+    }
+
+    fn test_update_arity_4<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let hash = WidePoseidonParameters::new(5);
+        check_update::<E, P, _>(hash, 4, 2, 9, 1 << 13);
+    }
+
+    fn test_update_arity_8<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let hash = WidePoseidonParameters::new(9);
+        check_update::<E, P, _>(hash, 8, 1, 5, 1 << 13);
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [
+            test_update_poseidon,
+            test_update_rescue,
+            test_update_arity_4,
+            test_update_arity_8,
+            test_incremental_poseidon,
+            test_incremental_arity_4
+        ],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [
+            test_update_poseidon,
+            test_update_rescue,
+            test_update_arity_4,
+            test_update_arity_8,
+            test_incremental_poseidon,
+            test_incremental_arity_4
+        ],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [
+            test_update_poseidon,
+            test_update_rescue,
+            test_update_arity_4,
+            test_update_arity_8,
+            test_incremental_poseidon,
+            test_incremental_arity_4
+        ],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
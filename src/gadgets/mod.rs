@@ -0,0 +1,136 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Higher-level gadgets built on the gates of the [`StandardComposer`].
+//!
+//! The gadgets are generic over the algebraic hash they use, through the
+//! [`CircuitHash`] trait implemented by the parameters of all the hashes of
+//! the crate.
+
+pub mod aes;
+pub mod bigint;
+pub mod bls;
+pub mod chacha20;
+pub mod int;
+pub mod merkle;
+pub mod poly1305;
+pub mod rsa;
+pub mod shielded;
+pub mod uint256;
+
+use crate::constraint_system::{
+    GmimcParameters, MimcParameters, PoseidonParameters, RescueParameters,
+    StandardComposer, Variable, WidePoseidonParameters,
+};
+use ark_ec::{PairingEngine, TEModelParameters};
+
+/// Hash of field elements computed both out of circuit and in circuit.
+pub trait CircuitHash<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Hashes `inputs` into a single field element.
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr;
+
+    /// Constrains the hash of `inputs`, returning the [`Variable`] holding
+    /// the result of [`CircuitHash::hash`].
+    fn hash_gadget(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+        inputs: &[Variable],
+    ) -> Variable;
+}
+
+impl<E, P> CircuitHash<E, P> for PoseidonParameters<E::Fr>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        PoseidonParameters::hash(self, inputs)
+    }
+
+    fn hash_gadget(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+        inputs: &[Variable],
+    ) -> Variable {
+        composer.poseidon_hash(self, inputs)
+    }
+}
+
+impl<E, P> CircuitHash<E, P> for RescueParameters<E::Fr>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        RescueParameters::hash(self, inputs)
+    }
+
+    fn hash_gadget(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+        inputs: &[Variable],
+    ) -> Variable {
+        composer.rescue_hash(self, inputs)
+    }
+}
+
+impl<E, P> CircuitHash<E, P> for MimcParameters<E::Fr>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        MimcParameters::hash(self, inputs)
+    }
+
+    fn hash_gadget(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+        inputs: &[Variable],
+    ) -> Variable {
+        composer.mimc_hash(self, inputs)
+    }
+}
+
+impl<E, P> CircuitHash<E, P> for GmimcParameters<E::Fr>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        GmimcParameters::hash(self, inputs)
+    }
+
+    fn hash_gadget(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+        inputs: &[Variable],
+    ) -> Variable {
+        composer.gmimc_hash(self, inputs)
+    }
+}
+
+impl<E, P> CircuitHash<E, P> for WidePoseidonParameters<E::Fr>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn hash(&self, inputs: &[E::Fr]) -> E::Fr {
+        WidePoseidonParameters::hash(self, inputs)
+    }
+
+    fn hash_gadget(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+        inputs: &[Variable],
+    ) -> Variable {
+        composer.wide_poseidon_hash(self, inputs)
+    }
+}
@@ -0,0 +1,383 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Poly1305 message authentication code of RFC 8439.
+//!
+//! [`poly1305`] computes the tag of a message out of circuit, and
+//! [`poly1305_gadget`] constrains the same computation. Keys are given as
+//! the little-endian words of a ChaCha20 block, so that the one-time key of
+//! a ChaCha20-Poly1305 record can be taken from the output of
+//! [`chacha20_block_gadget`].
+//!
+//! The accumulator is a single field element below `2^130`. Each block is
+//! multiplied by `r` with the top half of `r` scaled down by four, which
+//! clamping allows, so that the product folds into less than `2^196` and
+//! its reduction modulo `2^130 - 5` can be witnessed with a quotient
+//! without wrapping around the modulus of the field.
+//!
+//! [`chacha20_block_gadget`]: super::chacha20::chacha20_block_gadget
+
+use crate::constraint_system::{StandardComposer, Variable, WordVariable};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{BigInteger, Field, PrimeField};
+use num_traits::{One, Zero};
+
+/// Mask clearing the bits of `r` that must be zero.
+const CLAMP: u128 = 0x0ffffffc0ffffffc0ffffffc0fffffff;
+
+/// Mask of a limb of 26 bits.
+const LIMB: u64 = (1 << 26) - 1;
+
+/// Returns the integer of the little-endian `words`.
+fn words_to_integer(words: &[u32]) -> u128 {
+    words
+        .iter()
+        .rev()
+        .fold(0, |integer, word| (integer << 32) | *word as u128)
+}
+
+/// Returns the limbs of 26 bits of `integer`, from the least significant
+/// one.
+fn limbs(integer: u128) -> [u64; 5] {
+    let mut limbs = [0; 5];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = (integer >> (26 * i)) as u64 & LIMB;
+    }
+    limbs
+}
+
+/// Returns the tag of `message` under the one-time `key`, as little-endian
+/// words.
+pub fn poly1305(key: &[u32; 8], message: &[u8]) -> [u32; 4] {
+    let r = limbs(words_to_integer(&key[..4]) & CLAMP);
+    let s = words_to_integer(&key[4..]);
+
+    let mut h = [0u64; 5];
+    for block in message.chunks(16) {
+        let mut bytes = [0; 16];
+        bytes[..block.len()].copy_from_slice(block);
+        let mut m = limbs(u128::from_le_bytes(bytes));
+        let pad = 8 * block.len();
+        m[pad / 26] |= 1 << (pad % 26);
+        h.iter_mut().zip(m).for_each(|(h, m)| *h += m);
+
+        // Limbs of `r` past the top one wrap around multiplied by five
+        let mut product = [0u64; 5];
+        for (i, limb) in product.iter_mut().enumerate() {
+            for (j, h) in h.iter().enumerate() {
+                let r = if j <= i { r[i - j] } else { 5 * r[i + 5 - j] };
+                *limb += h * r;
+            }
+        }
+        let mut carry = 0;
+        for (h, limb) in h.iter_mut().zip(product) {
+            let limb = limb + carry;
+            *h = limb & LIMB;
+            carry = limb >> 26;
+        }
+        h[0] += 5 * carry;
+        h[1] += h[0] >> 26;
+        h[0] &= LIMB;
+    }
+
+    let mut carry = 0;
+    for limb in h.iter_mut().skip(1) {
+        *limb += carry;
+        carry = *limb >> 26;
+        *limb &= LIMB;
+    }
+    h[0] += 5 * carry;
+    h[1] += h[0] >> 26;
+    h[0] &= LIMB;
+
+    // h - p = h + 5 - 2^130, taken if h + 5 doesn't fit in 130 bits
+    let mut g = h;
+    let mut carry = 5;
+    for limb in g.iter_mut() {
+        *limb += carry;
+        carry = *limb >> 26;
+        *limb &= LIMB;
+    }
+    let h = if carry == 0 { h } else { g };
+
+    let tag = h.iter().enumerate().fold(s, |tag, (i, limb)| {
+        tag.wrapping_add((*limb as u128) << (26 * i))
+    });
+    [0, 1, 2, 3].map(|i| (tag >> (32 * i)) as u32)
+}
+
+/// Constrains the tag of `message` under the one-time `key`, returning the
+/// words of the result of [`poly1305`].
+///
+/// The bytes of the message are constrained to fit in eight bits.
+///
+/// # Panics
+///
+/// Panics if any of the words of the key doesn't have 32 bits.
+pub fn poly1305_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    key: &[WordVariable; 8],
+    message: &[Variable],
+) -> [WordVariable; 4]
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert!(
+        key.iter().all(|word| word.bits == 32),
+        "the words must have 32 bits"
+    );
+    let one = E::Fr::one();
+    let zero = E::Fr::zero();
+    let two = E::Fr::from(2u64);
+    let modulus = modulus::<E::Fr>();
+
+    // r = r_0 + 4 r_1 2^64, with the two low bits of r_1 cleared
+    let mut clamped = [key[0]; 4];
+    for (i, word) in clamped.iter_mut().enumerate() {
+        let mask = WordVariable {
+            var: composer.add_constant(E::Fr::from((CLAMP >> (32 * i)) as u32)),
+            bits: 32,
+        };
+        *word = composer.and_words(key[i], mask);
+    }
+    let r_low = composer.add(
+        (one, clamped[0].var),
+        (two.pow([32]), clamped[1].var),
+        zero,
+        None,
+    );
+    let r_high = composer.add(
+        (two.inverse().unwrap().square(), clamped[2].var),
+        (two.pow([30]), clamped[3].var),
+        zero,
+        None,
+    );
+
+    let mut h = composer.zero_var();
+    for block in message.chunks(16) {
+        let mut terms = vec![(one, h)];
+        for (i, byte) in block.iter().enumerate() {
+            composer.range_gate(*byte, 8);
+            terms.push((two.pow([8 * i as u64]), *byte));
+        }
+        let pad = two.pow([8 * block.len() as u64]);
+        let x = composer.linear_combination(&terms, pad);
+
+        // x = x_low + x_high 2^64, with x below 2^131
+        let (x_low_value, x_high_value) = split(composer.variables[x.0], 64);
+        let x_low = composer.add_input(x_low_value);
+        let x_high = composer.add_input(x_high_value);
+        let recomposed =
+            composer.add((one, x_low), (two.pow([64]), x_high), zero, None);
+        composer.assert_equal(recomposed, x);
+        composer.range_gate(x_low, 64);
+        composer.range_gate(x_high, 68);
+
+        // x r = x r_0 + x_low 4 r_1 2^64 + 5 x_high r_1 mod p
+        let product = composer.mul(one, x, r_low, zero, None);
+        let product = composer.big_mul(
+            two.pow([66]),
+            x_low,
+            r_high,
+            Some((one, product)),
+            zero,
+            None,
+        );
+        let product = composer.big_mul(
+            E::Fr::from(5u64),
+            x_high,
+            r_high,
+            Some((one, product)),
+            zero,
+            None,
+        );
+
+        let product_value = composer.variables[product.0];
+        let reduced = reduce(product_value);
+        let quotient = composer
+            .add_input((product_value - reduced) * modulus.inverse().unwrap());
+        h = composer.add((one, product), (-modulus, quotient), zero, None);
+        composer.range_gate(h, 130);
+        composer.range_gate(quotient, 68);
+    }
+
+    // h - b p, canonical if it and its sum with five fit in 130 bits
+    let h_value = composer.variables[h.0];
+    let above = composer.add_bool(h_value.into_repr() >= modulus.into_repr());
+    let h = composer.add((one, h), (-modulus, above.variable()), zero, None);
+    let zero_var = composer.zero_var();
+    let gap = composer.add((one, h), (zero, zero_var), E::Fr::from(5u64), None);
+    composer.range_gate(h, 130);
+    composer.range_gate(gap, 130);
+
+    // h + s = tag + top 2^128
+    let mut terms = vec![(one, h)];
+    let mut sum = composer.variables[h.0];
+    for (i, word) in key[4..].iter().enumerate() {
+        terms.push((two.pow([32 * i as u64]), word.var));
+        sum += two.pow([32 * i as u64]) * composer.variables[word.var.0];
+    }
+    let (tag_value, top_value) = split(sum, 128);
+    let tag_value = tag_value.into_repr();
+    let mut tag = [key[0]; 4];
+    for (i, word) in tag.iter_mut().enumerate() {
+        let value = (tag_value.as_ref()[i / 2] >> (32 * (i % 2))) as u32;
+        let var = composer.add_input(E::Fr::from(value));
+        *word = composer.range_gate(var, 32);
+        terms.push((-two.pow([32 * i as u64]), var));
+    }
+    let top = composer.add_input(top_value);
+    composer.range_gate(top, 4);
+    terms.push((-two.pow([128]), top));
+    let difference = composer.linear_combination(&terms, zero);
+    composer.constrain_to_constant(difference, zero, None);
+    tag
+}
+
+/// Returns the modulus of Poly1305, `2^130 - 5`.
+fn modulus<F>() -> F
+where
+    F: PrimeField,
+{
+    F::from(2u64).pow([130]) - F::from(5u64)
+}
+
+/// Returns the integers below and above `2^bits` in `value`.
+fn split<F>(value: F, bits: u32) -> (F, F)
+where
+    F: PrimeField,
+{
+    let mut high = value.into_repr();
+    high.divn(bits);
+    let high = F::from_repr(high).expect("shifting keeps the value in range");
+    (value - high * F::from(2u64).pow([bits as u64]), high)
+}
+
+/// Returns `value` modulo `2^130 - 5`, folding its bits above `2^130` as
+/// `2^130 = 5`.
+fn reduce<F>(mut value: F) -> F
+where
+    F: PrimeField,
+{
+    loop {
+        let (low, high) = split(value, 130);
+        if high.is_zero() {
+            break;
+        }
+        value = low + high * F::from(5u64);
+    }
+    let modulus = modulus::<F>();
+    if value.into_repr() >= modulus.into_repr() {
+        value -= modulus;
+    }
+    value
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    /// Key and tag of the example of section 2.5.2 of RFC 8439.
+    const KEY: [u32; 8] = [
+        0x78bed685, 0x336d5557, 0xfe52447f, 0xa806d542, 0x8a800301, 0xfdb20dfb,
+        0xaff6bf4a, 0x1bf54941,
+    ];
+    const MESSAGE: &[u8] = b"Cryptographic Forum Research Group";
+    const TAG: [u32; 4] = [0xc11d06a8, 0xc6365130, 0xaf8b2bc2, 0xa927010c];
+
+    #[test]
+    fn test_poly1305() {
+        assert_eq!(poly1305(&KEY, MESSAGE), TAG);
+        assert_eq!(
+            poly1305(&[u32::MAX; 8], &[0xff; 64]),
+            [0x2be30f90, 0xd7a85fc1, 0xe4efa8bc, 0xb17ee3c7]
+        );
+        // Accumulator of 2^130 - 2, above the modulus, from test vector #5
+        // of appendix A.3 of RFC 8439
+        let mut key = [0; 8];
+        key[0] = 2;
+        assert_eq!(poly1305(&key, &[0xff; 16]), [3, 0, 0, 0]);
+    }
+
+    fn test_poly1305_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mac = |key: [u32; 8], message: &[u8], tag: [u32; 4]| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let key = key.map(|word| {
+                        let var = composer.add_input(E::Fr::from(word));
+                        composer.range_gate(var, 32)
+                    });
+                    let message: Vec<_> = message
+                        .iter()
+                        .map(|byte| composer.add_input(E::Fr::from(*byte)))
+                        .collect();
+                    let result = poly1305_gadget(composer, &key, &message);
+                    for (word, value) in result.iter().zip(tag) {
+                        composer.constrain_to_constant(
+                            word.var,
+                            E::Fr::from(value),
+                            None,
+                        );
+                    }
+                },
+                1 << 11,
+            )
+        };
+        assert!(mac(KEY, MESSAGE, TAG).is_ok());
+        let mut wrong = TAG;
+        wrong[3] ^= 1 << 31;
+        assert!(mac(KEY, MESSAGE, wrong).is_err());
+
+        let mut key = [0; 8];
+        key[0] = 2;
+        assert!(mac(key, &[0xff; 16], [3, 0, 0, 0]).is_ok());
+        assert!(mac(
+            [u32::MAX; 8],
+            &[0xff; 48],
+            [0x6b6afc5e, 0x4cecfc51, 0x75507c78, 0xe4957c99]
+        )
+        .is_ok());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_poly1305_gadget],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_poly1305_gadget],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_poly1305_gadget],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
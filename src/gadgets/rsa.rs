@@ -0,0 +1,301 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! RSA signature verification.
+//!
+//! [`RsaPublicKey::verify`] checks a PKCS#1 v1.5 signature of a SHA-256
+//! digest under a key with the public exponent 65537, and
+//! [`rsa_verify_gadget`] constrains the same check on [`BigUintVariable`]s,
+//! so that circuits can prove the possession of a signature issued on a
+//! legal or identity document without revealing it. The exponentiation,
+//! sixteen squarings and a multiplication modulo the key, costs about 70k
+//! gates for RSA-2048.
+//!
+//! The message is hashed out of circuit and the gadgets take its digest.
+//! PSS encodings mask their salt with MGF1, which needs SHA-256 in circuit:
+//! [`rsa_public_gadget`] returns the encoded message of a signature, but
+//! decoding it is left to the caller.
+
+use crate::constraint_system::{StandardComposer, Variable};
+use crate::error::Error;
+use crate::gadgets::bigint::{
+    assert_less_than_gadget, mul_mod_gadget, BigUintVariable, LIMB_BITS,
+};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::Field;
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// Public exponent of the supported keys.
+pub const PUBLIC_EXPONENT: u32 = 65537;
+
+/// Number of squarings raising to [`PUBLIC_EXPONENT`], `2^16 + 1`, before
+/// the last multiplication.
+const SQUARINGS: usize = 16;
+
+/// DER encoding of the `DigestInfo` of SHA-256, preceding the digest in
+/// PKCS#1 v1.5 encodings.
+const SHA256_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03,
+    0x04, 0x02, 0x01, 0x05, 0x00, 0x04, 0x20,
+];
+
+/// Minimum number of bytes of padding in PKCS#1 v1.5 encodings.
+const MIN_PADDING: usize = 8;
+
+/// RSA public key with the exponent [`PUBLIC_EXPONENT`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RsaPublicKey {
+    /// Modulus of the key
+    modulus: BigUint,
+}
+
+impl RsaPublicKey {
+    /// Creates the public key of `modulus`.
+    pub fn new(modulus: BigUint) -> Self {
+        Self { modulus }
+    }
+
+    /// Returns the modulus of the key.
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns the number of bytes of the modulus, which is also the size
+    /// of the encoded messages.
+    pub fn size(&self) -> usize {
+        (self.modulus.bits() as usize).div_ceil(8)
+    }
+
+    /// Returns the number of limbs of the modulus in a [`BigUintVariable`].
+    pub fn num_limbs(&self) -> usize {
+        (self.modulus.bits() as usize).div_ceil(LIMB_BITS)
+    }
+
+    /// Checks that `signature` is a PKCS#1 v1.5 signature of the SHA-256
+    /// `digest` under the key.
+    pub fn verify(
+        &self,
+        digest: &[u8; 32],
+        signature: &BigUint,
+    ) -> Result<(), Error> {
+        let encoded = pkcs1v15_encode(digest, self.size())
+            .ok_or(Error::RsaVerificationFailure)?;
+        if signature < &self.modulus
+            && signature.modpow(&PUBLIC_EXPONENT.into(), &self.modulus)
+                == encoded
+        {
+            Ok(())
+        } else {
+            Err(Error::RsaVerificationFailure)
+        }
+    }
+}
+
+/// Returns the PKCS#1 v1.5 encoding of the SHA-256 `digest` in `size`
+/// bytes, `00 01 ff .. ff 00 || DigestInfo || digest`, or `None` if `size`
+/// is too small.
+fn pkcs1v15_encode(digest: &[u8; 32], size: usize) -> Option<BigUint> {
+    let padding = size.checked_sub(3 + SHA256_PREFIX.len() + digest.len())?;
+    if padding < MIN_PADDING {
+        return None;
+    }
+    let mut encoded = vec![0x00, 0x01];
+    encoded.resize(2 + padding, 0xff);
+    encoded.push(0x00);
+    encoded.extend_from_slice(&SHA256_PREFIX);
+    encoded.extend_from_slice(digest);
+    Some(BigUint::from_bytes_be(&encoded))
+}
+
+/// Constrains the public operation of RSA on `signature`, returning
+/// `signature^65537` modulo `modulus`, with as many limbs as `modulus`.
+///
+/// The signature is constrained to be less than the modulus.
+pub fn rsa_public_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    modulus: &BigUintVariable,
+    signature: &BigUintVariable,
+) -> BigUintVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert_less_than_gadget(composer, signature, modulus);
+    let mut power = signature.clone();
+    for _ in 0..SQUARINGS {
+        power = mul_mod_gadget(composer, &power, &power, modulus);
+    }
+    mul_mod_gadget(composer, &power, signature, modulus)
+}
+
+/// Constrains `signature` to be a PKCS#1 v1.5 signature of the SHA-256
+/// `digest` under the key of `modulus`.
+///
+/// The encoded message has eight bytes per limb of the modulus, which must
+/// therefore fill its limbs, as the moduli of RSA-2048 and RSA-4096 do. The
+/// bytes of the digest, in big-endian order, are constrained to fit in
+/// eight bits.
+///
+/// # Panics
+///
+/// Panics if the modulus has too few limbs for the encoding.
+pub fn rsa_verify_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    modulus: &BigUintVariable,
+    digest: &[Variable; 32],
+    signature: &BigUintVariable,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let size = modulus.limbs().len() * LIMB_BITS / 8;
+    let padding = pkcs1v15_encode(&[0; 32], size)
+        .expect("the modulus is too small for the encoding");
+    let encoded = rsa_public_gadget(composer, modulus, signature);
+
+    // The limbs holding the digest are recomposed from its bytes, and the
+    // others are constant
+    let digest_limbs = digest.len() * 8 / LIMB_BITS;
+    let mut padding = padding.to_u64_digits();
+    padding.resize(encoded.limbs().len(), 0);
+    for (limb, value) in encoded.limbs().iter().zip(padding).skip(digest_limbs)
+    {
+        composer.constrain_to_constant(*limb, E::Fr::from(value), None);
+    }
+    for (limb, bytes) in encoded.limbs().iter().zip(digest.rchunks(8)) {
+        let terms: Vec<_> = bytes
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, byte)| {
+                composer.range_gate(*byte, 8);
+                (E::Fr::from(2u64).pow([8 * i as u64]), *byte)
+            })
+            .collect();
+        let recomposed = composer.linear_combination(&terms, E::Fr::zero());
+        composer.assert_equal(recomposed, *limb);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+
+    /// Modulus of an RSA-2048 key.
+    const MODULUS: [&str; 8] = [
+        "bfa688b1940664d2ecde0d5422d1d57f7206a795e4b02d6ccfe1c6ed680048d2",
+        "7b488d8f8255984c57501cc6c30b93c392be530e5818406de7edd76e8f14c457",
+        "39b0fee6ae88408c19b601cf47fe1949931c4d8cf0bf08fae171ab71e2cf8735",
+        "2d22ba82ef484d0afab2161df00b7df5b28c19bbbb1ec7d27b980f7ffc2ad85c",
+        "1ef2b80225cd807b7abe8441f67851fe2fe74b30edb9bc9979fc3f1163ba775f",
+        "cc1b28a47f4fe98448f22dba07ff06c9b47d0bd9ebe629f3ff9f3919f7ff64e3",
+        "9bbdfa0704085948370c558295ac33999bfb6b2101f1d88f4cf259af04a9f002",
+        "a23a541aa24f637b9da0dab64e17099a4413facc204923b94c3d140bc2d98187",
+    ];
+
+    /// PKCS#1 v1.5 signature under [`MODULUS`] of the SHA-256 [`DIGEST`] of
+    /// "ark-plonk RSA gadget".
+    const SIGNATURE: [&str; 8] = [
+        "1eef2e373be4ccc90f9b7f7285e3f7f1c882c20af0a2f5b79c1c282bcd51ce73",
+        "9359c6490491a2e917ff359be002b2e5758be27e355dce7f92b0ec72e36e1516",
+        "a216964d89d59147cde36c834235c08a602920e9aed73122dc220aaf352aeeec",
+        "0511d073248f21509efb68f8cfd21fd6547eb87950b50220cfa0f01d60514d75",
+        "2a9891df2509811c2ddab1dbe8e408071c4173119cf93d672003514a2546c304",
+        "f86dc0340ff4a53413dbfbc08a8a8979cea641a07fc1d86844712ef09b5bc971",
+        "971c6330be123ec58b2db35a939732c1244906dbc9dc7b69c412bcdd74e1532e",
+        "eebbc56084c804e025ba4c323c813ab84ac4fb4510eae26bd4495c345e231baf",
+    ];
+
+    const DIGEST: [u8; 32] = [
+        0x56, 0xdb, 0x51, 0x79, 0xa4, 0xeb, 0x36, 0x00, 0x46, 0xa9, 0xf1, 0x1a,
+        0xe0, 0xe0, 0x9b, 0x17, 0x4c, 0x1e, 0xeb, 0x29, 0xaf, 0x3d, 0xf9, 0xe6,
+        0x16, 0xe5, 0x6d, 0xe6, 0x22, 0x63, 0x20, 0xe3,
+    ];
+
+    fn parse(hex: &[&str]) -> BigUint {
+        BigUint::parse_bytes(hex.concat().as_bytes(), 16).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let key = RsaPublicKey::new(parse(&MODULUS));
+        let signature = parse(&SIGNATURE);
+        assert_eq!(key.size(), 256);
+        assert!(key.verify(&DIGEST, &signature).is_ok());
+
+        let mut digest = DIGEST;
+        digest[31] ^= 1;
+        assert!(key.verify(&digest, &signature).is_err());
+        assert!(key.verify(&DIGEST, &(&signature + key.modulus())).is_err());
+    }
+
+    fn test_rsa_verify_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let key = RsaPublicKey::new(parse(&MODULUS));
+        let verify = |digest: [u8; 32]| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let modulus = BigUintVariable::constant(
+                        composer,
+                        key.modulus(),
+                        key.num_limbs(),
+                    );
+                    let digest = digest
+                        .map(|byte| composer.add_input(E::Fr::from(byte)));
+                    let signature = BigUintVariable::allocate(
+                        composer,
+                        &parse(&SIGNATURE),
+                        key.num_limbs(),
+                    );
+                    rsa_verify_gadget(composer, &modulus, &digest, &signature);
+                },
+                1 << 17,
+            )
+        };
+        assert!(verify(DIGEST).is_ok());
+        let mut digest = DIGEST;
+        digest[0] ^= 1;
+        assert!(verify(digest).is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_rsa_verify_gadget],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_rsa_verify_gadget],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_rsa_verify_gadget],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
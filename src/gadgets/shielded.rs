@@ -0,0 +1,467 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Notes of shielded payments.
+//!
+//! A [`Note`] holds a value owned by a public key of the embedded curve. It
+//! is published as a commitment, either the hash of its fields or a
+//! Pedersen commitment to them, and spent by revealing its nullifier, the
+//! hash of the commitment under a key only the owner can derive. The
+//! gadgets of this module constrain the same computations, and check that
+//! the prover owns the note it spends, so that payment circuits only have
+//! to compose them.
+//!
+//! All the hashes are computed with a [`CircuitHash`], and separated by a
+//! leading domain element.
+
+use crate::constraint_system::ecc::hash_to_curve::hash_to_curve;
+use crate::constraint_system::ecc::{generator, Point};
+use crate::constraint_system::{
+    PoseidonParameters, StandardComposer, Variable,
+};
+use crate::gadgets::CircuitHash;
+use crate::util;
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, PairingEngine,
+    ProjectiveCurve, TEModelParameters,
+};
+use ark_ff::{PrimeField, UniformRand};
+use num_traits::One;
+use rand_core::RngCore;
+
+/// Domain of the hash commitments to notes.
+const COMMITMENT_DOMAIN: u64 = 1;
+
+/// Domain of the hash of the owner and nonce in Pedersen commitments.
+const PEDERSEN_DOMAIN: u64 = 2;
+
+/// Domain of the nullifier keys.
+const NULLIFIER_KEY_DOMAIN: u64 = 3;
+
+/// Domain of the nullifiers.
+const NULLIFIER_DOMAIN: u64 = 4;
+
+/// Note holding a value owned by a public key.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct Note<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Public key of the owner
+    pub owner: GroupAffine<P>,
+
+    /// Value of the note
+    pub value: u64,
+
+    /// Nonce making the note unique
+    pub rho: E::Fr,
+
+    /// Randomness of the commitment
+    pub rcm: P::ScalarField,
+}
+
+/// Variables allocated for a [`Note`].
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Copy(bound = ""), Debug(bound = ""))]
+pub struct NoteVariables<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Public key of the owner
+    pub owner: Point<E, P>,
+
+    /// Value of the note, constrained to 64 bits
+    pub value: Variable,
+
+    /// Nonce making the note unique
+    pub rho: Variable,
+
+    /// Randomness of the commitment, as a scalar of the embedded curve
+    pub rcm: Variable,
+}
+
+impl<E, P> Note<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Creates a note of `value` owned by `owner`, with a random nonce and
+    /// commitment randomness.
+    pub fn new<R>(owner: GroupAffine<P>, value: u64, rng: &mut R) -> Self
+    where
+        R: RngCore,
+    {
+        Self {
+            owner,
+            value,
+            rho: E::Fr::rand(rng),
+            rcm: P::ScalarField::rand(rng),
+        }
+    }
+
+    /// Returns the hash commitment to the note.
+    pub fn commitment<H>(&self, hash: &H) -> E::Fr
+    where
+        H: CircuitHash<E, P>,
+    {
+        hash.hash(&[
+            E::Fr::from(COMMITMENT_DOMAIN),
+            self.owner.x,
+            self.owner.y,
+            E::Fr::from(self.value),
+            self.rho,
+            util::from_embedded_curve_scalar::<E, P>(self.rcm),
+        ])
+    }
+
+    /// Returns the Pedersen commitment to the note, whose value and
+    /// randomness multiply independent generators, so that commitments can
+    /// be added to add their values.
+    pub fn pedersen_commitment<H>(&self, hash: &H) -> GroupAffine<P>
+    where
+        H: CircuitHash<E, P>,
+    {
+        let [value_base, randomness_base, note_base] = pedersen_bases::<E, P>();
+        let note = hash.hash(&[
+            E::Fr::from(PEDERSEN_DOMAIN),
+            self.owner.x,
+            self.owner.y,
+            self.rho,
+        ]);
+        (value_base.into_projective().mul([self.value])
+            + randomness_base.mul(self.rcm)
+            + note_base.into_projective().mul(note.into_repr()))
+        .into_affine()
+    }
+
+    /// Returns the nullifier of the note, derived with the nullifier key of
+    /// `secret`.
+    pub fn nullifier<H>(&self, hash: &H, secret: P::ScalarField) -> E::Fr
+    where
+        H: CircuitHash<E, P>,
+    {
+        hash.hash(&[
+            E::Fr::from(NULLIFIER_DOMAIN),
+            nullifier_key(hash, secret),
+            self.commitment(hash),
+        ])
+    }
+
+    /// Adds the fields of the note to `composer`, constraining the value to
+    /// 64 bits.
+    pub fn allocate(
+        &self,
+        composer: &mut StandardComposer<E, P>,
+    ) -> NoteVariables<E, P> {
+        let value = composer.add_input(E::Fr::from(self.value));
+        composer.range_gate(value, 64);
+        NoteVariables {
+            owner: composer.add_affine(self.owner),
+            value,
+            rho: composer.add_input(self.rho),
+            rcm: composer.add_embedded_scalar(self.rcm),
+        }
+    }
+}
+
+/// Returns the nullifier key of the owner of `secret`.
+pub fn nullifier_key<E, P, H>(hash: &H, secret: P::ScalarField) -> E::Fr
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    hash.hash(&[
+        E::Fr::from(NULLIFIER_KEY_DOMAIN),
+        util::from_embedded_curve_scalar::<E, P>(secret),
+    ])
+}
+
+/// Returns the bases of the value, the randomness and the hash of the other
+/// fields in Pedersen commitments, hashed to the curve so that their
+/// discrete logarithms are unknown.
+fn pedersen_bases<E, P>() -> [GroupAffine<P>; 3]
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let params = PoseidonParameters::new();
+    [0u64, 1, 2].map(|index| {
+        hash_to_curve::<E, P>(
+            &params,
+            &[E::Fr::from(PEDERSEN_DOMAIN), E::Fr::from(index)],
+        )
+    })
+}
+
+/// Constrains the hash commitment to `note`, returning the [`Variable`]
+/// holding the result of [`Note::commitment`].
+pub fn note_commitment_gadget<E, P, H>(
+    composer: &mut StandardComposer<E, P>,
+    hash: &H,
+    note: &NoteVariables<E, P>,
+) -> Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    let domain = composer.add_constant(E::Fr::from(COMMITMENT_DOMAIN));
+    hash.hash_gadget(
+        composer,
+        &[
+            domain,
+            *note.owner.x(),
+            *note.owner.y(),
+            note.value,
+            note.rho,
+            note.rcm,
+        ],
+    )
+}
+
+/// Constrains the Pedersen commitment to `note`, returning the [`Point`]
+/// holding the result of [`Note::pedersen_commitment`].
+pub fn pedersen_note_commitment_gadget<E, P, H>(
+    composer: &mut StandardComposer<E, P>,
+    hash: &H,
+    note: &NoteVariables<E, P>,
+) -> Point<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    let [value_base, randomness_base, note_base] = pedersen_bases::<E, P>();
+    let domain = composer.add_constant(E::Fr::from(PEDERSEN_DOMAIN));
+    let hashed = hash.hash_gadget(
+        composer,
+        &[domain, *note.owner.x(), *note.owner.y(), note.rho],
+    );
+    let value = composer.fixed_base_scalar_mul(note.value, value_base);
+    let randomness = composer.fixed_base_scalar_mul(note.rcm, randomness_base);
+    // The hash may exceed the scalars supported by fixed-base
+    // multiplications
+    let note_base = composer.add_affine_to_circuit_description(note_base);
+    let hashed = composer.variable_base_scalar_mul(hashed, note_base);
+    let sum = composer.point_addition_gate(value, randomness);
+    composer.point_addition_gate(sum, hashed)
+}
+
+/// Constrains the nullifier of the note committed to by `commitment`,
+/// derived with the nullifier key of `secret`, returning the [`Variable`]
+/// holding the result of [`Note::nullifier`].
+///
+/// The `secret` is a scalar of the embedded curve, added with
+/// [`StandardComposer::add_embedded_scalar`], and is constrained to be
+/// below the order of the embedded group, so that each note has a single
+/// nullifier.
+pub fn nullifier_gadget<E, P, H>(
+    composer: &mut StandardComposer<E, P>,
+    hash: &H,
+    secret: Variable,
+    commitment: Variable,
+) -> Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    H: CircuitHash<E, P>,
+{
+    assert_embedded_scalar(composer, secret);
+    let domain = composer.add_constant(E::Fr::from(NULLIFIER_KEY_DOMAIN));
+    let key = hash.hash_gadget(composer, &[domain, secret]);
+    let domain = composer.add_constant(E::Fr::from(NULLIFIER_DOMAIN));
+    hash.hash_gadget(composer, &[domain, key, commitment])
+}
+
+/// Constrains `owner` to be the public key of `secret`, i.e. `secret`
+/// times the generator of the embedded curve.
+///
+/// The `secret` is a scalar of the embedded curve, added with
+/// [`StandardComposer::add_embedded_scalar`], and is constrained to be
+/// below the order of the embedded group.
+pub fn ownership_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    secret: Variable,
+    owner: Point<E, P>,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert_embedded_scalar(composer, secret);
+    let public_key = composer.fixed_base_scalar_mul(secret, generator::<P>());
+    composer.assert_equal_point(public_key, owner);
+}
+
+/// Constrains `secret` to be below the order of the prime order subgroup of
+/// the embedded curve.
+///
+/// The scalar multiplications use every bit of their scalar, so
+/// `secret + k r` would otherwise give the same public key for another
+/// nullifier key, and therefore another nullifier for the same note.
+fn assert_embedded_scalar<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    secret: Variable,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let max = util::from_embedded_curve_scalar::<E, P>(-P::ScalarField::one());
+    composer.assert_at_most(secret, max);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constraint_system::GmimcParameters;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use num_traits::{One, Zero};
+    use rand_core::OsRng;
+
+    fn check_note<E, P, H>(hash: H)
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        H: CircuitHash<E, P>,
+    {
+        let secret = P::ScalarField::rand(&mut OsRng);
+        let owner = generator::<P>().mul(secret).into_affine();
+        let note = Note::<E, P>::new(owner, 42, &mut OsRng);
+
+        // Commitments hide distinct notes of the same value and owner
+        let other = Note::<E, P>::new(owner, 42, &mut OsRng);
+        assert_ne!(note.commitment(&hash), other.commitment(&hash));
+        assert_ne!(
+            note.pedersen_commitment(&hash),
+            other.pedersen_commitment(&hash)
+        );
+        assert_ne!(
+            note.nullifier(&hash, secret),
+            other.nullifier(&hash, secret)
+        );
+
+        // Pedersen commitments add the values of the notes
+        let [value_base, randomness_base, _] = pedersen_bases::<E, P>();
+        let mut more = note;
+        more.value += 1;
+        assert_eq!(
+            more.pedersen_commitment(&hash),
+            note.pedersen_commitment(&hash) + value_base
+        );
+        more.rcm += P::ScalarField::one();
+        assert_eq!(
+            more.pedersen_commitment(&hash),
+            note.pedersen_commitment(&hash) + value_base + randomness_base
+        );
+
+        let nullifier = note.nullifier(&hash, secret);
+        let spend = |spender: E::Fr| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let variables = note.allocate(composer);
+                    let secret = composer.add_input(spender);
+                    ownership_gadget(composer, secret, variables.owner);
+
+                    let commitment =
+                        note_commitment_gadget(composer, &hash, &variables);
+                    composer.constrain_to_constant(
+                        commitment,
+                        note.commitment(&hash),
+                        None,
+                    );
+                    let pedersen = pedersen_note_commitment_gadget(
+                        composer, &hash, &variables,
+                    );
+                    composer.assert_equal_public_point(
+                        pedersen,
+                        note.pedersen_commitment(&hash),
+                    );
+                    let revealed =
+                        nullifier_gadget(composer, &hash, secret, commitment);
+                    composer.constrain_to_constant(
+                        revealed,
+                        E::Fr::zero(),
+                        Some(-nullifier),
+                    );
+                },
+                1 << 13,
+            )
+        };
+        let scalar = util::from_embedded_curve_scalar::<E, P>;
+        assert!(spend(scalar(secret)).is_ok());
+
+        // Only the owner can spend the note
+        assert!(spend(scalar(secret + P::ScalarField::one())).is_err());
+
+        // Aliases of the secret modulo the order of the embedded group give
+        // the same public key, but would reveal another nullifier
+        let order = scalar(-P::ScalarField::one()) + E::Fr::one();
+        let alias = scalar(secret) + order;
+        assert_ne!(
+            nullifier_key(&hash, secret),
+            hash.hash(&[E::Fr::from(NULLIFIER_KEY_DOMAIN), alias])
+        );
+        assert!(spend(alias).is_err());
+    }
+
+    fn test_note_poseidon<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        check_note::<E, P, _>(PoseidonParameters::new());
+    }
+
+    fn test_note_gmimc<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        check_note::<E, P, _>(GmimcParameters::new());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_note_poseidon, test_note_gmimc],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_note_poseidon, test_note_gmimc],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_note_poseidon, test_note_gmimc],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,486 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Arithmetic on 256-bit unsigned integers.
+//!
+//! A [`U256Variable`] is a [`BigUintVariable`] of four limbs, the size of
+//! the words of the EVM, on which additions and subtractions return their
+//! carry, products are kept in full, and reductions are by constant moduli,
+//! e.g. to emulate the base field of secp256k1.
+//!
+//! Products are computed with the schoolbook method: the gates of additions
+//! cost as much as the ones of multiplications, so Karatsuba's method would
+//! trade each product of limbs it saves for more additions.
+
+use crate::constraint_system::{BoolVariable, StandardComposer};
+use crate::gadgets::bigint::{self, BigUintVariable, LIMB_BITS};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::Field;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Number of limbs of a [`U256Variable`].
+pub const NUM_LIMBS: usize = 256 / LIMB_BITS;
+
+/// Unsigned integer of 256 bits, held by [`NUM_LIMBS`] limbs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct U256Variable {
+    /// Integer of [`NUM_LIMBS`] limbs
+    int: BigUintVariable,
+}
+
+impl U256Variable {
+    /// Adds `value` to `composer`, constraining each of its limbs to
+    /// [`LIMB_BITS`] bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` doesn't fit in 256 bits.
+    pub fn allocate<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: &BigUint,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        Self {
+            int: BigUintVariable::allocate(composer, value, NUM_LIMBS),
+        }
+    }
+
+    /// Returns the integer fixed to `value` as a part of the circuit
+    /// description.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` doesn't fit in 256 bits.
+    pub fn constant<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        value: &BigUint,
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        Self {
+            int: BigUintVariable::constant(composer, value, NUM_LIMBS),
+        }
+    }
+
+    /// Returns the integer as a [`BigUintVariable`], to use it with the
+    /// gadgets of [`bigint`].
+    pub fn as_biguint(&self) -> &BigUintVariable {
+        &self.int
+    }
+
+    /// Returns the value of the integer in the witness of `composer`.
+    pub fn value<E, P>(&self, composer: &StandardComposer<E, P>) -> BigUint
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        self.int.value(composer)
+    }
+}
+
+/// Constrains the sum of `a` and `b` modulo `2^256`, returning it with the
+/// bit carried out of it.
+pub fn add_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    b: &U256Variable,
+) -> (U256Variable, BoolVariable)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    add_limbs(composer, a, b, false)
+}
+
+/// Constrains the difference of `a` and `b` modulo `2^256`, returning it
+/// with the bit borrowed by it, which is set if `a` is less than `b`.
+pub fn sub_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    b: &U256Variable,
+) -> (U256Variable, BoolVariable)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    add_limbs(composer, a, b, true)
+}
+
+/// Constrains `a + b`, or `a - b` if `negate` is set, limb by limb, with a
+/// witnessed bit carried or borrowed between limbs, returning the result
+/// modulo `2^256` and the last bit.
+fn add_limbs<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    b: &U256Variable,
+    negate: bool,
+) -> (U256Variable, BoolVariable)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let one = E::Fr::one();
+    let sign = if negate { -one } else { one };
+    let two_64 = E::Fr::from(2u64).pow([LIMB_BITS as u64]);
+    let a_values = limb_values(composer, a);
+    let b_values = limb_values(composer, b);
+
+    let mut carry = composer.zero_var();
+    let mut carry_value = 0;
+    let mut limbs = Vec::with_capacity(NUM_LIMBS);
+    let mut bits = Vec::with_capacity(NUM_LIMBS);
+    for i in 0..NUM_LIMBS {
+        let (a_value, b_value) = (a_values[i] as i128, b_values[i] as i128);
+        let value = if negate {
+            a_value - b_value - carry_value
+        } else {
+            a_value + b_value + carry_value
+        };
+        let out = !(0..1 << LIMB_BITS).contains(&value);
+        let bit = composer.add_bool(out);
+        let limb = composer.linear_combination(
+            &[
+                (one, a.int.limbs[i]),
+                (sign, b.int.limbs[i]),
+                (sign, carry),
+                (-sign * two_64, bit.variable()),
+            ],
+            E::Fr::zero(),
+        );
+        composer.range_gate(limb, LIMB_BITS);
+        limbs.push(limb);
+        bits.push(bit);
+        carry = bit.variable();
+        carry_value = out as i128;
+    }
+    let int = BigUintVariable { limbs };
+    (U256Variable { int }, bits[NUM_LIMBS - 1])
+}
+
+/// Returns the limbs of the value of `x` in the witness of `composer`.
+fn limb_values<E, P>(
+    composer: &StandardComposer<E, P>,
+    x: &U256Variable,
+) -> Vec<u64>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut values = x.value(composer).to_u64_digits();
+    values.resize(NUM_LIMBS, 0);
+    values
+}
+
+/// Returns a bit set if `a` is strictly less than `b`, the bit borrowed by
+/// their difference.
+pub fn less_than_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    b: &U256Variable,
+) -> BoolVariable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    sub_gadget(composer, a, b).1
+}
+
+/// Constrains the product of `a` and `b`, returning its low and high 256
+/// bits.
+pub fn mul_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    b: &U256Variable,
+) -> (U256Variable, U256Variable)
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut limbs = bigint::mul_gadget(composer, &a.int, &b.int).limbs;
+    let high = limbs.split_off(NUM_LIMBS);
+    (
+        U256Variable {
+            int: BigUintVariable { limbs },
+        },
+        U256Variable {
+            int: BigUintVariable { limbs: high },
+        },
+    )
+}
+
+/// Constrains `a` modulo the constant `modulus`.
+///
+/// # Panics
+///
+/// Panics if `modulus` is zero or doesn't fit in 256 bits.
+pub fn reduce_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    modulus: &BigUint,
+) -> U256Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    reduce(composer, &a.int, modulus)
+}
+
+/// Constrains the product of `a` and `b` modulo the constant `modulus`,
+/// reducing their full product, so that `a` and `b` don't need to be
+/// reduced.
+///
+/// # Panics
+///
+/// Panics if `modulus` is zero or doesn't fit in 256 bits.
+pub fn mul_mod_gadget<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &U256Variable,
+    b: &U256Variable,
+    modulus: &BigUint,
+) -> U256Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let product = bigint::mul_gadget(composer, &a.int, &b.int);
+    reduce(composer, &product, modulus)
+}
+
+/// Constrains `a` modulo the constant `modulus`.
+fn reduce<E, P>(
+    composer: &mut StandardComposer<E, P>,
+    a: &BigUintVariable,
+    modulus: &BigUint,
+) -> U256Variable
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    assert!(!modulus.is_zero(), "the modulus must not be zero");
+    let modulus = BigUintVariable::constant(composer, modulus, NUM_LIMBS);
+    U256Variable {
+        int: bigint::reduce_gadget(composer, a, &modulus),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test, constraint_system::helper::*};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use rand_core::{OsRng, RngCore};
+
+    fn random_u256() -> BigUint {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        BigUint::from_bytes_le(&bytes)
+    }
+
+    /// Modulus of the base field of secp256k1.
+    fn secp256k1_modulus() -> BigUint {
+        (BigUint::one() << 256) - (BigUint::one() << 32) - 977u64
+    }
+
+    fn test_add_sub_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let two_256 = BigUint::one() << 256;
+        let add_sub = |a: &BigUint, b: &BigUint, sum: &BigUint, carry: bool| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = U256Variable::allocate(composer, a);
+                    let b = U256Variable::allocate(composer, b);
+                    let (result, carry_bit) = add_gadget(composer, &a, &b);
+                    let expected = U256Variable::constant(composer, sum);
+                    bigint::assert_equal_gadget(
+                        composer,
+                        &result.int,
+                        &expected.int,
+                    );
+                    composer.constrain_to_constant(
+                        carry_bit.variable(),
+                        E::Fr::from(carry as u64),
+                        None,
+                    );
+                    let (result, borrow) = sub_gadget(composer, &result, &b);
+                    bigint::assert_equal_gadget(composer, &result.int, &a.int);
+                    composer
+                        .assert_equal(borrow.variable(), carry_bit.variable());
+                },
+                1 << 8,
+            )
+        };
+        let a = random_u256();
+        let b = random_u256();
+        let sum = &a + &b;
+        let carry = sum >= two_256;
+        let sum = sum % &two_256;
+        assert!(add_sub(&a, &b, &sum, carry).is_ok());
+        assert!(add_sub(&a, &b, &sum, !carry).is_err());
+        assert!(add_sub(&a, &b, &(&sum ^ BigUint::one()), carry).is_err());
+        let max = &two_256 - 1u64;
+        assert!(add_sub(&max, &BigUint::one(), &BigUint::zero(), true).is_ok());
+        assert!(add_sub(&max, &BigUint::zero(), &max, false).is_ok());
+    }
+
+    fn test_less_than_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let less_than = |a: &BigUint, b: &BigUint, less: bool| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = U256Variable::allocate(composer, a);
+                    let b = U256Variable::allocate(composer, b);
+                    let result = less_than_gadget(composer, &a, &b);
+                    composer.constrain_to_constant(
+                        result.variable(),
+                        E::Fr::from(less as u64),
+                        None,
+                    );
+                },
+                1 << 7,
+            )
+        };
+        let a = random_u256();
+        let b = random_u256();
+        for (a, b) in [(&a, &b), (&b, &a), (&a, &a)] {
+            assert!(less_than(a, b, a < b).is_ok());
+            assert!(less_than(a, b, a >= b).is_err());
+        }
+    }
+
+    fn test_mul_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let a = random_u256();
+        let b = random_u256();
+        let product = &a * &b;
+        let mul = |low: &BigUint, high: &BigUint| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = U256Variable::allocate(composer, &a);
+                    let b = U256Variable::allocate(composer, &b);
+                    let (result_low, result_high) =
+                        mul_gadget(composer, &a, &b);
+                    let low = U256Variable::constant(composer, low);
+                    let high = U256Variable::constant(composer, high);
+                    bigint::assert_equal_gadget(
+                        composer,
+                        &result_low.int,
+                        &low.int,
+                    );
+                    bigint::assert_equal_gadget(
+                        composer,
+                        &result_high.int,
+                        &high.int,
+                    );
+                },
+                1 << 9,
+            )
+        };
+        let mask = (BigUint::one() << 256) - 1u64;
+        let (low, high) = (&product & &mask, &product >> 256);
+        assert!(mul(&low, &high).is_ok());
+        assert!(mul(&(&low ^ BigUint::one()), &high).is_err());
+        assert!(mul(&low, &(&high ^ BigUint::one())).is_err());
+    }
+
+    fn test_mul_mod_gadget<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let modulus = secp256k1_modulus();
+        // Neither of the factors is reduced
+        let a = &modulus + 5u64;
+        let b = random_u256() | (BigUint::one() << 255);
+        let mul_mod = |expected: &BigUint| {
+            gadget_tester(
+                |composer: &mut StandardComposer<E, P>| {
+                    let a = U256Variable::allocate(composer, &a);
+                    let b = U256Variable::allocate(composer, &b);
+                    let reduced = reduce_gadget(composer, &a, &modulus);
+                    let five = U256Variable::constant(composer, &5u64.into());
+                    bigint::assert_equal_gadget(
+                        composer,
+                        &reduced.int,
+                        &five.int,
+                    );
+                    let result = mul_mod_gadget(composer, &a, &b, &modulus);
+                    let expected = U256Variable::constant(composer, expected);
+                    bigint::assert_equal_gadget(
+                        composer,
+                        &result.int,
+                        &expected.int,
+                    );
+                },
+                1 << 11,
+            )
+        };
+        let product = &a * &b % &modulus;
+        assert!(mul_mod(&product).is_ok());
+        assert!(mul_mod(&(&product ^ BigUint::one())).is_err());
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [
+            test_add_sub_gadget,
+            test_less_than_gadget,
+            test_mul_gadget,
+            test_mul_mod_gadget
+        ],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [
+            test_add_sub_gadget,
+            test_less_than_gadget,
+            test_mul_gadget,
+            test_mul_mod_gadget
+        ],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [
+            test_add_sub_gadget,
+            test_less_than_gadget,
+            test_mul_gadget,
+            test_mul_mod_gadget
+        ],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,547 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Pluggable storage backends for caching keys and public parameters.
+//!
+//! Compiling a circuit and trimming the universal parameters are expensive
+//! operations, so services usually want to cache their results. The
+//! [`KeyStorage`] trait abstracts the place where these serialized blobs live
+//! and addresses them by a 32-byte digest. Two backends are provided out of
+//! the box: [`MemoryKeyStorage`] and [`FileKeyStorage`]. Any other backend
+//! (an object store, a database...) can be plugged in by implementing the
+//! trait.
+//!
+//! Backends aren't trusted: entries are checked when deserialized, verifier
+//! keys and universal parameters are checked against the digest they are
+//! stored under, and prover keys against their verifier key.
+//!
+//! [`Circuit::compile_with_storage`] and [`CompiledCircuit::from_storage`]
+//! use a backend to skip the compilation of circuits whose keys are stored.
+//!
+//! [`Circuit::compile_with_storage`]: crate::circuit::Circuit::compile_with_storage
+//! [`CompiledCircuit::from_storage`]: crate::circuit::CompiledCircuit::from_storage
+
+use crate::error::Error;
+use crate::proof_system::{ProverKey, VerifierKey};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use ark_poly_commit::kzg10::UniversalParams;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use core::sync::atomic::{AtomicU64, Ordering};
+use hashbrown::HashMap;
+use merlin::Transcript;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Digest used to address entries of a [`KeyStorage`].
+pub type KeyDigest = [u8; 32];
+
+/// Returns the digest the prover key generated together with the verifier
+/// key of digest `vk_digest` is stored under by
+/// [`KeyStorage::put_prover_key`].
+pub fn prover_key_digest(vk_digest: &KeyDigest) -> KeyDigest {
+    let mut transcript = Transcript::new(b"ark-plonk-prover-key");
+    transcript.append_message(b"vk_digest", vk_digest);
+    let mut digest = [0u8; 32];
+    transcript.challenge_bytes(b"digest", &mut digest);
+    digest
+}
+
+/// Returns the digest the public input positions and kinds of the circuit
+/// of the verifier key of digest `vk_digest` are stored under by
+/// [`Circuit::compile_with_storage`].
+///
+/// [`Circuit::compile_with_storage`]: crate::circuit::Circuit::compile_with_storage
+pub fn public_inputs_layout_digest(vk_digest: &KeyDigest) -> KeyDigest {
+    let mut transcript = Transcript::new(b"ark-plonk-public-inputs-layout");
+    transcript.append_message(b"vk_digest", vk_digest);
+    let mut digest = [0u8; 32];
+    transcript.challenge_bytes(b"digest", &mut digest);
+    digest
+}
+
+/// Returns a domain-separated 32-byte digest of `u_params`, which they are
+/// stored under by [`KeyStorage::put_universal_params`].
+pub fn universal_params_digest<E>(u_params: &UniversalParams<E>) -> KeyDigest
+where
+    E: PairingEngine,
+{
+    let mut bytes = Vec::with_capacity(u_params.serialized_size());
+    u_params
+        .serialize(&mut bytes)
+        .expect("serialization into a vector can't fail");
+    let mut transcript = Transcript::new(b"ark-plonk-universal-params");
+    transcript.append_message(b"params", &bytes);
+    let mut digest = [0u8; 32];
+    transcript.challenge_bytes(b"digest", &mut digest);
+    digest
+}
+
+/// Byte-level storage of keys and public parameters addressed by their
+/// digest.
+pub trait KeyStorage {
+    /// Returns the bytes stored under `digest` or `None` if there is no such
+    /// entry.
+    fn get(&self, digest: &KeyDigest) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Stores `bytes` under `digest`, replacing any previous entry.
+    fn put(&self, digest: &KeyDigest, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Returns the deserialized value stored under `digest` or `None` if
+    /// there is no such entry.
+    ///
+    /// The points of the value are checked to be on the curve and in its
+    /// prime order subgroup, since the backend may be shared with other,
+    /// less trusted, processes.
+    fn get_key<T>(&self, digest: &KeyDigest) -> Result<Option<T>, Error>
+    where
+        T: CanonicalDeserialize,
+    {
+        self.get(digest)?
+            .map(|bytes| T::deserialize(&bytes[..]))
+            .transpose()
+            .map_err(Error::from)
+    }
+
+    /// Serializes `key` and stores it under `digest`.
+    fn put_key<T>(&self, digest: &KeyDigest, key: &T) -> Result<(), Error>
+    where
+        T: CanonicalSerialize,
+    {
+        let mut bytes = Vec::with_capacity(key.serialized_size());
+        key.serialize(&mut bytes)?;
+        self.put(digest, &bytes)
+    }
+
+    /// Returns the verifier key stored under its `digest` by
+    /// [`KeyStorage::put_verifier_key`], or `None` if there is no such
+    /// entry.
+    ///
+    /// The key is read with [`VerifierKey::deserialize_checked`], and
+    /// [`Error::KeyDigestMismatch`] is returned if its
+    /// [`VerifierKey::digest`] isn't `digest`, so that a backend can't swap
+    /// the key of a circuit for another one.
+    fn get_verifier_key<E, P>(
+        &self,
+        digest: &KeyDigest,
+    ) -> Result<Option<VerifierKey<E, P>>, Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let key = match self.get(digest)? {
+            Some(bytes) => VerifierKey::deserialize_checked(&bytes[..])?,
+            None => return Ok(None),
+        };
+        if key.digest() != *digest {
+            return Err(Error::KeyDigestMismatch);
+        }
+        Ok(Some(key))
+    }
+
+    /// Stores `key` under its [`VerifierKey::digest`], which is returned.
+    fn put_verifier_key<E, P>(
+        &self,
+        key: &VerifierKey<E, P>,
+    ) -> Result<KeyDigest, Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let digest = key.digest();
+        self.put_key(&digest, key)?;
+        Ok(digest)
+    }
+
+    /// Returns the prover key generated together with `verifier_key`, stored
+    /// by [`KeyStorage::put_prover_key`], or `None` if there is no such
+    /// entry.
+    ///
+    /// [`Error::KeyDigestMismatch`] is returned if the stored key doesn't
+    /// [`ProverKey::matches`] `verifier_key`. The polynomials of the key
+    /// can't be checked against the commitments of `verifier_key` without
+    /// compiling the circuit again, but a prover key tampered with only
+    /// yields proofs that don't verify.
+    fn get_prover_key<E, P>(
+        &self,
+        verifier_key: &VerifierKey<E, P>,
+    ) -> Result<Option<ProverKey<E::Fr, P>>, Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let key: ProverKey<E::Fr, P> =
+            match self.get_key(&prover_key_digest(&verifier_key.digest()))? {
+                Some(key) => key,
+                None => return Ok(None),
+            };
+        if !key.matches(verifier_key) {
+            return Err(Error::KeyDigestMismatch);
+        }
+        Ok(Some(key))
+    }
+
+    /// Stores `key` under the [`prover_key_digest`] of the verifier key
+    /// generated together with it, which is returned.
+    fn put_prover_key<F, P>(
+        &self,
+        key: &ProverKey<F, P>,
+    ) -> Result<KeyDigest, Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let digest = prover_key_digest(&key.verifier_key_digest());
+        self.put_key(&digest, key)?;
+        Ok(digest)
+    }
+
+    /// Returns the universal parameters stored under their `digest` by
+    /// [`KeyStorage::put_universal_params`], or `None` if there is no such
+    /// entry.
+    ///
+    /// [`Error::KeyDigestMismatch`] is returned if the
+    /// [`universal_params_digest`] of the stored parameters isn't `digest`.
+    /// Parameters downloaded from an untrusted source should still be
+    /// checked with [`check_srs`] before their first use.
+    ///
+    /// [`check_srs`]: crate::srs::check_srs
+    fn get_universal_params<E>(
+        &self,
+        digest: &KeyDigest,
+    ) -> Result<Option<UniversalParams<E>>, Error>
+    where
+        E: PairingEngine,
+    {
+        let u_params: UniversalParams<E> = match self.get_key(digest)? {
+            Some(u_params) => u_params,
+            None => return Ok(None),
+        };
+        if universal_params_digest(&u_params) != *digest {
+            return Err(Error::KeyDigestMismatch);
+        }
+        Ok(Some(u_params))
+    }
+
+    /// Stores `u_params` under their [`universal_params_digest`], which is
+    /// returned.
+    fn put_universal_params<E>(
+        &self,
+        u_params: &UniversalParams<E>,
+    ) -> Result<KeyDigest, Error>
+    where
+        E: PairingEngine,
+    {
+        let digest = universal_params_digest(u_params);
+        self.put_key(&digest, u_params)?;
+        Ok(digest)
+    }
+}
+
+/// [`KeyStorage`] backed by an in-memory map.
+#[derive(Debug, Default)]
+pub struct MemoryKeyStorage {
+    entries: RwLock<HashMap<KeyDigest, Vec<u8>>>,
+}
+
+impl MemoryKeyStorage {
+    /// Creates an empty in-memory storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored entries.
+    pub fn len(&self) -> usize {
+        self.entries
+            .read()
+            .expect("poisoned key storage lock")
+            .len()
+    }
+
+    /// Returns `true` if there are no stored entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl KeyStorage for MemoryKeyStorage {
+    fn get(&self, digest: &KeyDigest) -> Result<Option<Vec<u8>>, Error> {
+        Ok(self
+            .entries
+            .read()
+            .expect("poisoned key storage lock")
+            .get(digest)
+            .cloned())
+    }
+
+    fn put(&self, digest: &KeyDigest, bytes: &[u8]) -> Result<(), Error> {
+        self.entries
+            .write()
+            .expect("poisoned key storage lock")
+            .insert(*digest, bytes.to_vec());
+        Ok(())
+    }
+}
+
+/// [`KeyStorage`] backed by a directory of the filesystem, storing each entry
+/// in a file named after the hex encoding of its digest.
+#[derive(Debug, Clone)]
+pub struct FileKeyStorage {
+    root: PathBuf,
+}
+
+impl FileKeyStorage {
+    /// Creates a storage rooted at `root`, creating the directory if it does
+    /// not exist yet.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Result<Self, Error> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Returns the directory the entries are stored in.
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+
+    fn path(&self, digest: &KeyDigest) -> PathBuf {
+        let name: String =
+            digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        self.root.join(name)
+    }
+}
+
+impl KeyStorage for FileKeyStorage {
+    fn get(&self, digest: &KeyDigest) -> Result<Option<Vec<u8>>, Error> {
+        match fs::read(self.path(digest)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn put(&self, digest: &KeyDigest, bytes: &[u8]) -> Result<(), Error> {
+        // Write to a temporary file first so that concurrent readers never
+        // observe a partially written entry. The name of the file is unique
+        // to the writer, so that concurrent writers don't clobber each
+        // other's file.
+        static NONCE: AtomicU64 = AtomicU64::new(0);
+        let path = self.path(digest);
+        let tmp = path.with_extension(format!(
+            "{}.{}.tmp",
+            std::process::id(),
+            NONCE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written =
+            fs::write(&tmp, bytes).and_then(|_| fs::rename(&tmp, &path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        Ok(written?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::proof_system::{Prover, ProverKey, Verifier, VerifierKey};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::{AffineCurve, PairingEngine, TEModelParameters};
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::{Powers, KZG10};
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use rand_core::OsRng;
+    use tempdir::TempDir;
+
+    fn check_storage<S: KeyStorage>(storage: &S) {
+        let digest = [7u8; 32];
+        assert!(storage.get(&digest).unwrap().is_none());
+
+        storage.put(&digest, b"first").unwrap();
+        assert_eq!(storage.get(&digest).unwrap().unwrap(), b"first");
+
+        storage.put(&digest, b"second").unwrap();
+        assert_eq!(storage.get(&digest).unwrap().unwrap(), b"second");
+        assert!(storage.get(&[0u8; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryKeyStorage::new();
+        check_storage(&storage);
+        assert_eq!(storage.len(), 1);
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let dir = TempDir::new("ark_plonk_key_storage").unwrap();
+        let storage = FileKeyStorage::new(dir.path().join("keys")).unwrap();
+        check_storage(&storage);
+
+        // A second handle on the same directory sees the stored entries.
+        let other = FileKeyStorage::new(storage.root().clone()).unwrap();
+        assert_eq!(other.get(&[7u8; 32]).unwrap().unwrap(), b"second");
+
+        // Concurrent writers of the same entry don't race on their
+        // temporary files
+        std::thread::scope(|scope| {
+            for i in 0..8u8 {
+                let storage = &storage;
+                scope.spawn(move || storage.put(&[9u8; 32], &[i; 64]).unwrap());
+            }
+        });
+        let bytes = storage.get(&[9u8; 32]).unwrap().unwrap();
+        assert!(bytes.iter().all(|byte| *byte == bytes[0]) && bytes[0] < 8);
+        assert_eq!(fs::read_dir(storage.root()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_checked_entries() {
+        let storage = MemoryKeyStorage::new();
+        let point =
+            <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator();
+        storage.put_key(&[1u8; 32], &point).unwrap();
+        assert_eq!(storage.get_key(&[1u8; 32]).unwrap(), Some(point));
+
+        // Points moved off the curve or out of the prime order subgroup are
+        // rejected
+        let mut bytes = storage.get(&[1u8; 32]).unwrap().unwrap();
+        bytes[0] ^= 1;
+        storage.put(&[1u8; 32], &bytes).unwrap();
+        assert!(storage
+            .get_key::<<Bls12_381 as PairingEngine>::G1Affine>(&[1u8; 32])
+            .is_err());
+    }
+
+    fn test_key_roundtrip<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut prover: Prover<E, P> = Prover::new(b"storage");
+        let mut verifier: Verifier<E, P> = Verifier::new(b"storage");
+        prover.mut_cs().add_dummy_constraints();
+        verifier.mut_cs().add_dummy_constraints();
+        let n = prover.circuit_size().next_power_of_two();
+
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, n, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        prover.preprocess(&powers).unwrap();
+        verifier.preprocess(&powers).unwrap();
+        let prover_key = prover.prover_key.unwrap();
+        let verifier_key = verifier.verifier_key.unwrap();
+
+        let storage = MemoryKeyStorage::new();
+        storage.put_key(&[1u8; 32], &prover_key).unwrap();
+        storage.put_key(&[2u8; 32], &verifier_key).unwrap();
+
+        let stored_prover_key: ProverKey<E::Fr, P> =
+            storage.get_key(&[1u8; 32]).unwrap().unwrap();
+        let stored_verifier_key: VerifierKey<E, P> =
+            storage.get_key(&[2u8; 32]).unwrap().unwrap();
+        assert_eq!(prover_key, stored_prover_key);
+        assert_eq!(verifier_key, stored_verifier_key);
+        assert!(storage
+            .get_key::<VerifierKey<E, P>>(&[3u8; 32])
+            .unwrap()
+            .is_none());
+
+        // Verifier keys are checked against the digest they are stored under
+        let digest = storage.put_verifier_key(&verifier_key).unwrap();
+        assert_eq!(digest, verifier_key.digest());
+        assert_eq!(
+            storage.get_verifier_key::<E, P>(&digest).unwrap().unwrap(),
+            verifier_key
+        );
+        let bytes = storage.get(&digest).unwrap().unwrap();
+        storage.put(&[4u8; 32], &bytes).unwrap();
+        assert!(matches!(
+            storage.get_verifier_key::<E, P>(&[4u8; 32]),
+            Err(Error::KeyDigestMismatch)
+        ));
+        assert!(storage
+            .get_verifier_key::<E, P>(&[3u8; 32])
+            .unwrap()
+            .is_none());
+
+        // Prover keys are stored under the digest of their verifier key and
+        // checked against it
+        assert!(storage.get_prover_key(&verifier_key).unwrap().is_none());
+        let digest = storage.put_prover_key(&prover_key).unwrap();
+        assert_eq!(digest, prover_key_digest(&verifier_key.digest()));
+        assert_eq!(
+            storage.get_prover_key(&verifier_key).unwrap().unwrap(),
+            prover_key
+        );
+        let mut other_key = prover_key.clone();
+        other_key.vk_digest.0 = [5u8; 32];
+        storage.put_key(&digest, &other_key).unwrap();
+        assert!(matches!(
+            storage.get_prover_key(&verifier_key),
+            Err(Error::KeyDigestMismatch)
+        ));
+
+        // Universal parameters are checked against the digest they are
+        // stored under
+        let digest = storage.put_universal_params(&pp).unwrap();
+        assert_eq!(digest, universal_params_digest(&pp));
+        let stored_pp: UniversalParams<E> =
+            storage.get_universal_params(&digest).unwrap().unwrap();
+        assert_eq!(stored_pp.powers_of_g, pp.powers_of_g);
+        assert_eq!(stored_pp.h, pp.h);
+        assert_eq!(stored_pp.beta_h, pp.beta_h);
+        let bytes = storage.get(&digest).unwrap().unwrap();
+        storage.put(&[6u8; 32], &bytes).unwrap();
+        assert!(matches!(
+            storage.get_universal_params::<E>(&[6u8; 32]),
+            Err(Error::KeyDigestMismatch)
+        ));
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_key_roundtrip],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_key_roundtrip],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_key_roundtrip],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -36,20 +36,41 @@
 #![deny(missing_docs)]
 
 extern crate alloc;
+// Lets the code generated by the derive macros refer to `ark_plonk` in tests
+#[cfg(feature = "derive")]
+extern crate self as ark_plonk;
 
 mod permutation;
 mod transcript;
 mod util;
 
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod artifact;
+#[cfg(feature = "bench")]
+pub mod bench;
 pub mod circuit;
+pub mod config;
 pub mod constraint_system;
 pub mod error;
+pub mod gadgets;
+pub mod key_storage;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod prelude;
+pub mod proof_cache;
 pub mod proof_system;
+pub mod srs;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 mod test;
 
+#[cfg(any(test, feature = "test-utils"))]
+#[doc(hidden)]
+pub use paste;
+
+pub use circuit::{prove, verify};
+
 #[doc = include_str!("../docs/notes-intro.md")]
 pub mod notes {
     #[doc = include_str!("../docs/notes-commitments.md")]
@@ -0,0 +1,353 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Loading of universal parameters and keys from memory-mapped files.
+//!
+//! Universal parameters supporting large circuits take gigabytes, and reading
+//! and deserializing all of them up front slows down startup and may not even
+//! fit in memory. An [`MmapUniversalParams`] maps the file instead and only
+//! deserializes the powers a circuit needs when trimming them to a commit
+//! key, leaving it to the operating system to page the rest in and out.
+//!
+//! Files are read in the unchecked encoding, as written by
+//! `serialize_unchecked`: points are stored uncompressed, so that any of them
+//! can be located without reading the previous ones, and they aren't checked
+//! when read. Parameters should thus
+//! come from a trusted source, or be checked once with
+//! [`check_srs`](crate::srs::check_srs).
+
+use crate::error::Error;
+use crate::proof_system::ProverKey;
+use alloc::collections::BTreeMap;
+use ark_ec::{AffineCurve, PairingEngine, TEModelParameters};
+use ark_ff::PrimeField;
+use ark_poly_commit::kzg10::{self, Powers, UniversalParams};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, SerializationError,
+};
+use core::convert::TryFrom;
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+/// Size of the serialized length of a collection, and of a `usize`.
+const LEN_SIZE: usize = 8;
+
+/// [`UniversalParams`] stored in a memory-mapped file, deserialized lazily.
+pub struct MmapUniversalParams<E>
+where
+    E: PairingEngine,
+{
+    /// Mapped file
+    map: Mmap,
+
+    /// Number of powers of `g`
+    num_powers: usize,
+
+    /// Offset of the powers of `gamma_g` in the file
+    gamma_offset: usize,
+
+    /// Number of powers of `gamma_g`
+    num_gamma_powers: usize,
+
+    /// Generator of G2
+    h: E::G2Affine,
+
+    /// Secret times `h`
+    beta_h: E::G2Affine,
+}
+
+impl<E> MmapUniversalParams<E>
+where
+    E: PairingEngine,
+{
+    /// Maps the universal parameters serialized in the file at `path`.
+    ///
+    /// Only the layout of the file and the G2 points are read at this point.
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only. Like any file based storage, it
+        // relies on the file not being modified while it is in use.
+        let map = unsafe { Mmap::map(&file)? };
+
+        let g1_size = g1_size::<E>();
+        let num_powers = read_len(&map, 0)?;
+        let gamma_offset = num_powers
+            .checked_mul(g1_size)
+            .and_then(|size| size.checked_add(LEN_SIZE))
+            .ok_or(SerializationError::InvalidData)?;
+        let num_gamma_powers = read_len(&map, gamma_offset)?;
+        let tail_offset = num_gamma_powers
+            .checked_mul(LEN_SIZE + g1_size)
+            .and_then(|size| size.checked_add(gamma_offset + LEN_SIZE))
+            .ok_or(SerializationError::InvalidData)?;
+
+        let mut tail = bytes_from(&map, tail_offset)?;
+        let h = E::G2Affine::deserialize_unchecked(&mut tail)?;
+        let beta_h = E::G2Affine::deserialize_unchecked(&mut tail)?;
+        Ok(Self {
+            map,
+            num_powers,
+            gamma_offset,
+            num_gamma_powers,
+            h,
+            beta_h,
+        })
+    }
+
+    /// Returns the number of powers of `g`, one more than the maximum degree
+    /// of the parameters.
+    pub fn num_powers(&self) -> usize {
+        self.num_powers
+    }
+
+    /// Deserializes the commit key for a circuit of `circuit_size` gates, as
+    /// trimmed from the whole parameters.
+    pub fn commit_key(
+        &self,
+        circuit_size: usize,
+    ) -> Result<Powers<'static, E>, Error> {
+        let required_powers = circuit_size + 1;
+        if required_powers > self.num_powers {
+            return Err(Error::UniversalParamsTooSmall {
+                circuit_size,
+                required_powers,
+                available_powers: self.num_powers,
+            });
+        }
+        let mut reader = bytes_from(&self.map, LEN_SIZE)?;
+        let powers_of_g = (0..required_powers)
+            .map(|_| E::G1Affine::deserialize_unchecked(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let powers_of_gamma_g =
+            vec![self.gamma_power(0)?, self.gamma_power(1)?];
+        Ok(Powers {
+            powers_of_g: powers_of_g.into(),
+            powers_of_gamma_g: powers_of_gamma_g.into(),
+        })
+    }
+
+    /// Deserializes the commitment scheme verifier key.
+    pub fn verifier_key(&self) -> Result<kzg10::VerifierKey<E>, Error> {
+        let g = E::G1Affine::deserialize_unchecked(bytes_from(
+            &self.map, LEN_SIZE,
+        )?)?;
+        Ok(kzg10::VerifierKey {
+            g,
+            gamma_g: self.gamma_power(0)?,
+            h: self.h,
+            beta_h: self.beta_h,
+            prepared_h: self.h.into(),
+            prepared_beta_h: self.beta_h.into(),
+        })
+    }
+
+    /// Deserializes the whole parameters.
+    pub fn universal_params(&self) -> Result<UniversalParams<E>, Error> {
+        let mut reader = &self.map[..];
+        let powers_of_g = Vec::deserialize_unchecked(&mut reader)?;
+        let powers_of_gamma_g = BTreeMap::deserialize_unchecked(&mut reader)?;
+        let h = E::G2Affine::deserialize_unchecked(&mut reader)?;
+        let beta_h = E::G2Affine::deserialize_unchecked(&mut reader)?;
+        let neg_powers_of_h = BTreeMap::deserialize_unchecked(&mut reader)?;
+        Ok(UniversalParams {
+            powers_of_g,
+            powers_of_gamma_g,
+            h,
+            beta_h,
+            neg_powers_of_h,
+            prepared_h: h.into(),
+            prepared_beta_h: beta_h.into(),
+        })
+    }
+
+    /// Deserializes the `i`-th power of `gamma_g`.
+    fn gamma_power(&self, i: usize) -> Result<E::G1Affine, Error> {
+        if i >= self.num_gamma_powers {
+            return Err(SerializationError::InvalidData.into());
+        }
+        // Entries are sorted by power, so that the `i`-th one is `i`
+        let offset =
+            self.gamma_offset + LEN_SIZE + i * (LEN_SIZE + g1_size::<E>());
+        let mut reader = bytes_from(&self.map, offset)?;
+        if usize::deserialize_unchecked(&mut reader)? != i {
+            return Err(SerializationError::InvalidData.into());
+        }
+        Ok(E::G1Affine::deserialize_unchecked(&mut reader)?)
+    }
+}
+
+/// Deserializes the [`ProverKey`] stored in the file at `path`, reading it
+/// through a memory map rather than into an intermediate buffer.
+pub fn load_prover_key<F, P, Q>(path: Q) -> Result<ProverKey<F, P>, Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    Q: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    // SAFETY: see `MmapUniversalParams::open`.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(ProverKey::deserialize_unchecked(&map[..])?)
+}
+
+/// Returns the size of a serialized G1 point.
+fn g1_size<E>() -> usize
+where
+    E: PairingEngine,
+{
+    E::G1Affine::prime_subgroup_generator().uncompressed_size()
+}
+
+/// Returns the bytes of `map` from `offset` on.
+fn bytes_from(map: &Mmap, offset: usize) -> Result<&[u8], Error> {
+    map.get(offset..)
+        .ok_or_else(|| SerializationError::InvalidData.into())
+}
+
+/// Reads the length of a collection at `offset`.
+fn read_len(map: &Mmap, offset: usize) -> Result<usize, Error> {
+    let len = u64::deserialize_unchecked(bytes_from(map, offset)?)?;
+    usize::try_from(len).map_err(|_| SerializationError::InvalidData.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::{trim_commit_key, trim_verifier_key};
+    use crate::proof_system::Prover;
+    use crate::{batch_test, batch_test_engine};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use rand_core::OsRng;
+    use std::fs;
+    use tempdir::TempDir;
+
+    fn test_mmap_universal_params<E>()
+    where
+        E: PairingEngine,
+    {
+        let dir = TempDir::new("ark_plonk_mmap").unwrap();
+        let path = dir.path().join("srs");
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(1 << 5, true, &mut OsRng)
+                .unwrap();
+        let mut bytes = Vec::new();
+        pp.powers_of_g.serialize_unchecked(&mut bytes).unwrap();
+        pp.powers_of_gamma_g
+            .serialize_unchecked(&mut bytes)
+            .unwrap();
+        pp.h.serialize_unchecked(&mut bytes).unwrap();
+        pp.beta_h.serialize_unchecked(&mut bytes).unwrap();
+        pp.neg_powers_of_h.serialize_unchecked(&mut bytes).unwrap();
+        fs::write(&path, &bytes).unwrap();
+
+        let mapped = MmapUniversalParams::<E>::open(&path).unwrap();
+        assert_eq!(mapped.num_powers(), pp.powers_of_g.len());
+
+        let commit_key = mapped.commit_key(1 << 4).unwrap();
+        let expected = trim_commit_key(&pp, 1 << 4).unwrap();
+        assert_eq!(commit_key.powers_of_g, expected.powers_of_g);
+        assert_eq!(commit_key.powers_of_gamma_g, expected.powers_of_gamma_g);
+        assert!(matches!(
+            mapped.commit_key(1 << 6),
+            Err(Error::UniversalParamsTooSmall { .. })
+        ));
+
+        let verifier_key = mapped.verifier_key().unwrap();
+        let expected = trim_verifier_key(&pp, 1 << 4).unwrap();
+        assert_eq!(verifier_key.g, expected.g);
+        assert_eq!(verifier_key.gamma_g, expected.gamma_g);
+        assert_eq!(verifier_key.h, expected.h);
+        assert_eq!(verifier_key.beta_h, expected.beta_h);
+
+        let loaded = mapped.universal_params().unwrap();
+        assert_eq!(loaded.powers_of_g, pp.powers_of_g);
+        assert_eq!(loaded.neg_powers_of_h, pp.neg_powers_of_h);
+
+        // Truncated files are rejected
+        fs::write(&path, &bytes[..LEN_SIZE + 2 * g1_size::<E>()]).unwrap();
+        assert!(matches!(
+            MmapUniversalParams::<E>::open(&path),
+            Err(Error::SerializationError { .. })
+        ));
+    }
+
+    fn test_load_prover_key<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut prover: Prover<E, P> = Prover::new(b"mmap");
+        prover.mut_cs().add_dummy_constraints();
+        let n = prover.circuit_size().next_power_of_two();
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        prover
+            .preprocess(&trim_commit_key(&pp, n).unwrap())
+            .unwrap();
+        let prover_key = prover.prover_key.unwrap();
+
+        let dir = TempDir::new("ark_plonk_mmap").unwrap();
+        let path = dir.path().join("prover_key");
+        let mut bytes = Vec::new();
+        prover_key.serialize_unchecked(&mut bytes).unwrap();
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(load_prover_key::<E::Fr, P, _>(&path).unwrap(), prover_key);
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_load_prover_key],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+    batch_test_engine!(
+        [test_mmap_universal_params],
+        []
+        => Bls12_381
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_load_prover_key],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+    batch_test_engine!(
+        [test_mmap_universal_params],
+        []
+        => Bls12_377
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_load_prover_key],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+    batch_test_engine!(
+        [test_mmap_universal_params],
+        []
+        => Bn254
+    );
+}
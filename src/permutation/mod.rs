@@ -9,7 +9,8 @@
 pub(crate) mod constants;
 
 use crate::constraint_system::{Variable, WireData};
-use ark_ff::PrimeField;
+use crate::proof_system::FftBackend;
+use ark_ff::{batch_inversion, PrimeField};
 use ark_poly::domain::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_poly::{univariate::DensePolynomial, UVPolynomial};
 use constants::*;
@@ -66,6 +67,30 @@ where
         var
     }
 
+    /// Reserves capacity for at least `additional` more [`Variable`]s.
+    pub fn reserve(&mut self, additional: usize) {
+        self.variable_map.reserve(additional);
+    }
+
+    /// Removes the [`Variable`]s whose index is at least `variables` and the
+    /// wires of the gates whose index is at least `gates`.
+    pub fn truncate(&mut self, variables: usize, gates: usize) {
+        self.variable_map.retain(|var, _| var.0 < variables);
+        self.variable_map.values_mut().for_each(|wires| {
+            wires.retain(|wire| match wire {
+                WireData::Left(index)
+                | WireData::Right(index)
+                | WireData::Output(index)
+                | WireData::Fourth(index) => *index < gates,
+            })
+        });
+    }
+
+    /// Removes the wires of every [`Variable`], keeping the variables.
+    pub fn clear_wires(&mut self) {
+        self.variable_map.values_mut().for_each(Vec::clear);
+    }
+
     /// Checks that the [`Variable`]s are valid by determining if they have been
     /// added to the system.
     fn valid_variables(&self, variables: &[Variable]) -> bool {
@@ -183,6 +208,7 @@ where
         &mut self,
         n: usize,
         domain: &GeneralEvaluationDomain<F>,
+        fft: &dyn FftBackend<F>,
     ) -> (
         DensePolynomial<F>,
         DensePolynomial<F>,
@@ -204,14 +230,18 @@ where
         let fourth_sigma =
             self.compute_permutation_lagrange(&sigmas[3], domain);
 
-        let left_sigma_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&left_sigma));
-        let right_sigma_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&right_sigma));
-        let out_sigma_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&out_sigma));
-        let fourth_sigma_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&fourth_sigma));
+        let left_sigma_poly = DensePolynomial::from_coefficients_vec(
+            fft.ifft(domain, &left_sigma),
+        );
+        let right_sigma_poly = DensePolynomial::from_coefficients_vec(
+            fft.ifft(domain, &right_sigma),
+        );
+        let out_sigma_poly = DensePolynomial::from_coefficients_vec(
+            fft.ifft(domain, &out_sigma),
+        );
+        let fourth_sigma_poly = DensePolynomial::from_coefficients_vec(
+            fft.ifft(domain, &fourth_sigma),
+        );
 
         (
             left_sigma_poly,
@@ -655,6 +685,7 @@ where
     pub fn compute_permutation_poly(
         &self,
         domain: &GeneralEvaluationDomain<F>,
+        fft: &dyn FftBackend<F>,
         wires: (&[F], &[F], &[F], &[F]),
         beta: F,
         gamma: F,
@@ -671,10 +702,10 @@ where
         let ks = vec![F::one(), K1::<F>(), K2::<F>(), K3::<F>()];
 
         let sigma_mappings = (
-            domain.fft(sigma_polys.0),
-            domain.fft(sigma_polys.1),
-            domain.fft(sigma_polys.2),
-            domain.fft(sigma_polys.3),
+            fft.fft(domain, sigma_polys.0),
+            fft.fft(domain, sigma_polys.1),
+            fft.fft(domain, sigma_polys.2),
+            fft.fft(domain, sigma_polys.3),
         );
 
         // Transpose wires and sigma values to get "rows" in the form [wl_i,
@@ -694,44 +725,51 @@ where
         // Non-parallelizable?
         let roots: Vec<F> = domain.elements().collect();
 
-        let product_argument = izip!(roots, gatewise_sigmas, gatewise_wires)
-            // Associate each wire value in a gate with the k defining its coset
-            .map(|(gate_root, gate_sigmas, gate_wires)| {
-                (gate_root, izip!(gate_sigmas, gate_wires, &ks))
-            })
-            // Now the ith element represents gate i and will have the form:
-            //   (root_i, ((w0_i, s0_i, k0), (w1_i, s1_i, k1), ..., (wm_i, sm_i,
-            // km)))   for m different wires, which is all the
-            // information   needed for a single product coefficient
-            // for a single gate Multiply up the numerator and
-            // denominator irreducibles for each gate   and pair the
-            // results
-            .map(|(gate_root, wire_params)| {
-                (
-                    // Numerator product
-                    wire_params
-                        .clone()
-                        .map(|(_sigma, wire, k)| {
-                            Permutation::numerator_irreducible(
-                                gate_root, *wire, *k, beta, gamma,
-                            )
-                        })
-                        .product::<F>(),
-                    // Denominator product
-                    wire_params
-                        .map(|(sigma, wire, _k)| {
-                            Permutation::denominator_irreducible(
-                                gate_root, *wire, sigma, beta, gamma,
-                            )
-                        })
-                        .product::<F>(),
-                )
-            })
-            // Divide each pair to get the single scalar representing each gate
-            .map(|(n, d)| n * d.inverse().unwrap())
-            // Collect into vector intermediary since rayon does not support
-            // `scan`
-            .collect::<Vec<F>>();
+        let (numerators, mut denominators): (Vec<F>, Vec<F>) =
+            izip!(roots, gatewise_sigmas, gatewise_wires)
+                // Associate each wire value in a gate with the k defining its
+                // coset
+                .map(|(gate_root, gate_sigmas, gate_wires)| {
+                    (gate_root, izip!(gate_sigmas, gate_wires, &ks))
+                })
+                // Now the ith element represents gate i and will have the form:
+                //   (root_i, ((w0_i, s0_i, k0), (w1_i, s1_i, k1), ..., (wm_i,
+                // sm_i, km)))   for m different wires, which is
+                // all the information   needed for a single
+                // product coefficient for a single gate
+                // Multiply up the numerator and denominator
+                // irreducibles for each gate   and pair the
+                // results
+                .map(|(gate_root, wire_params)| {
+                    (
+                        // Numerator product
+                        wire_params
+                            .clone()
+                            .map(|(_sigma, wire, k)| {
+                                Permutation::numerator_irreducible(
+                                    gate_root, *wire, *k, beta, gamma,
+                                )
+                            })
+                            .product::<F>(),
+                        // Denominator product
+                        wire_params
+                            .map(|(sigma, wire, _k)| {
+                                Permutation::denominator_irreducible(
+                                    gate_root, *wire, sigma, beta, gamma,
+                                )
+                            })
+                            .product::<F>(),
+                    )
+                })
+                .unzip();
+
+        // Divide each pair to get the single scalar representing each gate,
+        // inverting all of the denominators at once
+        batch_inversion(&mut denominators);
+        let product_argument = numerators
+            .into_iter()
+            .zip(denominators)
+            .map(|(n, d_inv)| n * d_inv);
 
         let mut z = Vec::with_capacity(n);
 
@@ -751,7 +789,7 @@ where
 
         assert_eq!(n, z.len());
 
-        DensePolynomial::<F>::from_coefficients_vec(domain.ifft(&z))
+        DensePolynomial::<F>::from_coefficients_vec(fft.ifft(domain, &z))
     }
 }
 
@@ -766,11 +804,13 @@ pub(crate) fn random_scalar<F: PrimeField, R: RngCore>(rng: &mut R) -> F {
 mod test {
     use super::*;
     use crate::batch_test;
+    use crate::proof_system::CpuBackend;
     use crate::{
         constraint_system::StandardComposer, util::EvaluationDomainExt,
     };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_ff::Field;
     use ark_ff::UniformRand;
     use ark_poly::univariate::DensePolynomial;
@@ -812,13 +852,13 @@ mod test {
             GeneralEvaluationDomain::<E::Fr>::new(cs.circuit_size()).unwrap();
         let pad = vec![E::Fr::zero(); domain.size() - cs.w_l.len()];
         let mut w_l_scalar: Vec<E::Fr> =
-            cs.w_l.iter().map(|v| cs.variables[v]).collect();
+            cs.w_l.iter().map(|v| cs.variables[v.0]).collect();
         let mut w_r_scalar: Vec<E::Fr> =
-            cs.w_r.iter().map(|v| cs.variables[v]).collect();
+            cs.w_r.iter().map(|v| cs.variables[v.0]).collect();
         let mut w_o_scalar: Vec<E::Fr> =
-            cs.w_o.iter().map(|v| cs.variables[v]).collect();
+            cs.w_o.iter().map(|v| cs.variables[v.0]).collect();
         let mut w_4_scalar: Vec<E::Fr> =
-            cs.w_4.iter().map(|v| cs.variables[v]).collect();
+            cs.w_4.iter().map(|v| cs.variables[v.0]).collect();
 
         w_l_scalar.extend(&pad);
         w_r_scalar.extend(&pad);
@@ -842,6 +882,7 @@ mod test {
 
         let mz = cs.perm.compute_permutation_poly(
             &domain,
+            &CpuBackend,
             (&w_l_scalar, &w_r_scalar, &w_o_scalar, &w_4_scalar),
             beta,
             gamma,
@@ -981,8 +1022,8 @@ mod test {
         let domain =
             GeneralEvaluationDomain::<E::Fr>::new(num_wire_mappings).unwrap();
         let w = domain.group_gen();
-        let w_squared = w.pow(&[2, 0, 0, 0]);
-        let w_cubed = w.pow(&[3, 0, 0, 0]);
+        let w_squared = w.pow([2]);
+        let w_cubed = w.pow([3]);
 
         // Check the left sigmas have been encoded properly
         // Left_sigma = {R0, L2, L3, L0}
@@ -1120,8 +1161,8 @@ mod test {
         let domain =
             GeneralEvaluationDomain::<E::Fr>::new(num_wire_mappings).unwrap();
         let w = domain.group_gen();
-        let w_squared = w.pow(&[2, 0, 0, 0]);
-        let w_cubed = w.pow(&[3, 0, 0, 0]);
+        let w_squared = w.pow([2]);
+        let w_cubed = w.pow([3]);
         // check the left sigmas have been encoded properly
         let encoded_left_sigma =
             perm.compute_permutation_lagrange(left_sigma, &domain);
@@ -1211,14 +1252,14 @@ mod test {
         let gamma = F::rand(&mut OsRng);
         assert_ne!(gamma, beta);
 
-        //1. Compute the permutation polynomial using both methods
+        // 1. Compute the permutation polynomial using both methods
         //
         let (
             left_sigma_poly,
             right_sigma_poly,
             out_sigma_poly,
             fourth_sigma_poly,
-        ) = perm.compute_sigma_polynomials(n, domain);
+        ) = perm.compute_sigma_polynomials(n, domain, &CpuBackend);
         let (z_vec, numerator_components, denominator_components) = perm
             .compute_slow_permutation_poly(
                 domain,
@@ -1272,7 +1313,7 @@ mod test {
         }
         assert_eq!(a_0 * b_0.inverse().unwrap(), F::one());
 
-        //3. Now we perform the two checks that need to be done on the
+        // 3. Now we perform the two checks that need to be done on the
         // permutation polynomial (z)
         let z_poly =
             DensePolynomial::<F>::from_coefficients_vec(domain.ifft(&z_vec));
@@ -1364,4 +1405,19 @@ mod test {
         ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Test on Bn254
+    batch_test!(
+        [test_multizip_permutation_poly,
+        test_permutation_format,
+        test_permutation_compute_sigmas_only_left_wires,
+        test_permutation_compute_sigmas,
+        test_basic_slow_permutation_poly
+        ],
+        []
+        => (
+        Bn254,
+        ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
@@ -10,9 +10,23 @@
 //! data structures of the plonk library.
 
 pub use crate::{
-    circuit::{self, Circuit, PublicInputValue, VerifierData},
-    constraint_system::{ecc::Point, StandardComposer, Variable},
+    circuit::{
+        self, prove, verify, verify_prepared_proof, verify_proof, Circuit,
+        CircuitInputs, CompiledCircuit, FeIntoPubInput, GeIntoPubInput,
+        PreparedVerifierKey, PublicInputBuilder, PublicInputKind,
+        PublicInputValue, PublicInputs, VerifierData, WitnessCommitment,
+    },
+    config::{ProverConfig, VerifierConfig},
+    constraint_system::{
+        ecc::Point, BoolVariable, Checkpoint, CircuitStats, GmimcParameters,
+        MimcParameters, PoseidonParameters, PoseidonSponge, RescueParameters,
+        StandardComposer, Variable, WidePoseidonParameters, WordVariable,
+    },
     error::Error,
-    proof_system::{Proof, VerifierKey},
+    proof_system::{PointEncoding, Proof, ProofVersion, VerifierKey},
     proof_system::{Prover, ProverKey, Verifier},
+    util::from_embedded_curve_scalar,
 };
+
+#[cfg(feature = "derive")]
+pub use crate::circuit::PlonkCircuit;
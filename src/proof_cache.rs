@@ -0,0 +1,295 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Cache of generated proofs, for services that receive the same proving
+//! request several times (e.g. idempotent retries).
+//!
+//! A [`ProofCache`] stores proofs in any [`KeyStorage`] backend, addressed by
+//! the digest of a [`ProofCacheKey`]. The key covers everything a proof
+//! depends on: the circuit identifier, the verifier key, the transcript
+//! label, the public inputs and the witness. It also covers the version of
+//! this crate. Entries generated with other keys or by other versions of the
+//! crate are therefore never returned, without having to purge the storage.
+
+use crate::circuit::PublicInputs;
+use crate::error::Error;
+use crate::key_storage::{KeyDigest, KeyStorage};
+use crate::proof_system::{Proof, VerifierKey};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use merlin::Transcript;
+
+/// Identifies a proving request in a [`ProofCache`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ProofCacheKey {
+    /// Identifier of the circuit
+    circuit_id: [u8; 32],
+
+    /// Digest of the verifier key of the circuit
+    vk_digest: [u8; 32],
+
+    /// Label the proof transcript is initialized with
+    transcript_init: Vec<u8>,
+
+    /// Digest of the public inputs
+    pi_digest: [u8; 32],
+
+    /// Digest of the witness
+    witness_digest: [u8; 32],
+}
+
+impl ProofCacheKey {
+    /// Creates the key of a request to prove the circuit `circuit_id`,
+    /// whose verifier key is `verifier_key`, for the given `public_inputs`.
+    ///
+    /// The `witness_digest` is usually computed with
+    /// [`StandardComposer::witness_digest`] after running the gadget of the
+    /// circuit.
+    ///
+    /// [`StandardComposer::witness_digest`]:
+    /// crate::constraint_system::StandardComposer::witness_digest
+    pub fn new<E, P>(
+        circuit_id: [u8; 32],
+        verifier_key: &VerifierKey<E, P>,
+        transcript_init: &[u8],
+        public_inputs: &PublicInputs<E>,
+        witness_digest: [u8; 32],
+    ) -> Self
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        Self {
+            circuit_id,
+            vk_digest: verifier_key.digest(),
+            transcript_init: transcript_init.to_vec(),
+            pi_digest: public_inputs.digest(),
+            witness_digest,
+        }
+    }
+
+    /// Returns the digest the proof is stored under, which also binds the
+    /// version of this crate.
+    pub fn digest(&self) -> KeyDigest {
+        let mut transcript = Transcript::new(b"ark-plonk-proof-cache");
+        transcript.append_message(
+            b"crate_version",
+            env!("CARGO_PKG_VERSION").as_bytes(),
+        );
+        transcript.append_message(b"circuit_id", &self.circuit_id);
+        transcript.append_message(b"vk_digest", &self.vk_digest);
+        transcript.append_message(b"transcript_init", &self.transcript_init);
+        transcript.append_message(b"pi_digest", &self.pi_digest);
+        transcript.append_message(b"witness_digest", &self.witness_digest);
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"digest", &mut digest);
+        digest
+    }
+}
+
+/// Cache of proofs backed by a [`KeyStorage`].
+#[derive(Debug, Default)]
+pub struct ProofCache<S>
+where
+    S: KeyStorage,
+{
+    storage: S,
+}
+
+impl<S> ProofCache<S>
+where
+    S: KeyStorage,
+{
+    /// Creates a cache storing its proofs in `storage`.
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns the underlying storage.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Returns the cached proof for `key` or `None` if there is no such
+    /// entry.
+    pub fn get<E, P>(
+        &self,
+        key: &ProofCacheKey,
+    ) -> Result<Option<Proof<E, P>>, Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        // Entries are fully checked since the storage may be shared with
+        // other, less trusted, processes.
+        self.storage
+            .get(&key.digest())?
+            .map(|bytes| Proof::deserialize(&bytes[..]))
+            .transpose()
+            .map_err(Error::from)
+    }
+
+    /// Caches `proof` under `key`.
+    pub fn put<E, P>(
+        &self,
+        key: &ProofCacheKey,
+        proof: &Proof<E, P>,
+    ) -> Result<(), Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut bytes = Vec::with_capacity(proof.serialized_size());
+        proof.serialize(&mut bytes)?;
+        self.storage.put(&key.digest(), &bytes)
+    }
+
+    /// Returns the cached proof for `key`, calling `prove` and caching its
+    /// result on a miss.
+    pub fn get_or_prove<E, P, F>(
+        &self,
+        key: &ProofCacheKey,
+        prove: F,
+    ) -> Result<Proof<E, P>, Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        F: FnOnce() -> Result<Proof<E, P>, Error>,
+    {
+        if let Some(proof) = self.get(key)? {
+            return Ok(proof);
+        }
+        let proof = prove()?;
+        self.put(key, &proof)?;
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::key_storage::MemoryKeyStorage;
+    use crate::proof_system::{Prover, Verifier};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::{Powers, KZG10};
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use core::cell::Cell;
+    use rand_core::OsRng;
+
+    fn test_proof_cache<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut prover: Prover<E, P> = Prover::new(b"cache");
+        let mut verifier: Verifier<E, P> = Verifier::new(b"cache");
+        prover.mut_cs().add_dummy_constraints();
+        verifier.mut_cs().add_dummy_constraints();
+        let n = prover.circuit_size().next_power_of_two();
+
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, n, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        verifier.preprocess(&powers).unwrap();
+        let verifier_key = verifier.verifier_key.unwrap();
+
+        let witness_digest = prover.mut_cs().witness_digest();
+        let public_inputs = PublicInputs::new();
+        let key = ProofCacheKey::new(
+            [1u8; 32],
+            &verifier_key,
+            b"cache",
+            &public_inputs,
+            witness_digest,
+        );
+
+        // Identical requests are only proved once
+        let cache = ProofCache::new(MemoryKeyStorage::new());
+        let proofs = Cell::new(0);
+        let prove = || {
+            proofs.set(proofs.get() + 1);
+            prover.prove(&powers)
+        };
+        let proof = cache.get_or_prove(&key, prove).unwrap();
+        let cached = cache
+            .get_or_prove(&key, || -> Result<Proof<E, P>, Error> {
+                unreachable!("the proof is cached")
+            })
+            .unwrap();
+        assert_eq!(proof, cached);
+        assert_eq!(proofs.get(), 1);
+        assert_eq!(cache.storage().len(), 1);
+
+        // Any change to the request misses the cache
+        let other_witness = ProofCacheKey::new(
+            [1u8; 32],
+            &verifier_key,
+            b"cache",
+            &public_inputs,
+            [0u8; 32],
+        );
+        let other_circuit = ProofCacheKey::new(
+            [2u8; 32],
+            &verifier_key,
+            b"cache",
+            &public_inputs,
+            witness_digest,
+        );
+        let other_label = ProofCacheKey::new(
+            [1u8; 32],
+            &verifier_key,
+            b"other",
+            &public_inputs,
+            witness_digest,
+        );
+        for other in [other_witness, other_circuit, other_label] {
+            assert_ne!(other.digest(), key.digest());
+            assert!(cache.get::<E, P>(&other).unwrap().is_none());
+        }
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_proof_cache],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_proof_cache],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_proof_cache],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
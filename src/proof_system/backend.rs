@@ -0,0 +1,423 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Pluggable implementations of the heavy arithmetic of the prover.
+//!
+//! Preprocessing and proving spend most of their time in multi-scalar
+//! multiplications, to commit to polynomials, and in FFTs over the circuit
+//! domain and its cosets. Both go through an [`MsmBackend`] and an
+//! [`FftBackend`], bundled in a [`Backend`], so that they can be offloaded to
+//! a GPU or other hardware without modifying the prover. [`CpuBackend`] is
+//! the default implementation, running on the CPU with arkworks.
+
+use crate::error::Error;
+use alloc::sync::Arc;
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{FftField, PrimeField};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_poly_commit::kzg10::{Commitment, Powers};
+use num_traits::Zero;
+
+/// Multi-scalar multiplication over the first group of a pairing engine.
+pub trait MsmBackend<E>: Send + Sync
+where
+    E: PairingEngine,
+{
+    /// Returns the sum of `bases` multiplied with `scalars`, which have the
+    /// same length.
+    fn msm(&self, bases: &[E::G1Affine], scalars: &[E::Fr]) -> E::G1Projective;
+}
+
+/// FFTs over the evaluation domains of a field.
+pub trait FftBackend<F>: Send + Sync
+where
+    F: FftField,
+{
+    /// Evaluates the polynomial of coefficients `coeffs` over `domain`.
+    fn fft(&self, domain: &GeneralEvaluationDomain<F>, coeffs: &[F]) -> Vec<F>;
+
+    /// Interpolates the coefficients of the polynomial of evaluations
+    /// `evals` over `domain`.
+    fn ifft(&self, domain: &GeneralEvaluationDomain<F>, evals: &[F]) -> Vec<F>;
+
+    /// Evaluates the polynomial of coefficients `coeffs` over the coset of
+    /// `domain` by the multiplicative generator of the field.
+    fn coset_fft(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        coeffs: &[F],
+    ) -> Vec<F>;
+
+    /// Interpolates the coefficients of the polynomial of evaluations
+    /// `evals` over the coset of `domain` by the multiplicative generator of
+    /// the field.
+    fn coset_ifft(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        evals: &[F],
+    ) -> Vec<F>;
+
+    /// Same as [`FftBackend::coset_fft`], overwriting `coeffs` with the
+    /// evaluations so that its allocation can be reused.
+    fn coset_fft_in_place(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        coeffs: &mut Vec<F>,
+    ) {
+        *coeffs = self.coset_fft(domain, coeffs);
+    }
+
+    /// Same as [`FftBackend::coset_ifft`], overwriting `evals` with the
+    /// coefficients so that its allocation can be reused.
+    fn coset_ifft_in_place(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        evals: &mut Vec<F>,
+    ) {
+        *evals = self.coset_ifft(domain, evals);
+    }
+}
+
+/// Backend running on the CPU with the arkworks implementations, in
+/// parallel with the `parallel` feature.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CpuBackend;
+
+impl<E> MsmBackend<E> for CpuBackend
+where
+    E: PairingEngine,
+{
+    fn msm(&self, bases: &[E::G1Affine], scalars: &[E::Fr]) -> E::G1Projective {
+        let scalars: Vec<_> = scalars.iter().map(E::Fr::into_repr).collect();
+        VariableBaseMSM::multi_scalar_mul(bases, &scalars)
+    }
+}
+
+impl<F> FftBackend<F> for CpuBackend
+where
+    F: FftField,
+{
+    fn fft(&self, domain: &GeneralEvaluationDomain<F>, coeffs: &[F]) -> Vec<F> {
+        domain.fft(coeffs)
+    }
+
+    fn ifft(&self, domain: &GeneralEvaluationDomain<F>, evals: &[F]) -> Vec<F> {
+        domain.ifft(evals)
+    }
+
+    fn coset_fft(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        coeffs: &[F],
+    ) -> Vec<F> {
+        domain.coset_fft(coeffs)
+    }
+
+    fn coset_ifft(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        evals: &[F],
+    ) -> Vec<F> {
+        domain.coset_ifft(evals)
+    }
+
+    fn coset_fft_in_place(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        coeffs: &mut Vec<F>,
+    ) {
+        domain.coset_fft_in_place(coeffs)
+    }
+
+    fn coset_ifft_in_place(
+        &self,
+        domain: &GeneralEvaluationDomain<F>,
+        evals: &mut Vec<F>,
+    ) {
+        domain.coset_ifft_in_place(evals)
+    }
+}
+
+/// MSM and FFT backends used by the [`Prover`](super::Prover) and the
+/// [`Verifier`](super::Verifier).
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct Backend<E>
+where
+    E: PairingEngine,
+{
+    /// Backend of the multi-scalar multiplications
+    msm: Arc<dyn MsmBackend<E>>,
+
+    /// Backend of the FFTs
+    fft: Arc<dyn FftBackend<E::Fr>>,
+}
+
+impl<E> Default for Backend<E>
+where
+    E: PairingEngine,
+{
+    fn default() -> Self {
+        Self {
+            msm: Arc::new(CpuBackend),
+            fft: Arc::new(CpuBackend),
+        }
+    }
+}
+
+impl<E> core::fmt::Debug for Backend<E>
+where
+    E: PairingEngine,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Backend").finish_non_exhaustive()
+    }
+}
+
+impl<E> Backend<E>
+where
+    E: PairingEngine,
+{
+    /// Creates a backend from its MSM and FFT backends.
+    pub fn new(
+        msm: Arc<dyn MsmBackend<E>>,
+        fft: Arc<dyn FftBackend<E::Fr>>,
+    ) -> Self {
+        Self { msm, fft }
+    }
+
+    /// Replaces the MSM backend, keeping the FFT one.
+    pub fn with_msm(mut self, msm: Arc<dyn MsmBackend<E>>) -> Self {
+        self.msm = msm;
+        self
+    }
+
+    /// Replaces the FFT backend, keeping the MSM one.
+    pub fn with_fft(mut self, fft: Arc<dyn FftBackend<E::Fr>>) -> Self {
+        self.fft = fft;
+        self
+    }
+
+    /// Returns the MSM backend.
+    pub fn msm(&self) -> &dyn MsmBackend<E> {
+        self.msm.as_ref()
+    }
+
+    /// Returns the FFT backend.
+    pub fn fft(&self) -> &dyn FftBackend<E::Fr> {
+        self.fft.as_ref()
+    }
+
+    /// Commits to `poly` with the powers of `commit_key`, as
+    /// [`KZG10::commit`](ark_poly_commit::kzg10::KZG10::commit) does without
+    /// hiding.
+    pub fn commit(
+        &self,
+        commit_key: &Powers<E>,
+        poly: &DensePolynomial<E::Fr>,
+    ) -> Result<Commitment<E>, Error> {
+        // Trailing zero coefficients, left by interpolation, are ignored
+        let num_powers = commit_key.powers_of_g.len();
+        let num_coefficients = poly
+            .coeffs
+            .iter()
+            .rposition(|coeff| !coeff.is_zero())
+            .map_or(0, |i| i + 1);
+        if num_coefficients > num_powers {
+            return Err(ark_poly_commit::Error::TooManyCoefficients {
+                num_coefficients,
+                num_powers,
+            }
+            .into());
+        }
+        Ok(Commitment(
+            self.msm
+                .msm(
+                    &commit_key.powers_of_g[..num_coefficients],
+                    &poly.coeffs[..num_coefficients],
+                )
+                .into_affine(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::constraint_system::helper::dummy_gadget;
+    use crate::proof_system::{Prover, Verifier};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::TEModelParameters;
+    use ark_poly::UVPolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use rand_core::OsRng;
+
+    /// CPU backend counting its calls.
+    #[derive(Default)]
+    struct CountingBackend {
+        msms: AtomicUsize,
+        ffts: AtomicUsize,
+    }
+
+    impl<E> MsmBackend<E> for CountingBackend
+    where
+        E: PairingEngine,
+    {
+        fn msm(
+            &self,
+            bases: &[E::G1Affine],
+            scalars: &[E::Fr],
+        ) -> E::G1Projective {
+            self.msms.fetch_add(1, Ordering::SeqCst);
+            MsmBackend::<E>::msm(&CpuBackend, bases, scalars)
+        }
+    }
+
+    impl<F> FftBackend<F> for CountingBackend
+    where
+        F: FftField,
+    {
+        fn fft(
+            &self,
+            domain: &GeneralEvaluationDomain<F>,
+            coeffs: &[F],
+        ) -> Vec<F> {
+            self.ffts.fetch_add(1, Ordering::SeqCst);
+            CpuBackend.fft(domain, coeffs)
+        }
+
+        fn ifft(
+            &self,
+            domain: &GeneralEvaluationDomain<F>,
+            evals: &[F],
+        ) -> Vec<F> {
+            self.ffts.fetch_add(1, Ordering::SeqCst);
+            CpuBackend.ifft(domain, evals)
+        }
+
+        fn coset_fft(
+            &self,
+            domain: &GeneralEvaluationDomain<F>,
+            coeffs: &[F],
+        ) -> Vec<F> {
+            self.ffts.fetch_add(1, Ordering::SeqCst);
+            CpuBackend.coset_fft(domain, coeffs)
+        }
+
+        fn coset_ifft(
+            &self,
+            domain: &GeneralEvaluationDomain<F>,
+            evals: &[F],
+        ) -> Vec<F> {
+            self.ffts.fetch_add(1, Ordering::SeqCst);
+            CpuBackend.coset_ifft(domain, evals)
+        }
+    }
+
+    fn test_backend_routing<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let n = 16;
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let (ck, vk) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, n, 0, None)
+                .unwrap();
+        let powers = Powers::<E> {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+
+        // Commitments match the ones of KZG10
+        let poly = DensePolynomial::<E::Fr>::rand(n - 1, &mut OsRng);
+        let backend = Backend::<E>::default();
+        assert_eq!(
+            backend.commit(&powers, &poly).unwrap(),
+            KZG10::commit(&powers, &poly, None, None).unwrap().0
+        );
+        assert!(matches!(
+            backend.commit(
+                &powers,
+                &DensePolynomial::rand(powers.size(), &mut OsRng)
+            ),
+            Err(Error::PCError {
+                error: ark_poly_commit::Error::TooManyCoefficients { .. }
+            })
+        ));
+
+        // Preprocessing and proving go through the backend
+        let counter = Arc::new(CountingBackend::default());
+        let mut prover = Prover::<E, P>::new(b"backend");
+        prover.set_backend(Backend::new(counter.clone(), counter.clone()));
+        dummy_gadget(8, prover.mut_cs());
+        prover.preprocess(&powers).unwrap();
+        let public_inputs = prover.cs.construct_dense_pi_vec();
+        let preprocessing_msms = counter.msms.load(Ordering::SeqCst);
+        let preprocessing_ffts = counter.ffts.load(Ordering::SeqCst);
+        assert!(preprocessing_msms > 0);
+        assert!(preprocessing_ffts > 0);
+        let proof = prover.prove(&powers).unwrap();
+        assert!(counter.msms.load(Ordering::SeqCst) > preprocessing_msms);
+        assert!(counter.ffts.load(Ordering::SeqCst) > preprocessing_ffts);
+
+        let mut verifier = Verifier::<E, P>::new(b"backend");
+        verifier.set_backend(Backend::new(counter.clone(), counter.clone()));
+        dummy_gadget(8, verifier.mut_cs());
+        let msms = counter.msms.load(Ordering::SeqCst);
+        verifier.preprocess(&powers).unwrap();
+        assert!(counter.msms.load(Ordering::SeqCst) > msms);
+        let pc_vk = ark_poly_commit::kzg10::VerifierKey {
+            g: vk.g,
+            gamma_g: vk.gamma_g,
+            h: vk.h,
+            beta_h: vk.beta_h,
+            prepared_h: vk.prepared_h,
+            prepared_beta_h: vk.prepared_beta_h,
+        };
+        verifier.verify(&proof, &pc_vk, &public_inputs).unwrap();
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_backend_routing],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_backend_routing],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_backend_routing],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
@@ -0,0 +1,272 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Cost model of proof verification.
+//!
+//! The [`VerificationCost`] of a circuit counts the group, pairing and field
+//! operations performed by the [`Verifier`](super::Verifier) together with
+//! the size of the data it consumes. These counts are the cost model for
+//! native targets, and can be priced with a [`GasSchedule`] for verification
+//! on an EVM chain.
+
+use crate::proof_system::{Proof, VerifierKey};
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_serialize::CanonicalSerialize;
+use num_traits::Zero;
+
+/// Number of terms of the combined opening check: both aggregated
+/// commitments, the generator and the two opening witnesses.
+const OPENING_TERMS: usize = 5;
+
+/// Number of commitments aggregated into the opening at the evaluation
+/// challenge: the linearisation polynomial combined with the quotient, the
+/// wires, three permutation polynomials and four selectors.
+const AGGREGATED_COMMITMENTS: usize = 12;
+
+/// Number of commitments aggregated into the opening at the shifted
+/// evaluation challenge.
+const AGGREGATED_SHIFT_COMMITMENTS: usize = 4;
+
+/// Number of quotient polynomial commitments combined by the verifier.
+const QUOTIENT_COMMITMENTS: usize = 4;
+
+/// Operations and data needed to verify a single [`Proof`] of a circuit.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct VerificationCost {
+    /// Scalar multiplications in `G1`
+    pub g1_scalar_muls: usize,
+
+    /// Additions in `G1`
+    pub g1_additions: usize,
+
+    /// Pairings of the final check, which share a single final
+    /// exponentiation
+    pub pairings: usize,
+
+    /// Estimated multiplications in the scalar field
+    pub field_muls: usize,
+
+    /// Inversions in the scalar field
+    pub field_inversions: usize,
+
+    /// Number of public inputs
+    pub public_inputs: usize,
+
+    /// Bytes of the serialized [`Proof`] and public inputs
+    pub calldata_bytes: usize,
+}
+
+impl VerificationCost {
+    /// Returns the gas needed to run the verification on an EVM chain with
+    /// the given `schedule`.
+    pub fn gas(&self, schedule: &GasSchedule) -> u64 {
+        let count = |ops: usize, price: u64| ops as u64 * price;
+        count(self.g1_scalar_muls, schedule.g1_mul)
+            + count(self.g1_additions, schedule.g1_add)
+            + schedule.pairing_base
+            + count(self.pairings, schedule.pairing_per_pair)
+            + count(self.field_muls, schedule.field_mul)
+            + count(self.field_inversions, schedule.field_inversion)
+            + count(self.calldata_bytes, schedule.calldata_byte)
+    }
+}
+
+/// Gas prices of the operations of a [`VerificationCost`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GasSchedule {
+    /// Price of a `G1` addition
+    pub g1_add: u64,
+
+    /// Price of a `G1` scalar multiplication
+    pub g1_mul: u64,
+
+    /// Fixed price of a pairing check
+    pub pairing_base: u64,
+
+    /// Price of each pairing of a pairing check
+    pub pairing_per_pair: u64,
+
+    /// Price of a scalar field multiplication
+    pub field_mul: u64,
+
+    /// Price of a scalar field inversion
+    pub field_inversion: u64,
+
+    /// Price of a calldata byte
+    pub calldata_byte: u64,
+}
+
+impl GasSchedule {
+    /// Prices of the BLS12-381 precompiles of EIP-2537, with field
+    /// multiplications priced as `MULMOD`, inversions as a 32-byte `MODEXP`
+    /// (EIP-2565) and calldata bytes as non-zero bytes (EIP-2028).
+    ///
+    /// There are no precompiles for other curves, so this schedule only
+    /// makes sense for keys over BLS12-381.
+    pub const EIP_2537: Self = Self {
+        g1_add: 375,
+        g1_mul: 12_000,
+        pairing_base: 37_700,
+        pairing_per_pair: 32_600,
+        field_mul: 8,
+        field_inversion: 1_360,
+        calldata_byte: 16,
+    };
+}
+
+impl<E, P> VerifierKey<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    /// Returns the cost of verifying a [`ProofVersion::LATEST`] proof against
+    /// this key with `public_inputs` public input values.
+    ///
+    /// [`ProofVersion::LATEST`]: crate::proof_system::ProofVersion::LATEST
+    pub fn verification_cost(&self, public_inputs: usize) -> VerificationCost {
+        let zero = E::Fr::zero();
+        let proof = Proof::<E, P>::default();
+        let (linearisation_terms, _) = proof.linearisation_terms(
+            zero, zero, zero, zero, zero, zero, zero, zero, zero, self,
+        );
+        let linearisation_terms = linearisation_terms.len();
+        let log_n = self.padded_circuit_size().trailing_zeros() as usize;
+
+        // Linearisation MSM, quotient commitment and its combination with
+        // the linearisation commitment, aggregation of both openings and the
+        // combined opening check.
+        let g1_scalar_muls = linearisation_terms
+            + (QUOTIENT_COMMITMENTS - 1)
+            + 1
+            + AGGREGATED_COMMITMENTS
+            + AGGREGATED_SHIFT_COMMITMENTS
+            + OPENING_TERMS;
+        let g1_additions = (linearisation_terms - 1)
+            + (QUOTIENT_COMMITMENTS - 1)
+            + 1
+            + (AGGREGATED_COMMITMENTS - 1)
+            + (AGGREGATED_SHIFT_COMMITMENTS - 1)
+            + (OPENING_TERMS - 1);
+
+        // Powers of the evaluation challenge, one exponentiation of the
+        // domain generator and a batch inversion per public input, the
+        // linearisation scalars and the powers of the aggregation
+        // challenges.
+        let field_muls = 4 * (log_n + 2)
+            + public_inputs * (2 * log_n + 5)
+            + 4 * linearisation_terms
+            + 2 * (AGGREGATED_COMMITMENTS + AGGREGATED_SHIFT_COMMITMENTS)
+            + 32;
+
+        // First Lagrange polynomial and the batch inversion of the public
+        // input denominators.
+        let field_inversions = 1 + usize::from(public_inputs > 0);
+
+        VerificationCost {
+            g1_scalar_muls,
+            g1_additions,
+            pairings: 2,
+            field_muls,
+            field_inversions,
+            public_inputs,
+            calldata_bytes: proof.serialized_size()
+                + public_inputs * zero.serialized_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::proof_system::{Prover, Verifier};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::{Powers, KZG10};
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use rand_core::OsRng;
+
+    fn test_verification_cost<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut prover: Prover<E, P> = Prover::new(b"cost");
+        let mut verifier: Verifier<E, P> = Verifier::new(b"cost");
+        prover.mut_cs().add_dummy_constraints();
+        verifier.mut_cs().add_dummy_constraints();
+        let n = prover.circuit_size().next_power_of_two();
+
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, n, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        verifier.preprocess(&powers).unwrap();
+        let proof = prover.prove(&powers).unwrap();
+        let key = verifier.verifier_key.unwrap();
+
+        // 7 arithmetic and 2 permutation commitments, the selectors of the
+        // unused custom gates are skipped
+        let cost = key.verification_cost(0);
+        assert_eq!(cost.g1_scalar_muls, 9 + 3 + 1 + 12 + 4 + 5);
+        assert_eq!(cost.pairings, 2);
+        assert_eq!(cost.field_inversions, 1);
+        assert_eq!(cost.calldata_bytes, proof.serialized_size());
+
+        // Public inputs only add field operations and calldata
+        let with_inputs = key.verification_cost(3);
+        assert_eq!(with_inputs.g1_scalar_muls, cost.g1_scalar_muls);
+        assert_eq!(with_inputs.field_inversions, 2);
+        assert!(with_inputs.field_muls > cost.field_muls);
+        assert_eq!(
+            with_inputs.calldata_bytes,
+            cost.calldata_bytes + 3 * E::Fr::zero().serialized_size()
+        );
+        assert!(
+            with_inputs.gas(&GasSchedule::EIP_2537)
+                > cost.gas(&GasSchedule::EIP_2537)
+        );
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_verification_cost],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_verification_cost],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_verification_cost],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
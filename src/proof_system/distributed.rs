@@ -0,0 +1,282 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Commitments computed across several machines.
+//!
+//! Committing to a polynomial is a multi-scalar multiplication of its
+//! coefficients with the powers of the commit key, which dominates the time
+//! spent proving large circuits. [`MsmShard::split`] cuts it into
+//! independent work units over disjoint ranges of the powers. Each shard can
+//! be serialized and computed by a remote [`MsmWorker`], which only needs
+//! the commit key, and the partial sums it returns add up to the commitment.
+//!
+//! [`commit_distributed`] coordinates the whole process for a single
+//! polynomial.
+
+use crate::error::Error;
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::PrimeField;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::kzg10::{Commitment, Powers};
+use ark_serialize::*;
+use num_traits::Zero;
+
+/// Coefficients of a polynomial to multiply with a range of the powers of
+/// the commit key, as part of the commitment to the polynomial.
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct MsmShard<E>
+where
+    E: PairingEngine,
+{
+    /// Index of the first power of the shard
+    offset: u64,
+
+    /// Coefficients multiplied with the powers from `offset` on
+    scalars: Vec<E::Fr>,
+}
+
+impl<E> MsmShard<E>
+where
+    E: PairingEngine,
+{
+    /// Splits the commitment to `poly` into at most `num_shards` shards of
+    /// consecutive coefficients.
+    pub fn split(
+        poly: &DensePolynomial<E::Fr>,
+        num_shards: usize,
+    ) -> Vec<Self> {
+        let shard_size = poly.coeffs.len().div_ceil(num_shards.max(1));
+        poly.coeffs
+            .chunks(shard_size.max(1))
+            .enumerate()
+            .map(|(i, scalars)| Self {
+                offset: (i * shard_size) as u64,
+                scalars: scalars.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Returns the index of the first power of the shard.
+    pub fn offset(&self) -> usize {
+        self.offset as usize
+    }
+
+    /// Returns the number of powers of the shard.
+    pub fn len(&self) -> usize {
+        self.scalars.len()
+    }
+
+    /// Returns `true` if the shard has no coefficients.
+    pub fn is_empty(&self) -> bool {
+        self.scalars.is_empty()
+    }
+
+    /// Computes the partial sum of the shard with the powers of
+    /// `commit_key`.
+    pub fn compute(
+        &self,
+        commit_key: &Powers<E>,
+    ) -> Result<E::G1Affine, Error> {
+        let end = self.offset() + self.len();
+        if end > commit_key.powers_of_g.len() {
+            return Err(Error::PolynomialDegreeTooLarge {
+                degree: end - 1,
+                max_degree: commit_key.powers_of_g.len().saturating_sub(1),
+            });
+        }
+        let scalars: Vec<_> =
+            self.scalars.iter().map(E::Fr::into_repr).collect();
+        Ok(VariableBaseMSM::multi_scalar_mul(
+            &commit_key.powers_of_g[self.offset()..end],
+            &scalars,
+        )
+        .into_affine())
+    }
+}
+
+/// Computes the partial sums of [`MsmShard`]s, for instance by sending them
+/// to other machines.
+pub trait MsmWorker<E>
+where
+    E: PairingEngine,
+{
+    /// Returns the partial sums of `shards`, in the same order.
+    fn compute_shards(
+        &self,
+        shards: &[MsmShard<E>],
+    ) -> Result<Vec<E::G1Affine>, Error>;
+}
+
+/// Computes the shards locally.
+impl<E> MsmWorker<E> for Powers<'_, E>
+where
+    E: PairingEngine,
+{
+    fn compute_shards(
+        &self,
+        shards: &[MsmShard<E>],
+    ) -> Result<Vec<E::G1Affine>, Error> {
+        shards.iter().map(|shard| shard.compute(self)).collect()
+    }
+}
+
+/// Adds up the partial sums of the shards of a polynomial into its
+/// commitment.
+pub fn combine_shards<E>(partial_sums: &[E::G1Affine]) -> Commitment<E>
+where
+    E: PairingEngine,
+{
+    Commitment(
+        partial_sums
+            .iter()
+            .fold(E::G1Projective::zero(), |sum, partial| {
+                sum.add_mixed(partial)
+            })
+            .into_affine(),
+    )
+}
+
+/// Commits to `poly` by splitting it into `num_shards` shards computed by
+/// `worker`.
+///
+/// The commitment is the same as the one of
+/// [`KZG10::commit`](ark_poly_commit::kzg10::KZG10::commit) with the commit
+/// key of the worker.
+pub fn commit_distributed<E, W>(
+    poly: &DensePolynomial<E::Fr>,
+    num_shards: usize,
+    worker: &W,
+) -> Result<Commitment<E>, Error>
+where
+    E: PairingEngine,
+    W: MsmWorker<E> + ?Sized,
+{
+    let shards = MsmShard::split(poly, num_shards);
+    let partial_sums = worker.compute_shards(&shards)?;
+    if partial_sums.len() != shards.len() {
+        return Err(Error::MsmShardCountMismatch {
+            expected: shards.len(),
+            actual: partial_sums.len(),
+        });
+    }
+    Ok(combine_shards(&partial_sums))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test_engine;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::UVPolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use rand_core::OsRng;
+
+    /// Worker losing the last partial sum.
+    struct LossyWorker<'a, E: PairingEngine>(Powers<'a, E>);
+
+    impl<E> MsmWorker<E> for LossyWorker<'_, E>
+    where
+        E: PairingEngine,
+    {
+        fn compute_shards(
+            &self,
+            shards: &[MsmShard<E>],
+        ) -> Result<Vec<E::G1Affine>, Error> {
+            let mut partial_sums = self.0.compute_shards(shards)?;
+            partial_sums.pop();
+            Ok(partial_sums)
+        }
+    }
+
+    fn test_distributed_commitment<E>()
+    where
+        E: PairingEngine,
+    {
+        let n = 32;
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(n, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, n, 0, None)
+                .unwrap();
+        let powers = Powers::<E> {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        let poly = DensePolynomial::<E::Fr>::rand(n - 3, &mut OsRng);
+        let commitment = KZG10::commit(&powers, &poly, None, None).unwrap().0;
+
+        // The shards cover the polynomial whatever their number
+        for num_shards in [0, 1, 3, 4, n - 2, 2 * n] {
+            let shards = MsmShard::<E>::split(&poly, num_shards);
+            assert!(shards.len() <= num_shards.max(1));
+            assert_eq!(
+                shards.iter().map(MsmShard::len).sum::<usize>(),
+                poly.coeffs.len()
+            );
+            assert_eq!(
+                commit_distributed(&poly, num_shards, &powers).unwrap(),
+                commitment
+            );
+        }
+
+        // Shards survive a round trip through their serialization
+        let shards = MsmShard::<E>::split(&poly, 4);
+        let mut bytes = Vec::new();
+        shards.serialize(&mut bytes).unwrap();
+        let partial_sums = powers
+            .compute_shards(
+                &Vec::<MsmShard<E>>::deserialize(bytes.as_slice()).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(combine_shards(&partial_sums), commitment);
+
+        // Shards past the end of the commit key and missing partial sums
+        // are rejected
+        let short_key = Powers::<E> {
+            powers_of_g: powers.powers_of_g[..8].into(),
+            powers_of_gamma_g: powers.powers_of_gamma_g.clone(),
+        };
+        assert!(matches!(
+            shards[1].compute(&short_key),
+            Err(Error::PolynomialDegreeTooLarge { max_degree: 7, .. })
+        ));
+        assert!(matches!(
+            commit_distributed(&poly, 4, &LossyWorker(powers)),
+            Err(Error::MsmShardCountMismatch {
+                expected: 4,
+                actual: 3
+            })
+        ));
+    }
+
+    // Test on Bls12-381
+    batch_test_engine!(
+        [test_distributed_commitment],
+        []
+        => Bls12_381
+    );
+
+    // Test on Bls12-377
+    batch_test_engine!(
+        [test_distributed_commitment],
+        []
+        => Bls12_377
+    );
+
+    // Test on Bn254
+    batch_test_engine!(
+        [test_distributed_commitment],
+        []
+        => Bn254
+    );
+}
@@ -0,0 +1,301 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Proofs that a re-randomized verifier key describes the same circuit as
+//! the key it was derived from.
+//!
+//! [`VerifierKey::rerandomize`] adds a multiple `r_i` of the commitment `V`
+//! to the vanishing polynomial `X^n - 1` to the commitment of every
+//! selector. A [`KeyEquivalenceProof`] is a Schnorr proof of knowledge of the
+//! `r_i`, folded with the powers of a challenge into a single one, so that a
+//! party can publish a blinded verifier key and convince anyone holding the
+//! deterministic one that both selectors agree over the domain, without
+//! revealing the blinding.
+
+use crate::error::Error;
+use crate::proof_system::widget::{
+    vanishing_commitment, SelectorBlinding, VerifierKey,
+};
+use crate::transcript::{TranscriptProtocol, TranscriptWrapper};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve, TEModelParameters};
+use ark_ff::{PrimeField, UniformRand};
+use ark_poly_commit::kzg10::{Commitment, UniversalParams};
+use ark_serialize::*;
+use num_traits::Zero;
+use rand_core::RngCore;
+
+/// Proof that a verifier key is another one re-randomized by
+/// [`VerifierKey::rerandomize`], i.e. that both describe the same circuit.
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct KeyEquivalenceProof<E>
+where
+    E: PairingEngine,
+{
+    /// Commitment to the nonce, as a multiple of the vanishing commitment
+    nonce_commitment: Commitment<E>,
+
+    /// Nonce plus the challenge times the folded blinding factors
+    response: E::Fr,
+}
+
+impl<E> KeyEquivalenceProof<E>
+where
+    E: PairingEngine,
+{
+    /// Proves that `blinded_key` is `key` re-randomized with `blinding`.
+    ///
+    /// The proof doesn't check the statement: a `blinding` that doesn't
+    /// match the keys yields a proof that fails to verify.
+    pub fn prove<P, R>(
+        u_params: &UniversalParams<E>,
+        key: &VerifierKey<E, P>,
+        blinded_key: &VerifierKey<E, P>,
+        blinding: &SelectorBlinding<E::Fr>,
+        rng: &mut R,
+    ) -> Result<Self, Error>
+    where
+        P: TEModelParameters<BaseField = E::Fr>,
+        R: RngCore,
+    {
+        let vanishing = vanishing_commitment::<E>(
+            &u_params.powers_of_g,
+            key.padded_circuit_size(),
+        )?;
+        let mut transcript = statement_transcript(key, blinded_key);
+        let folding = transcript.challenge_scalar(b"folding");
+        let blinding = blinding
+            .factors
+            .iter()
+            .rev()
+            .fold(E::Fr::zero(), |folded, factor| folded * folding + factor);
+
+        let nonce = E::Fr::rand(rng);
+        let nonce_commitment =
+            Commitment(vanishing.mul(nonce.into_repr()).into_affine());
+        transcript.append_commitment(b"nonce", &nonce_commitment);
+        let challenge = transcript.challenge_scalar(b"challenge");
+        Ok(Self {
+            nonce_commitment,
+            response: nonce + challenge * blinding,
+        })
+    }
+
+    /// Checks that `blinded_key` is `key` re-randomized, failing with
+    /// [`Error::KeyEquivalenceFailure`] otherwise.
+    ///
+    /// Besides the proof, the keys must have the same size, permutation
+    /// commitments and circuit identifier, which re-randomization doesn't
+    /// change.
+    pub fn verify<P>(
+        &self,
+        u_params: &UniversalParams<E>,
+        key: &VerifierKey<E, P>,
+        blinded_key: &VerifierKey<E, P>,
+    ) -> Result<(), Error>
+    where
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        if key.n != blinded_key.n
+            || key.permutation != blinded_key.permutation
+            || key.circuit_id != blinded_key.circuit_id
+        {
+            return Err(Error::KeyEquivalenceFailure);
+        }
+        let vanishing = vanishing_commitment::<E>(
+            &u_params.powers_of_g,
+            key.padded_circuit_size(),
+        )?;
+        let mut transcript = statement_transcript(key, blinded_key);
+        let folding: E::Fr = transcript.challenge_scalar(b"folding");
+        transcript.append_commitment(b"nonce", &self.nonce_commitment);
+        let challenge: E::Fr = transcript.challenge_scalar(b"challenge");
+
+        // Folded differences of the selector commitments
+        let differences = key
+            .selector_commitments()
+            .into_iter()
+            .zip(blinded_key.selector_commitments())
+            .map(|(commitment, blinded)| {
+                let mut difference = blinded.0.into_projective();
+                difference.add_assign_mixed(&-commitment.0);
+                difference
+            });
+        let difference = differences
+            .rev()
+            .fold(E::G1Projective::zero(), |folded, difference| {
+                folded.mul(folding.into_repr()) + difference
+            });
+
+        let lhs = vanishing.mul(self.response.into_repr());
+        let rhs = difference.mul(challenge.into_repr())
+            + self.nonce_commitment.0.into_projective();
+        if lhs != rhs {
+            return Err(Error::KeyEquivalenceFailure);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the transcript of the proof, seeded with the digests of the keys.
+fn statement_transcript<E, P>(
+    key: &VerifierKey<E, P>,
+    blinded_key: &VerifierKey<E, P>,
+) -> TranscriptWrapper<E>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let mut transcript = TranscriptWrapper::new(b"ark-plonk-key-equivalence");
+    transcript.append_message(b"key", &key.digest());
+    transcript.append_message(b"blinded_key", &blinded_key.digest());
+    transcript
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::circuit::{rerandomize_keys, Circuit};
+    use crate::constraint_system::StandardComposer;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use num_traits::One;
+    use rand_core::OsRng;
+
+    // Implements a circuit that checks:
+    // 1) a * b = c
+    // 2) a <= 2^8
+    #[derive(derivative::Derivative)]
+    #[derivative(Default(bound = ""))]
+    struct TestCircuit<E: PairingEngine> {
+        a: E::Fr,
+        b: E::Fr,
+        c: E::Fr,
+    }
+
+    impl<E, P> Circuit<E, P> for TestCircuit<E>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let c = composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+            composer.constrain_to_constant(c, self.c, None);
+            composer.range_gate(a, 8);
+            Ok(())
+        }
+    }
+
+    fn test_key_equivalence<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 6,
+            false,
+            &mut OsRng,
+        )
+        .unwrap();
+        let (mut pk, verifier_data) =
+            Circuit::<E, P>::compile(&mut TestCircuit::<E>::default(), &pp)
+                .unwrap();
+        let mut blinded_data = verifier_data.clone();
+        let blinding =
+            rerandomize_keys(&pp, &mut pk, &mut blinded_data, &mut OsRng)
+                .unwrap();
+        let (key, blinded_key) = (&verifier_data.key, &blinded_data.key);
+
+        let proof = KeyEquivalenceProof::prove(
+            &pp,
+            key,
+            blinded_key,
+            &blinding,
+            &mut OsRng,
+        )
+        .unwrap();
+        proof.verify(&pp, key, blinded_key).unwrap();
+
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+        assert_eq!(
+            KeyEquivalenceProof::<E>::deserialize(bytes.as_slice()).unwrap(),
+            proof
+        );
+
+        // The proof is bound to the order of the keys
+        assert!(matches!(
+            proof.verify(&pp, blinded_key, key),
+            Err(Error::KeyEquivalenceFailure)
+        ));
+
+        // Keys of another circuit are rejected
+        let (_, other_data) = Circuit::<E, P>::compile(
+            &mut TestCircuit::<E> {
+                c: E::Fr::one(),
+                ..Default::default()
+            },
+            &pp,
+        )
+        .unwrap();
+        assert!(matches!(
+            proof.verify(&pp, &other_data.key, blinded_key),
+            Err(Error::KeyEquivalenceFailure)
+        ));
+
+        // Other blindings don't prove the equivalence
+        let mut other_blinded_data = verifier_data.clone();
+        let other_blinding =
+            rerandomize_keys(&pp, &mut pk, &mut other_blinded_data, &mut OsRng)
+                .unwrap();
+        let proof = KeyEquivalenceProof::prove(
+            &pp,
+            key,
+            blinded_key,
+            &other_blinding,
+            &mut OsRng,
+        )
+        .unwrap();
+        assert!(matches!(
+            proof.verify(&pp, key, blinded_key),
+            Err(Error::KeyEquivalenceFailure)
+        ));
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_key_equivalence],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_key_equivalence],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
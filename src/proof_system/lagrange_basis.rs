@@ -0,0 +1,214 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Commit key in Lagrange basis.
+//!
+//! The wire values of a circuit are the evaluations of the wire polynomials
+//! over the circuit domain. Committing to them with the powers of the
+//! universal parameters first requires interpolating the polynomials with an
+//! inverse FFT. A [`LagrangeBasis`] holds instead the commitments to the
+//! Lagrange polynomials of the domain, so that the commitment to a
+//! polynomial is the multi-scalar multiplication of the basis with its
+//! evaluations.
+
+use crate::error::Error;
+use crate::proof_system::backend::{CpuBackend, MsmBackend};
+use crate::util::{self, EvaluationDomainExt};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField};
+use ark_poly::EvaluationDomain;
+use ark_poly_commit::kzg10::{Commitment, Powers};
+use num_traits::One;
+
+/// Commitments to the Lagrange polynomials of a circuit domain.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct LagrangeBasis<E>
+where
+    E: PairingEngine,
+{
+    /// Commitment to the `i`-th Lagrange polynomial of the domain
+    bases: Vec<E::G1Affine>,
+}
+
+impl<E> LagrangeBasis<E>
+where
+    E: PairingEngine,
+{
+    /// Converts the first powers of `commit_key` into the Lagrange basis of
+    /// the domain of `domain_size` elements.
+    ///
+    /// This performs an inverse FFT over group elements, which is much more
+    /// expensive than committing to a polynomial. The basis is meant to be
+    /// computed once per circuit size and reused for every proof.
+    pub fn from_powers(
+        commit_key: &Powers<E>,
+        domain_size: usize,
+    ) -> Result<Self, Error> {
+        let domain = util::evaluation_domain::<E::Fr>(domain_size)?;
+        let n = domain.size();
+        if commit_key.powers_of_g.len() < n {
+            return Err(Error::UniversalParamsTooSmall {
+                circuit_size: n,
+                required_powers: n,
+                available_powers: commit_key.powers_of_g.len(),
+            });
+        }
+        debug_assert!(n.is_power_of_two());
+
+        // [L_i(x)] = 1/n * sum_j w^{-ij} [x^j]
+        let mut points: Vec<E::G1Projective> = commit_key.powers_of_g[..n]
+            .iter()
+            .map(AffineCurve::into_projective)
+            .collect();
+        bit_reverse(&mut points);
+        let mut half = 1;
+        while half < n {
+            let step = domain.group_gen_inv().pow([(n / (2 * half)) as u64]);
+            for chunk in points.chunks_mut(2 * half) {
+                let mut twiddle = E::Fr::one();
+                for j in 0..half {
+                    let t = chunk[j + half].mul(twiddle.into_repr());
+                    chunk[j + half] = chunk[j] - t;
+                    chunk[j] += t;
+                    twiddle *= step;
+                }
+            }
+            half *= 2;
+        }
+        let size_inv = domain.size_inv().into_repr();
+        points
+            .iter_mut()
+            .for_each(|point| *point = point.mul(size_inv));
+
+        Ok(Self {
+            bases: E::G1Projective::batch_normalization_into_affine(&points),
+        })
+    }
+
+    /// Returns the size of the domain.
+    pub fn size(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Commits to the polynomial whose evaluations over the domain are
+    /// `evaluations`.
+    pub fn commit(
+        &self,
+        evaluations: &[E::Fr],
+    ) -> Result<Commitment<E>, Error> {
+        self.commit_with(evaluations, &CpuBackend)
+    }
+
+    /// Commits to the polynomial whose evaluations over the domain are
+    /// `evaluations`, with the multi-scalar multiplication computed by
+    /// `msm`.
+    pub(crate) fn commit_with(
+        &self,
+        evaluations: &[E::Fr],
+        msm: &dyn MsmBackend<E>,
+    ) -> Result<Commitment<E>, Error> {
+        if evaluations.len() != self.size() {
+            return Err(Error::LagrangeBasisSizeMismatch {
+                expected: self.size(),
+                actual: evaluations.len(),
+            });
+        }
+        Ok(Commitment(msm.msm(&self.bases, evaluations).into_affine()))
+    }
+}
+
+/// Reorders `values` by the bit reversal of their indices.
+fn bit_reverse<T>(values: &mut [T]) {
+    let n = values.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test_engine;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::UniformRand;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly::UVPolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use rand_core::OsRng;
+
+    fn test_lagrange_commitment<E>()
+    where
+        E: PairingEngine,
+    {
+        let n = 16;
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(2 * n, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, 2 * n, 0, None)
+                .unwrap();
+        let powers = Powers::<E> {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        let basis = LagrangeBasis::from_powers(&powers, n).unwrap();
+        assert_eq!(basis.size(), n);
+
+        // Committing to the evaluations matches committing to the
+        // interpolated polynomial
+        let domain = util::evaluation_domain::<E::Fr>(n).unwrap();
+        let evaluations: Vec<E::Fr> =
+            (0..n).map(|_| E::Fr::rand(&mut OsRng)).collect();
+        let poly =
+            DensePolynomial::from_coefficients_vec(domain.ifft(&evaluations));
+        assert_eq!(
+            basis.commit(&evaluations).unwrap(),
+            KZG10::commit(&powers, &poly, None, None).unwrap().0
+        );
+
+        assert!(matches!(
+            basis.commit(&evaluations[1..]),
+            Err(Error::LagrangeBasisSizeMismatch {
+                expected: 16,
+                actual: 15,
+            })
+        ));
+        assert!(matches!(
+            LagrangeBasis::from_powers(&powers, 4 * n),
+            Err(Error::UniversalParamsTooSmall { .. })
+        ));
+    }
+
+    // Test on Bls12-381
+    batch_test_engine!(
+        [test_lagrange_commitment],
+        []
+        => Bls12_381
+    );
+
+    // Test on Bls12-377
+    batch_test_engine!(
+        [test_lagrange_commitment],
+        []
+        => Bls12_377
+    );
+
+    // Test on Bn254
+    batch_test_engine!(
+        [test_lagrange_commitment],
+        []
+        => Bn254
+    );
+}
@@ -31,9 +31,6 @@ where
 {
     /// Proof-relevant Evaluations
     pub proof: ProofEvaluations<F>,
-
-    /// Evaluation of the linearisation sigma polynomial at `z`.
-    pub quot_eval: F,
 }
 
 /// Subset of all of the evaluations. These evaluations
@@ -113,14 +110,12 @@ pub fn compute<F, P>(
     w_r_poly: &DensePolynomial<F>,
     w_o_poly: &DensePolynomial<F>,
     w_4_poly: &DensePolynomial<F>,
-    t_x_poly: &DensePolynomial<F>,
     z_poly: &DensePolynomial<F>,
 ) -> (DensePolynomial<F>, Evaluations<F>)
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
-    let quot_eval = t_x_poly.evaluate(z_challenge);
     let a_eval = w_l_poly.evaluate(z_challenge);
     let b_eval = w_r_poly.evaluate(z_challenge);
     let c_eval = w_o_poly.evaluate(z_challenge);
@@ -197,7 +192,6 @@ where
                 linearisation_polynomial_eval,
                 permutation_eval,
             },
-            quot_eval,
         },
     )
 }
@@ -244,6 +238,7 @@ where
         b_eval,
         c_eval,
         d_eval,
+        a_next_eval,
         q_arith_eval,
     );
 
@@ -10,13 +10,87 @@ mod linearisation_poly;
 mod permutation;
 mod preprocess;
 mod quotient_poly;
+mod scratch;
 mod widget;
 
-pub mod proof;
-pub mod prover;
-pub mod verifier;
+pub(crate) mod backend;
+pub(crate) mod cost;
+pub(crate) mod equivalence;
+pub(crate) mod lagrange_basis;
+pub(crate) mod proof;
+pub(crate) mod prover;
+pub(crate) mod verifier;
 
+pub mod distributed;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod soundness;
+
+pub use backend::{Backend, CpuBackend, FftBackend, MsmBackend};
+pub use cost::{GasSchedule, VerificationCost};
+pub use equivalence::KeyEquivalenceProof;
+pub use lagrange_basis::LagrangeBasis;
 pub use proof::*;
-pub use prover::Prover;
+pub use prover::{ProgressCallback, Prover, ProverPhase, ProverProgress};
 pub use verifier::Verifier;
 pub use widget::*;
+
+pub use crate::transcript::{TranscriptEvent, TranscriptLog};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::circuit::CompiledCircuit;
+    use crate::constraint_system::StandardComposer;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::{PairingEngine, TEModelParameters};
+
+    /// Checks that the prover and verifier types can be moved to and shared
+    /// between threads, so proofs can be generated on worker threads.
+    fn test_send_sync<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<StandardComposer<E, P>>();
+        is_send_sync::<Prover<E, P>>();
+        is_send_sync::<Verifier<E, P>>();
+        is_send_sync::<ProverKey<E::Fr, P>>();
+        is_send_sync::<VerifierKey<E, P>>();
+        is_send_sync::<Proof<E, P>>();
+        is_send_sync::<CompiledCircuit<E, P>>();
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_send_sync],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_send_sync],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_send_sync],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
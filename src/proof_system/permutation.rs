@@ -41,25 +41,18 @@ where
 
     /// Fourth Permutation
     pub fourth_sigma: (DensePolynomial<F>, Evaluations<F>),
-
-    /// Linear Evaluations
-    pub linear_evaluations: Evaluations<F>,
-    /* Evaluations of f(x) = X
-     * [XXX: Remove this and
-     * benchmark if it makes a
-     * considerable difference
-     * -- These are just the
-     * domain elements] */
 }
 
 impl<F> ProverKey<F>
 where
     F: PrimeField,
 {
-    /// Computes the quotient polynomial at the `i`th domain point.
+    /// Computes the quotient polynomial at the `i`th domain point, whose
+    /// value is `x`.
     pub fn compute_quotient_i(
         &self,
         index: usize,
+        x: F,
         w_l_i: F,
         w_r_i: F,
         w_o_i: F,
@@ -72,7 +65,7 @@ where
         gamma: F,
     ) -> F {
         let a = self.compute_quotient_identity_range_check_i(
-            index, w_l_i, w_r_i, w_o_i, w_4_i, z_i, alpha, beta, gamma,
+            x, w_l_i, w_r_i, w_o_i, w_4_i, z_i, alpha, beta, gamma,
         );
         let b = self.compute_quotient_copy_range_check_i(
             index, w_l_i, w_r_i, w_o_i, w_4_i, z_i_next, alpha, beta, gamma,
@@ -89,7 +82,7 @@ where
     /// ```
     fn compute_quotient_identity_range_check_i(
         &self,
-        index: usize,
+        x: F,
         w_l_i: F,
         w_r_i: F,
         w_o_i: F,
@@ -99,7 +92,6 @@ where
         beta: F,
         gamma: F,
     ) -> F {
-        let x = self.linear_evaluations[index];
         (w_l_i + (beta * x) + gamma)
             * (w_r_i + (beta * K1::<F>() * x) + gamma)
             * (w_o_i + (beta * K2::<F>() * x) + gamma)
@@ -8,14 +8,17 @@
 
 use crate::constraint_system::StandardComposer;
 use crate::error::Error;
-use crate::proof_system::{widget, ProverKey};
+use crate::proof_system::{widget, Backend, ProverKey};
 use crate::transcript::TranscriptWrapper;
+use crate::util;
 use ark_ec::{PairingEngine, TEModelParameters};
 use ark_ff::PrimeField;
 use ark_poly::polynomial::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain};
-use ark_poly_commit::kzg10::{Powers, KZG10};
-use num_traits::{One, Zero};
+use ark_poly_commit::kzg10::Powers;
+use num_traits::Zero;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Struct that contains all of the selector and permutation [`Polynomial`]s in
 /// PLONK.
@@ -36,6 +39,7 @@ where
     q_logic: DensePolynomial<F>,
     q_fixed_group_add: DensePolynomial<F>,
     q_variable_group_add: DensePolynomial<F>,
+    q_lookahead: DensePolynomial<F>,
     left_sigma: DensePolynomial<F>,
     right_sigma: DensePolynomial<F>,
     out_sigma: DensePolynomial<F>,
@@ -70,6 +74,7 @@ where
         self.q_logic.extend(zeroes_scalar.iter());
         self.q_fixed_group_add.extend(zeroes_scalar.iter());
         self.q_variable_group_add.extend(zeroes_scalar.iter());
+        self.q_lookahead.extend(zeroes_scalar.iter());
 
         self.w_l.extend(zeroes_var.iter());
         self.w_r.extend(zeroes_var.iter());
@@ -94,6 +99,7 @@ where
             && self.q_logic.len() == k
             && self.q_fixed_group_add.len() == k
             && self.q_variable_group_add.len() == k
+            && self.q_lookahead.len() == k
             && self.w_l.len() == k
             && self.w_r.len() == k
             && self.w_o.len() == k
@@ -105,6 +111,21 @@ where
         }
     }
 
+    /// Checks that the result of every chained gate is placed on the left
+    /// wire of the gate that follows it.
+    fn check_lookahead_wires(&self) -> Result<(), Error> {
+        match self
+            .lookahead_wires
+            .iter()
+            .find(|(gate, var)| self.w_l.get(*gate + 1) != Some(var))
+        {
+            Some((gate, _)) => {
+                Err(Error::LookaheadWireMismatch { gate_index: *gate })
+            }
+            None => Ok(()),
+        }
+    }
+
     /// These are the parts of preprocessing that the prover must compute
     /// Although the prover does not need the verification key, he must compute
     /// the commitments in order to seed the transcript, allowing both the
@@ -114,83 +135,84 @@ where
         commit_key: &Powers<E>,
         transcript: &mut TranscriptWrapper<E>,
     ) -> Result<ProverKey<E::Fr, P>, Error> {
-        let (_, selectors, domain) =
-            self.preprocess_shared(commit_key, transcript)?;
+        self.preprocess_prover_with_backend(
+            commit_key,
+            transcript,
+            &Backend::default(),
+        )
+    }
 
-        let domain_4n =
-            GeneralEvaluationDomain::new(4 * domain.size()).unwrap();
-        let q_m_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_m),
-            domain_4n,
-        );
-        let q_l_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_l),
-            domain_4n,
-        );
-        let q_r_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_r),
-            domain_4n,
-        );
-        let q_o_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_o),
-            domain_4n,
-        );
-        let q_c_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_c),
-            domain_4n,
-        );
-        let q_4_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_4),
-            domain_4n,
-        );
-        let q_arith_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_arith),
-            domain_4n,
-        );
-        let q_range_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_range),
-            domain_4n,
-        );
-        let q_logic_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_logic),
-            domain_4n,
-        );
-        let q_fixed_group_add_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_fixed_group_add),
-            domain_4n,
-        );
-        let q_variable_group_add_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.q_variable_group_add),
-            domain_4n,
-        );
+    /// Runs [`StandardComposer::preprocess_prover`] with the MSMs and FFTs
+    /// computed by `backend`.
+    pub fn preprocess_prover_with_backend(
+        &mut self,
+        commit_key: &Powers<E>,
+        transcript: &mut TranscriptWrapper<E>,
+        backend: &Backend<E>,
+    ) -> Result<ProverKey<E::Fr, P>, Error> {
+        let (verifier_key, selectors, domain) =
+            self.preprocess_shared(commit_key, transcript, backend)?;
+
+        let fft = backend.fft();
+        let domain_4n = util::evaluation_domain(4 * domain.size())?;
+        let polys = [
+            &selectors.q_m,
+            &selectors.q_l,
+            &selectors.q_r,
+            &selectors.q_o,
+            &selectors.q_c,
+            &selectors.q_4,
+            &selectors.q_arith,
+            &selectors.q_range,
+            &selectors.q_logic,
+            &selectors.q_fixed_group_add,
+            &selectors.q_variable_group_add,
+            &selectors.q_lookahead,
+            &selectors.left_sigma,
+            &selectors.right_sigma,
+            &selectors.out_sigma,
+            &selectors.fourth_sigma,
+        ];
+        let coset_fft = |poly: &&DensePolynomial<E::Fr>| {
+            Evaluations::from_vec_and_domain(
+                fft.coset_fft(&domain_4n, poly),
+                domain_4n,
+            )
+        };
+        // The coset FFTs of the polynomials are independent of each other
+        #[cfg(feature = "parallel")]
+        let evals: Vec<_> = polys.par_iter().map(coset_fft).collect();
+        #[cfg(not(feature = "parallel"))]
+        let evals: Vec<_> = polys.iter().map(coset_fft).collect();
+        let [
+            q_m_eval_4n,
+            q_l_eval_4n,
+            q_r_eval_4n,
+            q_o_eval_4n,
+            q_c_eval_4n,
+            q_4_eval_4n,
+            q_arith_eval_4n,
+            q_range_eval_4n,
+            q_logic_eval_4n,
+            q_fixed_group_add_eval_4n,
+            q_variable_group_add_eval_4n,
+            q_lookahead_eval_4n,
+            left_sigma_eval_4n,
+            right_sigma_eval_4n,
+            out_sigma_eval_4n,
+            fourth_sigma_eval_4n,
+        ]: [_; 16] = match evals.try_into() {
+            Ok(evals) => evals,
+            Err(_) => unreachable!(),
+        };
 
-        let left_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.left_sigma),
-            domain_4n,
-        );
-        let right_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.right_sigma),
-            domain_4n,
-        );
-        let out_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.out_sigma),
-            domain_4n,
-        );
-        let fourth_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&selectors.fourth_sigma),
-            domain_4n,
-        );
-        // XXX: Remove this and compute it on the fly
-        let linear_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&[E::Fr::zero(), E::Fr::one()]),
+        // Compute 4n evaluations for X^n -1
+        let v_h_coset_4n = util::compute_vanishing_poly_over_coset(
             domain_4n,
+            domain.size() as u64,
         );
 
-        // Compute 4n evaluations for X^n -1
-        let v_h_coset_4n =
-            compute_vanishing_poly_over_coset(domain_4n, domain.size() as u64);
-
-        Ok(ProverKey::from_polynomials_and_evals(
+        let mut prover_key = ProverKey::from_polynomials_and_evals(
             domain.size(),
             (selectors.q_m, q_m_eval_4n),
             (selectors.q_l, q_l_eval_4n),
@@ -199,6 +221,7 @@ where
             (selectors.q_4, q_4_eval_4n),
             (selectors.q_c, q_c_eval_4n),
             (selectors.q_arith, q_arith_eval_4n),
+            (selectors.q_lookahead, q_lookahead_eval_4n),
             (selectors.q_range, q_range_eval_4n),
             (selectors.q_logic, q_logic_eval_4n),
             (selectors.q_fixed_group_add, q_fixed_group_add_eval_4n),
@@ -207,9 +230,10 @@ where
             (selectors.right_sigma, right_sigma_eval_4n),
             (selectors.out_sigma, out_sigma_eval_4n),
             (selectors.fourth_sigma, fourth_sigma_eval_4n),
-            linear_eval_4n,
             v_h_coset_4n,
-        ))
+        );
+        prover_key.vk_digest = widget::Bytes32(verifier_key.digest());
+        Ok(prover_key)
     }
 
     /// The verifier only requires the commitments in order to verify a
@@ -219,9 +243,24 @@ where
         &mut self,
         commit_key: &Powers<E>,
         transcript: &mut TranscriptWrapper<E>,
+    ) -> Result<widget::VerifierKey<E, P>, Error> {
+        self.preprocess_verifier_with_backend(
+            commit_key,
+            transcript,
+            &Backend::default(),
+        )
+    }
+
+    /// Runs [`StandardComposer::preprocess_verifier`] with the MSMs and FFTs
+    /// computed by `backend`.
+    pub fn preprocess_verifier_with_backend(
+        &mut self,
+        commit_key: &Powers<E>,
+        transcript: &mut TranscriptWrapper<E>,
+        backend: &Backend<E>,
     ) -> Result<widget::VerifierKey<E, P>, Error> {
         let (verifier_key, _, _) =
-            self.preprocess_shared(commit_key, transcript)?;
+            self.preprocess_shared(commit_key, transcript, backend)?;
         Ok(verifier_key)
     }
 
@@ -234,6 +273,7 @@ where
         &mut self,
         commit_key: &Powers<E>,
         transcript: &mut TranscriptWrapper<E>,
+        backend: &Backend<E>,
     ) -> Result<
         (
             widget::VerifierKey<E, P>,
@@ -242,48 +282,48 @@ where
         ),
         Error,
     > {
-        let domain = GeneralEvaluationDomain::new(self.circuit_size()).unwrap();
+        let domain = util::evaluation_domain(self.circuit_size())?;
 
         // Check that the length of the wires is consistent.
         self.check_poly_same_len()?;
+        self.check_lookahead_wires()?;
 
         // 1. Pad circuit to a power of two
         self.pad(domain.size() as usize - self.n);
 
-        let q_m_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_m),
-        };
-        let q_r_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_r),
-        };
-        let q_l_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_l),
-        };
-        let q_o_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_o),
-        };
-        let q_c_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_c),
-        };
-        let q_4_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_4),
-        };
-        let q_arith_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_arith),
-        };
-        let q_range_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_range),
-        };
-        let q_logic_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_logic),
-        };
-        let q_fixed_group_add_poly: DensePolynomial<E::Fr> = DensePolynomial {
-            coeffs: domain.ifft(&self.q_fixed_group_add),
-        };
+        let q_m_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(backend.fft().ifft(&domain, &self.q_m));
+        let q_r_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(backend.fft().ifft(&domain, &self.q_r));
+        let q_l_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(backend.fft().ifft(&domain, &self.q_l));
+        let q_o_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(backend.fft().ifft(&domain, &self.q_o));
+        let q_c_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(backend.fft().ifft(&domain, &self.q_c));
+        let q_4_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(backend.fft().ifft(&domain, &self.q_4));
+        let q_arith_poly: DensePolynomial<E::Fr> = util::normalized_polynomial(
+            backend.fft().ifft(&domain, &self.q_arith),
+        );
+        let q_range_poly: DensePolynomial<E::Fr> = util::normalized_polynomial(
+            backend.fft().ifft(&domain, &self.q_range),
+        );
+        let q_logic_poly: DensePolynomial<E::Fr> = util::normalized_polynomial(
+            backend.fft().ifft(&domain, &self.q_logic),
+        );
+        let q_fixed_group_add_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(
+                backend.fft().ifft(&domain, &self.q_fixed_group_add),
+            );
         let q_variable_group_add_poly: DensePolynomial<E::Fr> =
-            DensePolynomial {
-                coeffs: domain.ifft(&self.q_variable_group_add),
-            };
+            util::normalized_polynomial(
+                backend.fft().ifft(&domain, &self.q_variable_group_add),
+            );
+        let q_lookahead_poly: DensePolynomial<E::Fr> =
+            util::normalized_polynomial(
+                backend.fft().ifft(&domain, &self.q_lookahead),
+            );
 
         // 2. Compute the sigma polynomials
         let (
@@ -291,117 +331,67 @@ where
             right_sigma_poly,
             out_sigma_poly,
             fourth_sigma_poly,
-        ) = self.perm.compute_sigma_polynomials(self.n, &domain);
+        ) = self
+            .perm
+            .compute_sigma_polynomials(self.n, &domain, backend.fft());
 
-        let q_m_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &q_m_poly, None, None,
-        )?;
+        let q_m_poly_commit = backend.commit(commit_key, &q_m_poly)?;
 
-        let q_l_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &q_l_poly, None, None,
-        )?;
+        let q_l_poly_commit = backend.commit(commit_key, &q_l_poly)?;
 
-        let q_r_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &q_r_poly, None, None,
-        )?;
+        let q_r_poly_commit = backend.commit(commit_key, &q_r_poly)?;
 
-        let q_o_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &q_o_poly, None, None,
-        )?;
+        let q_o_poly_commit = backend.commit(commit_key, &q_o_poly)?;
 
-        let q_c_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &q_c_poly, None, None,
-        )?;
+        let q_c_poly_commit = backend.commit(commit_key, &q_c_poly)?;
 
-        let q_4_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &q_4_poly, None, None,
-        )?;
+        let q_4_poly_commit = backend.commit(commit_key, &q_4_poly)?;
 
-        let q_arith_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key,
-            &q_arith_poly,
-            None,
-            None,
-        )?;
+        let q_arith_poly_commit = backend.commit(commit_key, &q_arith_poly)?;
 
-        let q_range_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key,
-            &q_range_poly,
-            None,
-            None,
-        )?;
+        let q_lookahead_poly_commit =
+            backend.commit(commit_key, &q_lookahead_poly)?;
 
-        let q_logic_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key,
-            &q_logic_poly,
-            None,
-            None,
-        )?;
+        let q_range_poly_commit = backend.commit(commit_key, &q_range_poly)?;
+
+        let q_logic_poly_commit = backend.commit(commit_key, &q_logic_poly)?;
 
         let q_fixed_group_add_poly_commit =
-            KZG10::<E, DensePolynomial<E::Fr>>::commit(
-                commit_key,
-                &q_fixed_group_add_poly,
-                None,
-                None,
-            )?;
+            backend.commit(commit_key, &q_fixed_group_add_poly)?;
 
         let q_variable_group_add_poly_commit =
-            KZG10::<E, DensePolynomial<E::Fr>>::commit(
-                commit_key,
-                &q_variable_group_add_poly,
-                None,
-                None,
-            )?;
+            backend.commit(commit_key, &q_variable_group_add_poly)?;
 
         let left_sigma_poly_commit =
-            KZG10::<E, DensePolynomial<E::Fr>>::commit(
-                commit_key,
-                &left_sigma_poly,
-                None,
-                None,
-            )?;
+            backend.commit(commit_key, &left_sigma_poly)?;
 
         let right_sigma_poly_commit =
-            KZG10::<E, DensePolynomial<E::Fr>>::commit(
-                commit_key,
-                &right_sigma_poly,
-                None,
-                None,
-            )?;
-
-        let out_sigma_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key,
-            &out_sigma_poly,
-            None,
-            None,
-        )?;
+            backend.commit(commit_key, &right_sigma_poly)?;
+
+        let out_sigma_poly_commit =
+            backend.commit(commit_key, &out_sigma_poly)?;
 
         let fourth_sigma_poly_commit =
-            KZG10::<E, DensePolynomial<E::Fr>>::commit(
-                commit_key,
-                &fourth_sigma_poly,
-                None,
-                None,
-            )?;
+            backend.commit(commit_key, &fourth_sigma_poly)?;
 
         let verifier_key = widget::VerifierKey::from_polynomial_commitments(
             self.circuit_size(),
-            q_m_poly_commit.0,
-            q_l_poly_commit.0,
-            q_r_poly_commit.0,
-            q_o_poly_commit.0,
-            q_4_poly_commit.0,
-            q_c_poly_commit.0,
-            q_arith_poly_commit.0,
-            q_range_poly_commit.0,
-            q_logic_poly_commit.0,
-            q_fixed_group_add_poly_commit.0,
-            q_variable_group_add_poly_commit.0,
-            left_sigma_poly_commit.0,
-            right_sigma_poly_commit.0,
-            out_sigma_poly_commit.0,
-            fourth_sigma_poly_commit.0,
+            q_m_poly_commit,
+            q_l_poly_commit,
+            q_r_poly_commit,
+            q_o_poly_commit,
+            q_4_poly_commit,
+            q_c_poly_commit,
+            q_arith_poly_commit,
+            q_lookahead_poly_commit,
+            q_range_poly_commit,
+            q_logic_poly_commit,
+            q_fixed_group_add_poly_commit,
+            q_variable_group_add_poly_commit,
+            left_sigma_poly_commit,
+            right_sigma_poly_commit,
+            out_sigma_poly_commit,
+            fourth_sigma_poly_commit,
         );
 
         let selectors = SelectorPolynomials {
@@ -416,6 +406,7 @@ where
             q_logic: q_logic_poly,
             q_fixed_group_add: q_fixed_group_add_poly,
             q_variable_group_add: q_variable_group_add_poly,
+            q_lookahead: q_lookahead_poly,
             left_sigma: left_sigma_poly,
             right_sigma: right_sigma_poly,
             out_sigma: out_sigma_poly,
@@ -429,40 +420,18 @@ where
     }
 }
 
-/// Given that the domain size is `D`
-/// This function computes the `D` evaluation points for
-/// the vanishing polynomial of degree `n` over a coset
-pub fn compute_vanishing_poly_over_coset<F, D>(
-    domain: D,        // domain to evaluate over
-    poly_degree: u64, // degree of the vanishing polynomial
-) -> Evaluations<F, D>
-where
-    F: PrimeField,
-    D: EvaluationDomain<F>,
-{
-    assert!(
-        (domain.size() as u64) > poly_degree,
-        "domain_size = {}, poly_degree = {}",
-        domain.size() as u64,
-        poly_degree
-    );
-    let group_gen = domain.element(1);
-    let coset_gen = F::multiplicative_generator().pow(&[poly_degree, 0, 0, 0]);
-    let v_h: Vec<_> = (0..domain.size())
-        .map(|i| {
-            (coset_gen * group_gen.pow(&[poly_degree * i as u64, 0, 0, 0]))
-                - F::one()
-        })
-        .collect();
-    Evaluations::from_vec_and_domain(v_h, domain)
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::circuit::trim_commit_key;
     use crate::{batch_test, constraint_system::helper::*};
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::Polynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use num_traits::One;
+    use rand_core::OsRng;
 
     /// Tests that the circuit gets padded to the correct length.
     // FIXME: We can do this test without dummy_gadget method.
@@ -490,14 +459,45 @@ mod test {
         assert!(composer.q_logic.len() == size);
         assert!(composer.q_fixed_group_add.len() == size);
         assert!(composer.q_variable_group_add.len() == size);
+        assert!(composer.q_lookahead.len() == size);
         assert!(composer.w_l.len() == size);
         assert!(composer.w_r.len() == size);
         assert!(composer.w_o.len() == size);
     }
 
+    /// Tests that the preprocessed selectors have no leading zero
+    /// coefficients, even when their interpolation has some.
+    fn test_normalized_selectors<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let mut composer: StandardComposer<E, P> = StandardComposer::new();
+        dummy_gadget(100, &mut composer);
+        let size = composer.n.next_power_of_two();
+        composer.pad(size - composer.n);
+
+        // A constant selector interpolates to a constant polynomial
+        composer.q_lookahead = vec![E::Fr::one(); size];
+
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(size, false, &mut OsRng)
+                .unwrap();
+        let commit_key = trim_commit_key(&pp, size).unwrap();
+        let (_, selectors, _) = composer
+            .preprocess_shared(
+                &commit_key,
+                &mut TranscriptWrapper::new(b"test"),
+                &Backend::default(),
+            )
+            .unwrap();
+        assert_eq!(selectors.q_lookahead.coeffs, vec![E::Fr::one()]);
+        assert_eq!(selectors.q_lookahead.degree(), 0);
+    }
+
     // Bls12-381 tests
     batch_test!(
-        [test_pad],
+        [test_pad, test_normalized_selectors],
         [] => (
             Bls12_381,
             ark_ed_on_bls12_381::EdwardsParameters
@@ -506,10 +506,19 @@ mod test {
 
     // Bls12-377 tests
     batch_test!(
-        [test_pad],
+        [test_pad, test_normalized_selectors],
         [] => (
             Bls12_377,
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Bn254 tests
+    batch_test!(
+        [test_pad, test_normalized_selectors],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
@@ -24,15 +24,147 @@ use crate::{error::Error, transcript::TranscriptWrapper};
 use ark_ec::{msm::VariableBaseMSM, AffineCurve, TEModelParameters};
 use ark_ec::{PairingEngine, ProjectiveCurve};
 use ark_ff::{fields::batch_inversion, Field, PrimeField};
-use ark_poly::univariate::DensePolynomial;
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
-use ark_poly_commit::kzg10;
-use ark_poly_commit::kzg10::{Commitment, VerifierKey, KZG10};
+use ark_poly_commit::kzg10::{Commitment, VerifierKey};
 use ark_serialize::{
     CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
 };
 use core::marker::PhantomData;
-use rand_core::OsRng;
+use num_traits::{One, Zero};
+
+/// Format of a [`Proof`], which determines how the evaluations it carries are
+/// opened.
+///
+/// Serialized proofs start with the tag of their version, so that proofs of
+/// every version can be read. Only [`ProofVersion::LATEST`] proofs are
+/// generated and verified.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ProofVersion {
+    /// Separate KZG10 openings at the evaluation challenge `z` and at its
+    /// shift `z * omega`, checked by a batched pairing check.
+    ///
+    /// These proofs don't open the evaluations of the arithmetic selectors,
+    /// so they are no longer generated nor verified. They can still be read
+    /// to be inspected.
+    V1,
+
+    /// A single combined opening at both points (SHPLONK). The evaluation of
+    /// the linearisation polynomial is folded into the opening of the
+    /// quotient, so it is not part of the proof, and the selector
+    /// evaluations are opened together with the other polynomials.
+    V2,
+}
+
+impl ProofVersion {
+    /// Version of the proofs generated by default.
+    pub const LATEST: Self = Self::V2;
+
+    /// Returns the tag the version is serialized as.
+    fn tag(self) -> u8 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    /// Returns the version serialized as `tag`, if any.
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// Appends the version to `transcript`, so that the challenges of a
+    /// proof depend on the version it is verified as.
+    pub(crate) fn bind_transcript<E>(
+        self,
+        transcript: &mut TranscriptWrapper<E>,
+    ) where
+        E: PairingEngine,
+    {
+        transcript.append_message(b"dom-sep", b"proof_version");
+        transcript.append_message(b"version", &[self.tag()]);
+    }
+}
+
+impl Default for ProofVersion {
+    #[inline]
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
+
+/// Encoding of the points of a serialized [`Proof`] or
+/// [`VerifierKey`](PlonkVerifierKey).
+///
+/// Compressed points only store their abscissa, and take about half the
+/// space of uncompressed ones, but decoding them takes a square root. They
+/// suit verifiers paying for bytes, such as on-chain ones, while uncompressed
+/// points suit high-throughput verifiers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PointEncoding {
+    /// Compressed points, checked when read
+    Compressed,
+
+    /// Uncompressed points, checked when read
+    Uncompressed,
+
+    /// Uncompressed points, not checked when read. Only meant for data from
+    /// trusted storage.
+    Unchecked,
+}
+
+impl Default for PointEncoding {
+    #[inline]
+    fn default() -> Self {
+        Self::Compressed
+    }
+}
+
+impl PointEncoding {
+    /// Serializes `item` in the encoding.
+    pub(crate) fn write<T, W>(
+        self,
+        item: &T,
+        writer: W,
+    ) -> Result<(), SerializationError>
+    where
+        T: CanonicalSerialize,
+        W: Write,
+    {
+        match self {
+            Self::Compressed => item.serialize(writer),
+            Self::Uncompressed => item.serialize_uncompressed(writer),
+            Self::Unchecked => item.serialize_unchecked(writer),
+        }
+    }
+
+    /// Returns the size of `item` serialized in the encoding.
+    pub(crate) fn size<T>(self, item: &T) -> usize
+    where
+        T: CanonicalSerialize,
+    {
+        match self {
+            Self::Compressed => item.serialized_size(),
+            Self::Uncompressed | Self::Unchecked => item.uncompressed_size(),
+        }
+    }
+
+    /// Deserializes an item serialized in the encoding.
+    pub(crate) fn read<T, R>(self, reader: R) -> Result<T, SerializationError>
+    where
+        T: CanonicalDeserialize,
+        R: Read,
+    {
+        match self {
+            Self::Compressed => T::deserialize(reader),
+            Self::Uncompressed => T::deserialize_uncompressed(reader),
+            Self::Unchecked => T::deserialize_unchecked(reader),
+        }
+    }
+}
 
 /// A Proof is a composition of `Commitment`s to the Witness, Permutation,
 /// Quotient, Shifted and Opening polynomials as well as the
@@ -44,7 +176,7 @@ use rand_core::OsRng;
 /// [`Verifier`](super::Verifier) have in common succintly and without any
 /// capabilities of adquiring any kind of knowledge about the witness used to
 /// construct the Proof.
-#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derive(derivative::Derivative)]
 #[derivative(
     Clone(bound = ""),
     Debug(bound = ""),
@@ -57,6 +189,9 @@ where
     E: PairingEngine,
     P: TEModelParameters<BaseField = E::Fr>,
 {
+    /// Format of the proof.
+    pub(crate) version: ProofVersion,
+
     /// Commitment to the witness polynomial for the left wires.
     pub(crate) a_comm: Commitment<E>,
 
@@ -85,36 +220,388 @@ where
     pub(crate) t_4_comm: Commitment<E>,
 
     /// Commitment to the opening proof polynomial.
+    ///
+    /// For [`ProofVersion::V2`] proofs, commitment to the combined quotient
+    /// of the openings at both points.
     pub(crate) w_z_comm: Commitment<E>,
 
     /// Commitment to the shifted opening proof polynomial.
+    ///
+    /// For [`ProofVersion::V2`] proofs, commitment to the witness of the
+    /// combined opening at the final challenge.
     pub(crate) w_zw_comm: Commitment<E>,
 
     /// Subset of all of the evaluations added to the proof.
+    ///
+    /// [`ProofVersion::V2`] proofs don't carry the evaluation of the
+    /// linearisation polynomial, which is left to zero.
     pub(crate) evaluations: ProofEvaluations<E::Fr>,
 
     /// Type Parameter Marker
     pub(crate) __: PhantomData<P>,
 }
 
+/// Challenges and intermediate values derived by the verifier while checking
+/// a [`Proof`].
+///
+/// Since every challenge depends on all the previous transcript messages, the
+/// first diverging value between the traces computed by two parties points at
+/// the first message they disagree on.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct VerificationTrace<E>
+where
+    E: PairingEngine,
+{
+    /// Version of the verified proof.
+    pub version: ProofVersion,
+
+    /// Permutation challenge `beta`.
+    pub beta: E::Fr,
+
+    /// Permutation challenge `gamma`.
+    pub gamma: E::Fr,
+
+    /// Quotient challenge `alpha`.
+    pub alpha: E::Fr,
+
+    /// Range gate separation challenge.
+    pub range_sep_challenge: E::Fr,
+
+    /// Logic gate separation challenge.
+    pub logic_sep_challenge: E::Fr,
+
+    /// Fixed base scalar multiplication separation challenge.
+    pub fixed_base_sep_challenge: E::Fr,
+
+    /// Variable base scalar multiplication separation challenge.
+    pub var_base_sep_challenge: E::Fr,
+
+    /// Evaluation point challenge `z`.
+    pub z_challenge: E::Fr,
+
+    /// Aggregation challenge `v` for the opening at `z`.
+    pub v_challenge: E::Fr,
+
+    /// Aggregation challenge `u` for the opening at `z * omega`.
+    pub u_challenge: E::Fr,
+
+    /// Challenge separating the openings at `z` and `z * omega`.
+    pub opening_sep_challenge: E::Fr,
+
+    /// Point the combined opening is checked at.
+    pub opening_challenge: E::Fr,
+
+    /// Vanishing polynomial evaluated at `z`.
+    pub z_h_eval: E::Fr,
+
+    /// First lagrange polynomial evaluated at `z`.
+    pub l1_eval: E::Fr,
+
+    /// Reconstructed commitment to the quotient polynomial.
+    pub t_comm: Commitment<E>,
+
+    /// Reconstructed commitment to the linearisation polynomial.
+    pub r_comm: Commitment<E>,
+
+    /// Aggregated commitment opened at `z`, where the quotient and
+    /// linearisation commitments are aggregated as `[r]_1 - Z_H(z) * [t]_1`.
+    pub aggregate_proof_comm: Commitment<E>,
+
+    /// Aggregated evaluation at `z`.
+    pub aggregate_proof_eval: E::Fr,
+
+    /// Aggregated commitment opened at `z * omega`.
+    pub aggregate_shift_proof_comm: Commitment<E>,
+
+    /// Aggregated evaluation at `z * omega`.
+    pub aggregate_shift_proof_eval: E::Fr,
+
+    /// Result of the final pairing check.
+    pub pairing_check: bool,
+}
+
+/// Sizes of a serialized [`Proof`], see [`Proof::serialized_size_hints`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SerializedSizeHints {
+    /// Bytes of a proof serialized with compressed points
+    pub compressed: usize,
+
+    /// Bytes of a proof serialized with uncompressed points
+    pub uncompressed: usize,
+}
+
 impl<E, P> Proof<E, P>
 where
     E: PairingEngine,
     P: TEModelParameters<BaseField = E::Fr>,
 {
+    /// Returns the format of the proof.
+    pub fn version(&self) -> ProofVersion {
+        self.version
+    }
+
+    /// Deserializes a [`ProofVersion::V1`] proof serialized before proofs
+    /// were tagged with their version.
+    ///
+    /// Such proofs were generated by earlier versions of the crate. They can
+    /// be read to be inspected, but they aren't verified and have to be
+    /// generated again.
+    pub fn deserialize_unversioned<R>(
+        reader: R,
+    ) -> Result<Self, SerializationError>
+    where
+        R: Read,
+    {
+        Self::read_body(ProofVersion::V1, reader, PointEncoding::Compressed)
+    }
+
+    /// Deserializes a proof from an untrusted source, checking that every
+    /// commitment is on the curve and in its prime order subgroup.
+    ///
+    /// Proofs read from trusted storage can skip these checks with
+    /// [`CanonicalDeserialize::deserialize_unchecked`]. The verifier checks
+    /// the commitments of every proof anyway, see
+    /// [`Proof::check_commitments`].
+    pub fn deserialize_checked<R>(reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        Self::deserialize_with_encoding(reader, PointEncoding::Compressed)
+    }
+
+    /// Serializes the proof with its points in `encoding`.
+    pub fn serialize_with_encoding<W>(
+        &self,
+        writer: W,
+        encoding: PointEncoding,
+    ) -> Result<(), SerializationError>
+    where
+        W: Write,
+    {
+        encoding.write(self, writer)
+    }
+
+    /// Returns the size of the proof serialized with its points in
+    /// `encoding`.
+    pub fn serialized_size_with_encoding(
+        &self,
+        encoding: PointEncoding,
+    ) -> usize {
+        encoding.size(self)
+    }
+
+    /// Deserializes a proof whose points are in `encoding`.
+    ///
+    /// Unless the encoding is [`PointEncoding::Unchecked`], the commitments
+    /// are checked as in [`Proof::deserialize_checked`].
+    pub fn deserialize_with_encoding<R>(
+        reader: R,
+        encoding: PointEncoding,
+    ) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let proof: Self = encoding.read(reader)?;
+        if encoding != PointEncoding::Unchecked {
+            proof.check_commitments()?;
+        }
+        Ok(proof)
+    }
+
+    /// Checks that every commitment of the proof is on the curve and in its
+    /// prime order subgroup, failing with [`Error::InvalidCommitment`]
+    /// otherwise.
+    ///
+    /// Verifying a proof with a commitment outside of the subgroup is
+    /// unsound, so the verifier runs this check before anything else.
+    pub fn check_commitments(&self) -> Result<(), Error> {
+        check_commitments(&self.commitments())
+    }
+
+    /// Returns the commitments of the proof, in their serialization order.
+    fn commitments(&self) -> [&Commitment<E>; 11] {
+        [
+            &self.a_comm,
+            &self.b_comm,
+            &self.c_comm,
+            &self.d_comm,
+            &self.z_comm,
+            &self.t_1_comm,
+            &self.t_2_comm,
+            &self.t_3_comm,
+            &self.t_4_comm,
+            &self.w_z_comm,
+            &self.w_zw_comm,
+        ]
+    }
+
+    /// Writes the proof without its version tag.
+    fn write_body<W>(
+        &self,
+        mut writer: W,
+        encoding: PointEncoding,
+    ) -> Result<(), SerializationError>
+    where
+        W: Write,
+    {
+        for commitment in self.commitments() {
+            encoding.write(commitment, &mut writer)?;
+        }
+        match self.version {
+            ProofVersion::V1 => encoding.write(&self.evaluations, writer),
+            ProofVersion::V2 => {
+                for eval in v2_evaluations(&self.evaluations) {
+                    encoding.write(&eval, &mut writer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the size of the proof without its version tag.
+    fn body_size(&self, encoding: PointEncoding) -> usize {
+        let commitments = self
+            .commitments()
+            .iter()
+            .map(|commitment| encoding.size(*commitment))
+            .sum::<usize>();
+        let evaluations = match self.version {
+            ProofVersion::V1 => encoding.size(&self.evaluations),
+            ProofVersion::V2 => v2_evaluations(&self.evaluations)
+                .iter()
+                .map(|eval| encoding.size(eval))
+                .sum(),
+        };
+        commitments + evaluations
+    }
+
+    /// Reads a proof of `version` without its version tag.
+    fn read_body<R>(
+        version: ProofVersion,
+        mut reader: R,
+        encoding: PointEncoding,
+    ) -> Result<Self, SerializationError>
+    where
+        R: Read,
+    {
+        let a_comm = encoding.read(&mut reader)?;
+        let b_comm = encoding.read(&mut reader)?;
+        let c_comm = encoding.read(&mut reader)?;
+        let d_comm = encoding.read(&mut reader)?;
+        let z_comm = encoding.read(&mut reader)?;
+        let t_1_comm = encoding.read(&mut reader)?;
+        let t_2_comm = encoding.read(&mut reader)?;
+        let t_3_comm = encoding.read(&mut reader)?;
+        let t_4_comm = encoding.read(&mut reader)?;
+        let w_z_comm = encoding.read(&mut reader)?;
+        let w_zw_comm = encoding.read(&mut reader)?;
+        let evaluations = match version {
+            ProofVersion::V1 => encoding.read(&mut reader)?,
+            ProofVersion::V2 => {
+                let mut evals = [E::Fr::zero(); 15];
+                for eval in evals.iter_mut() {
+                    *eval = encoding.read(&mut reader)?;
+                }
+                from_v2_evaluations(evals)
+            }
+        };
+        Ok(Self {
+            version,
+            a_comm,
+            b_comm,
+            c_comm,
+            d_comm,
+            z_comm,
+            t_1_comm,
+            t_2_comm,
+            t_3_comm,
+            t_4_comm,
+            w_z_comm,
+            w_zw_comm,
+            evaluations,
+            __: PhantomData,
+        })
+    }
+
+    /// Reads a proof starting with its version tag.
+    fn read_versioned<R>(
+        mut reader: R,
+        encoding: PointEncoding,
+    ) -> Result<Self, SerializationError>
+    where
+        R: Read,
+    {
+        let version = ProofVersion::from_tag(u8::deserialize(&mut reader)?)
+            .ok_or(SerializationError::InvalidData)?;
+        Self::read_body(version, reader, encoding)
+    }
+
+    /// Returns the sizes of a serialized proof. They only depend on the
+    /// pairing engine, so they are known before any proof is generated.
+    pub fn serialized_size_hints() -> SerializedSizeHints {
+        let proof = Self::default();
+        SerializedSizeHints {
+            compressed: proof.serialized_size(),
+            uncompressed: proof.uncompressed_size(),
+        }
+    }
+
     /// Performs the verification of a [`Proof`] returning a boolean result.
+    ///
+    /// The `domain` must be the evaluation domain of the circuit
+    /// `plonk_verifier_key` was generated for. [`ProofVersion::V1`] proofs
+    /// are rejected.
     pub(crate) fn verify(
         &self,
         plonk_verifier_key: &PlonkVerifierKey<E, P>,
+        domain: &GeneralEvaluationDomain<E::Fr>,
         transcript: &mut TranscriptWrapper<E>,
         verifier_key: &VerifierKey<E>,
         pub_inputs: &[E::Fr],
     ) -> Result<(), Error> {
-        let domain =
-            GeneralEvaluationDomain::<E::Fr>::new(plonk_verifier_key.n)
-                .unwrap();
+        let trace = self.verification_trace(
+            plonk_verifier_key,
+            domain,
+            transcript,
+            verifier_key,
+            pub_inputs,
+        )?;
+        if trace.pairing_check {
+            Ok(())
+        } else {
+            Err(Error::PairingCheckFailure)
+        }
+    }
+
+    /// Runs the verification of a [`Proof`] returning all the challenges and
+    /// intermediate values computed along the way.
+    pub(crate) fn verification_trace(
+        &self,
+        plonk_verifier_key: &PlonkVerifierKey<E, P>,
+        domain: &GeneralEvaluationDomain<E::Fr>,
+        transcript: &mut TranscriptWrapper<E>,
+        verifier_key: &VerifierKey<E>,
+        pub_inputs: &[E::Fr],
+    ) -> Result<VerificationTrace<E>, Error> {
+        // The version is chosen by the prover, and V1 proofs don't open the
+        // evaluations of the arithmetic selectors
+        if self.version != ProofVersion::LATEST {
+            return Err(Error::LegacyProofRejected);
+        }
 
-        // Subgroup checks are done when the proof is deserialised.
+        // Proofs may have been deserialized without subgroup checks
+        self.check_commitments()?;
+
+        // Bind the proof to the circuit the key was generated for, and to
+        // the version it is verified as
+        plonk_verifier_key.bind_transcript(transcript);
+        self.version.bind_transcript(transcript);
 
         // In order for the Verifier and Prover to have the same view in the
         // non-interactive setting Both parties must commit the same
@@ -164,17 +651,17 @@ where
 
         // Compute first lagrange polynomial evaluated at `z_challenge`
         let l1_eval =
-            compute_first_lagrange_evaluation(&domain, &z_h_eval, &z_challenge);
+            compute_first_lagrange_evaluation(domain, &z_h_eval, &z_challenge);
 
-        // Compute quotient polynomial evaluated at `z_challenge`
-        let t_eval = self.compute_quotient_evaluation(
-            &domain,
+        // Compute `r(z) - Z_H(z) * t(z)`, which only depends on the
+        // challenges and the other evaluations
+        let quotient_offset = self.compute_quotient_offset(
+            domain,
             pub_inputs,
             alpha,
             beta,
             gamma,
             z_challenge,
-            z_h_eval,
             l1_eval,
             self.evaluations.permutation_eval,
         );
@@ -208,11 +695,6 @@ where
         transcript.append_scalar(b"q_r_eval", &self.evaluations.q_r_eval);
         transcript
             .append_scalar(b"perm_eval", &self.evaluations.permutation_eval);
-        transcript.append_scalar(b"t_eval", &t_eval);
-        transcript.append_scalar(
-            b"r_eval",
-            &self.evaluations.linearisation_polynomial_eval,
-        );
 
         // Compute linearisation commitment
         let r_comm = self.compute_linearisation_commitment(
@@ -229,77 +711,101 @@ where
         );
 
         // Commitment Scheme
-        // Now we delegate computation to the commitment scheme by batch
-        // checking two proofs.
+        // Now we delegate computation to the commitment scheme by checking
+        // the openings of two aggregated polynomials.
         //
-        // The `AggregateProof`, which proves that all the necessary
-        // polynomials evaluated at `z_challenge` are
-        // correct and a `Proof` which is proof that the
-        // permutation polynomial evaluated at the shifted root of unity is
-        // correct
-
-        // Generation of the first aggregated proof: It ensures that the
-        // polynomials evaluated at `z_challenge` are correct.
-
-        // Reconstruct the Aggregated Proof commitments and evals
-        // The proof consists of the witness commitment with no blinder
+        // The first one proves that all the necessary polynomials evaluated
+        // at `z_challenge` are correct and the second one that the
+        // permutation polynomial and the shifted wires evaluated at the
+        // shifted root of unity are correct.
+
+        // Reconstruct the aggregated commitments and evals
+        // The proofs consist of the witness commitments with no blinder
+        let v_challenge = transcript.challenge_scalar(b"aggregate_witness");
         let (aggregate_proof_commitment, aggregate_proof_eval) = self
-            .gen_aggregate_proof(
-                t_eval,
+            .gen_combined_aggregate_proof(
+                quotient_offset,
+                z_h_eval,
                 t_comm,
                 r_comm,
                 plonk_verifier_key,
-                transcript,
+                v_challenge,
             );
-        let aggregate_proof = kzg10::Proof {
-            w: self.w_z_comm.0,
-            random_v: None,
-        };
 
-        // Reconstruct the Aggregated Shift Proof commitments and evals
-        // The proof consists of the witness commitment with no blinder
+        let u_challenge = transcript.challenge_scalar(b"aggregate_witness");
         let (aggregate_shift_proof_commitment, aggregate_shift_proof_eval) =
-            self.gen_shift_aggregate_proof(transcript);
+            self.gen_shift_aggregate_proof(u_challenge);
 
-        let aggregate_shift_proof = kzg10::Proof {
-            w: self.w_zw_comm.0,
-            random_v: None,
-        };
+        let shifted_z_challenge = z_challenge * domain.group_gen();
 
-        // Add commitment to openings to transcript
+        let opening_sep_challenge =
+            transcript.challenge_scalar(b"opening separation challenge");
         transcript.append_commitment(b"w_z", &self.w_z_comm);
+        let opening_challenge =
+            transcript.challenge_scalar(b"opening challenge");
         transcript.append_commitment(b"w_z_w", &self.w_zw_comm);
 
-        let group_gen = domain.group_gen();
-
-        match KZG10::<_, DensePolynomial<_>>::batch_check(
+        let pairing_check = self.check_combined_opening(
             verifier_key,
-            &[aggregate_proof_commitment, aggregate_shift_proof_commitment],
-            &[z_challenge, (z_challenge * group_gen)],
-            &[aggregate_proof_eval, aggregate_shift_proof_eval],
-            &[aggregate_proof, aggregate_shift_proof],
-            &mut OsRng,
-        ) {
-            Ok(true) => Ok(()),
-            Ok(false) => Err(Error::ProofVerificationError),
-            Err(e) => panic!("{:?}", e),
-        }
+            (
+                z_challenge,
+                aggregate_proof_commitment,
+                aggregate_proof_eval,
+            ),
+            (
+                shifted_z_challenge,
+                aggregate_shift_proof_commitment,
+                aggregate_shift_proof_eval,
+            ),
+            opening_sep_challenge,
+            opening_challenge,
+        );
+
+        Ok(VerificationTrace {
+            version: self.version,
+            beta,
+            gamma,
+            alpha,
+            range_sep_challenge,
+            logic_sep_challenge,
+            fixed_base_sep_challenge,
+            var_base_sep_challenge,
+            z_challenge,
+            v_challenge,
+            u_challenge,
+            opening_sep_challenge,
+            opening_challenge,
+            z_h_eval,
+            l1_eval,
+            t_comm,
+            r_comm,
+            aggregate_proof_comm: aggregate_proof_commitment,
+            aggregate_proof_eval,
+            aggregate_shift_proof_comm: aggregate_shift_proof_commitment,
+            aggregate_shift_proof_eval,
+            pairing_check,
+        })
     }
 
-    // TODO: Doc this
-    fn gen_aggregate_proof(
+    /// Aggregates the commitments and evaluations opened at `z`, where the
+    /// linearisation and quotient polynomials are opened together as
+    /// `r(X) - Z_H(z) * t(X)`.
+    fn gen_combined_aggregate_proof(
         &self,
-        t_eval: E::Fr,
+        quotient_offset: E::Fr,
+        z_h_eval: E::Fr,
         t_comm: Commitment<E>,
         r_comm: Commitment<E>,
         plonk_verifier_key: &PlonkVerifierKey<E, P>,
-        transcript: &mut TranscriptWrapper<E>,
+        challenge: E::Fr,
     ) -> (Commitment<E>, E::Fr) {
-        let challenge = transcript.challenge_scalar(b"aggregate_witness");
+        let offset_comm = Commitment(
+            (r_comm.0.into_projective() - t_comm.0.mul(z_h_eval.into_repr()))
+                .into_affine(),
+        );
         util::linear_combination(
             &[
-                t_eval,
-                self.evaluations.linearisation_polynomial_eval,
+                quotient_offset,
                 self.evaluations.a_eval,
                 self.evaluations.b_eval,
                 self.evaluations.c_eval,
@@ -307,10 +813,13 @@ where
                 self.evaluations.left_sigma_eval,
                 self.evaluations.right_sigma_eval,
                 self.evaluations.out_sigma_eval,
+                self.evaluations.q_arith_eval,
+                self.evaluations.q_c_eval,
+                self.evaluations.q_l_eval,
+                self.evaluations.q_r_eval,
             ],
             &[
-                t_comm,
-                r_comm,
+                offset_comm,
                 self.a_comm,
                 self.b_comm,
                 self.c_comm,
@@ -318,6 +827,10 @@ where
                 plonk_verifier_key.permutation.left_sigma,
                 plonk_verifier_key.permutation.right_sigma,
                 plonk_verifier_key.permutation.out_sigma,
+                plonk_verifier_key.arithmetic.q_arith,
+                plonk_verifier_key.arithmetic.q_c,
+                plonk_verifier_key.arithmetic.q_l,
+                plonk_verifier_key.arithmetic.q_r,
             ],
             challenge,
         )
@@ -326,9 +839,8 @@ where
     // TODO: Doc this
     fn gen_shift_aggregate_proof(
         &self,
-        transcript: &mut TranscriptWrapper<E>,
+        challenge: E::Fr,
     ) -> (Commitment<E>, E::Fr) {
-        let challenge = transcript.challenge_scalar(b"aggregate_witness");
         util::linear_combination(
             &[
                 self.evaluations.permutation_eval,
@@ -341,8 +853,57 @@ where
         )
     }
 
-    // TODO: Doc this
-    fn compute_quotient_evaluation(
+    /// Checks the combined opening of a [`ProofVersion::V2`] proof.
+    ///
+    /// The aggregated polynomials `f` and `g` are opened at `z` and at
+    /// `z * omega` by the commitment `W` to
+    ///
+    /// ```text
+    /// h(X) = (f(X) - f(z)) / (X - z) + s * (g(X) - g(z * omega)) / (X - z * omega)
+    /// ```
+    ///
+    /// whose consistency is checked at the random point `x` with the witness
+    /// `W'` to `L(X) / (X - x)`, where
+    ///
+    /// ```text
+    /// L(X) = (x - z * omega) * (f(X) - f(z)) + s * (x - z) * (g(X) - g(z * omega))
+    ///      - (x - z) * (x - z * omega) * h(X)
+    /// ```
+    ///
+    /// vanishes at `x`, so that a single pairing check is needed.
+    fn check_combined_opening(
+        &self,
+        verifier_key: &VerifierKey<E>,
+        (z, f_comm, f_eval): (E::Fr, Commitment<E>, E::Fr),
+        (shifted_z, g_comm, g_eval): (E::Fr, Commitment<E>, E::Fr),
+        opening_sep_challenge: E::Fr,
+        opening_challenge: E::Fr,
+    ) -> bool {
+        let x = opening_challenge;
+        let f_scale = x - shifted_z;
+        let g_scale = opening_sep_challenge * (x - z);
+        let vanishing = (x - z) * (x - shifted_z);
+
+        // [L(X)] + x * W'
+        let lhs = f_comm.0.mul(f_scale) + g_comm.0.mul(g_scale)
+            - verifier_key.g.mul(f_scale * f_eval + g_scale * g_eval)
+            - self.w_z_comm.0.mul(vanishing)
+            + self.w_zw_comm.0.mul(x);
+
+        E::product_of_pairings(&[
+            (lhs.into_affine().into(), verifier_key.prepared_h.clone()),
+            (
+                (-self.w_zw_comm.0).into(),
+                verifier_key.prepared_beta_h.clone(),
+            ),
+        ])
+        .is_one()
+    }
+
+    /// Computes `r(z) - Z_H(z) * t(z)`, the evaluation of the linearisation
+    /// polynomial minus the quotient polynomial scaled by the vanishing
+    /// polynomial, which the verifier derives without either evaluation.
+    fn compute_quotient_offset(
         &self,
         domain: &GeneralEvaluationDomain<E::Fr>,
         pub_inputs: &[E::Fr],
@@ -350,7 +911,6 @@ where
         beta: E::Fr,
         gamma: E::Fr,
         z_challenge: E::Fr,
-        z_h_eval: E::Fr,
         l1_eval: E::Fr,
         z_hat_eval: E::Fr,
     ) -> E::Fr {
@@ -358,8 +918,6 @@ where
         let pi_eval = compute_barycentric_eval(pub_inputs, z_challenge, domain);
 
         let alpha_sq = alpha.square();
-        // r + PI(z)
-        let a = self.evaluations.linearisation_polynomial_eval + pi_eval;
 
         // a + beta * sigma_1 + gamma
         let beta_sig1 = beta * self.evaluations.left_sigma_eval;
@@ -381,8 +939,7 @@ where
         // l_1(z) * alpha^2
         let c = l1_eval * alpha_sq;
 
-        // Return t_eval
-        (a - b - c) * z_h_eval.inverse().unwrap()
+        b + c - pi_eval
     }
 
     /// Computes the quotient polynomial commitment at `z_challenge`.
@@ -392,9 +949,9 @@ where
         n: usize,
     ) -> Commitment<E> {
         let n = n as u64;
-        let z_n = z_challenge.pow(&[n, 0, 0, 0]);
-        let z_two_n = z_challenge.pow(&[2 * n, 0, 0, 0]);
-        let z_three_n = z_challenge.pow(&[3 * n, 0, 0, 0]);
+        let z_n = z_challenge.pow([n]);
+        let z_two_n = z_challenge.pow([2 * n]);
+        let z_three_n = z_challenge.pow([3 * n]);
         let t_comm = self.t_1_comm.0.into_projective()
             + self.t_2_comm.0.mul(z_n.into_repr())
             + self.t_3_comm.0.mul(z_two_n.into_repr())
@@ -416,6 +973,42 @@ where
         l1_eval: E::Fr,
         plonk_verifier_key: &PlonkVerifierKey<E, P>,
     ) -> Commitment<E> {
+        let (scalars, points) = self.linearisation_terms(
+            alpha,
+            beta,
+            gamma,
+            range_sep_challenge,
+            logic_sep_challenge,
+            fixed_base_sep_challenge,
+            var_base_sep_challenge,
+            z_challenge,
+            l1_eval,
+            plonk_verifier_key,
+        );
+
+        let scalars_repr =
+            scalars.iter().map(E::Fr::into_repr).collect::<Vec<_>>();
+
+        Commitment(
+            VariableBaseMSM::multi_scalar_mul(&points, &scalars_repr).into(),
+        )
+    }
+
+    /// Returns the scalars and commitments whose multi-scalar multiplication
+    /// is the commitment to `[r]_1`.
+    pub(crate) fn linearisation_terms(
+        &self,
+        alpha: E::Fr,
+        beta: E::Fr,
+        gamma: E::Fr,
+        range_sep_challenge: E::Fr,
+        logic_sep_challenge: E::Fr,
+        fixed_base_sep_challenge: E::Fr,
+        var_base_sep_challenge: E::Fr,
+        z_challenge: E::Fr,
+        l1_eval: E::Fr,
+        plonk_verifier_key: &PlonkVerifierKey<E, P>,
+    ) -> (Vec<E::Fr>, Vec<E::G1Affine>) {
         let mut scalars = Vec::with_capacity(6);
         let mut points = Vec::with_capacity(6);
 
@@ -471,12 +1064,136 @@ where
                 self.z_comm.0,
             );
 
-        let scalars_repr =
-            scalars.iter().map(E::Fr::into_repr).collect::<Vec<_>>();
+        (scalars, points)
+    }
+}
 
-        Commitment(
-            VariableBaseMSM::multi_scalar_mul(&points, &scalars_repr).into(),
-        )
+/// Checks that every one of `commitments` is on the curve and in its prime
+/// order subgroup.
+pub(crate) fn check_commitments<E>(
+    commitments: &[&Commitment<E>],
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+{
+    match commitments
+        .iter()
+        .position(|commitment| !util::is_valid_point(&commitment.0))
+    {
+        Some(index) => Err(Error::InvalidCommitment { index }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the evaluations carried by a [`ProofVersion::V2`] proof, which
+/// are all but the evaluation of the linearisation polynomial.
+fn v2_evaluations<F>(evaluations: &ProofEvaluations<F>) -> [F; 15]
+where
+    F: Field,
+{
+    [
+        evaluations.a_eval,
+        evaluations.b_eval,
+        evaluations.c_eval,
+        evaluations.d_eval,
+        evaluations.a_next_eval,
+        evaluations.b_next_eval,
+        evaluations.d_next_eval,
+        evaluations.q_arith_eval,
+        evaluations.q_c_eval,
+        evaluations.q_l_eval,
+        evaluations.q_r_eval,
+        evaluations.left_sigma_eval,
+        evaluations.right_sigma_eval,
+        evaluations.out_sigma_eval,
+        evaluations.permutation_eval,
+    ]
+}
+
+/// Inverse of [`v2_evaluations`].
+fn from_v2_evaluations<F>(evals: [F; 15]) -> ProofEvaluations<F>
+where
+    F: Field,
+{
+    let [a_eval, b_eval, c_eval, d_eval, a_next_eval, b_next_eval, d_next_eval, q_arith_eval, q_c_eval, q_l_eval, q_r_eval, left_sigma_eval, right_sigma_eval, out_sigma_eval, permutation_eval] =
+        evals;
+    ProofEvaluations {
+        a_eval,
+        b_eval,
+        c_eval,
+        d_eval,
+        a_next_eval,
+        b_next_eval,
+        d_next_eval,
+        q_arith_eval,
+        q_c_eval,
+        q_l_eval,
+        q_r_eval,
+        left_sigma_eval,
+        right_sigma_eval,
+        out_sigma_eval,
+        linearisation_polynomial_eval: F::zero(),
+        permutation_eval,
+    }
+}
+
+impl<E, P> CanonicalSerialize for Proof<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.version.tag().serialize(&mut writer)?;
+        self.write_body(writer, PointEncoding::Compressed)
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + self.body_size(PointEncoding::Compressed)
+    }
+
+    fn serialize_uncompressed<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.version.tag().serialize(&mut writer)?;
+        self.write_body(writer, PointEncoding::Uncompressed)
+    }
+
+    fn serialize_unchecked<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.version.tag().serialize(&mut writer)?;
+        self.write_body(writer, PointEncoding::Unchecked)
+    }
+
+    fn uncompressed_size(&self) -> usize {
+        1 + self.body_size(PointEncoding::Uncompressed)
+    }
+}
+
+impl<E, P> CanonicalDeserialize for Proof<E, P>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    fn deserialize<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Self::read_versioned(reader, PointEncoding::Compressed)
+    }
+
+    fn deserialize_uncompressed<R: Read>(
+        reader: R,
+    ) -> Result<Self, SerializationError> {
+        Self::read_versioned(reader, PointEncoding::Uncompressed)
+    }
+
+    fn deserialize_unchecked<R: Read>(
+        reader: R,
+    ) -> Result<Self, SerializationError> {
+        Self::read_versioned(reader, PointEncoding::Unchecked)
     }
 }
 
@@ -537,7 +1254,7 @@ where
         .map(|i| {
             // index of non-zero evaluation
             let index = non_zero_evaluations[i];
-            (group_gen_inv.pow(&[index as u64, 0, 0, 0]) * point) - F::one()
+            (group_gen_inv.pow([index as u64]) * point) - F::one()
         })
         .collect::<Vec<_>>();
     batch_inversion(&mut denominators);
@@ -559,7 +1276,10 @@ mod test {
     use crate::batch_test;
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
     use ark_ff::UniformRand;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
     use rand_core::OsRng;
 
     fn test_serde_proof<E, P>()
@@ -567,7 +1287,8 @@ mod test {
         E: PairingEngine,
         P: TEModelParameters<BaseField = E::Fr>,
     {
-        let proof = Proof::<E, P> {
+        let mut proof = Proof::<E, P> {
+            version: ProofVersion::V1,
             a_comm: Default::default(),
             b_comm: Default::default(),
             c_comm: Default::default(),
@@ -602,16 +1323,178 @@ mod test {
 
         let mut proof_bytes = vec![];
         proof.serialize(&mut proof_bytes).unwrap();
+        assert_eq!(proof_bytes.len(), proof.serialized_size());
+        assert_eq!(proof_bytes[0], ProofVersion::V1.tag());
 
         let obtained_proof =
             Proof::deserialize(proof_bytes.as_slice()).unwrap();
 
         assert!(proof == obtained_proof);
+
+        // Proofs serialized before versioning lack the tag
+        let obtained_proof =
+            Proof::deserialize_unversioned(&proof_bytes[1..]).unwrap();
+        assert!(proof == obtained_proof);
+
+        // V2 proofs don't carry the linearisation evaluation
+        proof.version = ProofVersion::V2;
+        proof.evaluations.linearisation_polynomial_eval = E::Fr::zero();
+        let mut v2_bytes = vec![];
+        proof.serialize(&mut v2_bytes).unwrap();
+        assert_eq!(v2_bytes.len(), proof.serialized_size());
+        assert_eq!(
+            v2_bytes.len() + E::Fr::zero().serialized_size(),
+            proof_bytes.len()
+        );
+        let obtained_proof = Proof::deserialize(v2_bytes.as_slice()).unwrap();
+        assert!(proof == obtained_proof);
+
+        let mut uncompressed_bytes = vec![];
+        proof
+            .serialize_uncompressed(&mut uncompressed_bytes)
+            .unwrap();
+        assert_eq!(uncompressed_bytes.len(), proof.uncompressed_size());
+        let obtained_proof =
+            Proof::deserialize_uncompressed(uncompressed_bytes.as_slice())
+                .unwrap();
+        assert!(proof == obtained_proof);
+
+        // Unknown versions are rejected
+        v2_bytes[0] = 0;
+        assert!(Proof::<E, P>::deserialize(v2_bytes.as_slice()).is_err());
+    }
+
+    fn test_proof_versions<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        use crate::constraint_system::StandardComposer;
+        use crate::proof_system::{Prover, Verifier};
+        use ark_poly_commit::kzg10::Powers;
+        use ark_poly_commit::sonic_pc::SonicKZG10;
+        use ark_poly_commit::PolynomialCommitment;
+
+        fn gadget<E, P>(composer: &mut StandardComposer<E, P>)
+        where
+            E: PairingEngine,
+            P: TEModelParameters<BaseField = E::Fr>,
+        {
+            let a = composer.add_input(E::Fr::from(3u64));
+            let b = composer.add_input(E::Fr::from(5u64));
+            composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+            composer.add(
+                (E::Fr::one(), a),
+                (E::Fr::one(), b),
+                E::Fr::zero(),
+                Some(-E::Fr::from(8u64)),
+            );
+        }
+
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(32, false, &mut OsRng)
+                .unwrap();
+        let (ck, vk) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, 8, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        let vk = VerifierKey {
+            g: vk.g,
+            gamma_g: vk.gamma_g,
+            h: vk.h,
+            beta_h: vk.beta_h,
+            prepared_h: vk.prepared_h,
+            prepared_beta_h: vk.prepared_beta_h,
+        };
+
+        let mut verifier: Verifier<E, P> = Verifier::new(b"versions");
+        gadget(verifier.mut_cs());
+        verifier.preprocess(&powers).unwrap();
+
+        let mut prover: Prover<E, P> = Prover::new(b"versions");
+        gadget(prover.mut_cs());
+        let pi = prover.cs.construct_dense_pi_vec();
+        let proof = prover.prove(&powers).unwrap();
+        assert_eq!(proof.version(), ProofVersion::LATEST);
+        assert!(verifier.verify(&proof, &vk, &pi).is_ok());
+
+        let mut bytes = vec![];
+        proof.serialize(&mut bytes).unwrap();
+        let proof = Proof::<E, P>::deserialize(bytes.as_slice()).unwrap();
+        assert!(verifier.verify(&proof, &vk, &pi).is_ok());
+
+        assert_eq!(
+            Proof::<E, P>::deserialize_checked(bytes.as_slice()).unwrap(),
+            proof
+        );
+
+        for encoding in [
+            PointEncoding::Compressed,
+            PointEncoding::Uncompressed,
+            PointEncoding::Unchecked,
+        ] {
+            let mut bytes = vec![];
+            proof.serialize_with_encoding(&mut bytes, encoding).unwrap();
+            assert_eq!(
+                bytes.len(),
+                proof.serialized_size_with_encoding(encoding)
+            );
+            assert_eq!(
+                Proof::<E, P>::deserialize_with_encoding(
+                    bytes.as_slice(),
+                    encoding
+                )
+                .unwrap(),
+                proof
+            );
+        }
+        assert!(
+            proof.serialized_size_with_encoding(PointEncoding::Compressed)
+                < proof
+                    .serialized_size_with_encoding(PointEncoding::Uncompressed)
+        );
+
+        // Commitments off the curve are rejected by the verifier
+        let mut unchecked_bytes = vec![];
+        proof.serialize_unchecked(&mut unchecked_bytes).unwrap();
+        unchecked_bytes[1] ^= 1;
+        let off_curve =
+            Proof::<E, P>::deserialize_unchecked(unchecked_bytes.as_slice())
+                .unwrap();
+        assert!(Proof::<E, P>::deserialize_with_encoding(
+            unchecked_bytes.as_slice(),
+            PointEncoding::Uncompressed
+        )
+        .is_err());
+        assert!(matches!(
+            off_curve.check_commitments(),
+            Err(Error::InvalidCommitment { index: 0 })
+        ));
+        assert!(matches!(
+            verifier.verify(&off_curve, &vk, &pi),
+            Err(Error::InvalidCommitment { index: 0 })
+        ));
+
+        // V1 proofs, which don't open the selector evaluations, are rejected
+        let mut legacy = proof.clone();
+        legacy.version = ProofVersion::V1;
+        assert!(matches!(
+            verifier.verify(&legacy, &vk, &pi),
+            Err(Error::LegacyProofRejected)
+        ));
+
+        // The selector evaluations are opened
+        let mut tampered = proof;
+        tampered.evaluations.q_arith_eval += E::Fr::one();
+        assert!(verifier.verify(&tampered, &vk, &pi).is_err());
     }
 
     // Bls12-381 tests
     batch_test!(
-        [test_serde_proof],
+        [test_serde_proof, test_proof_versions],
         [] => (
             Bls12_381,
             ark_ed_on_bls12_381::EdwardsParameters
@@ -620,10 +1503,19 @@ mod test {
 
     // Bls12-377 tests
     batch_test!(
-        [test_serde_proof],
+        [test_serde_proof, test_proof_versions],
         [] => (
             Bls12_377,
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    // Bn254 tests
+    batch_test!(
+        [test_serde_proof, test_proof_versions],
+        [] => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
 }
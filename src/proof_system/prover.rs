@@ -7,24 +7,116 @@
 //! Prover-side of the PLONK Proving System
 
 use crate::{
+    circuit::PublicInputs,
     constraint_system::{StandardComposer, Variable},
     error::Error,
     proof_system::{
-        linearisation_poly, proof::Proof, quotient_poly, ProverKey,
+        linearisation_poly,
+        proof::{Proof, ProofVersion},
+        quotient_poly,
+        scratch::ScratchPool,
+        Backend, LagrangeBasis, ProverKey,
     },
-    transcript::{TranscriptProtocol, TranscriptWrapper},
+    transcript::{TranscriptLog, TranscriptProtocol, TranscriptWrapper},
     util,
 };
+use alloc::sync::Arc;
 use ark_ec::{PairingEngine, TEModelParameters};
 use ark_ff::Field;
 use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
     UVPolynomial,
 };
-use ark_poly_commit::kzg10::{Powers, KZG10};
+use ark_poly_commit::kzg10::{Commitment, Powers};
 use core::marker::PhantomData;
 use core::ops::Add;
-use num_traits::Zero;
+use core::time::Duration;
+use num_traits::{One, Zero};
+use std::time::Instant;
+
+/// Phases of proof generation, in the order the [`Prover`] runs them.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ProverPhase {
+    /// Interpolation and commitment of the wire polynomials
+    WitnessPolynomials,
+
+    /// Computation and commitment of the permutation polynomial
+    PermutationPolynomial,
+
+    /// Computation and commitment of the quotient polynomial
+    QuotientPolynomial,
+
+    /// Computation of the linearisation polynomial and the evaluations
+    Linearisation,
+
+    /// Computation and commitment of the opening witnesses
+    Opening,
+}
+
+impl ProverPhase {
+    /// All the phases, in the order they are run.
+    pub const ALL: [Self; 5] = [
+        Self::WitnessPolynomials,
+        Self::PermutationPolynomial,
+        Self::QuotientPolynomial,
+        Self::Linearisation,
+        Self::Opening,
+    ];
+}
+
+/// Report of a completed [`ProverPhase`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ProverProgress {
+    /// Completed phase
+    pub phase: ProverPhase,
+
+    /// Time spent in the phase
+    pub elapsed: Duration,
+
+    /// Percentage of the phases completed so far
+    pub percent: u8,
+}
+
+/// Callback receiving a [`ProverProgress`] every time the [`Prover`]
+/// completes a phase.
+pub type ProgressCallback = Arc<dyn Fn(&ProverProgress) + Send + Sync>;
+
+/// Times the phases of a proof and reports them to a [`ProgressCallback`].
+struct PhaseTimer<'a> {
+    /// Callback to report to, if any
+    callback: Option<&'a ProgressCallback>,
+
+    /// Start of the current phase, only read from the clock when there is
+    /// a callback
+    start: Option<Instant>,
+
+    /// Number of completed phases
+    completed: usize,
+}
+
+impl<'a> PhaseTimer<'a> {
+    fn new(callback: Option<&'a ProgressCallback>) -> Self {
+        Self {
+            callback,
+            start: callback.map(|_| Instant::now()),
+            completed: 0,
+        }
+    }
+
+    /// Reports the completion of `phase` and starts timing the next one.
+    fn done(&mut self, phase: ProverPhase) {
+        self.completed += 1;
+        if let (Some(callback), Some(start)) = (self.callback, self.start) {
+            let now = Instant::now();
+            callback(&ProverProgress {
+                phase,
+                elapsed: now - start,
+                percent: (100 * self.completed / ProverPhase::ALL.len()) as u8,
+            });
+            self.start = Some(now);
+        }
+    }
+}
 
 /// Abstraction structure designed to construct a circuit and generate
 /// [`Proof`]s for it.
@@ -35,6 +127,10 @@ where
 {
     /// Proving Key which is used to create proofs about a specific PLONK
     /// circuit.
+    ///
+    /// Callers that keep the key elsewhere don't need to move it here, and
+    /// can pass it by reference to [`Prover::prove_with_preprocessed`]
+    /// instead.
     pub prover_key: Option<ProverKey<E::Fr, P>>,
 
     /// Circuit Description
@@ -44,6 +140,18 @@ where
     ///
     /// This is copied each time, we make a proof.
     pub preprocessed_transcript: TranscriptWrapper<E>,
+
+    /// Callback notified of the progress of proof generation.
+    pub(crate) progress: Option<ProgressCallback>,
+
+    /// Lagrange basis to commit to the wires from their values.
+    pub(crate) lagrange_basis: Option<Arc<LagrangeBasis<E>>>,
+
+    /// MSM and FFT backends used to preprocess and prove.
+    pub(crate) backend: Backend<E>,
+
+    /// Buffers reused across the FFTs of successive proofs.
+    pub(crate) scratch: ScratchPool<E::Fr>,
 }
 
 impl<E, P> Prover<E, P>
@@ -52,28 +160,72 @@ where
     P: TEModelParameters<BaseField = E::Fr>,
 {
     /// Creates a new `Prover` instance.
-    pub fn new(label: &'static [u8]) -> Self {
+    pub fn new(label: &[u8]) -> Self {
         Self {
             prover_key: None,
             cs: StandardComposer::new(),
             preprocessed_transcript: TranscriptWrapper::new(label),
+            progress: None,
+            lagrange_basis: None,
+            backend: Backend::default(),
+            scratch: ScratchPool::default(),
         }
     }
 
     /// Creates a new `Prover` object with some expected size.
-    pub fn with_expected_size(label: &'static [u8], size: usize) -> Self {
+    pub fn with_expected_size(label: &[u8], size: usize) -> Self {
         Self {
             prover_key: None,
             cs: StandardComposer::with_expected_size(size),
             preprocessed_transcript: TranscriptWrapper::new(label),
+            progress: None,
+            lagrange_basis: None,
+            backend: Backend::default(),
+            scratch: ScratchPool::default(),
         }
     }
 
+    /// Sets a `callback` notified with the timing and overall progress of
+    /// every [`ProverPhase`] of the following proofs.
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&ProverProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+    }
+
+    /// Sets the [`LagrangeBasis`] used to commit to the wires directly from
+    /// their values, instead of interpolating the wire polynomials first.
+    ///
+    /// It is only used for circuits whose domain has the size of the basis.
+    /// The commitments, and so the proofs, are the same either way.
+    pub fn set_lagrange_basis(&mut self, basis: Arc<LagrangeBasis<E>>) {
+        self.lagrange_basis = Some(basis);
+    }
+
+    /// Sets the [`Backend`] computing the MSMs and FFTs of the following
+    /// preprocessing and proofs, which is the [`CpuBackend`] by default.
+    ///
+    /// [`CpuBackend`]: crate::proof_system::CpuBackend
+    pub fn set_backend(&mut self, backend: Backend<E>) {
+        self.backend = backend;
+    }
+
     /// Returns a mutable copy of the underlying [`StandardComposer`].
     pub fn mut_cs(&mut self) -> &mut StandardComposer<E, P> {
         &mut self.cs
     }
 
+    /// Returns the public inputs of the circuit the `Prover` stores inside,
+    /// as expected by the verifier.
+    ///
+    /// [`Prover::prove`] clears the witness, so the public inputs must be
+    /// fetched before proving.
+    pub fn public_inputs(&self) -> PublicInputs<E> {
+        PublicInputs::from_pairs(self.cs.public_input_values())
+            .expect("the public input positions of the composer are unique")
+    }
+
     /// Returns the number of gates in the circuit thet the `Prover` actually
     /// stores inside.
     pub fn circuit_size(&self) -> usize {
@@ -85,9 +237,11 @@ where
         if self.prover_key.is_some() {
             return Err(Error::CircuitAlreadyPreprocessed);
         }
-        let pk = self
-            .cs
-            .preprocess_prover(commit_key, &mut self.preprocessed_transcript)?;
+        let pk = self.cs.preprocess_prover_with_backend(
+            commit_key,
+            &mut self.preprocessed_transcript,
+            &self.backend,
+        )?;
         self.prover_key = Some(pk);
         Ok(())
     }
@@ -122,9 +276,9 @@ where
         z_challenge: &E::Fr,
     ) -> DensePolynomial<E::Fr> {
         // Compute z^n , z^2n , z^3n
-        let z_n = z_challenge.pow(&[n as u64, 0, 0, 0]);
-        let z_two_n = z_challenge.pow(&[2 * n as u64, 0, 0, 0]);
-        let z_three_n = z_challenge.pow(&[3 * n as u64, 0, 0, 0]);
+        let z_n = z_challenge.pow([n as u64]);
+        let z_two_n = z_challenge.pow([2 * n as u64]);
+        let z_three_n = z_challenge.pow([3 * n as u64]);
         let a = t_1_poly;
         let b = t_2_poly * z_n;
         let c = t_3_poly * z_two_n;
@@ -134,25 +288,71 @@ where
 
     /// Convert variables to their actual witness values.
     fn to_scalars(&self, vars: &[Variable]) -> Vec<E::Fr> {
-        vars.iter().map(|var| self.cs.variables[var]).collect()
+        vars.iter().map(|var| self.cs.variables[var.0]).collect()
+    }
+
+    /// Warms up the `Prover` so that the first [`Proof`] doesn't pay the
+    /// cold-start costs of proving.
+    ///
+    /// This initializes the thread pool used by the parallel FFTs and MSMs,
+    /// runs FFTs over the same domains the prover uses for a circuit of the
+    /// current size and performs an MSM over the whole `commit_key`, so that
+    /// its memory is resident before the first proof is requested.
+    ///
+    /// Calling this method is optional and doesn't affect the [`Proof`]s
+    /// generated afterwards.
+    pub fn warmup(&self, commit_key: &Powers<E>) -> Result<(), Error> {
+        let domain = util::evaluation_domain::<E::Fr>(self.cs.circuit_size())?;
+        let domain_4n = util::evaluation_domain::<E::Fr>(4 * domain.size())?;
+
+        let fft = self.backend.fft();
+        let scalars = vec![E::Fr::one(); domain.size()];
+        let coeffs = fft.ifft(&domain, &scalars);
+        fft.coset_fft(&domain_4n, &coeffs);
+
+        let poly = DensePolynomial::from_coefficients_vec(vec![
+            E::Fr::one();
+            commit_key
+                .size()
+        ]);
+        self.backend.commit(commit_key, &poly)?;
+        Ok(())
     }
 
     /// Resets the witnesses in the prover object.
     ///
     /// This function is used when the user wants to make multiple proofs with
-    /// the same circuit.
+    /// the same circuit. The wire assignments and public inputs are dropped,
+    /// while the preprocessed [`ProverKey`], which captures the structure of
+    /// the circuit, and the seeded transcript are kept. The gadget must then
+    /// be run again on [`Prover::mut_cs`] to assign the new witnesses.
+    ///
+    /// The new composer reserves room for as many gates as the previous one,
+    /// so refilling it doesn't reallocate.
     pub fn clear_witness(&mut self) {
-        self.cs = StandardComposer::new();
+        self.cs = StandardComposer::with_expected_size(self.cs.circuit_size());
     }
 
     /// Clears all data in the `Prover` instance.
     ///
     /// This function is used when the user wants to use the same `Prover` to
-    /// make a [`Proof`] regarding a different circuit.
+    /// make a [`Proof`] regarding a different circuit. The transcript is reset
+    /// to its initial state, keeping the label the `Prover` was created with,
+    /// and the scratch buffers sized for the previous circuit are freed.
     pub fn clear(&mut self) {
         self.clear_witness();
+        self.release_scratch_buffers();
         self.prover_key = None;
-        self.preprocessed_transcript = TranscriptWrapper::new(b"plonk");
+        self.preprocessed_transcript.reset();
+    }
+
+    /// Frees the buffers kept by the `Prover` to reuse them across the FFTs
+    /// of successive proofs.
+    ///
+    /// The buffers are allocated again by the next proof, so this is only
+    /// useful to lower the memory held by an idle `Prover`.
+    pub fn release_scratch_buffers(&self) {
+        self.scratch.clear();
     }
 
     /// Keys the [`Transcript`] with additional seed information
@@ -161,27 +361,161 @@ where
     /// [`Transcript`]: merlin::Transcript
     /// [`Transcript::append_message`]: merlin::Transcript::append_message
     pub fn key_transcript(&mut self, label: &'static [u8], message: &[u8]) {
-        self.preprocessed_transcript
-            .transcript
-            .append_message(label, message);
+        self.preprocessed_transcript.append_message(label, message);
     }
 
-    /// Computes a single witness for multiple polynomials at the same point, by
-    /// taking a random linear combination of the individual witnesses.
+    /// Starts recording the messages appended to the transcript and the
+    /// challenges drawn from it, including those of the following proofs,
+    /// and returns the record.
     ///
-    /// The result does not depend on `z`, thus we can remove the term `f(z)`.
-    fn compute_aggregate_witness(
+    /// This is a debugging aid to compare the transcripts of two
+    /// implementations of the protocol event by event.
+    pub fn record_transcript(&mut self) -> TranscriptLog {
+        self.preprocessed_transcript.record()
+    }
+
+    /// Combines `polynomials` with increasing powers of `challenge`.
+    fn compute_aggregate_poly(
         polynomials: &[DensePolynomial<E::Fr>],
-        point: &E::Fr,
         challenge: E::Fr,
     ) -> DensePolynomial<E::Fr> {
-        util::ruffini(
-            util::powers_of(challenge)
-                .zip(polynomials)
-                .map(|(challenge, poly)| poly * challenge)
-                .fold(Zero::zero(), Add::add),
-            *point,
-        )
+        util::powers_of(challenge)
+            .zip(polynomials)
+            .map(|(challenge, poly)| poly * challenge)
+            .fold(Zero::zero(), Add::add)
+    }
+
+    /// Computes the witnesses of a single combined opening (SHPLONK) of
+    /// `polynomials` at `z` and of `shifted_polynomials` at `z * omega`, as
+    /// in [`ProofVersion::V2`] proofs.
+    ///
+    /// The polynomials opened at each point are aggregated into `f` and `g`,
+    /// and the first witness commits to
+    ///
+    /// ```text
+    /// h(X) = (f(X) - f(z)) / (X - z) + s * (g(X) - g(z * omega)) / (X - z * omega)
+    /// ```
+    ///
+    /// for a separation challenge `s`. The second one proves that `h` is
+    /// consistent with `f` and `g` at a random point `x`, by opening
+    ///
+    /// ```text
+    /// L(X) = (x - z * omega) * f(X) + s * (x - z) * g(X)
+    ///      - (x - z) * (x - z * omega) * h(X)
+    /// ```
+    ///
+    /// at `x`, where the verifier expects it to evaluate to the constant
+    /// terms left out of `L`.
+    fn compute_combined_opening(
+        &self,
+        commit_key: &Powers<E>,
+        transcript: &mut TranscriptWrapper<E>,
+        (z, polynomials): (E::Fr, &[DensePolynomial<E::Fr>]),
+        (shifted_z, shifted_polynomials): (E::Fr, &[DensePolynomial<E::Fr>]),
+    ) -> Result<(Commitment<E>, Commitment<E>), Error> {
+        let aw_challenge: E::Fr =
+            transcript.challenge_scalar(b"aggregate_witness");
+        let f = Self::compute_aggregate_poly(polynomials, aw_challenge);
+        let saw_challenge: E::Fr =
+            transcript.challenge_scalar(b"aggregate_witness");
+        let g =
+            Self::compute_aggregate_poly(shifted_polynomials, saw_challenge);
+
+        let opening_sep_challenge: E::Fr =
+            transcript.challenge_scalar(b"opening separation challenge");
+        let h = &util::ruffini(f.clone(), z)
+            + &(&util::ruffini(g.clone(), shifted_z) * opening_sep_challenge);
+        let w_z_comm = self.backend.commit(commit_key, &h)?;
+        transcript.append_commitment(b"w_z", &w_z_comm);
+
+        let x: E::Fr = transcript.challenge_scalar(b"opening challenge");
+        let l = &(&(&f * (x - shifted_z))
+            + &(&g * (opening_sep_challenge * (x - z))))
+            - &(&h * ((x - z) * (x - shifted_z)));
+        let w_zw_comm =
+            self.backend.commit(commit_key, &util::ruffini(l, x))?;
+        Ok((w_z_comm, w_zw_comm))
+    }
+
+    /// Converts the wires to scalars, padding them to the domain size.
+    fn wire_scalars(
+        &self,
+        domain: &GeneralEvaluationDomain<E::Fr>,
+    ) -> [Vec<E::Fr>; 4] {
+        let pad = vec![E::Fr::zero(); domain.size() - self.cs.w_l.len()];
+        [&self.cs.w_l, &self.cs.w_r, &self.cs.w_o, &self.cs.w_4]
+            .map(|wire| [&self.to_scalars(wire)[..], &pad].concat())
+    }
+
+    /// Interpolates the wire polynomials from their evaluations over
+    /// `domain`.
+    fn wire_polynomials(
+        &self,
+        domain: &GeneralEvaluationDomain<E::Fr>,
+        wire_scalars: &[Vec<E::Fr>; 4],
+    ) -> [DensePolynomial<E::Fr>; 4] {
+        [0, 1, 2, 3].map(|i| {
+            DensePolynomial::from_coefficients_vec(
+                self.backend.fft().ifft(domain, &wire_scalars[i]),
+            )
+        })
+    }
+
+    /// Commits to the wire polynomials of the witness, in the order left,
+    /// right, output and fourth.
+    ///
+    /// These are the same commitments the [`Proof`] generated for the
+    /// current witness contains.
+    pub fn commit_wires(
+        &self,
+        commit_key: &Powers<E>,
+    ) -> Result<[Commitment<E>; 4], Error> {
+        let domain = util::evaluation_domain(self.cs.circuit_size())?;
+        let wire_scalars = self.wire_scalars(&domain);
+        match self.lagrange_basis_for(&domain) {
+            Some(basis) => self.commit_wire_scalars(basis, &wire_scalars),
+            None => self.commit_wire_polynomials(
+                commit_key,
+                &self.wire_polynomials(&domain, &wire_scalars),
+            ),
+        }
+    }
+
+    /// Returns the Lagrange basis set for circuits of the size of `domain`,
+    /// if any.
+    fn lagrange_basis_for(
+        &self,
+        domain: &GeneralEvaluationDomain<E::Fr>,
+    ) -> Option<&LagrangeBasis<E>> {
+        self.lagrange_basis
+            .as_deref()
+            .filter(|basis| basis.size() == domain.size())
+    }
+
+    /// Commits to the wire polynomials from their evaluations.
+    fn commit_wire_scalars(
+        &self,
+        basis: &LagrangeBasis<E>,
+        wire_scalars: &[Vec<E::Fr>; 4],
+    ) -> Result<[Commitment<E>; 4], Error> {
+        let mut commitments = [Commitment::<E>::default(); 4];
+        for (commitment, scalars) in commitments.iter_mut().zip(wire_scalars) {
+            *commitment = basis.commit_with(scalars, self.backend.msm())?;
+        }
+        Ok(commitments)
+    }
+
+    /// Commits to the wire polynomials from their coefficients.
+    fn commit_wire_polynomials(
+        &self,
+        commit_key: &Powers<E>,
+        wire_polys: &[DensePolynomial<E::Fr>; 4],
+    ) -> Result<[Commitment<E>; 4], Error> {
+        let mut commitments = [Commitment::<E>::default(); 4];
+        for (commitment, poly) in commitments.iter_mut().zip(wire_polys) {
+            *commitment = self.backend.commit(commit_key, poly)?;
+        }
+        Ok(commitments)
     }
 
     /// Creates a [`Proof]` that demonstrates that a circuit is satisfied.
@@ -195,46 +529,50 @@ where
         commit_key: &Powers<E>,
         prover_key: &ProverKey<E::Fr, P>,
     ) -> Result<Proof<E, P>, Error> {
-        let domain =
-            GeneralEvaluationDomain::new(self.cs.circuit_size()).unwrap();
+        let domain = util::evaluation_domain(self.cs.circuit_size())?;
 
         // Since the caller is passing a pre-processed circuit
         // We assume that the Transcript has been seeded with the preprocessed
         // Commitments
         let mut transcript = self.preprocessed_transcript.clone();
+        let mut timer = PhaseTimer::new(self.progress.as_ref());
+
+        // Bind the proof to the circuit the keys were generated for
+        if let Some(circuit_id) = &prover_key.circuit_id {
+            transcript.circuit_binding(&circuit_id.0, &prover_key.vk_digest.0);
+        }
+        ProofVersion::LATEST.bind_transcript(&mut transcript);
 
         // 1. Compute witness Polynomials
         //
-        // Convert Variables to scalars padding them to the
-        // correct domain size.
-        let pad = vec![E::Fr::zero(); domain.size() - self.cs.w_l.len()];
-        let w_l_scalar = &[&self.to_scalars(&self.cs.w_l)[..], &pad].concat();
-        let w_r_scalar = &[&self.to_scalars(&self.cs.w_r)[..], &pad].concat();
-        let w_o_scalar = &[&self.to_scalars(&self.cs.w_o)[..], &pad].concat();
-        let w_4_scalar = &[&self.to_scalars(&self.cs.w_4)[..], &pad].concat();
-
-        // Witnesses are now in evaluation form, convert them to coefficients
-        // so that we may commit to them.
-        let w_l_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(w_l_scalar));
-        let w_r_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(w_r_scalar));
-        let w_o_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(w_o_scalar));
-        let w_4_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(w_4_scalar));
-
-        // Commit to witness polynomials.
-        let w_l_poly_commit = KZG10::commit(commit_key, &w_l_poly, None, None)?;
-        let w_r_poly_commit = KZG10::commit(commit_key, &w_r_poly, None, None)?;
-        let w_o_poly_commit = KZG10::commit(commit_key, &w_o_poly, None, None)?;
-        let w_4_poly_commit = KZG10::commit(commit_key, &w_4_poly, None, None)?;
+        // Convert Variables to scalars padding them to the correct domain
+        // size. Without a Lagrange basis of the domain size, they are
+        // converted to coefficients so that we may commit to them.
+        let mut wire_scalars = self.wire_scalars(&domain);
+        let (wire_commits, wire_polys) = match self.lagrange_basis_for(&domain)
+        {
+            Some(basis) => {
+                (self.commit_wire_scalars(basis, &wire_scalars)?, None)
+            }
+            None => {
+                let wire_polys = self.wire_polynomials(&domain, &wire_scalars);
+                (
+                    self.commit_wire_polynomials(commit_key, &wire_polys)?,
+                    Some(wire_polys),
+                )
+            }
+        };
+        let [w_l_poly_commit, w_r_poly_commit, w_o_poly_commit, w_4_poly_commit] =
+            wire_commits;
+        let [w_l_scalar, w_r_scalar, w_o_scalar, w_4_scalar] = &wire_scalars;
 
         // Add witness polynomial commitments to transcript.
-        transcript.append_commitment(b"w_l", &w_l_poly_commit.0);
-        transcript.append_commitment(b"w_r", &w_r_poly_commit.0);
-        transcript.append_commitment(b"w_o", &w_o_poly_commit.0);
-        transcript.append_commitment(b"w_4", &w_4_poly_commit.0);
+        transcript.append_commitment(b"w_l", &w_l_poly_commit);
+        transcript.append_commitment(b"w_r", &w_r_poly_commit);
+        transcript.append_commitment(b"w_o", &w_o_poly_commit);
+        transcript.append_commitment(b"w_4", &w_4_poly_commit);
+
+        timer.done(ProverPhase::WitnessPolynomials);
 
         // 2. Compute permutation polynomial
         //
@@ -248,6 +586,7 @@ where
         let z_poly = DensePolynomial::from_coefficients_slice(
             &self.cs.perm.compute_permutation_poly(
                 &domain,
+                self.backend.fft(),
                 (w_l_scalar, w_r_scalar, w_o_scalar, w_4_scalar),
                 beta,
                 gamma,
@@ -260,17 +599,28 @@ where
             ),
         );
 
+        // The quotient and the openings still need the coefficients of the
+        // wires, which are only interpolated now when they were committed to
+        // from their evaluations.
+        let [w_l_poly, w_r_poly, w_o_poly, w_4_poly] = wire_polys
+            .unwrap_or_else(|| self.wire_polynomials(&domain, &wire_scalars));
+
+        // The evaluations of the wires are the witness itself
+        wipe(wire_scalars.iter_mut());
+
         // Commit to permutation polynomial.
-        let z_poly_commit = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key, &z_poly, None, None,
-        )?;
+        let z_poly_commit = self.backend.commit(commit_key, &z_poly)?;
 
         // Add permutation polynomial commitment to transcript.
-        transcript.append_commitment(b"z", &z_poly_commit.0);
+        transcript.append_commitment(b"z", &z_poly_commit);
+
+        timer.done(ProverPhase::PermutationPolynomial);
 
         // 3. Compute public inputs polynomial.
         let pi_poly = DensePolynomial::from_coefficients_vec(
-            domain.ifft(&self.cs.construct_dense_pi_vec()),
+            self.backend
+                .fft()
+                .ifft(&domain, &self.cs.construct_dense_pi_vec()),
         );
 
         // 4. Compute quotient polynomial
@@ -287,8 +637,10 @@ where
         let var_base_sep_challenge =
             transcript.challenge_scalar(b"variable base separation challenge");
 
-        let t_poly = quotient_poly::compute(
+        let mut t_poly = quotient_poly::compute(
             &domain,
+            self.backend.fft(),
+            &self.scratch,
             prover_key,
             &z_poly,
             &w_l_poly,
@@ -306,27 +658,29 @@ where
         )?;
 
         // Split quotient polynomial into 4 degree `n` polynomials
-        let (t_1_poly, t_2_poly, t_3_poly, t_4_poly) =
+        let (mut t_1_poly, mut t_2_poly, mut t_3_poly, mut t_4_poly) =
             self.split_tx_poly(domain.size(), &t_poly);
 
         // Commit to splitted quotient polynomial
-        let t_1_commit = KZG10::commit(commit_key, &t_1_poly, None, None)?;
-        let t_2_commit = KZG10::commit(commit_key, &t_2_poly, None, None)?;
-        let t_3_commit = KZG10::commit(commit_key, &t_3_poly, None, None)?;
-        let t_4_commit = KZG10::commit(commit_key, &t_4_poly, None, None)?;
+        let t_1_commit = self.backend.commit(commit_key, &t_1_poly)?;
+        let t_2_commit = self.backend.commit(commit_key, &t_2_poly)?;
+        let t_3_commit = self.backend.commit(commit_key, &t_3_poly)?;
+        let t_4_commit = self.backend.commit(commit_key, &t_4_poly)?;
 
         // Add quotient polynomial commitments to transcript
-        transcript.append_commitment(b"t_1", &t_1_commit.0);
-        transcript.append_commitment(b"t_2", &t_2_commit.0);
-        transcript.append_commitment(b"t_3", &t_3_commit.0);
-        transcript.append_commitment(b"t_4", &t_4_commit.0);
+        transcript.append_commitment(b"t_1", &t_1_commit);
+        transcript.append_commitment(b"t_2", &t_2_commit);
+        transcript.append_commitment(b"t_3", &t_3_commit);
+        transcript.append_commitment(b"t_4", &t_4_commit);
+
+        timer.done(ProverPhase::QuotientPolynomial);
 
         // 4. Compute linearisation polynomial
         //
         // Compute evaluation challenge; `z`.
         let z_challenge = transcript.challenge_scalar(b"z");
 
-        let (lin_poly, evaluations) = linearisation_poly::compute(
+        let (mut lin_poly, evaluations) = linearisation_poly::compute(
             &domain,
             prover_key,
             &alpha,
@@ -341,7 +695,6 @@ where
             &w_r_poly,
             &w_o_poly,
             &w_4_poly,
-            &t_poly,
             &z_poly,
         );
 
@@ -373,17 +726,14 @@ where
         transcript.append_scalar(b"q_r_eval", &evaluations.proof.q_r_eval);
         transcript
             .append_scalar(b"perm_eval", &evaluations.proof.permutation_eval);
-        transcript.append_scalar(b"t_eval", &evaluations.quot_eval);
-        transcript.append_scalar(
-            b"r_eval",
-            &evaluations.proof.linearisation_polynomial_eval,
-        );
+
+        timer.done(ProverPhase::Linearisation);
 
         // 5. Compute Openings using KZG10
         //
         // We merge the quotient polynomial using the `z_challenge` so the SRS
         // is linear in the circuit size `n`
-        let quot = Self::compute_quotient_opening_poly(
+        let mut quot = Self::compute_quotient_opening_poly(
             domain.size(),
             &t_1_poly,
             &t_2_poly,
@@ -391,62 +741,70 @@ where
             &t_4_poly,
             &z_challenge,
         );
-
-        // Compute aggregate witness to polynomials evaluated at the evaluation
-        // challenge `z`
-        let aw_challenge: E::Fr =
-            transcript.challenge_scalar(b"aggregate_witness");
-        let aggregate_witness = Self::compute_aggregate_witness(
-            &[
-                quot,
-                lin_poly,
-                w_l_poly.clone(),
-                w_r_poly.clone(),
-                w_o_poly,
-                w_4_poly.clone(),
-                prover_key.permutation.left_sigma.0.clone(),
-                prover_key.permutation.right_sigma.0.clone(),
-                prover_key.permutation.out_sigma.0.clone(),
-            ],
-            &z_challenge,
-            aw_challenge,
+        wipe(
+            [
+                &mut t_poly,
+                &mut t_1_poly,
+                &mut t_2_poly,
+                &mut t_3_poly,
+                &mut t_4_poly,
+            ]
+            .map(|poly| &mut poly.coeffs),
         );
-        let w_z_comm = KZG10::<E, DensePolynomial<E::Fr>>::commit(
-            commit_key,
-            &aggregate_witness,
-            None,
-            None,
-        )?;
 
-        // Compute aggregate witness to polynomials evaluated at the shifted
-        // evaluation challenge
-        let saw_challenge: E::Fr =
-            transcript.challenge_scalar(b"aggregate_witness");
-        let shifted_aggregate_witness = Self::compute_aggregate_witness(
-            &[z_poly, w_l_poly, w_r_poly, w_4_poly],
-            &(z_challenge * domain.element(1)),
-            saw_challenge,
-        );
-        let w_zw_comm = KZG10::<E, DensePolynomial<E::Fr>>::commit(
+        let shifted_z_challenge = z_challenge * domain.element(1);
+        let mut shifted_polynomials =
+            [z_poly, w_l_poly.clone(), w_r_poly.clone(), w_4_poly.clone()];
+        // The linearisation polynomial is opened together with the quotient,
+        // so its evaluation is left out of the proof
+        let z_h_eval = domain.evaluate_vanishing_polynomial(z_challenge);
+        let offset_poly = &lin_poly - &(&quot * z_h_eval);
+        let mut polynomials = [
+            offset_poly,
+            w_l_poly,
+            w_r_poly,
+            w_o_poly,
+            w_4_poly,
+            prover_key.permutation.left_sigma.0.clone(),
+            prover_key.permutation.right_sigma.0.clone(),
+            prover_key.permutation.out_sigma.0.clone(),
+            prover_key.arithmetic.q_arith.0.clone(),
+            prover_key.arithmetic.q_c.0.clone(),
+            prover_key.arithmetic.q_l.0.clone(),
+            prover_key.arithmetic.q_r.0.clone(),
+        ];
+        let openings = self.compute_combined_opening(
             commit_key,
-            &shifted_aggregate_witness,
-            None,
-            None,
-        )?;
+            &mut transcript,
+            (z_challenge, &polynomials),
+            (shifted_z_challenge, &shifted_polynomials),
+        );
+        wipe(
+            [&mut quot, &mut lin_poly]
+                .into_iter()
+                .chain(&mut polynomials[..5])
+                .map(|poly| &mut poly.coeffs),
+        );
+        let (w_z_comm, w_zw_comm) = openings?;
+        let mut evaluations = evaluations.proof;
+        evaluations.linearisation_polynomial_eval = E::Fr::zero();
+        wipe(shifted_polynomials.iter_mut().map(|poly| &mut poly.coeffs));
+        timer.done(ProverPhase::Opening);
 
         Ok(Proof {
-            a_comm: w_l_poly_commit.0,
-            b_comm: w_r_poly_commit.0,
-            c_comm: w_o_poly_commit.0,
-            d_comm: w_4_poly_commit.0,
-            z_comm: z_poly_commit.0,
-            t_1_comm: t_1_commit.0,
-            t_2_comm: t_2_commit.0,
-            t_3_comm: t_3_commit.0,
-            t_4_comm: t_4_commit.0,
-            w_z_comm: w_z_comm.0,
-            w_zw_comm: w_zw_comm.0,
-            evaluations: evaluations.proof,
+            version: ProofVersion::LATEST,
+            a_comm: w_l_poly_commit,
+            b_comm: w_r_poly_commit,
+            c_comm: w_o_poly_commit,
+            d_comm: w_4_poly_commit,
+            z_comm: z_poly_commit,
+            t_1_comm: t_1_commit,
+            t_2_comm: t_2_commit,
+            t_3_comm: t_3_commit,
+            t_4_comm: t_4_commit,
+            w_z_comm,
+            w_zw_comm,
+            evaluations,
             __: PhantomData,
         })
     }
@@ -461,9 +819,10 @@ where
         if self.prover_key.is_none() {
             // Preprocess circuit and store preprocessed circuit and transcript
             // in the Prover.
-            self.prover_key = Some(self.cs.preprocess_prover(
+            self.prover_key = Some(self.cs.preprocess_prover_with_backend(
                 commit_key,
                 &mut self.preprocessed_transcript,
+                &self.backend,
             )?);
         }
 
@@ -488,3 +847,172 @@ where
         Prover::new(b"plonk")
     }
 }
+
+/// Overwrites `buffers`, which hold values derived from the witness, if the
+/// `zeroize` feature is enabled.
+fn wipe<'a, F>(buffers: impl IntoIterator<Item = &'a mut Vec<F>>)
+where
+    F: Field,
+{
+    #[cfg(feature = "zeroize")]
+    for buffer in buffers {
+        zeroize::Zeroize::zeroize(buffer);
+    }
+    #[cfg(not(feature = "zeroize"))]
+    let _ = buffers;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::proof_system::Verifier;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly_commit::kzg10::KZG10;
+    use ark_poly_commit::sonic_pc::SonicKZG10;
+    use ark_poly_commit::PolynomialCommitment;
+    use rand_core::OsRng;
+
+    fn mul_gadget<E, P>(
+        composer: &mut StandardComposer<E, P>,
+        a: u64,
+        b: u64,
+        c: u64,
+    ) where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let a = composer.add_input(E::Fr::from(a));
+        let b = composer.add_input(E::Fr::from(b));
+        let result = composer.mul(
+            E::Fr::one(),
+            a,
+            b,
+            E::Fr::zero(),
+            Some(-E::Fr::from(c)),
+        );
+        composer.assert_equal(result, composer.zero_var());
+    }
+
+    fn test_prover_reuse<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(32, false, &mut OsRng)
+                .unwrap();
+        let (ck, vk) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, 8, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+        let vk = ark_poly_commit::kzg10::VerifierKey {
+            g: vk.g,
+            gamma_g: vk.gamma_g,
+            h: vk.h,
+            beta_h: vk.beta_h,
+            prepared_h: vk.prepared_h,
+            prepared_beta_h: vk.prepared_beta_h,
+        };
+
+        let mut verifier: Verifier<E, P> = Verifier::new(b"reuse");
+        verifier.key_transcript(b"key", b"seed");
+        mul_gadget(verifier.mut_cs(), 0, 0, 0);
+        verifier.preprocess(&powers).unwrap();
+
+        // A single prover produces proofs for several witnesses
+        let mut prover: Prover<E, P> = Prover::new(b"reuse");
+        prover.key_transcript(b"key", b"seed");
+        let mut scratch_buffers = None;
+        for (a, b) in [(2, 3), (4, 5), (7, 11)] {
+            mul_gadget(prover.mut_cs(), a, b, a * b);
+            let pi = prover.cs.construct_dense_pi_vec();
+            let public_inputs = prover.public_inputs();
+            assert_eq!(public_inputs.positions(), prover.cs.pi_positions());
+            assert_eq!(public_inputs.values(), [E::Fr::from(a * b)]);
+            let proof = prover.prove(&powers).unwrap();
+            assert!(prover.prover_key.is_some());
+            assert!(prover.cs.public_inputs_sparse_store.is_empty());
+            assert!(verifier.verify(&proof, &vk, &pi).is_ok());
+
+            // The scratch buffers of the first proof are reused by the
+            // following ones
+            assert!(prover.scratch.len() > 0);
+            assert_eq!(
+                *scratch_buffers.get_or_insert(prover.scratch.len()),
+                prover.scratch.len()
+            );
+        }
+        prover.release_scratch_buffers();
+        assert_eq!(prover.scratch.len(), 0);
+    }
+
+    fn test_progress_callback<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let pp =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(32, false, &mut OsRng)
+                .unwrap();
+        let (ck, _) =
+            SonicKZG10::<E, DensePolynomial<E::Fr>>::trim(&pp, 8, 0, None)
+                .unwrap();
+        let powers = Powers {
+            powers_of_g: ck.powers_of_g.into(),
+            powers_of_gamma_g: ck.powers_of_gamma_g.into(),
+        };
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut prover: Prover<E, P> = Prover::new(b"progress");
+        let sink = reports.clone();
+        prover.set_progress_callback(move |progress| {
+            sink.lock().unwrap().push(*progress)
+        });
+        mul_gadget(prover.mut_cs(), 2, 3, 6);
+        prover.prove(&powers).unwrap();
+
+        let reports = reports.lock().unwrap();
+        let phases: Vec<_> =
+            reports.iter().map(|report| report.phase).collect();
+        assert_eq!(phases, ProverPhase::ALL);
+        let percents: Vec<_> =
+            reports.iter().map(|report| report.percent).collect();
+        assert_eq!(percents, [20, 40, 60, 80, 100]);
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_prover_reuse, test_progress_callback],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_prover_reuse, test_progress_callback],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_prover_reuse, test_progress_callback],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+}
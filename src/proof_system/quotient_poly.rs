@@ -8,8 +8,10 @@ use crate::proof_system::ecc::CurveAddition;
 use crate::proof_system::ecc::FixedBaseScalarMul;
 use crate::proof_system::logic::Logic;
 use crate::proof_system::range::Range;
+use crate::proof_system::scratch::ScratchPool;
 use crate::proof_system::widget::GateConstraint;
-use crate::proof_system::GateValues;
+use crate::proof_system::{FftBackend, GateValues};
+use crate::util::{self, EvaluationDomainExt};
 use crate::{error::Error, proof_system::ProverKey};
 use ark_ec::TEModelParameters;
 use ark_ff::PrimeField;
@@ -17,11 +19,19 @@ use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
     UVPolynomial,
 };
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Number of evaluations of the quotient computed together, small enough
+/// for the rows they read to fit in cache.
+const QUOTIENT_CHUNK_SIZE: usize = 1 << 10;
 
 /// Computes the Quotient [`DensePolynomial`] given the [`EvaluationDomain`], a
 /// [`ProverKey`], and some other info.
 pub fn compute<F, P>(
     domain: &GeneralEvaluationDomain<F>,
+    fft: &dyn FftBackend<F>,
+    scratch: &ScratchPool<F>,
     prover_key: &ProverKey<F, P>,
     z_poly: &DensePolynomial<F>,
     w_l_poly: &DensePolynomial<F>,
@@ -41,78 +51,111 @@ where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
-    let domain_4n =
-        GeneralEvaluationDomain::<F>::new(4 * domain.size()).unwrap();
-
-    let mut z_eval_4n = domain_4n.coset_fft(z_poly);
-    z_eval_4n.push(z_eval_4n[0]);
-    z_eval_4n.push(z_eval_4n[1]);
-    z_eval_4n.push(z_eval_4n[2]);
-    z_eval_4n.push(z_eval_4n[3]);
-
-    let mut wl_eval_4n = domain_4n.coset_fft(w_l_poly);
-    wl_eval_4n.push(wl_eval_4n[0]);
-    wl_eval_4n.push(wl_eval_4n[1]);
-    wl_eval_4n.push(wl_eval_4n[2]);
-    wl_eval_4n.push(wl_eval_4n[3]);
-
-    let mut wr_eval_4n = domain_4n.coset_fft(w_r_poly);
-    wr_eval_4n.push(wr_eval_4n[0]);
-    wr_eval_4n.push(wr_eval_4n[1]);
-    wr_eval_4n.push(wr_eval_4n[2]);
-    wr_eval_4n.push(wr_eval_4n[3]);
-
-    let wo_eval_4n = domain_4n.coset_fft(w_o_poly);
-
-    let mut w4_eval_4n = domain_4n.coset_fft(w_4_poly);
-    w4_eval_4n.push(w4_eval_4n[0]);
-    w4_eval_4n.push(w4_eval_4n[1]);
-    w4_eval_4n.push(w4_eval_4n[2]);
-    w4_eval_4n.push(w4_eval_4n[3]);
-
-    let gate_constraints = compute_gate_constraint_satisfiability(
-        domain,
-        *range_challenge,
-        *logic_challenge,
-        *fixed_base_challenge,
-        *var_base_challenge,
-        prover_key,
-        &wl_eval_4n,
-        &wr_eval_4n,
-        &wo_eval_4n,
-        &w4_eval_4n,
-        public_inputs_poly,
-    );
-
-    let permutation = compute_permutation_checks(
-        domain,
-        prover_key,
-        &wl_eval_4n,
-        &wr_eval_4n,
-        &wo_eval_4n,
-        &w4_eval_4n,
-        &z_eval_4n,
-        *alpha,
-        *beta,
-        *gamma,
-    );
+    let domain_4n = util::evaluation_domain::<F>(4 * domain.size())?;
+
+    // Evaluates a polynomial over the coset in a scratch buffer, appending
+    // the evaluations of the first row so that the next row of the last
+    // one can be looked up
+    let eval_4n = |poly: &DensePolynomial<F>| {
+        let mut evals = scratch.take_copy(poly);
+        fft.coset_fft_in_place(&domain_4n, &mut evals);
+        evals.extend_from_within(..4);
+        evals
+    };
+    let z_eval_4n = eval_4n(z_poly);
+    let wl_eval_4n = eval_4n(w_l_poly);
+    let wr_eval_4n = eval_4n(w_r_poly);
+    let wo_eval_4n = eval_4n(w_o_poly);
+    let w4_eval_4n = eval_4n(w_4_poly);
+
+    let mut pi_eval_4n = scratch.take_copy(public_inputs_poly);
+    fft.coset_fft_in_place(&domain_4n, &mut pi_eval_4n);
 
-    let quotient = (0..domain_4n.size())
-        .map(|i| {
-            let numerator = gate_constraints[i] + permutation[i];
+    let l1_poly_alpha =
+        compute_first_lagrange_poly_scaled(domain, fft, alpha.square());
+    let mut l1_alpha_sq_evals = scratch.take_copy(&l1_poly_alpha);
+    fft.coset_fft_in_place(&domain_4n, &mut l1_alpha_sq_evals);
+
+    // Every widget is evaluated on a chunk of rows before moving on to the
+    // next one, so that the rows stay in cache. The points of the coset are
+    // generated as the chunk is traversed rather than stored in the prover
+    // key.
+    let coset_generator = F::multiplicative_generator();
+    let group_gen = domain_4n.group_gen();
+    let compute_chunk = |(chunk_index, chunk): (usize, &mut [F])| {
+        let start = chunk_index * QUOTIENT_CHUNK_SIZE;
+        let mut point = coset_generator * domain_4n.element(start);
+        for (i, value) in (start..).zip(chunk) {
+            let gate_constraints = compute_gate_constraints_i(
+                i,
+                *range_challenge,
+                *logic_challenge,
+                *fixed_base_challenge,
+                *var_base_challenge,
+                prover_key,
+                &wl_eval_4n,
+                &wr_eval_4n,
+                &wo_eval_4n,
+                &w4_eval_4n,
+            );
+            let permutation = prover_key.permutation.compute_quotient_i(
+                i,
+                point,
+                wl_eval_4n[i],
+                wr_eval_4n[i],
+                wo_eval_4n[i],
+                w4_eval_4n[i],
+                z_eval_4n[i],
+                z_eval_4n[i + 4],
+                *alpha,
+                l1_alpha_sq_evals[i],
+                *beta,
+                *gamma,
+            );
             let denominator = prover_key.v_h_coset_4n()[i];
-            numerator * denominator.inverse().unwrap()
-        })
-        .collect::<Vec<_>>();
-
-    Ok(DensePolynomial {
-        coeffs: domain_4n.coset_ifft(&quotient),
-    })
+            *value = (gate_constraints + pi_eval_4n[i] + permutation)
+                * denominator.inverse().unwrap();
+            point *= group_gen;
+        }
+    };
+
+    let mut quotient = scratch.take();
+    quotient.resize(domain_4n.size(), F::zero());
+    #[cfg(feature = "parallel")]
+    quotient
+        .par_chunks_mut(QUOTIENT_CHUNK_SIZE)
+        .enumerate()
+        .for_each(compute_chunk);
+    #[cfg(not(feature = "parallel"))]
+    quotient
+        .chunks_mut(QUOTIENT_CHUNK_SIZE)
+        .enumerate()
+        .for_each(compute_chunk);
+
+    fft.coset_ifft_in_place(&domain_4n, &mut quotient);
+    let quotient_poly = DensePolynomial {
+        coeffs: quotient.to_vec(),
+    };
+
+    for buffer in [
+        z_eval_4n,
+        wl_eval_4n,
+        wr_eval_4n,
+        wo_eval_4n,
+        w4_eval_4n,
+        pi_eval_4n,
+        l1_alpha_sq_evals,
+        quotient,
+    ] {
+        scratch.give(buffer);
+    }
+    Ok(quotient_poly)
 }
 
-/// Ensures that the gate constraints are satisfied.
-fn compute_gate_constraint_satisfiability<F, P>(
-    domain: &GeneralEvaluationDomain<F>,
+/// Evaluates the gate constraints at the `i`-th point of the coset, without
+/// the public inputs.
+fn compute_gate_constraints_i<F, P>(
+    i: usize,
     range_challenge: F,
     logic_challenge: F,
     fixed_base_challenge: F,
@@ -122,119 +165,85 @@ fn compute_gate_constraint_satisfiability<F, P>(
     wr_eval_4n: &[F],
     wo_eval_4n: &[F],
     w4_eval_4n: &[F],
-    pi_poly: &DensePolynomial<F>,
-) -> Vec<F>
+) -> F
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
 {
-    let domain_4n =
-        GeneralEvaluationDomain::<F>::new(4 * domain.size()).unwrap();
-    let pi_eval_4n = domain_4n.coset_fft(pi_poly);
-
-    (0..domain_4n.size())
-        .map(|i| {
-            let values = GateValues {
-                left: wl_eval_4n[i],
-                right: wr_eval_4n[i],
-                output: wo_eval_4n[i],
-                fourth: w4_eval_4n[i],
-                left_next: wl_eval_4n[i + 4],
-                right_next: wr_eval_4n[i + 4],
-                fourth_next: w4_eval_4n[i + 4],
-                left_selector: prover_key.arithmetic.q_l.1[i],
-                right_selector: prover_key.arithmetic.q_r.1[i],
-                constant_selector: prover_key.arithmetic.q_c.1[i],
-            };
-
-            let arithmetic = prover_key.arithmetic.compute_quotient_i(
-                i,
-                values.left,
-                values.right,
-                values.output,
-                values.fourth,
-            );
-
-            let range = Range::quotient_term(
-                prover_key.range_selector.1[i],
-                range_challenge,
-                values,
-            );
-
-            let logic = Logic::quotient_term(
-                prover_key.logic_selector.1[i],
-                logic_challenge,
-                values,
-            );
+    let values = GateValues {
+        left: wl_eval_4n[i],
+        right: wr_eval_4n[i],
+        output: wo_eval_4n[i],
+        fourth: w4_eval_4n[i],
+        left_next: wl_eval_4n[i + 4],
+        right_next: wr_eval_4n[i + 4],
+        fourth_next: w4_eval_4n[i + 4],
+        left_selector: prover_key.arithmetic.q_l.1[i],
+        right_selector: prover_key.arithmetic.q_r.1[i],
+        constant_selector: prover_key.arithmetic.q_c.1[i],
+    };
+
+    let arithmetic = prover_key.arithmetic.compute_quotient_i(
+        i,
+        values.left,
+        values.right,
+        values.output,
+        values.fourth,
+        values.left_next,
+    );
 
-            let fixed_base_scalar_mul =
+    // The evaluations of the selectors of unused custom gates may have been
+    // dropped by `ProverKey::compress_selectors`
+    let range = prover_key
+        .range_selector
+        .1
+        .evals
+        .get(i)
+        .map_or(F::zero(), |selector| {
+            Range::quotient_term(*selector, range_challenge, values)
+        });
+
+    let logic = prover_key
+        .logic_selector
+        .1
+        .evals
+        .get(i)
+        .map_or(F::zero(), |selector| {
+            Logic::quotient_term(*selector, logic_challenge, values)
+        });
+
+    let fixed_base_scalar_mul =
+        prover_key.fixed_group_add_selector.1.evals.get(i).map_or(
+            F::zero(),
+            |selector| {
                 FixedBaseScalarMul::<_, P>::quotient_term(
-                    prover_key.fixed_group_add_selector.1[i],
+                    *selector,
                     fixed_base_challenge,
                     values,
-                );
-
-            let curve_addition = CurveAddition::<_, P>::quotient_term(
-                prover_key.variable_group_add_selector.1[i],
+                )
+            },
+        );
+
+    let curve_addition = prover_key
+        .variable_group_add_selector
+        .1
+        .evals
+        .get(i)
+        .map_or(F::zero(), |selector| {
+            CurveAddition::<_, P>::quotient_term(
+                *selector,
                 var_base_challenge,
                 values,
-            );
-
-            (arithmetic + pi_eval_4n[i])
-                + range
-                + logic
-                + fixed_base_scalar_mul
-                + curve_addition
-        })
-        .collect()
-}
-
-/// Computes the permutation contribution to the quotient polynomial over
-/// `domain`.
-fn compute_permutation_checks<F, P>(
-    domain: &GeneralEvaluationDomain<F>,
-    prover_key: &ProverKey<F, P>,
-    wl_eval_4n: &[F],
-    wr_eval_4n: &[F],
-    wo_eval_4n: &[F],
-    w4_eval_4n: &[F],
-    z_eval_4n: &[F],
-    alpha: F,
-    beta: F,
-    gamma: F,
-) -> Vec<F>
-where
-    F: PrimeField,
-    P: TEModelParameters<BaseField = F>,
-{
-    let domain_4n =
-        GeneralEvaluationDomain::<F>::new(4 * domain.size()).unwrap();
-    let l1_poly_alpha =
-        compute_first_lagrange_poly_scaled(domain, alpha.square());
-    let l1_alpha_sq_evals = domain_4n.coset_fft(&l1_poly_alpha.coeffs);
-
-    (0..domain_4n.size())
-        .map(|i| {
-            prover_key.permutation.compute_quotient_i(
-                i,
-                wl_eval_4n[i],
-                wr_eval_4n[i],
-                wo_eval_4n[i],
-                w4_eval_4n[i],
-                z_eval_4n[i],
-                z_eval_4n[i + 4],
-                alpha,
-                l1_alpha_sq_evals[i],
-                beta,
-                gamma,
             )
-        })
-        .collect()
+        });
+
+    arithmetic + range + logic + fixed_base_scalar_mul + curve_addition
 }
 
 /// Computes the first lagrange polynomial with the given `scale` over `domain`.
 fn compute_first_lagrange_poly_scaled<F>(
     domain: &GeneralEvaluationDomain<F>,
+    fft: &dyn FftBackend<F>,
     scale: F,
 ) -> DensePolynomial<F>
 where
@@ -242,6 +251,5 @@ where
 {
     let mut x_evals = vec![F::zero(); domain.size()];
     x_evals[0] = scale;
-    domain.ifft_in_place(&mut x_evals);
-    DensePolynomial::from_coefficients_vec(x_evals)
+    DensePolynomial::from_coefficients_vec(fft.ifft(domain, &x_evals))
 }
@@ -0,0 +1,96 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Scratch buffers reused across the FFTs of the prover.
+//!
+//! The quotient polynomial is computed from the evaluations of a handful of
+//! polynomials over a coset four times larger than the circuit domain. A
+//! [`ScratchPool`] keeps these buffers around once they are released, so
+//! that the following polynomials and proofs don't allocate them again.
+
+use std::sync::Mutex;
+
+/// Pool of buffers handed out by [`ScratchPool::take`] and given back with
+/// [`ScratchPool::give`].
+#[derive(Debug, Default)]
+pub(crate) struct ScratchPool<F> {
+    /// Released buffers
+    buffers: Mutex<Vec<Vec<F>>>,
+}
+
+impl<F> ScratchPool<F>
+where
+    F: Clone,
+{
+    /// Takes an empty buffer out of the pool, allocating a new one if the
+    /// pool is empty.
+    pub(crate) fn take(&self) -> Vec<F> {
+        let mut buffer = self.buffers.lock().unwrap().pop().unwrap_or_default();
+        buffer.clear();
+        buffer
+    }
+
+    /// Takes a buffer out of the pool holding a copy of `values`.
+    pub(crate) fn take_copy(&self, values: &[F]) -> Vec<F> {
+        let mut buffer = self.take();
+        buffer.extend_from_slice(values);
+        buffer
+    }
+
+    /// Gives `buffer` back to the pool for reuse.
+    #[cfg(not(feature = "zeroize"))]
+    pub(crate) fn give(&self, buffer: Vec<F>) {
+        self.buffers.lock().unwrap().push(buffer);
+    }
+
+    /// Gives `buffer` back to the pool for reuse, overwriting its values
+    /// since they may be derived from the witness.
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn give(&self, mut buffer: Vec<F>)
+    where
+        F: zeroize::Zeroize,
+    {
+        zeroize::Zeroize::zeroize(&mut buffer);
+        self.buffers.lock().unwrap().push(buffer);
+    }
+
+    /// Frees the buffers of the pool.
+    pub(crate) fn clear(&self) {
+        self.buffers.lock().unwrap().clear();
+    }
+
+    /// Returns the number of buffers in the pool.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.buffers.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scratch_reuse() {
+        let pool = ScratchPool::<u64>::default();
+        let buffer = pool.take_copy(&[1, 2, 3]);
+        assert_eq!(buffer, [1, 2, 3]);
+        let ptr = buffer.as_ptr();
+        pool.give(buffer);
+        assert_eq!(pool.len(), 1);
+
+        // The allocation is handed out again, emptied
+        let buffer = pool.take();
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 3);
+        assert_eq!(buffer.as_ptr(), ptr);
+        assert_eq!(pool.len(), 0);
+
+        pool.give(buffer);
+        pool.clear();
+        assert_eq!(pool.len(), 0);
+    }
+}
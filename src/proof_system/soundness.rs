@@ -0,0 +1,300 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Negative-path checks of the verification of a circuit.
+//!
+//! Starting from a valid [`Proof`] and its [`PublicInputs`], every element
+//! of the proof and every public input is perturbed in turn, and the
+//! verifier must reject all of the perturbed pairs. A perturbation the
+//! verifier accepts points at an element it doesn't check, as in most
+//! soundness bugs of the verifier.
+
+use crate::circuit::PublicInputs;
+use crate::error::Error;
+use crate::proof_system::Proof;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve, TEModelParameters};
+use ark_poly_commit::kzg10::Commitment;
+use num_traits::One;
+
+/// Element of a proof, or public input, altered by a perturbation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProofElement {
+    /// Commitment of the proof, by name
+    Commitment(&'static str),
+
+    /// Evaluation of the proof, by name
+    Evaluation(&'static str),
+
+    /// Public input at the given gate position
+    PublicInput(usize),
+}
+
+/// Name of an element of a [`Proof`], with its accessor.
+type Element<E, P, T> = (&'static str, fn(&mut Proof<E, P>) -> &mut T);
+
+/// Returns a copy of `proof` for each of its elements, with that element
+/// perturbed: the generator of `G1` is added to the commitments, and one to
+/// the evaluations.
+pub fn perturbed_proofs<E, P>(
+    proof: &Proof<E, P>,
+) -> Vec<(ProofElement, Proof<E, P>)>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let commitments: [Element<E, P, Commitment<E>>; 11] = [
+        ("a_comm", |proof| &mut proof.a_comm),
+        ("b_comm", |proof| &mut proof.b_comm),
+        ("c_comm", |proof| &mut proof.c_comm),
+        ("d_comm", |proof| &mut proof.d_comm),
+        ("z_comm", |proof| &mut proof.z_comm),
+        ("t_1_comm", |proof| &mut proof.t_1_comm),
+        ("t_2_comm", |proof| &mut proof.t_2_comm),
+        ("t_3_comm", |proof| &mut proof.t_3_comm),
+        ("t_4_comm", |proof| &mut proof.t_4_comm),
+        ("w_z_comm", |proof| &mut proof.w_z_comm),
+        ("w_zw_comm", |proof| &mut proof.w_zw_comm),
+    ];
+    let evaluations: [Element<E, P, E::Fr>; 16] = [
+        ("a_eval", |proof| &mut proof.evaluations.a_eval),
+        ("b_eval", |proof| &mut proof.evaluations.b_eval),
+        ("c_eval", |proof| &mut proof.evaluations.c_eval),
+        ("d_eval", |proof| &mut proof.evaluations.d_eval),
+        ("a_next_eval", |proof| &mut proof.evaluations.a_next_eval),
+        ("b_next_eval", |proof| &mut proof.evaluations.b_next_eval),
+        ("d_next_eval", |proof| &mut proof.evaluations.d_next_eval),
+        ("q_arith_eval", |proof| &mut proof.evaluations.q_arith_eval),
+        ("q_c_eval", |proof| &mut proof.evaluations.q_c_eval),
+        ("q_l_eval", |proof| &mut proof.evaluations.q_l_eval),
+        ("q_r_eval", |proof| &mut proof.evaluations.q_r_eval),
+        ("left_sigma_eval", |proof| {
+            &mut proof.evaluations.left_sigma_eval
+        }),
+        ("right_sigma_eval", |proof| {
+            &mut proof.evaluations.right_sigma_eval
+        }),
+        ("out_sigma_eval", |proof| {
+            &mut proof.evaluations.out_sigma_eval
+        }),
+        ("permutation_eval", |proof| {
+            &mut proof.evaluations.permutation_eval
+        }),
+        ("linearisation_polynomial_eval", |proof| {
+            &mut proof.evaluations.linearisation_polynomial_eval
+        }),
+    ];
+
+    let generator = E::G1Affine::prime_subgroup_generator();
+    let mut perturbed = Vec::new();
+    for (name, element) in commitments {
+        let mut proof = proof.clone();
+        let commitment = element(&mut proof);
+        commitment.0 = commitment
+            .0
+            .into_projective()
+            .add_mixed(&generator)
+            .into_affine();
+        perturbed.push((ProofElement::Commitment(name), proof));
+    }
+    for (name, element) in evaluations {
+        // Proofs don't carry the evaluation of the linearisation polynomial
+        if name == "linearisation_polynomial_eval" {
+            continue;
+        }
+        let mut proof = proof.clone();
+        *element(&mut proof) += E::Fr::one();
+        perturbed.push((ProofElement::Evaluation(name), proof));
+    }
+    perturbed
+}
+
+/// Returns a copy of `public_inputs` for each of them, with one added to
+/// its value.
+pub fn perturbed_public_inputs<E>(
+    public_inputs: &PublicInputs<E>,
+) -> Vec<(ProofElement, PublicInputs<E>)>
+where
+    E: PairingEngine,
+{
+    public_inputs
+        .positions()
+        .iter()
+        .zip(public_inputs.values())
+        .map(|(position, value)| {
+            let mut perturbed = public_inputs.clone();
+            perturbed.insert(*position, *value + E::Fr::one());
+            (ProofElement::PublicInput(*position), perturbed)
+        })
+        .collect()
+}
+
+/// Returns the perturbations of `proof` and `public_inputs` accepted by
+/// `verify`, which should be none.
+///
+/// Fails with the error of `verify` if it rejects the unperturbed pair.
+pub fn accepted_perturbations<E, P, F>(
+    proof: &Proof<E, P>,
+    public_inputs: &PublicInputs<E>,
+    verify: F,
+) -> Result<Vec<ProofElement>, Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    F: Fn(&Proof<E, P>, &PublicInputs<E>) -> Result<(), Error>,
+{
+    verify(proof, public_inputs)?;
+    let mut accepted = Vec::new();
+    for (element, proof) in perturbed_proofs(proof) {
+        if verify(&proof, public_inputs).is_ok() {
+            accepted.push(element);
+        }
+    }
+    for (element, public_inputs) in perturbed_public_inputs(public_inputs) {
+        if verify(proof, &public_inputs).is_ok() {
+            accepted.push(element);
+        }
+    }
+    Ok(accepted)
+}
+
+/// Asserts that `verify` accepts `proof` and `public_inputs`, and rejects
+/// all of their perturbations.
+///
+/// # Panics
+///
+/// Panics if the unperturbed pair is rejected, or lists the perturbations
+/// that are accepted.
+pub fn assert_sound<E, P, F>(
+    proof: &Proof<E, P>,
+    public_inputs: &PublicInputs<E>,
+    verify: F,
+) where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    F: Fn(&Proof<E, P>, &PublicInputs<E>) -> Result<(), Error>,
+{
+    let accepted = accepted_perturbations(proof, public_inputs, verify)
+        .expect("the valid proof must verify");
+    assert!(
+        accepted.is_empty(),
+        "the verifier accepted perturbed proofs: {:?}",
+        accepted
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test;
+    use crate::circuit::{verify_prepared_proof, Circuit, FeIntoPubInput};
+    use crate::config::ProverConfig;
+    use crate::constraint_system::StandardComposer;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use num_traits::Zero;
+    use rand_core::OsRng;
+
+    // Implements a circuit that checks:
+    // 1) a * b = c where C is a PI
+    // 2) a <= 2^8
+    #[derive(derivative::Derivative)]
+    #[derivative(Default(bound = ""))]
+    struct TestCircuit<E: PairingEngine> {
+        a: E::Fr,
+        b: E::Fr,
+        c: E::Fr,
+    }
+
+    impl<E, P> Circuit<E, P> for TestCircuit<E>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let c = composer.mul(E::Fr::one(), a, b, E::Fr::zero(), None);
+            composer.constrain_to_constant(c, E::Fr::zero(), Some(-self.c));
+            composer.range_gate(a, 8);
+            Ok(())
+        }
+    }
+
+    fn test_soundness<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let pp = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 6,
+            false,
+            &mut OsRng,
+        )
+        .unwrap();
+        let (pk, verifier_data) =
+            Circuit::<E, P>::compile(&mut TestCircuit::<E>::default(), &pp)
+                .unwrap();
+        let prepared_key = verifier_data.key.prepare(&pp).unwrap();
+        let public_inputs = verifier_data
+            .public_inputs(&[E::Fr::from(42u64).into_pi()])
+            .unwrap();
+
+        let config = ProverConfig::new(b"Test");
+        let mut circuit = TestCircuit::<E> {
+            a: E::Fr::from(6u64),
+            b: E::Fr::from(7u64),
+            c: E::Fr::from(42u64),
+        };
+        let proof = circuit.gen_proof(&pp, &pk, &config).unwrap();
+        let verify = |proof: &Proof<E, P>, pi: &PublicInputs<E>| {
+            verify_prepared_proof(
+                &prepared_key,
+                proof,
+                pi,
+                &config.verifier_config(),
+            )
+        };
+        assert_sound(&proof, &public_inputs, verify);
+
+        // A verifier skipping the public inputs is caught
+        let skipping = |proof: &Proof<E, P>, _: &PublicInputs<E>| {
+            verify(proof, &public_inputs)
+        };
+        assert_eq!(
+            accepted_perturbations(&proof, &public_inputs, skipping).unwrap(),
+            public_inputs
+                .positions()
+                .iter()
+                .map(|position| ProofElement::PublicInput(*position))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_soundness],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_soundness],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
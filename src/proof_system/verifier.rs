@@ -9,8 +9,9 @@
 use crate::constraint_system::StandardComposer;
 use crate::error::Error;
 use crate::proof_system::widget::VerifierKey as PlonkVerifierKey;
-use crate::proof_system::Proof;
-use crate::transcript::TranscriptWrapper;
+use crate::proof_system::{Backend, Proof, VerificationTrace};
+use crate::transcript::{TranscriptLog, TranscriptWrapper};
+use crate::util;
 use ark_ec::{PairingEngine, TEModelParameters};
 use ark_poly_commit::kzg10::{Powers, VerifierKey};
 
@@ -33,6 +34,9 @@ where
     /// not copied, then the verification procedure will modify the transcript,
     /// making it unusable for future proofs.
     pub preprocessed_transcript: TranscriptWrapper<E>,
+
+    /// MSM and FFT backends used to preprocess the circuit.
+    pub(crate) backend: Backend<E>,
 }
 
 impl<E, P> Verifier<E, P>
@@ -41,20 +45,22 @@ where
     P: TEModelParameters<BaseField = E::Fr>,
 {
     /// Creates a new `Verifier` instance.
-    pub fn new(label: &'static [u8]) -> Self {
+    pub fn new(label: &[u8]) -> Self {
         Self {
             verifier_key: None,
             cs: StandardComposer::new(),
             preprocessed_transcript: TranscriptWrapper::new(label),
+            backend: Backend::default(),
         }
     }
 
     /// Creates a new `Verifier` instance with some expected size.
-    pub fn with_expected_size(label: &'static [u8], size: usize) -> Self {
+    pub fn with_expected_size(label: &[u8], size: usize) -> Self {
         Self {
             verifier_key: None,
             cs: StandardComposer::with_expected_size(size),
             preprocessed_transcript: TranscriptWrapper::new(label),
+            backend: Backend::default(),
         }
     }
 
@@ -63,6 +69,14 @@ where
         self.cs.circuit_size()
     }
 
+    /// Sets the [`Backend`] computing the commitments of the following
+    /// preprocessing, which is the [`CpuBackend`] by default.
+    ///
+    /// [`CpuBackend`]: crate::proof_system::CpuBackend
+    pub fn set_backend(&mut self, backend: Backend<E>) {
+        self.backend = backend;
+    }
+
     /// Returns a mutable copy of the underlying composer.
     pub fn mut_cs(&mut self) -> &mut StandardComposer<E, P> {
         &mut self.cs
@@ -72,9 +86,10 @@ where
     /// descriptor so that the `Verifier` instance can verify [`Proof`]s
     /// for this circuit descriptor instance.
     pub fn preprocess(&mut self, commit_key: &Powers<E>) -> Result<(), Error> {
-        let vk = self.cs.preprocess_verifier(
+        let vk = self.cs.preprocess_verifier_with_backend(
             commit_key,
             &mut self.preprocessed_transcript,
+            &self.backend,
         )?;
 
         self.verifier_key = Some(vk);
@@ -87,9 +102,17 @@ where
     /// [`Transcript`]: merlin::Transcript
     /// [`Transcript::append_message`]: merlin::Transcript::append_message
     pub fn key_transcript(&mut self, label: &'static [u8], message: &[u8]) {
-        self.preprocessed_transcript
-            .transcript
-            .append_message(label, message);
+        self.preprocessed_transcript.append_message(label, message);
+    }
+
+    /// Starts recording the messages appended to the transcript and the
+    /// challenges drawn from it, including those of the following proofs,
+    /// and returns the record.
+    ///
+    /// This is a debugging aid to compare the transcripts of two
+    /// implementations of the protocol event by event.
+    pub fn record_transcript(&mut self) -> TranscriptLog {
+        self.preprocessed_transcript.record()
     }
 
     /// Verifies a [`Proof`] using `pc_verifier_key` and `public_inputs`.
@@ -99,8 +122,29 @@ where
         pc_verifier_key: &VerifierKey<E>,
         public_inputs: &[E::Fr],
     ) -> Result<(), Error> {
+        let verifier_key = self.verifier_key.as_ref().unwrap();
         proof.verify(
-            self.verifier_key.as_ref().unwrap(),
+            verifier_key,
+            &util::evaluation_domain(verifier_key.n)?,
+            &mut self.preprocessed_transcript.clone(),
+            pc_verifier_key,
+            public_inputs,
+        )
+    }
+
+    /// Runs the same checks as [`Verifier::verify`] but returns the
+    /// challenges and intermediate values computed along the way, whether
+    /// the [`Proof`] is valid or not.
+    pub fn verify_debug(
+        &self,
+        proof: &Proof<E, P>,
+        pc_verifier_key: &VerifierKey<E>,
+        public_inputs: &[E::Fr],
+    ) -> Result<VerificationTrace<E>, Error> {
+        let verifier_key = self.verifier_key.as_ref().unwrap();
+        proof.verification_trace(
+            verifier_key,
+            &util::evaluation_domain(verifier_key.n)?,
             &mut self.preprocessed_transcript.clone(),
             pc_verifier_key,
             public_inputs,
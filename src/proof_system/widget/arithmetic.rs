@@ -41,6 +41,9 @@ where
 
     /// Arithmetic Selector
     pub q_arith: (DensePolynomial<F>, Evaluations<F>),
+
+    /// Lookahead Selector
+    pub q_lookahead: (DensePolynomial<F>, Evaluations<F>),
 }
 
 impl<F> ProverKey<F>
@@ -56,12 +59,14 @@ where
         w_r_i: F,
         w_o_i: F,
         w_4_i: F,
+        w_l_next_i: F,
     ) -> F {
         ((w_l_i * w_r_i * self.q_m.1[index])
             + (w_l_i * self.q_l.1[index])
             + (w_r_i * self.q_r.1[index])
             + (w_o_i * self.q_o.1[index])
             + (w_4_i * self.q_4.1[index])
+            + (w_l_next_i * self.q_lookahead.1[index])
             + self.q_c.1[index])
             * self.q_arith.1[index]
     }
@@ -74,13 +79,15 @@ where
         b_eval: F,
         c_eval: F,
         d_eval: F,
+        a_next_eval: F,
         q_arith_eval: F,
     ) -> DensePolynomial<F> {
         &(&((&self.q_m.0 * (a_eval * b_eval))
             + (&self.q_l.0 * a_eval)
             + (&self.q_r.0 * b_eval)
             + (&self.q_o.0 * c_eval)
-            + (&self.q_4.0 * d_eval))
+            + (&self.q_4.0 * d_eval)
+            + (&self.q_lookahead.0 * a_next_eval))
             + &self.q_c.0)
             * q_arith_eval
     }
@@ -113,6 +120,9 @@ where
 
     /// Arithmetic Selector Commitment
     pub q_arith: Commitment<E>,
+
+    /// Lookahead Selector Commitment
+    pub q_lookahead: Commitment<E>,
 }
 
 impl<E> VerifierKey<E>
@@ -144,6 +154,9 @@ where
         scalars.push(evaluations.d_eval * q_arith_eval);
         points.push(self.q_4.0);
 
+        scalars.push(evaluations.a_next_eval * q_arith_eval);
+        points.push(self.q_lookahead.0);
+
         scalars.push(q_arith_eval);
         points.push(self.q_c.0);
     }
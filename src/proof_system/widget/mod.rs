@@ -6,27 +6,36 @@
 
 //! Proof System Widgets
 
-pub mod arithmetic;
-pub mod ecc;
-pub mod logic;
-pub mod range;
+pub(crate) mod arithmetic;
+pub(crate) mod ecc;
+pub(crate) mod logic;
+pub(crate) mod range;
 
+use crate::error::Error;
 use crate::proof_system::linearisation_poly::ProofEvaluations;
-use crate::proof_system::permutation;
+use crate::proof_system::{permutation, proof, PointEncoding};
 use crate::transcript::TranscriptProtocol;
-use ark_ec::{PairingEngine, TEModelParameters};
+use crate::util;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve, TEModelParameters};
 use ark_ff::{Field, PrimeField};
-use ark_poly::{univariate::DensePolynomial, Evaluations};
+use ark_poly::{
+    univariate::DensePolynomial, Evaluations, GeneralEvaluationDomain,
+};
+use ark_poly_commit::kzg10::Powers;
 use ark_poly_commit::sonic_pc::Commitment;
 use ark_serialize::*;
+use core::convert::TryFrom;
 use core::marker::PhantomData;
+use merlin::Transcript;
+use num_traits::Zero;
+use rand_core::RngCore;
 
 /// Gate Values
 ///
 /// This data structures holds the wire values for a given gate.
 #[derive(derivative::Derivative)]
 #[derivative(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
-pub struct GateValues<F>
+pub(crate) struct GateValues<F>
 where
     F: Field,
 {
@@ -68,7 +77,7 @@ where
 }
 
 /// Gate Constraint
-pub trait GateConstraint<F>
+pub(crate) trait GateConstraint<F>
 where
     F: Field,
 {
@@ -114,6 +123,11 @@ where
     ) where
         E: PairingEngine<Fr = F>,
     {
+        // The selectors of gates the circuit doesn't use commit to the
+        // identity and don't contribute to the linearisation commitment
+        if selector_commitment.0.is_zero() {
+            return;
+        }
         let coefficient = Self::constraints(
             separation_challenge,
             GateValues {
@@ -134,6 +148,32 @@ where
     }
 }
 
+/// Fixed-size byte string used to bind keys to the circuit they were
+/// generated for.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub(crate) struct Bytes32(pub(crate) [u8; 32]);
+
+impl CanonicalSerialize for Bytes32 {
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        Ok(writer.write_all(&self.0)?)
+    }
+
+    fn serialized_size(&self) -> usize {
+        32
+    }
+}
+
+impl CanonicalDeserialize for Bytes32 {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let mut bytes = [0u8; 32];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
 /// PLONK circuit Verification Key.
 ///
 /// This structure is used by the Verifier in order to verify a
@@ -171,6 +211,9 @@ where
     /// VerifierKey for permutation checks
     pub(crate) permutation: permutation::VerifierKey<E>,
 
+    /// Identifier of the circuit this key is bound to, if any.
+    pub(crate) circuit_id: Option<Bytes32>,
+
     /// Type Parameter Marker
     __: PhantomData<P>,
 }
@@ -192,6 +235,7 @@ where
         q_4: Commitment<E>,
         q_c: Commitment<E>,
         q_arith: Commitment<E>,
+        q_lookahead: Commitment<E>,
         q_range: Commitment<E>,
         q_logic: Commitment<E>,
         q_fixed_group_add: Commitment<E>,
@@ -211,6 +255,7 @@ where
                 q_4,
                 q_c,
                 q_arith,
+                q_lookahead,
             },
             range_selector_commitment: q_range,
             logic_selector_commitment: q_logic,
@@ -222,6 +267,7 @@ where
                 out_sigma,
                 fourth_sigma,
             },
+            circuit_id: None,
             __: PhantomData,
         }
     }
@@ -230,6 +276,127 @@ where
     pub fn padded_circuit_size(&self) -> usize {
         self.n.next_power_of_two()
     }
+
+    /// Binds this key and the matching `prover_key` to the circuit
+    /// identified by `circuit_id`.
+    ///
+    /// Once bound, the circuit identifier and the digest of this key are
+    /// appended to the transcript before any proof element, so that a
+    /// [`Proof`](super::Proof) generated for one circuit can't be verified
+    /// against another one even if both use the same transcript label.
+    pub fn bind_circuit(
+        &mut self,
+        circuit_id: [u8; 32],
+        prover_key: &mut ProverKey<E::Fr, P>,
+    ) {
+        self.circuit_id = Some(Bytes32(circuit_id));
+        prover_key.circuit_id = Some(Bytes32(circuit_id));
+        prover_key.vk_digest = Bytes32(self.digest());
+    }
+
+    /// Applies the `blinding` returned by [`ProverKey::rerandomize`] for the
+    /// matching `prover_key` to the selector commitments of this key, and
+    /// updates the verifier key digest recorded in `prover_key`.
+    ///
+    /// `commit_key` must hold the powers of a circuit of the padded size of
+    /// the key, otherwise [`Error::UniversalParamsTooSmall`] is returned and
+    /// the keys no longer match.
+    pub fn rerandomize(
+        &mut self,
+        commit_key: &Powers<E>,
+        blinding: &SelectorBlinding<E::Fr>,
+        prover_key: &mut ProverKey<E::Fr, P>,
+    ) -> Result<(), Error> {
+        let vanishing = vanishing_commitment::<E>(
+            &commit_key.powers_of_g,
+            self.padded_circuit_size(),
+        )?;
+        for (commitment, factor) in [
+            &mut self.arithmetic.q_m,
+            &mut self.arithmetic.q_l,
+            &mut self.arithmetic.q_r,
+            &mut self.arithmetic.q_o,
+            &mut self.arithmetic.q_4,
+            &mut self.arithmetic.q_c,
+            &mut self.arithmetic.q_arith,
+            &mut self.arithmetic.q_lookahead,
+            &mut self.range_selector_commitment,
+            &mut self.logic_selector_commitment,
+            &mut self.fixed_group_add_selector_commitment,
+            &mut self.variable_group_add_selector_commitment,
+        ]
+        .into_iter()
+        .zip(&blinding.factors)
+        {
+            if !factor.is_zero() {
+                commitment.0 = (vanishing.mul(factor.into_repr())
+                    + commitment.0.into_projective())
+                .into_affine();
+            }
+        }
+        prover_key.vk_digest = Bytes32(self.digest());
+        Ok(())
+    }
+
+    /// Returns the selector commitments of the key, in the order of the
+    /// factors of a [`SelectorBlinding`].
+    pub(crate) fn selector_commitments(&self) -> [&Commitment<E>; 12] {
+        [
+            &self.arithmetic.q_m,
+            &self.arithmetic.q_l,
+            &self.arithmetic.q_r,
+            &self.arithmetic.q_o,
+            &self.arithmetic.q_4,
+            &self.arithmetic.q_c,
+            &self.arithmetic.q_arith,
+            &self.arithmetic.q_lookahead,
+            &self.range_selector_commitment,
+            &self.logic_selector_commitment,
+            &self.fixed_group_add_selector_commitment,
+            &self.variable_group_add_selector_commitment,
+        ]
+    }
+
+    /// Returns a domain-separated 32-byte digest of the canonical
+    /// serialization of the key.
+    ///
+    /// The digest is a stable identifier of the circuit the key was
+    /// generated for, and can be checked against the
+    /// [`ProverKey::verifier_key_digest`] of a prover key to ensure both keys
+    /// match.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        self.serialize(&mut bytes)
+            .expect("serialization into a vector can't fail");
+        let mut transcript = Transcript::new(b"ark-plonk-verifier-key");
+        transcript.append_message(b"key", &bytes);
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"digest", &mut digest);
+        digest
+    }
+
+    /// Returns a domain-separated 32-byte digest of the circuit the key was
+    /// generated for, i.e. of its size and of the commitments to its
+    /// selector and permutation polynomials.
+    ///
+    /// Unlike [`VerifierKey::digest`], it doesn't depend on the circuit
+    /// identifier the key is bound to, so it can be used to derive that
+    /// identifier from the compiled circuit.
+    pub fn circuit_digest(&self) -> [u8; 32] {
+        let unbound = Self {
+            circuit_id: None,
+            ..self.clone()
+        };
+        let mut bytes = Vec::with_capacity(unbound.serialized_size());
+        unbound
+            .serialize(&mut bytes)
+            .expect("serialization into a vector can't fail");
+        let mut transcript = Transcript::new(b"ark-plonk-circuit");
+        transcript.append_message(b"circuit", &bytes);
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"digest", &mut digest);
+        digest
+    }
 }
 
 impl<E, P> VerifierKey<E, P>
@@ -237,6 +404,90 @@ where
     E: PairingEngine,
     P: TEModelParameters<BaseField = E::Fr>,
 {
+    /// Deserializes a key from an untrusted source, checking that every
+    /// commitment is on the curve and in its prime order subgroup.
+    ///
+    /// Keys read from trusted storage can skip these checks with
+    /// [`CanonicalDeserialize::deserialize_unchecked`].
+    pub fn deserialize_checked<R>(reader: R) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        Self::deserialize_with_encoding(reader, PointEncoding::Compressed)
+    }
+
+    /// Serializes the key with its points in `encoding`.
+    pub fn serialize_with_encoding<W>(
+        &self,
+        writer: W,
+        encoding: PointEncoding,
+    ) -> Result<(), SerializationError>
+    where
+        W: Write,
+    {
+        encoding.write(self, writer)
+    }
+
+    /// Returns the size of the key serialized with its points in `encoding`.
+    pub fn serialized_size_with_encoding(
+        &self,
+        encoding: PointEncoding,
+    ) -> usize {
+        encoding.size(self)
+    }
+
+    /// Deserializes a key whose points are in `encoding`.
+    ///
+    /// Unless the encoding is [`PointEncoding::Unchecked`], the commitments
+    /// are checked as in [`VerifierKey::deserialize_checked`].
+    pub fn deserialize_with_encoding<R>(
+        reader: R,
+        encoding: PointEncoding,
+    ) -> Result<Self, Error>
+    where
+        R: Read,
+    {
+        let key: Self = encoding.read(reader)?;
+        if encoding != PointEncoding::Unchecked {
+            key.check_commitments()?;
+        }
+        Ok(key)
+    }
+
+    /// Checks that every commitment of the key is on the curve and in its
+    /// prime order subgroup, failing with [`Error::InvalidCommitment`]
+    /// otherwise.
+    pub fn check_commitments(&self) -> Result<(), Error> {
+        proof::check_commitments(&[
+            &self.arithmetic.q_m,
+            &self.arithmetic.q_l,
+            &self.arithmetic.q_r,
+            &self.arithmetic.q_o,
+            &self.arithmetic.q_4,
+            &self.arithmetic.q_c,
+            &self.arithmetic.q_arith,
+            &self.arithmetic.q_lookahead,
+            &self.range_selector_commitment,
+            &self.logic_selector_commitment,
+            &self.fixed_group_add_selector_commitment,
+            &self.variable_group_add_selector_commitment,
+            &self.permutation.left_sigma,
+            &self.permutation.right_sigma,
+            &self.permutation.out_sigma,
+            &self.permutation.fourth_sigma,
+        ])
+    }
+
+    /// Appends the circuit binding, if any, to the transcript.
+    pub(crate) fn bind_transcript<T>(&self, transcript: &mut T)
+    where
+        T: TranscriptProtocol<E>,
+    {
+        if let Some(circuit_id) = &self.circuit_id {
+            transcript.circuit_binding(&circuit_id.0, &self.digest());
+        }
+    }
+
     /// Adds the circuit description to the transcript.
     pub(crate) fn seed_transcript<T>(&self, transcript: &mut T)
     where
@@ -249,6 +500,8 @@ where
         transcript.append_commitment(b"q_c", &self.arithmetic.q_c);
         transcript.append_commitment(b"q_4", &self.arithmetic.q_4);
         transcript.append_commitment(b"q_arith", &self.arithmetic.q_arith);
+        transcript
+            .append_commitment(b"q_lookahead", &self.arithmetic.q_lookahead);
         transcript
             .append_commitment(b"q_range", &self.range_selector_commitment);
         transcript
@@ -272,6 +525,40 @@ where
     }
 }
 
+/// Factors of the vanishing polynomial added to the selectors of a
+/// [`ProverKey`] by [`ProverKey::rerandomize`].
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct SelectorBlinding<F>
+where
+    F: PrimeField,
+{
+    /// Factor of each selector, in the order of
+    /// [`ProverKey::from_polynomials_and_evals`], zero for the dropped ones
+    pub(crate) factors: [F; 12],
+}
+
+/// Returns the commitment to the vanishing polynomial `X^n - 1` of the domain
+/// of size `n`, given the powers of the commitment key.
+pub(crate) fn vanishing_commitment<E>(
+    powers_of_g: &[E::G1Affine],
+    n: usize,
+) -> Result<E::G1Projective, Error>
+where
+    E: PairingEngine,
+{
+    if powers_of_g.len() <= n {
+        return Err(Error::UniversalParamsTooSmall {
+            circuit_size: n,
+            required_powers: n + 1,
+            available_powers: powers_of_g.len(),
+        });
+    }
+    let mut vanishing = powers_of_g[n].into_projective();
+    vanishing.add_assign_mixed(&-powers_of_g[0]);
+    Ok(vanishing)
+}
+
 /// PLONK circuit Proving Key.
 ///
 /// This structure is used by the Prover in order to construct a
@@ -318,6 +605,12 @@ where
     /// polynomial without having to perform IFFT
     pub(crate) v_h_coset_4n: Evaluations<F>,
 
+    /// Identifier of the circuit this key is bound to, if any.
+    pub(crate) circuit_id: Option<Bytes32>,
+
+    /// Digest of the matching [`VerifierKey`].
+    pub(crate) vk_digest: Bytes32,
+
     /// Type Parameter Marker
     __: PhantomData<P>,
 }
@@ -331,6 +624,215 @@ where
         &self.v_h_coset_4n
     }
 
+    /// Returns the selector and permutation polynomials of the key together
+    /// with their evaluations, in the order of
+    /// [`ProverKey::from_polynomials_and_evals`].
+    fn polynomials_and_evals(
+        &self,
+    ) -> [&(DensePolynomial<F>, Evaluations<F>); 16] {
+        [
+            &self.arithmetic.q_m,
+            &self.arithmetic.q_l,
+            &self.arithmetic.q_r,
+            &self.arithmetic.q_o,
+            &self.arithmetic.q_4,
+            &self.arithmetic.q_c,
+            &self.arithmetic.q_arith,
+            &self.arithmetic.q_lookahead,
+            &self.range_selector,
+            &self.logic_selector,
+            &self.fixed_group_add_selector,
+            &self.variable_group_add_selector,
+            &self.permutation.left_sigma,
+            &self.permutation.right_sigma,
+            &self.permutation.out_sigma,
+            &self.permutation.fourth_sigma,
+        ]
+    }
+
+    /// Returns the number of bytes taken by the selector and permutation
+    /// polynomials of the key and their evaluations, which account for all
+    /// but a constant part of its memory.
+    pub fn memory_footprint(&self) -> usize {
+        let elements = self
+            .polynomials_and_evals()
+            .iter()
+            .map(|(poly, evals)| poly.coeffs.len() + evals.evals.len())
+            .sum::<usize>()
+            + self.v_h_coset_4n.evals.len();
+        elements * core::mem::size_of::<F>()
+    }
+
+    /// Drops the polynomials and evaluations of the selectors of the custom
+    /// gates the circuit doesn't use, which are identically zero, and returns
+    /// the number of selectors dropped.
+    ///
+    /// The key generates the same proofs as before, in less memory and with
+    /// fewer terms in the quotient polynomial. The commitments to these
+    /// selectors in the [`VerifierKey`] are the identity, which the verifier
+    /// skips as well.
+    pub fn compress_selectors(&mut self) -> usize {
+        let mut dropped = 0;
+        for (poly, evals) in [
+            &mut self.range_selector,
+            &mut self.logic_selector,
+            &mut self.fixed_group_add_selector,
+            &mut self.variable_group_add_selector,
+        ] {
+            if poly.is_zero() && !evals.evals.is_empty() {
+                *poly = DensePolynomial::zero();
+                *evals = Evaluations::from_vec_and_domain(
+                    Vec::new(),
+                    evals.domain(),
+                );
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    /// Adds a fresh random multiple of the vanishing polynomial `X^n - 1` of
+    /// the domain to every selector of the key, and returns the factors to
+    /// pass to [`VerifierKey::rerandomize`] for the matching verifier key.
+    ///
+    /// The selectors keep their values over the domain, so the keys still
+    /// prove and verify the same circuit, but their commitments can't be
+    /// linked to those of the previous keys, and proofs generated with the
+    /// previous keys don't verify against the new ones. The selectors
+    /// dropped by [`ProverKey::compress_selectors`] are left as is.
+    pub fn rerandomize<R>(&mut self, rng: &mut R) -> SelectorBlinding<F>
+    where
+        R: RngCore,
+    {
+        let n = self.n;
+        let mut factors = [F::zero(); 12];
+        for ((poly, evals), factor) in [
+            &mut self.arithmetic.q_m,
+            &mut self.arithmetic.q_l,
+            &mut self.arithmetic.q_r,
+            &mut self.arithmetic.q_o,
+            &mut self.arithmetic.q_4,
+            &mut self.arithmetic.q_c,
+            &mut self.arithmetic.q_arith,
+            &mut self.arithmetic.q_lookahead,
+            &mut self.range_selector,
+            &mut self.logic_selector,
+            &mut self.fixed_group_add_selector,
+            &mut self.variable_group_add_selector,
+        ]
+        .into_iter()
+        .zip(&mut factors)
+        {
+            if evals.evals.is_empty() {
+                continue;
+            }
+            *factor = F::rand(rng);
+            let mut coeffs = core::mem::take(&mut poly.coeffs);
+            coeffs.resize(coeffs.len().max(n + 1), F::zero());
+            coeffs[0] -= *factor;
+            coeffs[n] += *factor;
+            *poly = util::normalized_polynomial(coeffs);
+            for (eval, v_h) in
+                evals.evals.iter_mut().zip(&self.v_h_coset_4n.evals)
+            {
+                *eval += *factor * v_h;
+            }
+        }
+        SelectorBlinding { factors }
+    }
+
+    /// Returns the [`VerifierKey::digest`] of the verifier key generated
+    /// together with this key.
+    pub fn verifier_key_digest(&self) -> [u8; 32] {
+        self.vk_digest.0
+    }
+
+    /// Checks whether `verifier_key` is the key generated together with this
+    /// one.
+    pub fn matches<E>(&self, verifier_key: &VerifierKey<E, P>) -> bool
+    where
+        E: PairingEngine<Fr = F>,
+    {
+        self.vk_digest.0 == verifier_key.digest()
+    }
+
+    /// Writes the key to `writer` in the streaming format, a versioned header
+    /// followed by the polynomials and their evaluations.
+    ///
+    /// Unlike [`CanonicalSerialize`], which is typically used to serialize
+    /// into a buffer holding the whole key, the polynomials are written in
+    /// chunks of [`STREAM_CHUNK_SIZE`] elements, so that writing to a file
+    /// doesn't take more memory than the key itself.
+    pub fn serialize_into_writer<W>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError>
+    where
+        W: Write,
+    {
+        writer.write_all(&PROVER_KEY_MAGIC)?;
+        PROVER_KEY_FORMAT_VERSION.serialize(&mut writer)?;
+        (self.n as u64).serialize(&mut writer)?;
+        self.circuit_id.serialize(&mut writer)?;
+        self.vk_digest.serialize(&mut writer)?;
+        for (poly, evals) in self.polynomials_and_evals() {
+            write_elements(&poly.coeffs, &mut writer)?;
+            write_evaluations(evals, &mut writer)?;
+        }
+        write_evaluations(&self.v_h_coset_4n, &mut writer)
+    }
+
+    /// Reads a key written by [`ProverKey::serialize_into_writer`] from
+    /// `reader`, in chunks of [`STREAM_CHUNK_SIZE`] elements.
+    ///
+    /// Inputs with a different magic or an unknown format version are
+    /// rejected with [`SerializationError::InvalidData`].
+    pub fn deserialize_from_reader<R>(
+        mut reader: R,
+    ) -> Result<Self, SerializationError>
+    where
+        R: Read,
+    {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != PROVER_KEY_MAGIC
+            || u8::deserialize(&mut reader)? != PROVER_KEY_FORMAT_VERSION
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        let n = usize::try_from(u64::deserialize(&mut reader)?)
+            .map_err(|_| SerializationError::InvalidData)?;
+        let circuit_id = Option::<Bytes32>::deserialize(&mut reader)?;
+        let vk_digest = Bytes32::deserialize(&mut reader)?;
+        let read_pair = |reader: &mut R| {
+            let poly = util::normalized_polynomial(read_elements(reader)?);
+            Ok::<_, SerializationError>((poly, read_evaluations(reader)?))
+        };
+        let mut prover_key = Self::from_polynomials_and_evals(
+            n,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_pair(&mut reader)?,
+            read_evaluations(&mut reader)?,
+        );
+        prover_key.circuit_id = circuit_id;
+        prover_key.vk_digest = vk_digest;
+        Ok(prover_key)
+    }
+
     /// Constructs a [`ProverKey`] from the widget ProverKey's that are
     /// constructed based on the selector polynomials and the
     /// sigma polynomials and it's evaluations.
@@ -343,6 +845,7 @@ where
         q_4: (DensePolynomial<F>, Evaluations<F>),
         q_c: (DensePolynomial<F>, Evaluations<F>),
         q_arith: (DensePolynomial<F>, Evaluations<F>),
+        q_lookahead: (DensePolynomial<F>, Evaluations<F>),
         q_range: (DensePolynomial<F>, Evaluations<F>),
         q_logic: (DensePolynomial<F>, Evaluations<F>),
         q_fixed_group_add: (DensePolynomial<F>, Evaluations<F>),
@@ -351,7 +854,6 @@ where
         right_sigma: (DensePolynomial<F>, Evaluations<F>),
         out_sigma: (DensePolynomial<F>, Evaluations<F>),
         fourth_sigma: (DensePolynomial<F>, Evaluations<F>),
-        linear_evaluations: Evaluations<F>,
         v_h_coset_4n: Evaluations<F>,
     ) -> Self {
         Self {
@@ -364,6 +866,7 @@ where
                 q_4,
                 q_c,
                 q_arith,
+                q_lookahead,
             },
             range_selector: q_range,
             logic_selector: q_logic,
@@ -374,14 +877,102 @@ where
                 right_sigma,
                 out_sigma,
                 fourth_sigma,
-                linear_evaluations,
             },
             v_h_coset_4n,
+            circuit_id: None,
+            vk_digest: Bytes32::default(),
             __: PhantomData,
         }
     }
 }
 
+/// Magic bytes starting a [`ProverKey`] in the streaming format.
+const PROVER_KEY_MAGIC: [u8; 8] = *b"plonk-pk";
+
+/// Version of the streaming format of [`ProverKey`].
+const PROVER_KEY_FORMAT_VERSION: u8 = 1;
+
+/// Number of field elements written or read at once by
+/// [`ProverKey::serialize_into_writer`] and
+/// [`ProverKey::deserialize_from_reader`].
+pub const STREAM_CHUNK_SIZE: usize = 1 << 12;
+
+/// Writes the length of `elements` followed by the elements, in chunks of
+/// [`STREAM_CHUNK_SIZE`].
+fn write_elements<F, W>(
+    elements: &[F],
+    writer: &mut W,
+) -> Result<(), SerializationError>
+where
+    F: PrimeField,
+    W: Write,
+{
+    (elements.len() as u64).serialize(&mut *writer)?;
+    let mut buffer =
+        Vec::with_capacity(STREAM_CHUNK_SIZE * F::zero().serialized_size());
+    for chunk in elements.chunks(STREAM_CHUNK_SIZE) {
+        buffer.clear();
+        for element in chunk {
+            element.serialize(&mut buffer)?;
+        }
+        writer.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+/// Reads elements written by [`write_elements`].
+///
+/// Memory is only allocated for the chunks actually read, so a corrupted
+/// length fails at the end of the input rather than with a huge allocation.
+fn read_elements<F, R>(reader: &mut R) -> Result<Vec<F>, SerializationError>
+where
+    F: PrimeField,
+    R: Read,
+{
+    let len = usize::try_from(u64::deserialize(&mut *reader)?)
+        .map_err(|_| SerializationError::InvalidData)?;
+    let element_size = F::zero().serialized_size();
+    let mut buffer = vec![0u8; STREAM_CHUNK_SIZE * element_size];
+    let mut elements = Vec::new();
+    while elements.len() < len {
+        let count = STREAM_CHUNK_SIZE.min(len - elements.len());
+        let bytes = &mut buffer[..count * element_size];
+        reader.read_exact(bytes)?;
+        elements.reserve(count);
+        let mut bytes = &bytes[..];
+        for _ in 0..count {
+            elements.push(F::deserialize(&mut bytes)?);
+        }
+    }
+    Ok(elements)
+}
+
+/// Writes `evaluations` and their domain.
+fn write_evaluations<F, W>(
+    evaluations: &Evaluations<F>,
+    writer: &mut W,
+) -> Result<(), SerializationError>
+where
+    F: PrimeField,
+    W: Write,
+{
+    write_elements(&evaluations.evals, writer)?;
+    evaluations.domain().serialize(&mut *writer)
+}
+
+/// Reads evaluations written by [`write_evaluations`].
+fn read_evaluations<F, R>(
+    reader: &mut R,
+) -> Result<Evaluations<F>, SerializationError>
+where
+    F: PrimeField,
+    R: Read,
+{
+    let evals = read_elements(reader)?;
+    let domain = GeneralEvaluationDomain::deserialize(&mut *reader)?;
+    Ok(Evaluations::from_vec_and_domain(evals, domain))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -427,6 +1018,7 @@ mod test {
         let q_4 = rand_poly_eval(n);
         let q_c = rand_poly_eval(n);
         let q_arith = rand_poly_eval(n);
+        let q_lookahead = rand_poly_eval(n);
         let q_range = rand_poly_eval(n);
         let q_logic = rand_poly_eval(n);
         let q_fixed_group_add = rand_poly_eval(n);
@@ -437,7 +1029,6 @@ mod test {
         let out_sigma = rand_poly_eval(n);
         let fourth_sigma = rand_poly_eval(n);
 
-        let linear_evaluations = rand_evaluations(n);
         let v_h_coset_4n = rand_evaluations(n);
 
         let prover_key = ProverKey::from_polynomials_and_evals(
@@ -449,6 +1040,7 @@ mod test {
             q_4,
             q_c,
             q_arith,
+            q_lookahead,
             q_range,
             q_logic,
             q_fixed_group_add,
@@ -457,7 +1049,6 @@ mod test {
             right_sigma,
             out_sigma,
             fourth_sigma,
-            linear_evaluations,
             v_h_coset_4n,
         );
 
@@ -473,6 +1064,30 @@ mod test {
             .unwrap();
 
         assert!(prover_key == obtained_pk);
+
+        let mut streamed_bytes = vec![];
+        prover_key
+            .serialize_into_writer(&mut streamed_bytes)
+            .unwrap();
+        let streamed_pk: ProverKey<BlsScalar, EdwardsParameters> =
+            ProverKey::deserialize_from_reader(streamed_bytes.as_slice())
+                .unwrap();
+        assert!(prover_key == streamed_pk);
+
+        // Truncated keys and unknown format versions are rejected
+        assert!(
+            ProverKey::<BlsScalar, EdwardsParameters>::deserialize_from_reader(
+                &streamed_bytes[..streamed_bytes.len() - 1]
+            )
+            .is_err()
+        );
+        streamed_bytes[PROVER_KEY_MAGIC.len()] = PROVER_KEY_FORMAT_VERSION + 1;
+        assert!(matches!(
+            ProverKey::<BlsScalar, EdwardsParameters>::deserialize_from_reader(
+                streamed_bytes.as_slice()
+            ),
+            Err(SerializationError::InvalidData)
+        ));
     }
 
     #[test]
@@ -486,6 +1101,7 @@ mod test {
         let q_4 = Commitment(G1Affine::default());
         let q_c = Commitment(G1Affine::default());
         let q_arith = Commitment(G1Affine::default());
+        let q_lookahead = Commitment(G1Affine::default());
         let q_range = Commitment(G1Affine::default());
         let q_logic = Commitment(G1Affine::default());
         let q_fixed_group_add = Commitment(G1Affine::default());
@@ -505,6 +1121,7 @@ mod test {
             q_4,
             q_c,
             q_arith,
+            q_lookahead,
             q_range,
             q_logic,
             q_fixed_group_add,
@@ -525,5 +1142,34 @@ mod test {
                 .unwrap();
 
         assert!(verifier_key == obtained_vk);
+
+        let mut verifier_key_bytes = vec![];
+        verifier_key.serialize(&mut verifier_key_bytes).unwrap();
+        let obtained_vk: VerifierKey<Bls12_381, EdwardsParameters> =
+            VerifierKey::deserialize_checked(verifier_key_bytes.as_slice())
+                .unwrap();
+        assert!(verifier_key == obtained_vk);
+
+        for encoding in [
+            PointEncoding::Compressed,
+            PointEncoding::Uncompressed,
+            PointEncoding::Unchecked,
+        ] {
+            let mut verifier_key_bytes = vec![];
+            verifier_key
+                .serialize_with_encoding(&mut verifier_key_bytes, encoding)
+                .unwrap();
+            assert_eq!(
+                verifier_key_bytes.len(),
+                verifier_key.serialized_size_with_encoding(encoding)
+            );
+            let obtained_vk: VerifierKey<Bls12_381, EdwardsParameters> =
+                VerifierKey::deserialize_with_encoding(
+                    verifier_key_bytes.as_slice(),
+                    encoding,
+                )
+                .unwrap();
+            assert!(verifier_key == obtained_vk);
+        }
     }
 }
@@ -0,0 +1,328 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Multi-party generation of universal parameters.
+//!
+//! Universal parameters are only secure if nobody knows their secret. In a
+//! powers of tau ceremony, participants take turns to multiply the secret
+//! of the current parameters by a secret of their own with [`contribute`],
+//! publishing the new parameters together with a [`ContributionProof`].
+//! Anyone can then check every contribution with [`verify_contribution`].
+//! The final parameters are secure as long as a single participant discarded
+//! their secret.
+//!
+//! A ceremony starts from parameters generated by a first participant, for
+//! instance with `KZG10::setup`.
+
+use crate::error::Error;
+use crate::srs::check_srs;
+use crate::util;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_poly_commit::kzg10::UniversalParams;
+use ark_serialize::*;
+use merlin::Transcript;
+use num_traits::{One, Zero};
+use rand_core::RngCore;
+
+/// Proof that parameters were derived from the previous ones by a single
+/// participant knowing the secret they multiplied them by.
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct ContributionProof<E>
+where
+    E: PairingEngine,
+{
+    /// Secret of the participant times the first power of `g`
+    tau_g: E::G1Affine,
+
+    /// Commitment of the proof of knowledge of the secret
+    commitment: E::G1Affine,
+
+    /// Response of the proof of knowledge of the secret
+    response: E::Fr,
+}
+
+/// Secret scalars of a contribution, overwritten when they are dropped with
+/// the `zeroize` feature.
+struct Secrets<F>
+where
+    F: Field,
+{
+    /// Secret of the participant
+    tau: F,
+
+    /// Inverse of the secret
+    tau_inv: F,
+
+    /// Nonce of the proof of knowledge of the secret
+    nonce: F,
+}
+
+#[cfg(feature = "zeroize")]
+impl<F> Drop for Secrets<F>
+where
+    F: Field,
+{
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.tau);
+        zeroize::Zeroize::zeroize(&mut self.tau_inv);
+        zeroize::Zeroize::zeroize(&mut self.nonce);
+    }
+}
+
+/// Multiplies the secret of `u_params` by a fresh secret sampled from `rng`,
+/// returning the new parameters and the [`ContributionProof`] to publish
+/// with them.
+///
+/// With the `zeroize` feature, the secret, its inverse and the nonce of the
+/// proof are overwritten before returning, including on errors.
+pub fn contribute<E, R>(
+    u_params: &UniversalParams<E>,
+    rng: &mut R,
+) -> Result<(UniversalParams<E>, ContributionProof<E>), Error>
+where
+    E: PairingEngine,
+    R: RngCore,
+{
+    check_srs(u_params)?;
+    let mut secrets = Secrets {
+        tau: E::Fr::zero(),
+        tau_inv: E::Fr::zero(),
+        nonce: E::Fr::zero(),
+    };
+    while secrets.tau.is_zero() {
+        secrets.tau = E::Fr::rand(rng);
+    }
+    secrets.tau_inv = secrets.tau.inverse().expect("secret is non-zero");
+    secrets.nonce = E::Fr::rand(rng);
+    let Secrets {
+        tau,
+        tau_inv,
+        nonce,
+    } = &secrets;
+
+    // The `i`-th power of the secret now is the previous one times `tau^i`
+    let mut next = u_params.clone();
+    let powers_of_g: Vec<E::G1Projective> = u_params
+        .powers_of_g
+        .iter()
+        .zip(util::powers_of(*tau))
+        .map(|(point, power)| point.mul(power.into_repr()))
+        .collect();
+    next.powers_of_g =
+        E::G1Projective::batch_normalization_into_affine(&powers_of_g);
+    for (i, point) in next.powers_of_gamma_g.iter_mut() {
+        *point = point.mul(tau.pow([*i as u64]).into_repr()).into_affine();
+    }
+    for (i, point) in next.neg_powers_of_h.iter_mut() {
+        *point = point
+            .mul(tau_inv.pow([*i as u64]).into_repr())
+            .into_affine();
+    }
+    next.beta_h = u_params.beta_h.mul(tau.into_repr()).into_affine();
+    next.prepared_beta_h = next.beta_h.into();
+
+    // Schnorr proof of knowledge of the secret
+    let g = u_params.powers_of_g[0];
+    let tau_g = g.mul(tau.into_repr()).into_affine();
+    let commitment = g.mul(nonce.into_repr()).into_affine();
+    let challenge =
+        contribution_challenge(u_params, &next, &tau_g, &commitment)?;
+    let proof = ContributionProof {
+        tau_g,
+        commitment,
+        response: *nonce + challenge * tau,
+    };
+    Ok((next, proof))
+}
+
+/// Checks that `next` was derived from `previous` by the contribution
+/// `proof` proves.
+///
+/// Besides checking the `proof`, this checks `next` with [`check_srs`].
+pub fn verify_contribution<E>(
+    previous: &UniversalParams<E>,
+    next: &UniversalParams<E>,
+    proof: &ContributionProof<E>,
+) -> Result<(), Error>
+where
+    E: PairingEngine,
+{
+    let invalid = |reason| Err(Error::InvalidContribution { reason });
+    check_srs(next)?;
+
+    // `next` has at least two powers of g, so `previous` has as many once
+    // their lengths match
+    if next.powers_of_g.len() != previous.powers_of_g.len() {
+        return invalid("the parameters don't have the same shape and bases");
+    }
+    let g = previous.powers_of_g[0];
+    if next.powers_of_g[0] != g
+        || next.h != previous.h
+        || next.powers_of_gamma_g.len() != previous.powers_of_gamma_g.len()
+        || next.powers_of_gamma_g.get(&0) != previous.powers_of_gamma_g.get(&0)
+        || next.neg_powers_of_h.len() != previous.neg_powers_of_h.len()
+    {
+        return invalid("the parameters don't have the same shape and bases");
+    }
+    if proof.tau_g.is_zero() {
+        return invalid("the secret is zero");
+    }
+
+    // The proof knows the secret of `tau_g`
+    let challenge = contribution_challenge(
+        previous,
+        next,
+        &proof.tau_g,
+        &proof.commitment,
+    )?;
+    let lhs = g.mul(proof.response.into_repr());
+    let rhs = proof.tau_g.mul(challenge.into_repr())
+        + proof.commitment.into_projective();
+    if lhs != rhs {
+        return invalid("the proof of knowledge of the secret doesn't hold");
+    }
+
+    // The secret of `next` is the one of `previous` times the one of
+    // `tau_g`, checked as e(tau_g, beta_h) = e(g, next_beta_h). The rest of
+    // `next` follows from its secret, as checked by `check_srs`.
+    let pairs = [
+        (proof.tau_g.into(), previous.prepared_beta_h.clone()),
+        ((-g).into(), next.prepared_beta_h.clone()),
+    ];
+    if !E::product_of_pairings(&pairs).is_one() {
+        return invalid("the secret isn't the product of the previous one");
+    }
+    Ok(())
+}
+
+/// Derives the challenge of the proof of knowledge of a contribution,
+/// binding the parameters before and after it.
+fn contribution_challenge<E>(
+    previous: &UniversalParams<E>,
+    next: &UniversalParams<E>,
+    tau_g: &E::G1Affine,
+    commitment: &E::G1Affine,
+) -> Result<E::Fr, Error>
+where
+    E: PairingEngine,
+{
+    let mut bytes = Vec::new();
+    previous.powers_of_g[1].serialize(&mut bytes)?;
+    previous.beta_h.serialize(&mut bytes)?;
+    next.powers_of_g[1].serialize(&mut bytes)?;
+    next.beta_h.serialize(&mut bytes)?;
+    tau_g.serialize(&mut bytes)?;
+    commitment.serialize(&mut bytes)?;
+
+    let mut transcript = Transcript::new(b"ark-plonk-ceremony");
+    transcript.append_message(b"contribution", &bytes);
+    let mut challenge = [0u8; 64];
+    transcript.challenge_bytes(b"challenge", &mut challenge);
+    Ok(E::Fr::from_le_bytes_mod_order(&challenge))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test_engine;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use rand_core::OsRng;
+
+    fn test_ceremony<E>()
+    where
+        E: PairingEngine,
+    {
+        let initial =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(1 << 4, true, &mut OsRng)
+                .unwrap();
+        let (first, first_proof) = contribute(&initial, &mut OsRng).unwrap();
+        let (second, second_proof) = contribute(&first, &mut OsRng).unwrap();
+        assert_ne!(first.beta_h, initial.beta_h);
+        assert!(verify_contribution(&initial, &first, &first_proof).is_ok());
+        assert!(verify_contribution(&first, &second, &second_proof).is_ok());
+
+        let is_invalid =
+            |result| matches!(result, Err(Error::InvalidContribution { .. }));
+
+        // Contributions only verify against the parameters they were
+        // applied to
+        assert!(is_invalid(verify_contribution(
+            &initial,
+            &second,
+            &second_proof
+        )));
+        assert!(is_invalid(verify_contribution(
+            &initial,
+            &first,
+            &second_proof
+        )));
+
+        let mut bad_proof = first_proof;
+        bad_proof.response += E::Fr::one();
+        assert!(is_invalid(verify_contribution(
+            &initial, &first, &bad_proof
+        )));
+
+        // Parameters generated independently aren't a contribution
+        let fresh =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(1 << 4, true, &mut OsRng)
+                .unwrap();
+        assert!(is_invalid(verify_contribution(
+            &initial,
+            &fresh,
+            &first_proof
+        )));
+
+        // Parameters without any power of g are rejected, not indexed
+        let mut empty = initial.clone();
+        empty.powers_of_g.clear();
+        assert!(is_invalid(verify_contribution(
+            &empty,
+            &first,
+            &first_proof
+        )));
+
+        let mut bytes = Vec::new();
+        first_proof.serialize(&mut bytes).unwrap();
+        assert_eq!(
+            ContributionProof::<E>::deserialize(&bytes[..]).unwrap(),
+            first_proof
+        );
+    }
+
+    // Test on Bls12-381
+    batch_test_engine!(
+        [test_ceremony],
+        []
+        => Bls12_381
+    );
+
+    // Test on Bls12-377
+    batch_test_engine!(
+        [test_ceremony],
+        []
+        => Bls12_377
+    );
+
+    // Test on Bn254
+    batch_test_engine!(
+        [test_ceremony],
+        []
+        => Bn254
+    );
+}
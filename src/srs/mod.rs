@@ -0,0 +1,525 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Sharing of the universal parameters between many circuits.
+//!
+//! Every circuit needs the universal parameters trimmed to its padded size,
+//! which are hundreds of megabytes for large circuits. Applications proving
+//! and verifying several kinds of circuits would otherwise keep one copy of
+//! the trimmed keys per circuit. An [`SrsManager`] owns the
+//! [`UniversalParams`] and trims them at most once per circuit size, handing
+//! out shared references to the trimmed keys:
+//! - [`CompiledCircuit::from_srs`] proves with the shared commit key.
+//! - [`SrsManager::prepare`] builds a [`PreparedVerifierKey`] for a circuit
+//!   from the shared commitment scheme verifier key.
+//! - [`SrsManager::lagrange_basis`] converts the commit key to the
+//!   [`LagrangeBasis`] of the circuit domain.
+//!
+//! Parameters coming from a third party ceremony can be sanity checked with
+//! [`check_srs`] before use, and the [`ceremony`] module supports running
+//! such a ceremony.
+//!
+//! [`CompiledCircuit::from_srs`]: crate::circuit::CompiledCircuit::from_srs
+
+pub mod ceremony;
+
+use crate::circuit::{
+    max_supported_circuit_size, trim_commit_key, trim_verifier_key,
+    PreparedVerifierKey,
+};
+use crate::error::Error;
+use crate::proof_system::{LagrangeBasis, VerifierKey};
+use crate::util;
+use alloc::sync::Arc;
+use ark_ec::msm::VariableBaseMSM;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve, TEModelParameters};
+use ark_ff::{Field, PrimeField, UniformRand};
+use ark_poly_commit::kzg10::{self, Powers, UniversalParams};
+use hashbrown::HashMap;
+use num_traits::{One, Zero};
+use rand_core::OsRng;
+use std::sync::RwLock;
+
+/// Owner of the [`UniversalParams`] caching their trimmed keys by circuit
+/// size.
+pub struct SrsManager<E>
+where
+    E: PairingEngine,
+{
+    /// Universal parameters the keys are trimmed from
+    u_params: UniversalParams<E>,
+
+    /// Commit keys, by padded circuit size
+    commit_keys: RwLock<HashMap<usize, Arc<Powers<'static, E>>>>,
+
+    /// Commitment scheme verifier keys, by padded circuit size
+    verifier_keys: RwLock<HashMap<usize, Arc<kzg10::VerifierKey<E>>>>,
+
+    /// Lagrange bases of the circuit domains, by padded circuit size
+    lagrange_bases: RwLock<HashMap<usize, Arc<LagrangeBasis<E>>>>,
+}
+
+impl<E> SrsManager<E>
+where
+    E: PairingEngine,
+{
+    /// Creates a manager owning `u_params`, with no trimmed keys yet.
+    pub fn new(u_params: UniversalParams<E>) -> Self {
+        Self {
+            u_params,
+            commit_keys: RwLock::new(HashMap::new()),
+            verifier_keys: RwLock::new(HashMap::new()),
+            lagrange_bases: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the universal parameters.
+    pub fn universal_params(&self) -> &UniversalParams<E> {
+        &self.u_params
+    }
+
+    /// Returns the largest padded circuit size supported by the universal
+    /// parameters, see [`max_supported_circuit_size`].
+    pub fn max_supported_circuit_size(&self) -> usize {
+        max_supported_circuit_size(&self.u_params)
+    }
+
+    /// Returns the commit key for circuits of `circuit_size` gates, trimming
+    /// it on the first request for that size.
+    pub fn commit_key(
+        &self,
+        circuit_size: usize,
+    ) -> Result<Arc<Powers<'static, E>>, Error> {
+        cached(&self.commit_keys, circuit_size, || {
+            trim_commit_key(&self.u_params, circuit_size)
+        })
+    }
+
+    /// Returns the commitment scheme verifier key for circuits of
+    /// `circuit_size` gates, trimming it on the first request for that
+    /// size.
+    pub fn verifier_key(
+        &self,
+        circuit_size: usize,
+    ) -> Result<Arc<kzg10::VerifierKey<E>>, Error> {
+        cached(&self.verifier_keys, circuit_size, || {
+            trim_verifier_key(&self.u_params, circuit_size)
+        })
+    }
+
+    /// Returns the [`LagrangeBasis`] of the domain of circuits of
+    /// `circuit_size` gates, computing it on the first request for that
+    /// size.
+    ///
+    /// Computing the basis is expensive, so it is only worth it for
+    /// circuits proved many times, see [`Prover::set_lagrange_basis`].
+    ///
+    /// [`Prover::set_lagrange_basis`]:
+    /// crate::proof_system::Prover::set_lagrange_basis
+    pub fn lagrange_basis(
+        &self,
+        circuit_size: usize,
+    ) -> Result<Arc<LagrangeBasis<E>>, Error> {
+        cached(&self.lagrange_bases, circuit_size, || {
+            LagrangeBasis::from_powers(
+                self.commit_key(circuit_size)?.as_ref(),
+                circuit_size,
+            )
+        })
+    }
+
+    /// Prepares `key` for verification, see [`VerifierKey::prepare`], with
+    /// the cached commitment scheme verifier key of its size.
+    pub fn prepare<P>(
+        &self,
+        key: &VerifierKey<E, P>,
+    ) -> Result<PreparedVerifierKey<E, P>, Error>
+    where
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let verifier_key = self.verifier_key(key.padded_circuit_size())?;
+        key.prepare_with(verifier_key.as_ref().clone())
+    }
+
+    /// Returns the circuit sizes for which a commit key is cached.
+    pub fn cached_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = self
+            .commit_keys
+            .read()
+            .expect("poisoned SRS lock")
+            .keys()
+            .copied()
+            .collect();
+        sizes.sort_unstable();
+        sizes
+    }
+
+    /// Drops every cached key. Keys handed out before remain valid.
+    pub fn clear(&self) {
+        self.commit_keys.write().expect("poisoned SRS lock").clear();
+        self.verifier_keys
+            .write()
+            .expect("poisoned SRS lock")
+            .clear();
+        self.lagrange_bases
+            .write()
+            .expect("poisoned SRS lock")
+            .clear();
+    }
+}
+
+/// Checks that `u_params` are a well formed powers of tau setup, as a sanity
+/// check of parameters coming from a third party ceremony.
+///
+/// Every point must be non-zero and belong to the prime order subgroup, and
+/// the powers in `G1` must be successive powers of the secret `beta_h` is
+/// the `G2` commitment to. The sequences are checked with a single pairing
+/// equation on random linear combinations of the powers. The subgroup checks
+/// cost a scalar multiplication per point, so checking large parameters
+/// takes a while.
+pub fn check_srs<E>(u_params: &UniversalParams<E>) -> Result<(), Error>
+where
+    E: PairingEngine,
+{
+    let invalid = |reason| Err(Error::InvalidUniversalParams { reason });
+    if u_params.powers_of_g.len() < 2 {
+        return invalid("less than two powers of g");
+    }
+    if u_params.h.is_zero() || u_params.beta_h.is_zero() {
+        return invalid("h or beta_h is zero");
+    }
+
+    let g1_points = u_params
+        .powers_of_g
+        .iter()
+        .chain(u_params.powers_of_gamma_g.values());
+    for point in g1_points {
+        if point.is_zero() || !in_prime_subgroup(point) {
+            return invalid("power of g outside of the prime order subgroup");
+        }
+    }
+    let g2_points = [u_params.h, u_params.beta_h]
+        .into_iter()
+        .chain(u_params.neg_powers_of_h.values().copied());
+    for point in g2_points {
+        if point.is_zero() || !in_prime_subgroup(&point) {
+            return invalid("power of h outside of the prime order subgroup");
+        }
+    }
+
+    // The prepared points are the ones used by the pairings
+    let g = u_params.powers_of_g[0];
+    let pairs = [
+        (g.into(), u_params.prepared_h.clone()),
+        ((-g).into(), E::G2Prepared::from(u_params.h)),
+        (g.into(), u_params.prepared_beta_h.clone()),
+        ((-g).into(), E::G2Prepared::from(u_params.beta_h)),
+    ];
+    if !E::product_of_pairings(&pairs).is_one() {
+        return invalid("prepared points don't match h and beta_h");
+    }
+
+    // Pairs of successive powers (P_i, P_{i+1}) of g and of gamma * g
+    let mut lower = Vec::new();
+    let mut upper = Vec::new();
+    for pair in u_params.powers_of_g.windows(2) {
+        lower.push(pair[0]);
+        upper.push(pair[1]);
+    }
+    let gamma_powers: Vec<_> = u_params.powers_of_gamma_g.iter().collect();
+    for pair in gamma_powers.windows(2) {
+        if *pair[1].0 == pair[0].0 + 1 {
+            lower.push(*pair[0].1);
+            upper.push(*pair[1].1);
+        }
+    }
+
+    // For random r, e(sum r^i P_i, beta_h) must equal e(sum r^i P_{i+1}, h)
+    let scalars: Vec<_> = util::powers_of(E::Fr::rand(&mut OsRng))
+        .take(lower.len())
+        .map(|scalar| scalar.into_repr())
+        .collect();
+    let lhs = VariableBaseMSM::multi_scalar_mul(&lower, &scalars);
+    let rhs = VariableBaseMSM::multi_scalar_mul(&upper, &scalars);
+    let pairs = [
+        (lhs.into_affine().into(), u_params.prepared_beta_h.clone()),
+        ((-rhs).into_affine().into(), u_params.prepared_h.clone()),
+    ];
+    if !E::product_of_pairings(&pairs).is_one() {
+        return invalid("powers of g aren't successive powers of beta");
+    }
+
+    // Negative powers H_i of h, if any, must satisfy
+    // e(beta * g, H_{i+1}) = e(g, H_i)
+    let mut lower = Vec::new();
+    let mut upper = Vec::new();
+    let neg_powers: Vec<_> = u_params.neg_powers_of_h.iter().collect();
+    for pair in neg_powers.windows(2) {
+        if *pair[1].0 == pair[0].0 + 1 {
+            lower.push(*pair[0].1);
+            upper.push(*pair[1].1);
+        }
+    }
+    if !lower.is_empty() {
+        let scalars: Vec<_> = util::powers_of(E::Fr::rand(&mut OsRng))
+            .take(lower.len())
+            .map(|scalar| scalar.into_repr())
+            .collect();
+        let lhs = VariableBaseMSM::multi_scalar_mul(&upper, &scalars);
+        let rhs = VariableBaseMSM::multi_scalar_mul(&lower, &scalars);
+        let pairs = [
+            (u_params.powers_of_g[1].into(), lhs.into_affine().into()),
+            ((-g).into(), rhs.into_affine().into()),
+        ];
+        if !E::product_of_pairings(&pairs).is_one() {
+            return invalid("negative powers of h aren't successive powers");
+        }
+    }
+    Ok(())
+}
+
+/// Returns `true` if `point` belongs to the subgroup of prime order.
+fn in_prime_subgroup<G>(point: &G) -> bool
+where
+    G: AffineCurve,
+{
+    point
+        .into_projective()
+        .mul(<G::ScalarField as Field>::characteristic())
+        .is_zero()
+}
+
+/// Returns the entry of `cache` for `circuit_size`, building it with `build`
+/// if there is none yet.
+fn cached<T, F>(
+    cache: &RwLock<HashMap<usize, Arc<T>>>,
+    circuit_size: usize,
+    build: F,
+) -> Result<Arc<T>, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    if let Some(entry) =
+        cache.read().expect("poisoned SRS lock").get(&circuit_size)
+    {
+        return Ok(entry.clone());
+    }
+    let entry = Arc::new(build()?);
+    Ok(cache
+        .write()
+        .expect("poisoned SRS lock")
+        .entry(circuit_size)
+        .or_insert(entry)
+        .clone())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::{
+        verify_prepared_proof, Circuit, CompiledCircuit, PublicInputs,
+    };
+    use crate::config::ProverConfig;
+    use crate::constraint_system::StandardComposer;
+    use crate::{batch_test, batch_test_engine};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ff::Field;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::kzg10::KZG10;
+    use core::marker::PhantomData;
+    use num_traits::{One, Zero};
+    use rand_core::{OsRng, RngCore};
+
+    // Implements a circuit that checks x^2 = y where y is a PI
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    struct SquareCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        x: E::Fr,
+        __: PhantomData<P>,
+    }
+
+    impl<E, P> Circuit<E, P> for SquareCircuit<E, P>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<E, P>,
+        ) -> Result<(), Error> {
+            let x = composer.add_input(self.x);
+            let y = composer.mul(E::Fr::one(), x, x, E::Fr::zero(), None);
+            composer.constrain_to_constant(
+                y,
+                E::Fr::zero(),
+                Some(-self.x.square()),
+            );
+            Ok(())
+        }
+    }
+
+    fn test_srs_manager<E, P>()
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        let u_params = KZG10::<E, DensePolynomial<E::Fr>>::setup(
+            1 << 5,
+            false,
+            &mut OsRng,
+        )
+        .unwrap();
+        let srs = SrsManager::new(u_params);
+        assert_eq!(srs.max_supported_circuit_size(), 1 << 5);
+
+        // Keys are trimmed once per size and shared afterwards
+        let commit_key = srs.commit_key(8).unwrap();
+        assert!(Arc::ptr_eq(&commit_key, &srs.commit_key(8).unwrap()));
+        assert_eq!(commit_key.powers_of_g.len(), 9);
+        let verifier_key = srs.verifier_key(8).unwrap();
+        assert!(Arc::ptr_eq(&verifier_key, &srs.verifier_key(8).unwrap()));
+        assert!(matches!(
+            srs.commit_key(1 << 6),
+            Err(Error::UniversalParamsTooSmall { .. })
+        ));
+        assert_eq!(srs.cached_sizes(), vec![8]);
+
+        // Circuits prove and verify with the shared keys
+        let mut circuit = SquareCircuit::<E, P>::default();
+        let (prover_key, verifier_data) =
+            circuit.compile(srs.universal_params()).unwrap();
+        circuit.x = E::Fr::from(3u64);
+        let config = ProverConfig::new(b"Test");
+        let compiled =
+            CompiledCircuit::from_srs(&mut circuit, &srs, prover_key, &config)
+                .unwrap();
+        let proof = compiled.prove().unwrap();
+
+        // The Lagrange basis yields the same proof
+        let size = verifier_data.key().padded_circuit_size();
+        let mut with_basis = compiled;
+        with_basis.set_lagrange_basis(srs.lagrange_basis(size).unwrap());
+        assert_eq!(with_basis.prove().unwrap(), proof);
+        let prepared_key = srs.prepare(verifier_data.key()).unwrap();
+        let public_inputs = PublicInputs::from_pairs(vec![(
+            verifier_data.pi_pos()[0],
+            E::Fr::from(9u64),
+        )])
+        .unwrap();
+        assert!(verify_prepared_proof(
+            &prepared_key,
+            &proof,
+            &public_inputs,
+            &config.verifier_config(),
+        )
+        .is_ok());
+
+        assert!(srs.cached_sizes().contains(&size));
+        srs.clear();
+        assert!(srs.cached_sizes().is_empty());
+    }
+
+    fn test_check_srs<E>()
+    where
+        E: PairingEngine,
+    {
+        let u_params =
+            KZG10::<E, DensePolynomial<E::Fr>>::setup(1 << 4, true, &mut OsRng)
+                .unwrap();
+        assert!(check_srs(&u_params).is_ok());
+
+        let is_invalid = |u_params: &UniversalParams<E>| {
+            matches!(
+                check_srs(u_params),
+                Err(Error::InvalidUniversalParams { .. })
+            )
+        };
+
+        // Powers out of sequence
+        let mut bad = u_params.clone();
+        bad.powers_of_g[3] = bad.powers_of_g[2];
+        assert!(is_invalid(&bad));
+        let mut bad = u_params.clone();
+        bad.powers_of_gamma_g.insert(5, u_params.powers_of_g[5]);
+        assert!(is_invalid(&bad));
+        let mut bad = u_params.clone();
+        bad.neg_powers_of_h.insert(2, u_params.h);
+        assert!(is_invalid(&bad));
+
+        // Prepared points not matching their affine form
+        let mut bad = u_params.clone();
+        bad.beta_h = bad.h;
+        assert!(is_invalid(&bad));
+
+        // Point on the curve but outside of the prime order subgroup, which
+        // only exists when G1 has a cofactor
+        if E::G1Affine::COFACTOR != [1] {
+            let outside = loop {
+                let mut bytes = [0u8; 128];
+                OsRng.fill_bytes(&mut bytes);
+                if let Some(point) = E::G1Affine::from_random_bytes(&bytes) {
+                    if !in_prime_subgroup(&point) {
+                        break point;
+                    }
+                }
+            };
+            let mut bad = u_params;
+            bad.powers_of_g[1] = outside;
+            assert!(is_invalid(&bad));
+        }
+    }
+
+    // Test on Bls12-381
+    batch_test!(
+        [test_srs_manager],
+        []
+        => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+    batch_test_engine!(
+        [test_check_srs],
+        []
+        => Bls12_381
+    );
+
+    // Test on Bls12-377
+    batch_test!(
+        [test_srs_manager],
+        []
+        => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+    batch_test_engine!(
+        [test_check_srs],
+        []
+        => Bls12_377
+    );
+
+    // Test on Bn254
+    batch_test!(
+        [test_srs_manager],
+        []
+        => (
+            Bn254,
+            ark_ed_on_bn254::EdwardsParameters
+        )
+    );
+    batch_test_engine!(
+        [test_check_srs],
+        []
+        => Bn254
+    );
+}
@@ -2,10 +2,18 @@
 ///
 /// The set of tests is split in two. The first set between `[]` is for regular
 /// tests that should not panic. The second set is for tests that should panic.
+///
+/// Each test is a function generic over the [`PairingEngine`] `E` and the
+/// [`TEModelParameters`] `P` of its embedded curve, e.g. calling
+/// [`gadget_tester`].
+///
+/// [`PairingEngine`]: ark_ec::PairingEngine
+/// [`TEModelParameters`]: ark_ec::TEModelParameters
+/// [`gadget_tester`]: crate::constraint_system::helper::gadget_tester
 #[macro_export]
 macro_rules! batch_test {
     ( [$($test_set:ident),*], [$($test_panic_set:ident),*] => ($engine:ty, $params:ty) ) => {
-        paste::item! {
+        $crate::paste::item! {
             $(
                 #[test]
                 #[allow(non_snake_case)]
@@ -24,3 +32,32 @@ macro_rules! batch_test {
         }
     }
 }
+
+/// Defines a set of tests on a pairing engine, like [`batch_test!`] but for
+/// tests which don't depend on the embedded curve.
+///
+/// Each test is a function generic over the [`PairingEngine`] `E` only.
+///
+/// [`PairingEngine`]: ark_ec::PairingEngine
+#[macro_export]
+macro_rules! batch_test_engine {
+    ( [$($test_set:ident),*], [$($test_panic_set:ident),*] => $engine:ty ) => {
+        $crate::paste::item! {
+            $(
+                #[test]
+                #[allow(non_snake_case)]
+                fn [< $test_set _on_ $engine>]() {
+                    $test_set::<$engine>()
+                }
+            )*
+            $(
+                #[test]
+                #[should_panic]
+                #[allow(non_snake_case)]
+                fn [< $test_panic_set _on_ $engine>]() {
+                    $test_panic_set::<$engine>()
+                }
+            )*
+        }
+    }
+}
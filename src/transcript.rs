@@ -7,12 +7,64 @@
 //! This is an extension over the [Merlin Transcript](Transcript) which adds a
 //! few extra functionalities.
 
+use alloc::sync::Arc;
 use ark_ec::PairingEngine;
 use ark_ff::{Field, PrimeField};
 use ark_poly_commit::kzg10::Commitment;
 use ark_serialize::CanonicalSerialize;
 use core::marker::PhantomData;
 use merlin::Transcript;
+use std::sync::Mutex;
+
+/// Message appended to a transcript, or challenge drawn from it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TranscriptEvent {
+    /// Message appended with its label
+    Append {
+        /// Label of the message
+        label: Vec<u8>,
+        /// Bytes of the message
+        message: Vec<u8>,
+    },
+
+    /// Challenge drawn with its label
+    Challenge {
+        /// Label of the challenge
+        label: Vec<u8>,
+        /// Bytes of the challenge
+        bytes: Vec<u8>,
+    },
+}
+
+/// Record of the [`TranscriptEvent`]s of a transcript, in order.
+///
+/// The record is shared by the clones of the transcript, so it also captures
+/// the events of the proofs made or verified from a seeded transcript.
+#[derive(Clone, Debug, Default)]
+pub struct TranscriptLog {
+    /// Events recorded so far
+    events: Arc<Mutex<Vec<TranscriptEvent>>>,
+}
+
+impl TranscriptLog {
+    /// Returns the events recorded so far.
+    pub fn events(&self) -> Vec<TranscriptEvent> {
+        self.events.lock().expect("poisoned transcript log").clone()
+    }
+
+    /// Drops the events recorded so far.
+    pub fn clear(&self) {
+        self.events.lock().expect("poisoned transcript log").clear();
+    }
+
+    /// Records `event`.
+    fn push(&self, event: TranscriptEvent) {
+        self.events
+            .lock()
+            .expect("poisoned transcript log")
+            .push(event);
+    }
+}
 
 /// Wrapper around [`Transcript`]
 #[derive(derivative::Derivative)]
@@ -24,6 +76,12 @@ where
     /// Base Transcript
     pub transcript: Transcript,
 
+    /// Label the transcript was initialized with
+    label: Vec<u8>,
+
+    /// Record of the events of the transcript, if enabled
+    log: Option<TranscriptLog>,
+
     /// Type Parameter Marker
     __: PhantomData<E>,
 }
@@ -33,13 +91,70 @@ where
     E: PairingEngine,
 {
     /// Builds a new [`TranscriptWrapper`] with the given `label`.
+    ///
+    /// Since [`Transcript::new`] only accepts `'static` labels, the
+    /// transcript is created with a fixed protocol label and `label` is
+    /// appended as its first message.
     #[inline]
-    pub fn new(label: &'static [u8]) -> Self {
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Transcript::new(b"ark-plonk");
+        transcript.append_message(b"label", label);
         Self {
-            transcript: Transcript::new(label),
+            transcript,
+            label: label.to_vec(),
+            log: None,
             __: PhantomData,
         }
     }
+
+    /// Returns the label the transcript was initialized with.
+    #[inline]
+    pub fn label(&self) -> &[u8] {
+        &self.label
+    }
+
+    /// Resets the transcript to its initial state, keeping its label and
+    /// its record.
+    pub fn reset(&mut self) {
+        let log = self.log.take();
+        *self = Self::new(&self.label);
+        self.log = log;
+    }
+
+    /// Starts recording the events of the transcript and of its future
+    /// clones, and returns the record. The messages appended before aren't
+    /// recorded.
+    pub fn record(&mut self) -> TranscriptLog {
+        self.log.get_or_insert_with(TranscriptLog::default).clone()
+    }
+
+    /// Returns the record of the events of the transcript, if enabled.
+    #[inline]
+    pub fn log(&self) -> Option<&TranscriptLog> {
+        self.log.as_ref()
+    }
+
+    /// Appends `message` with the given `label`.
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.transcript.append_message(label, message);
+        if let Some(log) = &self.log {
+            log.push(TranscriptEvent::Append {
+                label: label.to_vec(),
+                message: message.to_vec(),
+            });
+        }
+    }
+
+    /// Fills `dest` with challenge bytes drawn with the given `label`.
+    pub fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.transcript.challenge_bytes(label, dest);
+        if let Some(log) = &self.log {
+            log.push(TranscriptEvent::Challenge {
+                label: label.to_vec(),
+                bytes: dest.to_vec(),
+            });
+        }
+    }
 }
 
 /// Transcript adds an abstraction over the Merlin transcript
@@ -59,6 +174,9 @@ where
 
     /// Append domain separator for the circuit size.
     fn circuit_domain_sep(&mut self, n: u64);
+
+    /// Append the circuit identifier and the digest of its verifier key.
+    fn circuit_binding(&mut self, circuit_id: &[u8; 32], vk_digest: &[u8; 32]);
 }
 
 impl<E> TranscriptProtocol<E> for TranscriptWrapper<E>
@@ -72,13 +190,13 @@ where
     ) {
         let mut bytes = Vec::new();
         comm.0.serialize(&mut bytes).unwrap();
-        self.transcript.append_message(label, &bytes);
+        self.append_message(label, &bytes);
     }
 
     fn append_scalar(&mut self, label: &'static [u8], s: &E::Fr) {
         let mut bytes = Vec::new();
         s.serialize(&mut bytes).unwrap();
-        self.transcript.append_message(label, &bytes)
+        self.append_message(label, &bytes)
     }
 
     fn challenge_scalar(&mut self, label: &'static [u8]) -> E::Fr {
@@ -86,12 +204,187 @@ where
         // element
         let size = E::Fr::size_in_bits() / 8;
         let mut buf = vec![0u8; size];
-        self.transcript.challenge_bytes(label, &mut buf);
+        self.challenge_bytes(label, &mut buf);
         E::Fr::from_random_bytes(&buf).unwrap()
     }
 
     fn circuit_domain_sep(&mut self, n: u64) {
-        self.transcript.append_message(b"dom-sep", b"circuit_size");
-        self.transcript.append_u64(b"n", n);
+        self.append_message(b"dom-sep", b"circuit_size");
+        // Same encoding as `Transcript::append_u64`
+        self.append_message(b"n", &n.to_le_bytes());
+    }
+
+    fn circuit_binding(&mut self, circuit_id: &[u8; 32], vk_digest: &[u8; 32]) {
+        self.append_message(b"dom-sep", b"circuit_binding");
+        self.append_message(b"circuit_id", circuit_id);
+        self.append_message(b"vk_digest", vk_digest);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::constraint_system::{StandardComposer, SELECTOR_NAMES};
+    use crate::proof_system::{Prover, Verifier};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ed_on_bls12_381::EdwardsParameters;
+    use ark_poly_commit::kzg10::{Powers, VerifierKey};
+    use num_traits::{One, Zero};
+    use std::borrow::Cow;
+
+    /// Path of the canonical test vectors, regenerated by running the tests
+    /// with `PLONK_UPDATE_VECTORS` set.
+    const VECTORS_PATH: &str = "test-vectors/transcript_bls12_381.txt";
+
+    /// Fixed circuit of the test vectors, proving knowledge of `a` and `b`
+    /// of four bits such that `a * b + (a ^ b)` is the public input.
+    fn vectors_gadget(
+        composer: &mut StandardComposer<Bls12_381, EdwardsParameters>,
+    ) {
+        let a = composer.add_input(Fr::from(11u64));
+        let b = composer.add_input(Fr::from(6u64));
+        composer.range_gate(a, 4);
+        composer.range_gate(b, 4);
+        let product = composer.mul(Fr::one(), a, b, Fr::zero(), None);
+        let xor = composer.xor_gate(a, b, 4);
+        let sum = composer.add(
+            (Fr::one(), product),
+            (Fr::one(), xor),
+            Fr::zero(),
+            None,
+        );
+        composer.set_public(sum);
+    }
+
+    /// Commit and verifier keys of `max_degree` derived from a fixed secret,
+    /// so that the test vectors don't depend on a random number generator.
+    fn vectors_keys(
+        max_degree: usize,
+    ) -> (Powers<'static, Bls12_381>, VerifierKey<Bls12_381>) {
+        let tau = Fr::from(0x5eed_u64);
+        let g =
+            <Bls12_381 as PairingEngine>::G1Affine::prime_subgroup_generator();
+        let gamma_g = g.mul(Fr::from(7u64)).into_affine();
+        let h =
+            <Bls12_381 as PairingEngine>::G2Affine::prime_subgroup_generator();
+        let beta_h = h.mul(tau).into_affine();
+        let powers = |base: <Bls12_381 as PairingEngine>::G1Affine| {
+            crate::util::powers_of(tau)
+                .take(max_degree + 1)
+                .map(|power| base.mul(power).into_affine())
+                .collect::<Vec<_>>()
+        };
+        let powers_of_g = powers(g);
+        let powers_of_gamma_g = powers(gamma_g);
+        let verifier_key = VerifierKey {
+            g,
+            gamma_g,
+            h,
+            beta_h,
+            prepared_h: h.into(),
+            prepared_beta_h: beta_h.into(),
+        };
+        let powers = Powers {
+            powers_of_g: Cow::Owned(powers_of_g),
+            powers_of_gamma_g: Cow::Owned(powers_of_gamma_g),
+        };
+        (powers, verifier_key)
+    }
+
+    /// Hex encoding of `bytes`.
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Hex encoding of the serialization of `value`.
+    fn hex_serialized<T: CanonicalSerialize>(value: &T) -> String {
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes).unwrap();
+        hex(&bytes)
+    }
+
+    /// Writes `events` as lines of the test vectors.
+    fn write_events(vectors: &mut String, events: &[TranscriptEvent]) {
+        for event in events {
+            let (kind, label, bytes) = match event {
+                TranscriptEvent::Append { label, message } => {
+                    ("append", label, message)
+                }
+                TranscriptEvent::Challenge { label, bytes } => {
+                    ("challenge", label, bytes)
+                }
+            };
+            vectors.push_str(&format!(
+                "{} {} {}\n",
+                kind,
+                String::from_utf8_lossy(label).replace(' ', "_"),
+                hex(bytes)
+            ));
+        }
+    }
+
+    /// Tests that the recorded transcripts of the prover and the verifier
+    /// agree, and that proving the fixed circuit reproduces the canonical
+    /// selectors, commitments, transcript and proof.
+    #[test]
+    fn test_transcript_vectors() {
+        let mut prover = Prover::new(b"test vectors");
+        let prover_log = prover.record_transcript();
+        prover.key_transcript(b"key", b"additional seed information");
+        vectors_gadget(prover.mut_cs());
+        let public_inputs = prover.mut_cs().construct_dense_pi_vec();
+        let size = prover.circuit_size().next_power_of_two();
+        let (powers, pc_verifier_key) = vectors_keys(size + 6);
+
+        let mut vectors = String::new();
+        let description = prover.mut_cs().export();
+        for (i, gate) in description.gates.iter().enumerate() {
+            for (name, selector) in SELECTOR_NAMES.iter().zip(gate.selectors) {
+                vectors.push_str(&format!(
+                    "selector {}.{} {}\n",
+                    i,
+                    name,
+                    hex_serialized(&selector)
+                ));
+            }
+        }
+
+        prover.preprocess(&powers).unwrap();
+        let proof = prover.prove(&powers).unwrap();
+
+        let mut verifier = Verifier::new(b"test vectors");
+        let verifier_log = verifier.record_transcript();
+        verifier.key_transcript(b"key", b"additional seed information");
+        vectors_gadget(verifier.mut_cs());
+        verifier.preprocess(&powers).unwrap();
+        verifier
+            .verify(&proof, &pc_verifier_key, &public_inputs)
+            .unwrap();
+
+        // The verifier derives the same challenges from the same messages
+        let prover_events = prover_log.events();
+        let verifier_events = verifier_log.events();
+        assert!(verifier_events.starts_with(&prover_events));
+
+        vectors.push_str(&format!(
+            "verifier_key - {}\n",
+            hex_serialized(verifier.verifier_key.as_ref().unwrap())
+        ));
+        write_events(&mut vectors, &prover_events);
+        vectors.push_str(&format!("proof - {}\n", hex_serialized(&proof)));
+
+        let path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(VECTORS_PATH);
+        if std::env::var_os("PLONK_UPDATE_VECTORS").is_some() {
+            std::fs::write(&path, &vectors).unwrap();
+        }
+        let expected = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            vectors == expected,
+            "the transcript diverges from {}, set PLONK_UPDATE_VECTORS to \
+             regenerate it",
+            VECTORS_PATH
+        );
     }
 }
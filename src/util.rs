@@ -4,11 +4,14 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
+use crate::error::Error;
 use ark_ec::{AffineCurve, ModelParameters, PairingEngine, TEModelParameters};
-use ark_ff::{BigInteger, FftField, Field, FpParameters, PrimeField};
+use ark_ff::{
+    BigInteger, FftField, FftParameters, Field, FpParameters, PrimeField,
+};
 use ark_poly::{
-    univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
-    Polynomial, UVPolynomial,
+    univariate::DensePolynomial, EvaluationDomain, Evaluations,
+    GeneralEvaluationDomain, Polynomial, UVPolynomial,
 };
 use ark_poly_commit::kzg10::Commitment;
 
@@ -51,6 +54,27 @@ where
     DensePolynomial::from_coefficients_vec(quotient)
 }
 
+/// Builds a polynomial from `coeffs`, dropping its leading zero
+/// coefficients, which the arithmetic of [`DensePolynomial`] doesn't expect,
+/// unless it is identically zero.
+///
+/// The selectors of the gates a circuit doesn't use thus keep their zero
+/// coefficients, as the size of the key expects, until
+/// [`ProverKey::compress_selectors`] drops them.
+///
+/// [`ProverKey::compress_selectors`]:
+/// crate::proof_system::ProverKey::compress_selectors
+pub(crate) fn normalized_polynomial<F>(coeffs: Vec<F>) -> DensePolynomial<F>
+where
+    F: Field,
+{
+    let poly = DensePolynomial { coeffs };
+    match num_traits::Zero::is_zero(&poly) {
+        true => poly,
+        false => DensePolynomial::from_coefficients_vec(poly.coeffs),
+    }
+}
+
 /// Evaluation Domain Extension Trait
 pub trait EvaluationDomainExt<F>: EvaluationDomain<F>
 where
@@ -119,11 +143,83 @@ where
     }
 }
 
+/// Returns the greatest common divisor of `a` and `b`.
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// Evaluates the vanishing polynomial `X^poly_degree - 1` over the coset of
+/// `domain` shifted by the multiplicative generator of the field.
+///
+/// At the `i`th point of the coset, `(g * w^i)^n = g^n * (w^n)^i`, where `w^n`
+/// has order `size / gcd(size, n)`. The evaluations repeat with that period,
+/// which is `4` for the `4n` domain of the quotient, so only one period is
+/// computed without any exponentiation per point.
+pub fn compute_vanishing_poly_over_coset<F, D>(
+    domain: D,
+    poly_degree: u64,
+) -> Evaluations<F, D>
+where
+    F: PrimeField,
+    D: EvaluationDomain<F>,
+{
+    let size = domain.size() as u64;
+    assert!(
+        size > poly_degree,
+        "domain_size = {}, poly_degree = {}",
+        size,
+        poly_degree
+    );
+    let period = (size / gcd(size, poly_degree)) as usize;
+    let coset_gen = F::multiplicative_generator().pow([poly_degree]);
+    let group_gen = domain.element(1).pow([poly_degree]);
+    let cycle = powers_of(group_gen)
+        .take(period)
+        .map(|power| coset_gen * power - F::one())
+        .collect::<Vec<_>>();
+    let v_h = cycle.iter().cycle().take(domain.size()).copied().collect();
+    Evaluations::from_vec_and_domain(v_h, domain)
+}
+
+/// Returns `true` if `point` is on the curve and in its prime order subgroup.
+///
+/// Points deserialized without checks, or from their uncompressed encoding,
+/// aren't checked to be on the curve. The point is thus checked by decoding
+/// its compressed encoding, which recomputes its ordinate from the curve
+/// equation and checks the subgroup.
+pub(crate) fn is_valid_point<G>(point: &G) -> bool
+where
+    G: AffineCurve,
+{
+    let mut bytes = Vec::with_capacity(point.serialized_size());
+    point.serialize(&mut bytes).is_ok()
+        && G::deserialize(&bytes[..]).ok() == Some(*point)
+}
+
+/// Returns the [`GeneralEvaluationDomain`] of at least `size` elements, or an
+/// [`Error::InvalidEvalDomainSize`] if the field doesn't have a subgroup that
+/// large.
+pub(crate) fn evaluation_domain<F>(
+    size: usize,
+) -> Result<GeneralEvaluationDomain<F>, Error>
+where
+    F: FftField,
+{
+    GeneralEvaluationDomain::new(size).ok_or(Error::InvalidEvalDomainSize {
+        log_size_of_group: size.next_power_of_two().trailing_zeros(),
+        adacity: <F::FftParams as FftParameters>::TWO_ADICITY,
+    })
+}
+
 /// Get a pairing friendly curve scalar `E::Fr` from a scalar of the embedded
 /// curve. Panics if the embedded scalar is greater than the modulus of the
 /// pairing firendly curve scalar field
-#[allow(dead_code)]
-pub(crate) fn from_embedded_curve_scalar<E, P>(
+pub fn from_embedded_curve_scalar<E, P>(
     embedded_scalar: <P as ModelParameters>::ScalarField,
 ) -> E::Fr
 where
@@ -206,3 +302,50 @@ where
     );
     (combined_commitment, combined_eval)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_test_engine;
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use num_traits::One;
+
+    fn test_vanishing_poly_over_coset<E>()
+    where
+        E: PairingEngine,
+    {
+        let domain = GeneralEvaluationDomain::<E::Fr>::new(32).unwrap();
+        // Degrees dividing the size of the domain or not
+        for n in [0, 5, 8, 16] {
+            let v_h = compute_vanishing_poly_over_coset(domain, n);
+            assert_eq!(v_h.evals.len(), domain.size());
+            for (i, eval) in v_h.evals.iter().enumerate() {
+                let x = E::Fr::multiplicative_generator() * domain.element(i);
+                assert_eq!(*eval, x.pow([n]) - E::Fr::one());
+            }
+        }
+    }
+
+    // Test on Bls12-381
+    batch_test_engine!(
+        [test_vanishing_poly_over_coset],
+        []
+        => Bls12_381
+    );
+
+    // Test on Bls12-377
+    batch_test_engine!(
+        [test_vanishing_poly_over_coset],
+        []
+        => Bls12_377
+    );
+
+    // Test on Bn254
+    batch_test_engine!(
+        [test_vanishing_poly_over_coset],
+        []
+        => Bn254
+    );
+}